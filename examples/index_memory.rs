@@ -0,0 +1,88 @@
+//! Measure line index memory on a generated log file.
+//!
+//! Usage: cargo run --release --example index_memory [LINES]
+//!
+//! Writes LINES (default 10,000,000) timestamped lines to a temporary file,
+//! indexes it and compares the index size with the previous `LineInfo`
+//! layout, which stored an `Option<DateTime<Utc>>`.
+
+use std::io::{BufWriter, Write};
+use std::mem::size_of;
+use std::time::Instant;
+
+use chrono::{DateTime, Utc};
+use qlog::model::{LineInfo, LogStorage};
+
+/// `LineInfo` before timestamps were stored as epoch nanoseconds.
+#[allow(dead_code)]
+struct PreviousLineInfo {
+    offset: u64,
+    length: u32,
+    file_index: u32,
+    timestamp: Option<DateTime<Utc>>,
+}
+
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let lines: usize = match std::env::args().nth(1) {
+        Some(arg) => arg.parse()?,
+        None => 10_000_000,
+    };
+
+    let file = tempfile::NamedTempFile::new()?;
+    let started = Instant::now();
+    let mut out = BufWriter::new(file.as_file());
+    for i in 0..lines {
+        writeln!(
+            out,
+            "2026-02-13T{:02}:{:02}:{:02}.{:03}Z INFO request {} handled in {}ms",
+            i / 3_600_000 % 24,
+            i / 60_000 % 60,
+            i / 1000 % 60,
+            i % 1000,
+            i,
+            i % 250
+        )?;
+    }
+    out.flush()?;
+    drop(out);
+    println!("Wrote {} lines in {:.1?}", lines, started.elapsed());
+
+    let started = Instant::now();
+    let storage = LogStorage::from_file(file.path())?;
+    println!("Indexed in {:.1?}", started.elapsed());
+
+    let timestamped = (0..storage.len())
+        .filter(|&idx| {
+            storage
+                .get_line_info(idx)
+                .and_then(|info| info.timestamp_nanos())
+                .is_some()
+        })
+        .count();
+    let index = storage.index_bytes();
+    let previous = storage.len() * size_of::<PreviousLineInfo>();
+    let mib = |bytes: usize| bytes as f64 / (1024.0 * 1024.0);
+
+    println!(
+        "Lines:            {} ({} with timestamps)",
+        storage.len(),
+        timestamped
+    );
+    println!(
+        "File size:        {:.1} MiB",
+        mib(storage.mapped_bytes() as usize)
+    );
+    println!(
+        "LineInfo:         {} bytes (previously {})",
+        size_of::<LineInfo>(),
+        size_of::<PreviousLineInfo>()
+    );
+    println!("Index:            {:.1} MiB", mib(index));
+    println!("Previous layout:  {:.1} MiB", mib(previous));
+    println!(
+        "Saved:            {:.1} MiB ({:.0}%)",
+        mib(previous.saturating_sub(index)),
+        100.0 * previous.saturating_sub(index) as f64 / previous.max(1) as f64
+    );
+    Ok(())
+}
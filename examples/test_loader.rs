@@ -23,12 +23,12 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
                 println!("\n✓ Loaded: {}", path.display());
                 println!("  Lines: {}", storage.len());
 
-                if storage.len() > 0 {
+                if !storage.is_empty() {
                     let first = storage.get_line(0).unwrap();
                     println!("  First line: {}", first.as_str_lossy().trim());
 
                     if let Some(info) = storage.get_line_info(0) {
-                        if let Some(ts) = info.timestamp {
+                        if let Some(ts) = info.timestamp() {
                             println!("  First timestamp: {}", ts);
                         }
                     }
@@ -1,17 +1,174 @@
-use crate::clipboard::Clipboard;
+use crate::clipboard::{Clipboard, ClipboardBackend};
+use crate::columns::Columns;
 use crate::command::{self, CommandEffect};
-use crate::config::AppConfig;
+use crate::config::{AppConfig, FollowConfig, LargeFileConfig, SelectionConfig, StartupPosition};
+use crate::crash;
+use crate::detail::{self, DetailView};
+use crate::error::QlogError;
+use crate::exceptions::{self, ExceptionGroup};
+use crate::export;
+use crate::follow::{FollowedLine, Follower, LatestFile};
+use crate::gaps::{self, Gaps};
+use crate::help;
+use crate::hex::HexView;
+use crate::history::CommandHistory;
+use crate::jobs::{Job, JobContext};
 use crate::key_bindings::{Mode, Msg};
+use crate::loader::{LoadEvent, LoadProgress};
+use crate::model::text::{self, byte_to_char_offset};
 use crate::model::{
-    BMHMatcher, Direction, FilterKind, FilterList, LogStorage, Selection, VisualLineCache,
+    detect_level, filter_proposal, parse_filter_file, parse_time_bound, set_level_map, BMHMatcher,
+    Bookmark, Bookmarks, CaseMode, Context, Direction, FilterIndex, FilterKind, FilterList,
+    FilterRule, IndexMode, LevelSet, LineAnchor, LogLevel, LogStorage, Pins, Selection,
+    SelectionBytes, Throttle, VisualLineCache, ON_DEMAND_BLOCK_SIZE,
 };
+use crate::notify::{Notifications, Severity};
+use crate::perf::PerfStats;
+use crate::pods::{self, Pods};
+use crate::prompt::{Choice, Prompt};
+use crate::query::{self, QueryResult};
+use crate::quickfix::{self, QuickfixEntry};
+use crate::recent::RecentFiles;
+use crate::replay::Replay;
+use crate::search::{self, SearchJob};
+use crate::session::{self, SavedFilter, SavedLine, Session, Sessions};
+use crate::sidecar::Sidecar;
+use crate::source::LineSource;
+use crate::stats::{self, DrillDown, StatsRow};
+use crate::summary::{format_duration, LoadSummary};
+use crate::theme::{Gutter, Theme};
+use crate::tutor::Tutor;
+use crate::watch::{Watch, Watches};
 use lru::LruCache;
-use ratatui::style::Color;
-use std::cell::Cell;
-use std::fs::File;
-use std::io::Write;
+use ratatui::layout::Rect;
+use ratatui::style::{Color, Style};
+use std::borrow::Cow;
+use std::cell::{Cell, RefCell};
+use std::collections::BTreeSet;
+use std::fs::{File, OpenOptions};
+use std::io::{self, BufWriter, Write};
 use std::num::NonZeroUsize;
+use std::ops::Range;
+use std::path::{Path, PathBuf};
 use std::sync::mpsc::{channel, Receiver, Sender};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+/// Searches over more filtered lines than this count their matches in the
+/// background; lines on screen are highlighted right away either way.
+const BACKGROUND_SEARCH_LINES: usize = 10_000;
+
+/// `:stats`, `:exceptions` and `:sql` over more filtered lines than this
+/// run in the background; their overlay opens when the job is done.
+const BACKGROUND_OVERLAY_LINES: usize = 10_000;
+
+/// `:write` of more filtered lines than this runs in the background.
+const BACKGROUND_EXPORT_LINES: usize = 100_000;
+
+/// Ruler column used by `:ruler` when none is configured.
+const DEFAULT_RULER_COLUMN: usize = 120;
+
+/// Lines kept per message template when `:throttle` is toggled on.
+const DEFAULT_THROTTLE_LIMIT: usize = 3;
+
+/// Lines shown before and after each match when `:context` is toggled on.
+const DEFAULT_CONTEXT_LINES: usize = 3;
+
+/// How long the load summary popup stays up without a key press.
+const LOAD_SUMMARY_TIMEOUT: Duration = Duration::from_secs(8);
+
+/// Lines searched upwards for the timestamp of a continuation line's entry.
+const ENTRY_TIMESTAMP_LOOKBACK: usize = 100;
+
+/// Continuation lines shown with a plain entry in the detail pane.
+const DETAIL_ENTRY_LINES: usize = 2000;
+
+/// Lines searched backwards from the end for the startup position.
+const STARTUP_SCAN_LINES: usize = 200_000;
+
+/// Lines indexed per step when scrolling above the start of a tail-only file.
+const TAIL_EXTEND_LINES: usize = 10_000;
+
+/// Pause in typing after which the search typed so far is previewed.
+const SEARCH_PREVIEW_DELAY: Duration = Duration::from_millis(150);
+
+/// Lines moved per step of the mouse wheel.
+const WHEEL_LINES: usize = 3;
+
+/// Bookmarks and cursor of the previous file set, re-resolved once the
+/// new one is loaded.
+#[derive(Debug, Default)]
+struct ReloadAnchors {
+    bookmarks: Vec<(LineAnchor, Bookmark)>,
+    pins: Vec<LineAnchor>,
+    cursor: Option<LineAnchor>,
+}
+
+/// State of one file set, parked while another tab is shown.
+///
+/// The shown tab's state lives in the App fields themselves; switching tabs
+/// swaps it with the parked state of the tab switched to.
+#[derive(Debug, Default)]
+struct TabState {
+    storage: Option<Arc<LogStorage>>,
+    filtered_indices: Vec<usize>,
+    filters: FilterList,
+    filter_index: FilterIndex,
+    away_from: Option<usize>,
+    last_read: Option<usize>,
+    scroll_offset: usize,
+    horizontal_scroll: usize,
+    selected_line: usize,
+    search_query: Option<String>,
+    search_stack: Vec<String>,
+    search_state: Option<SearchState>,
+    search_job: Option<SearchJob>,
+    search_range: Option<SearchRange>,
+    selection: Selection,
+    bookmarks: Bookmarks,
+    hidden: BTreeSet<usize>,
+    pins: Pins,
+    quickfix: Option<Vec<QuickfixEntry>>,
+    watches: Watches,
+    replay: Option<Replay>,
+    pods: Pods,
+    drill_down: DrillDown,
+    follow: Option<Follower>,
+    follow_latest: Option<LatestFile>,
+    reload_anchors: Option<ReloadAnchors>,
+}
+
+/// What the active selection covers, shown in the status bar.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SelectionSummary {
+    pub lines: usize,
+    /// Total length of the selected lines, without line endings
+    pub bytes: u64,
+    /// Time between the first and last line, if both have a timestamp
+    pub span: Option<chrono::Duration>,
+}
+
+/// The view as it was when search input began. The search typed so far is
+/// previewed while typing; Esc goes back to this.
+#[derive(Debug)]
+struct SearchOrigin {
+    selected_line: usize,
+    scroll_offset: usize,
+    horizontal_scroll: usize,
+    /// The search the preview replaced, once a preview has run
+    parked: Option<ParkedSearch>,
+    /// Input the shown preview was run for
+    previewed: Option<String>,
+}
+
+/// A search set aside while another is previewed.
+#[derive(Debug)]
+struct ParkedSearch {
+    query: Option<String>,
+    stack: Vec<String>,
+    state: Option<SearchState>,
+    job: Option<SearchJob>,
+}
 
 /// Position of a match for O(1) lookup.
 #[derive(Debug, Clone, Copy)]
@@ -27,7 +184,7 @@ pub struct MatchPosition {
 /// Search state with LRU cache for line matches.
 #[derive(Debug)]
 pub struct SearchState {
-    /// The search query string (lowercase for case-insensitive matching)
+    /// The search query string (lowercase when the search ignores case)
     pub query: String,
     /// BMH matcher for efficient searching
     pub matcher: BMHMatcher,
@@ -40,6 +197,122 @@ pub struct SearchState {
     /// Cache of matches per line index (filtered_indices index)
     /// Key: filtered line index, Value: Vec of (byte_start, byte_end)
     pub match_cache: LruCache<usize, Vec<(usize, usize)>>,
+    /// Filtered lines the search covers (all of them without `:range`)
+    pub scope: Range<usize>,
+    /// Matchers of the searches this one refines; lines must contain them all
+    pub within: Vec<BMHMatcher>,
+}
+
+impl SearchState {
+    /// Drop the matches on filtered rows `rows`, which leave the view, and
+    /// move the later rows back. `indices` are the filtered lines before
+    /// the rows are removed.
+    fn remove_rows(&mut self, source: &dyn LineSource, indices: &[usize], rows: Range<usize>) {
+        let scanned = rows.start.max(self.scope.start)..rows.end.min(self.scope.end);
+        if !scanned.is_empty() {
+            let scan = |rows: Range<usize>| {
+                search::scan_matches(
+                    source,
+                    &indices[rows.clone()],
+                    rows.start,
+                    &self.matcher,
+                    &self.within,
+                )
+                .0
+            };
+            let removed = scan(scanned.clone());
+            match self.current_position {
+                Some(current) if current.filtered_idx >= scanned.end => {
+                    self.current_idx -= removed;
+                }
+                Some(current) if current.filtered_idx >= scanned.start => {
+                    // The first match after the removed rows takes the place of the current one
+                    let on_row = source
+                        .get_line(indices[current.filtered_idx])
+                        .map_or(0, |line| {
+                            search::line_matches(line.as_bytes(), &self.matcher, &self.within)
+                                .iter()
+                                .filter(|&&(start, _)| start < current.byte_offset)
+                                .count()
+                        });
+                    self.current_idx -= scan(scanned.start..current.filtered_idx) + on_row;
+                    self.current_position = None;
+                }
+                _ => {}
+            }
+            self.total_matches -= removed;
+            self.current_idx = self.current_idx.min(self.total_matches.saturating_sub(1));
+        }
+
+        let shift = |row: usize| {
+            if row >= rows.end {
+                row - rows.len()
+            } else {
+                row.min(rows.start)
+            }
+        };
+        if let Some(current) = &mut self.current_position {
+            current.filtered_idx = shift(current.filtered_idx);
+        }
+        self.scope = shift(self.scope.start)..shift(self.scope.end);
+        self.match_cache.clear();
+    }
+}
+
+/// Lines searches are limited to, set with `:range`.
+///
+/// The ends are storage indices, so the range survives re-filtering.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SearchRange {
+    /// First line in the range
+    pub first: usize,
+    /// Last line in the range, or None if it runs to the end (and takes in
+    /// followed lines)
+    pub last: Option<usize>,
+    /// Shown in the status bar, e.g. `10:00..11:30`
+    pub label: String,
+}
+
+/// Result of a `:sql` query, shown in the SQL overlay.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SqlView {
+    /// The query, shown as the overlay title
+    pub query: String,
+    pub result: QueryResult,
+    /// Selected row
+    pub selected: usize,
+}
+
+/// Groups of the `:exceptions` overlay.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ExceptionsView {
+    pub groups: Vec<ExceptionGroup>,
+    /// Selected group
+    pub selected: usize,
+}
+
+/// Chart of the `:gaps` overlay.
+#[derive(Debug, Clone, PartialEq)]
+pub struct GapsView {
+    pub gaps: Gaps,
+    /// What the gaps are between, e.g. `matches of 'retry'`
+    pub subject: String,
+}
+
+/// Rows of the `:stats` overlay.
+#[derive(Debug, Clone, PartialEq)]
+pub struct StatsView {
+    pub rows: Vec<StatsRow>,
+    /// Selected row
+    pub selected: usize,
+}
+
+/// Contents of an overlay, as computed by an overlay job.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Overlay {
+    Stats(Vec<StatsRow>),
+    Exceptions(Vec<ExceptionGroup>),
+    Sql(Result<SqlView, String>),
 }
 
 #[derive(Debug, Clone)]
@@ -50,20 +323,116 @@ pub enum LoadingStatus {
     Error(String),
 }
 
+/// A file set to open, with the index mode chosen for each file.
+#[derive(Debug, Clone, PartialEq)]
+pub struct OpenRequest {
+    pub files: Vec<(PathBuf, IndexMode)>,
+}
+
+impl OpenRequest {
+    /// Paths of all files in the request.
+    pub fn paths(&self) -> Vec<PathBuf> {
+        self.files.iter().map(|(path, _)| path.clone()).collect()
+    }
+}
+
+/// A file set waiting for the user to choose how its oversized files are indexed.
+#[derive(Debug, Clone)]
+pub struct LargeFilePrompt {
+    /// All files in the requested set
+    pub paths: Vec<PathBuf>,
+    /// Files above the size threshold, with their sizes in bytes
+    pub oversized: Vec<(PathBuf, u64)>,
+}
+
+/// What answering the open prompt does.
+#[derive(Debug, Clone)]
+pub enum PromptAction {
+    /// Open a file set once its oversized files have an index mode
+    OpenLargeFiles(LargeFilePrompt),
+    /// Write the lines in view over an existing file
+    Overwrite {
+        filename: String,
+        verbatim: bool,
+        context: Option<usize>,
+    },
+}
+
+/// The prompt asking how to index `oversized` files.
+fn large_file_prompt(config: &LargeFileConfig, oversized: &[(PathBuf, u64)]) -> Prompt {
+    Prompt::select(
+        format!(
+            " Large file{} (over {}) ",
+            if oversized.len() == 1 { "" } else { "s" },
+            export::format_size(config.threshold_bytes)
+        ),
+        oversized
+            .iter()
+            .map(|(path, size)| format!("{:>9}  {}", export::format_size(*size), path.display()))
+            .collect(),
+        vec![
+            Choice::new('f', "Full index (reads the whole file up front)"),
+            Choice::new(
+                'o',
+                "Index on demand (line counts per 1 MB block, exact offsets while scrolling)",
+            ),
+            Choice::new(
+                't',
+                format!(
+                    "Tail only (last {})",
+                    export::format_size(config.tail_bytes)
+                ),
+            ),
+        ],
+    )
+}
+
+/// Index mode for a file that does not need the large-file prompt.
+fn default_index_mode(config: &LargeFileConfig, size: u64) -> IndexMode {
+    if size > config.on_demand_bytes {
+        IndexMode::OnDemand {
+            block_size: ON_DEMAND_BLOCK_SIZE,
+        }
+    } else {
+        IndexMode::Full
+    }
+}
+
 #[derive(Debug)]
 pub struct App {
-    /// Memory-mapped log storage (replaces Vec<LogEntry>)
-    pub storage: Option<LogStorage>,
+    /// Memory-mapped log storage (replaces Vec<LogEntry>), shared with background jobs
+    pub storage: Option<Arc<LogStorage>>,
     /// Indices of lines that match current filters
     pub filtered_indices: Vec<usize>,
     /// Active filters (command-based)
     pub filters: FilterList,
+    /// Matching lines per filter rule
+    pub filter_index: FilterIndex,
     /// Current UI mode
     pub mode: Mode,
     /// Flag to exit the application
     pub should_quit: bool,
-    /// Status message to display
+    /// Line count when the terminal lost focus; None while it has focus
+    blurred_at_lines: Option<usize>,
+    /// Storage index of the first line that arrived after the view left the
+    /// live end (focus lost, follow stopped, cursor off the last line)
+    away_from: Option<usize>,
+    /// Storage index of the first line that arrived while away; the "last
+    /// read" marker is drawn above it
+    pub last_read: Option<usize>,
+    /// Status message to display; a new value is queued as a notification
+    /// by `update_notifications`
     pub status_message: String,
+    /// Severity `status_message` is queued with, set by `notify_info`,
+    /// `notify_warn` and `notify_error`
+    status_kind: Severity,
+    /// Status messages still up and the log `:messages` shows
+    pub notifications: Notifications,
+    pub message_list_selected: usize,
+    /// Sections of the help overlay while it is open
+    pub help: Vec<help::Section>,
+    /// First row of the help overlay shown
+    pub help_scroll: usize,
     /// Vertical scroll offset (in filtered lines)
     pub scroll_offset: usize,
     /// Horizontal scroll offset (in characters)
@@ -72,6 +441,8 @@ pub struct App {
     pub selected_line: usize,
     /// Loading status
     pub loading_status: LoadingStatus,
+    /// Lines, decompression and indexing progress of the running load
+    pub load_progress: LoadProgress,
     /// Receiver for async log loading
     pub log_receiver: Option<Receiver<LogStorage>>,
     /// Selected filter index in :list-filters view
@@ -84,52 +455,278 @@ pub struct App {
     pub viewport_height: Cell<usize>,
     /// Viewport width (updated by UI)
     pub viewport_width: Cell<usize>,
+    /// Screen area of the log lines (updated by UI)
+    pub view_area: Cell<Rect>,
+    /// Filtered line shown on each row of `view_area`; None for the
+    /// last-read marker (updated by UI)
+    pub view_rows: RefCell<Vec<Option<usize>>>,
+    /// Screen area of the filter list entries (updated by UI)
+    pub filter_list_area: Cell<Rect>,
+    /// Line the left button was pressed on, where a drag selection starts
+    drag_anchor: Option<usize>,
+    /// Whether the terminal reports mouse events (off with `--no-mouse`,
+    /// which leaves text selection to the terminal)
+    pub mouse_capture: bool,
+    /// Byte total of the selection (updated by UI as it grows)
+    selection_bytes: Cell<SelectionBytes>,
     /// Cache for visual line calculations
     visual_cache: VisualLineCache,
     /// Application configuration (colors + search)
     pub config: Option<AppConfig>,
     /// Current search query string
     pub search_query: Option<String>,
+    /// Earlier searches the current one refines, outermost first
+    pub search_stack: Vec<String>,
+    /// How searches treat case
+    pub case_mode: CaseMode,
+    /// Whether searches and filters match whole words only (`:set word`)
+    pub whole_word: bool,
     /// Search state with matcher and cache
     pub search_state: Option<SearchState>,
+    /// Search still scanning in the background
+    pub search_job: Option<SearchJob>,
+    /// Running `:write` exports and the files they write
+    pub export_jobs: Vec<(String, Job<std::io::Result<export::Written>>)>,
+    /// `:stats`, `:exceptions` or `:sql` running in the background
+    pub overlay_job: Option<Job<Overlay>>,
+    /// Lines searches are limited to
+    pub search_range: Option<SearchRange>,
     /// Active selection for Helix-style line selection
     pub selection: Selection,
+    /// Bookmarked lines (keyed by storage index, survive re-filtering)
+    pub bookmarks: Bookmarks,
+    /// Storage indices of lines hidden by hand for this session
+    pub hidden: BTreeSet<usize>,
+    /// Lines shown in the pinned pane above the log view
+    pub pins: Pins,
+    /// Selected row in the :pins list
+    pub pin_list_selected: usize,
+    /// Selected row in the bookmark list
+    pub bookmark_list_selected: usize,
+    /// Diagnostics found in the log, once `:errors` or `]q`/`[q` scanned it
+    pub quickfix: Option<Vec<QuickfixEntry>>,
+    /// Selected row in the :errors list
+    pub quickfix_selected: usize,
+    /// Expressions counted in the watch sidebar
+    pub watches: Watches,
+    /// Running `:replay`; lines after its end are not shown yet
+    pub replay: Option<Replay>,
+    /// Pods of a `kubectl logs --prefix` stream
+    pub pods: Pods,
+    /// Selected row in the :pods list
+    pub pod_list_selected: usize,
+    /// `[` or `]` waiting for the key that completes it
+    pending_bracket: Option<char>,
+    /// Where search input began, while it is open
+    search_origin: Option<SearchOrigin>,
+    /// When to preview the search being typed
+    search_preview_at: Option<Instant>,
+    /// Cursor and scroll before a `g`, while `gt` or `gT` may follow
+    pending_g: Option<(usize, usize)>,
+    /// Detail pane for the line under the cursor, while open
+    pub detail: Option<DetailView>,
+    /// Hex dump of the line under the cursor, while open
+    pub hex: Option<HexView>,
+    /// Result of the last `:sql` query, while its overlay is open
+    pub sql_view: Option<SqlView>,
+    /// Stats of the lines in view, while the `:stats` overlay is open
+    pub stats_view: Option<StatsView>,
+    /// Exceptions in view, while the `:exceptions` overlay is open
+    pub exceptions_view: Option<ExceptionsView>,
+    /// Gaps between matches, while the `:gaps` overlay is open
+    pub gaps_view: Option<GapsView>,
+    /// Level and time limits chosen in the stats overlay
+    pub drill_down: DrillDown,
     /// System clipboard wrapper (may be None on headless systems)
     pub clipboard: Option<Clipboard>,
     /// Index for cycling through command completions (None when not completing)
     pub completion_index: Option<usize>,
     /// Original prefix for completion (stored to enable cycling)
     completion_prefix: String,
+    /// Executed `:` commands, stepped through with Up/Down
+    pub command_history: CommandHistory,
+    /// Recently opened file sets (shown in the :recent overlay)
+    pub recent: RecentFiles,
+    /// Selected entry index in the :recent overlay
+    pub recent_selected: usize,
+    /// Parked state of each tab; the shown tab's slot is empty
+    tabs: Vec<TabState>,
+    active_tab: usize,
+    /// Question shown in the prompt overlay, and what answering it does
+    pub prompt: Option<Prompt>,
+    pub prompt_action: Option<PromptAction>,
+    /// File set the user asked to open; picked up by the main loop
+    pending_open: Option<OpenRequest>,
+    /// Active color theme
+    pub theme: Theme,
+    /// Level indicator column in front of each line
+    pub gutter: Gutter,
+    /// Underline the cursor line
+    pub cursor_underline: bool,
+    /// Column marked with a vertical ruler
+    pub ruler_column: Option<usize>,
+    /// Keeps only the first lines of each message template in the view
+    pub throttle: Option<Throttle>,
+    /// Lines around the lines that pass the filters shown as context
+    pub context: Option<Context>,
+    /// Fields structured lines are shown in as columns (`:columns`)
+    pub columns: Option<Columns>,
+    /// Guided tutorial in progress (`--tutor`, `:tutor`)
+    pub tutor: Option<Tutor>,
+    /// Timings and cache counters for the performance overlay
+    pub perf: PerfStats,
+    /// Whether the performance overlay is shown
+    pub show_perf: bool,
+    /// Summary popup of the last completed load
+    pub load_summary: Option<LoadSummary>,
+    /// When the summary popup was shown
+    load_summary_shown_at: Option<Instant>,
+    /// Follows the loaded files for new lines while follow mode is on
+    follow: Option<Follower>,
+    /// Newest file of a `--follow-latest` pattern, switched to when a newer
+    /// one appears while following
+    pub follow_latest: Option<LatestFile>,
+    /// Line state captured by `prepare_reload`, restored by `restore_anchors`
+    reload_anchors: Option<ReloadAnchors>,
+    /// Session file (see `Sessions::default_path`); None disables sessions
+    pub session_path: Option<PathBuf>,
+    /// Whether the session is stored on quit; `:session clear` turns it off
+    session_autosave: bool,
 }
 
 impl App {
     pub fn new() -> Self {
+        Self::with_config(AppConfig::load())
+    }
+
+    /// App using `config` instead of the config file found on disk.
+    pub fn with_config(config: Option<AppConfig>) -> Self {
         let viewport_width = 80;
+        set_level_map(
+            config
+                .as_ref()
+                .map(|c| c.levels.clone())
+                .unwrap_or_default(),
+        );
+        let theme_config = config.as_ref().map(|c| c.theme.clone()).unwrap_or_default();
+        let view_config = config.as_ref().map(|c| c.view.clone()).unwrap_or_default();
+        let clipboard_backend = config
+            .as_ref()
+            .map(|c| c.clipboard.backend)
+            .unwrap_or_default();
+        let command_history = match CommandHistory::default_path() {
+            Some(path) if config.as_ref().is_some_and(|c| c.history.save) => {
+                CommandHistory::load_from_path(&path)
+            }
+            _ => CommandHistory::new(),
+        };
+        let themes = config.as_ref().map(|c| c.themes.as_slice()).unwrap_or(&[]);
+        let theme = theme_config
+            .name
+            .as_deref()
+            .and_then(|name| Theme::find(themes, name))
+            .cloned()
+            .unwrap_or_default();
         Self {
             storage: None,
             filtered_indices: Vec::new(),
             filters: FilterList::new(),
+            filter_index: FilterIndex::new(),
             mode: Mode::Normal,
             should_quit: false,
+            blurred_at_lines: None,
+            away_from: None,
+            last_read: None,
             status_message: String::new(),
+            status_kind: Severity::Info,
+            notifications: Notifications::new(),
+            message_list_selected: 0,
+            help: Vec::new(),
+            help_scroll: 0,
             scroll_offset: 0,
             horizontal_scroll: 0,
             selected_line: 0,
             loading_status: LoadingStatus::Idle,
+            load_progress: LoadProgress::default(),
             log_receiver: None,
             filter_list_selected: 0,
             input_buffer: String::new(),
             wrap_mode: true,
             viewport_height: Cell::new(20),
             viewport_width: Cell::new(viewport_width),
+            view_area: Cell::new(Rect::default()),
+            view_rows: RefCell::new(Vec::new()),
+            filter_list_area: Cell::new(Rect::default()),
+            drag_anchor: None,
+            mouse_capture: true,
+            selection_bytes: Cell::new(SelectionBytes::default()),
             visual_cache: VisualLineCache::new(10000, viewport_width),
-            config: AppConfig::load(),
+            config,
             search_query: None,
+            search_stack: Vec::new(),
+            case_mode: CaseMode::default(),
+            whole_word: false,
             search_state: None,
+            search_job: None,
+            export_jobs: Vec::new(),
+            overlay_job: None,
+            search_range: None,
             selection: Selection::new(),
-            clipboard: Clipboard::new().ok(),
+            bookmarks: Bookmarks::new(),
+            hidden: BTreeSet::new(),
+            pins: Pins::new(),
+            pin_list_selected: 0,
+            bookmark_list_selected: 0,
+            quickfix: None,
+            quickfix_selected: 0,
+            watches: Watches::new(),
+            replay: None,
+            pods: Pods::new(),
+            pod_list_selected: 0,
+            pending_bracket: None,
+            search_origin: None,
+            search_preview_at: None,
+            pending_g: None,
+            detail: None,
+            hex: None,
+            sql_view: None,
+            stats_view: None,
+            exceptions_view: None,
+            gaps_view: None,
+            drill_down: DrillDown::default(),
+            clipboard: Clipboard::new(clipboard_backend).ok(),
             completion_index: None,
             completion_prefix: String::new(),
+            command_history,
+            recent: RecentFiles::load(),
+            recent_selected: 0,
+            tabs: vec![TabState::default()],
+            active_tab: 0,
+            prompt: None,
+            prompt_action: None,
+            pending_open: None,
+            gutter: theme_config.gutter.unwrap_or(theme.gutter),
+            theme,
+            cursor_underline: view_config.cursor_underline,
+            ruler_column: view_config.ruler_column,
+            throttle: None,
+            context: None,
+            columns: None,
+            tutor: None,
+            perf: PerfStats::default(),
+            show_perf: false,
+            load_summary: None,
+            load_summary_shown_at: None,
+            follow: None,
+            follow_latest: None,
+            reload_anchors: None,
+            // Unit tests never touch the real session file
+            session_path: if cfg!(test) {
+                None
+            } else {
+                Sessions::default_path()
+            },
+            session_autosave: true,
         }
     }
 
@@ -147,7 +744,9 @@ impl App {
 
         let idx = self.completion_index.map_or(0, |i| i + 1);
 
-        if let Some((completed, new_idx)) = command::complete(&self.completion_prefix, idx) {
+        if let Some((completed, new_idx)) =
+            command::complete(&self.completion_prefix, idx, self.aliases())
+        {
             self.completion_index = Some(new_idx);
 
             let args = self
@@ -165,23 +764,131 @@ impl App {
         self.filtered_indices.len()
     }
 
+    /// The lines being viewed, as a [`LineSource`].
+    ///
+    /// The main view, search, export and the overlays that scan the lines
+    /// in view (`:stats`, `:exceptions`, `:gaps`, `:sql`, hex) read lines
+    /// through this. `App` still owns a `LogStorage`, and what changes it or
+    /// needs its files uses it directly: loading, following, filtering,
+    /// retention, and the file names and offsets behind the hex view and
+    /// the sidecar lookup. Moving those behind a source is planned in
+    /// `openspec/changes/line-source-viewer`.
+    pub fn source(&self) -> Option<&(dyn LineSource + Sync)> {
+        Some(self.storage.as_deref()? as &(dyn LineSource + Sync))
+    }
+
     /// Get a line by its index in the storage.
-    pub fn get_line(&self, idx: usize) -> Option<crate::model::MmapStr> {
-        self.storage.as_ref()?.get_line(idx)
+    pub fn get_line(&self, idx: usize) -> Option<crate::model::MmapStr<'_>> {
+        self.source()?.get_line(idx)
     }
 
     /// Get a filtered entry by its index in the filtered list.
-    pub fn get_filtered_entry(&self, idx: usize) -> Option<crate::model::MmapStr> {
+    pub fn get_filtered_entry(&self, idx: usize) -> Option<crate::model::MmapStr<'_>> {
         self.filtered_indices
             .get(idx)
             .and_then(|&log_idx| self.get_line(log_idx))
     }
 
+    /// Get the storage index of the line under the cursor.
+    pub fn selected_storage_index(&self) -> Option<usize> {
+        self.filtered_indices.get(self.selected_line).copied()
+    }
+
+    /// Storage indices of the selected lines, or of the cursor line without
+    /// a selection.
+    fn selected_storage_lines(&self) -> Vec<usize> {
+        let lines = if self.selection.is_active() {
+            self.selection.lines(self.selected_line)
+        } else {
+            vec![self.selected_line]
+        };
+        lines
+            .into_iter()
+            .filter_map(|idx| self.filtered_indices.get(idx).copied())
+            .collect()
+    }
+
+    /// Move the cursor to a storage line, or the next visible line if it is filtered out.
+    fn select_storage_index(&mut self, storage_idx: usize) {
+        self.selected_line = self
+            .filtered_indices
+            .partition_point(|&idx| idx < storage_idx)
+            .min(self.filtered_len().saturating_sub(1));
+        self.clamp_scroll();
+    }
+
+    /// Check whether a filtered entry is bookmarked.
+    pub fn is_bookmarked(&self, filtered_idx: usize) -> bool {
+        self.filtered_indices
+            .get(filtered_idx)
+            .is_some_and(|&idx| self.bookmarks.contains(idx))
+    }
+
     /// Get the timestamp of a filtered entry.
     pub fn get_filtered_timestamp(&self, idx: usize) -> Option<chrono::DateTime<chrono::Utc>> {
         self.filtered_indices
             .get(idx)
-            .and_then(|&log_idx| self.storage.as_ref()?.get_line_info(log_idx)?.timestamp)
+            .and_then(|&log_idx| self.source()?.get_timestamp(log_idx))
+    }
+
+    /// Get the timestamp of a filtered entry in nanoseconds since the epoch.
+    fn get_filtered_timestamp_nanos(&self, idx: usize) -> Option<i64> {
+        let log_idx = *self.filtered_indices.get(idx)?;
+        self.storage
+            .as_ref()?
+            .get_line_info(log_idx)?
+            .timestamp_nanos()
+    }
+
+    /// Get the timestamp of the entry a filtered line belongs to.
+    ///
+    /// Continuation lines (stack traces, wrapped messages) have no timestamp
+    /// of their own and use the nearest one above them.
+    pub fn get_entry_timestamp(&self, idx: usize) -> Option<chrono::DateTime<chrono::Utc>> {
+        (0..=idx)
+            .rev()
+            .take(ENTRY_TIMESTAMP_LOOKBACK)
+            .find_map(|i| self.get_filtered_timestamp(i))
+    }
+
+    /// Time between the first and last line of the active selection.
+    ///
+    /// Returns the start and end timestamps, or `None` without a selection or
+    /// when either end has no timestamp.
+    pub fn selection_time_span(
+        &self,
+    ) -> Option<(chrono::DateTime<chrono::Utc>, chrono::DateTime<chrono::Utc>)> {
+        let (start, end) = self.selection.range(self.selected_line)?;
+        Some((
+            self.get_entry_timestamp(start)?,
+            self.get_entry_timestamp(end)?,
+        ))
+    }
+
+    /// Line count, size and time span of the active selection.
+    pub fn selection_summary(&self) -> Option<SelectionSummary> {
+        let (start, end) = self.selection.range(self.selected_line)?;
+        let storage = self.storage.as_ref()?;
+        let line_bytes = |idx: usize| {
+            self.filtered_indices
+                .get(idx)
+                .and_then(|&line_idx| storage.get_line_info(line_idx))
+                .map_or(0, |info| u64::from(info.length))
+        };
+        let (lines, bytes) = if self.selection.is_set() {
+            let lines = self.selection.lines(self.selected_line);
+            (lines.len(), lines.into_iter().map(line_bytes).sum())
+        } else {
+            let mut cache = self.selection_bytes.get();
+            let bytes = cache.total(start, end, line_bytes);
+            self.selection_bytes.set(cache);
+            (end - start + 1, bytes)
+        };
+        Some(SelectionSummary {
+            lines,
+            bytes,
+            span: self.selection_time_span().map(|(from, to)| to - from),
+        })
     }
 
     /// Get the color for a log line based on configuration.
@@ -191,6 +898,97 @@ impl App {
         self.config.as_ref()?.colors.get_line_color(line)
     }
 
+    /// Get the part of a line colored by a regex rule with a capture group.
+    pub fn get_color_region(&self, line: &str) -> Option<(Range<usize>, Style)> {
+        let color_match = self.config.as_ref()?.colors.get_color_match(line)?;
+        Some((color_match.region?, Style::default().fg(color_match.color)))
+    }
+
+    /// Get the text style for a log line.
+    ///
+    /// A matching `[colors]` pattern wins; otherwise the theme's style for
+    /// the line's level is used.
+    pub fn get_line_style(&self, line: &str, level: Option<LogLevel>) -> Style {
+        if let Some(color) = self.get_line_color(line) {
+            return Style::default().fg(color);
+        }
+        level
+            .and_then(|level| self.theme.level_style(level))
+            .map(|level_style| level_style.style())
+            .unwrap_or_default()
+    }
+
+    /// Get the gutter indicator and its style for a line of the given level.
+    ///
+    /// Returns `None` when the gutter is off.
+    pub fn gutter_glyph(&self, level: Option<LogLevel>) -> Option<(&'static str, Style)> {
+        let glyph = self.gutter.glyph(level)?;
+        let style = level
+            .map(|level| self.theme.gutter_style(level))
+            .unwrap_or_default();
+        Some((glyph, style))
+    }
+
+    /// Themes defined in the config.
+    fn custom_themes(&self) -> &[Theme] {
+        self.config
+            .as_ref()
+            .map(|c| c.themes.as_slice())
+            .unwrap_or(&[])
+    }
+
+    /// Names of the themes `:theme` can switch to.
+    pub fn theme_names(&self) -> Vec<&str> {
+        Theme::all_names(self.custom_themes())
+    }
+
+    /// Switch to a theme from the config or a built-in one, resetting the
+    /// gutter to the theme's default.
+    pub fn set_theme(&mut self, name: &str) -> bool {
+        match Theme::find(self.custom_themes(), name).cloned() {
+            Some(theme) => {
+                self.gutter = theme.gutter;
+                self.theme = theme;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Get the selected-line colors, falling back to defaults.
+    pub fn selection_config(&self) -> SelectionConfig {
+        self.config
+            .as_ref()
+            .map(|c| c.selection.clone())
+            .unwrap_or_default()
+    }
+
+    /// Get the follow mode configuration, falling back to defaults.
+    /// Command aliases from the config file.
+    pub fn aliases(&self) -> &[(String, String)] {
+        self.config.as_ref().map_or(&[], |c| c.aliases.as_slice())
+    }
+
+    pub fn follow_config(&self) -> FollowConfig {
+        self.config
+            .as_ref()
+            .map(|c| c.follow.clone())
+            .unwrap_or_default()
+    }
+
+    /// Get the large file configuration, falling back to defaults.
+    pub fn large_file_config(&self) -> LargeFileConfig {
+        self.config
+            .as_ref()
+            .map(|c| c.large_files.clone())
+            .unwrap_or_default()
+    }
+
+    /// Check whether files keep their index and bookmarks in sidecar databases.
+    pub fn sidecar_enabled(&self) -> bool {
+        self.config.as_ref().is_some_and(|c| c.index.sidecar)
+    }
+
     /// Get the search configuration.
     pub fn search_config(&self) -> Option<&crate::config::SearchConfig> {
         self.config.as_ref().map(|c| &c.search)
@@ -211,7 +1009,9 @@ impl App {
     pub fn check_for_loaded_logs(&mut self) {
         if let Some(ref receiver) = self.log_receiver {
             while let Ok(storage) = receiver.try_recv() {
-                self.storage = Some(storage);
+                self.storage = Some(Arc::new(storage));
+                self.filter_index.clear();
+                self.search_range = None;
                 if let LoadingStatus::Loading { current, total } = self.loading_status {
                     self.loading_status = LoadingStatus::Loading {
                         current: current + 1,
@@ -231,939 +1031,6278 @@ impl App {
 
     /// Set the storage directly.
     pub fn set_storage(&mut self, storage: LogStorage) {
-        self.storage = Some(storage);
+        self.cancel_search_job();
+        if self.follow.is_some() {
+            // Keep following across reloads, from the end of the new files
+            let mut follower = Follower::new(&storage, &self.follow_config());
+            follower.watch();
+            self.follow = Some(follower);
+        }
+        self.storage = Some(Arc::new(storage));
+        self.filter_index.clear();
+        self.search_range = None;
+        self.away_from = None;
+        self.last_read = None;
+        self.quickfix = None;
+        self.replay = None;
+        if let Some(storage) = &self.storage {
+            self.watches.recount(storage.as_ref());
+        }
         self.update_filtered_logs();
     }
 
-    /// Update filtered indices based on current filters.
-    /// Uses byte-based matching for zero-allocation filtering.
-    pub fn update_filtered_logs(&mut self) {
-        self.filtered_indices.clear();
+    /// Check whether the terminal has focus.
+    ///
+    /// Terminals that do not report focus changes always count as focused.
+    pub fn is_focused(&self) -> bool {
+        self.blurred_at_lines.is_none()
+    }
 
-        let Some(storage) = &self.storage else {
+    /// Record a terminal focus change.
+    ///
+    /// While unfocused the UI is not redrawn for followed lines, which are
+    /// still appended; on return the status bar says how many arrived.
+    pub fn set_focused(&mut self, focused: bool) {
+        if !focused {
+            self.blurred_at_lines.get_or_insert(self.total_lines());
+            if self.is_following() {
+                self.away_from.get_or_insert(self.total_lines());
+            }
+            return;
+        }
+        let Some(lines) = self.blurred_at_lines.take() else {
             return;
         };
+        self.return_to_live();
+        let new_lines = self.total_lines().saturating_sub(lines);
+        if self.is_following() && new_lines > 0 {
+            self.notify_info(format!(
+                "{} new line{} while unfocused",
+                new_lines,
+                if new_lines == 1 { "" } else { "s" }
+            ));
+        }
+    }
 
-        // Get viewport width for visual cache
-        let viewport_width = self.viewport_width.get();
-        if self.visual_cache.viewport_width() != viewport_width {
-            self.visual_cache.set_viewport_width(viewport_width);
+    /// Check whether follow mode is on.
+    pub fn is_following(&self) -> bool {
+        self.follow.is_some()
+    }
+
+    /// Start following the loaded files for newly written lines.
+    pub fn start_follow(&mut self) {
+        let Some(storage) = &self.storage else {
+            self.notify_warn("Nothing to follow");
+            return;
+        };
+        let mut follower = Follower::new(storage, &self.follow_config());
+        follower.watch();
+        let count = follower.source_count();
+        self.follow = Some(follower);
+        // Following starts from the end of the files
+        if self.replay.take().is_some() {
+            self.update_filtered_logs();
         }
-        if self.visual_cache.wrap_mode() != self.wrap_mode {
-            self.visual_cache.set_wrap_mode(self.wrap_mode);
+        // Lines written while paused are read on the next poll
+        if let Some(first_unread) = self.away_from.take() {
+            self.last_read = Some(first_unread);
+        }
+        self.notify_info(format!(
+            "Following {} file{}",
+            count,
+            if count == 1 { "" } else { "s" }
+        ));
+        self.on_go_to_bottom();
+    }
+
+    fn on_toggle_follow(&mut self) {
+        if self.follow.take().is_some() {
+            self.away_from.get_or_insert(self.total_lines());
+            self.notify_info("Stopped following");
+        } else {
+            self.start_follow();
         }
+    }
 
-        // Filter using byte-based matching
-        for (idx, mmap_str) in storage.iter_enumerated() {
-            let line_bytes = mmap_str.as_bytes();
-            if self.filters.matches(line_bytes) {
-                self.filtered_indices.push(idx);
+    /// Start replaying the log from `from` (the cursor line's time if
+    /// None) at `speed` times real time, or change the speed of the
+    /// running replay.
+    fn start_replay(&mut self, speed: Option<u32>, from: Option<String>) {
+        let now = Instant::now();
+        if let (Some(replay), None) = (&mut self.replay, &from) {
+            if let Some(speed) = speed {
+                replay.set_speed(speed, now);
             }
+            let status = format!("Replaying at {}x", replay.speed());
+            self.notify_info(status);
+            return;
         }
+        if self.is_following() {
+            self.notify_warn("Stop following (F) before replaying");
+            return;
+        }
+        let cursor_time = self.get_entry_timestamp(self.selected_line);
+        let start = match &from {
+            Some(text) => {
+                let reference = cursor_time
+                    .or_else(|| {
+                        (0..self.filtered_len()).find_map(|idx| self.get_filtered_timestamp(idx))
+                    })
+                    .map(|time| time.date_naive());
+                match reference.and_then(|date| parse_time_bound(text, date)) {
+                    Some(start) => start,
+                    None => {
+                        self.notify_error(format!("Not a time: {}", text));
+                        return;
+                    }
+                }
+            }
+            None => match cursor_time {
+                Some(start) => start,
+                None => {
+                    self.notify_warn("No timestamp to replay from");
+                    return;
+                }
+            },
+        };
+        let Some(storage) = &self.storage else {
+            self.notify_warn("Nothing to replay");
+            return;
+        };
+        let mut replay = Replay::new(start, speed.unwrap_or(1), now);
+        replay.advance(storage, now);
+        self.replay = Some(replay);
+        self.update_filtered_logs();
+        self.on_go_to_bottom();
+        self.notify_info(format!(
+            "Replaying from {} at {}x (:replay pause, :replay stop)",
+            start.format("%Y-%m-%d %H:%M:%S"),
+            speed.unwrap_or(1)
+        ));
+    }
 
-        // Clear visual cache since filtered indices changed
-        self.visual_cache.clear();
+    /// End the replay, showing every line again.
+    fn stop_replay(&mut self, message: &str) {
+        if self.replay.take().is_none() {
+            self.notify_warn("No replay running");
+            return;
+        }
+        self.update_filtered_logs();
+        self.notify_info(format!(
+            "{}; all {} lines shown",
+            message,
+            self.total_lines()
+        ));
+    }
 
-        // Clear selection since filter indices are now invalid
-        self.selection.clear();
+    /// Show the lines the replay clock has reached since the last poll.
+    pub fn poll_replay(&mut self) {
+        let (Some(replay), Some(storage)) = (&mut self.replay, &self.storage) else {
+            return;
+        };
+        let revealed = replay.advance(storage, Instant::now());
+        let finished = replay.end() >= storage.len();
+        if !revealed.is_empty() {
+            self.reveal_lines(revealed);
+        }
+        if finished {
+            self.replay = None;
+            self.notify_info("Replay finished");
+        }
     }
 
-    /// Calculate visual line offsets for the current filtered view.
-    /// Uses on-demand calculation with caching.
-    pub fn calculate_visual_range(
-        &mut self,
-        start_idx: usize,
-        count: usize,
-    ) -> Vec<(usize, usize)> {
-        self.filtered_indices
-            .iter()
-            .skip(start_idx)
-            .take(count)
-            .enumerate()
-            .map(|(i, &line_idx)| {
-                let visual_count = if let Some(storage) = &self.storage {
-                    if let Some(line) = storage.get_line(line_idx) {
-                        self.visual_cache
-                            .calculate_visual_lines(&line.as_str_lossy())
-                    } else {
-                        1
-                    }
-                } else {
-                    1
-                };
-                (line_idx, i * visual_count)
-            })
-            .collect()
+    /// Append lines written to the followed files since the last poll.
+    ///
+    /// New lines from all files are merged by timestamp and filtered on
+    /// arrival (see `append_lines`).
+    pub fn poll_follow(&mut self) {
+        if self.follow.is_none() {
+            return;
+        }
+        // A background search still reads the storage; try again next tick
+        if self.storage.as_mut().and_then(Arc::get_mut).is_none() {
+            return;
+        }
+        if let Some(to) = self
+            .follow_latest
+            .as_mut()
+            .and_then(|latest| latest.check(Instant::now()))
+        {
+            self.switch_to_latest(to);
+            return;
+        }
+        let Some(follower) = &mut self.follow else {
+            return;
+        };
+        let update = follower.poll();
+        if let Some((path, rotation)) = update.rotations.last() {
+            self.notify_info(format!(
+                "{} was {}; following the new file",
+                path.display(),
+                rotation.describe()
+            ));
+        }
+        self.append_lines(update.lines);
     }
 
-    /// Get the visual line offset for a specific filtered line index.
-    pub fn selected_visual_line(&self) -> usize {
-        self.scroll_offset + self.selected_line
+    /// Reopen the file set with the newest `--follow-latest` file in place
+    /// of the one followed so far. Following continues after the reload.
+    fn switch_to_latest(&mut self, to: PathBuf) {
+        let Some(storage) = &self.storage else {
+            return;
+        };
+        let previous = self
+            .follow_latest
+            .as_ref()
+            .and_then(|latest| latest.previous().last().cloned());
+        let mut paths: Vec<PathBuf> = (0..storage.file_count())
+            .filter_map(|file| storage.file_path(file))
+            .filter(|path| Some(*path) != previous.as_deref())
+            .map(Path::to_path_buf)
+            .collect();
+        paths.push(to);
+        self.request_open(paths);
     }
 
-    /// Get the current scroll position in visual lines.
-    pub fn scroll_visual_line(&self) -> usize {
-        self.scroll_offset
+    /// After the reload for a `--follow-latest` switch, go to the end of
+    /// the new file and say which one is followed now.
+    pub fn finish_latest_switch(&mut self) {
+        let Some(latest) = &mut self.follow_latest else {
+            return;
+        };
+        if !latest.take_switched() {
+            return;
+        }
+        let status = format!(
+            "Switched to {}, the newest file matching {}",
+            latest.current().display(),
+            latest.spec()
+        );
+        self.notify_info(status);
+        self.on_go_to_bottom();
     }
 
-    /// Find the entry index by visual line number.
-    pub fn find_entry_by_visual_line(&self, target_visual: usize) -> usize {
-        // Simplified: just return the target or clamped
-        target_visual.min(self.filtered_len().saturating_sub(1))
+    /// Name of the file followed for `--follow-latest`, while following.
+    pub fn latest_file_name(&self) -> Option<String> {
+        let latest = self
+            .follow_latest
+            .as_ref()
+            .filter(|_| self.is_following())?;
+        latest
+            .current()
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned())
     }
 
-    /// Handle keyboard input by translating to messages and processing them.
-    pub fn handle_key(&mut self, key: crossterm::event::KeyEvent) {
-        use crate::key_bindings::translate;
-        if let Some(msg) = translate(key, self.mode) {
-            self.process_message(msg);
+    /// Append lines after every loaded line, filtering them on arrival.
+    ///
+    /// The view stays pinned to the bottom if the cursor was on the last
+    /// line. Returns false, appending nothing, while the storage is shared
+    /// with a background search.
+    pub fn append_lines(&mut self, lines: Vec<FollowedLine>) -> bool {
+        let Some(storage) = self.storage.as_mut().and_then(Arc::get_mut) else {
+            return false;
+        };
+        let lines = self.pods.admit(lines, Instant::now());
+        if lines.is_empty() {
+            return true;
         }
-    }
 
-    /// Process a message and update application state accordingly.
-    fn process_message(&mut self, msg: Msg) {
-        match msg {
-            // Navigation
-            Msg::ScrollDown => self.on_scroll_down(),
-            Msg::ScrollUp => self.on_scroll_up(),
-            Msg::ScrollRight => self.on_scroll_right(),
-            Msg::ScrollLeft => self.on_scroll_left(),
-            Msg::GoToBottom => self.on_go_to_bottom(),
-            Msg::GoToTop => self.on_go_to_top(),
-
-            // Command mode
-            Msg::EnterCommand => self.on_enter_command(),
-            Msg::CancelCommand => self.on_cancel_command(),
-            Msg::SubmitCommand => self.on_submit_command(),
-            Msg::CommandTypeChar(c) => self.on_command_type_char(c),
-            Msg::CommandBackspace => self.on_command_backspace(),
-            Msg::CommandComplete => self.on_command_complete(),
-
-            // Search
-            Msg::EnterSearch => self.on_enter_search(),
-            Msg::CancelSearch => self.on_cancel_search(),
-            Msg::SubmitSearch => self.on_submit_search(),
-            Msg::SearchTypeChar(c) => self.on_search_type_char(c),
-            Msg::SearchBackspace => self.on_search_backspace(),
-            Msg::NextMatch => self.next_match(),
-            Msg::PrevMatch => self.prev_match(),
-            Msg::ClearSearch => self.on_clear_search(),
+        let first_idx = storage.len();
+        for line in &lines {
+            storage.append_line(line.file_index, &line.text, line.timestamp);
+        }
+        if !self.filters.is_empty() {
+            self.filter_index.update(storage, &self.filters);
+        }
+        let end = storage.len();
+        self.reveal_lines(first_idx..end);
 
-            // Selection
-            Msg::ToggleSelection => self.on_toggle_selection(),
-            Msg::YankSelection => self.on_yank(),
-            Msg::ClearSelection => self.on_clear_selection(),
+        if let (Some(entries), Some(storage)) = (&mut self.quickfix, &self.storage) {
+            entries.extend(quickfix::scan(storage.as_ref(), first_idx..end));
+        }
+        if let Some(storage) = &self.storage {
+            self.watches
+                .append(storage.as_ref(), first_idx..end, Instant::now());
+        }
+        self.apply_retention();
+        true
+    }
 
-            // Filter list
-            Msg::FilterListDown => self.on_filter_list_down(),
-            Msg::FilterListUp => self.on_filter_list_up(),
-            Msg::DeleteSelectedFilter => self.on_delete_selected_filter(),
-            Msg::CloseFilterList => self.on_close_filter_list(),
+    /// Add storage lines `range`, new to the view, to the filtered lines
+    /// and the search; appended lines and lines a replay reaches come in
+    /// this way.
+    ///
+    /// The view stays pinned to the bottom if the cursor was on the last
+    /// line.
+    fn reveal_lines(&mut self, range: Range<usize>) {
+        let Some(storage) = &self.storage else {
+            return;
+        };
+        let at_bottom = self.selected_line + 1 >= self.filtered_indices.len();
+        let first_new = self.filtered_indices.len();
+        if !at_bottom {
+            self.away_from.get_or_insert(range.start);
+        }
+        let keep = |idx: usize| !self.hidden.contains(&idx);
+        let mut entry_time = None;
+        for idx in range.clone() {
+            if self.filter_index.is_visible(&self.filters, idx)
+                && keep(idx)
+                && self.drill_down.admits(storage, idx, &mut entry_time)
+                && self.throttle.as_mut().is_none_or(|throttle| {
+                    storage
+                        .get_line(idx)
+                        .is_some_and(|line| throttle.admit(idx, line.as_bytes()))
+                })
+            {
+                match &mut self.context {
+                    Some(context) => context.push_match(&mut self.filtered_indices, idx, keep),
+                    None => self.filtered_indices.push(idx),
+                }
+            }
+        }
+        if let Some(context) = &mut self.context {
+            context.fill_after(&mut self.filtered_indices, range.end, keep);
+        }
 
-            // View options
-            Msg::ToggleWrap => self.on_toggle_wrap(),
+        let open_ended = self
+            .search_range
+            .as_ref()
+            .is_none_or(|range| range.last.is_none());
+        if let (Some(state), true) = (&mut self.search_state, open_ended) {
+            state.scope.end = self.filtered_indices.len();
+            let (count, first) = search::scan_matches(
+                storage.as_ref(),
+                &self.filtered_indices[first_new..],
+                first_new,
+                &state.matcher,
+                &state.within,
+            );
+            state.total_matches += count;
+            if state.current_position.is_none() {
+                state.current_position = first;
+            }
+        }
 
-            // Application
-            Msg::Quit => self.should_quit = true,
-            // Keys that don't map to an action in the current mode (e.g., unmapped keys in Normal mode)
-            Msg::NoOp => {}
+        if at_bottom && !self.selection.is_active() {
+            self.on_go_to_bottom();
         }
     }
 
-    // Navigation handlers
+    /// Prune the oldest appended lines beyond the `[follow]` retention
+    /// limits, appending them to the spill file first if one is set.
+    ///
+    /// Bookmarks, pins and hidden lines on pruned lines are dropped; the
+    /// cursor stays on its line unless that was pruned too. The view and
+    /// the search are updated in place rather than rebuilt, so following
+    /// at the limit costs no more than following below it.
+    fn apply_retention(&mut self) {
+        let config = self.follow_config();
+        let Some(storage) = self.storage.as_mut().and_then(Arc::get_mut) else {
+            return;
+        };
+        let count = config.retention.due(storage);
+        if count == 0 {
+            return;
+        }
+        let start = storage.live_start();
+        let pruned = start..start + count;
+        let spill_error = config.spill.as_ref().and_then(|path| {
+            let e = spill_lines(storage, pruned.clone(), path).err()?;
+            tracing::warn!("cannot spill pruned lines to {}: {}", path.display(), e);
+            Some(format!(
+                "Cannot spill pruned lines to {}: {}",
+                path.display(),
+                e
+            ))
+        });
+        // Rows of the view showing pruned lines go, with their matches
+        let from = self.filtered_indices.partition_point(|&idx| idx < start);
+        let to = self
+            .filtered_indices
+            .partition_point(|&idx| idx < pruned.end);
+        if let Some(state) = &mut self.search_state {
+            state.remove_rows(&*storage, &self.filtered_indices, from..to);
+        }
+        self.filter_index.remove_lines(storage, start, count);
+        storage.prune_live(count);
+        tracing::debug!(count, "pruned followed lines");
+        if let Some(message) = spill_error {
+            self.notify_error(message);
+        }
 
-    fn on_scroll_down(&mut self) {
-        self.status_message.clear();
-        if self.selected_line < self.filtered_len().saturating_sub(1) {
-            let old_line = self.selected_line;
-            self.selected_line += 1;
-            // Extend selection if active
-            if self.selection.is_active() {
-                let direction = if self.selected_line > old_line {
-                    Direction::Down
+        self.filtered_indices.drain(from..to);
+        for idx in &mut self.filtered_indices[from..] {
+            *idx -= count;
+        }
+        if to > from {
+            let shift_row = |row: usize| {
+                if row >= to {
+                    row - (to - from)
                 } else {
-                    Direction::Up
-                };
-                self.selection.extend(self.selected_line, direction);
+                    row.min(from)
+                }
+            };
+            self.selected_line = shift_row(self.selected_line);
+            self.scroll_offset = shift_row(self.scroll_offset);
+            self.visual_cache.clear();
+            self.selection.clear();
+            self.selection_bytes.set(SelectionBytes::default());
+        }
+        if let Some(throttle) = &mut self.throttle {
+            throttle.remove_lines(start, count);
+        }
+        if let Some(context) = &mut self.context {
+            context.remove_lines(start, count);
+        }
+
+        let shift = |idx: usize| {
+            if idx >= pruned.end {
+                idx - count
+            } else {
+                idx.min(start)
+            }
+        };
+        self.bookmarks.remove_lines(start, count);
+        self.pins.remove_lines(start, count);
+        self.away_from = self.away_from.map(shift);
+        self.last_read = self.last_read.map(shift);
+        self.hidden = std::mem::take(&mut self.hidden)
+            .into_iter()
+            .filter(|idx| !pruned.contains(idx))
+            .map(shift)
+            .collect();
+        if let Some(range) = &mut self.search_range {
+            range.first = shift(range.first);
+            range.last = range.last.map(shift);
+        }
+        if let Some(entries) = &mut self.quickfix {
+            entries.retain(|entry| !pruned.contains(&entry.line));
+            for entry in entries {
+                entry.line = shift(entry.line);
             }
         }
-        self.clamp_scroll();
     }
 
-    fn on_scroll_up(&mut self) {
-        self.status_message.clear();
-        let old_line = self.selected_line;
-        self.selected_line = self.selected_line.saturating_sub(1);
-        // Extend selection if active
-        if self.selection.is_active() {
-            let direction = if self.selected_line < old_line {
-                Direction::Up
-            } else {
-                Direction::Down
-            };
-            self.selection.extend(self.selected_line, direction);
+    /// Show the summary of a completed load in a popup and the status bar.
+    pub fn show_load_summary(&mut self, summary: LoadSummary) {
+        if summary.files_failed > 0 {
+            self.notify_warn(summary.status_line());
+        } else {
+            self.notify_info(summary.status_line());
         }
-        self.clamp_scroll();
+        self.load_summary = Some(summary);
+        self.load_summary_shown_at = Some(Instant::now());
     }
 
-    fn on_scroll_right(&mut self) {
-        self.horizontal_scroll = self.horizontal_scroll.saturating_add(4);
+    /// Hide the load summary popup once it has been up long enough.
+    pub fn expire_load_summary(&mut self) {
+        if self
+            .load_summary_shown_at
+            .is_some_and(|shown| shown.elapsed() >= LOAD_SUMMARY_TIMEOUT)
+        {
+            self.dismiss_load_summary();
+        }
     }
 
-    fn on_scroll_left(&mut self) {
-        self.horizontal_scroll = self.horizontal_scroll.saturating_sub(4);
+    fn dismiss_load_summary(&mut self) {
+        self.load_summary = None;
+        self.load_summary_shown_at = None;
     }
 
-    fn on_go_to_bottom(&mut self) {
-        self.selected_line = self.filtered_len().saturating_sub(1);
+    /// Move the cursor to the configured `startup_position` after a load.
+    pub fn apply_startup_position(&mut self) {
+        let position = self
+            .config
+            .as_ref()
+            .map(|c| c.view.startup_position)
+            .unwrap_or_default();
+        let last = self.filtered_len().saturating_sub(1);
+
+        // Positions not found near the end fall back to the last line
+        self.selected_line = match position {
+            StartupPosition::Start => return,
+            StartupPosition::End => last,
+            StartupPosition::LastError => self.find_last_error().unwrap_or(last),
+            StartupPosition::LastMinutes(minutes) => {
+                self.find_last_minutes_start(minutes).unwrap_or(last)
+            }
+        };
         self.clamp_scroll();
     }
 
-    fn on_go_to_top(&mut self) {
-        self.selected_line = 0;
-        self.scroll_offset = 0;
+    /// Filtered index of the last error line near the end.
+    fn find_last_error(&self) -> Option<usize> {
+        (0..self.filtered_len())
+            .rev()
+            .take(STARTUP_SCAN_LINES)
+            .find(|&idx| {
+                self.get_filtered_entry(idx)
+                    .is_some_and(|line| detect_level(&line.as_str_lossy()) == Some(LogLevel::Error))
+            })
     }
 
-    fn clamp_scroll(&mut self) {
-        if self.filtered_indices.is_empty() {
-            return;
+    /// Filtered index of the first line within `minutes` of the newest timestamp.
+    fn find_last_minutes_start(&self, minutes: u32) -> Option<usize> {
+        let mut recent = (0..self.filtered_len()).rev().take(STARTUP_SCAN_LINES);
+        let (newest_idx, newest) = recent
+            .by_ref()
+            .find_map(|idx| Some((idx, self.get_filtered_timestamp_nanos(idx)?)))?;
+        let cutoff = newest.saturating_sub(minutes as i64 * 60_000_000_000);
+
+        let mut start = newest_idx;
+        for idx in recent {
+            match self.get_filtered_timestamp_nanos(idx) {
+                Some(ts) if ts < cutoff => break,
+                Some(_) => start = idx,
+                // Continuation lines belong to the entry above them
+                None => {}
+            }
         }
+        Some(start)
+    }
 
-        self.selected_line = self
-            .selected_line
-            .min(self.filtered_len().saturating_sub(1));
-
-        let viewport_height = self.viewport_height.get();
-
-        let effective_height = if self.wrap_mode {
-            (viewport_height / 2).max(1)
-        } else {
-            viewport_height
-        };
+    /// Ask to open a file set.
+    ///
+    /// Files above the on-demand size are indexed lazily. Files above the
+    /// prompt threshold first show the large-file prompt so the user can
+    /// choose how they are indexed.
+    pub fn request_open(&mut self, paths: Vec<PathBuf>) {
+        let config = self.large_file_config();
+        let sizes: Vec<u64> = paths
+            .iter()
+            .map(|path| std::fs::metadata(path).map(|m| m.len()).unwrap_or(0))
+            .collect();
 
-        if self.selected_line < self.scroll_offset {
-            self.scroll_offset = self.selected_line;
-        } else if self.selected_line >= self.scroll_offset + effective_height {
-            self.scroll_offset = self
-                .selected_line
-                .saturating_sub(effective_height.saturating_sub(1));
+        let oversized: Vec<(PathBuf, u64)> = paths
+            .iter()
+            .zip(&sizes)
+            .filter(|(_, &size)| size > config.threshold_bytes)
+            .map(|(path, &size)| (path.clone(), size))
+            .collect();
+        if !oversized.is_empty() {
+            let prompt = large_file_prompt(&config, &oversized);
+            self.open_prompt(
+                prompt,
+                PromptAction::OpenLargeFiles(LargeFilePrompt { paths, oversized }),
+            );
+            return;
         }
-    }
-
-    // Command mode handlers
 
-    fn on_enter_command(&mut self) {
-        self.mode = Mode::Command;
+        let files = paths
+            .into_iter()
+            .zip(sizes)
+            .map(|(path, size)| (path, default_index_mode(&config, size)))
+            .collect();
+        self.pending_open = Some(OpenRequest { files });
     }
 
-    fn on_cancel_command(&mut self) {
-        self.mode = Mode::Normal;
-        self.input_buffer.clear();
+    /// Open a file set with the same index mode for every file, skipping
+    /// the large-file prompt (used for explicit `--tail` requests).
+    pub fn request_open_with(&mut self, paths: Vec<PathBuf>, mode: IndexMode) {
+        self.pending_open = Some(OpenRequest {
+            files: paths.into_iter().map(|p| (p, mode)).collect(),
+        });
     }
 
-    fn on_submit_command(&mut self) {
-        self.mode = self.execute_command();
-        self.input_buffer.clear();
+    /// Take the pending request to open a different file set, if any.
+    pub fn take_open_request(&mut self) -> Option<OpenRequest> {
+        self.pending_open.take()
     }
 
-    fn on_command_type_char(&mut self, c: char) {
-        self.completion_index = None;
-        self.completion_prefix.clear();
-        self.input_buffer.push(c);
+    pub fn tab_count(&self) -> usize {
+        self.tabs.len()
     }
 
-    fn on_command_backspace(&mut self) {
-        self.completion_index = None;
-        self.completion_prefix.clear();
-        self.input_buffer.pop();
+    /// Position of the shown tab, counted from 0.
+    pub fn active_tab(&self) -> usize {
+        self.active_tab
     }
 
-    fn on_command_complete(&mut self) {
-        self.apply_completion();
+    /// Name of each tab: its first file, with `+N` for the other files.
+    pub fn tab_labels(&self) -> Vec<String> {
+        (0..self.tabs.len())
+            .map(|idx| {
+                let storage = if idx == self.active_tab {
+                    self.storage.as_deref()
+                } else {
+                    self.tabs[idx].storage.as_deref()
+                };
+                let Some(storage) = storage else {
+                    return "(empty)".to_string();
+                };
+                let name = storage
+                    .file_path(0)
+                    .and_then(Path::file_name)
+                    .map(|name| name.to_string_lossy().into_owned())
+                    .unwrap_or_default();
+                match storage.file_count() {
+                    0 | 1 => name,
+                    count => format!("{} +{}", name, count - 1),
+                }
+            })
+            .collect()
     }
 
-    fn execute_command(&mut self) -> Mode {
-        let result = command::parse(&self.input_buffer);
-        self.status_message = result.status;
-
-        if let Some(effect) = result.effect {
-            match effect {
-                CommandEffect::Quit => {
-                    self.should_quit = true;
-                }
-                CommandEffect::AddFilter { kind, pattern } => {
-                    match kind {
-                        FilterKind::Include => self.filters.add_include(&pattern),
-                        FilterKind::Exclude => self.filters.add_exclude(&pattern),
-                    }
-                    self.update_filtered_logs();
-                }
-                CommandEffect::ClearFilters => {
-                    self.filters.clear();
-                    self.update_filtered_logs();
-                }
-                CommandEffect::WriteFilteredLogs { filename } => {
-                    match self.write_filtered_logs(&filename) {
-                        Ok(count) => {
-                            self.status_message = format!("Saved {} lines to {}", count, filename);
-                        }
-                        Err(e) => {
-                            self.status_message = format!("Error: {}", e);
-                        }
-                    }
-                }
-                CommandEffect::ListFilters => {
-                    self.filter_list_selected = 0;
-                    return Mode::FilterList;
-                }
-            }
+    /// Open `paths` in a new tab after the shown one.
+    pub fn open_tab(&mut self, paths: Vec<PathBuf>) {
+        if self.is_loading() {
+            self.notify_warn("Wait for the files to load");
+            return;
         }
-        Mode::Normal
+        let mut tab = TabState::default();
+        tab.filters.set_whole_word(self.whole_word);
+        self.tabs.insert(self.active_tab + 1, tab);
+        self.switch_tab(self.active_tab + 1);
+        self.request_open(paths);
     }
 
-    fn write_filtered_logs(&self, filename: &str) -> std::io::Result<usize> {
-        let mut file = File::create(filename)?;
-        let mut count = 0;
-
-        let Some(storage) = &self.storage else {
-            return Ok(0);
+    /// Close the shown tab and show its neighbour; the last tab stays.
+    fn close_tab(&mut self) {
+        if self.tabs.len() == 1 {
+            self.notify_warn("Cannot close the last tab (:q quits)");
+            return;
+        }
+        if self.is_loading() {
+            self.notify_warn("Wait for the files to load");
+            return;
+        }
+        let closing = self.active_tab;
+        let next = if closing + 1 < self.tabs.len() {
+            closing + 1
+        } else {
+            closing - 1
         };
-
-        for &idx in &self.filtered_indices {
-            if let Some(line) = storage.get_line(idx) {
-                writeln!(file, "{}", line.as_str_lossy())?;
-                count += 1;
-            }
+        self.switch_tab(next);
+        let closed = self.tabs.remove(closing);
+        if let Some(job) = closed.search_job {
+            job.cancel();
         }
-
-        Ok(count)
+        if next > closing {
+            self.active_tab -= 1;
+        }
+        self.notify_info(format!("Closed tab {}", closing + 1));
     }
 
-    // Search handlers
-
-    fn on_enter_search(&mut self) {
-        self.mode = Mode::SearchInput;
-        // Pre-populate with last search query if exists
-        if let Some(last_query) = &self.search_query {
-            self.input_buffer = last_query.clone();
+    /// Show the next tab, or the previous one, wrapping around.
+    fn step_tab(&mut self, forward: bool) {
+        let count = self.tabs.len();
+        if count == 1 {
+            self.notify_warn("Only one tab (:tabnew <file> opens another)");
+            return;
+        }
+        let next = if forward {
+            (self.active_tab + 1) % count
         } else {
-            self.input_buffer.clear();
+            (self.active_tab + count - 1) % count
+        };
+        if self.is_loading() {
+            self.notify_warn("Wait for the files to load");
+            return;
         }
+        self.switch_tab(next);
+        self.notify_info(format!(
+            "Tab {}/{}: {}",
+            next + 1,
+            count,
+            self.tab_labels()[next]
+        ));
     }
 
-    fn on_cancel_search(&mut self) {
+    /// Park the shown tab's state and show tab `idx`.
+    fn switch_tab(&mut self, idx: usize) {
+        let mut shown = std::mem::take(&mut self.tabs[idx]);
+        self.swap_tab_state(&mut shown);
+        self.tabs[self.active_tab] = shown;
+        self.active_tab = idx;
+
+        // Overlays belong to the tab left behind
+        self.overlay_job = None;
         self.mode = Mode::Normal;
-        self.input_buffer.clear();
+        self.detail = None;
+        self.hex = None;
+        self.sql_view = None;
+        self.stats_view = None;
+        self.exceptions_view = None;
+        self.gaps_view = None;
+        self.drag_anchor = None;
+        self.selection_bytes.set(SelectionBytes::default());
+        self.dismiss_load_summary();
+        self.visual_cache.clear();
     }
 
-    fn on_submit_search(&mut self) {
-        if self.input_buffer.trim().is_empty() {
-            // Empty query clears search
-            self.clear_search();
+    fn swap_tab_state(&mut self, tab: &mut TabState) {
+        use std::mem::swap;
+        swap(&mut self.storage, &mut tab.storage);
+        swap(&mut self.filtered_indices, &mut tab.filtered_indices);
+        swap(&mut self.filters, &mut tab.filters);
+        swap(&mut self.filter_index, &mut tab.filter_index);
+        swap(&mut self.away_from, &mut tab.away_from);
+        swap(&mut self.last_read, &mut tab.last_read);
+        swap(&mut self.scroll_offset, &mut tab.scroll_offset);
+        swap(&mut self.horizontal_scroll, &mut tab.horizontal_scroll);
+        swap(&mut self.selected_line, &mut tab.selected_line);
+        swap(&mut self.search_query, &mut tab.search_query);
+        swap(&mut self.search_stack, &mut tab.search_stack);
+        swap(&mut self.search_state, &mut tab.search_state);
+        swap(&mut self.search_job, &mut tab.search_job);
+        swap(&mut self.search_range, &mut tab.search_range);
+        swap(&mut self.selection, &mut tab.selection);
+        swap(&mut self.bookmarks, &mut tab.bookmarks);
+        swap(&mut self.hidden, &mut tab.hidden);
+        swap(&mut self.pins, &mut tab.pins);
+        swap(&mut self.quickfix, &mut tab.quickfix);
+        swap(&mut self.watches, &mut tab.watches);
+        swap(&mut self.replay, &mut tab.replay);
+        swap(&mut self.pods, &mut tab.pods);
+        swap(&mut self.drill_down, &mut tab.drill_down);
+        swap(&mut self.follow, &mut tab.follow);
+        swap(&mut self.follow_latest, &mut tab.follow_latest);
+        swap(&mut self.reload_anchors, &mut tab.reload_anchors);
+    }
+
+    /// Start counting the lines matching `expr` in the watch sidebar.
+    fn add_watch(&mut self, expr: &str) {
+        let Some(watch) = Watch::parse(expr) else {
+            self.notify_error("Usage: watch <text|key=value>");
+            return;
+        };
+        let expr = watch.expr().to_string();
+        let source = self.storage.as_deref().map(|s| s as &dyn LineSource);
+        if self.watches.add(watch, source) {
+            let count = self.watches.iter().last().map_or(0, Watch::count);
+            self.notify_info(format!("Watching {}: {} lines", expr, count));
         } else {
-            // Execute search with non-empty query
-            let query = self.input_buffer.trim().to_string();
-            self.search_query = Some(query.clone());
-            self.init_search_state(query);
+            self.notify_warn(format!("Already watching {}", expr));
         }
-        self.mode = Mode::Normal;
-        self.input_buffer.clear();
     }
 
-    fn on_search_type_char(&mut self, c: char) {
-        self.input_buffer.push(c);
+    /// Stop the watch numbered or written `target`, or all of them.
+    fn remove_watch(&mut self, target: Option<&str>) {
+        match target {
+            None if self.watches.is_empty() => self.notify_warn("No watches"),
+            None => {
+                let count = self.watches.len();
+                self.watches.clear();
+                self.notify_info(format!(
+                    "Removed {} watch{}",
+                    count,
+                    if count == 1 { "" } else { "es" }
+                ));
+            }
+            Some(target) => match self.watches.remove(target) {
+                Some(watch) => self.notify_info(format!("Stopped watching {}", watch.expr())),
+                None => self.notify_warn(format!("No watch {}", target)),
+            },
+        }
     }
 
-    fn on_search_backspace(&mut self) {
-        self.input_buffer.pop();
+    fn is_loading(&self) -> bool {
+        matches!(self.loading_status, LoadingStatus::Loading { .. }) || self.pending_open.is_some()
     }
 
-    fn on_clear_search(&mut self) {
+    /// Reset per-file state before a new file set is loaded.
+    /// Filters and view options are kept; line-based state is dropped.
+    pub fn prepare_reload(&mut self, total_files: usize) {
+        if let Err(e) = self.save_annotations() {
+            tracing::warn!("{}", e);
+        }
+        if let Err(e) = self.save_session() {
+            tracing::warn!("Error saving session: {}", e);
+        }
+        self.reload_anchors = self.storage.as_ref().map(|storage| ReloadAnchors {
+            bookmarks: self
+                .bookmarks
+                .iter()
+                .filter_map(|(idx, mark)| Some((LineAnchor::capture(storage, idx)?, mark.clone())))
+                .collect(),
+            pins: self
+                .pins
+                .iter()
+                .filter_map(|idx| LineAnchor::capture(storage, idx))
+                .collect(),
+            cursor: self
+                .selected_storage_index()
+                .and_then(|idx| LineAnchor::capture(storage, idx)),
+        });
+        self.storage = None;
+        self.filter_index.clear();
+        self.search_range = None;
+        self.filtered_indices.clear();
+        self.selection.clear();
+        self.bookmarks.clear();
+        self.hidden.clear();
+        self.pins.clear();
         self.clear_search();
+        self.selected_line = 0;
+        self.scroll_offset = 0;
+        self.horizontal_scroll = 0;
         self.status_message.clear();
+        self.dismiss_load_summary();
+        self.load_progress = LoadProgress::default();
+        self.loading_status = LoadingStatus::Loading {
+            current: 0,
+            total: total_files.max(1),
+        };
     }
 
-    // Selection handlers
+    /// Show the progress of a background load on the loading screen; a file
+    /// that failed to load is named in the status bar.
+    pub fn on_load_event(&mut self, event: &LoadEvent) {
+        self.load_progress.apply(event);
+        match event {
+            LoadEvent::FileStarted { file, total, .. } => {
+                self.loading_status = LoadingStatus::Loading {
+                    current: *file,
+                    total: (*total).max(*file),
+                };
+            }
+            LoadEvent::Error { message, .. } => self.notify_error(message.clone()),
+            _ => {}
+        }
+    }
 
-    fn on_toggle_selection(&mut self) {
-        if self.selection.is_active() {
-            // Extend selection - determine direction based on cursor movement
-            if let Some((start, _end)) = self.selection.range(self.selected_line) {
-                let direction = if self.selected_line > start {
-                    Direction::Down
-                } else {
-                    Direction::Up
-                };
-                self.selection.extend(self.selected_line, direction);
+    /// Put bookmarks and the cursor back on their lines after a reload.
+    ///
+    /// Lines are found by content, so they are restored even if they moved.
+    /// Returns true if the cursor was restored.
+    pub fn restore_anchors(&mut self) -> bool {
+        let (Some(anchors), Some(storage)) = (self.reload_anchors.take(), &self.storage) else {
+            return false;
+        };
+        let mut lost = 0;
+        for (anchor, mark) in anchors.bookmarks {
+            match anchor.resolve(storage) {
+                Some(idx) => self.bookmarks.insert(idx, mark),
+                None => lost += 1,
             }
-        } else {
-            // Start new selection at current cursor
-            self.selection.start(self.selected_line);
         }
+        for anchor in anchors.pins {
+            if let Some(idx) = anchor.resolve(storage) {
+                self.pins.add(idx);
+            }
+        }
+        let cursor = anchors.cursor.and_then(|anchor| anchor.resolve(storage));
+        if lost > 0 {
+            self.notify_warn(format!(
+                "{} bookmark{} not found after reload",
+                lost,
+                if lost == 1 { "" } else { "s" }
+            ));
+        }
+        if let Some(idx) = cursor {
+            self.select_storage_index(idx);
+        }
+        cursor.is_some()
     }
 
-    fn on_clear_selection(&mut self) {
-        self.selection.clear();
-        self.status_message.clear();
-    }
-
-    fn on_yank(&mut self) {
-        // Check if selection is active
-        if !self.selection.is_active() {
+    /// Put back bookmarks stored in the sidecar databases of the loaded files.
+    pub fn load_annotations(&mut self) {
+        if !self.sidecar_enabled() {
             return;
         }
-
-        // Check if clipboard is available
-        let Some(ref mut clipboard) = self.clipboard else {
-            self.status_message = "Clipboard unavailable - install display server".to_string();
-            return;
-        };
-
-        // Get the selection range
-        let Some((start, end)) = self.selection.range(self.selected_line) else {
+        let Some(storage) = self.storage.clone() else {
             return;
         };
-
-        // Retrieve the raw lines from storage
-        let Some(ref storage) = self.storage else {
-            return;
-        };
-
-        let mut lines = Vec::new();
-        for idx in start..=end {
-            if let Some(&storage_idx) = self.filtered_indices.get(idx) {
-                if let Some(line) = storage.get_line(storage_idx) {
-                    lines.push(line.as_str_lossy().to_string());
+        for file in 0..storage.file_count() {
+            let (Some(_), Some(path)) = (storage.file_lines(file), storage.file_path(file)) else {
+                continue;
+            };
+            match Sidecar::open(path).and_then(|sidecar| sidecar.load_bookmarks()) {
+                Ok(marks) => {
+                    for (local, mark) in marks {
+                        if let Some(idx) = storage.file_line_index(file, local) {
+                            self.bookmarks.insert(idx, mark);
+                        }
+                    }
                 }
+                Err(e) => self.notify_error(e.to_string()),
             }
         }
+    }
 
-        if lines.is_empty() {
-            return;
+    /// Store the bookmarks of each fully indexed file in its sidecar database.
+    pub fn save_annotations(&self) -> Result<(), QlogError> {
+        if !self.sidecar_enabled() {
+            return Ok(());
         }
-
-        // Join lines with newline
-        let text = lines.join("\n");
-
-        // Copy to clipboard
-        match clipboard.copy(&text) {
-            Ok(()) => {
-                self.status_message = format!("Copied {} lines to clipboard", lines.len());
-            }
-            Err(e) => {
-                self.status_message = format!("Failed to copy: {}", e);
-            }
+        let Some(storage) = &self.storage else {
+            return Ok(());
+        };
+        for file in 0..storage.file_count() {
+            let (Some(_), Some(path)) = (storage.file_lines(file), storage.file_path(file)) else {
+                continue;
+            };
+            let marks = self.bookmarks.iter().filter_map(|(idx, mark)| {
+                let (mark_file, local) = storage.locate(idx)?;
+                (mark_file == file).then_some((local, mark))
+            });
+            Sidecar::open(path)?.save_bookmarks(marks)?;
         }
+        Ok(())
     }
 
-    // Filter list handlers
-
-    fn on_filter_list_down(&mut self) {
-        let total = self.filters.len();
-        if self.filter_list_selected + 1 < total {
-            self.filter_list_selected += 1;
+    /// Session file and the key of the loaded file set's session in it, or
+    /// None if no loaded file has a path.
+    fn session_store(&self) -> Option<(PathBuf, Vec<PathBuf>)> {
+        let storage = self.storage.as_ref()?;
+        let paths: Vec<PathBuf> = (0..storage.file_count())
+            .filter_map(|file| storage.file_path(file).map(Path::to_path_buf))
+            .collect();
+        if paths.is_empty() {
+            return None;
         }
+        Some((self.session_path.clone()?, session::session_key(&paths)))
     }
 
-    fn on_filter_list_up(&mut self) {
-        self.filter_list_selected = self.filter_list_selected.saturating_sub(1);
+    /// Snapshot of the view state of the loaded file set.
+    fn capture_session(&self) -> Option<Session> {
+        let (_, paths) = self.session_store()?;
+        let storage = self.storage.as_ref()?;
+        let saved_line =
+            |idx| LineAnchor::capture(storage, idx).map(|anchor| SavedLine::from_anchor(&anchor));
+        Some(Session {
+            paths,
+            saved_at: chrono::Local::now(),
+            wrap: self.wrap_mode,
+            search: self
+                .search_stack
+                .iter()
+                .chain(&self.search_query)
+                .cloned()
+                .collect(),
+            cursor_row: self.selected_line.saturating_sub(self.scroll_offset),
+            cursor: self.selected_storage_index().and_then(saved_line),
+            filters: self
+                .filters
+                .iter()
+                .map(|(_, rule)| SavedFilter::from_rule(rule))
+                .collect(),
+            bookmarks: self
+                .bookmarks
+                .iter()
+                .filter_map(|(idx, mark)| {
+                    Some(SavedLine {
+                        note: mark.note.clone(),
+                        ..saved_line(idx)?
+                    })
+                })
+                .collect(),
+        })
     }
 
-    fn on_delete_selected_filter(&mut self) {
-        let includes = self.filters.includes().len();
-        if self.filter_list_selected < includes {
-            self.filters.remove_include(self.filter_list_selected);
-        } else {
-            self.filters
-                .remove_exclude(self.filter_list_selected - includes);
-        }
-        // Ensure selection stays valid after deletion
-        let total = self.filters.len();
-        if self.filter_list_selected >= total && total > 0 {
-            self.filter_list_selected = total - 1;
+    /// Put back filters, search, wrap mode, bookmarks and position from a
+    /// saved session. Returns true if the cursor was restored.
+    fn apply_session(&mut self, session: &Session) -> bool {
+        self.filters.clear();
+        for filter in &session.filters {
+            self.filters.add_rule(filter.to_rule());
         }
+        self.wrap_mode = session.wrap;
+        self.visual_cache.set_wrap_mode(self.wrap_mode);
         self.update_filtered_logs();
-        self.clear_search_on_refilter();
-        if self.filters.is_empty() {
-            self.mode = Mode::Normal;
-        }
-    }
 
-    fn on_close_filter_list(&mut self) {
-        self.mode = Mode::Normal;
-    }
-
-    // View option handlers
+        // Searching jumps to the first match, so the cursor is placed after
+        self.clear_search();
+        if let Some((query, outer)) = session.search.split_last() {
+            self.search_stack = outer.to_vec();
+            self.init_search_state(query.clone());
+        }
 
-    fn on_toggle_wrap(&mut self) {
-        self.wrap_mode = !self.wrap_mode;
-        self.visual_cache.set_wrap_mode(self.wrap_mode);
-        self.status_message = if self.wrap_mode {
-            "Wrap mode enabled".to_string()
-        } else {
-            "Wrap mode disabled".to_string()
+        let Some(storage) = self.storage.clone() else {
+            return false;
         };
+        self.bookmarks.clear();
+        for saved in &session.bookmarks {
+            if let Some(idx) = saved.anchor().and_then(|anchor| anchor.resolve(&storage)) {
+                let note = saved.note.clone();
+                self.bookmarks.insert(idx, Bookmark { note });
+            }
+        }
+        let cursor = session
+            .cursor
+            .as_ref()
+            .and_then(SavedLine::anchor)
+            .and_then(|anchor| anchor.resolve(&storage));
+        if let Some(idx) = cursor {
+            self.select_storage_index(idx);
+            self.scroll_offset = self.selected_line.saturating_sub(session.cursor_row);
+            self.clamp_scroll();
+        }
+        self.notify_info(format!(
+            "Session restored ({} filters, {} bookmarks)",
+            session.filters.len(),
+            self.bookmarks.len()
+        ));
+        cursor.is_some()
     }
 
-    /// Get the visual cache (for UI rendering).
-    pub fn visual_cache(&self) -> &VisualLineCache {
-        &self.visual_cache
+    /// Put back the saved session of the loaded file set, if there is one.
+    /// Returns true if the cursor was restored.
+    pub fn restore_session(&mut self) -> bool {
+        let Some((path, key)) = self.session_store() else {
+            return false;
+        };
+        match Sessions::load_from_path(&path).get(&key).cloned() {
+            Some(session) => self.apply_session(&session),
+            None => false,
+        }
     }
 
-    /// Get mutable visual cache.
-    pub fn visual_cache_mut(&mut self) -> &mut VisualLineCache {
-        &mut self.visual_cache
+    /// Store the view state of the loaded file set in the session file.
+    /// Returns false if there was nothing to store.
+    fn write_session(&self) -> io::Result<bool> {
+        let (Some((path, _)), Some(session)) = (self.session_store(), self.capture_session())
+        else {
+            return Ok(false);
+        };
+        let mut sessions = Sessions::load_from_path(&path);
+        sessions.put(session);
+        sessions.save_to_path(&path).map(|()| true)
     }
 
-    /// Get the total number of visual lines (approximation).
-    pub fn total_visual_lines(&self) -> usize {
-        // Return an estimate based on filtered count
-        self.filtered_len()
+    /// Store the session on quit or before other files are opened, unless
+    /// `:session clear` turned that off.
+    pub fn save_session(&self) -> io::Result<()> {
+        if self.session_autosave {
+            self.write_session()?;
+        }
+        Ok(())
     }
 
-    /// Get the number of lines in storage.
-    pub fn total_lines(&self) -> usize {
-        self.storage.as_ref().map(|s| s.len()).unwrap_or(0)
+    /// Open the :recent overlay.
+    pub fn show_recent(&mut self) {
+        self.recent_selected = 0;
+        self.mode = Mode::Recent;
     }
 
-    /// Clear search when filters change.
-    fn clear_search_on_refilter(&mut self) {
-        self.search_query = None;
-        self.search_state = None;
-    }
+    /// Update filtered indices based on current filters.
+    /// Uses byte-based matching for zero-allocation filtering.
+    #[tracing::instrument(level = "debug", skip_all)]
+    pub fn update_filtered_logs(&mut self) {
+        let started = Instant::now();
+        // Search matches and scope refer to the old filtered positions, and
+        // a running overlay job to the old filtered lines
+        self.clear_search_on_refilter();
+        self.overlay_job = None;
+        let cursor = self.selected_storage_index();
+        self.filtered_indices.clear();
 
-    /// Initialize search state with a query.
-    pub fn init_search_state(&mut self, query: String) {
-        if query.is_empty() {
-            self.clear_search();
+        let Some(storage) = &self.storage else {
             return;
-        }
-        let lower_query = query.to_lowercase();
-        let pattern_bytes = lower_query.bytes().collect::<Vec<u8>>();
-        let matcher = BMHMatcher::new(pattern_bytes);
-
-        // Compute total matches and first match position (before creating SearchState)
-        let (total, first_position) = self.compute_total_matches(&matcher);
-
-        // Create the search state with cached values
-        let state = SearchState {
-            query: lower_query,
-            matcher,
-            current_idx: 0,
-            current_position: first_position,
-            total_matches: total,
-            match_cache: LruCache::new(NonZeroUsize::new(100).unwrap()),
         };
-        self.search_state = Some(state);
-        self.search_query = Some(query);
 
-        // Navigate to first match if any
-        if total > 0 {
-            self.jump_to_match(0);
+        // Get viewport width for visual cache
+        let viewport_width = self.viewport_width.get();
+        if self.visual_cache.viewport_width() != viewport_width {
+            self.visual_cache.set_viewport_width(viewport_width);
+        }
+        if self.visual_cache.wrap_mode() != self.wraps() {
+            self.visual_cache.set_wrap_mode(self.wraps());
         }
-    }
 
-    /// Compute total matches and optionally first match position.
-    fn compute_total_matches(&self, matcher: &BMHMatcher) -> (usize, Option<MatchPosition>) {
-        let Some(storage) = &self.storage else {
-            return (0, None);
-        };
+        if let Some(throttle) = &mut self.throttle {
+            throttle.reset();
+        }
+        if let Some(context) = &mut self.context {
+            context.reset();
+        }
 
-        let mut total = 0;
-        let mut first_position = None;
+        let hides_lines = self.filters.hides_lines();
+        if hides_lines || (!self.filters.is_empty() && !storage.has_unindexed_blocks()) {
+            // Scans only patterns not seen before; dims stay lazy on on-demand indexes
+            self.filter_index.update(storage, &self.filters);
+        }
 
-        for (filtered_idx, &line_idx) in self.filtered_indices.iter().enumerate() {
-            let Some(line) = storage.get_line(line_idx) else {
-                continue;
-            };
-            let lower_bytes: Vec<u8> = line
-                .as_bytes()
+        if !hides_lines && self.throttle.is_none() && self.drill_down.is_empty() {
+            // Every line matches; skip reading content so on-demand indexes stay lazy
+            self.filtered_indices
+                .extend((0..storage.len()).filter(|idx| !self.hidden.contains(idx)));
+        } else {
+            let visible = self.filter_index.visible(&self.filters, storage.len());
+            let mut entry_time = None;
+            let visible = visible
                 .iter()
-                .map(|&b| b.to_ascii_lowercase())
-                .collect();
-            let matches = matcher.find_all(&lower_bytes);
-
-            for (start, end) in &matches {
-                if first_position.is_none() {
-                    first_position = Some(MatchPosition {
-                        filtered_idx,
-                        byte_offset: *start,
-                        match_len: end - start,
-                    });
+                .map(|idx| idx as usize)
+                .filter(|idx| !self.hidden.contains(idx))
+                .filter(|&idx| self.drill_down.admits(storage, idx, &mut entry_time));
+            match &mut self.throttle {
+                None => self.filtered_indices.extend(visible),
+                Some(throttle) => {
+                    // The throttle reads every shown line; build lazy indexes in parallel first
+                    storage.index_all();
+                    for idx in visible {
+                        if storage
+                            .get_line(idx)
+                            .is_some_and(|line| throttle.admit(idx, line.as_bytes()))
+                        {
+                            self.filtered_indices.push(idx);
+                        }
+                    }
+                }
+            }
+            if let Some(context) = &mut self.context {
+                let matches = std::mem::take(&mut self.filtered_indices);
+                let keep = |idx: usize| !self.hidden.contains(&idx);
+                for idx in matches {
+                    context.push_match(&mut self.filtered_indices, idx, keep);
                 }
-                total += 1;
+                context.fill_after(&mut self.filtered_indices, storage.len(), keep);
             }
         }
+        // Lines a replay has not reached are not shown yet
+        if let Some(replay) = &self.replay {
+            let shown = self
+                .filtered_indices
+                .partition_point(|&idx| idx < replay.end());
+            self.filtered_indices.truncate(shown);
+        }
+        tracing::debug!(
+            lines = storage.len(),
+            visible = self.filtered_indices.len(),
+            "filtered"
+        );
+        self.perf.filter = Some(started.elapsed());
 
-        (total, first_position)
-    }
-
-    /// Clear search state.
-    pub fn clear_search(&mut self) {
-        self.search_query = None;
-        self.search_state = None;
-    }
+        // Clear visual cache since filtered indices changed
+        self.visual_cache.clear();
 
-    /// Get matches for a specific line (with caching).
-    pub fn get_line_matches(&mut self, filtered_idx: usize) -> Vec<(usize, usize)> {
-        let Some(state) = &mut self.search_state else {
-            return Vec::new();
-        };
+        // Clear selection since filter indices are now invalid
+        self.selection.clear();
+        self.selection_bytes.set(SelectionBytes::default());
 
-        // Check cache first
-        if let Some(matches) = state.match_cache.get(&filtered_idx) {
-            return matches.clone();
+        // Keep the cursor on the same line (or the next one still shown)
+        if let Some(idx) = cursor {
+            self.select_storage_index(idx);
         }
+    }
 
-        // Get the line text
-        let Some(storage) = &self.storage else {
-            return Vec::new();
-        };
-        let Some(&line_idx) = self.filtered_indices.get(filtered_idx) else {
-            return Vec::new();
-        };
-        let Some(line) = storage.get_line(line_idx) else {
-            return Vec::new();
-        };
-
-        // Convert line to lowercase bytes for case-insensitive matching
-        let lower_bytes: Vec<u8> = line
-            .as_bytes()
+    /// Calculate visual line offsets for the current filtered view.
+    /// Uses on-demand calculation with caching.
+    pub fn calculate_visual_range(
+        &mut self,
+        start_idx: usize,
+        count: usize,
+    ) -> Vec<(usize, usize)> {
+        self.filtered_indices
             .iter()
-            .map(|&b| b.to_ascii_lowercase())
-            .collect();
-
-        // Find all matches
-        let matches = state.matcher.find_all(&lower_bytes);
+            .skip(start_idx)
+            .take(count)
+            .enumerate()
+            .map(|(i, &line_idx)| {
+                let visual_count = if let Some(storage) = &self.storage {
+                    if let Some(line) = storage.get_line(line_idx) {
+                        self.visual_cache
+                            .calculate_visual_lines(&line.as_str_lossy())
+                    } else {
+                        1
+                    }
+                } else {
+                    1
+                };
+                (line_idx, i * visual_count)
+            })
+            .collect()
+    }
 
-        // Cache the result (clone for return value, original goes into cache)
-        let result = matches.clone();
-        state.match_cache.put(filtered_idx, matches);
+    /// Get the visual line offset for a specific filtered line index.
+    pub fn selected_visual_line(&self) -> usize {
+        self.scroll_offset + self.selected_line
+    }
 
-        result
+    /// Get the current scroll position in visual lines.
+    pub fn scroll_visual_line(&self) -> usize {
+        self.scroll_offset
     }
 
-    /// Get total match count across all filtered lines.
-    /// Returns cached value for O(1) performance.
-    pub fn total_matches(&self) -> usize {
-        self.search_state
-            .as_ref()
-            .map(|s| s.total_matches)
-            .unwrap_or(0)
+    /// Find the entry index by visual line number.
+    pub fn find_entry_by_visual_line(&self, target_visual: usize) -> usize {
+        // Simplified: just return the target or clamped
+        target_visual.min(self.filtered_len().saturating_sub(1))
     }
 
-    /// Get current match display string (e.g., "3/42").
-    pub fn current_match_display(&self) -> Option<String> {
-        let state = self.search_state.as_ref()?;
-        if state.total_matches == 0 {
-            return None;
+    /// Handle keyboard input by translating to messages and processing them.
+    pub fn handle_key(&mut self, key: crossterm::event::KeyEvent) {
+        use crate::key_bindings::{translate, translate_bracket, translate_g};
+        // The summary popup goes away on the first key, which still takes effect
+        self.dismiss_load_summary();
+        let after_g = self.pending_g.take();
+        let msg = match self.pending_bracket.take() {
+            Some(prefix) => translate_bracket(prefix, key),
+            None => match after_g.zip(translate_g(key)) {
+                // `g` already jumped to the top; leave the tab where it was
+                Some(((line, scroll), msg)) => {
+                    self.selected_line = line;
+                    self.scroll_offset = scroll;
+                    Some(msg)
+                }
+                None => translate(key, self.mode),
+            },
+        };
+        if msg == Some(Msg::GoToTop) && key.code == crossterm::event::KeyCode::Char('g') {
+            self.pending_g = Some((self.selected_line, self.scroll_offset));
+        }
+        if let Some(msg) = msg {
+            self.process_message(msg);
+            self.check_tutor();
         }
-        Some(format!("{}/{}", state.current_idx + 1, state.total_matches))
     }
 
-    /// Navigate to next match (with wrap-around).
-    pub fn next_match(&mut self) {
-        let total = self.total_matches();
-        if total == 0 {
-            return;
+    /// Handle a mouse event: wheel, click or drag.
+    pub fn handle_mouse(&mut self, event: crossterm::event::MouseEvent) {
+        self.dismiss_load_summary();
+        if let Some(msg) = crate::key_bindings::translate_mouse(event, self.mode) {
+            self.process_message(msg);
+            self.check_tutor();
         }
+    }
 
-        let next_idx = if let Some(state) = &self.search_state {
-            (state.current_idx + 1) % total
-        } else {
-            0
+    /// Move the tutorial on once the goal of its current step is reached.
+    fn check_tutor(&mut self) {
+        let Some(mut tutor) = self.tutor.take() else {
+            return;
         };
-
-        self.jump_to_match(next_idx);
+        if let Some(title) = tutor.advance(self) {
+            self.notify_info(format!("✓ {}", title));
+        }
+        self.tutor = Some(tutor);
     }
 
-    /// Navigate to previous match (with wrap-around).
-    pub fn prev_match(&mut self) {
-        let total = self.total_matches();
-        if total == 0 {
-            return;
+    /// Process a message and update application state accordingly.
+    fn process_message(&mut self, msg: Msg) {
+        crash::record_action(crash::variant_name(&msg));
+        match msg {
+            // Navigation
+            Msg::ScrollDown => self.on_scroll_down(),
+            Msg::ScrollUp => self.on_scroll_up(),
+            Msg::ScrollRight => self.on_scroll_right(),
+            Msg::ScrollLeft => self.on_scroll_left(),
+            Msg::GoToBottom => {
+                self.on_go_to_bottom();
+                self.return_to_live();
+            }
+            Msg::JumpToLastRead => self.on_jump_to_last_read(),
+            Msg::GoToTop => self.on_go_to_top(),
+
+            // Command mode
+            Msg::EnterCommand => self.on_enter_command(),
+            Msg::CancelCommand => self.on_cancel_command(),
+            Msg::SubmitCommand => self.on_submit_command(),
+            Msg::CommandTypeChar(c) => self.on_command_type_char(c),
+            Msg::CommandBackspace => self.on_command_backspace(),
+            Msg::CommandComplete => self.on_command_complete(),
+            Msg::CommandHistoryOlder => self.on_command_history(true),
+            Msg::CommandHistoryNewer => self.on_command_history(false),
+
+            // Search
+            Msg::EnterSearch => self.on_enter_search(),
+            Msg::CancelSearch => self.on_cancel_search(),
+            Msg::SubmitSearch => self.on_submit_search(),
+            Msg::SearchTypeChar(c) => self.on_search_type_char(c),
+            Msg::SearchBackspace => self.on_search_backspace(),
+            Msg::NextMatch => self.next_match(),
+            Msg::PrevMatch => self.prev_match(),
+            Msg::ClearSearch => self.on_clear_search(),
+            Msg::PopSearch => self.on_pop_search(),
+
+            // Selection
+            Msg::ToggleSelection => self.on_toggle_selection(),
+            Msg::SelectMatches => self.on_select_matches(),
+            Msg::ToggleLevel(level) => self.on_toggle_level(level),
+            Msg::YankSelection => self.on_yank(),
+            Msg::ClearSelection => self.on_clear_selection(),
+
+            // Bookmarks
+            Msg::ToggleBookmark => self.on_toggle_bookmark(),
+            Msg::OpenBookmarkList => self.mode = self.open_bookmark_list(),
+            Msg::BookmarkListDown => self.on_bookmark_list_down(),
+            Msg::BookmarkListUp => self.on_bookmark_list_up(),
+            Msg::RemoveBookmark => self.on_remove_bookmark(),
+            Msg::JumpToBookmark => self.on_jump_to_bookmark(),
+            Msg::CloseBookmarkList => self.mode = Mode::Normal,
+
+            // Error list
+            Msg::Bracket(prefix) => self.pending_bracket = Some(prefix),
+            Msg::NextError => self.on_step_error(true),
+            Msg::PrevError => self.on_step_error(false),
+            Msg::ErrorListDown => {
+                let len = self.quickfix.as_ref().map_or(0, Vec::len);
+                if self.quickfix_selected + 1 < len {
+                    self.quickfix_selected += 1;
+                }
+            }
+            Msg::ErrorListUp => self.quickfix_selected = self.quickfix_selected.saturating_sub(1),
+            Msg::JumpToError => self.on_jump_to_error(),
+            Msg::CloseErrorList => self.mode = Mode::Normal,
+
+            // Tabs
+            Msg::NextTab => self.step_tab(true),
+            Msg::PrevTab => self.step_tab(false),
+
+            // Pod list
+            Msg::PodListDown => {
+                if self.pod_list_selected + 1 < self.pods.len() {
+                    self.pod_list_selected += 1;
+                }
+            }
+            Msg::PodListUp => self.pod_list_selected = self.pod_list_selected.saturating_sub(1),
+            Msg::TogglePodPause => self.on_toggle_pod_pause(),
+            Msg::TogglePodMute => self.on_toggle_pod_mute(),
+            Msg::ClosePodList => self.mode = Mode::Normal,
+
+            // Pins
+            Msg::TogglePin => self.on_toggle_pin(),
+            Msg::PinListDown => self.on_pin_list_down(),
+            Msg::PinListUp => self.on_pin_list_up(),
+            Msg::MovePinDown => self.on_move_pin(false),
+            Msg::MovePinUp => self.on_move_pin(true),
+            Msg::RemovePin => self.on_remove_pin(),
+            Msg::JumpToPin => self.on_jump_to_pin(),
+            Msg::ClosePinList => self.mode = Mode::Normal,
+
+            // SQL query results
+            Msg::SqlDown => self.on_sql_move(true),
+            Msg::SqlUp => self.on_sql_move(false),
+            Msg::JumpToSqlRow => self.on_jump_to_sql_row(),
+            Msg::FilterSqlRow(kind) => self.on_filter_sql_row(kind),
+            Msg::CloseSql => {
+                self.sql_view = None;
+                self.mode = Mode::Normal;
+            }
+
+            // Stats overlay
+            Msg::StatsDown => self.on_stats_move(true),
+            Msg::StatsUp => self.on_stats_move(false),
+            Msg::DrillDown => self.on_drill_down(),
+            Msg::CloseStats => {
+                self.stats_view = None;
+                self.mode = Mode::Normal;
+            }
+
+            // Exception groups
+            Msg::ExceptionsDown => self.on_exceptions_move(true),
+            Msg::ExceptionsUp => self.on_exceptions_move(false),
+            Msg::JumpToException => self.on_jump_to_exception(),
+            Msg::CloseExceptions => {
+                self.exceptions_view = None;
+                self.mode = Mode::Normal;
+            }
+
+            // Gap histogram
+            Msg::CloseGaps => {
+                self.gaps_view = None;
+                self.mode = Mode::Normal;
+            }
+
+            // Message log
+            Msg::MessagesDown => {
+                let last = self.notifications.log().len().saturating_sub(1);
+                self.message_list_selected = (self.message_list_selected + 1).min(last);
+            }
+            Msg::MessagesUp => {
+                self.message_list_selected = self.message_list_selected.saturating_sub(1);
+            }
+            Msg::CloseMessages => self.mode = Mode::Normal,
+
+            // Help overlay
+            Msg::OpenHelp => {
+                self.help = help::sections(self.aliases());
+                self.help_scroll = 0;
+                self.mode = Mode::Help;
+            }
+            Msg::HelpDown => self.scroll_help(1, true),
+            Msg::HelpUp => self.scroll_help(1, false),
+            Msg::HelpPageDown => self.scroll_help(self.viewport_height.get(), true),
+            Msg::HelpPageUp => self.scroll_help(self.viewport_height.get(), false),
+            Msg::CloseHelp => {
+                self.help.clear();
+                self.mode = Mode::Normal;
+            }
+
+            // Detail pane
+            Msg::OpenDetail => self.on_open_detail(),
+            Msg::DetailDown => self.detail.iter_mut().for_each(DetailView::cursor_down),
+            Msg::DetailUp => self.detail.iter_mut().for_each(DetailView::cursor_up),
+            Msg::ToggleFold => {
+                if let Some(detail) = &mut self.detail {
+                    detail.toggle_fold();
+                }
+            }
+            Msg::ExpandAll => self.set_detail_expanded(true),
+            Msg::CollapseAll => self.set_detail_expanded(false),
+            Msg::YankField => self.on_yank_field(),
+            Msg::FilterField(kind) => self.on_filter_field(kind),
+            Msg::CloseDetail => {
+                self.detail = None;
+                self.mode = Mode::Normal;
+            }
+
+            // Hex view
+            Msg::OpenHex => self.mode = self.open_hex(),
+            Msg::HexDown => self.hex.iter_mut().for_each(HexView::scroll_down),
+            Msg::HexUp => self.hex.iter_mut().for_each(HexView::scroll_up),
+            Msg::HexTop => self.hex.iter_mut().for_each(|hex| hex.scroll = 0),
+            Msg::HexBottom => self.hex.iter_mut().for_each(HexView::scroll_to_bottom),
+            Msg::CloseHex => {
+                self.hex = None;
+                self.mode = Mode::Normal;
+            }
+
+            // Hidden lines
+            Msg::HideLines => self.on_hide_lines(),
+            Msg::ProposeFilter(kind) => self.on_propose_filter(kind),
+            Msg::RestoreHidden => self.on_restore_hidden(),
+
+            // Filter list
+            Msg::FilterListDown => self.on_filter_list_down(),
+            Msg::FilterListUp => self.on_filter_list_up(),
+            Msg::DeleteSelectedFilter => self.on_delete_selected_filter(),
+            Msg::ToggleSelectedFilter => self.on_toggle_selected_filter(),
+            Msg::CloseFilterList => self.on_close_filter_list(),
+
+            // Recent files
+            Msg::RecentDown => self.on_recent_down(),
+            Msg::RecentUp => self.on_recent_up(),
+            Msg::OpenRecent => self.on_open_recent(),
+            Msg::CloseRecent => self.on_close_recent(),
+
+            // Prompt overlay
+            Msg::PromptDown => self.prompt.iter_mut().for_each(Prompt::move_down),
+            Msg::PromptUp => self.prompt.iter_mut().for_each(Prompt::move_up),
+            Msg::PromptAccept => {
+                let key = self.prompt.as_ref().and_then(Prompt::selected_key);
+                self.answer_prompt(key);
+            }
+            Msg::PromptKey(key) => {
+                if self
+                    .prompt
+                    .as_ref()
+                    .is_some_and(|prompt| prompt.has_key(key))
+                {
+                    self.answer_prompt(Some(key));
+                }
+            }
+            Msg::PromptCancel => self.answer_prompt(None),
+
+            // Mouse
+            Msg::WheelDown => (0..WHEEL_LINES).for_each(|_| self.on_scroll_down()),
+            Msg::WheelUp => (0..WHEEL_LINES).for_each(|_| self.on_scroll_up()),
+            Msg::Click { column, row } => self.on_click(column, row),
+            Msg::Drag { column, row } => self.on_drag(column, row),
+
+            // View options
+            Msg::ToggleWrap => self.on_toggle_wrap(),
+            Msg::ToggleCase => self.set_case_mode(self.case_mode.next()),
+            Msg::ToggleFollow => self.on_toggle_follow(),
+            Msg::TogglePerf => self.show_perf = !self.show_perf,
+
+            // Application
+            Msg::Quit => self.should_quit = true,
+            // Keys that don't map to an action in the current mode (e.g., unmapped keys in Normal mode)
+            Msg::NoOp => {}
         }
+    }
 
-        let prev_idx = if let Some(state) = &self.search_state {
-            if state.current_idx == 0 {
-                total - 1
-            } else {
-                state.current_idx - 1
+    // Navigation handlers
+
+    fn on_scroll_down(&mut self) {
+        self.status_message.clear();
+        if self.selected_line < self.filtered_len().saturating_sub(1) {
+            let old_line = self.selected_line;
+            self.selected_line += 1;
+            // Extend selection if active
+            if self.selection.is_active() {
+                let direction = if self.selected_line > old_line {
+                    Direction::Down
+                } else {
+                    Direction::Up
+                };
+                self.selection.extend(self.selected_line, direction);
             }
-        } else {
-            0
-        };
+        }
+        self.clamp_scroll();
+    }
 
-        self.jump_to_match(prev_idx);
+    fn on_scroll_up(&mut self) {
+        self.status_message.clear();
+        self.extend_tail_at_cursor();
+        let old_line = self.selected_line;
+        self.selected_line = self.selected_line.saturating_sub(1);
+        // Extend selection if active
+        if self.selection.is_active() {
+            let direction = if self.selected_line < old_line {
+                Direction::Up
+            } else {
+                Direction::Down
+            };
+            self.selection.extend(self.selected_line, direction);
+        }
+        self.clamp_scroll();
     }
 
-    /// Jump to a specific match by index.
-    fn jump_to_match(&mut self, match_idx: usize) {
-        let Some(state) = &mut self.search_state else {
+    /// When the cursor is on the first indexed line of a tail-only file,
+    /// index more of the file above it, keeping the cursor on the same line.
+    fn extend_tail_at_cursor(&mut self) {
+        let Some(idx) = self.selected_storage_index() else {
             return;
         };
+        let prev_idx = self
+            .selected_line
+            .checked_sub(1)
+            .and_then(|prev| self.filtered_indices.get(prev).copied());
+        self.cancel_search_job();
+        let Some(storage) = self.storage.as_mut().and_then(Arc::get_mut) else {
+            return;
+        };
+        let Some(file) = storage.line_file(idx) else {
+            return;
+        };
+        if !storage.is_partial(file) || prev_idx.and_then(|p| storage.line_file(p)) == Some(file) {
+            return;
+        }
 
-        if state.total_matches == 0 || match_idx >= state.total_matches {
+        let insert_at = storage.file_start(file).unwrap_or(idx);
+        let added = storage.extend_tail(file, TAIL_EXTEND_LINES);
+        if added == 0 {
             return;
         }
 
-        // Update current index
-        state.current_idx = match_idx;
+        let cursor_row = self.selected_line.saturating_sub(self.scroll_offset);
+        self.bookmarks.shift_from(insert_at, added);
+        self.pins.shift_from(insert_at, added);
+        for entry in self.quickfix.iter_mut().flatten() {
+            if entry.line >= insert_at {
+                entry.line += added;
+            }
+        }
+        let moved = self.hidden.split_off(&insert_at);
+        self.hidden.extend(moved.into_iter().map(|idx| idx + added));
+        let query = self.search_query.clone();
+        let stack = self.search_stack.clone();
+        self.update_filtered_logs();
+        self.selected_line = self
+            .filtered_indices
+            .binary_search(&(idx + added))
+            .unwrap_or_else(|pos| pos);
+        self.scroll_offset = self.selected_line.saturating_sub(cursor_row);
+        if let Some(query) = query {
+            self.search_stack = stack;
+            self.init_search_state(query);
+        }
+        self.notify_info(format!("Loaded {} earlier lines", added));
+    }
 
-        // Find the position of this match
-        if let Some(position) = self.get_match_position(match_idx) {
-            // Cache the position
-            if let Some(ref mut state) = self.search_state {
-                state.current_position = Some(position);
+    fn on_scroll_right(&mut self) {
+        self.horizontal_scroll = self.horizontal_scroll.saturating_add(4);
+    }
+
+    fn on_scroll_left(&mut self) {
+        self.horizontal_scroll = self.horizontal_scroll.saturating_sub(4);
+    }
+
+    fn on_go_to_bottom(&mut self) {
+        self.selected_line = self.filtered_len().saturating_sub(1);
+        self.clamp_scroll();
+    }
+
+    /// Place the "last read" marker when the view comes back to the live
+    /// end, if lines arrived while it was away.
+    fn return_to_live(&mut self) {
+        if let Some(first_unread) = self.away_from.take() {
+            if self.total_lines() > first_unread {
+                self.last_read = Some(first_unread);
             }
+        }
+    }
 
-            // Update vertical position
-            self.selected_line = position.filtered_idx;
-            self.clamp_scroll();
+    /// Filtered position of the first line below the "last read" marker,
+    /// while lines on both sides of it are shown.
+    pub fn last_read_row(&self) -> Option<usize> {
+        let first_unread = self.last_read?;
+        let row = self
+            .filtered_indices
+            .partition_point(|&idx| idx < first_unread);
+        (row > 0 && row < self.filtered_indices.len()).then_some(row)
+    }
 
-            // Horizontal auto-scroll
-            let Some(storage) = &self.storage else { return };
-            let Some(&line_idx) = self.filtered_indices.get(position.filtered_idx) else {
-                return;
-            };
-            let Some(line) = storage.get_line(line_idx) else {
-                return;
-            };
-            let line_text = line.as_str_lossy();
+    /// Whether the filtered line at `idx` starts a new group of matches and
+    /// their context, drawn after a separator row.
+    pub fn context_break(&self, idx: usize) -> bool {
+        self.context.is_some()
+            && idx > 0
+            && self
+                .filtered_indices
+                .get(idx)
+                .is_some_and(|&line| self.filtered_indices[idx - 1] + 1 != line)
+    }
 
-            // Calculate character offset from byte offset
-            let match_char_pos = byte_to_char_offset(&line_text, position.byte_offset);
-            // Calculate match length in characters (not bytes) for consistent scroll math
-            let match_text =
-                &line_text[position.byte_offset..position.byte_offset + position.match_len];
-            let match_char_len = match_text.chars().count();
+    /// Rows taken by the "last read" marker and context separators when the
+    /// view shows the filtered lines `top..=bottom`.
+    fn marker_rows(&self, top: usize, bottom: usize) -> usize {
+        let last_read = self
+            .last_read_row()
+            .is_some_and(|row| top <= row && row <= bottom);
+        let breaks = (top + 1..=bottom)
+            .filter(|&idx| self.context_break(idx))
+            .count();
+        usize::from(last_read) + breaks
+    }
 
-            let viewport_width = self.viewport_width.get();
-            let margin = 10;
+    fn on_jump_to_last_read(&mut self) {
+        match self.last_read_row() {
+            Some(row) => {
+                self.selected_line = row;
+                self.clamp_scroll();
+                self.notify_info("First line after the last read marker");
+            }
+            None => self.notify_warn("No lines arrived while away"),
+        }
+    }
 
-            // Only adjust horizontal scroll if match is outside viewport
-            if match_char_pos < self.horizontal_scroll {
-                // Match is left of viewport - scroll to show it with margin
-                self.horizontal_scroll = match_char_pos.saturating_sub(margin);
-            } else if match_char_pos + match_char_len
-                > self.horizontal_scroll + viewport_width.saturating_sub(margin)
-            {
-                // Match is right of viewport - scroll to show it
-                self.horizontal_scroll =
-                    (match_char_pos + match_char_len + margin).saturating_sub(viewport_width);
+    fn on_go_to_top(&mut self) {
+        self.selected_line = 0;
+        self.scroll_offset = 0;
+    }
+
+    fn clamp_scroll(&mut self) {
+        if self.filtered_indices.is_empty() {
+            return;
+        }
+
+        self.selected_line = self
+            .selected_line
+            .min(self.filtered_len().saturating_sub(1));
+
+        let viewport_height = self.viewport_height.get();
+
+        let effective_height = if self.wraps() {
+            (viewport_height / 2).max(1)
+        } else {
+            viewport_height
+        };
+        // Marker rows between the top and the cursor take rows of their own
+        let rows =
+            |top: usize| self.selected_line - top + 1 + self.marker_rows(top, self.selected_line);
+
+        if self.selected_line < self.scroll_offset {
+            self.scroll_offset = self.selected_line;
+        } else if self.selected_line >= self.scroll_offset + effective_height
+            || rows(self.scroll_offset) > effective_height
+        {
+            let mut top = self
+                .selected_line
+                .saturating_sub(effective_height.saturating_sub(1));
+            while top < self.selected_line && rows(top) > effective_height {
+                top += 1;
             }
+            self.scroll_offset = top;
         }
     }
 
-    /// Get the position of a match by its global index.
-    fn get_match_position(&self, match_idx: usize) -> Option<MatchPosition> {
-        let state = self.search_state.as_ref()?;
-        let storage = self.storage.as_ref()?;
+    // Command mode handlers
+
+    fn on_enter_command(&mut self) {
+        self.mode = Mode::Command;
+    }
+
+    fn on_cancel_command(&mut self) {
+        self.mode = Mode::Normal;
+        self.input_buffer.clear();
+        self.command_history.reset();
+    }
+
+    fn on_submit_command(&mut self) {
+        if let Err(e) = self.command_history.record(&self.input_buffer) {
+            tracing::warn!("Error saving command history: {}", e);
+        }
+        self.mode = self.execute_command();
+        self.input_buffer.clear();
+    }
+
+    fn on_command_type_char(&mut self, c: char) {
+        self.completion_index = None;
+        self.completion_prefix.clear();
+        self.command_history.reset();
+        self.input_buffer.push(c);
+    }
+
+    fn on_command_backspace(&mut self) {
+        self.completion_index = None;
+        self.completion_prefix.clear();
+        self.command_history.reset();
+        self.input_buffer.pop();
+    }
+
+    /// Replace the command line with an older (`older`) or newer command
+    /// from the history that starts with what was typed.
+    fn on_command_history(&mut self, older: bool) {
+        self.completion_index = None;
+        self.completion_prefix.clear();
+        let entry = if older {
+            self.command_history.older(&self.input_buffer)
+        } else {
+            self.command_history.newer()
+        };
+        if let Some(entry) = entry {
+            self.input_buffer = entry.to_string();
+        }
+    }
+
+    fn on_command_complete(&mut self) {
+        self.apply_completion();
+    }
+
+    /// Run the commands in the input buffer in order.
+    ///
+    /// Each command's status is collected into the status bar; the mode
+    /// opened by the last command that opens one is returned.
+    fn execute_command(&mut self) -> Mode {
+        let results = command::parse_chain(&self.input_buffer, self.aliases());
+        let mut statuses = Vec::new();
+        let mut severity = Severity::Info;
+        let mut mode = Mode::Normal;
+
+        for result in results {
+            match result.effect {
+                Some(effect) => {
+                    self.notify_info(result.status);
+                    match self.apply_command_effect(effect) {
+                        Mode::Normal => {}
+                        opened => mode = opened,
+                    }
+                }
+                // Only usage errors and unknown commands come without an effect
+                None => self.notify_error(result.status),
+            }
+            if !self.status_message.is_empty() {
+                severity = severity.max(self.status_kind);
+                statuses.push(std::mem::take(&mut self.status_message));
+            }
+            if self.should_quit {
+                break;
+            }
+        }
+
+        self.set_status(statuses.join("; "), severity);
+        mode
+    }
+
+    fn apply_command_effect(&mut self, effect: CommandEffect) -> Mode {
+        crash::record_action(format!("command {}", crash::variant_name(&effect)));
+        match effect {
+            CommandEffect::Quit => {
+                self.should_quit = true;
+            }
+            CommandEffect::AddFilter {
+                kind,
+                pattern,
+                file,
+            } => {
+                self.filters
+                    .add_rule(FilterRule::new(pattern, kind).with_file(file));
+                self.update_filtered_logs();
+            }
+            CommandEffect::LoadFilterFile { path } => match std::fs::read_to_string(&path) {
+                Ok(text) => {
+                    let rules = parse_filter_file(&text);
+                    let count = rules.len();
+                    for (kind, pattern) in rules {
+                        self.filters.add(kind, pattern);
+                    }
+                    self.update_filtered_logs();
+                    self.notify_info(format!("Added {} filters from {}", count, path));
+                }
+                Err(e) => self.notify_error(format!("Error reading {}: {}", path, e)),
+            },
+            CommandEffect::ClearFilters => {
+                self.filters.clear();
+                self.drill_down = DrillDown::default();
+                self.update_filtered_logs();
+            }
+            CommandEffect::WriteFilteredLogs {
+                filename,
+                verbatim,
+                context,
+            } => {
+                let verbatim = verbatim || self.config.as_ref().is_some_and(|c| c.export.verbatim);
+                if Path::new(&filename).exists() {
+                    let prompt = Prompt::confirm(
+                        " Overwrite? ",
+                        vec![format!("{} already exists.", filename)],
+                        "Overwrite it with the lines in view",
+                    );
+                    self.open_prompt(
+                        prompt,
+                        PromptAction::Overwrite {
+                            filename,
+                            verbatim,
+                            context,
+                        },
+                    );
+                    return Mode::Prompt;
+                }
+                self.write_filtered_logs(&filename, verbatim, context);
+            }
+            CommandEffect::ListFilters => {
+                self.filter_list_selected = 0;
+                return Mode::FilterList;
+            }
+            CommandEffect::SetNote { note } => {
+                if let Some(idx) = self.selected_storage_index() {
+                    self.notify_info(if note.is_empty() {
+                        "Note cleared".to_string()
+                    } else {
+                        "Note added".to_string()
+                    });
+                    self.bookmarks.set_note(idx, note);
+                }
+            }
+            CommandEffect::Sql { query } => return self.run_sql(query),
+            CommandEffect::ShowStats => {
+                let Some((source, indices)) = self.overlay_input() else {
+                    self.notify_warn("No logs loaded");
+                    return Mode::Normal;
+                };
+                let lines = indices.len();
+                return self.run_overlay("stats".to_string(), lines, move |ctx| {
+                    if let Some(rows) = stats::compute_in_job(&*source, &indices, &ctx) {
+                        ctx.send(Overlay::Stats(rows));
+                    }
+                });
+            }
+            CommandEffect::ShowGaps => return self.show_gaps(),
+            CommandEffect::ListExceptions => {
+                let Some((source, indices)) = self.overlay_input() else {
+                    self.notify_warn("No logs loaded");
+                    return Mode::Normal;
+                };
+                let lines = indices.len();
+                return self.run_overlay("exceptions".to_string(), lines, move |ctx| {
+                    if let Some(groups) = exceptions::scan_in_job(&*source, &indices, &ctx) {
+                        ctx.send(Overlay::Exceptions(groups));
+                    }
+                });
+            }
+            CommandEffect::SetLevels { levels } => {
+                self.set_levels(levels);
+            }
+            CommandEffect::ClearDrillDown => {
+                self.drill_down = DrillDown::default();
+                self.update_filtered_logs();
+                self.notify_info("Stats limits cleared");
+            }
+            CommandEffect::SaveSession => {
+                self.session_autosave = true;
+                match self.write_session() {
+                    Ok(true) => self.notify_info("Session saved"),
+                    Ok(false) => self.notify_warn("No files to save a session for"),
+                    Err(e) => self.notify_error(format!("Error saving session: {}", e)),
+                }
+            }
+            CommandEffect::LoadSession => {
+                let saved = self
+                    .session_store()
+                    .and_then(|(path, key)| Sessions::load_from_path(&path).get(&key).cloned());
+                match saved {
+                    Some(session) => {
+                        self.apply_session(&session);
+                    }
+                    None => self.notify_warn("No saved session for these files"),
+                }
+            }
+            CommandEffect::ClearSession => {
+                self.session_autosave = false;
+                let removed = self.session_store().and_then(|(path, key)| {
+                    let mut sessions = Sessions::load_from_path(&path);
+                    sessions.remove(&key).then(|| sessions.save_to_path(&path))
+                });
+                match removed {
+                    Some(Ok(())) => self.notify_info("Session cleared; not saved on quit"),
+                    Some(Err(e)) => self.notify_error(format!("Error saving session: {}", e)),
+                    None => self.notify_warn("No saved session for these files"),
+                }
+            }
+            CommandEffect::ShowHex => return self.open_hex(),
+            CommandEffect::ListPods => {
+                if self.pods.is_empty() {
+                    self.notify_warn("No pods seen (pipe in `kubectl logs -f --prefix`)");
+                } else {
+                    self.pod_list_selected = self.pod_list_selected.min(self.pods.len() - 1);
+                    return Mode::Pods;
+                }
+            }
+            CommandEffect::ListErrors => {
+                let cursor = self.selected_storage_index().unwrap_or(0);
+                let entries = self.scan_quickfix();
+                if entries.is_empty() {
+                    self.notify_warn("No compiler or test errors found");
+                } else {
+                    self.quickfix_selected = entries
+                        .iter()
+                        .position(|entry| entry.line >= cursor)
+                        .unwrap_or(entries.len() - 1);
+                    return Mode::Errors;
+                }
+            }
+            CommandEffect::ListMessages => {
+                self.update_notifications();
+                let count = self.notifications.log().len();
+                if count == 0 {
+                    self.notify_warn("No messages yet");
+                } else {
+                    self.message_list_selected = count - 1;
+                    return Mode::Messages;
+                }
+            }
+            CommandEffect::ListPins => {
+                if self.pins.is_empty() {
+                    self.notify_warn("No pinned lines (p pins the current line)");
+                } else {
+                    self.pin_list_selected = 0;
+                    return Mode::Pins;
+                }
+            }
+            CommandEffect::ShowRecent => {
+                if self.recent.is_empty() {
+                    self.notify_warn("No recent files");
+                } else {
+                    self.recent_selected = 0;
+                    return Mode::Recent;
+                }
+            }
+            CommandEffect::SetTheme { name: None } => {
+                self.notify_info(format!(
+                    "Theme: {} (available: {})",
+                    self.theme.name,
+                    self.theme_names().join(", ")
+                ));
+            }
+            CommandEffect::SetTheme { name: Some(name) } => {
+                if self.set_theme(&name) {
+                    self.notify_info(format!("Theme: {}", self.theme.name));
+                } else {
+                    self.notify_error(format!(
+                        "Unknown theme: {} (available: {})",
+                        name,
+                        self.theme_names().join(", ")
+                    ));
+                }
+            }
+            CommandEffect::SetGutter { gutter } => {
+                self.gutter = gutter.unwrap_or_else(|| self.gutter.next());
+                self.notify_info(format!("Gutter: {}", self.gutter.name()));
+            }
+            CommandEffect::SetClipboard { backend } => {
+                self.set_clipboard(backend);
+            }
+            CommandEffect::SetRuler { column } => {
+                self.set_ruler(column);
+            }
+            CommandEffect::ToggleRuler => {
+                let column = match self.ruler_column {
+                    Some(_) => None,
+                    None => Some(
+                        self.config
+                            .as_ref()
+                            .and_then(|c| c.view.ruler_column)
+                            .unwrap_or(DEFAULT_RULER_COLUMN),
+                    ),
+                };
+                self.set_ruler(column);
+            }
+            CommandEffect::SetThrottle { limit } => {
+                self.set_throttle(limit);
+            }
+            CommandEffect::ToggleThrottle => {
+                let limit = match self.throttle {
+                    Some(_) => None,
+                    None => Some(DEFAULT_THROTTLE_LIMIT),
+                };
+                self.set_throttle(limit);
+            }
+            CommandEffect::SetContext { lines } => {
+                self.set_context(lines);
+            }
+            CommandEffect::SetCaseMode { mode } => self.set_case_mode(mode),
+            CommandEffect::SetWholeWord { whole_word } => self.set_whole_word(whole_word),
+            CommandEffect::SetColumns { columns } => {
+                self.set_columns(columns);
+            }
+            CommandEffect::ToggleColumns => {
+                let columns = match self.columns {
+                    Some(_) => None,
+                    None => Some(Columns::default()),
+                };
+                self.set_columns(columns);
+            }
+            CommandEffect::StartTutor => {
+                self.tutor = Some(Tutor::new());
+                self.notify_info("Tutorial started - follow the steps below the logs");
+            }
+            CommandEffect::SkipTutorStep => match &mut self.tutor {
+                Some(tutor) => {
+                    tutor.skip();
+                    let title = tutor.current().title;
+                    self.notify_info(format!("Tutorial: {}", title));
+                }
+                None => self.notify_warn("No tutorial running - :tutor starts it"),
+            },
+            CommandEffect::CloseTutor => {
+                self.tutor = None;
+                self.notify_info("Tutorial closed (:tutor starts it again)");
+            }
+            CommandEffect::ToggleContext => {
+                let lines = match self.context {
+                    Some(_) => None,
+                    None => Some(DEFAULT_CONTEXT_LINES),
+                };
+                self.set_context(lines);
+            }
+            CommandEffect::OpenTab { paths } => match paths.iter().find(|path| !path.is_file()) {
+                Some(missing) => {
+                    self.notify_error(format!("No such file: {}", missing.display()));
+                }
+                None => self.open_tab(paths),
+            },
+            CommandEffect::CloseTab => self.close_tab(),
+            CommandEffect::StepTab { forward } => self.step_tab(forward),
+            CommandEffect::StartReplay { speed, from } => self.start_replay(speed, from),
+            CommandEffect::PauseReplay => match &mut self.replay {
+                Some(replay) => {
+                    if replay.toggle_pause(Instant::now()) {
+                        self.notify_info("Replay paused (:replay pause resumes)");
+                    } else {
+                        let speed = replay.speed();
+                        self.notify_info(format!("Replaying at {}x", speed));
+                    }
+                }
+                None => self.notify_warn("No replay running"),
+            },
+            CommandEffect::StopReplay => self.stop_replay("Replay stopped"),
+            CommandEffect::AddWatch { expr } => self.add_watch(&expr),
+            CommandEffect::RemoveWatch { target } => self.remove_watch(target.as_deref()),
+            CommandEffect::ShowTime => {
+                self.show_selection_time();
+            }
+            CommandEffect::SetSearchRange { from, to } => {
+                match self.resolve_search_range(from, to) {
+                    Ok(range) => self.set_search_range(Some(range)),
+                    Err(message) => self.notify_error(message),
+                }
+            }
+            CommandEffect::ClearSearchRange => {
+                self.set_search_range(None);
+            }
+            CommandEffect::ExportTable { fields, filename } => {
+                self.export_table(&fields, filename.as_deref());
+            }
+            CommandEffect::ListBookmarks => return self.open_bookmark_list(),
+            CommandEffect::ClearBookmarks => {
+                let count = self.bookmarks.len();
+                self.bookmarks.clear();
+                self.notify_info(format!(
+                    "Cleared {} bookmark{}",
+                    count,
+                    if count == 1 { "" } else { "s" }
+                ));
+            }
+            CommandEffect::ExportBookmarks { filename } => match self.export_bookmarks(&filename) {
+                Ok(0) => self.notify_warn("No bookmarks to export"),
+                Ok(count) => {
+                    self.notify_info(format!("Exported {} bookmarks to {}", count, filename))
+                }
+                Err(e) => self.notify_error(format!("Error: {}", e)),
+            },
+        }
+        Mode::Normal
+    }
+
+    fn show_selection_time(&mut self) {
+        let Some((start, end)) = self.selection.range(self.selected_line) else {
+            self.notify_warn("Select a range with x first, then run :time");
+            return;
+        };
+        match self.selection_time_span() {
+            Some((from, to)) => self.notify_info(format!(
+                "{} between line {} ({}) and line {} ({})",
+                format_duration(to - from),
+                start + 1,
+                from.format("%H:%M:%S%.3f"),
+                end + 1,
+                to.format("%H:%M:%S%.3f")
+            )),
+            None => self.notify_warn("No timestamps at the ends of the selection"),
+        }
+    }
+
+    /// Storage lines between two `:range` times, or the selection if both
+    /// are None.
+    fn resolve_search_range(
+        &self,
+        from: Option<String>,
+        to: Option<String>,
+    ) -> Result<SearchRange, String> {
+        if from.is_none() && to.is_none() {
+            let Some((start, end)) = self.selection.range(self.selected_line) else {
+                return Err("Select lines with x or give a time range: range from..to".to_string());
+            };
+            return Ok(SearchRange {
+                first: self.filtered_indices[start],
+                last: Some(self.filtered_indices[end]),
+                label: "selection".to_string(),
+            });
+        }
+
+        // Times of day are taken on the date of the line under the cursor
+        let reference = self
+            .get_entry_timestamp(self.selected_line)
+            .or_else(|| (0..self.filtered_len()).find_map(|idx| self.get_filtered_timestamp(idx)))
+            .ok_or_else(|| "No timestamps to limit the search by".to_string())?
+            .date_naive();
+        let parse = |text: &Option<String>| match text {
+            Some(text) => parse_time_bound(text, reference)
+                .and_then(|time| time.timestamp_nanos_opt())
+                .map(Some)
+                .ok_or_else(|| format!("Not a time: {}", text)),
+            None => Ok(None),
+        };
+        let (from_nanos, to_nanos) = (parse(&from)?, parse(&to)?);
+
+        // Continuation lines count as part of the entry above them
+        let mut entry_time = None;
+        let mut first = None;
+        let mut last = None;
+        for (pos, &idx) in self.filtered_indices.iter().enumerate() {
+            if let Some(time) = self.get_filtered_timestamp_nanos(pos) {
+                entry_time = Some(time);
+            }
+            let Some(time) = entry_time else {
+                continue;
+            };
+            let after_start = from_nanos.is_none_or(|from| time >= from);
+            let before_end = to_nanos.is_none_or(|to| time <= to);
+            if after_start && before_end {
+                first.get_or_insert(idx);
+                last = Some(idx);
+            }
+        }
+        let first = first.ok_or_else(|| "No lines in that time range".to_string())?;
+        let label = format!(
+            "{}..{}",
+            from.as_deref().unwrap_or_default(),
+            to.as_deref().unwrap_or_default()
+        );
+        Ok(SearchRange {
+            first,
+            last: to.and(last),
+            label,
+        })
+    }
+
+    /// Limit searches to `range`, re-running the active search in it.
+    fn set_search_range(&mut self, range: Option<SearchRange>) {
+        self.search_range = range;
+        if let Some(query) = self.search_query.clone() {
+            self.init_search_state(query);
+        }
+        let lines = self.search_scope().len();
+        let status = match &self.search_range {
+            Some(range) => format!(
+                "Search limited to {} ({} line{})",
+                range.label,
+                lines,
+                if lines == 1 { "" } else { "s" }
+            ),
+            None => "Search range cleared".to_string(),
+        };
+        self.notify_info(status);
+    }
+
+    /// Label of the `:range` searches are limited to.
+    pub fn search_range_label(&self) -> Option<&str> {
+        self.search_range.as_ref().map(|range| range.label.as_str())
+    }
+
+    /// Switch yanks to another clipboard backend, or report the current
+    /// one. A backend that cannot be set up leaves the current one in place.
+    fn set_clipboard(&mut self, backend: Option<ClipboardBackend>) {
+        match (backend, &self.clipboard) {
+            (None, Some(clipboard)) => {
+                let backend = clipboard.backend();
+                self.notify_info(format!(
+                    "Clipboard: {} (yanks go to the {})",
+                    backend.name(),
+                    backend.target()
+                ));
+            }
+            (None, None) => self.notify_error(
+                "Clipboard unavailable - install display server or use :clipboard osc52",
+            ),
+            (Some(backend), _) => match Clipboard::new(backend) {
+                Ok(clipboard) => {
+                    self.clipboard = Some(clipboard);
+                    self.notify_info(format!(
+                        "Clipboard: {} (yanks go to the {})",
+                        backend.name(),
+                        backend.target()
+                    ));
+                }
+                Err(e) => self.notify_error(e.to_string()),
+            },
+        }
+    }
+
+    fn set_ruler(&mut self, column: Option<usize>) {
+        self.ruler_column = column;
+        self.notify_info(match column {
+            Some(column) => format!("Ruler at column {}", column),
+            None => "Ruler off".to_string(),
+        });
+    }
+
+    fn set_throttle(&mut self, limit: Option<usize>) {
+        self.throttle = limit.map(Throttle::new);
+        self.update_filtered_logs();
+        self.notify_info(match &self.throttle {
+            Some(throttle) => format!(
+                "Showing the first {} lines of each message ({} repeats hidden)",
+                throttle.limit(),
+                throttle.dropped()
+            ),
+            None => "Throttle off".to_string(),
+        });
+    }
+
+    /// Scroll the help overlay by `rows`, keeping its last row in reach.
+    fn scroll_help(&mut self, rows: usize, down: bool) {
+        let last = help::rows(&self.help).saturating_sub(1);
+        self.help_scroll = if down {
+            (self.help_scroll + rows).min(last)
+        } else {
+            self.help_scroll.saturating_sub(rows)
+        };
+    }
+
+    /// Set how searches treat case and run the current search again.
+    pub fn set_case_mode(&mut self, mode: CaseMode) {
+        self.case_mode = mode;
+        if let Some(query) = self.search_query.clone() {
+            self.init_search_state(query);
+        }
+        self.notify_info(match mode {
+            CaseMode::Ignore => "Search: ignore case".to_string(),
+            CaseMode::Smart => "Search: smart case (queries with uppercase match case)".to_string(),
+            CaseMode::Sensitive => "Search: match case".to_string(),
+        });
+    }
+
+    /// Match searches and the filters of every tab as whole words only, or
+    /// anywhere, and apply them again.
+    pub fn set_whole_word(&mut self, whole_word: bool) {
+        self.whole_word = whole_word;
+        self.filters.set_whole_word(whole_word);
+        for tab in &mut self.tabs {
+            tab.filters.set_whole_word(whole_word);
+        }
+        let query = self.search_query.clone();
+        let stack = self.search_stack.clone();
+        self.update_filtered_logs();
+        if let Some(query) = query {
+            self.search_stack = stack;
+            self.init_search_state(query);
+        }
+        self.notify_info(if whole_word {
+            "Searches and filters match whole words".to_string()
+        } else {
+            "Searches and filters match anywhere".to_string()
+        });
+    }
+
+    /// Show `lines` lines around each line that passes the filters, or
+    /// only the lines that pass them.
+    pub fn set_context(&mut self, lines: Option<usize>) {
+        self.context = lines.map(Context::new);
+        self.update_filtered_logs();
+        self.notify_info(match lines {
+            Some(lines) => format!(
+                "Showing {} line{} of context around each match",
+                lines,
+                if lines == 1 { "" } else { "s" }
+            ),
+            None => "Context off".to_string(),
+        });
+    }
+
+    /// Show structured lines as columns of the given fields, or as text.
+    pub fn set_columns(&mut self, columns: Option<Columns>) {
+        self.notify_info(match &columns {
+            Some(columns) => format!("Columns: {}", columns.label()),
+            None => "Columns off".to_string(),
+        });
+        self.columns = columns;
+        self.visual_cache.set_wrap_mode(self.wraps());
+        self.clamp_scroll();
+    }
+
+    /// Whether long lines wrap: wrap mode is on and lines are not shown as
+    /// columns, which take one row each.
+    pub fn wraps(&self) -> bool {
+        self.wrap_mode && self.columns.is_none()
+    }
+
+    /// Number of lines hidden by the throttle after the filtered line at
+    /// `idx`, shown as a `(+N more like this)` marker.
+    pub fn throttled_after(&self, idx: usize) -> Option<usize> {
+        let storage_idx = *self.filtered_indices.get(idx)?;
+        self.throttle.as_ref()?.more_after(storage_idx)
+    }
+
+    /// Save the filtered lines to `filename`, in the background if there
+    /// are many; `verbatim` keeps their original bytes and line endings.
+    /// With `context`, the matches are saved with that many lines around
+    /// each and `--` between groups. The outcome goes to the status bar.
+    fn write_filtered_logs(&mut self, filename: &str, verbatim: bool, context: Option<usize>) {
+        let indices = self.export_indices(context);
+        let lines = indices.len();
+        let grouped = context.is_some();
+        if let Some(storage) = self
+            .storage
+            .as_ref()
+            .filter(|_| lines > BACKGROUND_EXPORT_LINES)
+        {
+            let size = export::estimated_size(storage, &indices);
+            let job = export::spawn_write(
+                storage.clone(),
+                indices.into_owned(),
+                PathBuf::from(filename),
+                verbatim,
+                grouped,
+            );
+            self.export_jobs.push((filename.to_string(), job));
+            self.notify_info(format!(
+                "Writing {} lines (~{}) to {}...",
+                lines,
+                export::format_size(size),
+                filename
+            ));
+            return;
+        }
+
+        let written = match &self.storage {
+            Some(storage) => export::write_file(
+                storage.as_ref(),
+                &indices,
+                Path::new(filename),
+                verbatim,
+                grouped,
+            ),
+            None => File::create(filename).map(|_| export::Written::default()),
+        };
+        match written {
+            Ok(written) => self.notify_info(written.message(filename)),
+            Err(e) => self.notify_error(format!("Error: {}", e)),
+        }
+    }
+
+    /// The lines `:write` saves: the filtered lines, or with `context`, the
+    /// matches among them (not the context shown around them) with
+    /// `context` lines around each, as the view shows them.
+    fn export_indices(&self, context: Option<usize>) -> Cow<'_, [usize]> {
+        let (Some(lines), Some(storage)) = (context, &self.storage) else {
+            return Cow::Borrowed(&self.filtered_indices);
+        };
+        let keep = |idx: usize| !self.hidden.contains(&idx);
+        let mut around = Context::new(lines);
+        let mut indices = Vec::new();
+        for &idx in &self.filtered_indices {
+            if !self.context.as_ref().is_some_and(|c| c.is_context(idx)) {
+                around.push_match(&mut indices, idx, keep);
+            }
+        }
+        let end = self.replay.as_ref().map_or(storage.len(), Replay::end);
+        around.fill_after(&mut indices, end, keep);
+        Cow::Owned(indices)
+    }
+
+    /// Render the selection (or the cursor line) as a markdown table and
+    /// copy it, or write it to `filename`. The outcome goes to the status bar.
+    fn export_table(&mut self, fields: &[String], filename: Option<&str>) {
+        let lines = self.selected_storage_lines();
+        let Some(storage) = self.storage.as_ref().filter(|_| !lines.is_empty()) else {
+            self.notify_warn("Nothing to export");
+            return;
+        };
+        let table = export::markdown_table(storage.as_ref(), &lines, fields);
+        let rows = format!(
+            "{} row{}",
+            lines.len(),
+            if lines.len() == 1 { "" } else { "s" }
+        );
+
+        let exported = match filename {
+            Some(filename) => std::fs::write(filename, table)
+                .map(|()| format!("Wrote table of {} to {}", rows, filename))
+                .map_err(|e| format!("Error: {}", e)),
+            None => match &mut self.clipboard {
+                Some(clipboard) => clipboard
+                    .copy(&table)
+                    .map(|()| format!("Copied table of {} to clipboard", rows))
+                    .map_err(|e| format!("Failed to copy: {}", e)),
+                None => Err("Clipboard unavailable - use :table [field ...] > file".to_string()),
+            },
+        };
+        match exported {
+            Ok(message) => self.notify_info(message),
+            Err(message) => self.notify_error(message),
+        }
+    }
+
+    /// Write a markdown report of all bookmarks. Returns the bookmark count.
+    fn export_bookmarks(&self, filename: &str) -> std::io::Result<usize> {
+        let Some(storage) = &self.storage else {
+            return Ok(0);
+        };
+        if self.bookmarks.is_empty() && self.pins.is_empty() {
+            return Ok(0);
+        }
+
+        let report = export::bookmarks_report(
+            storage,
+            &self.bookmarks,
+            &self.pins,
+            export::BOOKMARK_CONTEXT_LINES,
+        );
+        std::fs::write(filename, report)?;
+        Ok(self.bookmarks.len() + self.pins.len())
+    }
+
+    // Search handlers
+
+    fn on_enter_search(&mut self) {
+        self.mode = Mode::SearchInput;
+        self.search_origin = Some(SearchOrigin {
+            selected_line: self.selected_line,
+            scroll_offset: self.scroll_offset,
+            horizontal_scroll: self.horizontal_scroll,
+            parked: None,
+            previewed: None,
+        });
+        self.search_preview_at = None;
+        // Pre-populate with last search query if exists
+        if let Some(last_query) = &self.search_query {
+            self.input_buffer = last_query.clone();
+        } else {
+            self.input_buffer.clear();
+        }
+    }
+
+    fn on_cancel_search(&mut self) {
+        if let Some(origin) = self.search_origin.take() {
+            self.restore_search_origin(origin);
+        }
+        self.search_preview_at = None;
+        self.mode = Mode::Normal;
+        self.input_buffer.clear();
+    }
+
+    fn on_submit_search(&mut self) {
+        self.search_preview_at = None;
+        if let Some(origin) = self.search_origin.take() {
+            let input = self.input_buffer.trim();
+            if !input.is_empty() && input != "&" && origin.previewed.as_deref() == Some(input) {
+                // The preview is the search asked for
+                if let Some(job) = origin.parked.and_then(|parked| parked.job) {
+                    job.cancel();
+                }
+                self.mode = Mode::Normal;
+                self.input_buffer.clear();
+                return;
+            }
+            self.restore_search_origin(origin);
+        }
+        if self.input_buffer.trim().is_empty() {
+            // Empty query clears search
+            self.clear_search();
+        } else {
+            // Execute search with non-empty query; `&term` refines the
+            // current search and `&` alone goes back one level
+            let input = self.input_buffer.trim().to_string();
+            match input.strip_prefix('&').map(str::trim) {
+                Some("") => self.on_pop_search(),
+                Some(term) => {
+                    if let Some(outer) = self.search_query.take() {
+                        self.search_stack.push(outer);
+                    }
+                    self.init_search_state(term.to_string());
+                }
+                None => {
+                    self.search_stack.clear();
+                    self.init_search_state(input);
+                }
+            }
+        }
+        self.mode = Mode::Normal;
+        self.input_buffer.clear();
+    }
+
+    fn on_search_type_char(&mut self, c: char) {
+        self.input_buffer.push(c);
+        self.search_preview_at = Some(Instant::now() + SEARCH_PREVIEW_DELAY);
+    }
+
+    fn on_search_backspace(&mut self) {
+        self.input_buffer.pop();
+        self.search_preview_at = Some(Instant::now() + SEARCH_PREVIEW_DELAY);
+    }
+
+    /// Preview the search being typed once typing has paused.
+    pub fn poll_search_preview(&mut self) {
+        self.poll_search_preview_at(Instant::now());
+    }
+
+    fn poll_search_preview_at(&mut self, now: Instant) {
+        if self.search_preview_at.is_some_and(|due| due <= now) {
+            self.search_preview_at = None;
+            self.preview_search();
+        }
+    }
+
+    /// Highlight the matches of the input so far and move to the first,
+    /// as Enter would, setting aside the search it replaces.
+    fn preview_search(&mut self) {
+        if self.mode != Mode::SearchInput {
+            return;
+        }
+        let input = self.input_buffer.trim().to_string();
+        let Some(origin) = &mut self.search_origin else {
+            return;
+        };
+        if origin.previewed.as_deref() == Some(input.as_str()) {
+            return;
+        }
+        origin.previewed = Some(input.clone());
+        let parked = origin.parked.get_or_insert_with(|| ParkedSearch {
+            query: self.search_query.take(),
+            stack: std::mem::take(&mut self.search_stack),
+            state: self.search_state.take(),
+            job: self.search_job.take(),
+        });
+        // `&term` refines the parked search, as on Enter
+        let (stack, term) = match input.strip_prefix('&').map(str::trim) {
+            Some(term) => (
+                parked.stack.iter().chain(&parked.query).cloned().collect(),
+                term,
+            ),
+            None => (Vec::new(), input.as_str()),
+        };
+
+        // Matches are looked for from where search input began
+        self.selected_line = origin.selected_line;
+        self.scroll_offset = origin.scroll_offset;
+        self.horizontal_scroll = origin.horizontal_scroll;
+        if term.is_empty() {
+            self.clear_search();
+        } else {
+            self.search_stack = stack;
+            self.init_search_state(term.to_string());
+        }
+    }
+
+    /// Go back to the cursor and the search from before search input.
+    fn restore_search_origin(&mut self, origin: SearchOrigin) {
+        if let Some(parked) = origin.parked {
+            self.cancel_search_job();
+            self.search_query = parked.query;
+            self.search_stack = parked.stack;
+            self.search_state = parked.state;
+            self.search_job = parked.job;
+        }
+        self.selected_line = origin.selected_line;
+        self.scroll_offset = origin.scroll_offset;
+        self.horizontal_scroll = origin.horizontal_scroll;
+        self.clamp_scroll();
+    }
+
+    fn on_clear_search(&mut self) {
+        self.clear_search();
+        self.status_message.clear();
+    }
+
+    // Selection handlers
+
+    /// Filtered line at a screen position of the log view, if any.
+    fn line_at(&self, column: u16, row: u16) -> Option<usize> {
+        let area = self.view_area.get();
+        if column < area.x || column >= area.right() || row < area.y {
+            return None;
+        }
+        let rows = self.view_rows.borrow();
+        rows.get((row - area.y) as usize).copied().flatten()
+    }
+
+    /// A click on a log line moves the cursor there and drops the selection;
+    /// in the filter list it selects the clicked filter. Clicks elsewhere,
+    /// including on the last-read marker, do nothing.
+    fn on_click(&mut self, column: u16, row: u16) {
+        if self.mode == Mode::FilterList {
+            let area = self.filter_list_area.get();
+            if row >= area.y && row < area.bottom() {
+                let idx = (row - area.y) as usize;
+                if idx < self.filter_list_len() {
+                    self.filter_list_selected = idx;
+                }
+            }
+            return;
+        }
+        let Some(idx) = self.line_at(column, row) else {
+            return;
+        };
+        self.status_message.clear();
+        self.selection.clear();
+        self.selected_line = idx;
+        self.drag_anchor = Some(idx);
+        self.clamp_scroll();
+    }
+
+    /// Dragging from a clicked line selects the lines up to the one under
+    /// the mouse; above or below the view it scrolls one line at a time.
+    fn on_drag(&mut self, column: u16, row: u16) {
+        let Some(anchor) = self.drag_anchor else {
+            return;
+        };
+        let area = self.view_area.get();
+        let last = self.filtered_len().saturating_sub(1);
+        let target = if row < area.y {
+            self.selected_line.saturating_sub(1)
+        } else if row >= area.bottom() {
+            (self.selected_line + 1).min(last)
+        } else {
+            let column = column.clamp(area.x, area.right().saturating_sub(1));
+            match self.line_at(column, row) {
+                Some(idx) => idx,
+                None => return,
+            }
+        };
+        if !self.selection.is_active() {
+            self.selection.start(anchor);
+        }
+        let direction = if target >= self.selected_line {
+            Direction::Down
+        } else {
+            Direction::Up
+        };
+        self.selected_line = target;
+        self.selection.extend(target, direction);
+        self.clamp_scroll();
+    }
+
+    fn on_toggle_selection(&mut self) {
+        if self.selection.is_active() {
+            // Extend selection - determine direction based on cursor movement
+            if let Some((start, _end)) = self.selection.range(self.selected_line) {
+                let direction = if self.selected_line > start {
+                    Direction::Down
+                } else {
+                    Direction::Up
+                };
+                self.selection.extend(self.selected_line, direction);
+            }
+        } else {
+            // Start new selection at current cursor
+            self.selection.start(self.selected_line);
+        }
+    }
+
+    /// Select every line in the search scope with a match of the search.
+    fn on_select_matches(&mut self) {
+        let (Some(state), Some(source)) = (&self.search_state, self.source()) else {
+            self.notify_warn("Search with / first, then select the matches with %");
+            return;
+        };
+        let scope = self.search_scope();
+        let lines = search::matching_lines(
+            source,
+            &self.filtered_indices[scope.clone()],
+            scope.start,
+            &state.matcher,
+            &state.within,
+        );
+        let query = state.query.clone();
+        if lines.is_empty() {
+            self.notify_warn(format!("No lines match '{}'", query));
+            return;
+        }
+        self.notify_info(format!(
+            "Selected {} line{} matching '{}'",
+            lines.len(),
+            if lines.len() == 1 { "" } else { "s" },
+            query
+        ));
+        self.selection.select_lines(lines);
+    }
+
+    fn on_clear_selection(&mut self) {
+        self.selection.clear();
+        match self.cancel_focused_job() {
+            Some(message) => self.notify_info(message),
+            None => self.status_message.clear(),
+        }
+    }
+
+    fn on_yank(&mut self) {
+        // Check if selection is active
+        if !self.selection.is_active() {
+            return;
+        }
+
+        // Check if clipboard is available
+        let Some(ref mut clipboard) = self.clipboard else {
+            self.notify_error(
+                "Clipboard unavailable - install display server or use :clipboard osc52",
+            );
+            return;
+        };
+
+        // Retrieve the raw lines from storage
+        let Some(ref storage) = self.storage else {
+            return;
+        };
+
+        let mut lines = Vec::new();
+        for idx in self.selection.lines(self.selected_line) {
+            if let Some(&storage_idx) = self.filtered_indices.get(idx) {
+                if let Some(line) = storage.get_line(storage_idx) {
+                    lines.push(line.as_str_lossy().to_string());
+                }
+            }
+        }
+
+        if lines.is_empty() {
+            return;
+        }
+
+        // Join lines with newline
+        let text = lines.join("\n");
+
+        // Copy to clipboard
+        match clipboard.copy(&text) {
+            Ok(()) => {
+                let status = format!(
+                    "Copied {} lines to {}",
+                    lines.len(),
+                    clipboard.backend().target()
+                );
+                self.notify_info(status);
+            }
+            Err(e) => {
+                self.notify_error(format!("Failed to copy: {}", e));
+            }
+        }
+    }
+
+    // Bookmark handlers
+
+    fn on_toggle_bookmark(&mut self) {
+        if self.selection.is_active() {
+            let lines = self.selected_storage_lines();
+            for &idx in &lines {
+                self.bookmarks.add(idx);
+            }
+            self.selection.clear();
+            self.notify_info(format!("Bookmarked {} lines", lines.len()));
+            return;
+        }
+        let Some(idx) = self.selected_storage_index() else {
+            return;
+        };
+        let status = if self.bookmarks.toggle(idx) {
+            format!("Bookmarked line {}", self.selected_line + 1)
+        } else {
+            format!("Removed bookmark from line {}", self.selected_line + 1)
+        };
+        self.notify_info(status);
+    }
+
+    /// Scan the whole log for diagnostics, replacing the previous list.
+    fn scan_quickfix(&mut self) -> &[QuickfixEntry] {
+        let entries = self
+            .source()
+            .map(|source| quickfix::scan(source, 0..source.len()))
+            .unwrap_or_default();
+        self.quickfix.insert(entries)
+    }
+
+    /// Move to the next (or previous) diagnostic in view after (before) the
+    /// cursor line.
+    fn on_step_error(&mut self, forward: bool) {
+        if self.quickfix.is_none() {
+            self.scan_quickfix();
+        }
+        let entries = self.quickfix.as_deref().unwrap_or_default();
+        let cursor = self.selected_storage_index().unwrap_or(0);
+        let in_view = |(pos, entry): (usize, &QuickfixEntry)| {
+            let line = self.filtered_indices.binary_search(&entry.line).ok()?;
+            Some((pos, line))
+        };
+        let target = if forward {
+            entries
+                .iter()
+                .enumerate()
+                .filter(|(_, entry)| entry.line > cursor)
+                .find_map(in_view)
+        } else {
+            entries
+                .iter()
+                .enumerate()
+                .rev()
+                .filter(|(_, entry)| entry.line < cursor)
+                .find_map(in_view)
+        };
+        let Some((pos, line)) = target else {
+            self.notify_warn(if entries.is_empty() {
+                "No compiler or test errors found".to_string()
+            } else if forward {
+                "No more errors below".to_string()
+            } else {
+                "No more errors above".to_string()
+            });
+            return;
+        };
+        self.notify_info(error_status(entries, pos));
+        self.quickfix_selected = pos;
+        self.selected_line = line;
+        self.clamp_scroll();
+    }
+
+    /// Move the cursor to the selected diagnostic's line in the log view.
+    fn on_jump_to_error(&mut self) {
+        self.mode = Mode::Normal;
+        let Some(entries) = &self.quickfix else {
+            return;
+        };
+        let Some(entry) = entries.get(self.quickfix_selected) else {
+            return;
+        };
+        match self.filtered_indices.binary_search(&entry.line) {
+            Ok(line) => {
+                self.notify_info(error_status(entries, self.quickfix_selected));
+                self.selected_line = line;
+                self.clamp_scroll();
+            }
+            Err(_) => self.notify_warn("Error line is filtered out"),
+        }
+    }
+
+    /// Pause the selected pod, or resume it and show the lines held back.
+    fn on_toggle_pod_pause(&mut self) {
+        let idx = self.pod_list_selected;
+        let Some(released) = self.pods.toggle_pause(idx) else {
+            return;
+        };
+        let name = self.pods.get(idx).map_or("", |pod| pod.name.as_str());
+        self.notify_info(if released.is_empty() {
+            format!("Paused {}", name)
+        } else {
+            format!(
+                "Resumed {} ({} held line{})",
+                name,
+                released.len(),
+                if released.len() == 1 { "" } else { "s" }
+            )
+        });
+        if !released.is_empty() {
+            self.append_lines(released);
+        }
+    }
+
+    /// Check whether the lines of pod `name` are hidden by its mute filter.
+    pub fn is_pod_muted(&self, name: &str) -> bool {
+        let pattern = pods::mute_pattern(name);
+        self.filters.excludes().iter().any(|rule| {
+            rule.enabled && rule.file.is_none() && rule.pattern.eq_ignore_ascii_case(&pattern)
+        })
+    }
+
+    /// Hide or show the lines of the selected pod with an exclude filter on
+    /// its prefix.
+    fn on_toggle_pod_mute(&mut self) {
+        let Some(name) = self
+            .pods
+            .get(self.pod_list_selected)
+            .map(|pod| pod.name.clone())
+        else {
+            return;
+        };
+        let pattern = pods::mute_pattern(&name);
+        if self.is_pod_muted(&name) {
+            let muting: Vec<usize> = self
+                .filters
+                .iter()
+                .filter(|(_, rule)| {
+                    rule.kind == FilterKind::Exclude
+                        && rule.file.is_none()
+                        && rule.pattern.eq_ignore_ascii_case(&pattern)
+                })
+                .map(|(index, _)| index)
+                .collect();
+            for index in muting.into_iter().rev() {
+                self.filters.remove(index);
+            }
+            self.notify_info(format!("Unmuted {}", name));
+        } else {
+            self.filters.add(FilterKind::Exclude, pattern);
+            self.notify_info(format!("Muted {}", name));
+        }
+        self.update_filtered_logs();
+    }
+
+    /// Badge of the pod a line came from: its prefix in the pod's color.
+    pub fn pod_badge(&self, line: &str) -> Option<(Range<usize>, Style)> {
+        let (pod, prefix) = self.pods.of_line(line.as_bytes())?;
+        Some((prefix, Style::default().fg(Color::Black).bg(pod.color)))
+    }
+
+    /// Open the bookmark list on the bookmark at or after the cursor line.
+    fn open_bookmark_list(&mut self) -> Mode {
+        if self.bookmarks.is_empty() {
+            self.notify_warn("No bookmarks (m bookmarks the current line)");
+            return Mode::Normal;
+        }
+        let cursor = self.selected_storage_index().unwrap_or(0);
+        self.bookmark_list_selected = self
+            .bookmarks
+            .iter()
+            .position(|(idx, _)| idx >= cursor)
+            .unwrap_or(self.bookmarks.len() - 1);
+        Mode::Bookmarks
+    }
+
+    fn on_bookmark_list_down(&mut self) {
+        if self.bookmark_list_selected + 1 < self.bookmarks.len() {
+            self.bookmark_list_selected += 1;
+        }
+    }
+
+    fn on_bookmark_list_up(&mut self) {
+        self.bookmark_list_selected = self.bookmark_list_selected.saturating_sub(1);
+    }
+
+    fn on_remove_bookmark(&mut self) {
+        let Some((idx, _)) = self.bookmarks.iter().nth(self.bookmark_list_selected) else {
+            return;
+        };
+        self.bookmarks.remove(idx);
+        if self.bookmarks.is_empty() {
+            self.mode = Mode::Normal;
+        } else {
+            self.bookmark_list_selected = self.bookmark_list_selected.min(self.bookmarks.len() - 1);
+        }
+    }
+
+    /// Move the cursor to the selected bookmark's line in the log view.
+    fn on_jump_to_bookmark(&mut self) {
+        self.mode = Mode::Normal;
+        let Some((idx, _)) = self.bookmarks.iter().nth(self.bookmark_list_selected) else {
+            return;
+        };
+        match self.filtered_indices.binary_search(&idx) {
+            Ok(line) => {
+                self.selected_line = line;
+                self.clamp_scroll();
+            }
+            Err(_) => self.notify_warn("Bookmarked line is filtered out"),
+        }
+    }
+
+    // Pin handlers
+
+    /// Pin the selected lines, or toggle the pin on the cursor line.
+    fn on_toggle_pin(&mut self) {
+        if self.selection.is_active() {
+            let lines = self.selected_storage_lines();
+            for &idx in &lines {
+                self.pins.add(idx);
+            }
+            self.selection.clear();
+            self.notify_info(format!("Pinned {} lines", lines.len()));
+            return;
+        }
+        let Some(idx) = self.selected_storage_index() else {
+            return;
+        };
+        let status = if self.pins.toggle(idx) {
+            format!("Pinned line {}", self.selected_line + 1)
+        } else {
+            format!("Unpinned line {}", self.selected_line + 1)
+        };
+        self.notify_info(status);
+    }
+
+    fn on_pin_list_down(&mut self) {
+        if self.pin_list_selected + 1 < self.pins.len() {
+            self.pin_list_selected += 1;
+        }
+    }
+
+    fn on_pin_list_up(&mut self) {
+        self.pin_list_selected = self.pin_list_selected.saturating_sub(1);
+    }
+
+    fn on_move_pin(&mut self, up: bool) {
+        let pos = self.pin_list_selected;
+        if up && self.pins.move_up(pos) {
+            self.pin_list_selected -= 1;
+        } else if !up && self.pins.move_down(pos) {
+            self.pin_list_selected += 1;
+        }
+    }
+
+    fn on_remove_pin(&mut self) {
+        self.pins.remove(self.pin_list_selected);
+        if self.pins.is_empty() {
+            self.mode = Mode::Normal;
+        } else {
+            self.pin_list_selected = self.pin_list_selected.min(self.pins.len() - 1);
+        }
+    }
+
+    /// Move the cursor to the selected pin's line in the log view.
+    fn on_jump_to_pin(&mut self) {
+        self.mode = Mode::Normal;
+        let Some(idx) = self.pins.get(self.pin_list_selected) else {
+            return;
+        };
+        match self.filtered_indices.binary_search(&idx) {
+            Ok(line) => {
+                self.selected_line = line;
+                self.clamp_scroll();
+            }
+            Err(_) => self.notify_warn("Pinned line is filtered out"),
+        }
+    }
+
+    // SQL overlay handlers
+
+    /// Run a `:sql` query over the filtered lines, with the sidecar
+    /// database of the file under the cursor attached if there is one.
+    ///
+    /// Bookmarks are stored first so queries on them see the current ones.
+    fn run_sql(&mut self, sql: String) -> Mode {
+        let Some(storage) = self.storage.as_deref() else {
+            self.notify_error("No logs loaded");
+            return Mode::Normal;
+        };
+        let file = self
+            .selected_storage_index()
+            .and_then(|idx| storage.line_file(idx))
+            .unwrap_or(0);
+        let sidecar = storage
+            .file_path(file)
+            .filter(|_| self.sidecar_enabled() && storage.file_lines(file).is_some())
+            .map(Sidecar::path_for)
+            .filter(|path| path.exists());
+        if sidecar.is_some() {
+            if let Err(e) = self.save_annotations() {
+                self.notify_error(e.to_string());
+                return Mode::Normal;
+            }
+        }
+
+        let Some((source, indices)) = self.overlay_input() else {
+            return Mode::Normal;
+        };
+        let lines = indices.len().min(query::MAX_TABLE_LINES);
+        self.run_overlay(format!("sql '{}'", sql), lines, move |ctx| {
+            let Some(result) =
+                query::run_in_job(&*source, &indices, sidecar.as_deref(), &sql, &ctx)
+            else {
+                return;
+            };
+            ctx.send(Overlay::Sql(result.map(|result| SqlView {
+                query: sql,
+                result,
+                selected: 0,
+            })));
+        })
+    }
+
+    fn on_sql_move(&mut self, down: bool) {
+        if let Some(view) = &mut self.sql_view {
+            view.selected = if down {
+                (view.selected + 1).min(view.result.rows.len().saturating_sub(1))
+            } else {
+                view.selected.saturating_sub(1)
+            };
+        }
+    }
+
+    /// Go to the line named in the `line` column of the selected row.
+    ///
+    /// `line` is taken as numbered in the viewer, as in the `logs` table.
+    fn on_jump_to_sql_row(&mut self) {
+        let Some(view) = self.sql_view.take() else {
+            return;
+        };
+        self.mode = Mode::Normal;
+        let line = view
+            .result
+            .columns
+            .iter()
+            .position(|column| column.eq_ignore_ascii_case("line"))
+            .and_then(|col| view.result.rows.get(view.selected)?.get(col))
+            .and_then(|value| value.parse::<usize>().ok())
+            .filter(|&line| line > 0);
+        match line {
+            Some(line) => self.select_storage_index(line - 1),
+            None => self.notify_warn("No line column in the query result"),
+        }
+    }
+
+    /// Filter on the selected row's key: its first value other than `line`.
+    fn on_filter_sql_row(&mut self, kind: FilterKind) {
+        let Some(view) = self.sql_view.take() else {
+            return;
+        };
+        self.mode = Mode::Normal;
+        let value = view.result.rows.get(view.selected).and_then(|row| {
+            view.result
+                .columns
+                .iter()
+                .zip(row)
+                .find(|(column, value)| {
+                    !column.eq_ignore_ascii_case("line") && value.as_str() != "NULL"
+                })
+                .map(|(_, value)| value.clone())
+        });
+        let Some(pattern) = value.filter(|value| !value.is_empty()) else {
+            self.notify_warn("No value to filter on in this row");
+            return;
+        };
+        self.notify_info(format!("Added {} filter: {}", kind.name(), pattern));
+        self.filters.add(kind, pattern);
+        self.update_filtered_logs();
+    }
+
+    fn on_stats_move(&mut self, down: bool) {
+        if let Some(view) = &mut self.stats_view {
+            view.selected = if down {
+                (view.selected + 1).min(view.rows.len().saturating_sub(1))
+            } else {
+                view.selected.saturating_sub(1)
+            };
+        }
+    }
+
+    /// Open the `:gaps` chart of the time between search matches, or
+    /// between the lines in view without a search.
+    fn show_gaps(&mut self) -> Mode {
+        let Some(source) = self.source() else {
+            self.notify_warn("No logs loaded");
+            return Mode::Normal;
+        };
+        let (times, subject) = match (&self.search_state, &self.search_query) {
+            (Some(state), Some(query)) => (
+                gaps::line_times(
+                    source,
+                    &self.filtered_indices[state.scope.clone()],
+                    Some((&state.matcher, &state.within)),
+                ),
+                format!("matches of '{}'", query),
+            ),
+            _ => (
+                gaps::line_times(source, &self.filtered_indices, None),
+                "lines in view".to_string(),
+            ),
+        };
+        let Some(gaps) = gaps::compute(&times) else {
+            self.notify_warn(format!(
+                "No gaps: fewer than two {} have a timestamp",
+                subject
+            ));
+            return Mode::Normal;
+        };
+        self.gaps_view = Some(GapsView { gaps, subject });
+        Mode::Gaps
+    }
+
+    fn on_exceptions_move(&mut self, down: bool) {
+        if let Some(view) = &mut self.exceptions_view {
+            view.selected = if down {
+                (view.selected + 1).min(view.groups.len().saturating_sub(1))
+            } else {
+                view.selected.saturating_sub(1)
+            };
+        }
+    }
+
+    /// Move the cursor to the latest instance of the selected exception
+    /// group.
+    fn on_jump_to_exception(&mut self) {
+        let Some(view) = self.exceptions_view.take() else {
+            return;
+        };
+        self.mode = Mode::Normal;
+        let Some(group) = view.groups.get(view.selected) else {
+            return;
+        };
+        let Some(idx) = group.latest() else {
+            return;
+        };
+        match self.filtered_indices.binary_search(&idx) {
+            Ok(line) => {
+                self.notify_info(format!("Latest of {} {}", group.count(), group.name));
+                self.selected_line = line;
+                self.clamp_scroll();
+            }
+            Err(_) => self.notify_warn("Exception line is filtered out"),
+        }
+    }
+
+    /// Show only lines of `levels`, or lines of every level.
+    fn set_levels(&mut self, levels: Option<LevelSet>) {
+        self.drill_down.levels = levels;
+        self.update_filtered_logs();
+        match levels {
+            None => self.notify_info("Showing lines of every level"),
+            Some(levels) if levels.is_empty() => {
+                self.notify_warn("Every level hidden (1-5 show them again)")
+            }
+            Some(levels) => self.notify_info(format!("Showing {} lines", levels.label())),
+        }
+    }
+
+    /// Show or hide the lines of `level`; showing every level again drops
+    /// the level limit.
+    fn on_toggle_level(&mut self, level: LogLevel) {
+        let mut levels = self.drill_down.levels.unwrap_or(LevelSet::ALL);
+        levels.toggle(level);
+        self.set_levels(Some(levels).filter(|&levels| levels != LevelSet::ALL));
+    }
+
+    /// Narrow the view to the selected stats row: its level, its
+    /// `SourceContext` or its time bucket.
+    fn on_drill_down(&mut self) {
+        let Some(view) = self.stats_view.take() else {
+            return;
+        };
+        self.mode = Mode::Normal;
+        let Some(row) = view.rows.get(view.selected) else {
+            return;
+        };
+        let status = match row {
+            StatsRow::Level { level, .. } => {
+                self.drill_down.levels = Some(LevelSet::only(*level));
+                format!("Showing {} lines", level.name())
+            }
+            StatsRow::Context { .. } => {
+                let Some(pattern) = row.context_filter() else {
+                    return;
+                };
+                let status = format!("Added include filter: {}", pattern);
+                self.filters.add(FilterKind::Include, pattern);
+                status
+            }
+            StatsRow::Bucket { from, to, .. } => {
+                self.drill_down.time = Some((*from, *to));
+                format!("Showing {}", stats::time_range_label(*from, *to))
+            }
+        };
+        self.notify_info(status);
+        self.update_filtered_logs();
+    }
+
+    // Detail pane handlers
+
+    fn on_open_detail(&mut self) {
+        let Some(line) = self.get_filtered_entry(self.selected_line) else {
+            return;
+        };
+        let mut text = line.as_str_lossy().into_owned();
+        // A plain entry comes with its continuation lines (a stack trace)
+        if let (Some(storage), Some(idx)) = (&self.storage, self.selected_storage_index()) {
+            let starts_entry = storage
+                .get_line_info(idx)
+                .is_some_and(|info| info.timestamp().is_some());
+            if starts_entry && detail::parse_json(&text).is_none() {
+                for next in (idx + 1..storage.len()).take(DETAIL_ENTRY_LINES) {
+                    if storage
+                        .get_line_info(next)
+                        .is_none_or(|info| info.timestamp().is_some())
+                    {
+                        break;
+                    }
+                    let Some(line) = storage.get_line(next) else {
+                        break;
+                    };
+                    text.push('\n');
+                    text.push_str(&line.as_str_lossy());
+                }
+            }
+        }
+        self.detail = Some(DetailView::new(&text));
+        self.mode = Mode::Detail;
+    }
+
+    /// Open the hex view of the line under the cursor.
+    fn open_hex(&mut self) -> Mode {
+        let (Some(source), Some(storage), Some(idx)) =
+            (self.source(), &self.storage, self.selected_storage_index())
+        else {
+            return Mode::Normal;
+        };
+        let Some(bytes) = source.raw_line(idx) else {
+            return Mode::Normal;
+        };
+        let name = storage
+            .line_path(idx)
+            .and_then(Path::file_name)
+            .map(|name| name.to_string_lossy().into_owned());
+        let located = storage.locate(idx);
+        let source = match (name, located) {
+            (Some(name), Some((file, local))) => match storage.compression(file) {
+                Some(format) => format!(
+                    "{} line {} ({}, decompressed)",
+                    name,
+                    local + 1,
+                    format.name()
+                ),
+                None => format!("{} line {}", name, local + 1),
+            },
+            (Some(name), None) => format!("{}, received while following", name),
+            (None, _) => format!("line {}", idx + 1),
+        };
+        let offset = located
+            .and_then(|_| storage.get_line_info(idx))
+            .map(|info| info.offset);
+        self.hex = Some(HexView::new(bytes.to_vec(), offset, source));
+        Mode::Hex
+    }
+
+    /// Copy the focused field's value (not the whole line) to the clipboard.
+    fn on_yank_field(&mut self) {
+        let Some((key, value)) = self.detail.as_ref().and_then(DetailView::focused_value) else {
+            return;
+        };
+        let Some(ref mut clipboard) = self.clipboard else {
+            self.notify_error(
+                "Clipboard unavailable - install display server or use :clipboard osc52",
+            );
+            return;
+        };
+        match clipboard.copy(&value) {
+            Ok(()) => match key {
+                Some(key) => self.notify_info(format!("Copied value of {}", key)),
+                None => self.notify_info("Copied value"),
+            },
+            Err(e) => self.notify_error(format!("Failed to copy: {}", e)),
+        }
+    }
+
+    /// Add the focused field (`"key":value`) as a filter of the given kind.
+    fn on_filter_field(&mut self, kind: FilterKind) {
+        let Some(pattern) = self.detail.as_ref().and_then(DetailView::focused_filter) else {
+            self.notify_warn("Select a field with a single value to filter on");
+            return;
+        };
+        self.notify_info(format!("Added {} filter: {}", kind.name(), pattern));
+        self.filters.add(kind, pattern);
+        self.update_filtered_logs();
+    }
+
+    fn set_detail_expanded(&mut self, expanded: bool) {
+        if let Some(detail) = &mut self.detail {
+            detail.set_all_expanded(expanded);
+        }
+    }
+
+    /// Open the command line with a filter for the text the selected lines
+    /// (or the cursor line) have in common, ready to edit.
+    fn on_propose_filter(&mut self, kind: FilterKind) {
+        let lines: Vec<String> = self
+            .selected_storage_lines()
+            .into_iter()
+            .filter_map(|idx| self.storage.as_ref()?.get_line(idx))
+            .map(|line| line.as_str_lossy().into_owned())
+            .collect();
+        let lines: Vec<&str> = lines.iter().map(String::as_str).collect();
+        let Some(pattern) = filter_proposal(&lines) else {
+            self.notify_warn("No common text to filter on");
+            return;
+        };
+        let command = match kind {
+            FilterKind::Include => "filter",
+            FilterKind::Exclude => "filter-out",
+            FilterKind::Dim => "dim",
+        };
+        self.input_buffer = format!("{} {}", command, pattern);
+        self.mode = Mode::Command;
+    }
+
+    // Hidden line handlers
+
+    /// Hide the selected lines, or the cursor line, from the view.
+    fn on_hide_lines(&mut self) {
+        let lines = self.selected_storage_lines();
+        if lines.is_empty() {
+            return;
+        }
+        let count = lines.len();
+        self.hidden.extend(lines);
+
+        self.update_filtered_logs();
+        self.notify_info(format!(
+            "Hid {} line{} (U restores)",
+            count,
+            if count == 1 { "" } else { "s" }
+        ));
+    }
+
+    /// Show all manually hidden lines again.
+    fn on_restore_hidden(&mut self) {
+        if self.hidden.is_empty() {
+            self.notify_warn("No hidden lines");
+            return;
+        }
+        let count = self.hidden.len();
+        self.hidden.clear();
+        self.update_filtered_logs();
+        self.notify_info(format!(
+            "Restored {} hidden line{}",
+            count,
+            if count == 1 { "" } else { "s" }
+        ));
+    }
+
+    // Filter list handlers
+
+    /// Number of rows in the filter list: every filter, plus the hidden lines.
+    pub fn filter_list_len(&self) -> usize {
+        self.filters.len() + usize::from(!self.hidden.is_empty())
+    }
+
+    fn on_filter_list_down(&mut self) {
+        let total = self.filter_list_len();
+        if self.filter_list_selected + 1 < total {
+            self.filter_list_selected += 1;
+        }
+    }
+
+    fn on_filter_list_up(&mut self) {
+        self.filter_list_selected = self.filter_list_selected.saturating_sub(1);
+    }
+
+    fn on_toggle_selected_filter(&mut self) {
+        let Some(enabled) = self.filters.toggle(self.filter_list_selected) else {
+            return;
+        };
+        self.notify_info(if enabled {
+            "Filter enabled".to_string()
+        } else {
+            "Filter disabled".to_string()
+        });
+        self.update_filtered_logs();
+    }
+
+    /// Check whether the line at storage index `idx` is dimmed: shown as
+    /// context of a match, or matching a dim filter (using the filter index
+    /// when it covers the line).
+    pub fn is_line_dimmed(&self, idx: usize, text: &[u8]) -> bool {
+        if self.context.as_ref().is_some_and(|c| c.is_context(idx)) {
+            return true;
+        }
+        self.filter_index
+            .is_dimmed(&self.filters, idx)
+            .unwrap_or_else(|| {
+                let path = self.storage.as_ref().and_then(|s| s.line_path(idx));
+                self.filters.is_dimmed_from(text, path)
+            })
+    }
+
+    fn on_delete_selected_filter(&mut self) {
+        if self.filter_list_selected >= self.filters.len() {
+            // The "manually hidden" row
+            self.notify_info(format!("Restored {} hidden lines", self.hidden.len()));
+            self.hidden.clear();
+        } else {
+            self.filters.remove(self.filter_list_selected);
+        }
+        // Ensure selection stays valid after deletion
+        let total = self.filter_list_len();
+        if self.filter_list_selected >= total && total > 0 {
+            self.filter_list_selected = total - 1;
+        }
+        self.update_filtered_logs();
+        if total == 0 {
+            self.mode = Mode::Normal;
+        }
+    }
+
+    fn on_close_filter_list(&mut self) {
+        self.mode = Mode::Normal;
+    }
+
+    // Recent files handlers
+
+    fn on_recent_down(&mut self) {
+        if self.recent_selected + 1 < self.recent.len() {
+            self.recent_selected += 1;
+        }
+    }
+
+    fn on_recent_up(&mut self) {
+        self.recent_selected = self.recent_selected.saturating_sub(1);
+    }
+
+    fn on_open_recent(&mut self) {
+        self.mode = Mode::Normal;
+        if let Some(entry) = self.recent.get(self.recent_selected) {
+            self.request_open(entry.paths.clone());
+        }
+    }
+
+    fn on_close_recent(&mut self) {
+        self.mode = Mode::Normal;
+    }
+
+    // Large file prompt handlers
+
+    /// Apply the chosen index mode to the oversized files, or cancel the open.
+    /// Show `prompt`; answering it does `action`.
+    fn open_prompt(&mut self, prompt: Prompt, action: PromptAction) {
+        self.prompt = Some(prompt);
+        self.prompt_action = Some(action);
+        self.mode = Mode::Prompt;
+    }
+
+    /// Close the prompt and act on the choice picked by `key`, or on its
+    /// cancellation if None.
+    fn answer_prompt(&mut self, key: Option<char>) {
+        self.mode = Mode::Normal;
+        self.prompt = None;
+        match self.prompt_action.take() {
+            Some(PromptAction::OpenLargeFiles(files)) => {
+                let mode = key.map(|key| match key {
+                    'f' => IndexMode::Full,
+                    'o' => IndexMode::OnDemand {
+                        block_size: ON_DEMAND_BLOCK_SIZE,
+                    },
+                    _ => IndexMode::Tail {
+                        bytes: self.large_file_config().tail_bytes,
+                    },
+                });
+                self.on_resolve_large_files(files, mode);
+            }
+            Some(PromptAction::Overwrite {
+                filename,
+                verbatim,
+                context,
+            }) => {
+                if key == Some('y') {
+                    self.write_filtered_logs(&filename, verbatim, context);
+                } else {
+                    self.notify_info(format!("Not written ({} kept)", filename));
+                }
+            }
+            None => {}
+        }
+    }
+
+    fn on_resolve_large_files(&mut self, prompt: LargeFilePrompt, mode: Option<IndexMode>) {
+        let Some(mode) = mode else {
+            self.notify_info("Open cancelled");
+            return;
+        };
+
+        let config = self.large_file_config();
+        let files = prompt
+            .paths
+            .into_iter()
+            .map(|path| {
+                let oversized = prompt.oversized.iter().any(|(p, _)| *p == path);
+                let file_mode = if oversized {
+                    mode
+                } else {
+                    let size = std::fs::metadata(&path).map(|m| m.len()).unwrap_or(0);
+                    default_index_mode(&config, size)
+                };
+                (path, file_mode)
+            })
+            .collect();
+        self.pending_open = Some(OpenRequest { files });
+    }
+
+    // View option handlers
+
+    fn on_toggle_wrap(&mut self) {
+        self.wrap_mode = !self.wrap_mode;
+        self.visual_cache.set_wrap_mode(self.wraps());
+        self.notify_info(if self.wrap_mode {
+            "Wrap mode enabled".to_string()
+        } else {
+            "Wrap mode disabled".to_string()
+        });
+    }
+
+    /// Get the visual cache (for UI rendering).
+    pub fn visual_cache(&self) -> &VisualLineCache {
+        &self.visual_cache
+    }
+
+    /// Get mutable visual cache.
+    pub fn visual_cache_mut(&mut self) -> &mut VisualLineCache {
+        &mut self.visual_cache
+    }
+
+    /// Get the total number of visual lines (approximation).
+    pub fn total_visual_lines(&self) -> usize {
+        // Return an estimate based on filtered count
+        self.filtered_len()
+    }
+
+    /// Get the number of lines in storage.
+    pub fn total_lines(&self) -> usize {
+        self.storage.as_ref().map(|s| s.len()).unwrap_or(0)
+    }
+
+    /// Clear search when filters change.
+    fn clear_search_on_refilter(&mut self) {
+        self.cancel_search_job();
+        self.search_query = None;
+        self.search_stack.clear();
+        self.search_state = None;
+    }
+
+    /// Initialize search state with a query.
+    ///
+    /// Highlighting is independent of the total count: `get_line_matches`
+    /// scans each line on screen when it is drawn, so matches show up on the
+    /// next frame while a background job is still counting.
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub fn init_search_state(&mut self, query: String) {
+        if query.is_empty() {
+            self.clear_search();
+            return;
+        }
+        let matcher = search::query_matcher(&query, self.case_mode, self.whole_word);
+        let within: Vec<BMHMatcher> = self
+            .search_stack
+            .iter()
+            .map(|outer| search::query_matcher(outer, self.case_mode, self.whole_word))
+            .collect();
+
+        self.cancel_search_job();
+        let scope = self.search_scope();
+        let background = self.storage.as_ref().is_some_and(|storage| {
+            storage.has_unindexed_blocks() || scope.len() > BACKGROUND_SEARCH_LINES
+        });
+
+        // Compute total matches and first match position (before creating SearchState);
+        // large scans report them incrementally from a background job instead
+        let (total, first_position) = if background {
+            if let Some(storage) = &self.storage {
+                self.search_job = Some(search::spawn(
+                    format!("search '{}'", query),
+                    storage.clone(),
+                    self.filtered_indices[scope.clone()].to_vec(),
+                    scope.start,
+                    matcher.clone(),
+                    within.clone(),
+                ));
+            }
+            (0, None)
+        } else {
+            self.compute_total_matches(&matcher, &within, scope.clone())
+        };
+        tracing::debug!(matches = total, background, "search started");
+
+        // Create the search state with cached values
+        let state = SearchState {
+            query: if matcher.ignores_case() {
+                query.to_lowercase()
+            } else {
+                query.clone()
+            },
+            matcher,
+            current_idx: 0,
+            current_position: first_position,
+            total_matches: total,
+            match_cache: LruCache::new(NonZeroUsize::new(100).unwrap()),
+            scope,
+            within,
+        };
+        self.search_state = Some(state);
+        self.search_query = Some(query);
+
+        // Navigate to first match if any
+        if total > 0 {
+            self.jump_to_match(0);
+        }
+    }
+
+    /// Compute total matches and optionally first match position.
+    fn compute_total_matches(
+        &self,
+        matcher: &BMHMatcher,
+        within: &[BMHMatcher],
+        scope: Range<usize>,
+    ) -> (usize, Option<MatchPosition>) {
+        let Some(storage) = &self.storage else {
+            return (0, None);
+        };
+        let first = scope.start;
+        search::scan_matches(
+            storage.as_ref(),
+            &self.filtered_indices[scope],
+            first,
+            matcher,
+            within,
+        )
+    }
+
+    /// Filtered lines a new search covers: the `:range`, or every line.
+    fn search_scope(&self) -> Range<usize> {
+        let len = self.filtered_indices.len();
+        let Some(range) = &self.search_range else {
+            return 0..len;
+        };
+        let start = self
+            .filtered_indices
+            .partition_point(|&idx| idx < range.first);
+        let end = match range.last {
+            Some(last) => self.filtered_indices.partition_point(|&idx| idx <= last),
+            None => len,
+        };
+        start..end.max(start)
+    }
+
+    /// Apply progress from the background search, jumping to the first match once found.
+    pub fn poll_search_job(&mut self) {
+        let Some(job) = &mut self.search_job else {
+            return;
+        };
+        let updates = job.poll();
+        let finished = job.is_finished();
+        if finished {
+            self.search_job = None;
+        }
+
+        let Some(state) = &mut self.search_state else {
+            return;
+        };
+        let had_match = state.total_matches > 0;
+        for update in updates {
+            state.total_matches += update.matches;
+            if state.current_position.is_none() {
+                state.current_position = update.first;
+            }
+        }
+        if !had_match && state.total_matches > 0 {
+            self.jump_to_match(0);
+        }
+    }
+
+    /// The lines in view and the source they come from, for an overlay job.
+    fn overlay_input(&self) -> Option<(Arc<dyn LineSource + Send + Sync>, Vec<usize>)> {
+        let storage = self.storage.clone()?;
+        Some((storage, self.filtered_indices.clone()))
+    }
+
+    /// Compute an overlay with `work`, which sends it and goes over `lines`
+    /// lines. Work over many lines runs as a job and the overlay opens when
+    /// it is done; a job already running is cancelled.
+    fn run_overlay(
+        &mut self,
+        name: String,
+        lines: usize,
+        work: impl FnOnce(JobContext<Overlay>) + Send + 'static,
+    ) -> Mode {
+        self.overlay_job = None;
+        if lines <= BACKGROUND_OVERLAY_LINES {
+            return match Job::run_here(work).pop() {
+                Some(overlay) => self.open_overlay(overlay),
+                None => Mode::Normal,
+            };
+        }
+        self.notify_info(format!("Running {} over {} lines...", name, lines));
+        self.overlay_job = Some(Job::spawn(name, lines, work));
+        Mode::Normal
+    }
+
+    /// Show a computed overlay, or say why there is nothing to show.
+    fn open_overlay(&mut self, overlay: Overlay) -> Mode {
+        match overlay {
+            Overlay::Stats(rows) if rows.is_empty() => {
+                self.notify_warn("No stats for the lines in view");
+            }
+            Overlay::Stats(rows) => {
+                self.stats_view = Some(StatsView { rows, selected: 0 });
+                return Mode::Stats;
+            }
+            Overlay::Exceptions(groups) if groups.is_empty() => {
+                self.notify_warn("No exceptions in the lines in view");
+            }
+            Overlay::Exceptions(groups) => {
+                self.exceptions_view = Some(ExceptionsView {
+                    groups,
+                    selected: 0,
+                });
+                return Mode::Exceptions;
+            }
+            Overlay::Sql(Ok(view)) if view.result.rows.is_empty() => {
+                self.notify_warn("Query returned no rows");
+            }
+            Overlay::Sql(Ok(view)) => {
+                let rows = view.result.rows.len();
+                let mut status = format!(
+                    "{}{} row{}",
+                    if view.result.truncated { "First " } else { "" },
+                    rows,
+                    if rows == 1 { "" } else { "s" },
+                );
+                if self.filtered_indices.len() > query::MAX_TABLE_LINES {
+                    status.push_str(&format!(
+                        " (logs holds the first {} filtered lines)",
+                        query::MAX_TABLE_LINES
+                    ));
+                }
+                self.notify_info(status);
+                self.sql_view = Some(view);
+                return Mode::Sql;
+            }
+            Overlay::Sql(Err(message)) => self.notify_error(message),
+        }
+        Mode::Normal
+    }
+
+    /// Open the overlay of a finished overlay job, unless another view was
+    /// opened in the meantime.
+    fn poll_overlay_job(&mut self) {
+        let Some(job) = &mut self.overlay_job else {
+            return;
+        };
+        let overlay = job.poll().pop();
+        let name = job.name().to_string();
+        if overlay.is_some() || job.is_finished() {
+            self.overlay_job = None;
+        }
+        let Some(overlay) = overlay else {
+            return;
+        };
+        if self.mode == Mode::Normal {
+            self.mode = self.open_overlay(overlay);
+        } else {
+            self.notify_warn(format!("{} is done; run it again to see it", name));
+        }
+    }
+
+    /// Apply progress from every background job.
+    pub fn poll_jobs(&mut self) {
+        self.poll_search_job();
+        self.poll_overlay_job();
+        let mut finished = Vec::new();
+        for (filename, job) in &mut self.export_jobs {
+            for result in job.poll() {
+                finished.push(match result {
+                    Ok(written) => (written.message(filename), Severity::Info),
+                    Err(e) => (format!("Error: {}", e), Severity::Error),
+                });
+            }
+        }
+        self.export_jobs.retain(|(_, job)| !job.is_finished());
+        for (message, severity) in finished {
+            self.post(message, severity);
+        }
+    }
+
+    /// Show `text` in the status bar as information.
+    pub fn notify_info(&mut self, text: impl Into<String>) {
+        self.set_status(text.into(), Severity::Info);
+    }
+
+    /// Show `text` in the status bar as a warning: nothing found, nothing
+    /// to do, or an action refused.
+    pub fn notify_warn(&mut self, text: impl Into<String>) {
+        self.set_status(text.into(), Severity::Warning);
+    }
+
+    /// Show `text` in the status bar as an error: a failure or a command
+    /// that could not be understood.
+    pub fn notify_error(&mut self, text: impl Into<String>) {
+        self.set_status(text.into(), Severity::Error);
+    }
+
+    /// Set the status message; a later one set before the next update
+    /// replaces it.
+    fn set_status(&mut self, text: String, severity: Severity) {
+        self.status_message = text;
+        self.status_kind = severity;
+    }
+
+    /// Post a status message right away. Messages still up stay queued
+    /// behind it rather than being replaced.
+    pub fn post(&mut self, text: String, severity: Severity) {
+        self.update_notifications();
+        self.notifications
+            .push(text.clone(), severity, Instant::now());
+        self.set_status(text, severity);
+    }
+
+    /// Queue a status message set since the last update, then show the
+    /// newest one not expired. A cleared status message dismisses them all.
+    pub fn update_notifications(&mut self) {
+        self.update_notifications_at(Instant::now());
+    }
+
+    fn update_notifications_at(&mut self, now: Instant) {
+        let shown = self.notifications.current().map(|n| n.text.as_str());
+        if self.status_message != shown.unwrap_or_default() {
+            if self.status_message.is_empty() {
+                self.notifications.dismiss();
+            } else {
+                let text = self.status_message.clone();
+                self.notifications.push(text, self.status_kind, now);
+            }
+        }
+        // A message set directly on `status_message` is information
+        self.status_kind = Severity::Info;
+        self.notifications.expire(now);
+        self.status_message = self
+            .notifications
+            .current()
+            .map(|n| n.text.clone())
+            .unwrap_or_default();
+    }
+
+    /// Severity of the status message shown.
+    pub fn status_severity(&self) -> Severity {
+        match self.notifications.current() {
+            Some(current) if current.text == self.status_message => current.severity,
+            _ => self.status_kind,
+        }
+    }
+
+    /// Name and percent done of each running job, oldest first.
+    pub fn running_jobs(&self) -> Vec<(&str, usize)> {
+        let search = self.search_job.iter();
+        let exports = self.export_jobs.iter().map(|(_, job)| job);
+        let overlay = self.overlay_job.iter();
+        let mut jobs: Vec<(Instant, &str, usize)> = search
+            .map(|job| (job.started(), job.name(), job.percent()))
+            .chain(exports.map(|job| (job.started(), job.name(), job.percent())))
+            .chain(overlay.map(|job| (job.started(), job.name(), job.percent())))
+            .collect();
+        jobs.sort_by_key(|&(started, _, _)| started);
+        jobs.into_iter()
+            .map(|(_, name, percent)| (name, percent))
+            .collect()
+    }
+
+    /// Cancel the most recently started job, which the jobs overlay lists
+    /// last. Returns the status message, or None if no job is running.
+    fn cancel_focused_job(&mut self) -> Option<String> {
+        enum Running {
+            Export(usize),
+            Search,
+            Overlay,
+        }
+        let exports = self
+            .export_jobs
+            .iter()
+            .enumerate()
+            .map(|(idx, (_, job))| (job.started(), Running::Export(idx)));
+        let search = self
+            .search_job
+            .iter()
+            .map(|job| (job.started(), Running::Search));
+        let overlay = self
+            .overlay_job
+            .iter()
+            .map(|job| (job.started(), Running::Overlay));
+        let (_, newest) = search
+            .chain(exports)
+            .chain(overlay)
+            .max_by_key(|(started, _)| *started)?;
+        match newest {
+            Running::Export(idx) => {
+                let (filename, job) = self.export_jobs.remove(idx);
+                job.cancel();
+                Some(format!("Cancelled writing {}", filename))
+            }
+            Running::Search => {
+                self.cancel_search_job();
+                Some(format!(
+                    "Search cancelled ({} matches so far)",
+                    self.total_matches()
+                ))
+            }
+            Running::Overlay => {
+                let job = self.overlay_job.take()?;
+                let message = format!("Cancelled {}", job.name());
+                job.cancel();
+                Some(message)
+            }
+        }
+    }
+
+    /// Search progress in percent while a background search is running.
+    pub fn search_progress(&self) -> Option<usize> {
+        self.search_job.as_ref().map(|job| job.percent())
+    }
+
+    /// Stop a running background search, keeping the matches found so far.
+    fn cancel_search_job(&mut self) {
+        if let Some(job) = self.search_job.take() {
+            job.cancel();
+        }
+    }
+
+    /// Clear search state.
+    pub fn clear_search(&mut self) {
+        self.cancel_search_job();
+        self.search_query = None;
+        self.search_stack.clear();
+        self.search_state = None;
+    }
+
+    /// Go back to the search the current one refines, or clear the search
+    /// if it refines nothing.
+    fn on_pop_search(&mut self) {
+        if self.search_query.is_none() {
+            return;
+        }
+        match self.search_stack.pop() {
+            Some(outer) => {
+                self.notify_info(format!("Back to search '{}'", outer));
+                self.init_search_state(outer);
+            }
+            None => {
+                self.clear_search();
+                self.notify_info("Search cleared");
+            }
+        }
+    }
+
+    /// Refinement stack of the current search, e.g. `'timeout' › 'retry'`.
+    pub fn search_breadcrumb(&self) -> Option<String> {
+        let query = self.search_query.as_ref()?;
+        let terms: Vec<String> = self
+            .search_stack
+            .iter()
+            .chain(std::iter::once(query))
+            .map(|term| format!("'{}'", term))
+            .collect();
+        Some(terms.join(" › "))
+    }
+
+    /// Get matches for a specific line (with caching).
+    pub fn get_line_matches(&mut self, filtered_idx: usize) -> Vec<(usize, usize)> {
+        let Some(state) = &mut self.search_state else {
+            return Vec::new();
+        };
+
+        if !state.scope.contains(&filtered_idx) {
+            return Vec::new();
+        }
+
+        // Check cache first
+        if let Some(matches) = state.match_cache.get(&filtered_idx) {
+            self.perf.match_cache.record(true);
+            return matches.clone();
+        }
+        self.perf.match_cache.record(false);
+
+        // Get the line text
+        let Some(storage) = &self.storage else {
+            return Vec::new();
+        };
+        let Some(&line_idx) = self.filtered_indices.get(filtered_idx) else {
+            return Vec::new();
+        };
+        let Some(line) = storage.get_line(line_idx) else {
+            return Vec::new();
+        };
+
+        // Find all matches
+        let matches = search::line_matches(line.as_bytes(), &state.matcher, &state.within);
+
+        // Cache the result (clone for return value, original goes into cache)
+        let result = matches.clone();
+        state.match_cache.put(filtered_idx, matches);
+
+        result
+    }
+
+    /// Search matches in `text`, the filtered line at `filtered_idx` as
+    /// shown in the column view.
+    pub fn text_matches(&self, filtered_idx: usize, text: &str) -> Vec<(usize, usize)> {
+        let Some(state) = &self.search_state else {
+            return Vec::new();
+        };
+        if !state.scope.contains(&filtered_idx) {
+            return Vec::new();
+        }
+        search::line_matches(text.as_bytes(), &state.matcher, &state.within)
+    }
+
+    /// Get total match count across all filtered lines.
+    /// Returns cached value for O(1) performance.
+    pub fn total_matches(&self) -> usize {
+        self.search_state
+            .as_ref()
+            .map(|s| s.total_matches)
+            .unwrap_or(0)
+    }
+
+    /// Get current match display string (e.g., "3/42", or "3/42+" while
+    /// the background count is still running).
+    pub fn current_match_display(&self) -> Option<String> {
+        let state = self.search_state.as_ref()?;
+        if state.total_matches == 0 {
+            return None;
+        }
+        let counting = if self.search_job.is_some() { "+" } else { "" };
+        Some(format!(
+            "{}/{}{}",
+            state.current_idx + 1,
+            state.total_matches,
+            counting
+        ))
+    }
+
+    /// Navigate to next match (with wrap-around).
+    pub fn next_match(&mut self) {
+        let total = self.total_matches();
+        if total == 0 {
+            return;
+        }
+
+        let next_idx = if let Some(state) = &self.search_state {
+            (state.current_idx + 1) % total
+        } else {
+            0
+        };
+
+        self.jump_to_match(next_idx);
+    }
+
+    /// Navigate to previous match (with wrap-around).
+    pub fn prev_match(&mut self) {
+        let total = self.total_matches();
+        if total == 0 {
+            return;
+        }
+
+        let prev_idx = if let Some(state) = &self.search_state {
+            if state.current_idx == 0 {
+                total - 1
+            } else {
+                state.current_idx - 1
+            }
+        } else {
+            0
+        };
+
+        self.jump_to_match(prev_idx);
+    }
+
+    /// Jump to a specific match by index.
+    fn jump_to_match(&mut self, match_idx: usize) {
+        let Some(state) = &mut self.search_state else {
+            return;
+        };
+
+        if state.total_matches == 0 || match_idx >= state.total_matches {
+            return;
+        }
+
+        // Update current index
+        state.current_idx = match_idx;
+
+        // Find the position of this match
+        if let Some(position) = self.get_match_position(match_idx) {
+            // Cache the position
+            if let Some(ref mut state) = self.search_state {
+                state.current_position = Some(position);
+            }
+
+            // Update vertical position
+            self.selected_line = position.filtered_idx;
+            self.clamp_scroll();
+
+            // Horizontal auto-scroll; match offsets are in the raw text,
+            // not in the row of the column view
+            if self.columns.is_some() {
+                return;
+            }
+            let Some(storage) = &self.storage else { return };
+            let Some(&line_idx) = self.filtered_indices.get(position.filtered_idx) else {
+                return;
+            };
+            let Some(line) = storage.get_line(line_idx) else {
+                return;
+            };
+            let line_text = line.as_str_lossy();
+
+            // Calculate character offset from byte offset
+            let match_char_pos = byte_to_char_offset(&line_text, position.byte_offset);
+            // Calculate match length in characters (not bytes) for consistent scroll math
+            let match_text = text::slice_bytes(
+                &line_text,
+                position.byte_offset..position.byte_offset + position.match_len,
+            );
+            let match_char_len = match_text.chars().count();
+
+            let viewport_width = self.viewport_width.get();
+            let margin = 10;
+
+            // Only adjust horizontal scroll if match is outside viewport
+            if match_char_pos < self.horizontal_scroll {
+                // Match is left of viewport - scroll to show it with margin
+                self.horizontal_scroll = match_char_pos.saturating_sub(margin);
+            } else if match_char_pos + match_char_len
+                > self.horizontal_scroll + viewport_width.saturating_sub(margin)
+            {
+                // Match is right of viewport - scroll to show it
+                self.horizontal_scroll =
+                    (match_char_pos + match_char_len + margin).saturating_sub(viewport_width);
+            }
+        }
+    }
+
+    /// Get the position of a match by its global index.
+    fn get_match_position(&self, match_idx: usize) -> Option<MatchPosition> {
+        let state = self.search_state.as_ref()?;
+        let storage = self.storage.as_ref()?;
+
+        let mut current_match = 0;
+
+        for filtered_idx in state.scope.clone() {
+            let line_idx = *self.filtered_indices.get(filtered_idx)?;
+            let line = storage.get_line(line_idx)?;
+            let matches = search::line_matches(line.as_bytes(), &state.matcher, &state.within);
+
+            for (start, end) in matches {
+                if current_match == match_idx {
+                    return Some(MatchPosition {
+                        filtered_idx,
+                        byte_offset: start,
+                        match_len: end - start,
+                    });
+                }
+                current_match += 1;
+            }
+        }
+
+        None
+    }
+
+    /// Check if a specific position is the current match.
+    /// Uses O(1) cached position lookup instead of linear scan.
+    pub fn is_current_match(&self, filtered_idx: usize, byte_offset: usize) -> bool {
+        let Some(state) = &self.search_state else {
+            return false;
+        };
+
+        // Use cached position for O(1) lookup
+        if let Some(pos) = state.current_position {
+            return pos.filtered_idx == filtered_idx && pos.byte_offset == byte_offset;
+        }
+
+        false
+    }
+
+    /// Check if there is an active search.
+    pub fn has_search(&self) -> bool {
+        self.search_state.is_some()
+    }
+
+    /// Get the search query if any.
+    pub fn get_search_query(&self) -> Option<&str> {
+        self.search_query.as_deref()
+    }
+}
+
+impl Default for App {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// `(3/12) src/main.rs:10:5: mismatched types` for entry `pos`.
+fn error_status(entries: &[QuickfixEntry], pos: usize) -> String {
+    let entry = &entries[pos];
+    let mut status = format!("({}/{}) {}", pos + 1, entries.len(), entry.location());
+    if !entry.message.is_empty() {
+        status.push_str(&format!(": {}", entry.message));
+    }
+    status
+}
+
+/// Append lines `range` of `storage` to the file at `path`.
+fn spill_lines(storage: &LogStorage, range: Range<usize>, path: &Path) -> std::io::Result<()> {
+    let file = OpenOptions::new().create(true).append(true).open(path)?;
+    let mut out = BufWriter::new(file);
+    for idx in range {
+        if let Some(line) = storage.get_line(idx) {
+            out.write_all(line.as_bytes())?;
+            out.write_all(b"\n")?;
+        }
+    }
+    out.flush()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::log_storage::storage_from;
+    use ratatui::style::Modifier;
+    use std::io::Write;
+    use tempfile::NamedTempFile;
+
+    fn create_test_storage() -> LogStorage {
+        let mut temp_file = NamedTempFile::new().unwrap();
+        writeln!(temp_file, "Line 1").unwrap();
+        writeln!(temp_file, "Line 2").unwrap();
+        writeln!(temp_file, "Line 3").unwrap();
+        LogStorage::from_file(temp_file.path()).unwrap()
+    }
+
+    #[test]
+    fn test_app_new() {
+        let app = App::new();
+        assert!(app.storage.is_none());
+        assert!(app.filtered_indices.is_empty());
+        assert_eq!(app.mode, Mode::Normal);
+    }
+
+    #[test]
+    fn test_set_storage() {
+        let mut app = App::new();
+        let storage = create_test_storage();
+
+        app.set_storage(storage);
+
+        assert!(app.storage.is_some());
+        assert_eq!(app.total_lines(), 3);
+    }
+
+    #[test]
+    fn test_get_line() {
+        let mut app = App::new();
+        let storage = create_test_storage();
+        app.set_storage(storage);
+
+        let line = app.get_line(0).unwrap();
+        assert_eq!(line.as_str_lossy().trim(), "Line 1");
+
+        let line = app.get_line(1).unwrap();
+        assert_eq!(line.as_str_lossy().trim(), "Line 2");
+    }
+
+    #[test]
+    fn test_search_init_and_clear() {
+        let mut app = App::new();
+
+        // Initially no search
+        assert!(!app.has_search());
+        assert_eq!(app.get_search_query(), None);
+
+        // Initialize search
+        app.init_search_state("test".to_string());
+
+        // Now has search
+        assert!(app.has_search());
+        assert_eq!(app.get_search_query(), Some("test"));
+
+        // Clear search
+        app.clear_search();
+
+        // No search anymore
+        assert!(!app.has_search());
+        assert_eq!(app.get_search_query(), None);
+    }
+
+    #[test]
+    fn test_search_matches() {
+        let mut app = App::new();
+        let mut temp_file = NamedTempFile::new().unwrap();
+        writeln!(temp_file, "This is a test line").unwrap();
+        writeln!(temp_file, "Another test line").unwrap();
+        writeln!(temp_file, "No match here").unwrap();
+        let storage = LogStorage::from_file(temp_file.path()).unwrap();
+        app.set_storage(storage);
+
+        // Initialize search for "test"
+        app.init_search_state("test".to_string());
+
+        // Check total matches (should be 2)
+        assert_eq!(app.total_matches(), 2);
+
+        // Get line matches for line 0
+        let matches = app.get_line_matches(0);
+        assert_eq!(matches.len(), 1);
+        assert_eq!(matches[0], (10, 14)); // "test" position in "This is a test line"
+
+        // Get line matches for line 1
+        let matches = app.get_line_matches(1);
+        assert_eq!(matches.len(), 1);
+        assert_eq!(matches[0], (8, 12)); // "test" position in "Another test line"
+
+        // Get line matches for line 2 (no matches)
+        let matches = app.get_line_matches(2);
+        assert_eq!(matches.len(), 0);
+    }
+
+    #[test]
+    fn test_search_case_insensitive() {
+        let mut app = App::new();
+        let mut temp_file = NamedTempFile::new().unwrap();
+        writeln!(temp_file, "ERROR message").unwrap();
+        writeln!(temp_file, "error message").unwrap();
+        writeln!(temp_file, "Error message").unwrap();
+        let storage = LogStorage::from_file(temp_file.path()).unwrap();
+        app.set_storage(storage);
+
+        // Search for lowercase "error" - should match all variations
+        app.init_search_state("error".to_string());
+
+        // Each line should have one match
+        assert_eq!(app.get_line_matches(0).len(), 1);
+        assert_eq!(app.get_line_matches(1).len(), 1);
+        assert_eq!(app.get_line_matches(2).len(), 1);
+    }
+
+    #[test]
+    fn test_smart_case_search() {
+        let (_file, mut app) = app_with_lines(&["Timeout here", "timeout there", "TIMEOUT"]);
+        app.init_search_state("Timeout".to_string());
+        assert_eq!(app.total_matches(), 3);
+
+        app.input_buffer = "set smartcase".to_string();
+        app.process_message(Msg::SubmitCommand);
+        assert_eq!(app.case_mode, CaseMode::Smart);
+        assert_eq!(app.total_matches(), 1);
+        assert_eq!(app.get_line_matches(1), vec![]);
+        app.init_search_state("timeout".to_string());
+        assert_eq!(app.total_matches(), 3);
+
+        app.process_message(Msg::ToggleCase);
+        assert_eq!(app.status_message, "Search: match case");
+        assert_eq!(app.total_matches(), 1);
+        assert_eq!(app.get_line_matches(1), vec![(0, 7)]);
+    }
+
+    #[test]
+    fn test_whole_word_search_and_filters() {
+        let (_file, mut app) = app_with_lines(&["error: disk", "errors: 0", "no_error here"]);
+        app.init_search_state("w error".to_string());
+        assert_eq!(app.total_matches(), 1);
+        assert_eq!(app.get_line_matches(0), vec![(0, 5)]);
+        app.filters.add(FilterKind::Include, "error".to_string());
+        app.update_filtered_logs();
+        app.init_search_state("error".to_string());
+        assert_eq!(app.total_matches(), 3);
+
+        app.input_buffer = "set word".to_string();
+        app.process_message(Msg::SubmitCommand);
+        assert_eq!(app.status_message, "Searches and filters match whole words");
+        assert_eq!(app.filtered_indices, vec![0]);
+        assert_eq!(app.total_matches(), 1);
+
+        app.input_buffer = "set noword".to_string();
+        app.process_message(Msg::SubmitCommand);
+        assert_eq!(app.filtered_indices, vec![0, 1, 2]);
+        assert_eq!(app.total_matches(), 3);
+    }
+
+    #[test]
+    fn test_search_preview_while_typing() {
+        let (_file, mut app) = app_with_lines(&[
+            "start",
+            "timeout one",
+            "retry",
+            "timeout two, retry",
+            "done",
+        ]);
+        let later = || Instant::now() + Duration::from_secs(1);
+        app.init_search_state("retry".to_string());
+        assert_eq!(app.selected_line, 2);
+        app.process_message(Msg::ScrollDown);
+
+        app.process_message(Msg::EnterSearch);
+        app.input_buffer.clear();
+        for c in "time".chars() {
+            app.process_message(Msg::SearchTypeChar(c));
+        }
+        // Nothing happens until typing pauses
+        app.poll_search_preview_at(Instant::now());
+        assert_eq!(app.get_search_query(), Some("retry"));
+        app.poll_search_preview_at(later());
+        assert_eq!(app.get_search_query(), Some("time"));
+        assert_eq!(app.selected_line, 1);
+        assert_eq!(app.get_line_matches(1), vec![(0, 4)]);
+
+        // Esc brings back the search and the cursor
+        app.process_message(Msg::CancelSearch);
+        assert_eq!(app.get_search_query(), Some("retry"));
+        assert_eq!(app.total_matches(), 2);
+        assert_eq!(app.selected_line, 3);
+
+        // A refinement previews within the search it refines
+        app.process_message(Msg::EnterSearch);
+        app.input_buffer = "&tim".to_string();
+        app.process_message(Msg::SearchTypeChar('e'));
+        app.poll_search_preview_at(later());
+        assert_eq!(app.total_matches(), 1);
+        app.process_message(Msg::SubmitSearch);
+        assert_eq!(app.mode, Mode::Normal);
+        assert_eq!(app.search_breadcrumb().as_deref(), Some("'retry' › 'time'"));
+        assert_eq!(app.selected_line, 3);
+    }
+
+    #[test]
+    fn test_refined_search_stack() {
+        let (_file, mut app) = app_with_lines(&[
+            "timeout, retry 1",
+            "retry after error",
+            "timeout, giving up",
+            "timeout, retry 2",
+        ]);
+        let search = |app: &mut App, input: &str| {
+            app.mode = Mode::SearchInput;
+            app.input_buffer = input.to_string();
+            app.process_message(Msg::SubmitSearch);
+        };
+
+        search(&mut app, "timeout");
+        assert_eq!(app.total_matches(), 3);
+        search(&mut app, "&retry");
+        assert_eq!(app.total_matches(), 2);
+        assert!(app.get_line_matches(1).is_empty());
+        assert_eq!(
+            app.search_breadcrumb().as_deref(),
+            Some("'timeout' › 'retry'")
+        );
+
+        app.process_message(Msg::PopSearch);
+        assert_eq!(app.get_search_query(), Some("timeout"));
+        assert_eq!(app.total_matches(), 3);
+        app.process_message(Msg::PopSearch);
+        assert!(!app.has_search());
+    }
+
+    #[test]
+    fn test_search_filter_clears_search() {
+        let mut app = App::new();
+        let mut temp_file = NamedTempFile::new().unwrap();
+        writeln!(temp_file, "test line 1").unwrap();
+        writeln!(temp_file, "test line 2").unwrap();
+        let storage = LogStorage::from_file(temp_file.path()).unwrap();
+        app.set_storage(storage);
+
+        // Set up a search
+        app.init_search_state("test".to_string());
+        assert!(app.has_search());
+
+        // Any re-filter clears the search, however the filters changed
+        app.filters.add_include("1");
+        app.update_filtered_logs();
+
+        // Search should be cleared
+        assert!(!app.has_search());
+        assert_eq!(app.get_search_query(), None);
+    }
+
+    #[test]
+    fn test_bookmark_survives_refilter() {
+        let mut app = App::new();
+        app.set_storage(create_test_storage());
+        app.selected_line = 2;
+        app.process_message(Msg::ToggleBookmark);
+        assert!(app.bookmarks.contains(2));
+
+        app.filters.add_include("Line 3");
+        app.update_filtered_logs();
+        assert!(app.is_bookmarked(0));
+    }
+
+    #[test]
+    fn test_bookmark_list_jumps_and_clears() {
+        let (_file, mut app) = app_with_lines(&["a", "ERROR b", "c", "ERROR d"]);
+        for line in [1, 3] {
+            app.selected_line = line;
+            app.process_message(Msg::ToggleBookmark);
+        }
+        app.filters.add_include("error");
+        app.update_filtered_logs();
+        app.selected_line = 0;
+
+        app.process_message(Msg::OpenBookmarkList);
+        assert_eq!(app.mode, Mode::Bookmarks);
+        app.process_message(Msg::BookmarkListDown);
+        app.process_message(Msg::JumpToBookmark);
+        assert_eq!(app.mode, Mode::Normal);
+        assert_eq!(app.selected_storage_index(), Some(3));
+
+        app.mode = Mode::Command;
+        app.input_buffer = "bookmark clear".to_string();
+        app.process_message(Msg::SubmitCommand);
+        assert!(app.bookmarks.is_empty());
+        assert_eq!(app.status_message, "Cleared 2 bookmarks");
+        app.process_message(Msg::OpenBookmarkList);
+        assert_eq!(app.mode, Mode::Normal);
+    }
+
+    #[test]
+    fn test_tabs_keep_their_own_state() {
+        let (_api, mut app) = app_with_lines(&["api one", "api two", "api three"]);
+        app.filters.add_include("two");
+        app.update_filtered_logs();
+        let mut worker = NamedTempFile::new().unwrap();
+        writeln!(worker, "worker one\nworker two").unwrap();
+
+        app.input_buffer = format!("tabnew {}", worker.path().display());
+        app.process_message(Msg::SubmitCommand);
+        assert_eq!(app.tab_count(), 2);
+        assert_eq!(app.active_tab(), 1);
+        // The main loop loads the files into the new tab
+        app.take_open_request().unwrap();
+        app.set_storage(LogStorage::from_file(worker.path()).unwrap());
+        assert_eq!(app.filtered_len(), 2);
+        assert!(app.filters.is_empty());
+        app.selected_line = 1;
+
+        // `g` jumps to the top, but `gt` leaves the cursor where it was
+        let key = |c| crossterm::event::KeyEvent::from(crossterm::event::KeyCode::Char(c));
+        app.handle_key(key('g'));
+        app.handle_key(key('t'));
+        assert_eq!(app.active_tab(), 0);
+        assert_eq!(app.filtered_len(), 1);
+        assert_eq!(app.filters.len(), 1);
+        app.handle_key(key('g'));
+        app.handle_key(key('T'));
+        assert_eq!(app.active_tab(), 1);
+        assert_eq!(app.selected_line, 1);
+
+        app.input_buffer = "tabclose".to_string();
+        app.process_message(Msg::SubmitCommand);
+        assert_eq!(app.tab_count(), 1);
+        assert_eq!(app.filters.len(), 1);
+        app.input_buffer = "tabclose".to_string();
+        app.process_message(Msg::SubmitCommand);
+        assert_eq!(app.status_message, "Cannot close the last tab (:q quits)");
+    }
+
+    #[test]
+    fn test_error_list_from_build_output() {
+        let (_file, mut app) = app_with_lines(&[
+            "   Compiling app v0.1.0",
+            "error[E0425]: cannot find value `x` in this scope",
+            "  --> src/main.rs:4:13",
+            "tests/test_api.py:42: AssertionError",
+        ]);
+        let key = |c| crossterm::event::KeyEvent::from(crossterm::event::KeyCode::Char(c));
+
+        app.handle_key(key(']'));
+        app.handle_key(key('q'));
+        assert_eq!(app.selected_line, 1);
+        assert_eq!(
+            app.status_message,
+            "(1/2) src/main.rs:4:13: cannot find value `x` in this scope"
+        );
+        app.handle_key(key(']'));
+        app.handle_key(key('q'));
+        assert_eq!(app.selected_line, 3);
+        app.handle_key(key(']'));
+        app.handle_key(key('q'));
+        assert_eq!(app.status_message, "No more errors below");
+
+        app.mode = Mode::Command;
+        app.input_buffer = "errors".to_string();
+        app.process_message(Msg::SubmitCommand);
+        assert_eq!(app.mode, Mode::Errors);
+        assert_eq!(app.quickfix_selected, 1);
+        app.process_message(Msg::ErrorListUp);
+        app.process_message(Msg::JumpToError);
+        assert_eq!(app.selected_line, 1);
+    }
+
+    #[test]
+    fn test_pod_list_pause_and_mute() {
+        let mut app = App::new();
+        app.set_storage(LogStorage::empty());
+        let lines = |texts: &[&str]| -> Vec<FollowedLine> {
+            texts
+                .iter()
+                .map(|text| FollowedLine {
+                    file_index: 0,
+                    text: text.as_bytes().to_vec(),
+                    timestamp: None,
+                })
+                .collect()
+        };
+        app.append_lines(lines(&["[pod/api-a/api] one", "[pod/api-b/api] two"]));
+        assert_eq!(
+            app.pod_badge("[pod/api-b/api] two").map(|(range, _)| range),
+            Some(0..16)
+        );
+
+        app.mode = Mode::Command;
+        app.input_buffer = "pods".to_string();
+        app.process_message(Msg::SubmitCommand);
+        assert_eq!(app.mode, Mode::Pods);
+        app.process_message(Msg::TogglePodPause);
+        assert_eq!(app.status_message, "Paused api-a");
+        app.append_lines(lines(&["[pod/api-a/api] held", "[pod/api-b/api] three"]));
+        assert_eq!(app.total_lines(), 3);
+        app.process_message(Msg::TogglePodPause);
+        assert_eq!(app.status_message, "Resumed api-a (1 held line)");
+        assert_eq!(app.total_lines(), 4);
+
+        app.process_message(Msg::PodListDown);
+        app.process_message(Msg::TogglePodMute);
+        assert!(app.is_pod_muted("api-b"));
+        assert_eq!(app.filtered_len(), 2);
+        app.process_message(Msg::TogglePodMute);
+        assert!(app.filters.is_empty());
+        assert_eq!(app.filtered_len(), 4);
+    }
+
+    #[test]
+    fn test_command_history_up_down() {
+        let mut app = App::new();
+        app.command_history = CommandHistory::new();
+        app.set_storage(create_test_storage());
+        for command in ["filter error", "gutter", "filter-out retry"] {
+            app.mode = Mode::Command;
+            app.input_buffer = command.to_string();
+            app.process_message(Msg::SubmitCommand);
+        }
+
+        app.mode = Mode::Command;
+        app.process_message(Msg::CommandTypeChar('f'));
+        app.process_message(Msg::CommandHistoryOlder);
+        assert_eq!(app.input_buffer, "filter-out retry");
+        app.process_message(Msg::CommandHistoryOlder);
+        assert_eq!(app.input_buffer, "filter error");
+        app.process_message(Msg::CommandHistoryOlder);
+        assert_eq!(app.input_buffer, "filter error");
+        app.process_message(Msg::CommandHistoryNewer);
+        app.process_message(Msg::CommandHistoryNewer);
+        assert_eq!(app.input_buffer, "f");
+
+        app.process_message(Msg::CancelCommand);
+        app.mode = Mode::Command;
+        app.process_message(Msg::CommandHistoryOlder);
+        assert_eq!(app.input_buffer, "filter-out retry");
+    }
+
+    #[test]
+    fn test_session_round_trip() {
+        let lines = [
+            "2024-01-01 10:00:00 INFO start",
+            "2024-01-01 10:00:01 DEBUG healthcheck",
+            "2024-01-01 10:00:02 ERROR timeout talking to db",
+            "2024-01-01 10:00:03 ERROR timeout, retry 1",
+            "2024-01-01 10:00:04 INFO done",
+        ];
+        let (file, mut app) = app_with_lines(&lines);
+        let dir = tempfile::TempDir::new().unwrap();
+        let session_path = dir.path().join("session.toml");
+        app.session_path = Some(session_path.clone());
+        let run = |app: &mut App, command: &str| {
+            app.mode = Mode::Command;
+            app.input_buffer = command.to_string();
+            app.process_message(Msg::SubmitCommand);
+        };
+
+        run(&mut app, "filter-out healthcheck");
+        app.init_search_state("timeout".to_string());
+        app.search_stack.push("timeout".to_string());
+        app.init_search_state("retry".to_string());
+        app.bookmarks.set_note(2, "first timeout");
+        app.wrap_mode = false;
+        app.select_storage_index(4);
+        run(&mut app, "session save");
+        assert_eq!(app.status_message, "Session saved");
+
+        let mut restored = App::new();
+        restored.session_path = Some(session_path.clone());
+        restored.set_storage(LogStorage::from_file(file.path()).unwrap());
+        assert!(restored.restore_session());
+        assert_eq!(restored.filters.len(), 1);
+        assert_eq!(restored.filtered_len(), 4);
+        assert_eq!(restored.search_stack, ["timeout"]);
+        assert_eq!(restored.search_query.as_deref(), Some("retry"));
+        assert!(!restored.wrap_mode);
+        assert_eq!(
+            restored
+                .bookmarks
+                .get(2)
+                .and_then(|mark| mark.note.as_deref()),
+            Some("first timeout")
+        );
+        assert_eq!(restored.selected_storage_index(), Some(4));
+
+        run(&mut restored, "session clear");
+        assert_eq!(
+            restored.status_message,
+            "Session cleared; not saved on quit"
+        );
+        restored.save_session().unwrap();
+        let mut fresh = App::new();
+        fresh.session_path = Some(session_path);
+        fresh.set_storage(LogStorage::from_file(file.path()).unwrap());
+        assert!(!fresh.restore_session());
+        run(&mut fresh, "session load");
+        assert_eq!(fresh.status_message, "No saved session for these files");
+    }
+
+    #[test]
+    fn test_export_bookmarks_writes_report() {
+        let mut app = App::new();
+        app.set_storage(create_test_storage());
+        app.selected_line = 1;
+        app.input_buffer = "note suspicious".to_string();
+        app.execute_command();
+
+        let out = tempfile::NamedTempFile::new().unwrap();
+        let count = app.export_bookmarks(out.path().to_str().unwrap()).unwrap();
+        assert_eq!(count, 1);
+
+        let report = std::fs::read_to_string(out.path()).unwrap();
+        assert!(report.contains("> suspicious"));
+        assert!(report.contains("> 2 | Line 2"));
+    }
+
+    #[test]
+    fn test_chained_commands_report_each_status() {
+        let (_file, mut app) = app_with_lines(&["error a", "error retry", "info"]);
+        app.input_buffer = "filter error; filter-out retry | bogus; list-filters".to_string();
+        let mode = app.execute_command();
+
+        assert_eq!(mode, Mode::FilterList);
+        assert_eq!(app.filtered_indices, vec![0]);
+        assert_eq!(
+            app.status_message,
+            "Added filter: error; Added filter-out: retry; Unknown command: bogus"
+        );
+    }
+
+    #[test]
+    fn test_open_recent_sets_pending_request() {
+        let mut app = App::new();
+        app.recent = RecentFiles::new();
+        app.recent.record(&[PathBuf::from("/logs/old.log")]);
+        app.recent.record(&[PathBuf::from("/logs/new.log")]);
+
+        app.show_recent();
+        app.process_message(Msg::RecentDown);
+        app.process_message(Msg::OpenRecent);
+
+        assert_eq!(app.mode, Mode::Normal);
+        assert_eq!(
+            app.take_open_request().map(|r| r.paths()),
+            Some(vec![PathBuf::from("/logs/old.log")])
+        );
+        assert_eq!(app.take_open_request(), None);
+    }
+
+    #[test]
+    fn test_theme_command_sets_level_styles() {
+        let mut app = App::new();
+        app.config = None;
+        app.input_buffer = "theme deuteranopia".to_string();
+        app.execute_command();
+
+        assert_eq!(app.theme.name, "deuteranopia");
+        assert_eq!(app.gutter, Gutter::Symbols);
+        let style = app.get_line_style("ERROR boom", Some(LogLevel::Error));
+        assert!(style.add_modifier.contains(Modifier::BOLD));
+
+        app.input_buffer = "theme nope".to_string();
+        app.execute_command();
+        assert_eq!(app.theme.name, "deuteranopia");
+        assert!(app.status_message.starts_with("Unknown theme: nope"));
+    }
+
+    #[test]
+    fn test_theme_command_finds_config_themes() {
+        let mut app = App::new();
+        let mut config = AppConfig::default();
+        let mut night = Theme::by_name("nord").unwrap().clone();
+        night.name = "night".into();
+        night.gutter = Gutter::Letters;
+        config.themes.push(night);
+        app.config = Some(config);
+
+        app.input_buffer = "theme".to_string();
+        app.execute_command();
+        assert!(app.status_message.ends_with(
+            "(available: night, default, high-contrast, deuteranopia, protanopia, nord)"
+        ));
+
+        app.input_buffer = "theme Night".to_string();
+        app.execute_command();
+        assert_eq!(app.theme.name, "night");
+        assert_eq!(app.gutter, Gutter::Letters);
+        assert_eq!(app.theme.chrome, Theme::by_name("nord").unwrap().chrome);
+    }
+
+    #[test]
+    fn test_gutter_command() {
+        let mut app = App::new();
+        app.gutter = Gutter::Off;
+        app.input_buffer = "gutter".to_string();
+        app.execute_command();
+        assert_eq!(app.gutter, Gutter::Letters);
+        assert_eq!(
+            app.gutter_glyph(Some(LogLevel::Debug))
+                .map(|(glyph, _)| glyph),
+            Some("D")
+        );
+
+        app.input_buffer = "gutter off".to_string();
+        app.execute_command();
+        assert_eq!(app.gutter, Gutter::Off);
+        assert_eq!(app.gutter_glyph(Some(LogLevel::Debug)), None);
+    }
+
+    #[test]
+    fn test_throttle_keeps_first_lines_of_each_template() {
+        let (_file, mut app) = app_with_lines(&[
+            "heartbeat seq=1",
+            "heartbeat seq=2",
+            "user 7 logged in",
+            "heartbeat seq=3",
+            "heartbeat seq=4",
+        ]);
+        app.input_buffer = "throttle 2".to_string();
+        app.execute_command();
+
+        assert_eq!(app.filtered_indices, vec![0, 1, 2]);
+        assert_eq!(app.throttled_after(1), Some(2));
+        assert_eq!(
+            app.status_message,
+            "Showing the first 2 lines of each message (2 repeats hidden)"
+        );
+
+        app.input_buffer = "throttle".to_string();
+        app.execute_command();
+        assert_eq!(app.filtered_len(), 5);
+    }
+
+    #[test]
+    fn test_ruler_command_toggles() {
+        let mut app = App::new();
+        app.config = None;
+        app.ruler_column = None;
+
+        app.input_buffer = "ruler".to_string();
+        app.execute_command();
+        assert_eq!(app.ruler_column, Some(DEFAULT_RULER_COLUMN));
+
+        app.input_buffer = "ruler 80".to_string();
+        app.execute_command();
+        assert_eq!(app.ruler_column, Some(80));
+        assert_eq!(app.status_message, "Ruler at column 80");
+
+        app.input_buffer = "ruler".to_string();
+        app.execute_command();
+        assert_eq!(app.ruler_column, None);
+    }
+
+    fn app_with_lines(lines: &[&str]) -> (NamedTempFile, App) {
+        let (temp_file, storage) = storage_from(lines);
+        let mut app = App::new();
+        app.set_storage(storage);
+        (temp_file, app)
+    }
+
+    #[test]
+    fn test_hex_view_of_current_line() {
+        let (_file, mut app) = app_with_lines(&["first", "caf\u{e9} \u{1b}[31mred"]);
+        app.selected_line = 1;
+        app.process_message(Msg::OpenHex);
+        assert_eq!(app.mode, Mode::Hex);
+        let hex = app.hex.as_ref().unwrap();
+        assert_eq!(hex.offset(), Some(6));
+        assert!(hex.source.ends_with("line 2"));
+        assert_eq!(hex.summary(), "14 bytes + \\n, 1 control, 2 non-ASCII");
+
+        app.process_message(Msg::CloseHex);
+        assert_eq!(app.mode, Mode::Normal);
+        assert!(app.hex.is_none());
+    }
+
+    #[test]
+    fn test_mouse_click_and_drag_select_lines() {
+        let (_file, mut app) = app_with_lines(&["a", "b", "c", "d", "e"]);
+        app.view_area.set(Rect::new(1, 1, 40, 4));
+        app.viewport_height.set(4);
+        *app.view_rows.borrow_mut() = vec![Some(0), None, Some(1), Some(2)];
+
+        app.process_message(Msg::Click { column: 5, row: 3 });
+        assert_eq!(app.selected_line, 1);
+        app.process_message(Msg::Click { column: 5, row: 2 });
+        assert_eq!(app.selected_line, 1);
+        assert!(!app.selection.is_active());
+
+        app.process_message(Msg::Drag { column: 60, row: 4 });
+        assert_eq!(app.selected_line, 2);
+        assert_eq!(app.selection.range(app.selected_line), Some((1, 2)));
+        app.process_message(Msg::Drag { column: 5, row: 9 });
+        assert_eq!(app.selection.range(app.selected_line), Some((1, 3)));
+
+        app.process_message(Msg::Click { column: 5, row: 1 });
+        assert_eq!(app.selected_line, 0);
+        assert!(!app.selection.is_active());
+
+        app.process_message(Msg::WheelDown);
+        assert_eq!(app.selected_line, WHEEL_LINES);
+    }
+
+    #[test]
+    fn test_startup_position_last_error() {
+        let (_file, mut app) = app_with_lines(&[
+            "2024-01-01 10:00:00 ERROR first",
+            "2024-01-01 10:01:00 ERROR second",
+            "2024-01-01 10:02:00 INFO ok",
+        ]);
+        let mut config = AppConfig::default();
+        config.view.startup_position = StartupPosition::LastError;
+        app.config = Some(config);
+
+        app.apply_startup_position();
+        assert_eq!(app.selected_line, 1);
+    }
+
+    #[test]
+    fn test_startup_position_last_minutes() {
+        let (_file, mut app) = app_with_lines(&[
+            "2024-01-01 10:00:00 INFO old",
+            "2024-01-01 10:20:00 INFO recent",
+            "  continuation",
+            "2024-01-01 10:25:00 INFO newest",
+        ]);
+        let mut config = AppConfig::default();
+        config.view.startup_position = StartupPosition::LastMinutes(10);
+        app.config = Some(config);
+
+        app.apply_startup_position();
+        assert_eq!(app.selected_line, 1);
+    }
+
+    #[test]
+    fn test_load_summary_dismissed_by_key() {
+        let mut app = App::new();
+        let storage = create_test_storage();
+        let summary = LoadSummary::new(&storage, 1, 0, 0);
+        app.set_storage(storage);
+        app.show_load_summary(summary);
+        assert_eq!(
+            app.status_message,
+            "Loaded 3 lines from 1 file, 0 errors, 0 warnings"
+        );
+
+        app.expire_load_summary();
+        assert!(app.load_summary.is_some());
+
+        app.handle_key(crossterm::event::KeyEvent::from(
+            crossterm::event::KeyCode::Char('j'),
+        ));
+        assert!(app.load_summary.is_none());
+        assert_eq!(app.selected_line, 1);
+    }
+
+    #[test]
+    fn test_follow_appends_filtered_lines_and_stays_at_bottom() {
+        let (mut file, mut app) = app_with_lines(&["2024-01-01 10:00:00 INFO start"]);
+        app.filters.add_include("INFO");
+        app.update_filtered_logs();
+
+        app.process_message(Msg::ToggleFollow);
+        assert!(app.is_following());
+        assert_eq!(app.status_message, "Following 1 file");
+
+        writeln!(file, "2024-01-01 10:00:01 DEBUG noise").unwrap();
+        writeln!(file, "2024-01-01 10:00:02 INFO next").unwrap();
+        file.flush().unwrap();
+        app.poll_follow();
+
+        assert_eq!(app.total_lines(), 3);
+        assert_eq!(app.filtered_indices, vec![0, 2]);
+        assert_eq!(app.selected_line, 1);
+
+        app.process_message(Msg::ToggleFollow);
+        assert!(!app.is_following());
+    }
+
+    #[test]
+    fn test_replay_reveals_lines_from_a_start_time() {
+        let (_file, mut app) = app_with_lines(&[
+            "2024-01-01 10:00:00 INFO start",
+            "2024-01-01 10:05:00 WARN slow",
+            "2024-01-01 10:10:00 ERROR down",
+            "  at frame",
+        ]);
+        app.input_buffer = "replay 10:05".to_string();
+        app.execute_command();
+        assert!(app
+            .status_message
+            .starts_with("Replaying from 2024-01-01 10:05:00 at 1x"));
+        assert_eq!(app.filtered_len(), 2);
+        assert_eq!(app.selected_line, 1);
+
+        // Filters keep lines the replay has not reached hidden
+        app.filters.add_exclude("start");
+        app.update_filtered_logs();
+        assert_eq!(app.filtered_len(), 1);
+
+        app.input_buffer = "replay 100000x".to_string();
+        app.execute_command();
+        assert_eq!(app.status_message, "Replaying at 100000x");
+        std::thread::sleep(std::time::Duration::from_millis(10));
+        app.poll_replay();
+        assert!(app.replay.is_none());
+        assert_eq!(app.status_message, "Replay finished");
+        assert_eq!(app.filtered_len(), 3);
+        assert_eq!(app.selected_line, 2);
+
+        app.input_buffer = "replay nonsense".to_string();
+        app.execute_command();
+        assert_eq!(app.status_message, "Not a time: nonsense");
+        app.input_buffer = "replay 9:00".to_string();
+        app.execute_command();
+        assert_eq!(app.filtered_len(), 0);
+        app.input_buffer = "replay stop".to_string();
+        app.execute_command();
+        assert_eq!(app.status_message, "Replay stopped; all 4 lines shown");
+        assert_eq!(app.filtered_len(), 3);
+    }
+
+    #[test]
+    fn test_watches_count_loaded_and_appended_lines() {
+        let (_file, mut app) = app_with_lines(&[
+            "10:00:00 ERROR db down",
+            "10:00:01 INFO method=GET status=500",
+            "10:00:02 ERROR retry",
+        ]);
+        app.input_buffer = "watch \"error\"; watch status=500".to_string();
+        app.execute_command();
+        assert_eq!(
+            app.status_message,
+            "Watching \"error\": 2 lines; Watching status=500: 1 lines"
+        );
+        let counts: Vec<usize> = app.watches.iter().map(Watch::count).collect();
+        assert_eq!(counts, [2, 1]);
+
+        app.append_lines(vec![
+            FollowedLine {
+                file_index: 0,
+                text: b"10:00:03 ERROR again".to_vec(),
+                timestamp: None,
+            },
+            FollowedLine {
+                file_index: 0,
+                text: br#"{"msg":"GET /","status":500}"#.to_vec(),
+                timestamp: None,
+            },
+        ]);
+        let counts: Vec<usize> = app.watches.iter().map(Watch::count).collect();
+        assert_eq!(counts, [3, 2]);
+
+        app.input_buffer = "unwatch 1".to_string();
+        app.execute_command();
+        assert_eq!(app.status_message, "Stopped watching \"error\"");
+        app.input_buffer = "unwatch".to_string();
+        app.execute_command();
+        assert_eq!(app.status_message, "Removed 1 watch");
+        assert!(app.watches.is_empty());
+    }
+
+    #[test]
+    fn test_retention_prunes_oldest_followed_lines() {
+        let (_file, mut app) = app_with_lines(&["2024-01-01 09:00:00 INFO loaded"]);
+        let spill = tempfile::NamedTempFile::new().unwrap();
+        let mut config = AppConfig::default();
+        config.follow.retention.lines = Some(4);
+        config.follow.spill = Some(spill.path().to_path_buf());
+        app.config = Some(config);
+        let line = |n: usize| FollowedLine {
+            file_index: 0,
+            text: format!("2024-01-01 10:00:0{} INFO line {}", n, n).into_bytes(),
+            timestamp: None,
+        };
+
+        app.append_lines((0..4).map(line).collect());
+        app.bookmarks.toggle(2);
+        app.bookmarks.toggle(4);
+        app.append_lines((4..6).map(line).collect());
+
+        assert_eq!(app.total_lines(), 5);
+        assert_eq!(app.filtered_indices, vec![0, 1, 2, 3, 4]);
+        let texts: Vec<String> = app
+            .storage
+            .as_ref()
+            .unwrap()
+            .iter()
+            .map(|line| line.as_str_lossy().into_owned())
+            .collect();
+        assert_eq!(texts[1], "2024-01-01 10:00:02 INFO line 2");
+        let marks: Vec<usize> = app.bookmarks.iter().map(|(idx, _)| idx).collect();
+        assert_eq!(marks, vec![2]);
+        assert_eq!(app.selected_line, 4);
+        assert_eq!(
+            std::fs::read_to_string(spill.path()).unwrap(),
+            "2024-01-01 10:00:00 INFO line 0\n2024-01-01 10:00:01 INFO line 1\n"
+        );
+    }
+
+    #[test]
+    fn test_retention_keeps_the_search() {
+        let (_file, mut app) = app_with_lines(&["2024-01-01 09:00:00 INFO loaded"]);
+        let mut config = AppConfig::default();
+        config.follow.retention.lines = Some(10);
+        app.config = Some(config);
+        let line = |n: usize| FollowedLine {
+            file_index: 0,
+            text: format!(
+                "2024-01-01 10:00:{:02} {} line {}",
+                n,
+                if n.is_multiple_of(2) { "WARN" } else { "INFO" },
+                n
+            )
+            .into_bytes(),
+            timestamp: None,
+        };
 
-        let mut current_match = 0;
+        app.append_lines((0..10).map(line).collect());
+        app.init_search_state("WARN".to_string());
+        app.next_match();
+        assert_eq!(app.current_match_display().as_deref(), Some("2/5"));
+
+        // Down to 9 followed lines: lines 0 and 1 go, and the match on line 0 with them
+        app.append_lines(vec![line(10)]);
+        assert_eq!(app.total_lines(), 10);
+        assert!(app.search_job.is_none());
+        assert_eq!(app.get_search_query(), Some("WARN"));
+        assert_eq!(app.current_match_display().as_deref(), Some("1/5"));
+        let current = app.search_state.as_ref().unwrap().current_position.unwrap();
+        assert_eq!(current.filtered_idx, 1);
+        app.next_match();
+        assert_eq!(app.current_match_display().as_deref(), Some("2/5"));
+        assert_eq!(app.selected_storage_index(), Some(3));
+    }
 
-        for (filtered_idx, &line_idx) in self.filtered_indices.iter().enumerate() {
-            let line = storage.get_line(line_idx)?;
-            let lower_bytes: Vec<u8> = line
-                .as_bytes()
-                .iter()
-                .map(|&b| b.to_ascii_lowercase())
-                .collect();
-            let matches = state.matcher.find_all(&lower_bytes);
+    #[test]
+    fn test_last_read_marker_after_returning_to_bottom() {
+        let (mut file, mut app) = app_with_lines(&[
+            "2024-01-01 10:00:00 INFO one",
+            "2024-01-01 10:00:01 INFO two",
+        ]);
+        app.process_message(Msg::ToggleFollow);
+        app.process_message(Msg::ScrollUp);
+
+        writeln!(file, "2024-01-01 10:00:02 INFO three").unwrap();
+        writeln!(file, "2024-01-01 10:00:03 INFO four").unwrap();
+        file.flush().unwrap();
+        app.poll_follow();
+        assert_eq!(app.selected_line, 0);
+        assert_eq!(app.last_read_row(), None);
+
+        app.process_message(Msg::GoToBottom);
+        assert_eq!(app.last_read_row(), Some(2));
+        app.process_message(Msg::JumpToLastRead);
+        assert_eq!(app.selected_line, 2);
+
+        // Lines arriving while watching the bottom move no marker
+        app.process_message(Msg::GoToBottom);
+        writeln!(file, "2024-01-01 10:00:04 INFO five").unwrap();
+        file.flush().unwrap();
+        app.poll_follow();
+        app.process_message(Msg::GoToBottom);
+        assert_eq!(app.last_read_row(), Some(2));
+    }
 
-            for (start, end) in matches {
-                if current_match == match_idx {
-                    return Some(MatchPosition {
-                        filtered_idx,
-                        byte_offset: start,
-                        match_len: end - start,
-                    });
-                }
-                current_match += 1;
-            }
-        }
+    #[test]
+    fn test_follow_while_unfocused_reports_new_lines() {
+        let (mut file, mut app) = app_with_lines(&["2024-01-01 10:00:00 INFO start"]);
+        app.process_message(Msg::ToggleFollow);
+        app.set_focused(false);
+        assert!(!app.is_focused());
+
+        writeln!(file, "2024-01-01 10:00:01 INFO one").unwrap();
+        writeln!(file, "2024-01-01 10:00:02 INFO two").unwrap();
+        file.flush().unwrap();
+        app.poll_follow();
+        assert_eq!(app.total_lines(), 3);
 
-        None
+        app.set_focused(true);
+        assert!(app.is_focused());
+        assert_eq!(app.status_message, "2 new lines while unfocused");
+        assert_eq!(app.last_read_row(), Some(1));
     }
 
-    /// Check if a specific position is the current match.
-    /// Uses O(1) cached position lookup instead of linear scan.
-    pub fn is_current_match(&self, filtered_idx: usize, byte_offset: usize) -> bool {
-        let Some(state) = &self.search_state else {
-            return false;
+    #[test]
+    fn test_escape_cancels_newest_job() {
+        let (_file, mut app) = app_with_lines(&["line"]);
+        let spin = |name: &str| {
+            Job::spawn(name, 10, |ctx| {
+                while !ctx.is_cancelled() {
+                    std::thread::sleep(Duration::from_millis(1));
+                }
+            })
         };
+        app.export_jobs
+            .push(("a.log".to_string(), spin("write a.log")));
+        app.export_jobs
+            .push(("b.log".to_string(), spin("write b.log")));
+        assert_eq!(
+            app.running_jobs(),
+            vec![("write a.log", 0), ("write b.log", 0)]
+        );
+
+        app.process_message(Msg::ClearSelection);
+        assert_eq!(app.status_message, "Cancelled writing b.log");
+        app.process_message(Msg::ClearSelection);
+        assert_eq!(app.status_message, "Cancelled writing a.log");
+        assert!(app.running_jobs().is_empty());
+        app.process_message(Msg::ClearSelection);
+        assert_eq!(app.status_message, "");
+    }
 
-        // Use cached position for O(1) lookup
-        if let Some(pos) = state.current_position {
-            return pos.filtered_idx == filtered_idx && pos.byte_offset == byte_offset;
-        }
-
-        false
+    #[test]
+    fn test_time_command_reports_selection_span() {
+        let (_file, mut app) = app_with_lines(&[
+            "2024-01-01 10:00:00.250 INFO request",
+            "  continuation",
+            "2024-01-01 10:00:01.500 INFO response",
+            "  trailing detail",
+        ]);
+
+        app.input_buffer = "time".to_string();
+        app.execute_command();
+        assert_eq!(
+            app.status_message,
+            "Select a range with x first, then run :time"
+        );
+
+        app.selected_line = 1;
+        app.process_message(Msg::ToggleSelection);
+        app.selected_line = 3;
+        app.input_buffer = "time".to_string();
+        app.execute_command();
+        assert_eq!(
+            app.status_message,
+            "1.250s between line 2 (10:00:00.250) and line 4 (10:00:01.500)"
+        );
     }
 
-    /// Check if there is an active search.
-    pub fn has_search(&self) -> bool {
-        self.search_state.is_some()
+    #[test]
+    fn test_search_limited_to_range() {
+        let (_file, mut app) = app_with_lines(&[
+            "2024-01-01 10:00:00 ERROR timeout",
+            "2024-01-01 10:30:00 ERROR timeout",
+            "  timeout detail",
+            "2024-01-01 11:00:00 ERROR timeout",
+            "2024-01-01 11:30:00 ERROR timeout",
+        ]);
+        app.init_search_state("timeout".to_string());
+        assert_eq!(app.total_matches(), 5);
+
+        app.input_buffer = "range 10:15..11:00".to_string();
+        app.execute_command();
+        assert_eq!(
+            app.status_message,
+            "Search limited to 10:15..11:00 (3 lines)"
+        );
+        assert_eq!(app.total_matches(), 3);
+        assert_eq!(app.current_match_display(), Some("1/3".to_string()));
+        assert_eq!(app.selected_line, 1);
+        assert!(app.get_line_matches(0).is_empty());
+        app.prev_match();
+        assert_eq!(app.selected_line, 3);
+
+        app.input_buffer = "range 11:00..".to_string();
+        app.execute_command();
+        assert_eq!(app.total_matches(), 2);
+        assert_eq!(app.search_range_label(), Some("11:00.."));
+
+        app.input_buffer = "range 12:00..".to_string();
+        app.execute_command();
+        assert_eq!(app.status_message, "No lines in that time range");
+
+        app.selected_line = 0;
+        app.process_message(Msg::ToggleSelection);
+        app.selected_line = 1;
+        app.input_buffer = "range".to_string();
+        app.execute_command();
+        assert_eq!(app.status_message, "Search limited to selection (2 lines)");
+        assert_eq!(app.total_matches(), 2);
+
+        app.input_buffer = "range off".to_string();
+        app.execute_command();
+        assert_eq!(app.status_message, "Search range cleared");
+        assert_eq!(app.total_matches(), 5);
     }
 
-    /// Get the search query if any.
-    pub fn get_search_query(&self) -> Option<&str> {
-        self.search_query.as_deref()
+    #[test]
+    fn test_filter_scoped_to_one_file() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let worker = dir.path().join("worker.log");
+        let api = dir.path().join("api.log");
+        std::fs::write(&worker, "job 1 timeout\njob 2 done\n").unwrap();
+        std::fs::write(&api, "GET /a timeout\nGET /b ok\n").unwrap();
+        let mut app = App::new();
+        app.set_storage(LogStorage::merge(vec![
+            LogStorage::from_file(&worker).unwrap(),
+            LogStorage::from_file(&api).unwrap(),
+        ]));
+
+        app.input_buffer = "filter-out --file worker.log timeout".to_string();
+        app.execute_command();
+        assert_eq!(
+            app.status_message,
+            "Added filter-out: timeout (in worker.log)"
+        );
+        assert_eq!(app.filtered_indices, vec![1, 2, 3]);
+        assert_eq!(app.filter_index.count(&app.filters.excludes()[0]), Some(1));
+
+        app.input_buffer = "filter --file api.log ok".to_string();
+        app.execute_command();
+        assert_eq!(app.filtered_indices, vec![1, 3]);
+
+        app.input_buffer = "dim --file api.log GET".to_string();
+        app.execute_command();
+        assert!(app.is_line_dimmed(3, b"GET /b ok"));
+        assert!(!app.is_line_dimmed(1, b"job 2 done"));
     }
-}
 
-/// Convert byte offset to character offset in a string.
-/// Safely handles multi-byte UTF-8 characters by using char_indices.
-fn byte_to_char_offset(text: &str, byte_offset: usize) -> usize {
-    text.char_indices()
-        .take_while(|(idx, _)| *idx < byte_offset)
-        .count()
-}
+    #[test]
+    fn test_filter_file_command() {
+        let (_file, mut app) = app_with_lines(&[
+            "GET /orders 200",
+            "GET /healthz 200",
+            "POST /orders 500",
+            "GET /metrics 200",
+        ]);
+        let mut rules = NamedTempFile::new().unwrap();
+        writeln!(rules, "# noise\n!healthz\n!/metrics\n\norders").unwrap();
+
+        app.input_buffer = format!("filter-file {}", rules.path().display());
+        app.execute_command();
+        assert_eq!(
+            app.status_message,
+            format!("Added 3 filters from {}", rules.path().display())
+        );
+        assert_eq!(app.filtered_indices, vec![0, 2]);
+
+        app.input_buffer = "filter-file /nonexistent/rules.txt".to_string();
+        app.execute_command();
+        assert!(app
+            .status_message
+            .starts_with("Error reading /nonexistent/rules.txt:"));
+        assert_eq!(app.filters.len(), 3);
+    }
 
-impl Default for App {
-    fn default() -> Self {
-        Self::new()
+    #[test]
+    fn test_table_command_writes_selection() {
+        let (_file, mut app) = app_with_lines(&[
+            "2026-02-13 10:00:00 INFO start",
+            "2026-02-13 10:00:01 ERROR failed status=500",
+            "2026-02-13 10:00:02 INFO done",
+        ]);
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("table.md");
+
+        app.selected_line = 1;
+        app.input_buffer = format!("table status > {}", path.display());
+        app.execute_command();
+        assert_eq!(
+            app.status_message,
+            format!("Wrote table of 1 row to {}", path.display())
+        );
+        let table = std::fs::read_to_string(&path).unwrap();
+        assert_eq!(table.lines().count(), 3);
+        assert!(table.ends_with("| error | 2026-02-13 10:00:01 ERROR failed status=500 | 500 |\n"));
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use std::io::Write;
-    use tempfile::NamedTempFile;
+    #[test]
+    fn test_propose_filter_from_selection() {
+        let (_file, mut app) = app_with_lines(&[
+            "10:00:01 WARN retry 1 of job sync-users",
+            "10:00:02 WARN retry 2 of job sync-users",
+            "10:00:03 INFO done",
+        ]);
+        app.process_message(Msg::ToggleSelection);
+        app.selected_line = 1;
+        app.process_message(Msg::ProposeFilter(FilterKind::Exclude));
+        assert_eq!(app.mode, Mode::Command);
+        assert_eq!(app.input_buffer, "filter-out of job sync-users");
+
+        app.process_message(Msg::SubmitCommand);
+        assert_eq!(app.filtered_indices, vec![2]);
+
+        app.selected_line = 0;
+        app.process_message(Msg::ProposeFilter(FilterKind::Include));
+        assert_eq!(app.input_buffer, "filter INFO done");
+    }
 
-    fn create_test_storage() -> LogStorage {
-        let mut temp_file = NamedTempFile::new().unwrap();
-        writeln!(temp_file, "Line 1").unwrap();
-        writeln!(temp_file, "Line 2").unwrap();
-        writeln!(temp_file, "Line 3").unwrap();
-        LogStorage::from_file(temp_file.path()).unwrap()
+    #[test]
+    fn test_selection_summary_follows_cursor() {
+        let (_file, mut app) = app_with_lines(&[
+            "2024-01-01 10:00:00 INFO start",
+            "  detail",
+            "2024-01-01 10:00:30 INFO done",
+        ]);
+        assert_eq!(app.selection_summary(), None);
+
+        app.process_message(Msg::ToggleSelection);
+        let summary = app.selection_summary().unwrap();
+        assert_eq!((summary.lines, summary.bytes), (1, 30));
+        assert_eq!(summary.span, Some(chrono::Duration::zero()));
+
+        app.selected_line = 2;
+        let summary = app.selection_summary().unwrap();
+        assert_eq!((summary.lines, summary.bytes), (3, 67));
+        assert_eq!(summary.span, Some(chrono::Duration::seconds(30)));
+
+        app.selected_line = 1;
+        assert_eq!(app.selection_summary().unwrap().bytes, 38);
     }
 
     #[test]
-    fn test_app_new() {
-        let app = App::new();
-        assert!(app.storage.is_none());
-        assert!(app.filtered_indices.is_empty());
+    fn test_request_open_prompts_for_large_files() {
+        let mut small = NamedTempFile::new().unwrap();
+        writeln!(small, "short").unwrap();
+        let mut big = NamedTempFile::new().unwrap();
+        writeln!(big, "{}", "x".repeat(4096)).unwrap();
+
+        let mut app = App::new();
+        let mut config = AppConfig::default();
+        config.large_files.threshold_bytes = 1024;
+        config.large_files.tail_bytes = 512;
+        app.config = Some(config);
+
+        app.request_open(vec![small.path().to_path_buf(), big.path().to_path_buf()]);
+        assert_eq!(app.mode, Mode::Prompt);
+        assert_eq!(app.take_open_request(), None);
+        assert_eq!(app.prompt.as_ref().unwrap().message.len(), 1);
+        assert!(matches!(
+            &app.prompt_action,
+            Some(PromptAction::OpenLargeFiles(files)) if files.oversized.len() == 1
+        ));
+
+        app.process_message(Msg::PromptKey('t'));
         assert_eq!(app.mode, Mode::Normal);
+        assert_eq!(
+            app.take_open_request().unwrap().files,
+            vec![
+                (small.path().to_path_buf(), IndexMode::Full),
+                (big.path().to_path_buf(), IndexMode::Tail { bytes: 512 }),
+            ]
+        );
     }
 
     #[test]
-    fn test_set_storage() {
-        let mut app = App::new();
-        let storage = create_test_storage();
+    fn test_scroll_up_extends_tail_only_file() {
+        let mut temp_file = NamedTempFile::new().unwrap();
+        for i in 1..=10 {
+            writeln!(temp_file, "line {}", i).unwrap();
+        }
+        let storage =
+            LogStorage::from_file_with(temp_file.path(), IndexMode::TailLines { lines: 3 })
+                .unwrap();
 
+        let mut app = App::new();
         app.set_storage(storage);
+        app.bookmarks.toggle(1);
+        assert_eq!(app.filtered_len(), 3);
+
+        app.process_message(Msg::ScrollUp);
+
+        assert_eq!(app.filtered_len(), 10);
+        // The cursor moved up one line from "line 8" rather than jumping
+        assert_eq!(
+            app.get_filtered_entry(app.selected_line)
+                .unwrap()
+                .as_str_lossy(),
+            "line 7"
+        );
+        assert!(app.bookmarks.contains(8));
+        assert_eq!(app.status_message, "Loaded 7 earlier lines");
+    }
 
-        assert!(app.storage.is_some());
-        assert_eq!(app.total_lines(), 3);
+    #[test]
+    fn test_request_open_indexes_mid_size_files_on_demand() {
+        let mut file = NamedTempFile::new().unwrap();
+        writeln!(file, "{}", "x".repeat(4096)).unwrap();
+
+        let mut app = App::new();
+        let mut config = AppConfig::default();
+        config.large_files.on_demand_bytes = 1024;
+        app.config = Some(config);
+
+        app.request_open(vec![file.path().to_path_buf()]);
+
+        assert_eq!(app.mode, Mode::Normal);
+        assert_eq!(
+            app.take_open_request().unwrap().files,
+            vec![(
+                file.path().to_path_buf(),
+                IndexMode::OnDemand {
+                    block_size: ON_DEMAND_BLOCK_SIZE
+                }
+            )]
+        );
     }
 
     #[test]
-    fn test_get_line() {
+    fn test_search_on_lazy_index_runs_in_background() {
+        let mut temp_file = NamedTempFile::new().unwrap();
+        for i in 0..50 {
+            let text = if i == 30 { "needle here" } else { "hay" };
+            writeln!(temp_file, "{} {}", text, i).unwrap();
+        }
+        let storage =
+            LogStorage::from_file_with(temp_file.path(), IndexMode::OnDemand { block_size: 64 })
+                .unwrap();
+
         let mut app = App::new();
-        let storage = create_test_storage();
         app.set_storage(storage);
+        app.init_search_state("NEEDLE".to_string());
+        assert!(app.search_job.is_some());
 
-        let line = app.get_line(0).unwrap();
-        assert_eq!(line.as_str_lossy().trim(), "Line 1");
+        // Lines on screen are highlighted before the count arrives
+        assert_eq!(app.current_match_display(), None);
+        assert_eq!(app.get_line_matches(30), vec![(0, 6)]);
 
-        let line = app.get_line(1).unwrap();
-        assert_eq!(line.as_str_lossy().trim(), "Line 2");
+        while app.search_job.is_some() {
+            app.poll_search_job();
+            std::thread::sleep(std::time::Duration::from_millis(1));
+        }
+
+        assert_eq!(app.total_matches(), 1);
+        assert_eq!(app.selected_line, 30);
+        assert_eq!(app.search_progress(), None);
+        assert_eq!(app.current_match_display().as_deref(), Some("1/1"));
     }
 
     #[test]
-    fn test_search_init_and_clear() {
+    fn test_write_over_existing_file_asks_first() {
+        let (_file, mut app) = app_with_lines(&["one", "two"]);
+        let dir = tempfile::tempdir().unwrap();
+        let out = dir.path().join("out.log");
+        std::fs::write(&out, "keep me\n").unwrap();
+
+        app.input_buffer = format!("w {}", out.display());
+        app.process_message(Msg::SubmitCommand);
+        assert_eq!(app.mode, Mode::Prompt);
+        // Enter alone picks the highlighted No
+        app.process_message(Msg::PromptAccept);
+        assert_eq!(app.mode, Mode::Normal);
+        assert_eq!(std::fs::read_to_string(&out).unwrap(), "keep me\n");
+        assert_eq!(
+            app.status_message,
+            format!("Not written ({} kept)", out.display())
+        );
+
+        app.input_buffer = format!("w {}", out.display());
+        app.process_message(Msg::SubmitCommand);
+        app.process_message(Msg::PromptKey('x'));
+        assert_eq!(app.mode, Mode::Prompt);
+        app.process_message(Msg::PromptKey('y'));
+        assert_eq!(std::fs::read_to_string(&out).unwrap(), "one\ntwo\n");
+    }
+
+    #[test]
+    fn test_cancel_large_file_prompt() {
+        let mut big = NamedTempFile::new().unwrap();
+        writeln!(big, "{}", "x".repeat(4096)).unwrap();
+
         let mut app = App::new();
+        let mut config = AppConfig::default();
+        config.large_files.threshold_bytes = 1024;
+        app.config = Some(config);
 
-        // Initially no search
-        assert!(!app.has_search());
-        assert_eq!(app.get_search_query(), None);
+        app.request_open(vec![big.path().to_path_buf()]);
+        app.process_message(Msg::PromptCancel);
 
-        // Initialize search
-        app.init_search_state("test".to_string());
+        assert_eq!(app.mode, Mode::Normal);
+        assert!(app.prompt.is_none() && app.prompt_action.is_none());
+        assert_eq!(app.take_open_request(), None);
+        assert_eq!(app.status_message, "Open cancelled");
+    }
 
-        // Now has search
-        assert!(app.has_search());
-        assert_eq!(app.get_search_query(), Some("test"));
+    #[test]
+    fn test_prepare_reload_drops_line_state() {
+        let mut app = App::new();
+        app.set_storage(create_test_storage());
+        app.bookmarks.toggle(1);
+        app.filters.add_include("Line");
 
-        // Clear search
-        app.clear_search();
+        app.prepare_reload(2);
 
-        // No search anymore
-        assert!(!app.has_search());
-        assert_eq!(app.get_search_query(), None);
+        assert!(app.storage.is_none());
+        assert!(app.bookmarks.is_empty());
+        assert_eq!(app.filters.len(), 1);
+        assert!(matches!(
+            app.loading_status,
+            LoadingStatus::Loading {
+                current: 0,
+                total: 2
+            }
+        ));
     }
 
     #[test]
-    fn test_search_matches() {
-        let mut app = App::new();
-        let mut temp_file = NamedTempFile::new().unwrap();
-        writeln!(temp_file, "This is a test line").unwrap();
-        writeln!(temp_file, "Another test line").unwrap();
-        writeln!(temp_file, "No match here").unwrap();
-        let storage = LogStorage::from_file(temp_file.path()).unwrap();
-        app.set_storage(storage);
+    fn test_reload_restores_bookmarks_and_cursor_by_content() {
+        let (file, mut app) = app_with_lines(&["a", "b", "target", "c"]);
+        app.bookmarks.set_note(2, "here");
+        app.bookmarks.toggle(3);
+        app.selected_line = 2;
+
+        app.prepare_reload(1);
+        let mut rewritten = File::create(file.path()).unwrap();
+        writeln!(rewritten, "new\na\nb\ntarget").unwrap();
+        app.set_storage(LogStorage::from_file(file.path()).unwrap());
+
+        assert!(app.restore_anchors());
+        assert_eq!(app.selected_line, 3);
+        assert_eq!(app.bookmarks.get(3).unwrap().note.as_deref(), Some("here"));
+        assert_eq!(app.bookmarks.len(), 1);
+        assert_eq!(app.status_message, "1 bookmark not found after reload");
+    }
 
-        // Initialize search for "test"
-        app.init_search_state("test".to_string());
+    #[test]
+    fn test_refilter_keeps_cursor_line() {
+        let (_file, mut app) = app_with_lines(&["keep 1", "drop", "keep 2", "keep 3"]);
+        app.selected_line = 2;
 
-        // Check total matches (should be 2)
-        assert_eq!(app.total_matches(), 2);
+        app.filters.add_include("keep");
+        app.update_filtered_logs();
+        assert_eq!(app.selected_storage_index(), Some(2));
 
-        // Get line matches for line 0
-        let matches = app.get_line_matches(0);
-        assert_eq!(matches.len(), 1);
-        assert_eq!(matches[0], (10, 14)); // "test" position in "This is a test line"
+        app.filters.clear();
+        app.update_filtered_logs();
+        assert_eq!(app.selected_line, 2);
+    }
 
-        // Get line matches for line 1
-        let matches = app.get_line_matches(1);
-        assert_eq!(matches.len(), 1);
-        assert_eq!(matches[0], (8, 12)); // "test" position in "Another test line"
+    #[test]
+    fn test_hide_lines_and_restore_from_filter_list() {
+        let (_file, mut app) = app_with_lines(&["a", "noise 1", "noise 2", "b"]);
+        app.selected_line = 1;
+        app.process_message(Msg::ToggleSelection);
+        app.selected_line = 2;
+        app.process_message(Msg::HideLines);
+
+        assert_eq!(app.filtered_indices, vec![0, 3]);
+        assert_eq!(app.status_message, "Hid 2 lines (U restores)");
+        assert_eq!(app.filter_list_len(), 1);
+
+        app.mode = Mode::FilterList;
+        app.filter_list_selected = 0;
+        app.process_message(Msg::DeleteSelectedFilter);
+        assert!(app.hidden.is_empty());
+        assert_eq!(app.filtered_len(), 4);
+        assert_eq!(app.mode, Mode::Normal);
+    }
 
-        // Get line matches for line 2 (no matches)
-        let matches = app.get_line_matches(2);
-        assert_eq!(matches.len(), 0);
+    #[test]
+    fn test_context_lines_around_filter_matches() {
+        let (_file, mut app) = app_with_lines(&["a", "b", "ERROR 1", "c", "d", "e", "ERROR 2"]);
+        app.filters.add_include("error");
+        app.set_context(Some(1));
+        assert_eq!(app.filtered_indices, vec![1, 2, 3, 5, 6]);
+        assert_eq!(
+            app.status_message,
+            "Showing 1 line of context around each match"
+        );
+        assert!(app.is_line_dimmed(1, b"b") && !app.is_line_dimmed(2, b"ERROR 1"));
+        assert!(app.context_break(3) && !app.context_break(2));
+
+        app.set_context(None);
+        assert_eq!(app.filtered_indices, vec![2, 6]);
+        assert!(!app.context_break(1));
     }
 
     #[test]
-    fn test_search_case_insensitive() {
-        let mut app = App::new();
-        let mut temp_file = NamedTempFile::new().unwrap();
-        writeln!(temp_file, "ERROR message").unwrap();
-        writeln!(temp_file, "error message").unwrap();
-        writeln!(temp_file, "Error message").unwrap();
-        let storage = LogStorage::from_file(temp_file.path()).unwrap();
-        app.set_storage(storage);
+    fn test_write_with_context_groups_matches() {
+        let (_file, mut app) = app_with_lines(&["a", "b", "ERROR 1", "c", "d", "e", "ERROR 2"]);
+        app.filters.add_include("error");
+        // The view's own context does not count as matches
+        app.set_context(Some(2));
+        let dir = tempfile::tempdir().unwrap();
+        let out = dir.path().join("out.log");
+
+        app.input_buffer = format!("w --context 1 {}", out.display());
+        app.process_message(Msg::SubmitCommand);
+        assert_eq!(
+            std::fs::read_to_string(&out).unwrap(),
+            "b\nERROR 1\nc\n--\ne\nERROR 2\n"
+        );
+        assert!(app.status_message.starts_with("Saved 5 lines"));
+    }
+
+    #[test]
+    fn test_select_matches_for_bulk_bookmark_and_hide() {
+        let (_file, mut app) =
+            app_with_lines(&["ERROR a", "ok", "error b", "fine", "ERROR c", "done"]);
+        app.process_message(Msg::SelectMatches);
+        assert_eq!(
+            app.status_message,
+            "Search with / first, then select the matches with %"
+        );
 
-        // Search for lowercase "error" - should match all variations
         app.init_search_state("error".to_string());
+        app.process_message(Msg::SelectMatches);
+        assert_eq!(app.status_message, "Selected 3 lines matching 'error'");
+        assert_eq!(app.selection.lines(app.selected_line), vec![0, 2, 4]);
+        assert_eq!(app.selection_summary().unwrap().lines, 3);
+
+        app.process_message(Msg::ToggleBookmark);
+        assert_eq!(app.status_message, "Bookmarked 3 lines");
+        assert!(app.bookmarks.contains(2) && !app.bookmarks.contains(1));
+        assert!(!app.selection.is_active());
+
+        app.process_message(Msg::SelectMatches);
+        app.process_message(Msg::HideLines);
+        assert_eq!(app.status_message, "Hid 3 lines (U restores)");
+        assert_eq!(app.filtered_indices, vec![1, 3, 5]);
+    }
 
-        // Each line should have one match
-        assert_eq!(app.get_line_matches(0).len(), 1);
-        assert_eq!(app.get_line_matches(1).len(), 1);
-        assert_eq!(app.get_line_matches(2).len(), 1);
+    #[test]
+    fn test_toggle_filter_from_filter_list() {
+        let (_file, mut app) = app_with_lines(&["ERROR db", "INFO ok", "ERROR retry", "WARN slow"]);
+        app.filters.add_include("error");
+        app.filters.add_exclude("retry");
+        app.update_filtered_logs();
+        assert_eq!(app.filtered_indices, vec![0]);
+        assert_eq!(app.filter_index.count(&app.filters.includes()[0]), Some(2));
+
+        app.mode = Mode::FilterList;
+        app.filter_list_selected = 0;
+        app.process_message(Msg::ToggleSelectedFilter);
+        assert_eq!(app.status_message, "Filter disabled");
+        assert_eq!(app.filtered_indices, vec![0, 1, 3]);
+
+        app.process_message(Msg::ToggleSelectedFilter);
+        assert_eq!(app.status_message, "Filter enabled");
+        assert_eq!(app.filtered_indices, vec![0]);
+        assert_eq!(app.mode, Mode::FilterList);
     }
 
     #[test]
-    fn test_search_filter_clears_search() {
+    fn test_pin_lines_reorder_and_jump() {
+        let (_file, mut app) = app_with_lines(&["config", "a", "b", "ERROR boom"]);
+        app.process_message(Msg::TogglePin);
+        app.selected_line = 3;
+        app.process_message(Msg::TogglePin);
+        assert_eq!(app.status_message, "Pinned line 4");
+
+        app.input_buffer = "pins".to_string();
+        app.mode = app.execute_command();
+        assert_eq!(app.mode, Mode::Pins);
+
+        app.process_message(Msg::PinListDown);
+        app.process_message(Msg::MovePinUp);
+        assert_eq!(app.pins.iter().collect::<Vec<_>>(), vec![3, 0]);
+        assert_eq!(app.pin_list_selected, 0);
+
+        app.process_message(Msg::PinListDown);
+        app.process_message(Msg::JumpToPin);
+        assert_eq!(app.mode, Mode::Normal);
+        assert_eq!(app.selected_line, 0);
+    }
+
+    #[test]
+    fn test_detail_pane_folds_long_values() {
+        let payload = "y".repeat(500);
+        let line = format!(r#"{{"msg":"upload","body":"{}"}}"#, payload);
+        let (_file, mut app) = app_with_lines(&[&line]);
+
+        app.process_message(Msg::OpenDetail);
+        assert_eq!(app.mode, Mode::Detail);
+        let folded = app.detail.as_ref().unwrap().rows();
+        assert!(folded[2].text.ends_with("(+ 420 chars)"));
+
+        app.process_message(Msg::ExpandAll);
+        assert!(app.detail.as_ref().unwrap().rows()[2]
+            .text
+            .contains(&payload));
+
+        app.process_message(Msg::CloseDetail);
+        assert_eq!(app.mode, Mode::Normal);
+        assert!(app.detail.is_none());
+    }
+
+    #[test]
+    fn test_detail_pane_shows_the_trace_of_an_entry() {
+        let mut lines = vec!["2024-01-01 10:00:00 ERROR stack overflow"];
+        lines.extend(["  at Parser.parse()"; 5]);
+        lines.push("2024-01-01 10:00:01 INFO next");
+        let (_file, mut app) = app_with_lines(&lines);
+
+        app.process_message(Msg::OpenDetail);
+        let rows: Vec<String> = app
+            .detail
+            .as_ref()
+            .unwrap()
+            .rows()
+            .iter()
+            .map(|row| row.line())
+            .collect();
+        assert_eq!(
+            rows,
+            vec![
+                "2024-01-01 10:00:00 ERROR stack overflow",
+                "  at Parser.parse()",
+                "… (+ 4 repeats of 1 frame)",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_filter_on_detail_field() {
+        let (_file, mut app) = app_with_lines(&[
+            r#"{"RequestId":"a1","msg":"start"}"#,
+            r#"{"RequestId":"b2","msg":"other"}"#,
+            r#"{"RequestId":"a1","msg":"done"}"#,
+        ]);
+        app.process_message(Msg::OpenDetail);
+        app.process_message(Msg::DetailDown);
+        app.process_message(Msg::FilterField(FilterKind::Include));
+
+        assert_eq!(app.filtered_indices, vec![0, 2]);
+        assert_eq!(
+            app.status_message,
+            r#"Added include filter: "RequestId":"a1""#
+        );
+    }
+
+    #[test]
+    fn test_perf_overlay_counters() {
+        let (_file, mut app) = app_with_lines(&["alpha", "beta", "alphabet"]);
+        app.process_message(Msg::TogglePerf);
+        assert!(app.show_perf);
+        app.update_filtered_logs();
+        assert!(app.perf.filter.is_some());
+
+        app.init_search_state("alpha".to_string());
+        app.get_line_matches(0);
+        app.get_line_matches(0);
+        assert_eq!(app.perf.match_cache.hits, 1);
+        assert_eq!(app.perf.match_cache.misses, 1);
+
+        app.process_message(Msg::TogglePerf);
+        assert!(!app.show_perf);
+    }
+
+    #[test]
+    fn test_sidecar_keeps_bookmarks_and_answers_sql() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let log = dir.path().join("app.log");
+        std::fs::write(&log, "INFO start\nERROR failed\nINFO done\nERROR again\n").unwrap();
+        let mut config = AppConfig::default();
+        config.index.sidecar = true;
+
         let mut app = App::new();
-        let mut temp_file = NamedTempFile::new().unwrap();
-        writeln!(temp_file, "test line 1").unwrap();
-        writeln!(temp_file, "test line 2").unwrap();
-        let storage = LogStorage::from_file(temp_file.path()).unwrap();
-        app.set_storage(storage);
+        app.config = Some(config.clone());
+        app.set_storage(crate::sidecar::load_storage(&log).unwrap());
+        app.bookmarks.set_note(1, "first failure");
+        app.save_annotations().unwrap();
+
+        let mut reopened = App::new();
+        reopened.config = Some(config);
+        reopened.set_storage(crate::sidecar::load_storage(&log).unwrap());
+        reopened.load_annotations();
+        assert_eq!(
+            reopened.bookmarks.get(1).unwrap().note.as_deref(),
+            Some("first failure")
+        );
+
+        reopened.input_buffer = "sql SELECT line FROM lines WHERE level = 'error'".to_string();
+        reopened.process_message(Msg::SubmitCommand);
+        assert_eq!(reopened.mode, Mode::Sql);
+        assert_eq!(reopened.status_message, "2 rows");
+        reopened.process_message(Msg::SqlDown);
+        reopened.process_message(Msg::JumpToSqlRow);
+        assert_eq!(reopened.mode, Mode::Normal);
+        assert_eq!(reopened.selected_line, 3);
+    }
 
-        // Set up a search
-        app.init_search_state("test".to_string());
-        assert!(app.has_search());
+    #[test]
+    fn test_sql_rows_drive_a_filter() {
+        let (_file, mut app) = app_with_lines(&[
+            "GET /a status=500 user=ann",
+            "GET /b status=200 user=bob",
+            "GET /c status=500 user=ann",
+            "GET /d status=500 user=cid",
+        ]);
+        app.input_buffer =
+            "sql SELECT user, count(*) AS n FROM logs WHERE status = 500 GROUP BY user ORDER BY n DESC"
+                .to_string();
+        app.process_message(Msg::SubmitCommand);
+        assert_eq!(app.mode, Mode::Sql);
+        assert_eq!(app.status_message, "2 rows");
+
+        app.process_message(Msg::FilterSqlRow(FilterKind::Include));
+        assert_eq!(app.mode, Mode::Normal);
+        assert_eq!(app.status_message, "Added include filter: ann");
+        assert_eq!(app.filtered_indices, vec![0, 2]);
+    }
 
-        // Simulate filter change - clear search
-        app.clear_search_on_refilter();
+    #[test]
+    fn test_overlays_over_many_lines_run_as_jobs() {
+        let lines: Vec<String> = (0..=BACKGROUND_OVERLAY_LINES)
+            .map(|n| format!("2024-01-01 10:00:00 ERROR request {}", n))
+            .collect();
+        let lines: Vec<&str> = lines.iter().map(String::as_str).collect();
+        let (_file, mut app) = app_with_lines(&lines);
+        let finish = |app: &mut App| {
+            while app.overlay_job.is_some() {
+                app.poll_jobs();
+                std::thread::sleep(Duration::from_millis(1));
+            }
+        };
 
-        // Search should be cleared
+        app.input_buffer = "stats".to_string();
+        app.process_message(Msg::SubmitCommand);
+        assert_eq!(app.mode, Mode::Normal);
+        assert_eq!(app.running_jobs()[0].0, "stats");
+        app.process_message(Msg::ClearSelection);
+        assert_eq!(app.status_message, "Cancelled stats");
+        assert!(app.running_jobs().is_empty());
+
+        app.input_buffer = "stats".to_string();
+        app.process_message(Msg::SubmitCommand);
+        finish(&mut app);
+        assert_eq!(app.mode, Mode::Stats);
+        assert_eq!(
+            app.stats_view.as_ref().unwrap().rows[0].count(),
+            lines.len()
+        );
+
+        app.mode = Mode::Normal;
+        app.input_buffer = "sql SELECT count(*) FROM logs".to_string();
+        app.process_message(Msg::SubmitCommand);
+        assert_eq!(app.running_jobs()[0].0, "sql 'SELECT count(*) FROM logs'");
+        finish(&mut app);
+        assert_eq!(app.mode, Mode::Sql);
+        let view = app.sql_view.as_ref().unwrap();
+        assert_eq!(view.result.rows, vec![vec![lines.len().to_string()]]);
+    }
+
+    #[test]
+    fn test_status_messages_queue_and_expire() {
+        let (_file, mut app) = app_with_lines(&["2024-01-01 10:00:00 INFO a"]);
+        let start = Instant::now();
+        app.input_buffer = "foo".to_string();
+        app.process_message(Msg::SubmitCommand);
+        app.update_notifications_at(start);
+        app.notify_info("Bookmarked 3 lines");
+        app.update_notifications_at(start);
+        assert_eq!(app.notifications.more(), 1);
+        assert_eq!(app.status_severity(), Severity::Info);
+
+        // The info message expires first and the error shows again
+        app.update_notifications_at(start + Duration::from_secs(6));
+        assert_eq!(app.status_message, "Unknown command: foo");
+        assert_eq!(app.status_severity(), Severity::Error);
+        app.update_notifications_at(start + Duration::from_secs(16));
+        assert_eq!(app.status_message, "");
+
+        app.post("Wrote 2 lines to a.log".to_string(), Severity::Info);
+        app.post("Error: disk full".to_string(), Severity::Error);
+        assert_eq!(app.notifications.more(), 1);
+        assert_eq!(app.status_severity(), Severity::Error);
+        app.status_message.clear();
+        app.update_notifications();
+        assert_eq!(app.notifications.current().map(|n| n.text.as_str()), None);
+
+        app.input_buffer = "messages".to_string();
+        app.process_message(Msg::SubmitCommand);
+        assert_eq!(app.mode, Mode::Messages);
+        assert_eq!(app.message_list_selected, 3);
+        app.process_message(Msg::MessagesUp);
+        assert_eq!(app.message_list_selected, 2);
+        app.process_message(Msg::CloseMessages);
+        assert_eq!(app.mode, Mode::Normal);
+    }
+
+    #[test]
+    fn test_stats_rows_drill_down() {
+        let (_file, mut app) = app_with_lines(&[
+            r#"2024-01-01 10:00:00 INFO {"SourceContext":"Api"}"#,
+            r#"2024-01-01 10:00:10 ERROR {"SourceContext":"Db"}"#,
+            "    at Db.Open()",
+            r#"2024-01-01 11:00:00 ERROR {"SourceContext":"Api"}"#,
+        ]);
+        app.input_buffer = "stats".to_string();
+        app.process_message(Msg::SubmitCommand);
+        assert_eq!(app.mode, Mode::Stats);
+
+        // Rows: error, info, Api, Db, then the time buckets
+        app.process_message(Msg::DrillDown);
+        assert_eq!(app.status_message, "Showing error lines");
+        assert_eq!(app.filtered_indices, vec![1, 3]);
+
+        app.input_buffer = "stats off; stats".to_string();
+        app.process_message(Msg::SubmitCommand);
+        app.process_message(Msg::StatsDown);
+        app.process_message(Msg::StatsDown);
+        app.process_message(Msg::DrillDown);
+        assert_eq!(
+            app.status_message,
+            r#"Added include filter: "SourceContext":"Api""#
+        );
+        assert_eq!(app.filtered_indices, vec![0, 3]);
+
+        app.filters.clear();
+        app.update_filtered_logs();
+        app.input_buffer = "stats".to_string();
+        app.process_message(Msg::SubmitCommand);
+        for _ in 0..4 {
+            app.process_message(Msg::StatsDown);
+        }
+        app.process_message(Msg::DrillDown);
+        assert!(app.status_message.starts_with("Showing 10:00:00.."));
+        assert_eq!(app.filtered_indices, vec![0, 1, 2]);
+    }
+
+    #[test]
+    fn test_exception_groups_jump_to_latest() {
+        let (_file, mut app) = app_with_lines(&[
+            "2024-01-01 10:00:00 ERROR request failed",
+            "System.TimeoutException: slow",
+            "   at Api.Call()",
+            "2024-01-01 10:00:01 INFO ok",
+            r#"{"@t":"2024-01-01T10:00:02Z","@x":"System.NullReferenceException: null\n   at Api.Map()"}"#,
+            "2024-01-01 10:00:03 ERROR request failed",
+            "System.TimeoutException: slow",
+            "   at Api.Call()",
+        ]);
+        app.input_buffer = "exceptions".to_string();
+        app.process_message(Msg::SubmitCommand);
+        assert_eq!(app.mode, Mode::Exceptions);
+        let groups = &app.exceptions_view.as_ref().unwrap().groups;
+        assert_eq!(groups.len(), 2);
+        assert_eq!(groups[0].name, "System.TimeoutException");
+
+        app.process_message(Msg::JumpToException);
+        assert_eq!(app.mode, Mode::Normal);
+        assert_eq!(app.status_message, "Latest of 2 System.TimeoutException");
+        assert_eq!(app.selected_line, 5);
+
+        app.input_buffer = "exceptions".to_string();
+        app.process_message(Msg::SubmitCommand);
+        app.process_message(Msg::ExceptionsDown);
+        app.process_message(Msg::ExceptionsDown);
+        app.process_message(Msg::JumpToException);
+        assert_eq!(app.selected_line, 4);
+
+        app.filters.add(FilterKind::Include, "INFO".to_string());
+        app.update_filtered_logs();
+        app.input_buffer = "exceptions".to_string();
+        app.process_message(Msg::SubmitCommand);
+        assert_eq!(app.mode, Mode::Normal);
+        assert_eq!(app.status_message, "No exceptions in the lines in view");
+    }
+
+    #[test]
+    fn test_gaps_between_search_matches() {
+        let (_file, mut app) = app_with_lines(&[
+            "2024-01-01 10:00:00 INFO job ran",
+            "2024-01-01 10:00:30 WARN retry",
+            "2024-01-01 10:01:00 INFO job ran",
+            "2024-01-01 10:02:00 INFO job ran",
+        ]);
+        app.input_buffer = "gaps".to_string();
+        app.process_message(Msg::SubmitCommand);
+        assert_eq!(app.mode, Mode::Gaps);
+        let view = app.gaps_view.as_ref().unwrap();
+        assert_eq!(view.subject, "lines in view");
+        assert_eq!(view.gaps.count, 3);
+        app.process_message(Msg::CloseGaps);
+
+        app.init_search_state("job".to_string());
+        app.input_buffer = "gaps".to_string();
+        app.process_message(Msg::SubmitCommand);
+        let view = app.gaps_view.as_ref().unwrap();
+        assert_eq!(view.subject, "matches of 'job'");
+        assert_eq!(view.gaps.min, chrono::Duration::seconds(60));
+        app.process_message(Msg::CloseGaps);
+
+        app.init_search_state("retry".to_string());
+        app.input_buffer = "gaps".to_string();
+        app.process_message(Msg::SubmitCommand);
+        assert_eq!(app.mode, Mode::Normal);
+        assert_eq!(
+            app.status_message,
+            "No gaps: fewer than two matches of 'retry' have a timestamp"
+        );
+
+        // Narrowing the view ends the search, so the chart covers the view
+        app.init_search_state("job".to_string());
+        app.input_buffer = "filter job".to_string();
+        app.process_message(Msg::SubmitCommand);
         assert!(!app.has_search());
-        assert_eq!(app.get_search_query(), None);
+        app.input_buffer = "gaps".to_string();
+        app.process_message(Msg::SubmitCommand);
+        assert_eq!(app.gaps_view.as_ref().unwrap().subject, "lines in view");
+    }
+
+    #[test]
+    fn test_level_command_and_toggles() {
+        let (_file, mut app) = app_with_lines(&[
+            "2024-01-01 10:00:00 ERROR db down",
+            "2024-01-01 10:00:01 WARN slow",
+            "2024-01-01 10:00:02 INFO ok",
+            "    continued",
+            "2024-01-01 10:00:03 DEBUG detail",
+        ]);
+        assert_eq!(
+            app.storage.as_ref().unwrap().line_level(1),
+            Some(LogLevel::Warn)
+        );
+
+        app.input_buffer = "level warn+".to_string();
+        app.process_message(Msg::SubmitCommand);
+        assert_eq!(app.status_message, "Showing warn+ lines");
+        assert_eq!(app.filtered_indices, vec![0, 1]);
+
+        app.process_message(Msg::ToggleLevel(LogLevel::Debug));
+        assert_eq!(app.status_message, "Showing error,warn,debug lines");
+        assert_eq!(app.filtered_indices, vec![0, 1, 4]);
+
+        app.input_buffer = "level off".to_string();
+        app.process_message(Msg::SubmitCommand);
+        app.process_message(Msg::ToggleLevel(LogLevel::Info));
+        assert_eq!(app.drill_down.label(), "level error,warn,debug,trace");
+        app.process_message(Msg::ToggleLevel(LogLevel::Info));
+        assert_eq!(app.status_message, "Showing lines of every level");
+        assert_eq!(app.filtered_len(), 5);
+    }
+
+    #[test]
+    fn test_clipboard_command() {
+        let mut app = App::with_config(None);
+        app.input_buffer = "clipboard osc52".to_string();
+        app.process_message(Msg::SubmitCommand);
+        assert_eq!(
+            app.status_message,
+            "Clipboard: osc52 (yanks go to the terminal clipboard)"
+        );
+        assert_eq!(
+            app.clipboard.as_ref().map(Clipboard::backend),
+            Some(ClipboardBackend::Osc52)
+        );
+
+        app.input_buffer = "clipboard".to_string();
+        app.process_message(Msg::SubmitCommand);
+        assert!(app.status_message.starts_with("Clipboard: osc52"));
     }
 
     #[test]
@@ -0,0 +1,238 @@
+//! Headless batch mode (`qlog --print`).
+//!
+//! The filters, level and search given on the command line are applied to
+//! the input and the lines that remain are written to standard output,
+//! without the terminal UI. Files are memory-mapped and matched like in the
+//! viewer, so qlog can stand in for `grep` in scripts and pipelines; piped
+//! input is filtered line by line as it arrives. With `-C`, lines around
+//! each match are printed too, with `--` between separate groups.
+
+use std::collections::VecDeque;
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::path::Path;
+
+use crate::export::LineWriter;
+use crate::model::{detect_level, BMHMatcher, Context, FilterList, LevelSet, LogStorage};
+
+/// Matching lines of a file collected before they are written.
+const PRINT_CHUNK_LINES: usize = 10_000;
+
+/// Which lines `--print` writes.
+#[derive(Debug, Clone)]
+pub struct LineFilter {
+    pub filters: FilterList,
+    pub levels: Option<LevelSet>,
+    /// Lowercased search query; lines must contain it, ignoring case
+    search: Option<BMHMatcher>,
+    /// Lines printed before and after each admitted line
+    pub context: usize,
+}
+
+impl LineFilter {
+    pub fn new(filters: FilterList, levels: Option<LevelSet>, search: Option<&str>) -> Self {
+        Self {
+            filters,
+            levels,
+            search: search
+                .filter(|query| !query.is_empty())
+                .map(|query| BMHMatcher::new(query.to_lowercase().into_bytes())),
+            context: 0,
+        }
+    }
+
+    /// Check whether a line, from the file at `path` if known, is printed.
+    pub fn admits(&self, text: &[u8], path: Option<&Path>) -> bool {
+        if !self.filters.matches_from(text, path) {
+            return false;
+        }
+        if let Some(levels) = self.levels {
+            if !detect_level(&String::from_utf8_lossy(text)).is_some_and(|l| levels.contains(l)) {
+                return false;
+            }
+        }
+        match &self.search {
+            Some(matcher) => matcher.contains(&text.to_ascii_lowercase()),
+            None => true,
+        }
+    }
+}
+
+/// Write the lines of `storage` that `filter` admits, and their context, in
+/// the order of the storage (by time for several files). Returns the number
+/// of lines written.
+pub fn print_storage(
+    storage: &LogStorage,
+    filter: &LineFilter,
+    verbatim: bool,
+    out: impl Write,
+) -> io::Result<usize> {
+    let mut writer = LineWriter::new(out, verbatim).grouped(filter.context > 0);
+    let mut context = (filter.context > 0).then(|| Context::new(filter.context));
+    let mut chunk = Vec::with_capacity(PRINT_CHUNK_LINES);
+    for idx in 0..storage.len() {
+        let Some(line) = storage.get_line(idx) else {
+            continue;
+        };
+        if filter.admits(line.as_bytes(), storage.line_path(idx)) {
+            match &mut context {
+                Some(context) => context.push_match(&mut chunk, idx, |_| true),
+                None => chunk.push(idx),
+            }
+        }
+        if chunk.len() >= PRINT_CHUNK_LINES {
+            writer.write(storage, &chunk)?;
+            chunk.clear();
+        }
+    }
+    if let Some(context) = &mut context {
+        context.fill_after(&mut chunk, storage.len(), |_| true);
+    }
+    writer.write(storage, &chunk)?;
+    Ok(writer.finish()?.lines)
+}
+
+/// Write the lines read from `reader` that `filter` admits, and their
+/// context, as they arrive. Output is flushed whenever the input has nothing
+/// more buffered, so a followed stream shows up without delay. Returns the
+/// number of lines written.
+pub fn print_reader(
+    mut reader: BufReader<impl Read>,
+    filter: &LineFilter,
+    mut out: impl Write,
+) -> io::Result<usize> {
+    let mut count = 0;
+    let mut buf = Vec::new();
+    // Lines since the last one written, up to the context before a match
+    let mut before: VecDeque<Vec<u8>> = VecDeque::with_capacity(filter.context);
+    // Lines still to write after the last match
+    let mut after = 0;
+    let mut line_no = 0;
+    let mut last_written: Option<usize> = None;
+    loop {
+        buf.clear();
+        if reader.read_until(b'\n', &mut buf)? == 0 {
+            break;
+        }
+        let line = buf.strip_suffix(b"\n").unwrap_or(&buf);
+        let line = line.strip_suffix(b"\r").unwrap_or(line);
+        if filter.admits(line, None) {
+            let first = line_no - before.len();
+            if filter.context > 0 && last_written.is_some_and(|last| first != last + 1) {
+                out.write_all(b"--\n")?;
+            }
+            for context in before.drain(..).chain([line.to_vec()]) {
+                out.write_all(&context)?;
+                out.write_all(b"\n")?;
+                count += 1;
+            }
+            last_written = Some(line_no);
+            after = filter.context;
+        } else if after > 0 {
+            out.write_all(line)?;
+            out.write_all(b"\n")?;
+            count += 1;
+            last_written = Some(line_no);
+            after -= 1;
+        } else if filter.context > 0 {
+            if before.len() == filter.context {
+                before.pop_front();
+            }
+            before.push_back(line.to_vec());
+        }
+        line_no += 1;
+        if reader.buffer().is_empty() {
+            out.flush()?;
+        }
+    }
+    out.flush()?;
+    Ok(count)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::{FilterKind, LogLevel};
+    use std::io::Write;
+    use tempfile::NamedTempFile;
+
+    const LINES: &str = "2024-01-01 10:00:00 INFO start\n\
+                         2024-01-01 10:00:01 ERROR Timeout talking to db\n\
+                         2024-01-01 10:00:02 WARN timeout retry\n\
+                         2024-01-01 10:00:03 ERROR healthcheck timeout\n";
+
+    fn filter(levels: Option<LevelSet>, search: Option<&str>) -> LineFilter {
+        let mut filters = FilterList::new();
+        filters.add(FilterKind::Exclude, "healthcheck");
+        LineFilter::new(filters, levels, search)
+    }
+
+    #[test]
+    fn test_print_storage_applies_filters_level_and_search() {
+        let mut file = NamedTempFile::new().unwrap();
+        file.write_all(LINES.as_bytes()).unwrap();
+        let storage = LogStorage::from_file(file.path()).unwrap();
+
+        let mut out = Vec::new();
+        let errors = filter(Some(LevelSet::only(LogLevel::Error)), Some("TIMEOUT"));
+        let count = print_storage(&storage, &errors, false, &mut out).unwrap();
+        assert_eq!(count, 1);
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "2024-01-01 10:00:01 ERROR Timeout talking to db\n"
+        );
+
+        let warnings = filter(LevelSet::parse("warn+"), Some("timeout"));
+        let count = print_storage(&storage, &warnings, false, &mut Vec::new()).unwrap();
+        assert_eq!(count, 2);
+    }
+
+    #[test]
+    fn test_print_context_with_separators() {
+        let lines: String = (0..10).map(|n| format!("line {}\n", n)).collect();
+        let mut filter = LineFilter::new(FilterList::new(), None, Some("2"));
+        filter.context = 1;
+        let lines = lines.replace("line 7", "line 72");
+        let expected = "line 1\nline 2\nline 3\n--\nline 6\nline 72\nline 8\n";
+
+        let mut file = NamedTempFile::new().unwrap();
+        file.write_all(lines.as_bytes()).unwrap();
+        let storage = LogStorage::from_file(file.path()).unwrap();
+        let mut out = Vec::new();
+        assert_eq!(
+            print_storage(&storage, &filter, false, &mut out).unwrap(),
+            6
+        );
+        assert_eq!(String::from_utf8(out).unwrap(), expected);
+
+        let mut out = Vec::new();
+        let count = print_reader(BufReader::new(lines.as_bytes()), &filter, &mut out);
+        assert_eq!(count.unwrap(), 6);
+        assert_eq!(String::from_utf8(out).unwrap(), expected);
+
+        // Without context, matches are not grouped
+        filter.context = 0;
+        let mut out = Vec::new();
+        print_storage(&storage, &filter, false, &mut out).unwrap();
+        assert_eq!(String::from_utf8(out).unwrap(), "line 2\nline 72\n");
+        let mut out = Vec::new();
+        print_reader(BufReader::new(lines.as_bytes()), &filter, &mut out).unwrap();
+        assert_eq!(String::from_utf8(out).unwrap(), "line 2\nline 72\n");
+    }
+
+    #[test]
+    fn test_print_reader_filters_piped_lines() {
+        let mut out = Vec::new();
+        let input = LINES.replace('\n', "\r\n");
+        let count = print_reader(
+            BufReader::new(input.as_bytes()),
+            &filter(None, Some("timeout")),
+            &mut out,
+        );
+        assert_eq!(count.unwrap(), 2);
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "2024-01-01 10:00:01 ERROR Timeout talking to db\n\
+             2024-01-01 10:00:02 WARN timeout retry\n"
+        );
+    }
+}
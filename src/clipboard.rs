@@ -1,3 +1,5 @@
+use std::io::Write;
+
 use arboard::Clipboard as ArboardClipboard;
 
 /// Error type for clipboard operations
@@ -20,34 +22,171 @@ impl std::fmt::Display for ClipboardError {
 
 impl std::error::Error for ClipboardError {}
 
+/// Where yanked text goes.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ClipboardBackend {
+    /// The system clipboard (`Ctrl+V` paste)
+    System,
+    /// The X11/Wayland primary selection (middle-click paste)
+    Primary,
+    /// Both the system clipboard and the primary selection
+    #[default]
+    Both,
+    /// The terminal's clipboard, through an OSC 52 escape sequence; works
+    /// over SSH and without a display server if the terminal supports it
+    Osc52,
+}
+
+impl ClipboardBackend {
+    /// Parse a backend name (`system`, `primary`, `both`, `osc52`).
+    pub fn parse(name: &str) -> Option<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "system" | "clipboard" => Some(Self::System),
+            "primary" => Some(Self::Primary),
+            "both" => Some(Self::Both),
+            "osc52" | "osc-52" | "terminal" => Some(Self::Osc52),
+            _ => None,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::System => "system",
+            Self::Primary => "primary",
+            Self::Both => "both",
+            Self::Osc52 => "osc52",
+        }
+    }
+
+    /// What the text is copied to, for status messages.
+    pub fn target(self) -> &'static str {
+        match self {
+            Self::Primary if HAS_PRIMARY => "primary selection",
+            Self::Both if HAS_PRIMARY => "clipboard and primary selection",
+            Self::Osc52 => "terminal clipboard",
+            _ => "clipboard",
+        }
+    }
+}
+
+/// Whether the platform has a primary selection; elsewhere `primary` and
+/// `both` copy to the system clipboard only.
+const HAS_PRIMARY: bool = cfg!(all(
+    unix,
+    not(any(
+        target_os = "macos",
+        target_os = "android",
+        target_os = "emscripten"
+    ))
+));
+
 /// Wrapper around arboard clipboard with error handling
 pub struct Clipboard {
-    inner: ArboardClipboard,
+    backend: ClipboardBackend,
+    /// None for OSC 52, which needs no display server
+    inner: Option<ArboardClipboard>,
 }
 
 impl std::fmt::Debug for Clipboard {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         f.debug_struct("Clipboard")
+            .field("backend", &self.backend)
             .field("inner", &"<arboard::Clipboard>")
             .finish()
     }
 }
 
 impl Clipboard {
-    /// Initialize clipboard (may fail on headless systems)
-    pub fn new() -> Result<Self, ClipboardError> {
-        let inner =
-            ArboardClipboard::new().map_err(|e| ClipboardError::InitFailed(e.to_string()))?;
+    /// Initialize clipboard (may fail on headless systems unless the
+    /// backend is OSC 52)
+    pub fn new(backend: ClipboardBackend) -> Result<Self, ClipboardError> {
+        let inner = match backend {
+            ClipboardBackend::Osc52 => None,
+            _ => Some(
+                ArboardClipboard::new().map_err(|e| ClipboardError::InitFailed(e.to_string()))?,
+            ),
+        };
+
+        Ok(Self { backend, inner })
+    }
 
-        Ok(Self { inner })
+    pub fn backend(&self) -> ClipboardBackend {
+        self.backend
     }
 
-    /// Copy text to system clipboard
+    /// Copy text to the clipboard of the backend
     pub fn copy(&mut self, text: &str) -> Result<(), ClipboardError> {
-        self.inner
-            .set_text(text)
-            .map_err(|e| ClipboardError::CopyFailed(e.to_string()))
+        let Some(inner) = &mut self.inner else {
+            return write_osc52(text).map_err(|e| ClipboardError::CopyFailed(e.to_string()));
+        };
+        let copied = match self.backend {
+            ClipboardBackend::Primary if HAS_PRIMARY => set_primary(inner, text),
+            ClipboardBackend::Both if HAS_PRIMARY => {
+                inner.set_text(text).and_then(|()| set_primary(inner, text))
+            }
+            _ => inner.set_text(text),
+        };
+        copied.map_err(|e| ClipboardError::CopyFailed(e.to_string()))
+    }
+}
+
+#[cfg(all(
+    unix,
+    not(any(target_os = "macos", target_os = "android", target_os = "emscripten"))
+))]
+fn set_primary(inner: &mut ArboardClipboard, text: &str) -> Result<(), arboard::Error> {
+    use arboard::{LinuxClipboardKind, SetExtLinux};
+    inner
+        .set()
+        .clipboard(LinuxClipboardKind::Primary)
+        .text(text.to_string())
+}
+
+#[cfg(not(all(
+    unix,
+    not(any(target_os = "macos", target_os = "android", target_os = "emscripten"))
+)))]
+fn set_primary(inner: &mut ArboardClipboard, text: &str) -> Result<(), arboard::Error> {
+    inner.set_text(text)
+}
+
+/// Hand `text` to the terminal's clipboard. Inside tmux the sequence is
+/// passed through to the outer terminal.
+fn write_osc52(text: &str) -> std::io::Result<()> {
+    let tmux = std::env::var_os("TMUX").is_some();
+    let mut stdout = std::io::stdout();
+    stdout.write_all(osc52_sequence(text, tmux).as_bytes())?;
+    stdout.flush()
+}
+
+/// The OSC 52 escape sequence setting the clipboard to `text`.
+fn osc52_sequence(text: &str, tmux: bool) -> String {
+    let osc = format!("\x1b]52;c;{}\x07", base64(text.as_bytes()));
+    if tmux {
+        format!("\x1bPtmux;{}\x1b\\", osc.replace('\x1b', "\x1b\x1b"))
+    } else {
+        osc
+    }
+}
+
+/// Standard base64 with padding.
+fn base64(bytes: &[u8]) -> String {
+    const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+    let mut out = String::with_capacity(bytes.len().div_ceil(3) * 4);
+    for chunk in bytes.chunks(3) {
+        let n = chunk
+            .iter()
+            .enumerate()
+            .fold(0u32, |n, (i, &b)| n | (b as u32) << (16 - 8 * i));
+        for i in 0..4 {
+            if i <= chunk.len() {
+                out.push(ALPHABET[(n >> (18 - 6 * i) & 0x3f) as usize] as char);
+            } else {
+                out.push('=');
+            }
+        }
     }
+    out
 }
 
 #[cfg(test)]
@@ -62,4 +201,34 @@ mod tests {
         let err = ClipboardError::CopyFailed("access denied".to_string());
         assert!(err.to_string().contains("copy failed"));
     }
+
+    #[test]
+    fn test_backend_names() {
+        for backend in [
+            ClipboardBackend::System,
+            ClipboardBackend::Primary,
+            ClipboardBackend::Both,
+            ClipboardBackend::Osc52,
+        ] {
+            assert_eq!(ClipboardBackend::parse(backend.name()), Some(backend));
+        }
+        assert_eq!(
+            ClipboardBackend::parse("OSC-52"),
+            Some(ClipboardBackend::Osc52)
+        );
+        assert_eq!(ClipboardBackend::parse("secondary"), None);
+    }
+
+    #[test]
+    fn test_osc52_sequence() {
+        assert_eq!(base64(b""), "");
+        assert_eq!(base64(b"f"), "Zg==");
+        assert_eq!(base64(b"fo"), "Zm8=");
+        assert_eq!(base64(b"foobar"), "Zm9vYmFy");
+        assert_eq!(osc52_sequence("hi\n", false), "\x1b]52;c;aGkK\x07");
+        assert_eq!(
+            osc52_sequence("hi\n", true),
+            "\x1bPtmux;\x1b\x1b]52;c;aGkK\x07\x1b\\"
+        );
+    }
 }
@@ -0,0 +1,199 @@
+//! Column view of structured lines (`:columns`).
+//!
+//! JSON lines and lines with `key=value` pairs are shown as aligned columns
+//! of chosen fields (time, level, source and message by default) instead of
+//! their raw text. Column widths follow the widest value on screen, up to a
+//! limit; the last column takes the rest of the row. Lines with neither
+//! JSON nor `key=value` pairs are shown as they are.
+
+use crate::export::{json_field, logfmt_field, LineRecord, SOURCE_KEYS};
+use crate::source::LineSource;
+
+/// Fields shown by `:columns` without an argument.
+pub const DEFAULT_FIELDS: &[&str] = &["ts", "level", "source", "msg"];
+
+/// Widest a column other than the last gets before values are cut.
+const MAX_COLUMN_WIDTH: usize = 40;
+
+/// Space between columns.
+const COLUMN_GAP: &str = "  ";
+
+/// Fields shown as columns.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Columns {
+    fields: Vec<String>,
+}
+
+impl Default for Columns {
+    fn default() -> Self {
+        Self::new(
+            DEFAULT_FIELDS
+                .iter()
+                .map(|field| field.to_string())
+                .collect(),
+        )
+    }
+}
+
+impl Columns {
+    pub fn new(fields: Vec<String>) -> Self {
+        Self { fields }
+    }
+
+    /// Parse a comma-separated field list such as `ts,level,msg`.
+    pub fn parse(spec: &str) -> Option<Self> {
+        let fields: Vec<String> = spec
+            .split(',')
+            .map(str::trim)
+            .filter(|field| !field.is_empty())
+            .map(str::to_string)
+            .collect();
+        (!fields.is_empty()).then(|| Self::new(fields))
+    }
+
+    pub fn fields(&self) -> &[String] {
+        &self.fields
+    }
+
+    /// The fields as `ts,level,msg`.
+    pub fn label(&self) -> String {
+        self.fields.join(",")
+    }
+
+    /// Values of the fields of line `idx`, or None for a line that is
+    /// neither JSON nor has `key=value` pairs.
+    pub fn cells(&self, source: &dyn LineSource, idx: usize) -> Option<Vec<String>> {
+        let record = LineRecord::read(source, idx)?;
+        let structured = match &record.json {
+            Some(json) => json.is_object(),
+            None => !record.fields().is_empty(),
+        };
+        if !structured {
+            return None;
+        }
+        Some(
+            self.fields
+                .iter()
+                .map(|field| cell_text(&field_value(&record, field)))
+                .collect(),
+        )
+    }
+
+    /// Lay rows of cells out as aligned text; rows without cells stay None.
+    pub fn layout(rows: &[Option<Vec<String>>]) -> Vec<Option<String>> {
+        let mut widths: Vec<usize> = Vec::new();
+        for cells in rows.iter().flatten() {
+            widths.resize(widths.len().max(cells.len()), 0);
+            for (width, cell) in widths.iter_mut().zip(cells) {
+                *width = (*width).max(cell.chars().count().min(MAX_COLUMN_WIDTH));
+            }
+        }
+        rows.iter()
+            .map(|cells| {
+                let cells = cells.as_ref()?;
+                let last = cells.len().saturating_sub(1);
+                let row: Vec<String> = cells
+                    .iter()
+                    .zip(&widths)
+                    .enumerate()
+                    .map(|(col, (cell, &width))| {
+                        if col == last {
+                            cell.clone()
+                        } else {
+                            format!("{:<width$}", truncate(cell, width), width = width)
+                        }
+                    })
+                    .collect();
+                Some(row.join(COLUMN_GAP).trim_end().to_string())
+            })
+            .collect()
+    }
+}
+
+/// Value of `field` in a line: `ts`, `level`, `msg` and `source` name the
+/// usual keys for them; other names are JSON keys (`a.b` for nested ones)
+/// or `key=value` pairs.
+fn field_value(record: &LineRecord, field: &str) -> String {
+    let lookup = |keys: &[&str]| {
+        keys.iter().find_map(|key| match &record.json {
+            Some(json) => json_field(json, key),
+            None => logfmt_field(&record.text, key),
+        })
+    };
+    match field.to_ascii_lowercase().as_str() {
+        "ts" | "time" | "timestamp" => record.time.clone(),
+        "level" | "lvl" => record.level.clone(),
+        "msg" | "message" => match record.json {
+            Some(_) => record.message.clone(),
+            None => lookup(&["msg", "message"]).unwrap_or_else(|| record.text.clone()),
+        },
+        "source" => lookup(SOURCE_KEYS).unwrap_or_default(),
+        _ => lookup(&[field]).unwrap_or_default(),
+    }
+}
+
+/// A value on one line: line breaks and tabs become spaces.
+fn cell_text(value: &str) -> String {
+    value.replace(['\n', '\r', '\t'], " ")
+}
+
+/// Cut `text` to `width` characters, ending with `…` when cut.
+fn truncate(text: &str, width: usize) -> String {
+    if text.chars().count() <= width {
+        return text.to_string();
+    }
+    let mut cut: String = text.chars().take(width.saturating_sub(1)).collect();
+    cut.push('…');
+    cut
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::log_storage::storage_from;
+
+    #[test]
+    fn test_cells_of_json_logfmt_and_plain_lines() {
+        let (_file, storage) = storage_from(&[
+            r#"{"time":"2024-01-01T10:00:00Z","level":"warn","logger":"db","msg":"slow query","ms":812}"#,
+            r#"ts=10:00:01 level=info source=api msg="GET /orders" status=200"#,
+            "just some text",
+        ]);
+
+        let columns = Columns::parse("level, source,msg,ms").unwrap();
+        assert_eq!(
+            columns.cells(&storage, 0).unwrap(),
+            ["warn", "db", "slow query", "812"]
+        );
+        assert_eq!(
+            columns.cells(&storage, 1).unwrap(),
+            ["info", "api", "GET /orders", ""]
+        );
+        assert_eq!(columns.cells(&storage, 2), None);
+        assert_eq!(Columns::parse(" , "), None);
+    }
+
+    #[test]
+    fn test_layout_aligns_columns() {
+        let long = "x".repeat(50);
+        let rows = vec![
+            Some(vec![
+                "warn".to_string(),
+                "db".to_string(),
+                "slow".to_string(),
+            ]),
+            None,
+            Some(vec!["info".to_string(), long.clone(), "ok".to_string()]),
+        ];
+        let laid_out = Columns::layout(&rows);
+        assert_eq!(
+            laid_out[0].as_deref(),
+            Some(format!("warn  db{}  slow", " ".repeat(38)).as_str())
+        );
+        assert_eq!(laid_out[1], None);
+        assert_eq!(
+            laid_out[2].as_deref(),
+            Some(format!("info  {}…  ok", "x".repeat(39)).as_str())
+        );
+    }
+}
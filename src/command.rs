@@ -1,22 +1,280 @@
-use crate::model::FilterKind;
+use crate::clipboard::ClipboardBackend;
+use crate::columns::Columns;
+use crate::model::{CaseMode, FilterKind, LevelSet};
+use crate::replay::Replay;
+use crate::theme::Gutter;
 use chrono::Local;
+use std::path::PathBuf;
 
-const COMMANDS: &[&str] = &[
-    "filter",
-    "filter-clear",
-    "filter-out",
-    "list-filters",
-    "quit",
-    "write",
+/// Built-in commands: name, arguments and what the command does, as the
+/// help overlay lists them. Completion offers the names in this order.
+const COMMANDS: &[(&str, &str, &str)] = &[
+    (
+        "filter",
+        "<pattern>",
+        "Show only lines matching the pattern",
+    ),
+    ("filter-clear", "", "Remove all filters"),
+    ("filter-out", "<pattern>", "Hide lines matching the pattern"),
+    ("list-filters", "", "View and manage the filters"),
+    ("quit", "", "Quit (also q)"),
+    (
+        "write",
+        "[--verbatim] [--context lines] [file]",
+        "Save the lines in view to a file (also w)",
+    ),
+    (
+        "bookmark",
+        "[clear]",
+        "Open the bookmark list, or remove every bookmark",
+    ),
+    (
+        "bookmarks-export",
+        "[file]",
+        "Write a markdown report of pins and bookmarks",
+    ),
+    ("note", "[text]", "Attach a note to the line, or clear it"),
+    ("recent", "", "Show recently opened file sets"),
+    ("theme", "[name]", "Switch theme, or list the themes"),
+    (
+        "gutter",
+        "[off|letters|symbols]",
+        "Set or cycle the level gutter",
+    ),
+    (
+        "clipboard",
+        "[system|primary|both|osc52]",
+        "Choose where y copies to",
+    ),
+    ("ruler", "[column|off]", "Mark a column with a ruler"),
+    (
+        "time",
+        "",
+        "Show the time between the first and last selected line",
+    ),
+    ("pins", "", "Manage pinned lines"),
+    ("errors", "", "List compiler and test errors in the log"),
+    (
+        "gaps",
+        "",
+        "Chart the time between search matches or lines in view",
+    ),
+    (
+        "exceptions",
+        "",
+        "Group the exceptions in view by type and top frame",
+    ),
+    ("messages", "", "Show every status message (also mes)"),
+    ("pods", "", "List the pods of a kubectl logs stream"),
+    (
+        "session",
+        "save|load|clear",
+        "Save, restore or forget the view of these files",
+    ),
+    ("dim", "<pattern>", "Dim lines matching the pattern"),
+    (
+        "throttle",
+        "[count|off]",
+        "Show only the first lines of each repeated message",
+    ),
+    (
+        "context",
+        "[lines|off]",
+        "Show lines around each line that passes the filters",
+    ),
+    (
+        "columns",
+        "[field,...|off]",
+        "Show structured lines as columns",
+    ),
+    ("tutor", "[skip|off]", "Start or leave the guided tutorial"),
+    ("tabnew", "<file> [file...]", "Open files in a new tab"),
+    ("tabclose", "", "Close the tab shown (also tabc)"),
+    ("tabnext", "", "Switch to the next tab (also tabn)"),
+    ("tabprevious", "", "Switch to the previous tab (also tabp)"),
+    (
+        "watch",
+        "<text|key=value>",
+        "Count matching lines in the watch sidebar",
+    ),
+    (
+        "unwatch",
+        "[number|expression]",
+        "Remove a watch, or all of them",
+    ),
+    (
+        "replay",
+        "[speed] [time]|pause|stop",
+        "Play the log back from a time",
+    ),
+    (
+        "table",
+        "[field ...] [> file]",
+        "Copy the selection as a markdown table",
+    ),
+    ("filter-file", "<path>", "Add the filters listed in a file"),
+    ("range", "[from..to|off]", "Limit searches to a time range"),
+    ("sql", "<query>", "Run SQL over the lines in view"),
+    (
+        "stats",
+        "[off]",
+        "Show level counts, sources and a time histogram",
+    ),
+    (
+        "level",
+        "<levels>|off",
+        "Show only lines of the given levels",
+    ),
+    ("hex", "", "Open the hex view of the line"),
+    (
+        "set",
+        "ignorecase|noignorecase|smartcase|nosmartcase|word|noword",
+        "Choose how searches treat case and whole words",
+    ),
 ];
 
 #[derive(Debug, Clone, PartialEq)]
 pub enum CommandEffect {
     Quit,
-    AddFilter { kind: FilterKind, pattern: String },
+    AddFilter {
+        kind: FilterKind,
+        pattern: String,
+        /// Source file the filter is limited to (`--file worker.log`)
+        file: Option<String>,
+    },
     ClearFilters,
-    WriteFilteredLogs { filename: String },
+    WriteFilteredLogs {
+        filename: String,
+        /// Write the lines' original bytes and line endings
+        verbatim: bool,
+        /// Write the matches with this many lines around each, and `--`
+        /// between groups (`--context 3`)
+        context: Option<usize>,
+    },
     ListFilters,
+    SetNote {
+        note: String,
+    },
+    ExportBookmarks {
+        filename: String,
+    },
+    ListBookmarks,
+    ClearBookmarks,
+    ShowRecent,
+    SetTheme {
+        name: Option<String>,
+    },
+    SetGutter {
+        gutter: Option<Gutter>,
+    },
+    /// Switch the clipboard backend; None shows the current one
+    SetClipboard {
+        backend: Option<ClipboardBackend>,
+    },
+    SetRuler {
+        column: Option<usize>,
+    },
+    ToggleRuler,
+    SetThrottle {
+        limit: Option<usize>,
+    },
+    ToggleThrottle,
+    /// Show `lines` lines around each line that passes the filters, or none
+    SetContext {
+        lines: Option<usize>,
+    },
+    ToggleContext,
+    /// How searches treat case (`:set ignorecase`, `:set smartcase`)
+    SetCaseMode {
+        mode: CaseMode,
+    },
+    /// Match searches and filters as whole words only (`:set word`)
+    SetWholeWord {
+        whole_word: bool,
+    },
+    /// Show structured lines as columns of the given fields, or as text
+    SetColumns {
+        columns: Option<Columns>,
+    },
+    ToggleColumns,
+    /// Start the tutorial over on the open logs
+    StartTutor,
+    /// Go on to the next step of the tutorial
+    SkipTutorStep,
+    CloseTutor,
+    /// Open files in a new tab
+    OpenTab {
+        paths: Vec<PathBuf>,
+    },
+    CloseTab,
+    /// Switch to the next tab, or the previous one
+    StepTab {
+        forward: bool,
+    },
+    /// Count the lines matching an expression in the watch sidebar
+    AddWatch {
+        expr: String,
+    },
+    /// Stop the watch with this number or expression, or every watch
+    RemoveWatch {
+        target: Option<String>,
+    },
+    /// Replay the log from a time (the cursor line's by default), or change
+    /// the speed of the running replay
+    StartReplay {
+        speed: Option<u32>,
+        from: Option<String>,
+    },
+    PauseReplay,
+    /// End the replay and show every line
+    StopReplay,
+    ShowTime,
+    ListPins,
+    /// Open the list of compiler and test-runner diagnostics in the log
+    ListErrors,
+    /// Open the exception groups of the lines in view
+    ListExceptions,
+    /// Chart the time between consecutive matches or lines in view
+    ShowGaps,
+    /// Show the log of status messages
+    ListMessages,
+    /// Open the list of pods in a `kubectl logs --prefix` stream
+    ListPods,
+    /// Store the view state of the loaded files in the session file
+    SaveSession,
+    /// Put back the stored view state of the loaded files
+    LoadSession,
+    /// Forget the stored view state and stop saving it on quit
+    ClearSession,
+    LoadFilterFile {
+        path: String,
+    },
+    /// Copy the selection as a markdown table, or write it to `filename`
+    ExportTable {
+        fields: Vec<String>,
+        filename: Option<String>,
+    },
+    /// Limit searches to a time range, or to the selection if both ends are
+    /// None
+    SetSearchRange {
+        from: Option<String>,
+        to: Option<String>,
+    },
+    ClearSearchRange,
+    /// Query the filtered lines (and the sidecar tables, if enabled)
+    Sql {
+        query: String,
+    },
+    /// Open the stats overlay of the lines in view
+    ShowStats,
+    /// Show the bytes of the current line as a hex dump
+    ShowHex,
+    /// Drop the level and time limits set from the stats overlay
+    ClearDrillDown,
+    /// Show only lines of the given levels, or lines of every level
+    SetLevels {
+        levels: Option<LevelSet>,
+    },
 }
 
 #[derive(Debug, Clone)]
@@ -25,8 +283,10 @@ pub struct CommandResult {
     pub status: String,
 }
 
-pub fn parse(input: &str) -> CommandResult {
-    let (cmd, arg) = split_command(input);
+/// Parse a command line, expanding a leading alias from `aliases` first.
+pub fn parse(input: &str, aliases: &[(String, String)]) -> CommandResult {
+    let expanded = alias_expansion(input, aliases);
+    let (cmd, arg) = split_command(expanded.as_deref().unwrap_or(input));
 
     match cmd {
         "q" | "quit" => CommandResult {
@@ -34,41 +294,82 @@ pub fn parse(input: &str) -> CommandResult {
             status: String::new(),
         },
         "w" | "write" => {
-            let filename = arg.map(|s| s.to_string()).unwrap_or_else(|| {
-                let timestamp = Local::now().format("%Y%m%d-%H%M%S");
-                format!("filtered-logs-{}.log", timestamp)
-            });
+            let Some((verbatim, context, arg)) = write_options(arg.unwrap_or("")) else {
+                return CommandResult {
+                    effect: None,
+                    status: "Usage: write [--verbatim] [--context lines] [filename]".to_string(),
+                };
+            };
+            let filename = Some(arg)
+                .filter(|arg| !arg.is_empty())
+                .map(|s| s.to_string())
+                .unwrap_or_else(|| {
+                    let timestamp = Local::now().format("%Y%m%d-%H%M%S");
+                    format!("filtered-logs-{}.log", timestamp)
+                });
             CommandResult {
-                effect: Some(CommandEffect::WriteFilteredLogs { filename }),
+                effect: Some(CommandEffect::WriteFilteredLogs {
+                    filename,
+                    verbatim,
+                    context,
+                }),
                 status: String::new(),
             }
         }
-        "filter" => match arg {
-            Some(pattern) if !pattern.is_empty() => CommandResult {
+        "filter" => match arg.map(file_scope) {
+            Some((file, pattern)) if !pattern.is_empty() => CommandResult {
+                status: format!("Added filter: {}{}", pattern, scope_label(&file)),
                 effect: Some(CommandEffect::AddFilter {
                     kind: FilterKind::Include,
                     pattern: pattern.to_string(),
+                    file,
                 }),
-                status: format!("Added filter: {}", pattern),
             },
             _ => CommandResult {
                 effect: None,
                 status: "Usage: filter <pattern>".to_string(),
             },
         },
-        "filter-out" => match arg {
-            Some(pattern) if !pattern.is_empty() => CommandResult {
+        "filter-out" => match arg.map(file_scope) {
+            Some((file, pattern)) if !pattern.is_empty() => CommandResult {
+                status: format!("Added filter-out: {}{}", pattern, scope_label(&file)),
                 effect: Some(CommandEffect::AddFilter {
                     kind: FilterKind::Exclude,
                     pattern: pattern.to_string(),
+                    file,
                 }),
-                status: format!("Added filter-out: {}", pattern),
             },
             _ => CommandResult {
                 effect: None,
                 status: "Usage: filter-out <pattern>".to_string(),
             },
         },
+        "dim" => match arg.map(file_scope) {
+            Some((file, pattern)) if !pattern.is_empty() => CommandResult {
+                status: format!("Dimming: {}{}", pattern, scope_label(&file)),
+                effect: Some(CommandEffect::AddFilter {
+                    kind: FilterKind::Dim,
+                    pattern: pattern.to_string(),
+                    file,
+                }),
+            },
+            _ => CommandResult {
+                effect: None,
+                status: "Usage: dim <pattern>".to_string(),
+            },
+        },
+        "filter-file" => match arg {
+            Some(path) => CommandResult {
+                effect: Some(CommandEffect::LoadFilterFile {
+                    path: path.to_string(),
+                }),
+                status: String::new(),
+            },
+            None => CommandResult {
+                effect: None,
+                status: "Usage: filter-file <path>".to_string(),
+            },
+        },
         "filter-clear" => CommandResult {
             effect: Some(CommandEffect::ClearFilters),
             status: "Filters cleared".to_string(),
@@ -77,6 +378,417 @@ pub fn parse(input: &str) -> CommandResult {
             effect: Some(CommandEffect::ListFilters),
             status: String::new(),
         },
+        "note" => CommandResult {
+            effect: Some(CommandEffect::SetNote {
+                note: arg.unwrap_or_default().to_string(),
+            }),
+            status: String::new(),
+        },
+        "bookmark" | "bookmarks" => match arg {
+            None => CommandResult {
+                effect: Some(CommandEffect::ListBookmarks),
+                status: String::new(),
+            },
+            Some("clear") => CommandResult {
+                effect: Some(CommandEffect::ClearBookmarks),
+                status: String::new(),
+            },
+            Some(_) => CommandResult {
+                effect: None,
+                status: "Usage: bookmark [clear]".to_string(),
+            },
+        },
+        "bookmarks-export" => {
+            let filename = arg.map(|s| s.to_string()).unwrap_or_else(|| {
+                let timestamp = Local::now().format("%Y%m%d-%H%M%S");
+                format!("bookmarks-{}.md", timestamp)
+            });
+            CommandResult {
+                effect: Some(CommandEffect::ExportBookmarks { filename }),
+                status: String::new(),
+            }
+        }
+        "recent" => CommandResult {
+            effect: Some(CommandEffect::ShowRecent),
+            status: String::new(),
+        },
+        "theme" => CommandResult {
+            effect: Some(CommandEffect::SetTheme {
+                name: arg.map(|s| s.to_string()),
+            }),
+            status: String::new(),
+        },
+        "gutter" => match arg.map(Gutter::parse) {
+            Some(None) => CommandResult {
+                effect: None,
+                status: "Usage: gutter [off|letters|symbols]".to_string(),
+            },
+            gutter => CommandResult {
+                effect: Some(CommandEffect::SetGutter {
+                    gutter: gutter.flatten(),
+                }),
+                status: String::new(),
+            },
+        },
+        "clipboard" => match arg.map(ClipboardBackend::parse) {
+            Some(None) => CommandResult {
+                effect: None,
+                status: "Usage: clipboard [system|primary|both|osc52]".to_string(),
+            },
+            backend => CommandResult {
+                effect: Some(CommandEffect::SetClipboard {
+                    backend: backend.flatten(),
+                }),
+                status: String::new(),
+            },
+        },
+        "ruler" => match arg {
+            None => CommandResult {
+                effect: Some(CommandEffect::ToggleRuler),
+                status: String::new(),
+            },
+            Some("off") => CommandResult {
+                effect: Some(CommandEffect::SetRuler { column: None }),
+                status: String::new(),
+            },
+            Some(value) => match value.parse::<usize>() {
+                Ok(column) if column > 0 => CommandResult {
+                    effect: Some(CommandEffect::SetRuler {
+                        column: Some(column),
+                    }),
+                    status: String::new(),
+                },
+                _ => CommandResult {
+                    effect: None,
+                    status: "Usage: ruler [column|off]".to_string(),
+                },
+            },
+        },
+        "throttle" => match arg {
+            None => CommandResult {
+                effect: Some(CommandEffect::ToggleThrottle),
+                status: String::new(),
+            },
+            Some("off") => CommandResult {
+                effect: Some(CommandEffect::SetThrottle { limit: None }),
+                status: String::new(),
+            },
+            Some(value) => match value.parse::<usize>() {
+                Ok(limit) if limit > 0 => CommandResult {
+                    effect: Some(CommandEffect::SetThrottle { limit: Some(limit) }),
+                    status: String::new(),
+                },
+                _ => CommandResult {
+                    effect: None,
+                    status: "Usage: throttle [count|off]".to_string(),
+                },
+            },
+        },
+        "context" => match arg {
+            None => CommandResult {
+                effect: Some(CommandEffect::ToggleContext),
+                status: String::new(),
+            },
+            Some("off") => CommandResult {
+                effect: Some(CommandEffect::SetContext { lines: None }),
+                status: String::new(),
+            },
+            Some(value) => match value.parse::<usize>() {
+                Ok(lines) => CommandResult {
+                    effect: Some(CommandEffect::SetContext {
+                        lines: Some(lines).filter(|&lines| lines > 0),
+                    }),
+                    status: String::new(),
+                },
+                Err(_) => CommandResult {
+                    effect: None,
+                    status: "Usage: context [lines|off]".to_string(),
+                },
+            },
+        },
+        "columns" => match arg {
+            None => CommandResult {
+                effect: Some(CommandEffect::ToggleColumns),
+                status: String::new(),
+            },
+            Some("off") => CommandResult {
+                effect: Some(CommandEffect::SetColumns { columns: None }),
+                status: String::new(),
+            },
+            Some(spec) => match Columns::parse(spec) {
+                Some(columns) => CommandResult {
+                    effect: Some(CommandEffect::SetColumns {
+                        columns: Some(columns),
+                    }),
+                    status: String::new(),
+                },
+                None => CommandResult {
+                    effect: None,
+                    status: "Usage: columns [field,field,...|off]".to_string(),
+                },
+            },
+        },
+        "tutor" => match arg {
+            None => CommandResult {
+                effect: Some(CommandEffect::StartTutor),
+                status: String::new(),
+            },
+            Some("skip") => CommandResult {
+                effect: Some(CommandEffect::SkipTutorStep),
+                status: String::new(),
+            },
+            Some("off") => CommandResult {
+                effect: Some(CommandEffect::CloseTutor),
+                status: String::new(),
+            },
+            Some(_) => CommandResult {
+                effect: None,
+                status: "Usage: tutor [skip|off]".to_string(),
+            },
+        },
+        "tabnew" | "tabe" => match arg {
+            Some(arg) => CommandResult {
+                effect: Some(CommandEffect::OpenTab {
+                    paths: arg.split_whitespace().map(PathBuf::from).collect(),
+                }),
+                status: String::new(),
+            },
+            None => CommandResult {
+                effect: None,
+                status: "Usage: tabnew <file> [file...]".to_string(),
+            },
+        },
+        "tabclose" | "tabc" => CommandResult {
+            effect: Some(CommandEffect::CloseTab),
+            status: String::new(),
+        },
+        "tabnext" | "tabn" => CommandResult {
+            effect: Some(CommandEffect::StepTab { forward: true }),
+            status: String::new(),
+        },
+        "tabprevious" | "tabp" | "tabN" => CommandResult {
+            effect: Some(CommandEffect::StepTab { forward: false }),
+            status: String::new(),
+        },
+        "watch" => match arg {
+            Some(expr) => CommandResult {
+                effect: Some(CommandEffect::AddWatch {
+                    expr: expr.to_string(),
+                }),
+                status: String::new(),
+            },
+            None => CommandResult {
+                effect: None,
+                status: "Usage: watch <text|key=value>".to_string(),
+            },
+        },
+        "unwatch" => CommandResult {
+            effect: Some(CommandEffect::RemoveWatch {
+                target: arg.map(str::to_string),
+            }),
+            status: String::new(),
+        },
+        "replay" => {
+            let effect = match arg {
+                Some("pause") => CommandEffect::PauseReplay,
+                Some("stop" | "off") => CommandEffect::StopReplay,
+                _ => {
+                    let (first, rest) = match arg {
+                        Some(arg) => match arg.split_once(' ') {
+                            Some((first, rest)) => (Some(first), Some(rest.trim())),
+                            None => (Some(arg), None),
+                        },
+                        None => (None, None),
+                    };
+                    match first.and_then(Replay::parse_speed) {
+                        Some(speed) => CommandEffect::StartReplay {
+                            speed: Some(speed),
+                            from: rest.map(str::to_string),
+                        },
+                        None => CommandEffect::StartReplay {
+                            speed: None,
+                            from: arg.map(str::to_string),
+                        },
+                    }
+                }
+            };
+            CommandResult {
+                effect: Some(effect),
+                status: String::new(),
+            }
+        }
+        "time" => CommandResult {
+            effect: Some(CommandEffect::ShowTime),
+            status: String::new(),
+        },
+        "table" => {
+            let (fields, filename) = match arg.and_then(|arg| arg.split_once('>')) {
+                Some((fields, filename)) => (fields, Some(filename.trim())),
+                None => (arg.unwrap_or_default(), None),
+            };
+            match filename {
+                Some("") => CommandResult {
+                    effect: None,
+                    status: "Usage: table [field ...] [> file]".to_string(),
+                },
+                filename => CommandResult {
+                    effect: Some(CommandEffect::ExportTable {
+                        fields: fields
+                            .split([' ', ','])
+                            .filter(|field| !field.is_empty())
+                            .map(str::to_string)
+                            .collect(),
+                        filename: filename.map(str::to_string),
+                    }),
+                    status: String::new(),
+                },
+            }
+        }
+        "range" => match arg {
+            None => CommandResult {
+                effect: Some(CommandEffect::SetSearchRange {
+                    from: None,
+                    to: None,
+                }),
+                status: String::new(),
+            },
+            Some("off") => CommandResult {
+                effect: Some(CommandEffect::ClearSearchRange),
+                status: String::new(),
+            },
+            Some(arg) => match arg.split_once("..") {
+                Some((from, to)) if !(from.trim().is_empty() && to.trim().is_empty()) => {
+                    let bound =
+                        |time: &str| Some(time.trim().to_string()).filter(|t| !t.is_empty());
+                    CommandResult {
+                        effect: Some(CommandEffect::SetSearchRange {
+                            from: bound(from),
+                            to: bound(to),
+                        }),
+                        status: String::new(),
+                    }
+                }
+                _ => CommandResult {
+                    effect: None,
+                    status: "Usage: range [from..to|off]".to_string(),
+                },
+            },
+        },
+        "sql" => match arg {
+            Some(query) => CommandResult {
+                effect: Some(CommandEffect::Sql {
+                    query: query.to_string(),
+                }),
+                status: String::new(),
+            },
+            None => CommandResult {
+                effect: None,
+                status: "Usage: sql <query>".to_string(),
+            },
+        },
+        "hex" => CommandResult {
+            effect: Some(CommandEffect::ShowHex),
+            status: String::new(),
+        },
+        "set" => {
+            let effect = match arg {
+                Some("ignorecase" | "ic" | "nosmartcase" | "noscs") => {
+                    Some(CommandEffect::SetCaseMode {
+                        mode: CaseMode::Ignore,
+                    })
+                }
+                Some("smartcase" | "scs") => Some(CommandEffect::SetCaseMode {
+                    mode: CaseMode::Smart,
+                }),
+                Some("noignorecase" | "noic") => Some(CommandEffect::SetCaseMode {
+                    mode: CaseMode::Sensitive,
+                }),
+                Some("word") => Some(CommandEffect::SetWholeWord { whole_word: true }),
+                Some("noword") => Some(CommandEffect::SetWholeWord { whole_word: false }),
+                _ => None,
+            };
+            match effect {
+                Some(effect) => CommandResult {
+                    effect: Some(effect),
+                    status: String::new(),
+                },
+                None => CommandResult {
+                    effect: None,
+                    status: "Usage: set ignorecase|noignorecase|smartcase|nosmartcase|word|noword"
+                        .to_string(),
+                },
+            }
+        }
+        "stats" => match arg {
+            None => CommandResult {
+                effect: Some(CommandEffect::ShowStats),
+                status: String::new(),
+            },
+            Some("off") => CommandResult {
+                effect: Some(CommandEffect::ClearDrillDown),
+                status: String::new(),
+            },
+            Some(_) => CommandResult {
+                effect: None,
+                status: "Usage: stats [off]".to_string(),
+            },
+        },
+        "level" => match arg {
+            Some("off") => CommandResult {
+                effect: Some(CommandEffect::SetLevels { levels: None }),
+                status: String::new(),
+            },
+            Some(spec) if LevelSet::parse(spec).is_some() => CommandResult {
+                effect: Some(CommandEffect::SetLevels {
+                    levels: LevelSet::parse(spec),
+                }),
+                status: String::new(),
+            },
+            _ => CommandResult {
+                effect: None,
+                status: "Usage: level <level>[+][,<level>...] | off (e.g. level warn+)".to_string(),
+            },
+        },
+        "pins" => CommandResult {
+            effect: Some(CommandEffect::ListPins),
+            status: String::new(),
+        },
+        "pods" => CommandResult {
+            effect: Some(CommandEffect::ListPods),
+            status: String::new(),
+        },
+        "errors" | "copen" => CommandResult {
+            effect: Some(CommandEffect::ListErrors),
+            status: String::new(),
+        },
+        "gaps" => CommandResult {
+            effect: Some(CommandEffect::ShowGaps),
+            status: String::new(),
+        },
+        "exceptions" => CommandResult {
+            effect: Some(CommandEffect::ListExceptions),
+            status: String::new(),
+        },
+        "messages" | "mes" => CommandResult {
+            effect: Some(CommandEffect::ListMessages),
+            status: String::new(),
+        },
+        "session" => {
+            let effect = match arg {
+                Some("save") => Some(CommandEffect::SaveSession),
+                Some("load") => Some(CommandEffect::LoadSession),
+                Some("clear") => Some(CommandEffect::ClearSession),
+                _ => None,
+            };
+            CommandResult {
+                status: if effect.is_some() {
+                    String::new()
+                } else {
+                    "Usage: session save|load|clear".to_string()
+                },
+                effect,
+            }
+        }
         "" => CommandResult {
             effect: None,
             status: String::new(),
@@ -88,6 +800,44 @@ pub fn parse(input: &str) -> CommandResult {
     }
 }
 
+/// Split the leading `--verbatim` and `--context <lines>` options off a
+/// write argument, in either order. None if `--context` has no number.
+fn write_options(mut arg: &str) -> Option<(bool, Option<usize>, &str)> {
+    let mut verbatim = false;
+    let mut context = None;
+    loop {
+        let (option, rest) = arg.split_once(' ').unwrap_or((arg, ""));
+        match option {
+            "--verbatim" => verbatim = true,
+            "--context" => {
+                let rest = rest.trim_start();
+                let (lines, after) = rest.split_once(' ').unwrap_or((rest, ""));
+                context = Some(lines.parse().ok()?);
+                arg = after.trim_start();
+                continue;
+            }
+            _ => return Some((verbatim, context, arg)),
+        }
+        arg = rest.trim_start();
+    }
+}
+
+/// Split a leading `--file <name>` off a filter argument.
+fn file_scope(arg: &str) -> (Option<String>, &str) {
+    let Some(rest) = arg.strip_prefix("--file ") else {
+        return (None, arg);
+    };
+    let rest = rest.trim_start();
+    let (file, pattern) = rest.split_once(' ').unwrap_or((rest, ""));
+    (Some(file.to_string()), pattern.trim())
+}
+
+fn scope_label(file: &Option<String>) -> String {
+    file.as_ref()
+        .map(|file| format!(" (in {})", file))
+        .unwrap_or_default()
+}
+
 fn split_command(input: &str) -> (&str, Option<&str>) {
     let input = input.trim();
     let mut parts = input.splitn(2, ' ');
@@ -96,12 +846,124 @@ fn split_command(input: &str) -> (&str, Option<&str>) {
     (cmd, arg)
 }
 
-pub fn complete(prefix: &str, index: usize) -> Option<(String, usize)> {
+/// The built-in commands as usage (`filter <pattern>`) and what each does.
+pub fn help() -> impl Iterator<Item = (String, &'static str)> {
+    COMMANDS.iter().map(|&(name, args, summary)| {
+        let usage = if args.is_empty() {
+            name.to_string()
+        } else {
+            format!("{} {}", name, args)
+        };
+        (usage, summary)
+    })
+}
+
+/// Whether `name` is a built-in command or one of its shortcuts.
+pub fn is_builtin(name: &str) -> bool {
+    COMMANDS.iter().any(|&(command, ..)| command == name) || matches!(name, "q" | "w")
+}
+
+/// Expansion of an alias at the start of `input`, with any arguments after
+/// it appended. Built-in commands cannot be shadowed, and the expansion is
+/// not expanded again.
+fn alias_expansion(input: &str, aliases: &[(String, String)]) -> Option<String> {
+    let (cmd, arg) = split_command(input);
+    if is_builtin(cmd) {
+        return None;
+    }
+    let (_, expansion) = aliases.iter().find(|(name, _)| name == cmd)?;
+    Some(match arg {
+        Some(arg) => format!("{} {}", expansion, arg),
+        None => expansion.clone(),
+    })
+}
+
+/// Parse a chain of commands separated by `;` or `|`.
+///
+/// An alias may expand to a chain of its own; its commands are parsed in
+/// place of the alias.
+pub fn parse_chain(input: &str, aliases: &[(String, String)]) -> Vec<CommandResult> {
+    let mut results = Vec::new();
+    for segment in split_chain(input) {
+        match alias_expansion(&segment, aliases) {
+            Some(expanded) => {
+                results.extend(split_chain(&expanded).iter().map(|cmd| parse(cmd, &[])));
+            }
+            None => results.push(parse(&segment, &[])),
+        }
+    }
+    results
+}
+
+/// Commands whose argument is free text: once it starts, the rest of the
+/// line belongs to it, separators included.
+const FREE_TEXT_COMMANDS: &[&str] = &["sql", "note"];
+
+/// Whether `command` is a free-text command whose argument has started.
+fn takes_rest_of_line(command: &str) -> bool {
+    let (cmd, arg) = split_command(command);
+    arg.is_some() && FREE_TEXT_COMMANDS.contains(&cmd)
+}
+
+/// Split a command line at `;` and `|`.
+///
+/// Separators inside single or double quotes are kept, along with the
+/// quotes themselves; `\;` and `\|` insert a literal separator. The
+/// argument of a free-text command such as `sql` runs to the end of the
+/// line unsplit. Empty commands are dropped.
+fn split_chain(input: &str) -> Vec<String> {
+    let mut commands = Vec::new();
+    let mut current = String::new();
+    let mut quote = None;
+    let mut chars = input.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match (c, quote) {
+            ('\\', _)
+                if matches!(chars.peek(), Some(';' | '|')) && !takes_rest_of_line(&current) =>
+            {
+                current.extend(chars.next());
+            }
+            ('"' | '\'', None) => {
+                quote = Some(c);
+                current.push(c);
+            }
+            (c, Some(q)) if c == q => {
+                quote = None;
+                current.push(c);
+            }
+            (';' | '|', None) if !takes_rest_of_line(&current) => {
+                commands.push(std::mem::take(&mut current))
+            }
+            _ => current.push(c),
+        }
+    }
+    commands.push(current);
+
+    commands
+        .into_iter()
+        .map(|cmd| cmd.trim().to_string())
+        .filter(|cmd| !cmd.is_empty())
+        .collect()
+}
+
+/// Complete a command name; aliases are offered after the built-in commands.
+pub fn complete(
+    prefix: &str,
+    index: usize,
+    aliases: &[(String, String)],
+) -> Option<(String, usize)> {
     let lower_prefix = prefix.to_lowercase();
     let matches: Vec<&str> = COMMANDS
         .iter()
-        .filter(|&&cmd| cmd.to_lowercase().starts_with(&lower_prefix))
-        .copied()
+        .map(|&(name, ..)| name)
+        .chain(
+            aliases
+                .iter()
+                .map(|(name, _)| name.as_str())
+                .filter(|name| !is_builtin(name)),
+        )
+        .filter(|cmd| cmd.to_lowercase().starts_with(&lower_prefix))
         .collect();
 
     if matches.is_empty() {
@@ -118,75 +980,194 @@ mod tests {
 
     #[test]
     fn test_complete_empty() {
-        let (result, _) = complete("", 0).unwrap();
+        let (result, _) = complete("", 0, &[]).unwrap();
         assert_eq!(result, "filter");
     }
 
+    #[test]
+    fn test_help_lists_known_commands() {
+        for (usage, _) in help() {
+            let name = usage.split(' ').next().unwrap();
+            assert!(is_builtin(name));
+            assert!(
+                !parse(name, &[]).status.starts_with("Unknown command"),
+                "{}",
+                name
+            );
+        }
+    }
+
     #[test]
     fn test_complete_partial() {
-        let (result, idx) = complete("fi", 0).unwrap();
+        let (result, idx) = complete("fi", 0, &[]).unwrap();
         assert_eq!(result, "filter");
         assert_eq!(idx, 0);
 
-        let (result, idx) = complete("fi", 1).unwrap();
+        let (result, idx) = complete("fi", 1, &[]).unwrap();
         assert_eq!(result, "filter-clear");
         assert_eq!(idx, 1);
 
-        let (result, idx) = complete("fi", 2).unwrap();
+        let (result, idx) = complete("fi", 2, &[]).unwrap();
         assert_eq!(result, "filter-out");
         assert_eq!(idx, 2);
     }
 
     #[test]
     fn test_complete_wraps() {
-        let matches: Vec<_> = (0..4).filter_map(|i| complete("fi", i)).collect();
-        assert_eq!(matches.len(), 4);
+        let matches: Vec<_> = (0..5).filter_map(|i| complete("fi", i, &[])).collect();
+        assert_eq!(matches.len(), 5);
 
-        let (result, _) = complete("fi", 3).unwrap();
+        let (result, _) = complete("fi", 4, &[]).unwrap();
         assert_eq!(result, "filter");
 
-        let (result, _) = complete("fi", 0).unwrap();
+        let (result, _) = complete("fi", 0, &[]).unwrap();
         assert_eq!(result, "filter");
     }
 
     #[test]
     fn test_complete_no_match() {
-        assert!(complete("xyz", 0).is_none());
+        assert!(complete("xyz", 0, &[]).is_none());
     }
 
     #[test]
     fn test_complete_shortcut_expands() {
         // "q" should complete to "quit" (shortcut expands to full command)
-        let result = complete("q", 0);
+        let result = complete("q", 0, &[]);
         let (text, _) = result.unwrap();
         assert_eq!(text, "quit");
     }
 
+    #[test]
+    fn test_complete_includes_aliases() {
+        let aliases = vec![
+            ("fe".to_string(), "filter error".to_string()),
+            ("quit".to_string(), "filter quit".to_string()),
+        ];
+        let (result, _) = complete("f", 4, &aliases).unwrap();
+        assert_eq!(result, "fe");
+        assert_eq!(complete("qu", 1, &aliases).unwrap().0, "quit");
+    }
+
+    #[test]
+    fn test_parse_alias() {
+        let aliases = vec![
+            ("fe".to_string(), "filter error".to_string()),
+            ("q".to_string(), "filter-clear".to_string()),
+        ];
+        assert_eq!(
+            parse("fe", &aliases).effect,
+            Some(CommandEffect::AddFilter {
+                kind: FilterKind::Include,
+                pattern: "error".to_string(),
+                file: None,
+            })
+        );
+        assert_eq!(
+            parse("fe timeout", &aliases).effect,
+            Some(CommandEffect::AddFilter {
+                kind: FilterKind::Include,
+                pattern: "error timeout".to_string(),
+                file: None,
+            })
+        );
+        // Built-in commands cannot be shadowed
+        assert_eq!(parse("q", &aliases).effect, Some(CommandEffect::Quit));
+    }
+
+    #[test]
+    fn test_split_chain() {
+        assert_eq!(
+            split_chain("filter error; filter-out retry | list-filters"),
+            vec!["filter error", "filter-out retry", "list-filters"]
+        );
+        assert_eq!(
+            split_chain(r#"filter "a;b" ; filter 'x|y';;"#),
+            vec![r#"filter "a;b""#, "filter 'x|y'"]
+        );
+        assert_eq!(split_chain(r"filter a\|b"), vec!["filter a|b"]);
+        assert!(split_chain("  ").is_empty());
+        // Free text keeps its separators, but the command name still ends one
+        assert_eq!(
+            split_chain("filter error; note retry; see #12 | later"),
+            vec!["filter error", "note retry; see #12 | later"]
+        );
+        assert_eq!(
+            split_chain("note; list-filters"),
+            vec!["note", "list-filters"]
+        );
+    }
+
+    #[test]
+    fn test_parse_chain_expands_aliases() {
+        let aliases = vec![(
+            "clean".to_string(),
+            "filter-out healthcheck; filter-out metrics".to_string(),
+        )];
+        let effects: Vec<_> = parse_chain("clean | filter error", &aliases)
+            .into_iter()
+            .map(|r| r.effect)
+            .collect();
+        assert_eq!(effects.len(), 3);
+        assert_eq!(
+            effects[1],
+            Some(CommandEffect::AddFilter {
+                kind: FilterKind::Exclude,
+                pattern: "metrics".to_string(),
+                file: None,
+            })
+        );
+    }
+
     #[test]
     fn test_parse_quit() {
-        let result = parse("quit");
+        let result = parse("quit", &[]);
         assert_eq!(result.effect, Some(CommandEffect::Quit));
         assert!(result.status.is_empty());
 
-        let result = parse("q");
+        let result = parse("q", &[]);
         assert_eq!(result.effect, Some(CommandEffect::Quit));
     }
 
     #[test]
     fn test_parse_write() {
-        let result = parse("write test.log");
+        let result = parse("write test.log", &[]);
         assert_eq!(
             result.effect,
             Some(CommandEffect::WriteFilteredLogs {
-                filename: "test.log".to_string()
+                filename: "test.log".to_string(),
+                verbatim: false,
+                context: None,
             })
         );
 
-        let result = parse("w");
+        let result = parse("w --verbatim out.log", &[]);
+        assert_eq!(
+            result.effect,
+            Some(CommandEffect::WriteFilteredLogs {
+                filename: "out.log".to_string(),
+                verbatim: true,
+                context: None,
+            })
+        );
+
+        let result = parse("w --context 3 --verbatim out.log", &[]);
+        assert_eq!(
+            result.effect,
+            Some(CommandEffect::WriteFilteredLogs {
+                filename: "out.log".to_string(),
+                verbatim: true,
+                context: Some(3),
+            })
+        );
+        let result = parse("w --context out.log", &[]);
+        assert_eq!(result.effect, None);
+        assert!(result.status.starts_with("Usage: write"));
+
+        let result = parse("w --verbatim", &[]);
         assert!(
             matches!(
                 result.effect,
-                Some(CommandEffect::WriteFilteredLogs { ref filename })
+                Some(CommandEffect::WriteFilteredLogs { ref filename, verbatim: true, .. })
                 if filename.starts_with("filtered-logs-") && filename.ends_with(".log")
             ),
             "Expected timestamped filename, got {:?}",
@@ -194,62 +1175,560 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_parse_set_case() {
+        assert_eq!(
+            parse("set smartcase", &[]).effect,
+            Some(CommandEffect::SetCaseMode {
+                mode: CaseMode::Smart
+            })
+        );
+        assert_eq!(
+            parse("set noic", &[]).effect,
+            Some(CommandEffect::SetCaseMode {
+                mode: CaseMode::Sensitive
+            })
+        );
+        assert_eq!(
+            parse("set word", &[]).effect,
+            Some(CommandEffect::SetWholeWord { whole_word: true })
+        );
+        assert_eq!(
+            parse("set noword", &[]).effect,
+            Some(CommandEffect::SetWholeWord { whole_word: false })
+        );
+        let result = parse("set wrap", &[]);
+        assert_eq!(result.effect, None);
+        assert!(result.status.starts_with("Usage: set"));
+    }
+
     #[test]
     fn test_parse_filter() {
-        let result = parse("filter error");
+        let result = parse("filter error", &[]);
         assert_eq!(
             result.effect,
             Some(CommandEffect::AddFilter {
                 kind: FilterKind::Include,
-                pattern: "error".to_string()
+                pattern: "error".to_string(),
+                file: None,
             })
         );
         assert_eq!(result.status, "Added filter: error");
 
-        let result = parse("filter");
+        let result = parse("filter", &[]);
         assert_eq!(result.effect, None);
         assert_eq!(result.status, "Usage: filter <pattern>");
     }
 
     #[test]
     fn test_parse_filter_out() {
-        let result = parse("filter-out debug");
+        let result = parse("filter-out debug", &[]);
         assert_eq!(
             result.effect,
             Some(CommandEffect::AddFilter {
                 kind: FilterKind::Exclude,
-                pattern: "debug".to_string()
+                pattern: "debug".to_string(),
+                file: None,
             })
         );
 
-        let result = parse("filter-out");
+        let result = parse("filter-out", &[]);
         assert_eq!(result.effect, None);
         assert_eq!(result.status, "Usage: filter-out <pattern>");
     }
 
+    #[test]
+    fn test_parse_dim() {
+        let result = parse("dim healthcheck", &[]);
+        assert_eq!(
+            result.effect,
+            Some(CommandEffect::AddFilter {
+                kind: FilterKind::Dim,
+                pattern: "healthcheck".to_string(),
+                file: None,
+            })
+        );
+        assert_eq!(parse("dim", &[]).status, "Usage: dim <pattern>");
+    }
+
     #[test]
     fn test_parse_filter_clear() {
-        let result = parse("filter-clear");
+        let result = parse("filter-clear", &[]);
         assert_eq!(result.effect, Some(CommandEffect::ClearFilters));
         assert_eq!(result.status, "Filters cleared");
     }
 
     #[test]
     fn test_parse_list_filters() {
-        let result = parse("list-filters");
+        let result = parse("list-filters", &[]);
         assert_eq!(result.effect, Some(CommandEffect::ListFilters));
     }
 
+    #[test]
+    fn test_parse_note() {
+        let result = parse("note root cause here", &[]);
+        assert_eq!(
+            result.effect,
+            Some(CommandEffect::SetNote {
+                note: "root cause here".to_string()
+            })
+        );
+
+        let result = parse("note", &[]);
+        assert_eq!(
+            result.effect,
+            Some(CommandEffect::SetNote {
+                note: String::new()
+            })
+        );
+    }
+
+    #[test]
+    fn test_parse_session() {
+        assert_eq!(
+            parse("session save", &[]).effect,
+            Some(CommandEffect::SaveSession)
+        );
+        assert_eq!(
+            parse("session load", &[]).effect,
+            Some(CommandEffect::LoadSession)
+        );
+        assert_eq!(
+            parse("session clear", &[]).effect,
+            Some(CommandEffect::ClearSession)
+        );
+        let result = parse("session", &[]);
+        assert_eq!(result.effect, None);
+        assert_eq!(result.status, "Usage: session save|load|clear");
+    }
+
+    #[test]
+    fn test_parse_bookmark() {
+        assert_eq!(
+            parse("bookmark", &[]).effect,
+            Some(CommandEffect::ListBookmarks)
+        );
+        assert_eq!(
+            parse("bookmark clear", &[]).effect,
+            Some(CommandEffect::ClearBookmarks)
+        );
+        let result = parse("bookmark all", &[]);
+        assert_eq!(result.effect, None);
+        assert_eq!(result.status, "Usage: bookmark [clear]");
+    }
+
+    #[test]
+    fn test_parse_bookmarks_export() {
+        let result = parse("bookmarks-export report.md", &[]);
+        assert_eq!(
+            result.effect,
+            Some(CommandEffect::ExportBookmarks {
+                filename: "report.md".to_string()
+            })
+        );
+
+        let result = parse("bookmarks-export", &[]);
+        assert!(matches!(
+            result.effect,
+            Some(CommandEffect::ExportBookmarks { ref filename })
+            if filename.starts_with("bookmarks-") && filename.ends_with(".md")
+        ));
+    }
+
+    #[test]
+    fn test_parse_recent() {
+        let result = parse("recent", &[]);
+        assert_eq!(result.effect, Some(CommandEffect::ShowRecent));
+    }
+
+    #[test]
+    fn test_parse_theme() {
+        assert_eq!(
+            parse("theme deuteranopia", &[]).effect,
+            Some(CommandEffect::SetTheme {
+                name: Some("deuteranopia".to_string())
+            })
+        );
+        assert_eq!(
+            parse("theme", &[]).effect,
+            Some(CommandEffect::SetTheme { name: None })
+        );
+    }
+
+    #[test]
+    fn test_parse_gutter() {
+        assert_eq!(
+            parse("gutter letters", &[]).effect,
+            Some(CommandEffect::SetGutter {
+                gutter: Some(Gutter::Letters)
+            })
+        );
+        assert_eq!(
+            parse("gutter", &[]).effect,
+            Some(CommandEffect::SetGutter { gutter: None })
+        );
+        let invalid = parse("gutter emoji", &[]);
+        assert_eq!(invalid.effect, None);
+        assert_eq!(invalid.status, "Usage: gutter [off|letters|symbols]");
+    }
+
+    #[test]
+    fn test_parse_clipboard() {
+        assert_eq!(
+            parse("clipboard osc52", &[]).effect,
+            Some(CommandEffect::SetClipboard {
+                backend: Some(ClipboardBackend::Osc52)
+            })
+        );
+        assert_eq!(
+            parse("clipboard", &[]).effect,
+            Some(CommandEffect::SetClipboard { backend: None })
+        );
+        let invalid = parse("clipboard secondary", &[]);
+        assert_eq!(invalid.effect, None);
+        assert_eq!(
+            invalid.status,
+            "Usage: clipboard [system|primary|both|osc52]"
+        );
+    }
+
+    #[test]
+    fn test_parse_ruler() {
+        assert_eq!(parse("ruler", &[]).effect, Some(CommandEffect::ToggleRuler));
+        assert_eq!(
+            parse("ruler 120", &[]).effect,
+            Some(CommandEffect::SetRuler { column: Some(120) })
+        );
+        assert_eq!(
+            parse("ruler off", &[]).effect,
+            Some(CommandEffect::SetRuler { column: None })
+        );
+        assert_eq!(parse("ruler 0", &[]).status, "Usage: ruler [column|off]");
+    }
+
+    #[test]
+    fn test_parse_throttle() {
+        assert_eq!(
+            parse("throttle", &[]).effect,
+            Some(CommandEffect::ToggleThrottle)
+        );
+        assert_eq!(
+            parse("throttle 5", &[]).effect,
+            Some(CommandEffect::SetThrottle { limit: Some(5) })
+        );
+        assert_eq!(
+            parse("throttle off", &[]).effect,
+            Some(CommandEffect::SetThrottle { limit: None })
+        );
+        assert_eq!(
+            parse("throttle 0", &[]).status,
+            "Usage: throttle [count|off]"
+        );
+    }
+
+    #[test]
+    fn test_parse_level() {
+        assert_eq!(
+            parse("level error+", &[]).effect,
+            Some(CommandEffect::SetLevels {
+                levels: LevelSet::parse("error")
+            })
+        );
+        assert_eq!(
+            parse("level warn,debug", &[]).effect,
+            Some(CommandEffect::SetLevels {
+                levels: LevelSet::parse("debug,warn")
+            })
+        );
+        assert_eq!(
+            parse("level off", &[]).effect,
+            Some(CommandEffect::SetLevels { levels: None })
+        );
+        assert_eq!(parse("level", &[]).effect, None);
+        assert_eq!(parse("level loud", &[]).effect, None);
+    }
+
+    #[test]
+    fn test_parse_columns() {
+        assert_eq!(
+            parse("columns", &[]).effect,
+            Some(CommandEffect::ToggleColumns)
+        );
+        assert_eq!(
+            parse("columns ts, level,msg", &[]).effect,
+            Some(CommandEffect::SetColumns {
+                columns: Columns::parse("ts,level,msg")
+            })
+        );
+        assert_eq!(
+            parse("columns off", &[]).effect,
+            Some(CommandEffect::SetColumns { columns: None })
+        );
+        assert_eq!(
+            parse("columns ,", &[]).status,
+            "Usage: columns [field,field,...|off]"
+        );
+    }
+
+    #[test]
+    fn test_parse_tutor() {
+        assert_eq!(parse("tutor", &[]).effect, Some(CommandEffect::StartTutor));
+        assert_eq!(
+            parse("tutor skip", &[]).effect,
+            Some(CommandEffect::SkipTutorStep)
+        );
+        assert_eq!(
+            parse("tutor off", &[]).effect,
+            Some(CommandEffect::CloseTutor)
+        );
+        assert_eq!(parse("tutor next", &[]).status, "Usage: tutor [skip|off]");
+    }
+
+    #[test]
+    fn test_parse_tabs() {
+        assert_eq!(
+            parse("tabnew api.log worker.log", &[]).effect,
+            Some(CommandEffect::OpenTab {
+                paths: vec![PathBuf::from("api.log"), PathBuf::from("worker.log")],
+            })
+        );
+        assert_eq!(
+            parse("tabnew", &[]).status,
+            "Usage: tabnew <file> [file...]"
+        );
+        assert_eq!(parse("tabc", &[]).effect, Some(CommandEffect::CloseTab));
+        assert_eq!(
+            parse("tabp", &[]).effect,
+            Some(CommandEffect::StepTab { forward: false })
+        );
+    }
+
+    #[test]
+    fn test_parse_watch() {
+        assert_eq!(
+            parse("watch \"ERROR\"", &[]).effect,
+            Some(CommandEffect::AddWatch {
+                expr: "\"ERROR\"".to_string(),
+            })
+        );
+        assert_eq!(parse("watch", &[]).status, "Usage: watch <text|key=value>");
+        assert_eq!(
+            parse("unwatch 2", &[]).effect,
+            Some(CommandEffect::RemoveWatch {
+                target: Some("2".to_string()),
+            })
+        );
+        assert_eq!(
+            parse("unwatch", &[]).effect,
+            Some(CommandEffect::RemoveWatch { target: None })
+        );
+    }
+
+    #[test]
+    fn test_parse_replay() {
+        assert_eq!(
+            parse("replay", &[]).effect,
+            Some(CommandEffect::StartReplay {
+                speed: None,
+                from: None,
+            })
+        );
+        assert_eq!(
+            parse("replay 60x 10:32", &[]).effect,
+            Some(CommandEffect::StartReplay {
+                speed: Some(60),
+                from: Some("10:32".to_string()),
+            })
+        );
+        assert_eq!(
+            parse("replay 2024-01-01 10:32", &[]).effect,
+            Some(CommandEffect::StartReplay {
+                speed: None,
+                from: Some("2024-01-01 10:32".to_string()),
+            })
+        );
+        assert_eq!(
+            parse("replay pause", &[]).effect,
+            Some(CommandEffect::PauseReplay)
+        );
+        assert_eq!(
+            parse("replay stop", &[]).effect,
+            Some(CommandEffect::StopReplay)
+        );
+    }
+
+    #[test]
+    fn test_parse_context() {
+        assert_eq!(
+            parse("context", &[]).effect,
+            Some(CommandEffect::ToggleContext)
+        );
+        assert_eq!(
+            parse("context 2", &[]).effect,
+            Some(CommandEffect::SetContext { lines: Some(2) })
+        );
+        assert_eq!(
+            parse("context 0", &[]).effect,
+            Some(CommandEffect::SetContext { lines: None })
+        );
+        assert_eq!(
+            parse("context many", &[]).status,
+            "Usage: context [lines|off]"
+        );
+    }
+
+    #[test]
+    fn test_parse_sql() {
+        assert_eq!(
+            parse("sql SELECT line FROM lines WHERE level = 'error'", &[]).effect,
+            Some(CommandEffect::Sql {
+                query: "SELECT line FROM lines WHERE level = 'error'".to_string(),
+            })
+        );
+        let result = parse("sql", &[]);
+        assert_eq!(result.effect, None);
+        assert_eq!(result.status, "Usage: sql <query>");
+        // `||` concatenates in SQL rather than chaining commands
+        let chain = parse_chain("sql SELECT level || ': ' || message FROM logs", &[]);
+        assert_eq!(
+            chain
+                .into_iter()
+                .map(|result| result.effect)
+                .collect::<Vec<_>>(),
+            vec![Some(CommandEffect::Sql {
+                query: "SELECT level || ': ' || message FROM logs".to_string(),
+            })]
+        );
+    }
+
+    #[test]
+    fn test_parse_exceptions() {
+        assert_eq!(
+            parse("exceptions", &[]).effect,
+            Some(CommandEffect::ListExceptions)
+        );
+        assert_eq!(parse("gaps", &[]).effect, Some(CommandEffect::ShowGaps));
+    }
+
+    #[test]
+    fn test_parse_stats() {
+        assert_eq!(parse("stats", &[]).effect, Some(CommandEffect::ShowStats));
+        assert_eq!(
+            parse("stats off", &[]).effect,
+            Some(CommandEffect::ClearDrillDown)
+        );
+        assert_eq!(parse("stats now", &[]).status, "Usage: stats [off]");
+    }
+
+    #[test]
+    fn test_parse_range() {
+        assert_eq!(
+            parse("range 10:00..11:30", &[]).effect,
+            Some(CommandEffect::SetSearchRange {
+                from: Some("10:00".to_string()),
+                to: Some("11:30".to_string()),
+            })
+        );
+        assert_eq!(
+            parse("range 2026-02-13 10:00:00..", &[]).effect,
+            Some(CommandEffect::SetSearchRange {
+                from: Some("2026-02-13 10:00:00".to_string()),
+                to: None,
+            })
+        );
+        assert_eq!(
+            parse("range", &[]).effect,
+            Some(CommandEffect::SetSearchRange {
+                from: None,
+                to: None,
+            })
+        );
+        assert_eq!(
+            parse("range off", &[]).effect,
+            Some(CommandEffect::ClearSearchRange)
+        );
+        let result = parse("range 10:00", &[]);
+        assert_eq!(result.effect, None);
+        assert_eq!(result.status, "Usage: range [from..to|off]");
+        assert_eq!(parse("range ..", &[]).effect, None);
+    }
+
+    #[test]
+    fn test_parse_time() {
+        assert_eq!(parse("time", &[]).effect, Some(CommandEffect::ShowTime));
+        assert_eq!(parse("pins", &[]).effect, Some(CommandEffect::ListPins));
+    }
+
+    #[test]
+    fn test_parse_messages() {
+        assert_eq!(
+            parse("messages", &[]).effect,
+            Some(CommandEffect::ListMessages)
+        );
+        assert_eq!(parse("mes", &[]).effect, Some(CommandEffect::ListMessages));
+    }
+
+    #[test]
+    fn test_parse_filter_scoped_to_file() {
+        let result = parse("filter-out --file worker.log connection reset", &[]);
+        assert_eq!(
+            result.effect,
+            Some(CommandEffect::AddFilter {
+                kind: FilterKind::Exclude,
+                pattern: "connection reset".to_string(),
+                file: Some("worker.log".to_string()),
+            })
+        );
+        assert_eq!(
+            result.status,
+            "Added filter-out: connection reset (in worker.log)"
+        );
+        assert_eq!(parse("filter --file worker.log", &[]).effect, None);
+    }
+
+    #[test]
+    fn test_parse_filter_file() {
+        assert_eq!(
+            parse("filter-file rules/noise.txt", &[]).effect,
+            Some(CommandEffect::LoadFilterFile {
+                path: "rules/noise.txt".to_string()
+            })
+        );
+        assert_eq!(
+            parse("filter-file", &[]).status,
+            "Usage: filter-file <path>"
+        );
+    }
+
+    #[test]
+    fn test_parse_table() {
+        assert_eq!(
+            parse("table", &[]).effect,
+            Some(CommandEffect::ExportTable {
+                fields: vec![],
+                filename: None
+            })
+        );
+        assert_eq!(
+            parse("table user, status > evidence.md", &[]).effect,
+            Some(CommandEffect::ExportTable {
+                fields: vec!["user".to_string(), "status".to_string()],
+                filename: Some("evidence.md".to_string())
+            })
+        );
+        assert_eq!(parse("table id >", &[]).effect, None);
+    }
+
     #[test]
     fn test_parse_unknown() {
-        let result = parse("unknown");
+        let result = parse("unknown", &[]);
         assert_eq!(result.effect, None);
         assert_eq!(result.status, "Unknown command: unknown");
     }
 
     #[test]
     fn test_parse_empty() {
-        let result = parse("");
+        let result = parse("", &[]);
         assert_eq!(result.effect, None);
         assert!(result.status.is_empty());
     }
@@ -21,13 +21,27 @@
 //! - `error*` = starts with "error"
 //! - `*error*` = contains "error"
 //!
+//! A compound rule such as `{ all = ["error", "payment"], color = "red" }`
+//! combines patterns with `all`, `any` and `none` lists.
+//!
 //! First match wins based on config file order.
+//!
+//! Colors are names such as `red` or `light_cyan`, or RGB values such as
+//! `#d08770`.
 
 use std::fs;
 use std::io::{self, Write};
-use std::path::PathBuf;
+use std::ops::Range;
+use std::path::{Path, PathBuf};
 
 use ratatui::style::{Color, Modifier, Style};
+use regex::{Regex, RegexBuilder};
+
+use crate::clipboard::ClipboardBackend;
+use crate::command;
+use crate::error::QlogError;
+use crate::model::{LevelMap, LogLevel, Retention};
+use crate::theme::{Gutter, Theme};
 
 /// Configuration for search highlight colors.
 #[derive(Debug, Clone)]
@@ -59,20 +73,183 @@ impl Default for SearchConfig {
     }
 }
 
+/// Configuration for selected-line colors.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct SelectionConfig {
+    /// Background of the cursor line; `None` takes the theme's
+    pub cursor_bg: Option<Color>,
+    /// Background of other lines in a range selection; `None` takes the theme's
+    pub range_bg: Option<Color>,
+    /// Text color on selected lines; `None` keeps line colors that stay
+    /// readable on the selection background and replaces the rest
+    pub fg: Option<Color>,
+}
+
+/// Where the cursor is placed after a file set finishes loading.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub enum StartupPosition {
+    /// First line
+    #[default]
+    Start,
+    /// Last line
+    End,
+    /// Last error line near the end of the logs
+    LastError,
+    /// First line within this many minutes of the newest timestamp
+    LastMinutes(u32),
+}
+
+impl StartupPosition {
+    /// Minutes used by `last-minutes` when `startup_minutes` is not set.
+    const DEFAULT_MINUTES: u32 = 10;
+
+    /// Parse a `startup_position` value.
+    fn parse(name: &str, minutes: Option<u32>) -> Option<Self> {
+        match name.to_lowercase().as_str() {
+            "start" => Some(Self::Start),
+            "end" => Some(Self::End),
+            "last-error" => Some(Self::LastError),
+            "last-minutes" => Some(Self::LastMinutes(minutes.unwrap_or(Self::DEFAULT_MINUTES))),
+            _ => None,
+        }
+    }
+}
+
+/// Configuration for position aids in the log view.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ViewConfig {
+    /// Underline the cursor line in addition to its background
+    pub cursor_underline: bool,
+    /// Column marked with a vertical ruler, counted from the left edge of the log view
+    pub ruler_column: Option<usize>,
+    /// Cursor position after loading
+    pub startup_position: StartupPosition,
+}
+
+/// Configuration for follow mode.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FollowConfig {
+    /// Read the last lines of a rotated-away file before switching to the new one
+    pub load_rotated: bool,
+    /// Give lines without a timestamp the timestamp of the line before them
+    pub inherit_timestamps: bool,
+    /// Followed lines kept in memory; older ones are pruned
+    pub retention: Retention,
+    /// File pruned lines are appended to
+    pub spill: Option<PathBuf>,
+}
+
+impl Default for FollowConfig {
+    fn default() -> Self {
+        Self {
+            load_rotated: true,
+            inherit_timestamps: false,
+            retention: Retention::default(),
+            spill: None,
+        }
+    }
+}
+
+/// Configuration for the SQLite sidecar index.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct IndexConfig {
+    /// Keep each file's line index and bookmarks in `<file>.qlog.db`
+    pub sidecar: bool,
+}
+
+/// Configuration for the `:` command history.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct HistoryConfig {
+    /// Keep the command history in `~/.qlog/history` across sessions
+    pub save: bool,
+}
+
+/// Configuration for `:write`.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ExportConfig {
+    /// Write lines with their original bytes and line endings, as
+    /// `:write --verbatim` does
+    pub verbatim: bool,
+}
+
+/// Configuration for yanking.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ClipboardConfig {
+    /// Where yanked text goes
+    pub backend: ClipboardBackend,
+}
+
+/// Bytes per mebibyte, the unit of size settings in the config file.
+const MIB: u64 = 1024 * 1024;
+
+/// Configuration for opening very large files.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LargeFileConfig {
+    /// Files larger than this (in bytes) trigger the open prompt
+    pub threshold_bytes: u64,
+    /// Files larger than this (in bytes) are indexed on demand without prompting
+    pub on_demand_bytes: u64,
+    /// Bytes indexed from the end of a file in tail-only mode
+    pub tail_bytes: u64,
+}
+
+impl Default for LargeFileConfig {
+    fn default() -> Self {
+        Self {
+            threshold_bytes: 1024 * MIB,
+            on_demand_bytes: 64 * MIB,
+            tail_bytes: 256 * MIB,
+        }
+    }
+}
+
+/// Theme selection from the `[theme]` section.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ThemeConfig {
+    /// Name of the theme to start with, built-in or from `[themes]`
+    pub name: Option<String>,
+    /// Level gutter, overriding the theme's default
+    pub gutter: Option<Gutter>,
+}
+
 /// Unified application configuration.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default)]
 pub struct AppConfig {
     /// Log line color configuration
     pub colors: ColorConfig,
     /// Search highlight configuration
     pub search: SearchConfig,
+    /// Large file open prompt configuration
+    pub large_files: LargeFileConfig,
+    /// Theme and level gutter configuration
+    pub theme: ThemeConfig,
+    /// Themes defined in `[themes.<name>]` tables, in config order
+    pub themes: Vec<Theme>,
+    /// Selected-line colors
+    pub selection: SelectionConfig,
+    /// Cursor line and column ruler
+    pub view: ViewConfig,
+    /// Follow mode
+    pub follow: FollowConfig,
+    /// Sidecar index databases
+    pub index: IndexConfig,
+    /// Command history
+    pub history: HistoryConfig,
+    /// Exports of the filtered lines
+    pub export: ExportConfig,
+    /// Clipboard used by yanks
+    pub clipboard: ClipboardConfig,
+    /// Command aliases (name, expansion) in config order
+    pub aliases: Vec<(String, String)>,
+    /// Extra level words and patterns for level detection
+    pub levels: LevelMap,
 }
 
 /// Configuration for log line coloring.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default)]
 pub struct ColorConfig {
-    /// List of pattern-color pairs in order (for first-match-wins semantics)
-    patterns: Vec<(PatternMatcher, Color)>,
+    /// List of rule-color pairs in order (for first-match-wins semantics)
+    rules: Vec<(ColorRule, Color)>,
 }
 
 impl ColorConfig {
@@ -81,34 +258,15 @@ impl ColorConfig {
     /// Checks `./.qlog/qlog.toml` first, then falls back to `~/.qlog/qlog.toml`.
     /// Returns `None` if no config file is found or if parsing fails.
     pub fn load() -> Option<Self> {
-        // Try current directory first
-        let local_config = PathBuf::from(".qlog/qlog.toml");
-        if local_config.exists() {
-            return Self::load_from_path(&local_config);
-        }
-
-        // Fall back to home directory
-        if let Some(home_dir) = dirs::home_dir() {
-            let home_config = home_dir.join(".qlog/qlog.toml");
-            if home_config.exists() {
-                return Self::load_from_path(&home_config);
-            }
-        }
-
-        None
+        AppConfig::find_path().and_then(|path| Self::load_from_path(&path))
     }
 
     /// Load configuration from a specific path.
-    fn load_from_path(path: &PathBuf) -> Option<Self> {
-        match fs::read_to_string(path) {
+    fn load_from_path(path: &Path) -> Option<Self> {
+        match read_config(path) {
             Ok(content) => Self::parse_toml(&content),
             Err(e) => {
-                let _ = writeln!(
-                    io::stderr(),
-                    "Error reading config file {}: {}",
-                    path.display(),
-                    e
-                );
+                let _ = writeln!(io::stderr(), "{}", e);
                 None
             }
         }
@@ -120,56 +278,174 @@ impl ColorConfig {
         let doc = content.parse::<toml::Table>().ok()?;
 
         let colors_table = doc.get("colors")?.as_table()?;
+        let mut issues = Vec::new();
+        let rules = parse_color_rules(colors_table, &mut issues);
+        report_issues(&issues);
+
+        if rules.is_empty() {
+            None
+        } else {
+            Some(Self { rules })
+        }
+    }
 
-        let mut patterns = Vec::new();
-
-        for (pattern, color_value) in colors_table {
-            let color_str = match color_value.as_str() {
-                Some(s) => s,
-                None => {
-                    let _ = writeln!(
-                        io::stderr(),
-                        "Invalid color value for pattern '{}': expected string",
-                        pattern
-                    );
+    /// Get the color for a log line.
+    ///
+    /// Returns the color of the first matching rule, or `None` if no rules
+    /// match or the first matching rule only colors part of the line.
+    pub fn get_line_color(&self, line: &str) -> Option<Color> {
+        self.get_color_match(line)
+            .filter(|m| m.region.is_none())
+            .map(|m| m.color)
+    }
+
+    /// Get the first matching rule's color and the region it applies to.
+    pub fn get_color_match(&self, line: &str) -> Option<ColorMatch> {
+        let line_lower = line.to_lowercase();
+        let (rule, color) = self
+            .rules
+            .iter()
+            .find(|(rule, _)| rule.matches(line, &line_lower))?;
+        Some(ColorMatch {
+            color: *color,
+            region: rule.region(line),
+        })
+    }
+}
+
+/// Color chosen for a line by the first matching rule.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ColorMatch {
+    pub color: Color,
+    /// Byte range to color, when the rule's regex has a capture group;
+    /// `None` colors the whole line
+    pub region: Option<Range<usize>>,
+}
+
+/// Parse the `[colors]` table into rules, in file order.
+///
+/// Entries are either `pattern = "color"` or a compound rule such as
+/// `payment = { all = ["error", "payment"], color = "light_red" }`.
+/// Invalid entries are added to `issues` and skipped.
+fn parse_color_rules(table: &toml::Table, issues: &mut Vec<String>) -> Vec<(ColorRule, Color)> {
+    let mut rules = Vec::new();
+    for (name, value) in table {
+        let (rule, color_str) = match value {
+            toml::Value::String(color) => match PatternMatcher::parse(name) {
+                Ok(matcher) => (ColorRule::from_matcher(matcher), color.as_str()),
+                Err(e) => {
+                    issues.push(format!("Invalid pattern '{}': {}", name, e));
                     continue;
                 }
-            };
-
-            let color = match parse_color(color_str) {
-                Some(c) => c,
-                None => {
-                    let _ = writeln!(
-                        io::stderr(),
-                        "Unknown color '{}' for pattern '{}'",
-                        color_str,
-                        pattern
-                    );
+            },
+            toml::Value::Table(rule_table) => match ColorRule::from_table(rule_table) {
+                Ok(rule) => match rule_table.get("color").and_then(|v| v.as_str()) {
+                    Some(color) => (rule, color),
+                    None => {
+                        issues.push(format!(
+                            "Color rule '{}' needs a color = \"...\" entry",
+                            name
+                        ));
+                        continue;
+                    }
+                },
+                Err(e) => {
+                    issues.push(format!("Invalid color rule '{}': {}", name, e));
                     continue;
                 }
-            };
+            },
+            _ => {
+                issues.push(format!(
+                    "Invalid color value for pattern '{}': expected string or table",
+                    name
+                ));
+                continue;
+            }
+        };
 
-            let matcher = PatternMatcher::new(pattern);
-            patterns.push((matcher, color));
+        match parse_color(color_str) {
+            Some(color) => rules.push((rule, color)),
+            None => {
+                issues.push(format!(
+                    "Unknown color '{}' for pattern '{}'",
+                    color_str, name
+                ));
+            }
         }
+    }
+    rules
+}
 
-        if patterns.is_empty() {
-            None
-        } else {
-            Some(Self { patterns })
+/// A color rule combining line patterns.
+///
+/// A line matches when every `all` pattern matches, at least one `any`
+/// pattern matches (if there are any), and no `none` pattern matches.
+/// A plain `pattern = "color"` entry is a rule with a single `all` pattern.
+#[derive(Debug, Clone, Default)]
+pub struct ColorRule {
+    all: Vec<PatternMatcher>,
+    any: Vec<PatternMatcher>,
+    none: Vec<PatternMatcher>,
+}
+
+impl ColorRule {
+    /// Rule matching a single wildcard pattern.
+    pub fn pattern(pattern: &str) -> Self {
+        Self::from_matcher(PatternMatcher::new(pattern))
+    }
+
+    fn from_matcher(matcher: PatternMatcher) -> Self {
+        Self {
+            all: vec![matcher],
+            ..Self::default()
         }
     }
 
-    /// Get the color for a log line.
-    ///
-    /// Returns the color of the first matching pattern, or `None` if no patterns match.
-    pub fn get_line_color(&self, line: &str) -> Option<Color> {
-        for (matcher, color) in &self.patterns {
-            if matcher.is_match(line) {
-                return Some(*color);
+    /// Parse the `all`, `any` and `none` pattern lists of a compound rule.
+    fn from_table(table: &toml::Table) -> Result<Self, String> {
+        let list = |key: &str| -> Result<Vec<PatternMatcher>, String> {
+            match table.get(key) {
+                None => Ok(Vec::new()),
+                Some(toml::Value::String(pattern)) => Ok(vec![PatternMatcher::parse(pattern)?]),
+                Some(toml::Value::Array(items)) => items
+                    .iter()
+                    .map(|item| {
+                        item.as_str()
+                            .ok_or_else(|| format!("'{}' must list strings", key))
+                            .and_then(PatternMatcher::parse)
+                    })
+                    .collect(),
+                Some(_) => Err(format!("'{}' must be a string or a list of strings", key)),
             }
+        };
+        let rule = Self {
+            all: list("all")?,
+            any: list("any")?,
+            none: list("none")?,
+        };
+        if rule.all.is_empty() && rule.any.is_empty() && rule.none.is_empty() {
+            return Err("expected at least one of all, any or none".to_string());
         }
-        None
+        Ok(rule)
+    }
+
+    /// Check if a line matches this rule (case-insensitive).
+    pub fn is_match(&self, line: &str) -> bool {
+        self.matches(line, &line.to_lowercase())
+    }
+
+    fn matches(&self, line: &str, line_lower: &str) -> bool {
+        self.all.iter().all(|m| m.matches(line, line_lower))
+            && (self.any.is_empty() || self.any.iter().any(|m| m.matches(line, line_lower)))
+            && !self.none.iter().any(|m| m.matches(line, line_lower))
+    }
+
+    /// Region captured by the first regex pattern with a capture group.
+    fn region(&self, line: &str) -> Option<Range<usize>> {
+        self.all
+            .iter()
+            .chain(&self.any)
+            .find_map(|m| m.capture(line))
     }
 }
 
@@ -180,6 +456,8 @@ impl ColorConfig {
 /// - `*` at start: ends with match
 /// - `*` at end: starts with match
 /// - `*` at both ends: contains match
+///
+/// A `re:` prefix makes the rest of the pattern a regular expression.
 #[derive(Debug, Clone)]
 pub struct PatternMatcher {
     /// The pattern to match
@@ -188,7 +466,7 @@ pub struct PatternMatcher {
     match_type: MatchType,
 }
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone)]
 enum MatchType {
     /// Pattern must be contained in the line
     Contains,
@@ -196,12 +474,28 @@ enum MatchType {
     StartsWith,
     /// Line must end with pattern
     EndsWith,
-    /// Line must exactly match pattern
-    Exact,
+    /// Line must match a case-insensitive regex
+    Regex(Regex),
 }
 
 impl PatternMatcher {
-    /// Create a new pattern matcher from a pattern string.
+    /// Create a matcher from a config pattern: a regex with a `re:` prefix,
+    /// otherwise a wildcard pattern.
+    pub fn parse(pattern: &str) -> Result<Self, String> {
+        let Some(regex) = pattern.strip_prefix("re:") else {
+            return Ok(Self::new(pattern));
+        };
+        let regex = RegexBuilder::new(regex)
+            .case_insensitive(true)
+            .build()
+            .map_err(|e| e.to_string())?;
+        Ok(Self {
+            pattern: pattern.to_string(),
+            match_type: MatchType::Regex(regex),
+        })
+    }
+
+    /// Create a new pattern matcher from a wildcard pattern string.
     pub fn new(pattern: &str) -> Self {
         let has_leading_wildcard = pattern.starts_with('*');
         let has_trailing_wildcard = pattern.ends_with('*');
@@ -238,15 +532,27 @@ impl PatternMatcher {
 
     /// Check if a line matches this pattern (case-insensitive).
     pub fn is_match(&self, line: &str) -> bool {
-        let line_lower = line.to_lowercase();
+        self.matches(line, &line.to_lowercase())
+    }
 
-        match self.match_type {
+    /// Match against a line, given along with its lowercase form.
+    fn matches(&self, line: &str, line_lower: &str) -> bool {
+        match &self.match_type {
             MatchType::Contains => line_lower.contains(&self.pattern),
             MatchType::StartsWith => line_lower.starts_with(&self.pattern),
             MatchType::EndsWith => line_lower.ends_with(&self.pattern),
-            MatchType::Exact => line_lower == self.pattern,
+            MatchType::Regex(regex) => regex.is_match(line),
         }
     }
+
+    /// Byte range of the first capture group that took part in the match.
+    fn capture(&self, line: &str) -> Option<Range<usize>> {
+        let MatchType::Regex(regex) = &self.match_type else {
+            return None;
+        };
+        let captures = regex.captures(line)?;
+        captures.iter().skip(1).flatten().next().map(|m| m.range())
+    }
 }
 
 impl AppConfig {
@@ -255,114 +561,571 @@ impl AppConfig {
     /// Checks `./.qlog/qlog.toml` first, then falls back to `~/.qlog/qlog.toml`.
     /// Returns default configuration if no config file is found.
     pub fn load() -> Option<Self> {
-        // Try current directory first
+        Self::find_path().and_then(|path| Self::load_from_path(&path))
+    }
+
+    /// Path of the config file in use: `./.qlog/qlog.toml` if it exists,
+    /// otherwise `~/.qlog/qlog.toml`.
+    pub fn find_path() -> Option<PathBuf> {
         let local_config = PathBuf::from(".qlog/qlog.toml");
         if local_config.exists() {
-            return Self::load_from_path(&local_config);
+            return Some(local_config);
         }
+        let home_config = dirs::home_dir()?.join(".qlog/qlog.toml");
+        home_config.exists().then_some(home_config)
+    }
 
-        // Fall back to home directory
-        if let Some(home_dir) = dirs::home_dir() {
-            let home_config = home_dir.join(".qlog/qlog.toml");
-            if home_config.exists() {
-                return Self::load_from_path(&home_config);
-            }
+    /// Validate a config file without starting the UI.
+    ///
+    /// Returns every problem found, including unknown sections and keys
+    /// that would otherwise be ignored silently. Fails if the file cannot
+    /// be read.
+    pub fn check_file(path: &Path) -> Result<Vec<String>, QlogError> {
+        let content = read_config(path)?;
+        let mut issues = Vec::new();
+        if Self::parse_with_issues(&content, &mut issues).is_err() {
+            return Ok(issues);
+        }
+        if let Ok(doc) = content.parse::<toml::Table>() {
+            issues.extend(unknown_keys(&doc));
         }
+        Ok(issues)
+    }
 
-        None
+    /// Load configuration from `path`.
+    ///
+    /// Fails if the file cannot be read or is not valid TOML; settings that
+    /// cannot be used are skipped and reported on stderr.
+    pub fn load_file(path: &Path) -> Result<Self, QlogError> {
+        let content = read_config(path)?;
+        Self::parse_toml(&content).map_err(|message| QlogError::InvalidConfig {
+            path: path.to_path_buf(),
+            message,
+        })
     }
 
-    /// Load configuration from a specific path.
-    fn load_from_path(path: &PathBuf) -> Option<Self> {
-        match fs::read_to_string(path) {
-            Ok(content) => Self::parse_toml(&content),
+    /// Load configuration from a specific path, reporting errors on stderr.
+    fn load_from_path(path: &Path) -> Option<Self> {
+        match Self::load_file(path) {
+            Ok(config) => Some(config),
+            Err(QlogError::InvalidConfig { .. }) => None,
             Err(e) => {
-                let _ = writeln!(
-                    io::stderr(),
-                    "Error reading config file {}: {}",
-                    path.display(),
-                    e
-                );
+                let _ = writeln!(io::stderr(), "{}", e);
                 None
             }
         }
     }
 
+    /// Parse TOML configuration content, reporting problems on stderr.
+    fn parse_toml(content: &str) -> Result<Self, String> {
+        let mut issues = Vec::new();
+        let config = Self::parse_with_issues(content, &mut issues);
+        report_issues(&issues);
+        config
+    }
+
     /// Parse TOML configuration content.
-    fn parse_toml(content: &str) -> Option<Self> {
-        let doc = content.parse::<toml::Table>().ok()?;
+    ///
+    /// Fails only if the content is not valid TOML; settings that cannot be
+    /// used are skipped and described in `issues`.
+    fn parse_with_issues(content: &str, issues: &mut Vec<String>) -> Result<Self, String> {
+        let doc = match content.parse::<toml::Table>() {
+            Ok(doc) => doc,
+            Err(e) => {
+                let message = format!("Invalid TOML: {}", e.message().trim());
+                issues.push(message.clone());
+                return Err(message);
+            }
+        };
+        mistyped_keys(&doc, issues);
 
         // Parse colors section
-        let colors = if let Some(colors_table) = doc.get("colors").and_then(|v| v.as_table()) {
-            let mut patterns = Vec::new();
-            for (pattern, color_value) in colors_table {
-                let color_str = match color_value.as_str() {
-                    Some(s) => s,
-                    None => {
-                        let _ = writeln!(
-                            io::stderr(),
-                            "Invalid color value for pattern '{}': expected string",
-                            pattern
-                        );
-                        continue;
-                    }
-                };
-
-                let color = match parse_color(color_str) {
-                    Some(c) => c,
-                    None => {
-                        let _ = writeln!(
-                            io::stderr(),
-                            "Unknown color '{}' for pattern '{}'",
-                            color_str,
-                            pattern
-                        );
-                        continue;
-                    }
-                };
-
-                let matcher = PatternMatcher::new(pattern);
-                patterns.push((matcher, color));
-            }
-            ColorConfig { patterns }
-        } else {
-            ColorConfig {
-                patterns: Vec::new(),
-            }
+        let colors = ColorConfig {
+            rules: doc
+                .get("colors")
+                .and_then(|v| v.as_table())
+                .map(|table| parse_color_rules(table, issues))
+                .unwrap_or_default(),
         };
 
         // Parse search section
         let mut search = SearchConfig::default();
         if let Some(search_table) = doc.get("search").and_then(|v| v.as_table()) {
-            if let Some(fg) = search_table.get("match_fg").and_then(|v| v.as_str()) {
-                if let Some(color) = parse_color(fg) {
-                    search.match_fg = color;
-                }
+            if let Some(color) = color_setting(search_table, "match_fg", issues) {
+                search.match_fg = color;
             }
-            if let Some(bg) = search_table.get("match_bg").and_then(|v| v.as_str()) {
-                if let Some(color) = parse_color(bg) {
-                    search.match_bg = color;
-                }
+            if let Some(color) = color_setting(search_table, "match_bg", issues) {
+                search.match_bg = color;
             }
             if let Some(style) = search_table.get("match_style").and_then(|v| v.as_str()) {
                 search.match_style = parse_style(style);
             }
-            if let Some(fg) = search_table.get("current_fg").and_then(|v| v.as_str()) {
-                if let Some(color) = parse_color(fg) {
-                    search.current_fg = color;
-                }
+            if let Some(color) = color_setting(search_table, "current_fg", issues) {
+                search.current_fg = color;
             }
-            if let Some(bg) = search_table.get("current_bg").and_then(|v| v.as_str()) {
-                if let Some(color) = parse_color(bg) {
-                    search.current_bg = color;
-                }
+            if let Some(color) = color_setting(search_table, "current_bg", issues) {
+                search.current_bg = color;
             }
             if let Some(style) = search_table.get("current_style").and_then(|v| v.as_str()) {
                 search.current_style = parse_style(style);
             }
         }
 
-        Some(Self { colors, search })
+        // Parse large_files section
+        let mut large_files = LargeFileConfig::default();
+        if let Some(table) = doc.get("large_files").and_then(|v| v.as_table()) {
+            if let Some(mb) = table.get("threshold_mb").and_then(|v| v.as_integer()) {
+                large_files.threshold_bytes = mb.max(0) as u64 * MIB;
+            }
+            if let Some(mb) = table.get("on_demand_mb").and_then(|v| v.as_integer()) {
+                large_files.on_demand_bytes = mb.max(0) as u64 * MIB;
+            }
+            if let Some(mb) = table.get("tail_mb").and_then(|v| v.as_integer()) {
+                large_files.tail_bytes = mb.max(1) as u64 * MIB;
+            }
+        }
+
+        // Parse themes section before [theme], which may name one of them
+        let themes = doc
+            .get("themes")
+            .and_then(|v| v.as_table())
+            .map(|table| parse_themes(table, issues))
+            .unwrap_or_default();
+
+        // Parse theme section
+        let mut theme = ThemeConfig::default();
+        if let Some(table) = doc.get("theme").and_then(|v| v.as_table()) {
+            theme.name = table
+                .get("name")
+                .and_then(|v| v.as_str())
+                .map(|s| s.to_string());
+            if let Some(name) = theme.name.as_deref() {
+                if Theme::find(&themes, name).is_none() {
+                    issues.push(format!(
+                        "Unknown theme '{}': expected one of {}",
+                        name,
+                        Theme::all_names(&themes).join(", ")
+                    ));
+                }
+            }
+            if let Some(name) = table.get("gutter").and_then(|v| v.as_str()) {
+                theme.gutter = Gutter::parse(name);
+                if theme.gutter.is_none() {
+                    issues.push(format!(
+                        "Unknown gutter '{}': expected off, letters or symbols",
+                        name
+                    ));
+                }
+            }
+        }
+
+        // Parse selection section
+        let mut selection = SelectionConfig::default();
+        if let Some(table) = doc.get("selection").and_then(|v| v.as_table()) {
+            selection.cursor_bg = color_setting(table, "cursor_bg", issues);
+            selection.range_bg = color_setting(table, "range_bg", issues);
+            if table
+                .get("fg")
+                .and_then(|v| v.as_str())
+                .is_some_and(|fg| !fg.eq_ignore_ascii_case("auto"))
+            {
+                selection.fg = color_setting(table, "fg", issues);
+            }
+        }
+
+        // Parse view section
+        let mut view = ViewConfig::default();
+        if let Some(table) = doc.get("view").and_then(|v| v.as_table()) {
+            if let Some(underline) = table.get("cursor_underline").and_then(|v| v.as_bool()) {
+                view.cursor_underline = underline;
+            }
+            if let Some(column) = table.get("ruler_column").and_then(|v| v.as_integer()) {
+                view.ruler_column = (column > 0).then_some(column as usize);
+            }
+            if let Some(name) = table.get("startup_position").and_then(|v| v.as_str()) {
+                let minutes = table
+                    .get("startup_minutes")
+                    .and_then(|v| v.as_integer())
+                    .map(|m| m.clamp(1, u32::MAX as i64) as u32);
+                match StartupPosition::parse(name, minutes) {
+                    Some(position) => view.startup_position = position,
+                    None => {
+                        issues.push(format!("Unknown startup_position '{}': expected start, end, last-error or last-minutes",
+                            name));
+                    }
+                }
+            }
+        }
+
+        // Parse follow section
+        let mut follow = FollowConfig::default();
+        if let Some(table) = doc.get("follow").and_then(|v| v.as_table()) {
+            if let Some(load_rotated) = table.get("load_rotated").and_then(|v| v.as_bool()) {
+                follow.load_rotated = load_rotated;
+            }
+            if let Some(inherit) = table.get("inherit_timestamps").and_then(|v| v.as_bool()) {
+                follow.inherit_timestamps = inherit;
+            }
+            if let Some(lines) = table.get("retention_lines").and_then(|v| v.as_integer()) {
+                follow.retention.lines = (lines > 0).then_some(lines as usize);
+            }
+            if let Some(hours) = table
+                .get("retention_hours")
+                .and_then(|v| v.as_float().or_else(|| v.as_integer().map(|h| h as f64)))
+            {
+                follow.retention.max_age =
+                    (hours > 0.0).then(|| chrono::Duration::seconds((hours * 3600.0) as i64));
+            }
+            if let Some(path) = table.get("spill").and_then(|v| v.as_str()) {
+                follow.spill = Some(PathBuf::from(path));
+            }
+        }
+
+        // Parse index section
+        let mut index = IndexConfig::default();
+        if let Some(table) = doc.get("index").and_then(|v| v.as_table()) {
+            if let Some(sidecar) = table.get("sidecar").and_then(|v| v.as_bool()) {
+                index.sidecar = sidecar;
+            }
+        }
+
+        // Parse history section
+        let mut history = HistoryConfig::default();
+        if let Some(table) = doc.get("history").and_then(|v| v.as_table()) {
+            if let Some(save) = table.get("save").and_then(|v| v.as_bool()) {
+                history.save = save;
+            }
+        }
+
+        // Parse export section
+        let mut export = ExportConfig::default();
+        if let Some(table) = doc.get("export").and_then(|v| v.as_table()) {
+            if let Some(verbatim) = table.get("verbatim").and_then(|v| v.as_bool()) {
+                export.verbatim = verbatim;
+            }
+        }
+
+        // Parse clipboard section
+        let mut clipboard = ClipboardConfig::default();
+        if let Some(table) = doc.get("clipboard").and_then(|v| v.as_table()) {
+            if let Some(name) = table.get("backend").and_then(|v| v.as_str()) {
+                match ClipboardBackend::parse(name) {
+                    Some(backend) => clipboard.backend = backend,
+                    None => issues.push(format!(
+                        "Unknown clipboard backend '{}': expected system, primary, both or osc52",
+                        name
+                    )),
+                }
+            }
+        }
+
+        // Parse aliases section
+        let mut aliases = Vec::new();
+        if let Some(table) = doc.get("aliases").and_then(|v| v.as_table()) {
+            for (name, value) in table {
+                let Some(expansion) = value.as_str() else {
+                    issues.push(format!(
+                        "Invalid alias '{}': expected a command string",
+                        name
+                    ));
+                    continue;
+                };
+                if command::is_builtin(name) {
+                    issues.push(format!(
+                        "Alias '{}' shadows a built-in command and is ignored",
+                        name
+                    ));
+                    continue;
+                }
+                aliases.push((name.clone(), expansion.trim().to_string()));
+            }
+        }
+
+        // Parse levels section
+        let mut levels = LevelMap::new();
+        if let Some(table) = doc.get("levels").and_then(|v| v.as_table()) {
+            for (key, value) in table {
+                let Some(level) = value.as_str().and_then(LogLevel::from_keyword) else {
+                    issues.push(format!(
+                        "Invalid level for '{}': expected error, warn, info, debug or trace",
+                        key
+                    ));
+                    continue;
+                };
+                if let Err(e) = levels.add(key, level) {
+                    issues.push(format!("Invalid level pattern '{}': {}", key, e));
+                }
+            }
+        }
+
+        Ok(Self {
+            colors,
+            search,
+            large_files,
+            theme,
+            themes,
+            selection,
+            view,
+            follow,
+            index,
+            history,
+            export,
+            clipboard,
+            aliases,
+            levels,
+        })
+    }
+}
+
+/// Keys accepted in each config section with the type of their value;
+/// `[colors]`, `[aliases]`, `[levels]` and `[themes]` take any key
+/// (`[themes]` checks its tables itself).
+const KNOWN_KEYS: &[(&str, &[(&str, ValueType)])] = &[
+    ("colors", &[]),
+    ("aliases", &[]),
+    ("levels", &[]),
+    ("themes", &[]),
+    (
+        "search",
+        &[
+            ("match_fg", ValueType::String),
+            ("match_bg", ValueType::String),
+            ("match_style", ValueType::String),
+            ("current_fg", ValueType::String),
+            ("current_bg", ValueType::String),
+            ("current_style", ValueType::String),
+        ],
+    ),
+    (
+        "large_files",
+        &[
+            ("threshold_mb", ValueType::Integer),
+            ("on_demand_mb", ValueType::Integer),
+            ("tail_mb", ValueType::Integer),
+        ],
+    ),
+    (
+        "theme",
+        &[("name", ValueType::String), ("gutter", ValueType::String)],
+    ),
+    (
+        "selection",
+        &[
+            ("cursor_bg", ValueType::String),
+            ("range_bg", ValueType::String),
+            ("fg", ValueType::String),
+        ],
+    ),
+    (
+        "view",
+        &[
+            ("cursor_underline", ValueType::Bool),
+            ("ruler_column", ValueType::Integer),
+            ("startup_position", ValueType::String),
+            ("startup_minutes", ValueType::Integer),
+        ],
+    ),
+    (
+        "follow",
+        &[
+            ("load_rotated", ValueType::Bool),
+            ("inherit_timestamps", ValueType::Bool),
+            ("retention_lines", ValueType::Integer),
+            ("retention_hours", ValueType::Number),
+            ("spill", ValueType::String),
+        ],
+    ),
+    ("index", &[("sidecar", ValueType::Bool)]),
+    ("history", &[("save", ValueType::Bool)]),
+    ("export", &[("verbatim", ValueType::Bool)]),
+    ("clipboard", &[("backend", ValueType::String)]),
+];
+
+/// Type of the value a known config key takes.
+#[derive(Debug, Clone, Copy)]
+enum ValueType {
+    String,
+    Bool,
+    Integer,
+    /// An integer or a float
+    Number,
+}
+
+impl ValueType {
+    fn accepts(self, value: &toml::Value) -> bool {
+        match self {
+            ValueType::String => value.is_str(),
+            ValueType::Bool => value.is_bool(),
+            ValueType::Integer => value.is_integer(),
+            ValueType::Number => value.is_integer() || value.is_float(),
+        }
+    }
+
+    fn describe(self) -> &'static str {
+        match self {
+            ValueType::String => "a string",
+            ValueType::Bool => "true or false",
+            ValueType::Integer => "an integer",
+            ValueType::Number => "a number",
+        }
+    }
+}
+
+/// Describe known keys of `doc` whose value has the wrong type; the
+/// settings they hold are skipped.
+fn mistyped_keys(doc: &toml::Table, issues: &mut Vec<String>) {
+    for (section, keys) in KNOWN_KEYS {
+        let Some(table) = doc.get(*section).and_then(|v| v.as_table()) else {
+            continue;
+        };
+        for (key, value_type) in keys.iter() {
+            if table
+                .get(*key)
+                .is_some_and(|value| !value_type.accepts(value))
+            {
+                issues.push(format!(
+                    "'{}' in [{}] must be {}",
+                    key,
+                    section,
+                    value_type.describe()
+                ));
+            }
+        }
+    }
+}
+
+/// Describe sections and keys of `doc` that qlog does not know.
+fn unknown_keys(doc: &toml::Table) -> Vec<String> {
+    let mut issues = Vec::new();
+    for (section, value) in doc {
+        let Some(&(_, keys)) = KNOWN_KEYS.iter().find(|(name, _)| name == section) else {
+            issues.push(format!("Unknown section [{}]", section));
+            continue;
+        };
+        let Some(table) = value.as_table() else {
+            issues.push(format!("'{}' must be a [{}] section", section, section));
+            continue;
+        };
+        if keys.is_empty() {
+            continue;
+        }
+        for key in table
+            .keys()
+            .filter(|key| !keys.iter().any(|(known, _)| known == key))
+        {
+            issues.push(format!("Unknown key '{}' in [{}]", key, section));
+        }
+    }
+    issues
+}
+
+/// Keys accepted in a `[themes.<name>]` table, besides the level names.
+const THEME_KEYS: &[&str] = &[
+    "base",
+    "description",
+    "gutter",
+    "border",
+    "overlay_border",
+    "status_fg",
+    "status_bg",
+    "cursor_bg",
+    "range_bg",
+    "scrollbar",
+];
+
+/// Parse the `[themes.<name>]` tables.
+///
+/// Each theme starts as a copy of its `base` (the default theme if not
+/// given) and replaces the level and chrome colors it sets. Problems are
+/// added to `issues`; a theme with an unknown base is skipped.
+fn parse_themes(table: &toml::Table, issues: &mut Vec<String>) -> Vec<Theme> {
+    let mut themes: Vec<Theme> = Vec::new();
+    for (name, value) in table {
+        let Some(settings) = value.as_table() else {
+            issues.push(format!("'{}' must be a [themes.{}] section", name, name));
+            continue;
+        };
+        let base_name = settings
+            .get("base")
+            .and_then(|v| v.as_str())
+            .unwrap_or("default");
+        // A theme can build on one defined above it
+        let Some(base) = Theme::find(&themes, base_name) else {
+            issues.push(format!(
+                "Unknown base theme '{}' for [themes.{}]",
+                base_name, name
+            ));
+            continue;
+        };
+        let mut theme = base.clone();
+        theme.name = name.clone().into();
+        theme.description = match settings.get("description").and_then(|v| v.as_str()) {
+            Some(description) => description.to_string().into(),
+            None => format!("Based on {}", base.name).into(),
+        };
+        if let Some(gutter) = settings.get("gutter").and_then(|v| v.as_str()) {
+            match Gutter::parse(gutter) {
+                Some(gutter) => theme.gutter = gutter,
+                None => issues.push(format!(
+                    "Unknown gutter '{}' in [themes.{}]: expected off, letters or symbols",
+                    gutter, name
+                )),
+            }
+        }
+        for level in LogLevel::ALL {
+            if let Some(color) = color_setting(settings, level.name(), issues) {
+                theme.set_level_color(level, color);
+            }
+        }
+        let chrome = &mut theme.chrome;
+        let fields: [(&str, &mut Color); 7] = [
+            ("border", &mut chrome.border),
+            ("overlay_border", &mut chrome.overlay_border),
+            ("status_fg", &mut chrome.status_fg),
+            ("status_bg", &mut chrome.status_bg),
+            ("cursor_bg", &mut chrome.cursor_bg),
+            ("range_bg", &mut chrome.range_bg),
+            ("scrollbar", &mut chrome.scrollbar),
+        ];
+        for (key, field) in fields {
+            if let Some(color) = color_setting(settings, key, issues) {
+                *field = color;
+            }
+        }
+        let levels: Vec<&str> = LogLevel::ALL.iter().map(|level| level.name()).collect();
+        for key in settings
+            .keys()
+            .filter(|key| !THEME_KEYS.contains(&key.as_str()) && !levels.contains(&key.as_str()))
+        {
+            issues.push(format!("Unknown key '{}' in [themes.{}]", key, name));
+        }
+        themes.push(theme);
+    }
+    themes
+}
+
+/// Read a color setting, noting unknown color names in `issues`.
+fn color_setting(table: &toml::Table, key: &str, issues: &mut Vec<String>) -> Option<Color> {
+    let name = table.get(key)?.as_str()?;
+    let color = parse_color(name);
+    if color.is_none() {
+        issues.push(format!("Unknown color '{}' for {}", name, key));
+    }
+    color
+}
+
+/// Read a config file.
+fn read_config(path: &Path) -> Result<String, QlogError> {
+    fs::read_to_string(path).map_err(|source| QlogError::ReadConfig {
+        path: path.to_path_buf(),
+        source,
+    })
+}
+
+/// Print config problems on stderr.
+fn report_issues(issues: &[String]) {
+    for issue in issues {
+        let _ = writeln!(io::stderr(), "{}", issue);
     }
 }
 
@@ -382,8 +1145,11 @@ fn parse_style(style_str: &str) -> Style {
     style
 }
 
-/// Parse a color name to a ratatui Color.
+/// Parse a color name or `#rrggbb` value to a ratatui Color.
 fn parse_color(name: &str) -> Option<Color> {
+    if let Some(hex) = name.strip_prefix('#') {
+        return parse_hex_color(hex);
+    }
     let color = match name.to_lowercase().as_str() {
         "red" => Color::Red,
         "green" => Color::Green,
@@ -401,11 +1167,32 @@ fn parse_color(name: &str) -> Option<Color> {
         "light_yellow" => Color::LightYellow,
         "light_magenta" => Color::LightMagenta,
         "light_cyan" => Color::LightCyan,
+        "default" | "reset" => Color::Reset,
         _ => return None,
     };
     Some(color)
 }
 
+/// Parse `rrggbb` (or the short `rgb`) hex digits to an RGB color.
+fn parse_hex_color(hex: &str) -> Option<Color> {
+    if !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+        return None;
+    }
+    let channel = |digits: &str| u8::from_str_radix(digits, 16).ok();
+    match hex.len() {
+        6 => Some(Color::Rgb(
+            channel(&hex[0..2])?,
+            channel(&hex[2..4])?,
+            channel(&hex[4..6])?,
+        )),
+        3 => {
+            let short = |i: usize| channel(&hex[i..i + 1]).map(|c| c * 17);
+            Some(Color::Rgb(short(0)?, short(1)?, short(2)?))
+        }
+        _ => None,
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -448,11 +1235,12 @@ mod tests {
 
     #[test]
     fn test_color_config_first_match_wins() {
-        let mut patterns = Vec::new();
-        patterns.push((PatternMatcher::new("error"), Color::Red));
-        patterns.push((PatternMatcher::new("warning"), Color::Yellow));
+        let rules = vec![
+            (ColorRule::pattern("error"), Color::Red),
+            (ColorRule::pattern("warning"), Color::Yellow),
+        ];
 
-        let config = ColorConfig { patterns };
+        let config = ColorConfig { rules };
 
         // Line with "error" should get red (first match)
         assert_eq!(config.get_line_color("error warning"), Some(Color::Red));
@@ -464,6 +1252,62 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_compound_color_rules() {
+        let config = AppConfig::parse_toml(
+            r#"
+[colors]
+payment = { all = ["error", "payment"], color = "light_red" }
+error = "yellow"
+noisy = { any = ["retry", "timeout"], none = "error", color = "gray" }
+broken = { color = "red" }
+"#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            config.colors.get_line_color("ERROR payment declined"),
+            Some(Color::LightRed)
+        );
+        assert_eq!(
+            config.colors.get_line_color("error: disk full"),
+            Some(Color::Yellow)
+        );
+        assert_eq!(
+            config.colors.get_line_color("timeout, will retry"),
+            Some(Color::Gray)
+        );
+        assert_eq!(config.colors.get_line_color("all good"), None);
+    }
+
+    #[test]
+    fn test_regex_color_rules() {
+        let config = AppConfig::parse_toml(
+            r#"
+[colors]
+'re:took (\d{4,}) ?ms' = "red"
+'re:^\s*at ' = "dark_gray"
+'re:[unclosed' = "blue"
+"#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            config.colors.get_color_match("request took 12000ms"),
+            Some(ColorMatch {
+                color: Color::Red,
+                region: Some(13..18),
+            })
+        );
+        assert_eq!(config.colors.get_line_color("request took 12000ms"), None);
+        assert_eq!(config.colors.get_line_color("took 12ms"), None);
+        assert_eq!(
+            config.colors.get_line_color("    AT Foo.bar()"),
+            Some(Color::DarkGray)
+        );
+        assert!(PatternMatcher::parse("re:[unclosed").is_err());
+    }
+
     #[test]
     fn test_parse_color() {
         assert_eq!(parse_color("red"), Some(Color::Red));
@@ -471,6 +1315,11 @@ mod tests {
         assert_eq!(parse_color("Green"), Some(Color::Green));
         assert_eq!(parse_color("dark_gray"), Some(Color::DarkGray));
         assert_eq!(parse_color("light_cyan"), Some(Color::LightCyan));
+        assert_eq!(parse_color("#D08770"), Some(Color::Rgb(208, 135, 112)));
+        assert_eq!(parse_color("#0f8"), Some(Color::Rgb(0, 255, 136)));
+        assert_eq!(parse_color("default"), Some(Color::Reset));
+        assert_eq!(parse_color("#12345"), None);
+        assert_eq!(parse_color("#gg0000"), None);
         assert_eq!(parse_color("invalid"), None);
     }
 
@@ -520,6 +1369,264 @@ success = "green""#
         assert!(result.is_none());
     }
 
+    #[test]
+    fn test_large_files_section() {
+        let config = AppConfig::parse_toml(
+            "[large_files]\nthreshold_mb = 64\non_demand_mb = 16\ntail_mb = 8",
+        )
+        .unwrap();
+        assert_eq!(config.large_files.threshold_bytes, 64 * MIB);
+        assert_eq!(config.large_files.on_demand_bytes, 16 * MIB);
+        assert_eq!(config.large_files.tail_bytes, 8 * MIB);
+
+        let defaults = AppConfig::parse_toml("").unwrap();
+        assert_eq!(defaults.large_files, LargeFileConfig::default());
+    }
+
+    #[test]
+    fn test_theme_section() {
+        let config =
+            AppConfig::parse_toml("[theme]\nname = \"deuteranopia\"\ngutter = \"letters\"")
+                .unwrap();
+        assert_eq!(config.theme.name.as_deref(), Some("deuteranopia"));
+        assert_eq!(config.theme.gutter, Some(Gutter::Letters));
+
+        let defaults = AppConfig::parse_toml("").unwrap();
+        assert_eq!(defaults.theme, ThemeConfig::default());
+    }
+
+    #[test]
+    fn test_themes_section() {
+        let mut issues = Vec::new();
+        let config = AppConfig::parse_with_issues(
+            "[theme]\nname = \"night\"\n\
+             [themes.night]\nbase = \"high-contrast\"\nerror = \"#ff5555\"\n\
+             status_bg = \"#282a36\"\ncursor_bg = \"blue\"\n\
+             [themes.night-letters]\nbase = \"night\"\ngutter = \"letters\"\n\
+             [themes.broken]\nbase = \"solarized\"\n\
+             [themes.odd]\nborder = \"mauve\"\nshadow = \"black\"\n",
+            &mut issues,
+        )
+        .unwrap();
+        assert_eq!(
+            issues,
+            [
+                "Unknown base theme 'solarized' for [themes.broken]",
+                "Unknown color 'mauve' for border",
+                "Unknown key 'shadow' in [themes.odd]",
+            ]
+        );
+        let names: Vec<&str> = config.themes.iter().map(|t| t.name.as_ref()).collect();
+        assert_eq!(names, ["night", "night-letters", "odd"]);
+
+        let night = &config.themes[0];
+        let error = night.level_style(LogLevel::Error).unwrap();
+        assert_eq!(error.fg, Color::Rgb(255, 85, 85));
+        assert!(error.modifiers.contains(Modifier::BOLD));
+        assert_eq!(night.chrome.status_bg, Color::Rgb(40, 42, 54));
+        assert_eq!(night.chrome.cursor_bg, Color::Blue);
+        assert_eq!(night.chrome.overlay_border, Color::Cyan);
+        assert_eq!(night.gutter, Gutter::Symbols);
+        assert_eq!(config.themes[1].gutter, Gutter::Letters);
+        assert_eq!(config.themes[1].chrome.status_bg, Color::Rgb(40, 42, 54));
+    }
+
+    #[test]
+    fn test_selection_section() {
+        let config = AppConfig::parse_toml(
+            "[selection]\ncursor_bg = \"blue\"\nrange_bg = \"dark_gray\"\nfg = \"white\"",
+        )
+        .unwrap();
+        assert_eq!(config.selection.cursor_bg, Some(Color::Blue));
+        assert_eq!(config.selection.range_bg, Some(Color::DarkGray));
+        assert_eq!(config.selection.fg, Some(Color::White));
+
+        let auto = AppConfig::parse_toml("[selection]\nfg = \"auto\"").unwrap();
+        assert_eq!(auto.selection, SelectionConfig::default());
+    }
+
+    #[test]
+    fn test_view_section() {
+        let config =
+            AppConfig::parse_toml("[view]\ncursor_underline = true\nruler_column = 120").unwrap();
+        assert!(config.view.cursor_underline);
+        assert_eq!(config.view.ruler_column, Some(120));
+
+        let disabled = AppConfig::parse_toml("[view]\nruler_column = 0").unwrap();
+        assert_eq!(disabled.view, ViewConfig::default());
+    }
+
+    #[test]
+    fn test_startup_position() {
+        let config = AppConfig::parse_toml(
+            "[view]\nstartup_position = \"last-minutes\"\nstartup_minutes = 30",
+        )
+        .unwrap();
+        assert_eq!(
+            config.view.startup_position,
+            StartupPosition::LastMinutes(30)
+        );
+
+        let last_error =
+            AppConfig::parse_toml("[view]\nstartup_position = \"last-error\"").unwrap();
+        assert_eq!(last_error.view.startup_position, StartupPosition::LastError);
+
+        let unknown = AppConfig::parse_toml("[view]\nstartup_position = \"middle\"").unwrap();
+        assert_eq!(unknown.view.startup_position, StartupPosition::Start);
+    }
+
+    #[test]
+    fn test_index_section() {
+        let config = AppConfig::parse_toml("[index]\nsidecar = true").unwrap();
+        assert!(config.index.sidecar);
+        assert!(!AppConfig::parse_toml("").unwrap().index.sidecar);
+    }
+
+    #[test]
+    fn test_history_section() {
+        let config = AppConfig::parse_toml("[history]\nsave = true").unwrap();
+        assert!(config.history.save);
+        assert!(!AppConfig::parse_toml("").unwrap().history.save);
+    }
+
+    #[test]
+    fn test_clipboard_section() {
+        let config = AppConfig::parse_toml("[clipboard]\nbackend = \"osc52\"").unwrap();
+        assert_eq!(config.clipboard.backend, ClipboardBackend::Osc52);
+        let config = AppConfig::parse_toml("").unwrap();
+        assert_eq!(config.clipboard.backend, ClipboardBackend::Both);
+
+        let mut issues = Vec::new();
+        let config =
+            AppConfig::parse_with_issues("[clipboard]\nbackend = \"secondary\"", &mut issues)
+                .unwrap();
+        assert_eq!(config.clipboard.backend, ClipboardBackend::Both);
+        assert_eq!(issues.len(), 1);
+    }
+
+    #[test]
+    fn test_export_section() {
+        let config = AppConfig::parse_toml("[export]\nverbatim = true").unwrap();
+        assert!(config.export.verbatim);
+        assert!(!AppConfig::parse_toml("").unwrap().export.verbatim);
+    }
+
+    #[test]
+    fn test_follow_section() {
+        let config =
+            AppConfig::parse_toml("[follow]\nload_rotated = false\ninherit_timestamps = true")
+                .unwrap();
+        assert!(!config.follow.load_rotated);
+        assert!(config.follow.inherit_timestamps);
+        let default = AppConfig::parse_toml("").unwrap().follow;
+        assert!(default.load_rotated);
+        assert!(!default.inherit_timestamps);
+        assert!(default.retention.is_unlimited());
+
+        let config = AppConfig::parse_toml(
+            "[follow]\nretention_lines = 500000\nretention_hours = 1.5\nspill = \"pruned.log\"",
+        )
+        .unwrap();
+        assert_eq!(config.follow.retention.lines, Some(500_000));
+        assert_eq!(
+            config.follow.retention.max_age,
+            Some(chrono::Duration::minutes(90))
+        );
+        assert_eq!(config.follow.spill, Some(PathBuf::from("pruned.log")));
+    }
+
+    #[test]
+    fn test_load_file_errors_name_the_file() {
+        let mut file = tempfile::NamedTempFile::new().unwrap();
+        writeln!(file, "[colors").unwrap();
+        let err = AppConfig::load_file(file.path()).unwrap_err();
+        assert!(matches!(err, QlogError::InvalidConfig { .. }));
+        assert_eq!(err.path(), file.path());
+        assert!(err.to_string().contains("Invalid TOML"), "{}", err);
+
+        let missing = file.path().with_extension("missing");
+        let err = AppConfig::load_file(&missing).unwrap_err();
+        assert!(matches!(err, QlogError::ReadConfig { .. }));
+        assert!(err
+            .to_string()
+            .starts_with(&format!("Cannot read config file {}", missing.display())));
+    }
+
+    #[test]
+    fn test_check_file_reports_problems() {
+        let mut file = tempfile::NamedTempFile::new().unwrap();
+        write!(
+            file,
+            "[colors]\nerror = \"crimson\"\n[theme]\nname = \"solarized\"\n\
+             [large_files]\ntreshold_mb = 10\n[keymap]\nx = \"quit\"\n"
+        )
+        .unwrap();
+
+        let issues = AppConfig::check_file(file.path()).unwrap();
+        assert_eq!(issues.len(), 4, "{:?}", issues);
+        assert!(issues[0].starts_with("Unknown color 'crimson'"));
+        assert!(issues[1].starts_with("Unknown theme 'solarized'"));
+        assert_eq!(issues[2], "Unknown key 'treshold_mb' in [large_files]");
+        assert_eq!(issues[3], "Unknown section [keymap]");
+
+        let mut mistyped = tempfile::NamedTempFile::new().unwrap();
+        write!(
+            mistyped,
+            "[large_files]\nthreshold_mb = \"10\"\n[view]\ncursor_underline = 1\n\
+             [follow]\nload_rotated = \"yes\"\n"
+        )
+        .unwrap();
+        let issues = AppConfig::check_file(mistyped.path()).unwrap();
+        assert_eq!(
+            issues,
+            [
+                "'threshold_mb' in [large_files] must be an integer",
+                "'cursor_underline' in [view] must be true or false",
+                "'load_rotated' in [follow] must be true or false",
+            ]
+        );
+
+        let mut broken = tempfile::NamedTempFile::new().unwrap();
+        writeln!(broken, "[colors").unwrap();
+        let issues = AppConfig::check_file(broken.path()).unwrap();
+        assert!(issues[0].starts_with("Invalid TOML"));
+    }
+
+    #[test]
+    fn test_levels_section() {
+        let mut issues = Vec::new();
+        let config = AppConfig::parse_with_issues(
+            "[levels]\nWRN = \"warning\"\n'sev=4' = \"warn\"\n're:(' = \"error\"\nX = \"loud\"",
+            &mut issues,
+        )
+        .unwrap();
+        assert_eq!(
+            config.levels.detect("ts=1 sev=4 slow"),
+            Some(LogLevel::Warn)
+        );
+        assert_eq!(issues.len(), 2, "{:?}", issues);
+        assert!(issues[0].starts_with("Invalid level pattern 're:('"));
+        assert_eq!(
+            issues[1],
+            "Invalid level for 'X': expected error, warn, info, debug or trace"
+        );
+    }
+
+    #[test]
+    fn test_aliases_section() {
+        let config = AppConfig::parse_toml(
+            "[aliases]\nfe = \"filter error\"\nclean = \"filter-out healthcheck\"\nq = \"quit\"\nbad = 3",
+        )
+        .unwrap();
+        assert_eq!(
+            config.aliases,
+            vec![
+                ("fe".to_string(), "filter error".to_string()),
+                ("clean".to_string(), "filter-out healthcheck".to_string()),
+            ]
+        );
+    }
+
     #[test]
     fn test_wildcard_pattern() {
         let matcher = PatternMatcher::new("*TODO*");
@@ -0,0 +1,221 @@
+//! Crash reports written when qlog panics.
+//!
+//! The UI keeps a small snapshot of its state (terminal size, line and
+//! filter counts, the names of recent actions) up to date. On panic the hook
+//! restores the terminal, writes the snapshot to `~/.qlog/crash-<time>.txt`
+//! and prints the path. Reports never contain log content, filter patterns
+//! or search queries: actions are recorded by name only.
+
+use std::collections::VecDeque;
+use std::fmt::{Debug, Write as _};
+use std::fs;
+use std::io;
+use std::panic::PanicHookInfo;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use chrono::Local;
+
+use crate::key_bindings::Mode;
+
+/// Number of recent actions kept for the report.
+const MAX_ACTIONS: usize = 20;
+
+static CONTEXT: Mutex<CrashContext> = Mutex::new(CrashContext::new());
+
+/// State included in a crash report.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CrashContext {
+    pub terminal: (u16, u16),
+    pub mode: Mode,
+    pub lines: usize,
+    pub visible: usize,
+    pub filters: usize,
+    /// Names of the most recent actions, oldest first
+    pub actions: VecDeque<String>,
+}
+
+impl CrashContext {
+    pub const fn new() -> Self {
+        Self {
+            terminal: (0, 0),
+            mode: Mode::Normal,
+            lines: 0,
+            visible: 0,
+            filters: 0,
+            actions: VecDeque::new(),
+        }
+    }
+
+    fn push_action(&mut self, action: String) {
+        if self.actions.len() == MAX_ACTIONS {
+            self.actions.pop_front();
+        }
+        self.actions.push_back(action);
+    }
+
+    /// Render the report for a panic at `location`.
+    pub fn report(&self, location: &str, thread: &str) -> String {
+        let mut out = String::new();
+        let _ = writeln!(out, "qlog {} crash report", env!("CARGO_PKG_VERSION"));
+        let _ = writeln!(out, "time:     {}", Local::now().to_rfc3339());
+        let _ = writeln!(
+            out,
+            "platform: {} {}",
+            std::env::consts::OS,
+            std::env::consts::ARCH
+        );
+        let _ = writeln!(out, "panic at: {} (thread {})", location, thread);
+        let _ = writeln!(out, "terminal: {}x{}", self.terminal.0, self.terminal.1);
+        let _ = writeln!(out, "mode:     {:?}", self.mode);
+        let _ = writeln!(out, "lines:    {} ({} shown)", self.lines, self.visible);
+        let _ = writeln!(out, "filters:  {}", self.filters);
+        let _ = writeln!(out, "\nlast {} actions:", self.actions.len());
+        for action in &self.actions {
+            let _ = writeln!(out, "  {}", action);
+        }
+        out
+    }
+}
+
+impl Default for CrashContext {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Name of an enum variant without its payload, e.g. `AddFilter` for
+/// `AddFilter { kind: Include, pattern: "..." }`.
+pub fn variant_name(value: &impl Debug) -> String {
+    let debug = format!("{:?}", value);
+    let end = debug.find(['(', ' ', '{']).unwrap_or(debug.len());
+    debug[..end].to_string()
+}
+
+/// Remember an action for the next crash report.
+pub fn record_action(action: String) {
+    if let Ok(mut context) = CONTEXT.lock() {
+        context.push_action(action);
+    }
+}
+
+/// Refresh the counts included in a crash report.
+pub fn update_view(terminal: (u16, u16), mode: Mode, lines: usize, visible: usize, filters: usize) {
+    if let Ok(mut context) = CONTEXT.lock() {
+        context.terminal = terminal;
+        context.mode = mode;
+        context.lines = lines;
+        context.visible = visible;
+        context.filters = filters;
+    }
+}
+
+/// Directory crash reports are written to (`~/.qlog`, or the temp dir).
+fn report_dir() -> PathBuf {
+    dirs::home_dir()
+        .map(|home| home.join(".qlog"))
+        .unwrap_or_else(std::env::temp_dir)
+}
+
+fn write_report(dir: &Path, report: &str) -> io::Result<PathBuf> {
+    fs::create_dir_all(dir)?;
+    let path = dir.join(format!(
+        "crash-{}.txt",
+        Local::now().format("%Y%m%d-%H%M%S")
+    ));
+    fs::write(&path, report)?;
+    Ok(path)
+}
+
+/// Install a panic hook that writes a crash report.
+///
+/// A panic on the main thread calls `restore_terminal` first, so the panic
+/// message and the report path are printed to a usable terminal.
+pub fn install_hook(restore_terminal: fn()) {
+    let default_hook = std::panic::take_hook();
+    std::panic::set_hook(Box::new(move |info: &PanicHookInfo<'_>| {
+        let main_thread = std::thread::current().name() == Some("main");
+        if main_thread {
+            restore_terminal();
+        }
+        default_hook(info);
+
+        let location = info
+            .location()
+            .map(|location| format!("{}:{}", location.file(), location.line()))
+            .unwrap_or_else(|| "unknown".to_string());
+        let thread = std::thread::current()
+            .name()
+            .unwrap_or("unnamed")
+            .to_string();
+        // A panic while the context is locked must not abort the report
+        let context = match CONTEXT.lock() {
+            Ok(context) => context.clone(),
+            Err(poisoned) => poisoned.into_inner().clone(),
+        };
+        let report = format!(
+            "{}\nbacktrace:\n{}\n",
+            context.report(&location, &thread),
+            std::backtrace::Backtrace::force_capture()
+        );
+
+        match write_report(&report_dir(), &report) {
+            Ok(path) => eprintln!(
+                "qlog crashed. A report was written to {}\n\
+                 It holds no log content; please attach it to a bug report.",
+                path.display()
+            ),
+            Err(e) => eprintln!("qlog crashed. Could not write a crash report: {}", e),
+        }
+    }));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::command::CommandEffect;
+    use crate::key_bindings::Msg;
+    use crate::model::FilterKind;
+
+    #[test]
+    fn test_variant_name_drops_payload() {
+        assert_eq!(variant_name(&Msg::ScrollDown), "ScrollDown");
+        assert_eq!(variant_name(&Msg::SearchTypeChar('s')), "SearchTypeChar");
+        let effect = CommandEffect::AddFilter {
+            kind: FilterKind::Include,
+            pattern: "secret".to_string(),
+            file: None,
+        };
+        assert_eq!(variant_name(&effect), "AddFilter");
+    }
+
+    #[test]
+    fn test_report_keeps_last_actions() {
+        let mut context = CrashContext::new();
+        for i in 0..25 {
+            context.push_action(format!("Action{}", i));
+        }
+        context.terminal = (120, 40);
+        context.lines = 1000;
+        context.visible = 10;
+        context.filters = 2;
+
+        assert_eq!(context.actions.len(), MAX_ACTIONS);
+        assert_eq!(context.actions.front().unwrap(), "Action5");
+
+        let report = context.report("src/app.rs:10", "main");
+        assert!(report.contains("panic at: src/app.rs:10 (thread main)"));
+        assert!(report.contains("terminal: 120x40"));
+        assert!(report.contains("lines:    1000 (10 shown)"));
+        assert!(report.contains("filters:  2"));
+        assert!(report.contains("  Action24\n"));
+        assert!(!report.contains("Action4\n"));
+    }
+
+    #[test]
+    fn test_write_report() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = write_report(&dir.path().join(".qlog"), "report").unwrap();
+        assert_eq!(fs::read_to_string(path).unwrap(), "report");
+    }
+}
@@ -0,0 +1,611 @@
+//! Detail pane for a single log line.
+//!
+//! JSON lines (optionally after a plain prefix such as a timestamp) are
+//! pretty-printed one field per row. Long string values and long arrays are
+//! folded behind `(+ N chars)` / `(+ N items)` markers so lines carrying huge
+//! payloads stay navigable; folds open and close individually or all at once.
+//! Nested objects and multi-line strings (stack traces) start open and fold
+//! to a single row, except traces too long to read through, which start
+//! folded. Frames repeated over and over (deep recursion) are shown once,
+//! with the repeats folded behind a `… (+ 27 repeats of 2 frames)` row.
+//!
+//! Serilog events (`Timestamp`/`Level`/`MessageTemplate`/`Properties`/
+//! `Exception`, or the compact `@t`/`@l`/`@mt`/`@x` form) list those fields
+//! first, so the message and exception are not buried among properties.
+
+use std::collections::HashSet;
+
+use serde_json::Value;
+
+/// Strings longer than this many characters are folded.
+const STRING_FOLD_CHARS: usize = 200;
+
+/// Characters of a folded string that stay visible.
+const STRING_PREVIEW_CHARS: usize = 80;
+
+/// Arrays with more items than this are folded.
+const ARRAY_FOLD_ITEMS: usize = 20;
+
+/// Items of a folded array that stay visible.
+const ARRAY_PREVIEW_ITEMS: usize = 5;
+
+/// Multi-line strings with more lines than this start folded.
+const TRACE_OPEN_LINES: usize = 60;
+
+/// Longest run of frames that is looked for repeats.
+const MAX_REPEAT_FRAMES: usize = 10;
+
+/// Repeats of a run of frames, after the first, before they are folded.
+const MIN_REPEATS: usize = 2;
+
+/// Brackets tried as the start of the JSON part of a line.
+const JSON_START_CANDIDATES: usize = 4;
+
+/// Spaces per nesting level.
+const INDENT: usize = 2;
+
+/// Serilog event fields, shown first in this order.
+const SERILOG_FIELDS: [&str; 13] = [
+    "Timestamp",
+    "@t",
+    "Level",
+    "@l",
+    "RenderedMessage",
+    "@m",
+    "Message",
+    "MessageTemplate",
+    "@mt",
+    "Exception",
+    "@x",
+    "Properties",
+    "@r",
+];
+
+/// One rendered row of the detail pane.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DetailRow {
+    pub depth: usize,
+    /// Object key or array index, if the row is a field
+    pub key: Option<String>,
+    /// Rendered value, bracket, or fold marker
+    pub text: String,
+    /// Path of the value that toggles when this row is folded or unfolded
+    pub fold: Option<String>,
+    /// Whether the row stands for a folded value
+    pub folded: bool,
+    /// Value copied when the row is yanked: strings unquoted and never
+    /// folded, objects and arrays as compact JSON
+    pub value: Option<String>,
+    /// Filter pattern matching this field in the raw line, e.g. `"RequestId":"abc"`
+    pub filter: Option<String>,
+}
+
+impl DetailRow {
+    fn new(depth: usize, key: Option<String>, text: impl Into<String>) -> Self {
+        Self {
+            depth,
+            key,
+            text: text.into(),
+            fold: None,
+            folded: false,
+            value: None,
+            filter: None,
+        }
+    }
+
+    fn foldable(mut self, path: &str) -> Self {
+        self.fold = Some(path.to_string());
+        self
+    }
+
+    fn folded(mut self, path: &str) -> Self {
+        self.folded = true;
+        self.foldable(path)
+    }
+
+    fn with_value(mut self, value: impl Into<String>) -> Self {
+        self.value = Some(value.into());
+        self
+    }
+
+    /// Set the filter pattern of a scalar field from its compact JSON.
+    ///
+    /// Object fields match as `"key":value`, the way compact JSON loggers
+    /// write them; array items match on the value alone.
+    fn with_filter(mut self, json: &str) -> Self {
+        self.filter = Some(match &self.key {
+            Some(key) if key.starts_with('"') => format!("{}:{}", key, json),
+            _ => json.to_string(),
+        });
+        self
+    }
+
+    /// Check whether the cursor stops on this row (closing brackets are skipped).
+    pub fn is_field(&self) -> bool {
+        self.value.is_some() || self.fold.is_some()
+    }
+
+    /// Full row text with indentation and key.
+    pub fn line(&self) -> String {
+        let indent = " ".repeat(self.depth * INDENT);
+        match &self.key {
+            Some(key) => format!("{}{}: {}", indent, key, self.text),
+            None => format!("{}{}", indent, self.text),
+        }
+    }
+}
+
+/// Detail view of one line.
+#[derive(Debug, Clone)]
+pub struct DetailView {
+    /// Text before the JSON value, e.g. a timestamp and level
+    prefix: String,
+    /// Parsed JSON, or `None` when the line is shown as plain text
+    json: Option<Value>,
+    raw: String,
+    /// Every fold opened (`E`) or closed (`C`); None keeps each value's default
+    expand_all: Option<bool>,
+    /// Folds flipped from their default
+    toggled: HashSet<String>,
+    /// Selected row
+    pub cursor: usize,
+}
+
+impl DetailView {
+    pub fn new(line: &str) -> Self {
+        let (prefix, json) = match parse_json(line) {
+            Some((prefix, value)) => (prefix.trim_end().to_string(), Some(value)),
+            None => (String::new(), None),
+        };
+        Self {
+            prefix,
+            json,
+            raw: line.to_string(),
+            expand_all: None,
+            toggled: HashSet::new(),
+            cursor: 0,
+        }
+    }
+
+    /// Check whether the line was recognized as JSON.
+    pub fn is_json(&self) -> bool {
+        self.json.is_some()
+    }
+
+    /// Check whether the line is a Serilog event.
+    pub fn is_serilog(&self) -> bool {
+        let Some(Value::Object(map)) = &self.json else {
+            return false;
+        };
+        (map.contains_key("Timestamp") && map.contains_key("Level"))
+            || (map.contains_key("@t") && (map.contains_key("@mt") || map.contains_key("@m")))
+    }
+
+    /// Rows to display, with folds applied.
+    pub fn rows(&self) -> Vec<DetailRow> {
+        let mut rows = Vec::new();
+        let Some(json) = &self.json else {
+            // A plain entry with its continuation lines shows one row each
+            let lines: Vec<&str> = self.raw.trim_end().lines().collect();
+            if lines.len() > 1 {
+                self.push_lines(&mut rows, 0, "", &lines);
+            } else {
+                rows.push(DetailRow::new(0, None, self.raw.clone()).with_value(self.raw.clone()));
+            }
+            return rows;
+        };
+        if !self.prefix.is_empty() {
+            rows.push(DetailRow::new(0, None, self.prefix.clone()).with_value(self.prefix.clone()));
+        }
+        self.push_value(&mut rows, 0, None, "", json);
+        rows
+    }
+
+    fn is_expanded(&self, path: &str, default_open: bool) -> bool {
+        self.expand_all.unwrap_or(default_open) != self.toggled.contains(path)
+    }
+
+    fn push_value(
+        &self,
+        rows: &mut Vec<DetailRow>,
+        depth: usize,
+        key: Option<String>,
+        path: &str,
+        value: &Value,
+    ) {
+        let compact = || value.to_string();
+        match value {
+            // The root object has no fold
+            Value::Object(map)
+                if !map.is_empty() && !path.is_empty() && !self.is_expanded(path, true) =>
+            {
+                let text = format!(
+                    "{{… {} field{}}}",
+                    map.len(),
+                    if map.len() == 1 { "" } else { "s" }
+                );
+                rows.push(
+                    DetailRow::new(depth, key, text)
+                        .folded(path)
+                        .with_value(compact()),
+                );
+            }
+            Value::Object(map) if !map.is_empty() => {
+                let open = DetailRow::new(depth, key, "{").with_value(compact());
+                rows.push(if path.is_empty() {
+                    open
+                } else {
+                    open.foldable(path)
+                });
+                let mut fields: Vec<(&String, &Value)> = map.iter().collect();
+                if path.is_empty() && self.is_serilog() {
+                    fields.sort_by_key(|(k, _)| {
+                        SERILOG_FIELDS
+                            .iter()
+                            .position(|field| field == k)
+                            .unwrap_or(SERILOG_FIELDS.len())
+                    });
+                }
+                for (k, v) in fields {
+                    let child = format!("{}/{}", path, k);
+                    self.push_value(rows, depth + 1, Some(quote(k)), &child, v);
+                }
+                rows.push(DetailRow::new(depth, None, "}"));
+            }
+            Value::Array(items) if !items.is_empty() => {
+                let long = items.len() > ARRAY_FOLD_ITEMS;
+                let folded = long && !self.is_expanded(path, false);
+                let open = DetailRow::new(depth, key, "[").with_value(compact());
+                rows.push(if long { open.foldable(path) } else { open });
+
+                let shown = if folded {
+                    ARRAY_PREVIEW_ITEMS
+                } else {
+                    items.len()
+                };
+                for (i, item) in items.iter().take(shown).enumerate() {
+                    let child = format!("{}/{}", path, i);
+                    self.push_value(rows, depth + 1, Some(i.to_string()), &child, item);
+                }
+                if folded {
+                    let marker = format!("… (+ {} items)", items.len() - shown);
+                    rows.push(DetailRow::new(depth + 1, None, marker).folded(path));
+                }
+                rows.push(DetailRow::new(depth, None, "]"));
+            }
+            Value::String(s) if s.trim_end().contains('\n') => {
+                let lines: Vec<&str> = s.trim_end().lines().collect();
+                let open = self.is_expanded(path, lines.len() <= TRACE_OPEN_LINES);
+                let row = if open {
+                    DetailRow::new(depth, key, format!("({} lines)", lines.len())).foldable(path)
+                } else {
+                    let more = lines.len() - 1;
+                    let text = format!(
+                        "{}… (+ {} line{})",
+                        quote(lines[0]),
+                        more,
+                        if more == 1 { "" } else { "s" }
+                    );
+                    DetailRow::new(depth, key, text).folded(path)
+                };
+                rows.push(row.with_value(s.clone()).with_filter(&quote(s)));
+                if open {
+                    self.push_lines(rows, depth + 1, path, &lines);
+                }
+            }
+            Value::String(s) => {
+                let chars = s.chars().count();
+                let row = if chars <= STRING_FOLD_CHARS {
+                    DetailRow::new(depth, key, quote(s))
+                } else if self.is_expanded(path, false) {
+                    DetailRow::new(depth, key, quote(s)).foldable(path)
+                } else {
+                    let preview: String = s.chars().take(STRING_PREVIEW_CHARS).collect();
+                    let text = format!(
+                        "{}… (+ {} chars)",
+                        quote(&preview).trim_end_matches('"'),
+                        chars - STRING_PREVIEW_CHARS
+                    );
+                    DetailRow::new(depth, key, text).folded(path)
+                };
+                rows.push(row.with_value(s.clone()).with_filter(&quote(s)));
+            }
+            _ => rows.push(
+                DetailRow::new(depth, key, compact())
+                    .with_value(compact())
+                    .with_filter(&compact()),
+            ),
+        }
+    }
+
+    /// Add a row for each of `lines`, the lines of the value at `path`.
+    /// A run of frames repeated right after itself is shown once, followed
+    /// by a fold holding the repeats.
+    fn push_lines(&self, rows: &mut Vec<DetailRow>, depth: usize, path: &str, lines: &[&str]) {
+        let row = |line: &str| DetailRow::new(depth, None, line.trim_end()).with_value(line.trim());
+        let mut pos = 0;
+        while pos < lines.len() {
+            let Some((frames, repeats)) = find_repeats(lines, pos) else {
+                rows.push(row(lines[pos]));
+                pos += 1;
+                continue;
+            };
+            rows.extend(lines[pos..pos + frames].iter().map(|line| row(line)));
+            pos += frames;
+            let end = pos + frames * repeats;
+            let fold = format!("{}#{}", path, pos);
+            let label = format!(
+                "{} repeats of {} frame{}",
+                repeats,
+                frames,
+                if frames == 1 { "" } else { "s" }
+            );
+            if self.is_expanded(&fold, false) {
+                rows.push(DetailRow::new(depth, None, format!("({})", label)).foldable(&fold));
+                rows.extend(lines[pos..end].iter().map(|line| row(line)));
+            } else {
+                rows.push(DetailRow::new(depth, None, format!("… (+ {})", label)).folded(&fold));
+            }
+            pos = end;
+        }
+    }
+
+    /// Open or close the fold on the selected row. Returns false if the
+    /// row has no fold.
+    pub fn toggle_fold(&mut self) -> bool {
+        let rows = self.rows();
+        let Some(path) = rows.get(self.cursor).and_then(|row| row.fold.clone()) else {
+            return false;
+        };
+        if !self.toggled.remove(&path) {
+            self.toggled.insert(path.clone());
+        }
+        // Keep the cursor on the row that owns the fold
+        if let Some(pos) = self
+            .rows()
+            .iter()
+            .position(|row| row.fold.as_deref() == Some(path.as_str()))
+        {
+            self.cursor = pos;
+        }
+        true
+    }
+
+    /// Open (`true`) or close every fold.
+    pub fn set_all_expanded(&mut self, expanded: bool) {
+        self.expand_all = Some(expanded);
+        self.toggled.clear();
+        self.cursor = self.cursor.min(self.rows().len().saturating_sub(1));
+    }
+
+    /// Move to the next field, skipping closing brackets.
+    pub fn cursor_down(&mut self) {
+        let rows = self.rows();
+        if let Some(pos) = (self.cursor + 1..rows.len()).find(|&pos| rows[pos].is_field()) {
+            self.cursor = pos;
+        }
+    }
+
+    /// Move to the previous field, skipping closing brackets.
+    pub fn cursor_up(&mut self) {
+        let rows = self.rows();
+        if let Some(pos) = (0..self.cursor).rev().find(|&pos| rows[pos].is_field()) {
+            self.cursor = pos;
+        }
+    }
+
+    /// Filter pattern for the selected field, if it holds a single value.
+    pub fn focused_filter(&self) -> Option<String> {
+        self.rows().into_iter().nth(self.cursor)?.filter
+    }
+
+    /// Key and value of the selected field, for yanking.
+    pub fn focused_value(&self) -> Option<(Option<String>, String)> {
+        let row = self.rows().into_iter().nth(self.cursor)?;
+        Some((row.key, row.value?))
+    }
+}
+
+/// Find a JSON object or array that runs to the end of `line`, returning
+/// the text before it and the parsed value.
+///
+/// Only the first few brackets are tried, so `[INFO] {...}` still works
+/// without parsing from every bracket of a long plain line.
+pub(crate) fn parse_json(line: &str) -> Option<(&str, Value)> {
+    line.match_indices(['{', '['])
+        .take(JSON_START_CANDIDATES)
+        .find_map(|(start, _)| {
+            let value = serde_json::from_str(line[start..].trim_end()).ok()?;
+            Some((&line[..start], value))
+        })
+}
+
+/// Find a run of frames at `start` of `lines` that repeats right after
+/// itself at least `MIN_REPEATS` times. Returns the frames in the run and
+/// the repeats after it; the run covering the most lines wins, the shorter
+/// one on a tie.
+fn find_repeats(lines: &[&str], start: usize) -> Option<(usize, usize)> {
+    (1..=MAX_REPEAT_FRAMES)
+        .filter_map(|frames| {
+            let run = lines.get(start..start + frames)?;
+            if run.iter().all(|line| line.trim().is_empty()) {
+                return None;
+            }
+            let repeats = lines[start + frames..]
+                .chunks_exact(frames)
+                .take_while(|chunk| chunk.iter().zip(run).all(|(a, b)| a.trim() == b.trim()))
+                .count();
+            (repeats >= MIN_REPEATS).then_some((frames, repeats))
+        })
+        .max_by_key(|&(frames, repeats)| (frames * repeats, std::cmp::Reverse(frames)))
+}
+
+/// JSON-quote a string.
+fn quote(s: &str) -> String {
+    serde_json::to_string(s).unwrap_or_else(|_| format!("\"{}\"", s))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lines(view: &DetailView) -> Vec<String> {
+        view.rows().iter().map(DetailRow::line).collect()
+    }
+
+    #[test]
+    fn test_json_after_prefix_keeps_key_order() {
+        let view = DetailView::new(r#"2024-01-01 INFO {"z":1,"a":{"b":true},"list":[]}"#);
+        assert!(view.is_json());
+        assert_eq!(
+            lines(&view),
+            vec![
+                "2024-01-01 INFO",
+                "{",
+                r#"  "z": 1"#,
+                r#"  "a": {"#,
+                r#"    "b": true"#,
+                "  }",
+                r#"  "list": []"#,
+                "}",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_long_values_fold_and_expand() {
+        let long = "x".repeat(300);
+        let items: Vec<String> = (0..30).map(|i| i.to_string()).collect();
+        let line = format!(r#"{{"payload":"{}","ids":[{}]}}"#, long, items.join(","));
+        let mut view = DetailView::new(&line);
+
+        let rows = lines(&view);
+        assert!(rows[1].ends_with("… (+ 220 chars)"));
+        assert!(rows.contains(&"    … (+ 25 items)".to_string()));
+        assert_eq!(rows.len(), 11);
+
+        view.cursor = 1;
+        assert!(view.toggle_fold());
+        assert!(lines(&view)[1].ends_with(&format!("{}\"", long)));
+
+        view.set_all_expanded(true);
+        assert_eq!(view.rows().len(), 35);
+        view.set_all_expanded(false);
+        assert_eq!(view.rows().len(), 11);
+    }
+
+    #[test]
+    fn test_navigate_fields_and_yank_value() {
+        let mut view = DetailView::new(r#"{"req":{"id":"abc-123","n":2},"ok":true}"#);
+        view.cursor_down();
+        view.cursor_down();
+        assert_eq!(
+            view.focused_value(),
+            Some((Some(r#""id""#.to_string()), "abc-123".to_string()))
+        );
+
+        // The closing brace of "req" is skipped
+        view.cursor_down();
+        view.cursor_down();
+        assert_eq!(view.focused_value().unwrap().1, "true");
+        view.cursor_down();
+        assert_eq!(view.focused_value().unwrap().1, "true");
+
+        view.cursor = 1;
+        assert_eq!(view.focused_value().unwrap().1, r#"{"id":"abc-123","n":2}"#);
+        assert_eq!(view.focused_filter(), None);
+    }
+
+    #[test]
+    fn test_field_filter_patterns() {
+        let mut view = DetailView::new(r#"{"RequestId":"abc","codes":[404]}"#);
+        view.cursor = 1;
+        assert_eq!(
+            view.focused_filter().as_deref(),
+            Some(r#""RequestId":"abc""#)
+        );
+        view.cursor = 3;
+        assert_eq!(view.focused_filter().as_deref(), Some("404"));
+    }
+
+    #[test]
+    fn test_serilog_event_fields_first_and_stack_trace_rows() {
+        let line = r#"{"Properties":{"RequestId":"abc"},"Exception":"System.Exception: boom\n   at Api.Run()","Level":"Error","Timestamp":"2024-01-01T10:00:00Z","MessageTemplate":"Failed {RequestId}"}"#;
+        let mut view = DetailView::new(line);
+        assert!(view.is_serilog());
+        assert_eq!(
+            lines(&view),
+            vec![
+                "{",
+                r#"  "Timestamp": "2024-01-01T10:00:00Z""#,
+                r#"  "Level": "Error""#,
+                r#"  "MessageTemplate": "Failed {RequestId}""#,
+                r#"  "Exception": (2 lines)"#,
+                "    System.Exception: boom",
+                "       at Api.Run()",
+                r#"  "Properties": {"#,
+                r#"    "RequestId": "abc""#,
+                "  }",
+                "}",
+            ]
+        );
+
+        view.cursor = 6;
+        assert_eq!(view.focused_value().unwrap().1, "at Api.Run()");
+        view.cursor = 4;
+        assert!(view.toggle_fold());
+        view.cursor = 5;
+        assert!(view.toggle_fold());
+        let rows = lines(&view);
+        assert_eq!(
+            rows[4],
+            r#"  "Exception": "System.Exception: boom"… (+ 1 line)"#
+        );
+        assert_eq!(rows[5], r#"  "Properties": {… 1 field}"#);
+        assert_eq!(rows.len(), 7);
+    }
+
+    #[test]
+    fn test_repeated_frames_fold() {
+        let mut trace = vec!["java.lang.StackOverflowError"];
+        trace.extend(["\tat A.f(A.java:1)", "\tat A.g(A.java:2)"].repeat(4));
+        trace.extend(["\tat A.main(A.java:9)", "\t... 3 more"]);
+        let mut view = DetailView::new(&trace.join("\n"));
+        assert_eq!(
+            lines(&view),
+            vec![
+                "java.lang.StackOverflowError",
+                "\tat A.f(A.java:1)",
+                "\tat A.g(A.java:2)",
+                "… (+ 3 repeats of 2 frames)",
+                "\tat A.main(A.java:9)",
+                "\t... 3 more",
+            ]
+        );
+
+        view.cursor = 3;
+        assert!(view.toggle_fold());
+        let rows = lines(&view);
+        assert_eq!(rows[3], "(3 repeats of 2 frames)");
+        assert_eq!(rows.len(), 12);
+
+        // In a JSON string too; a long trace starts folded
+        let mut frames = vec!["boom".to_string()];
+        frames.extend((0..70).map(|n| format!("  at f{}()", n)));
+        let line = serde_json::json!({ "x": frames.join("\n") }).to_string();
+        let mut view = DetailView::new(&line);
+        assert_eq!(lines(&view)[1], r#"  "x": "boom"… (+ 70 lines)"#);
+        view.set_all_expanded(true);
+        assert_eq!(view.rows().len(), 74);
+    }
+
+    #[test]
+    fn test_plain_line_is_one_row() {
+        let bracketed = DetailView::new(r#"[INFO] {"ok":true}"#);
+        assert_eq!(lines(&bracketed)[0], "[INFO]");
+
+        let view = DetailView::new("plain text {not json");
+        assert!(!view.is_json());
+        assert_eq!(lines(&view), vec!["plain text {not json"]);
+    }
+}
@@ -0,0 +1,215 @@
+//! Running the viewer from another program.
+
+use std::io;
+use std::time::{Duration, Instant};
+
+use crossterm::{
+    event::{
+        self, DisableFocusChange, DisableMouseCapture, EnableFocusChange, EnableMouseCapture,
+        Event, KeyCode, KeyEventKind, KeyModifiers,
+    },
+    execute,
+    terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
+};
+use ratatui::{backend::CrosstermBackend, Terminal};
+
+use crate::app::App;
+use crate::follow::FollowedLine;
+use crate::model::LogStorage;
+use crate::source::LineSource;
+
+/// Event loop ticks with and without focus, as in the `qlog` binary.
+const TICK_RATE: Duration = Duration::from_millis(50);
+const UNFOCUSED_TICK_RATE: Duration = Duration::from_millis(500);
+
+/// Show the lines of `source` in the viewer until the user quits.
+///
+/// Takes over the terminal (raw mode, alternate screen) and restores it on
+/// return. Filters, search, selection and the other features work as on log
+/// files; lines the source gains while the viewer runs are appended, as in
+/// follow mode.
+///
+/// ```no_run
+/// let lines: Vec<String> = (0..1000).map(|i| format!("INFO request {}", i)).collect();
+/// qlog::run_with_source(lines)?;
+/// # Ok::<(), std::io::Error>(())
+/// ```
+pub fn run_with_source(source: impl LineSource) -> io::Result<()> {
+    run_with_source_in(App::new(), source)
+}
+
+/// Show the lines of `source` in `app`, which the caller has set up
+/// beforehand (filters, wrap mode, config), until the user quits.
+///
+/// A search started in `app` beforehand (`App::init_search_state`) runs
+/// once the first lines arrive.
+pub fn run_with_source_in(mut app: App, mut source: impl LineSource) -> io::Result<()> {
+    let mut search = app.get_search_query().map(str::to_string);
+    app.set_storage(LogStorage::empty());
+    source.refresh();
+    let mut fed = feed(&mut app, &mut source, 0);
+    app.apply_startup_position();
+    start_search(&mut app, &mut search);
+
+    enable_raw_mode()?;
+    execute!(io::stdout(), EnterAlternateScreen, EnableFocusChange)?;
+    if app.mouse_capture {
+        execute!(io::stdout(), EnableMouseCapture)?;
+    }
+    let mut terminal = Terminal::new(CrosstermBackend::new(io::stdout()))?;
+
+    let result = (|| {
+        let mut last_tick = Instant::now();
+        // Input always redraws; unfocused, nothing else does
+        let mut redraw = true;
+        while !app.should_quit {
+            source.refresh();
+            fed = feed(&mut app, &mut source, fed);
+            start_search(&mut app, &mut search);
+            app.poll_replay();
+            app.poll_search_preview();
+            app.poll_jobs();
+            app.expire_load_summary();
+            app.update_notifications();
+            if std::mem::take(&mut redraw) || app.is_focused() {
+                terminal.draw(|f| crate::ui::draw(f, &mut app))?;
+            }
+
+            let tick_rate = if app.is_focused() {
+                TICK_RATE
+            } else {
+                UNFOCUSED_TICK_RATE
+            };
+            let timeout = tick_rate.saturating_sub(last_tick.elapsed());
+            if event::poll(timeout)? {
+                redraw = true;
+                match event::read()? {
+                    Event::Mouse(mouse) => app.handle_mouse(mouse),
+                    Event::FocusGained => app.set_focused(true),
+                    Event::FocusLost => app.set_focused(false),
+                    Event::Key(key) if key.kind == KeyEventKind::Press => {
+                        if key.code == KeyCode::Char('c')
+                            && key.modifiers.contains(KeyModifiers::CONTROL)
+                        {
+                            app.should_quit = true;
+                        } else {
+                            app.handle_key(key);
+                        }
+                    }
+                    _ => {}
+                }
+            }
+            if last_tick.elapsed() >= tick_rate {
+                last_tick = Instant::now();
+            }
+        }
+        Ok(())
+    })();
+
+    disable_raw_mode()?;
+    execute!(
+        terminal.backend_mut(),
+        LeaveAlternateScreen,
+        DisableMouseCapture,
+        DisableFocusChange
+    )?;
+    terminal.show_cursor()?;
+    result
+}
+
+/// Append the lines `source` has beyond the first `fed` to the viewer,
+/// then release them from the source. Returns the number of lines fed so
+/// far.
+fn feed(app: &mut App, source: &mut impl LineSource, fed: usize) -> usize {
+    let len = source.len();
+    if len <= fed {
+        return fed;
+    }
+    let lines = (fed..len)
+        .filter_map(|idx| {
+            Some(FollowedLine {
+                file_index: 0,
+                text: source.get_line(idx)?.as_bytes().to_vec(),
+                timestamp: source.get_timestamp(idx),
+            })
+        })
+        .collect();
+    if !app.append_lines(lines) {
+        return fed;
+    }
+    source.release(len);
+    len
+}
+
+/// Run the pending `search` once the viewer has lines to search.
+fn start_search(app: &mut App, search: &mut Option<String>) {
+    if app.total_lines() == 0 {
+        return;
+    }
+    if let Some(query) = search.take() {
+        app.init_search_state(query);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::source::ChannelSource;
+
+    #[test]
+    fn test_feed_appends_new_lines() {
+        let mut app = App::new();
+        app.set_storage(LogStorage::empty());
+        let mut lines = vec![
+            "2024-01-01 10:00:00 INFO start".to_string(),
+            "2024-01-01 10:00:01 ERROR failed".to_string(),
+        ];
+        app.filters.add_include("error");
+        app.update_filtered_logs();
+
+        let fed = feed(&mut app, &mut lines, 0);
+        assert_eq!(fed, 2);
+        assert_eq!(app.total_lines(), 2);
+        assert_eq!(app.filtered_indices, vec![1]);
+
+        lines.push("2024-01-01 10:00:02 ERROR again".to_string());
+        assert_eq!(feed(&mut app, &mut lines, fed), 3);
+        assert_eq!(app.filtered_indices, vec![1, 2]);
+        assert!(app
+            .get_filtered_timestamp(1)
+            .is_some_and(|time| time.to_rfc3339() == "2024-01-01T10:00:02+00:00"));
+    }
+
+    #[test]
+    fn test_feed_releases_piped_lines() {
+        let mut app = App::new();
+        app.set_storage(LogStorage::empty());
+        let (tx, rx) = std::sync::mpsc::channel();
+        let mut source = ChannelSource::new(rx);
+        tx.send("INFO start".to_string()).unwrap();
+        source.refresh();
+
+        assert_eq!(feed(&mut app, &mut source, 0), 1);
+        assert_eq!(app.total_lines(), 1);
+        assert!(source.get_line(0).is_none());
+    }
+
+    #[test]
+    fn test_search_waits_for_the_first_lines() {
+        let mut app = App::new();
+        app.set_storage(LogStorage::empty());
+        let mut search = Some("failed".to_string());
+        start_search(&mut app, &mut search);
+        assert!(search.is_some());
+
+        let mut lines = vec![
+            "2024-01-01 10:00:00 INFO start".to_string(),
+            "2024-01-01 10:00:01 ERROR failed".to_string(),
+        ];
+        feed(&mut app, &mut lines, 0);
+        start_search(&mut app, &mut search);
+        assert!(search.is_none());
+        assert_eq!(app.get_search_query(), Some("failed"));
+        assert_eq!(app.selected_line, 1);
+    }
+}
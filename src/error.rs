@@ -0,0 +1,89 @@
+//! Errors returned by the qlog library.
+
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// Error from loading logs or configuration, naming the file and the
+/// operation that failed.
+#[derive(Debug, thiserror::Error)]
+pub enum QlogError {
+    #[error("Cannot open {}: {source}", path.display())]
+    Open {
+        path: PathBuf,
+        #[source]
+        source: io::Error,
+    },
+    #[error("Cannot memory-map {}: {source}", path.display())]
+    Map {
+        path: PathBuf,
+        #[source]
+        source: io::Error,
+    },
+    #[error("Cannot decompress {}: {source}", path.display())]
+    Decompress {
+        path: PathBuf,
+        #[source]
+        source: io::Error,
+    },
+    #[error("Cannot read config file {}: {source}", path.display())]
+    ReadConfig {
+        path: PathBuf,
+        #[source]
+        source: io::Error,
+    },
+    #[error("Invalid config file {}: {message}", path.display())]
+    InvalidConfig { path: PathBuf, message: String },
+    #[error("Cannot use index database {}: {source}", path.display())]
+    Sidecar {
+        path: PathBuf,
+        #[source]
+        source: rusqlite::Error,
+    },
+}
+
+impl QlogError {
+    /// File the error is about.
+    pub fn path(&self) -> &Path {
+        match self {
+            QlogError::Open { path, .. }
+            | QlogError::Map { path, .. }
+            | QlogError::Decompress { path, .. }
+            | QlogError::ReadConfig { path, .. }
+            | QlogError::InvalidConfig { path, .. }
+            | QlogError::Sidecar { path, .. } => path.as_path(),
+        }
+    }
+
+    /// Underlying I/O error, if there is one.
+    pub fn io_error(&self) -> Option<&io::Error> {
+        match self {
+            QlogError::Open { source, .. }
+            | QlogError::Map { source, .. }
+            | QlogError::Decompress { source, .. }
+            | QlogError::ReadConfig { source, .. } => Some(source),
+            QlogError::InvalidConfig { .. } | QlogError::Sidecar { .. } => None,
+        }
+    }
+}
+
+/// Result type of the qlog library.
+pub type Result<T, E = QlogError> = std::result::Result<T, E>;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_message_names_file_and_operation() {
+        let err = QlogError::Open {
+            path: PathBuf::from("/var/log/app.log"),
+            source: io::Error::from(io::ErrorKind::NotFound),
+        };
+        assert_eq!(
+            err.to_string(),
+            "Cannot open /var/log/app.log: entity not found"
+        );
+        assert_eq!(err.path(), Path::new("/var/log/app.log"));
+        assert_eq!(err.io_error().unwrap().kind(), io::ErrorKind::NotFound);
+    }
+}
@@ -0,0 +1,321 @@
+//! Exceptions grouped by type and top frame, shown by `:exceptions`.
+//!
+//! A small error tracker: every entry in view with an `Exception` (or
+//! `@x`) field, or followed by a stack trace, is counted under its
+//! exception type and the frame it was thrown from, with the times it was
+//! first and last seen. Enter in the overlay goes to the latest instance.
+//!
+//! Traces are recognized in .NET and Java form (`   at Api.Run()`) and as
+//! Python tracebacks, whose innermost frame comes last.
+
+use std::cmp::Reverse;
+use std::collections::HashMap;
+
+use chrono::{DateTime, Utc};
+use rayon::prelude::*;
+
+use crate::detail::parse_json;
+use crate::export::json_field;
+use crate::jobs::JobContext;
+use crate::source::LineSource;
+
+/// JSON fields holding an exception and its trace.
+const EXCEPTION_FIELDS: [&str; 2] = ["Exception", "@x"];
+
+/// Continuation lines of an entry read for its trace.
+const TRACE_SCAN_LINES: usize = 500;
+
+const PYTHON_TRACEBACK: &str = "Traceback (most recent call last):";
+
+/// Lines scanned by a job between checks for cancellation.
+const SCAN_CHUNK_LINES: usize = 10_000;
+
+/// Storage index, type and top frame of an exception found.
+type Found = (usize, String, Option<String>);
+
+/// The instances of one exception type thrown from one frame.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ExceptionGroup {
+    /// Exception type, such as `System.InvalidOperationException`
+    pub name: String,
+    /// Frame the exception was thrown from, if the trace has one
+    pub frame: Option<String>,
+    /// Storage indices of the entries, in log order
+    pub lines: Vec<usize>,
+    pub first_seen: Option<DateTime<Utc>>,
+    pub last_seen: Option<DateTime<Utc>>,
+}
+
+impl ExceptionGroup {
+    pub fn count(&self) -> usize {
+        self.lines.len()
+    }
+
+    /// Storage index of the latest instance.
+    pub fn latest(&self) -> Option<usize> {
+        self.lines.last().copied()
+    }
+}
+
+/// Group the exceptions among the lines `indices` refer to, most frequent
+/// first.
+pub fn scan(source: &(dyn LineSource + Sync), indices: &[usize]) -> Vec<ExceptionGroup> {
+    group(source, find(source, indices))
+}
+
+/// [`scan`] on the thread of a job, in chunks so it reports progress;
+/// None once the job is cancelled.
+pub fn scan_in_job<U>(
+    source: &(dyn LineSource + Sync),
+    indices: &[usize],
+    ctx: &JobContext<U>,
+) -> Option<Vec<ExceptionGroup>> {
+    let mut found = Vec::new();
+    for chunk in indices.chunks(SCAN_CHUNK_LINES) {
+        if ctx.is_cancelled() {
+            return None;
+        }
+        found.extend(find(source, chunk));
+        ctx.advance(chunk.len());
+    }
+    Some(group(source, found))
+}
+
+fn find(source: &(dyn LineSource + Sync), indices: &[usize]) -> Vec<Found> {
+    indices
+        .par_iter()
+        .filter_map(|&idx| {
+            let (name, frame) = exception_at(source, idx)?;
+            Some((idx, name, frame))
+        })
+        .collect()
+}
+
+/// Group exceptions found, in log order, most frequent first.
+fn group(source: &dyn LineSource, found: Vec<Found>) -> Vec<ExceptionGroup> {
+    let mut groups: Vec<ExceptionGroup> = Vec::new();
+    let mut positions: HashMap<(String, Option<String>), usize> = HashMap::new();
+    for (idx, name, frame) in found {
+        let time = source.get_timestamp(idx);
+        let pos = *positions
+            .entry((name.clone(), frame.clone()))
+            .or_insert_with(|| {
+                groups.push(ExceptionGroup {
+                    name,
+                    frame,
+                    lines: Vec::new(),
+                    first_seen: None,
+                    last_seen: None,
+                });
+                groups.len() - 1
+            });
+        let group = &mut groups[pos];
+        group.lines.push(idx);
+        if let Some(time) = time {
+            group.first_seen = Some(group.first_seen.map_or(time, |first| first.min(time)));
+            group.last_seen = Some(group.last_seen.map_or(time, |last| last.max(time)));
+        }
+    }
+    // Stable, so equal counts keep the order they were first seen in
+    groups.sort_by_key(|group| Reverse(group.count()));
+    groups
+}
+
+/// Type and top frame of the exception logged at line `idx`: from an
+/// exception field of a JSON line, or from the trace in the continuation
+/// lines of a plain entry.
+fn exception_at(source: &dyn LineSource, idx: usize) -> Option<(String, Option<String>)> {
+    let line = source.get_line(idx)?;
+    let text = line.as_str_lossy();
+    if EXCEPTION_FIELDS
+        .iter()
+        .any(|field| text.contains(&format!("\"{}\"", field)))
+    {
+        if let Some((_, json)) = parse_json(&text) {
+            let exception = EXCEPTION_FIELDS
+                .iter()
+                .find_map(|field| json_field(&json, field))
+                .filter(|exception| !exception.trim().is_empty())?;
+            let lines: Vec<&str> = exception.lines().collect();
+            return Some(match parse_trace(&lines) {
+                Some(found) => found,
+                None => (first_line_name(&exception), None),
+            });
+        }
+    }
+
+    // Continuation lines (no timestamp) belong to the entry above them
+    source.get_timestamp(idx)?;
+    let continuation: Vec<String> = (idx + 1..source.len())
+        .take(TRACE_SCAN_LINES)
+        .take_while(|&next| source.get_timestamp(next).is_none())
+        .filter_map(|next| Some(source.get_line(next)?.as_str_lossy().into_owned()))
+        .collect();
+    if continuation.is_empty() {
+        return None;
+    }
+    let lines: Vec<&str> = std::iter::once(text.as_ref())
+        .chain(continuation.iter().map(String::as_str))
+        .collect();
+    parse_trace(&lines)
+}
+
+/// Type and top frame of a stack trace; None if `lines` hold no frames.
+fn parse_trace(lines: &[&str]) -> Option<(String, Option<String>)> {
+    if let Some(start) = lines
+        .iter()
+        .position(|line| line.trim() == PYTHON_TRACEBACK)
+    {
+        return parse_python_trace(&lines[start + 1..]);
+    }
+    let first_frame = lines
+        .iter()
+        .position(|line| line.trim_start().starts_with("at "))?;
+    let frame = lines[first_frame].trim_start()["at ".len()..].trim();
+    // The exception is named on the nearest line above its frames
+    let name = lines[..first_frame]
+        .iter()
+        .rev()
+        .find_map(|line| exception_name(line))
+        .unwrap_or("(unknown)");
+    Some((name.to_string(), Some(frame.to_string())))
+}
+
+/// Type and innermost frame of the lines after `Traceback (most recent
+/// call last):`. The exception follows the frames, on the first line that
+/// is not indented.
+fn parse_python_trace(lines: &[&str]) -> Option<(String, Option<String>)> {
+    let last_frame = lines
+        .iter()
+        .rposition(|line| line.trim_start().starts_with("File \""))?;
+    let rest = &lines[last_frame + 1..];
+    let name = rest
+        .iter()
+        .filter(|line| !line.starts_with(char::is_whitespace))
+        .chain(rest)
+        .find_map(|line| exception_name(line))
+        .unwrap_or("(unknown)");
+    Some((name.to_string(), Some(lines[last_frame].trim().to_string())))
+}
+
+/// The first word of `line` that names an exception type: a (dotted)
+/// identifier whose last part is capitalized and ends in `Exception` or
+/// `Error`, such as `java.lang.IllegalStateException` or `ValueError`.
+fn exception_name(line: &str) -> Option<&str> {
+    line.split(|c: char| !(c.is_alphanumeric() || matches!(c, '.' | '_' | '$')))
+        .map(|word| word.trim_matches('.'))
+        .find(|word| {
+            let last = word.rsplit('.').next().unwrap_or(word);
+            last.starts_with(|c: char| c.is_ascii_uppercase())
+                && (last.ends_with("Exception") || last.ends_with("Error"))
+                // Not a level written in capitals
+                && last.chars().any(|c| c.is_ascii_lowercase())
+        })
+}
+
+/// Name of an exception given without frames: a type on its first line,
+/// or the first line up to its message.
+fn first_line_name(exception: &str) -> String {
+    let first = exception.lines().next().unwrap_or_default();
+    exception_name(first)
+        .unwrap_or_else(|| first.split(": ").next().unwrap_or(first))
+        .trim()
+        .to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::log_storage::storage_from;
+
+    #[test]
+    fn test_traces_give_type_and_top_frame() {
+        let dotnet = [
+            "2024-01-01 10:00:00 ERROR Request failed",
+            "System.InvalidOperationException: Sequence contains no elements",
+            "   at System.Linq.Enumerable.First[T](IEnumerable`1 source)",
+            "   at Api.Orders.Get(Int32 id) in /src/Orders.cs:line 12",
+        ];
+        assert_eq!(
+            parse_trace(&dotnet),
+            Some((
+                "System.InvalidOperationException".to_string(),
+                Some("System.Linq.Enumerable.First[T](IEnumerable`1 source)".to_string())
+            ))
+        );
+
+        let python = [
+            "ERROR worker crashed",
+            "Traceback (most recent call last):",
+            "  File \"app.py\", line 3, in <module>",
+            "    main()",
+            "  File \"app.py\", line 2, in main",
+            "    raise KeyError('id')",
+            "KeyError: 'id'",
+        ];
+        assert_eq!(
+            parse_trace(&python),
+            Some((
+                "KeyError".to_string(),
+                Some("File \"app.py\", line 2, in main".to_string())
+            ))
+        );
+
+        assert_eq!(parse_trace(&["ERROR no trace", "  details"]), None);
+        assert_eq!(exception_name("ERROR boom"), None);
+        assert_eq!(first_line_name("Timeout: gave up"), "Timeout");
+    }
+
+    #[test]
+    fn test_groups_count_and_time_exceptions() {
+        let (_file, storage) = storage_from(&[
+            "2024-01-01 10:00:00 ERROR failed",
+            "java.lang.IllegalStateException: closed",
+            "\tat com.acme.Pool.get(Pool.java:40)",
+            "2024-01-01 10:00:05 INFO ok",
+            r#"{"@t":"2024-01-01T10:01:00Z","@x":"System.TimeoutException: slow\n   at Api.Call()"}"#,
+            "2024-01-01 10:02:00 ERROR failed again",
+            "java.lang.IllegalStateException: closed",
+            "\tat com.acme.Pool.get(Pool.java:40)",
+            "2024-01-01 10:03:00 ERROR other",
+            "java.lang.IllegalStateException: closed",
+            "\tat com.acme.Pool.put(Pool.java:52)",
+        ]);
+        let indices: Vec<usize> = (0..storage.len()).collect();
+
+        let groups = scan(&storage, &indices);
+        let summary: Vec<(&str, Option<&str>, usize)> = groups
+            .iter()
+            .map(|group| (group.name.as_str(), group.frame.as_deref(), group.count()))
+            .collect();
+        assert_eq!(
+            summary,
+            [
+                (
+                    "java.lang.IllegalStateException",
+                    Some("com.acme.Pool.get(Pool.java:40)"),
+                    2
+                ),
+                ("System.TimeoutException", Some("Api.Call()"), 1),
+                (
+                    "java.lang.IllegalStateException",
+                    Some("com.acme.Pool.put(Pool.java:52)"),
+                    1
+                ),
+            ]
+        );
+        assert_eq!(groups[0].lines, [0, 5]);
+        assert_eq!(groups[0].latest(), Some(5));
+        assert_eq!(
+            groups[0].first_seen.unwrap().format("%H:%M").to_string(),
+            "10:00"
+        );
+        assert_eq!(
+            groups[0].last_seen.unwrap().format("%H:%M").to_string(),
+            "10:02"
+        );
+
+        // Only the lines in view count
+        assert_eq!(scan(&storage, &[3, 4]).len(), 1);
+    }
+}
@@ -0,0 +1,652 @@
+//! Export helpers that render parts of the log view as shareable documents.
+
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use chrono::Local;
+use serde_json::Value;
+
+use crate::detail::parse_json;
+use crate::jobs::Job;
+use crate::model::{detect_level, Bookmarks, LogStorage, Pins};
+use crate::source::LineSource;
+
+/// Number of lines shown before and after each bookmarked line in reports.
+pub const BOOKMARK_CONTEXT_LINES: usize = 2;
+
+/// Lines written between progress updates and cancel checks.
+const WRITE_CHUNK_LINES: usize = 10_000;
+
+/// Size of the buffer exported lines are written through.
+const WRITE_BUFFER_BYTES: usize = 8 << 20;
+
+/// Exports taking less than this report no throughput.
+const MIN_RATE_DURATION: Duration = Duration::from_millis(500);
+
+/// Lines and bytes an export wrote, and how long it took.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Written {
+    pub lines: usize,
+    pub bytes: u64,
+    pub elapsed: Duration,
+}
+
+impl Written {
+    /// Status message for an export to `filename`, e.g.
+    /// `Saved 120000 lines (1.2 GB) to out.log in 3.4s, 361.4 MB/s`.
+    pub fn message(&self, filename: &str) -> String {
+        let mut message = format!(
+            "Saved {} lines ({}) to {}",
+            self.lines,
+            format_size(self.bytes),
+            filename
+        );
+        if self.elapsed >= MIN_RATE_DURATION {
+            let rate = self.bytes as f64 / self.elapsed.as_secs_f64();
+            message.push_str(&format!(
+                " in {:.1}s, {}/s",
+                self.elapsed.as_secs_f64(),
+                format_size(rate as u64)
+            ));
+        }
+        message
+    }
+}
+
+/// Size the lines `indices` refer to take once written: their lengths plus
+/// a newline each.
+pub fn estimated_size(storage: &LogStorage, indices: &[usize]) -> u64 {
+    indices
+        .iter()
+        .filter_map(|&idx| storage.get_line_info(idx))
+        .map(|info| info.length as u64 + 1)
+        .sum()
+}
+
+/// Writes exported lines, counting what it wrote.
+///
+/// Lines are written as text with a `\n` each. Verbatim, they are the bytes
+/// stored in their file instead, invalid UTF-8 and `\r\n` included; a line
+/// without a terminator (the end of a file that does not end in one) gets
+/// a `\n` only if another line follows it, so an extract of a whole file is
+/// identical to it. Grouped, a `--` line goes before each line that does
+/// not follow the line written before it, as `grep -C` does.
+pub struct LineWriter<W: Write> {
+    out: W,
+    verbatim: bool,
+    grouped: bool,
+    /// The last line was written without a terminator
+    unterminated: bool,
+    /// Index of the last line written
+    last: Option<usize>,
+    written: Written,
+    started: Instant,
+}
+
+impl<W: Write> LineWriter<W> {
+    pub fn new(out: W, verbatim: bool) -> Self {
+        Self {
+            out,
+            verbatim,
+            grouped: false,
+            unterminated: false,
+            last: None,
+            written: Written::default(),
+            started: Instant::now(),
+        }
+    }
+
+    /// Separate groups of adjacent lines with `--` lines.
+    pub fn grouped(mut self, grouped: bool) -> Self {
+        self.grouped = grouped;
+        self
+    }
+
+    /// Write the lines `indices` refer to.
+    pub fn write(&mut self, source: &dyn LineSource, indices: &[usize]) -> io::Result<()> {
+        for &idx in indices {
+            if self.grouped && self.last.is_some_and(|last| idx != last + 1) {
+                self.write_separator()?;
+            }
+            self.last = Some(idx);
+            if self.unterminated {
+                self.out.write_all(b"\n")?;
+                self.written.bytes += 1;
+                self.unterminated = false;
+            }
+            let raw = self.verbatim.then(|| source.raw_line(idx)).flatten();
+            if let Some(raw) = raw {
+                self.out.write_all(raw)?;
+                self.written.bytes += raw.len() as u64;
+                self.unterminated = !raw.ends_with(b"\n");
+            } else if let Some(line) = source.get_line(idx) {
+                let text = line.as_str_lossy();
+                self.out.write_all(text.as_bytes())?;
+                self.out.write_all(b"\n")?;
+                self.written.bytes += text.len() as u64 + 1;
+            } else {
+                continue;
+            }
+            self.written.lines += 1;
+        }
+        Ok(())
+    }
+
+    /// Write a `--` line between groups of lines.
+    fn write_separator(&mut self) -> io::Result<()> {
+        if self.unterminated {
+            self.out.write_all(b"\n")?;
+            self.written.bytes += 1;
+            self.unterminated = false;
+        }
+        self.out.write_all(b"--\n")?;
+        self.written.bytes += 3;
+        Ok(())
+    }
+
+    /// Flush the output. Returns what was written since the writer was
+    /// created.
+    pub fn finish(mut self) -> io::Result<Written> {
+        self.out.flush()?;
+        self.written.elapsed = self.started.elapsed();
+        Ok(self.written)
+    }
+}
+
+/// Write the lines `indices` refer to into a new file at `path`;
+/// `grouped` separates groups of adjacent lines with `--` lines.
+pub fn write_file(
+    source: &dyn LineSource,
+    indices: &[usize],
+    path: &Path,
+    verbatim: bool,
+    grouped: bool,
+) -> io::Result<Written> {
+    let out = BufWriter::with_capacity(WRITE_BUFFER_BYTES, File::create(path)?);
+    let mut writer = LineWriter::new(out, verbatim).grouped(grouped);
+    writer.write(source, indices)?;
+    writer.finish()
+}
+
+/// Write the lines `indices` refer to into `path` on a worker thread.
+///
+/// The job sends what was written, or the error, when it is done.
+/// A cancelled export removes the partly written file.
+pub fn spawn_write(
+    source: Arc<dyn LineSource + Send + Sync>,
+    indices: Vec<usize>,
+    path: PathBuf,
+    verbatim: bool,
+    grouped: bool,
+) -> Job<io::Result<Written>> {
+    let name = format!("write {}", path.display());
+    Job::spawn(name, indices.len(), move |ctx| {
+        let write = || -> io::Result<Option<Written>> {
+            let out = BufWriter::with_capacity(WRITE_BUFFER_BYTES, File::create(&path)?);
+            let mut writer = LineWriter::new(out, verbatim).grouped(grouped);
+            for chunk in indices.chunks(WRITE_CHUNK_LINES) {
+                if ctx.is_cancelled() {
+                    return Ok(None);
+                }
+                writer.write(&*source, chunk)?;
+                ctx.advance(chunk.len());
+            }
+            writer.finish().map(Some)
+        };
+        match write() {
+            Ok(Some(written)) => {
+                ctx.send(Ok(written));
+            }
+            Ok(None) => {
+                let _ = std::fs::remove_file(&path);
+            }
+            Err(e) => {
+                ctx.send(Err(e));
+            }
+        }
+    })
+}
+
+/// Render bookmarked lines as a markdown report for incident postmortems.
+///
+/// Pinned lines are listed first, in pin order. Each bookmark then becomes
+/// a section with its source file, line number, timestamp, note, and
+/// `context` neighbouring lines from the same file.
+pub fn bookmarks_report(
+    storage: &LogStorage,
+    bookmarks: &Bookmarks,
+    pins: &Pins,
+    context: usize,
+) -> String {
+    let mut out = String::new();
+    out.push_str("# Bookmarks\n\n");
+    out.push_str(&format!(
+        "_{} bookmark(s) · {} pinned · generated {}_\n",
+        bookmarks.len(),
+        pins.len(),
+        Local::now().format("%Y-%m-%d %H:%M:%S")
+    ));
+
+    if !pins.is_empty() {
+        let lines: Vec<(String, String)> = pins
+            .iter()
+            .filter_map(|idx| {
+                let text = storage.get_line(idx)?.as_str_lossy().trim_end().to_string();
+                Some((line_location(storage, idx), text))
+            })
+            .collect();
+        let fence = code_fence(lines.iter().map(|(_, text)| text.as_str()));
+        out.push_str("\n## Pinned\n\n");
+        out.push_str(&format!("{}text\n", fence));
+        for (location, text) in &lines {
+            out.push_str(&format!("{} | {}\n", location, text));
+        }
+        out.push_str(&format!("{}\n", fence));
+    }
+
+    for (n, (idx, mark)) in bookmarks.iter().enumerate() {
+        let Some(info) = storage.get_line_info(idx) else {
+            continue;
+        };
+
+        let location = line_location(storage, idx);
+        out.push_str(&format!("\n## {}. `{}`", n + 1, location));
+        if let Some(ts) = info.timestamp() {
+            out.push_str(&format!(" · {}", ts.format("%Y-%m-%d %H:%M:%S%.3f UTC")));
+        }
+        out.push_str("\n\n");
+
+        if let Some(note) = &mark.note {
+            for note_line in note.lines() {
+                out.push_str(&format!("> {}\n", note_line));
+            }
+            out.push('\n');
+        }
+
+        // Context is limited to lines from the same file so merged views
+        // don't leak unrelated neighbours into the excerpt.
+        let start = idx.saturating_sub(context);
+        let end = (idx + context).min(storage.len().saturating_sub(1));
+        let excerpt: Vec<(usize, String)> = (start..=end)
+            .filter(|&i| {
+                storage
+                    .get_line_info(i)
+                    .is_some_and(|other| other.file_index() == info.file_index())
+            })
+            .filter_map(|i| {
+                let text = storage.get_line(i)?.as_str_lossy().trim_end().to_string();
+                Some((i, text))
+            })
+            .collect();
+
+        let fence = code_fence(excerpt.iter().map(|(_, text)| text.as_str()));
+        // Lines of a merged view are interleaved by time, so each carries
+        // its own number
+        let numbers: Vec<usize> = excerpt
+            .iter()
+            .map(|(i, _)| storage.line_number_in_file(*i).unwrap_or(0))
+            .collect();
+        let width = numbers.iter().max().map_or(1, |n| n.to_string().len());
+
+        out.push_str(&format!("{}text\n", fence));
+        for ((i, text), number) in excerpt.iter().zip(numbers) {
+            let marker = if *i == idx { '>' } else { ' ' };
+            out.push_str(&format!("{} {:>width$} | {}\n", marker, number, text));
+        }
+        out.push_str(&format!("{}\n", fence));
+    }
+
+    out
+}
+
+/// JSON keys holding the time, message and level of structured lines.
+const TIME_KEYS: &[&str] = &["timestamp", "time", "ts", "@timestamp"];
+const MESSAGE_KEYS: &[&str] = &["message", "msg"];
+const LEVEL_KEYS: &[&str] = &["level", "lvl", "severity"];
+/// Keys naming the component that wrote a structured line.
+pub(crate) const SOURCE_KEYS: &[&str] = &[
+    "SourceContext",
+    "source",
+    "logger",
+    "logger_name",
+    "component",
+    "module",
+];
+
+/// Render lines as a GitHub-flavored markdown table.
+///
+/// Columns are the timestamp, level and message, then one column per entry
+/// of `fields`. JSON lines are read by key (`a.b` looks into nested
+/// objects); other lines give their `key=value` pairs as fields and the
+/// whole line as the message.
+pub fn markdown_table(source: &dyn LineSource, indices: &[usize], fields: &[String]) -> String {
+    let mut out = String::new();
+    let header: Vec<&str> = ["Time", "Level", "Message"]
+        .into_iter()
+        .chain(fields.iter().map(String::as_str))
+        .collect();
+    out.push_str(&table_row(header.iter().map(|name| name.to_string())));
+    out.push_str(&table_row(header.iter().map(|_| "---".to_string())));
+
+    for &idx in indices {
+        let Some(record) = LineRecord::read(source, idx) else {
+            continue;
+        };
+        let values = fields.iter().map(|field| match &record.json {
+            Some(value) => json_field(value, field).unwrap_or_default(),
+            None => logfmt_field(&record.text, field).unwrap_or_default(),
+        });
+
+        out.push_str(&table_row(
+            [record.time, record.level, record.message]
+                .into_iter()
+                .chain(values),
+        ));
+    }
+    out
+}
+
+/// Time, level and message of a line, taken from its JSON keys where it
+/// has them.
+pub(crate) struct LineRecord {
+    /// Timestamp as `2024-01-01 10:00:00.000`, or empty
+    pub time: String,
+    /// Level name, or empty
+    pub level: String,
+    pub message: String,
+    /// Line text without trailing whitespace
+    pub text: String,
+    /// Parsed line, for JSON lines
+    pub json: Option<Value>,
+}
+
+impl LineRecord {
+    pub fn read(source: &dyn LineSource, idx: usize) -> Option<Self> {
+        let text = source.get_line(idx)?.as_str_lossy().trim_end().to_string();
+        let json = parse_json(&text).map(|(_, value)| value);
+        let lookup = |keys: &[&str]| {
+            keys.iter()
+                .find_map(|key| json.as_ref().and_then(|value| json_field(value, key)))
+        };
+        let time = source
+            .get_timestamp(idx)
+            .map(|ts| ts.format("%Y-%m-%d %H:%M:%S%.3f").to_string())
+            .or_else(|| lookup(TIME_KEYS))
+            .unwrap_or_default();
+        let level = lookup(LEVEL_KEYS)
+            .or_else(|| detect_level(&text).map(|level| level.name().to_string()))
+            .unwrap_or_default();
+        let message = lookup(MESSAGE_KEYS).unwrap_or_else(|| text.clone());
+        Some(Self {
+            time,
+            level,
+            message,
+            text,
+            json,
+        })
+    }
+
+    /// Fields of the line: top-level JSON keys, or the `key=value` pairs of
+    /// a plain line.
+    pub fn fields(&self) -> Vec<(String, Value)> {
+        match &self.json {
+            Some(Value::Object(map)) => map
+                .iter()
+                .map(|(key, value)| (key.clone(), value.clone()))
+                .collect(),
+            Some(_) => Vec::new(),
+            None => self
+                .text
+                .split_whitespace()
+                .filter_map(|token| {
+                    let (key, value) = token.split_once('=')?;
+                    (!key.is_empty())
+                        .then(|| (key.to_string(), Value::from(value.trim_matches('"'))))
+                })
+                .collect(),
+        }
+    }
+}
+
+/// Value at a dotted key path, with strings unquoted.
+pub(crate) fn json_field(value: &Value, key: &str) -> Option<String> {
+    let field = key
+        .split('.')
+        .try_fold(value, |value, part| value.get(part))?;
+    Some(match field {
+        Value::String(s) => s.clone(),
+        other => other.to_string(),
+    })
+}
+
+/// Value of a `key=value` pair in a plain line, with quotes removed; a
+/// quoted value (`msg="GET /orders"`) may contain spaces.
+pub(crate) fn logfmt_field(line: &str, key: &str) -> Option<String> {
+    line.match_indices(key).find_map(|(start, _)| {
+        if line[..start]
+            .chars()
+            .next_back()
+            .is_some_and(|c| !c.is_whitespace())
+        {
+            return None;
+        }
+        let value = line[start + key.len()..].strip_prefix('=')?;
+        let value = match value.strip_prefix('"') {
+            Some(quoted) => quoted.split('"').next().unwrap_or_default(),
+            None => value.split_whitespace().next().unwrap_or_default(),
+        };
+        Some(value.to_string())
+    })
+}
+
+/// One table row; pipes are escaped and line breaks flattened.
+fn table_row(cells: impl IntoIterator<Item = String>) -> String {
+    let cells: Vec<String> = cells
+        .into_iter()
+        .map(|cell| cell.replace('|', "\\|").replace(['\n', '\r'], " "))
+        .collect();
+    format!("| {} |\n", cells.join(" | "))
+}
+
+/// Format a line as `path:line` using its source file and per-file line number.
+fn line_location(storage: &LogStorage, idx: usize) -> String {
+    let path = storage
+        .line_path(idx)
+        .map(|p| p.display().to_string())
+        .unwrap_or_else(|| "<unknown>".to_string());
+    let number = storage.line_number_in_file(idx).unwrap_or(idx + 1);
+    format!("{}:{}", path, number)
+}
+
+/// Format a byte count with a binary unit, e.g. `40.0 GB`.
+pub fn format_size(bytes: u64) -> String {
+    const UNITS: [&str; 5] = ["B", "KB", "MB", "GB", "TB"];
+    let mut value = bytes as f64;
+    let mut unit = 0;
+    while value >= 1024.0 && unit < UNITS.len() - 1 {
+        value /= 1024.0;
+        unit += 1;
+    }
+    if unit == 0 {
+        format!("{} {}", bytes, UNITS[0])
+    } else {
+        format!("{:.1} {}", value, UNITS[unit])
+    }
+}
+
+/// Pick a code fence that is longer than any backtick run in the content.
+fn code_fence<'a>(lines: impl Iterator<Item = &'a str>) -> String {
+    let longest_run = lines
+        .map(|line| {
+            line.split(|c| c != '`')
+                .map(|run| run.len())
+                .max()
+                .unwrap_or(0)
+        })
+        .max()
+        .unwrap_or(0);
+    "`".repeat(longest_run.max(2) + 1)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::log_storage::storage_from;
+    use std::io::Write;
+    use tempfile::NamedTempFile;
+
+    #[test]
+    fn test_spawn_write_writes_lines() {
+        let (_file, storage) = storage_from(&["one", "two", "three"]);
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("out.log");
+        let mut job = spawn_write(Arc::new(storage), vec![0, 2], path.clone(), false, false);
+        assert_eq!(job.name(), format!("write {}", path.display()));
+
+        let mut results = Vec::new();
+        while !job.is_finished() {
+            results.extend(job.poll());
+            std::thread::sleep(std::time::Duration::from_millis(1));
+        }
+        assert_eq!(results.len(), 1);
+        let written = results[0].as_ref().unwrap();
+        assert_eq!((written.lines, written.bytes), (2, 10));
+        assert_eq!(std::fs::read_to_string(path).unwrap(), "one\nthree\n");
+        assert_eq!(
+            estimated_size(&storage_from(&["one", "three"]).1, &[0, 1]),
+            10
+        );
+    }
+
+    #[test]
+    fn test_verbatim_export_keeps_bytes_and_line_endings() {
+        let mut file = NamedTempFile::new().unwrap();
+        file.write_all(b"one\r\nbad \xff\nlast").unwrap();
+        let storage = LogStorage::from_file(file.path()).unwrap();
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("out.log");
+
+        let written = write_file(&storage, &[0, 1, 2], &path, true, false).unwrap();
+        assert_eq!(std::fs::read(&path).unwrap(), b"one\r\nbad \xff\nlast");
+        assert_eq!((written.lines, written.bytes), (3, 15));
+
+        write_file(&storage, &[2, 0], &path, true, false).unwrap();
+        assert_eq!(std::fs::read(&path).unwrap(), b"last\none\r\n");
+
+        write_file(&storage, &[1, 2], &path, false, false).unwrap();
+        assert_eq!(
+            std::fs::read(&path).unwrap(),
+            "bad \u{fffd}\nlast\n".as_bytes()
+        );
+
+        // Grouped, the unterminated last line ends before the separator
+        write_file(&storage, &[0, 2, 1], &path, true, true).unwrap();
+        assert_eq!(
+            std::fs::read(&path).unwrap(),
+            b"one\r\n--\nlast\n--\nbad \xff\n"
+        );
+    }
+
+    #[test]
+    fn test_written_message() {
+        let mut written = Written {
+            lines: 3,
+            bytes: 3 << 30,
+            elapsed: Duration::from_millis(100),
+        };
+        assert_eq!(
+            written.message("out.log"),
+            "Saved 3 lines (3.0 GB) to out.log"
+        );
+        written.elapsed = Duration::from_secs(4);
+        assert_eq!(
+            written.message("out.log"),
+            "Saved 3 lines (3.0 GB) to out.log in 4.0s, 768.0 MB/s"
+        );
+    }
+
+    #[test]
+    fn test_report_contains_location_note_and_context() {
+        let (file, storage) = storage_from(&[
+            "2026-02-13 10:30:40 start",
+            "2026-02-13 10:30:41 connecting",
+            "2026-02-13 10:30:45 ERROR timeout",
+            "2026-02-13 10:30:46 retrying",
+            "2026-02-13 10:30:47 done",
+        ]);
+        let mut marks = Bookmarks::new();
+        marks.set_note(2, "gateway timed out");
+
+        let report = bookmarks_report(&storage, &marks, &Pins::new(), 1);
+
+        assert!(report.starts_with("# Bookmarks"));
+        assert!(report.contains(&format!("`{}:3`", file.path().display())));
+        assert!(report.contains("2026-02-13 10:30:45.000 UTC"));
+        assert!(report.contains("> gateway timed out"));
+        assert!(report.contains("  2 | 2026-02-13 10:30:41 connecting"));
+        assert!(report.contains("> 3 | 2026-02-13 10:30:45 ERROR timeout"));
+        assert!(report.contains("  4 | 2026-02-13 10:30:46 retrying"));
+        assert!(!report.contains("start"));
+    }
+
+    #[test]
+    fn test_report_context_clamped_at_file_start() {
+        let (_file, storage) = storage_from(&["first", "second"]);
+        let mut marks = Bookmarks::new();
+        marks.toggle(0);
+
+        let report = bookmarks_report(&storage, &marks, &Pins::new(), 3);
+
+        assert!(report.contains("> 1 | first"));
+        assert!(report.contains("  2 | second"));
+    }
+
+    #[test]
+    fn test_report_lists_pins_in_order() {
+        let (file, storage) = storage_from(&["config loaded", "noise", "ERROR boom"]);
+        let mut pins = Pins::new();
+        pins.add(2);
+        pins.add(0);
+
+        let report = bookmarks_report(&storage, &Bookmarks::new(), &pins, 1);
+
+        let path = file.path().display();
+        let error = report.find(&format!("{}:3 | ERROR boom", path)).unwrap();
+        let config = report.find(&format!("{}:1 | config loaded", path)).unwrap();
+        assert!(report.contains("## Pinned"));
+        assert!(error < config);
+        assert!(!report.contains("noise"));
+    }
+
+    #[test]
+    fn test_markdown_table_reads_json_and_logfmt() {
+        let (_file, storage) = storage_from(&[
+            r#"{"time":"2026-02-13T10:30:45Z","level":"warn","msg":"slow | query","req":{"id":"a1"}}"#,
+            "2026-02-13 10:30:46 ERROR request failed req.id=b2 status=500",
+        ]);
+        let fields = vec!["req.id".to_string(), "status".to_string()];
+
+        let table = markdown_table(&storage, &[0, 1], &fields);
+        let rows: Vec<&str> = table.lines().collect();
+        assert_eq!(rows[0], "| Time | Level | Message | req.id | status |");
+        assert_eq!(rows[1], "| --- | --- | --- | --- | --- |");
+        assert_eq!(
+            rows[2],
+            "| 2026-02-13T10:30:45Z | warn | slow \\| query | a1 |  |"
+        );
+        assert_eq!(
+            rows[3],
+            "| 2026-02-13 10:30:46.000 | error | 2026-02-13 10:30:46 ERROR request failed \
+             req.id=b2 status=500 | b2 | 500 |"
+        );
+    }
+
+    #[test]
+    fn test_code_fence_escapes_backticks() {
+        assert_eq!(code_fence(["plain"].into_iter()), "```");
+        assert_eq!(code_fence(["has ``` fence"].into_iter()), "````");
+    }
+}
@@ -0,0 +1,873 @@
+//! Following files for newly written lines.
+//!
+//! Each followed file keeps its own read offset. Lines written to several
+//! files between two polls are interleaved by timestamp, so `app.log` and
+//! `worker.log` read as one timeline; lines without a timestamp stay with
+//! the entry they continue, and entries without any timestamp keep their
+//! arrival order. Ties are broken by file, then by position in the file, so
+//! the merged order never depends on timing. With `inherit_timestamps`,
+//! lines without a timestamp take the last one seen in their file, even
+//! across polls.
+//!
+//! Rotation is detected per file: a file that shrank was truncated (or
+//! copied and truncated, when a rotated copy sits next to it), and a file
+//! whose name now points at a different inode was renamed and recreated.
+//! Either way following continues from the start of the new file. A file
+//! truncated and refilled past the old read offset between two polls looks
+//! like one that grew, so polls need to be frequent.
+//!
+//! A [`Follower`] that [watches](Follower::watch) its files only reads them
+//! when their directories report a change, so following many quiet files
+//! costs nothing between writes; it still polls every
+//! `WATCH_FALLBACK_INTERVAL` in case a change went unreported (network file
+//! systems, for one, send no notifications).
+//!
+//! With `--follow-latest "app-*.log"` a [`LatestFile`] picks the newest file
+//! matching the pattern and notices when a newer one appears (daily
+//! rotation into dated files), so the viewer can switch to it.
+
+use std::collections::BTreeSet;
+use std::fs::{File, Metadata};
+use std::io::{Read, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{channel, Receiver};
+use std::time::{Duration, Instant};
+
+use chrono::{DateTime, Utc};
+use notify::{EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+
+use crate::config::FollowConfig;
+use crate::glob::glob_match;
+use crate::model::{detect_timestamp, LogStorage};
+
+/// Longest a watched follower goes without polling when no change is reported.
+const WATCH_FALLBACK_INTERVAL: Duration = Duration::from_secs(1);
+
+/// How often the directory of a `--follow-latest` pattern is listed.
+const LATEST_CHECK_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Extensions of compressed rotated files, which are never read back.
+const COMPRESSED_EXTENSIONS: [&str; 5] = ["gz", "bz2", "xz", "zst", "zip"];
+
+/// A complete line read from a followed file.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FollowedLine {
+    /// Storage file index of the file the line was written to
+    pub file_index: usize,
+    pub text: Vec<u8>,
+    pub timestamp: Option<DateTime<Utc>>,
+}
+
+/// How a followed file was rotated.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Rotation {
+    /// Emptied in place and written again from the start
+    Truncated,
+    /// Renamed away, with a new file created under the old name
+    Recreated,
+    /// Copied to a rotated name, then emptied in place
+    CopyTruncated,
+}
+
+impl Rotation {
+    pub fn describe(self) -> &'static str {
+        match self {
+            Rotation::Truncated => "truncated",
+            Rotation::Recreated => "renamed and recreated",
+            Rotation::CopyTruncated => "copied and truncated",
+        }
+    }
+}
+
+/// Result of one poll over all followed files.
+#[derive(Debug, Default)]
+pub struct FollowUpdate {
+    /// New lines from every file, merged into one timeline
+    pub lines: Vec<FollowedLine>,
+    /// Files that were rotated since the last poll
+    pub rotations: Vec<(PathBuf, Rotation)>,
+}
+
+/// Identity of a file on disk, used to notice a file replaced under the same name.
+#[cfg(unix)]
+fn file_id(metadata: &Metadata) -> Option<(u64, u64)> {
+    use std::os::unix::fs::MetadataExt;
+    Some((metadata.dev(), metadata.ino()))
+}
+
+#[cfg(not(unix))]
+fn file_id(_metadata: &Metadata) -> Option<(u64, u64)> {
+    None
+}
+
+/// Read state of one followed file.
+#[derive(Debug)]
+struct FollowSource {
+    path: PathBuf,
+    file_index: usize,
+    /// Open handle; keeps reading the same file after it is renamed away
+    file: Option<File>,
+    /// Identity of the open file
+    id: Option<(u64, u64)>,
+    /// Bytes of the file consumed so far
+    offset: u64,
+    /// Trailing bytes of a line that is still being written
+    partial: Vec<u8>,
+    /// Give lines without a timestamp the last timestamp read
+    inherit_timestamps: bool,
+    /// Timestamp of the last timestamped line read
+    last_timestamp: Option<DateTime<Utc>>,
+}
+
+impl FollowSource {
+    fn new(path: PathBuf, file_index: usize, offset: u64, inherit_timestamps: bool) -> Self {
+        let mut source = Self {
+            path,
+            file_index,
+            file: None,
+            id: None,
+            offset: 0,
+            partial: Vec::new(),
+            inherit_timestamps,
+            last_timestamp: None,
+        };
+        source.reopen();
+        if source.file.is_some() {
+            source.offset = offset;
+        }
+        source
+    }
+
+    /// Open the file currently at `path` and read it from the start.
+    fn reopen(&mut self) {
+        self.file = File::open(&self.path).ok();
+        self.id = self
+            .file
+            .as_ref()
+            .and_then(|file| file.metadata().ok())
+            .as_ref()
+            .and_then(file_id);
+        self.offset = 0;
+        self.partial.clear();
+    }
+
+    /// Check whether `path` now names a different file than the open one.
+    fn was_replaced(&self) -> bool {
+        let Some(id) = self.id else {
+            return false;
+        };
+        std::fs::metadata(&self.path)
+            .ok()
+            .as_ref()
+            .and_then(file_id)
+            .is_some_and(|current| current != id)
+    }
+
+    /// Read complete lines written since the last poll, following rotation.
+    ///
+    /// With `load_rotated`, lines written to the old file just before it was
+    /// rotated are read from the rotated-away file first.
+    fn poll(&mut self, load_rotated: bool) -> (Vec<FollowedLine>, Option<Rotation>) {
+        if self.file.is_none() {
+            self.reopen();
+        }
+        let Some(len) = self
+            .file
+            .as_ref()
+            .and_then(|file| file.metadata().ok())
+            .map(|m| m.len())
+        else {
+            return (Vec::new(), None);
+        };
+
+        if self.was_replaced() {
+            let mut lines = Vec::new();
+            if load_rotated {
+                // The open handle still reads the renamed file
+                let rest = self
+                    .file
+                    .as_mut()
+                    .map(|file| read_from(file, self.offset))
+                    .unwrap_or_default();
+                lines = self.split_lines(&rest, true);
+            }
+            self.reopen();
+            lines.extend(self.read_appended());
+            return (lines, Some(Rotation::Recreated));
+        }
+
+        if len < self.offset {
+            let copy = find_rotated_copy(&self.path, self.offset);
+            let mut lines = Vec::new();
+            if let (true, Some(copy)) = (load_rotated, &copy) {
+                let rest = File::open(copy)
+                    .map(|mut file| read_from(&mut file, self.offset))
+                    .unwrap_or_default();
+                lines = self.split_lines(&rest, true);
+            }
+            self.offset = 0;
+            self.partial.clear();
+            lines.extend(self.read_appended());
+            let rotation = match copy {
+                Some(_) => Rotation::CopyTruncated,
+                None => Rotation::Truncated,
+            };
+            return (lines, Some(rotation));
+        }
+
+        (self.read_appended(), None)
+    }
+
+    /// Read complete lines appended to the open file since the last read.
+    fn read_appended(&mut self) -> Vec<FollowedLine> {
+        let Some(file) = &mut self.file else {
+            return Vec::new();
+        };
+        let data = read_from(file, self.offset);
+        self.offset += data.len() as u64;
+        self.split_lines(&data, false)
+    }
+
+    /// Split `data` into lines after any partial line kept from the last read.
+    ///
+    /// An unterminated last line is kept for the next read unless the file
+    /// is `finished`.
+    fn split_lines(&mut self, data: &[u8], finished: bool) -> Vec<FollowedLine> {
+        let mut buf = std::mem::take(&mut self.partial);
+        buf.extend_from_slice(data);
+        if !finished {
+            let complete = memchr::memrchr(b'\n', &buf).map_or(0, |pos| pos + 1);
+            self.partial = buf.split_off(complete);
+        }
+        let text = buf.strip_suffix(b"\n").unwrap_or(&buf);
+        if text.is_empty() {
+            return Vec::new();
+        }
+
+        text.split(|&b| b == b'\n')
+            .map(|line| {
+                let line = line.strip_suffix(b"\r").unwrap_or(line);
+                let mut timestamp = detect_timestamp(&String::from_utf8_lossy(line));
+                if timestamp.is_some() {
+                    self.last_timestamp = timestamp;
+                } else if self.inherit_timestamps {
+                    timestamp = self.last_timestamp;
+                }
+                FollowedLine {
+                    file_index: self.file_index,
+                    text: line.to_vec(),
+                    timestamp,
+                }
+            })
+            .collect()
+    }
+}
+
+/// Read everything from `offset` to the current end of `file`.
+fn read_from(file: &mut File, offset: u64) -> Vec<u8> {
+    let mut data = Vec::new();
+    if file.seek(SeekFrom::Start(offset)).is_ok() {
+        let _ = file.read_to_end(&mut data);
+    }
+    data
+}
+
+/// Find the copy a copytruncate rotation left next to `path`.
+///
+/// Rotated copies are named after the file with a suffix (`app.log.1`,
+/// `app.log-20240101`); the newest uncompressed one at least `min_len`
+/// bytes long is taken.
+fn find_rotated_copy(path: &Path, min_len: u64) -> Option<PathBuf> {
+    let name = path.file_name()?.to_str()?;
+    let dir = match path.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => parent,
+        _ => Path::new("."),
+    };
+
+    std::fs::read_dir(dir)
+        .ok()?
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| {
+            let file_name = entry.file_name();
+            let file_name = file_name.to_string_lossy();
+            let rotated_name = file_name
+                .strip_prefix(name)
+                .is_some_and(|suffix| suffix.starts_with(['.', '-']) && suffix.len() > 1);
+            let compressed = Path::new(file_name.as_ref())
+                .extension()
+                .and_then(|ext| ext.to_str())
+                .is_some_and(|ext| COMPRESSED_EXTENSIONS.contains(&ext));
+            rotated_name && !compressed
+        })
+        .filter_map(|entry| {
+            let metadata = entry.metadata().ok()?;
+            (metadata.is_file() && metadata.len() >= min_len)
+                .then_some((metadata.modified().ok()?, entry.path()))
+        })
+        .max_by_key(|(modified, _)| *modified)
+        .map(|(_, path)| path)
+}
+
+/// Change notifications for the directories of followed files.
+#[derive(Debug)]
+struct FileWatch {
+    /// Kept alive for as long as notifications are wanted
+    _watcher: RecommendedWatcher,
+    rx: Receiver<notify::Result<notify::Event>>,
+    last_poll: Option<Instant>,
+}
+
+impl FileWatch {
+    /// Whether the files should be polled now: something in their
+    /// directories changed, or the fallback interval is up. Reads (our own
+    /// included) do not count as changes.
+    fn due(&mut self) -> bool {
+        // Drain every event, not just up to the first change
+        let changes = self
+            .rx
+            .try_iter()
+            .filter(|event| {
+                event
+                    .as_ref()
+                    .map_or(true, |event| !matches!(event.kind, EventKind::Access(_)))
+            })
+            .count();
+        let changed = changes > 0;
+        let due = changed
+            || self
+                .last_poll
+                .is_none_or(|last| last.elapsed() >= WATCH_FALLBACK_INTERVAL);
+        if due {
+            self.last_poll = Some(Instant::now());
+        }
+        due
+    }
+}
+
+/// Follows every file of a storage, starting at the end of what was loaded.
+#[derive(Debug)]
+pub struct Follower {
+    sources: Vec<FollowSource>,
+    load_rotated: bool,
+    /// Change notifications, if watching; without them every poll reads
+    watch: Option<FileWatch>,
+}
+
+impl Follower {
+    /// Follow the files of `storage`. Compressed files are not followed:
+    /// they are rotated logs and their size says nothing about the text.
+    pub fn new(storage: &LogStorage, config: &FollowConfig) -> Self {
+        let sources = (0..storage.file_count())
+            .filter(|&file_index| storage.compression(file_index).is_none())
+            .filter_map(|file_index| {
+                Some(FollowSource::new(
+                    storage.file_path(file_index)?.to_path_buf(),
+                    file_index,
+                    storage.mapped_len(file_index)?,
+                    config.inherit_timestamps,
+                ))
+            })
+            .collect();
+        Self {
+            sources,
+            load_rotated: config.load_rotated,
+            watch: None,
+        }
+    }
+
+    /// Read the files only when a change is reported (or after
+    /// `WATCH_FALLBACK_INTERVAL`) instead of on every poll.
+    ///
+    /// The directories are watched rather than the files, so a file renamed
+    /// away and recreated is still noticed. Where change notifications are
+    /// not available every poll keeps reading.
+    pub fn watch(&mut self) {
+        let (tx, rx) = channel();
+        let Ok(mut watcher) = notify::recommended_watcher(tx) else {
+            return;
+        };
+        let dirs: BTreeSet<PathBuf> = self
+            .sources
+            .iter()
+            .map(|source| match source.path.parent() {
+                Some(dir) if !dir.as_os_str().is_empty() => dir.to_path_buf(),
+                _ => PathBuf::from("."),
+            })
+            .collect();
+        for dir in dirs {
+            if watcher.watch(&dir, RecursiveMode::NonRecursive).is_err() {
+                return;
+            }
+        }
+        self.watch = Some(FileWatch {
+            _watcher: watcher,
+            rx,
+            last_poll: None,
+        });
+    }
+
+    /// Check whether polls wait for change notifications.
+    pub fn is_watching(&self) -> bool {
+        self.watch.is_some()
+    }
+
+    /// Number of files being followed.
+    pub fn source_count(&self) -> usize {
+        self.sources.len()
+    }
+
+    /// Read new lines from every file, merged into one timeline.
+    ///
+    /// Each file is checked for rotation on its own, so one rotating file
+    /// does not affect the others.
+    pub fn poll(&mut self) -> FollowUpdate {
+        if self.watch.as_mut().is_some_and(|watch| !watch.due()) {
+            return FollowUpdate::default();
+        }
+        let mut rotations = Vec::new();
+        let batches = self
+            .sources
+            .iter_mut()
+            .map(|source| {
+                let (lines, rotation) = source.poll(self.load_rotated);
+                if let Some(rotation) = rotation {
+                    rotations.push((source.path.clone(), rotation));
+                }
+                lines
+            })
+            .collect();
+        FollowUpdate {
+            lines: merge_by_timestamp(batches),
+            rotations,
+        }
+    }
+}
+
+/// The newest file matching a `--follow-latest` pattern.
+///
+/// Files are compared by modification time (then by name, so dated names
+/// break ties). A file that was left for a newer one is never switched back
+/// to, even if a late write makes it the most recently modified again.
+#[derive(Debug, Clone)]
+pub struct LatestFile {
+    /// The pattern as given, e.g. `logs/app-*.log`
+    spec: String,
+    /// Directory listed for matches
+    dir: PathBuf,
+    /// File name pattern with `*` and `?`
+    pattern: String,
+    current: PathBuf,
+    /// Files followed before the current one
+    previous: Vec<PathBuf>,
+    last_check: Instant,
+    /// Set on a switch until the new file has been loaded
+    switched: bool,
+}
+
+impl LatestFile {
+    /// Find the newest file matching `spec`. Wildcards are only allowed in
+    /// the file name; an error says why nothing can be followed.
+    pub fn new(spec: &str) -> Result<Self, String> {
+        let path = Path::new(spec);
+        let pattern = path
+            .file_name()
+            .and_then(|name| name.to_str())
+            .ok_or_else(|| format!("--follow-latest needs a file name pattern, got '{}'", spec))?;
+        let dir = match path.parent() {
+            Some(parent) if !parent.as_os_str().is_empty() => parent.to_path_buf(),
+            _ => PathBuf::from("."),
+        };
+        if dir.to_string_lossy().contains(['*', '?']) {
+            return Err(format!(
+                "--follow-latest only supports wildcards in the file name, got '{}'",
+                spec
+            ));
+        }
+        let current = newest_match(&dir, pattern, &[])
+            .ok_or_else(|| format!("no file matches '{}'", spec))?;
+        Ok(Self {
+            spec: spec.to_string(),
+            dir,
+            pattern: pattern.to_string(),
+            current,
+            previous: Vec::new(),
+            last_check: Instant::now(),
+            switched: false,
+        })
+    }
+
+    /// The pattern as given.
+    pub fn spec(&self) -> &str {
+        &self.spec
+    }
+
+    /// The file currently followed.
+    pub fn current(&self) -> &Path {
+        &self.current
+    }
+
+    /// Files followed before the current one, oldest first.
+    pub fn previous(&self) -> &[PathBuf] {
+        &self.previous
+    }
+
+    /// Look for a newer matching file, at most every
+    /// `LATEST_CHECK_INTERVAL`. Returns the file to switch to, which
+    /// becomes the current one.
+    pub fn check(&mut self, now: Instant) -> Option<PathBuf> {
+        if now.duration_since(self.last_check) < LATEST_CHECK_INTERVAL {
+            return None;
+        }
+        self.last_check = now;
+        let newest = newest_match(&self.dir, &self.pattern, &self.previous)?;
+        if newest == self.current {
+            return None;
+        }
+        self.previous
+            .push(std::mem::replace(&mut self.current, newest.clone()));
+        self.switched = true;
+        Some(newest)
+    }
+
+    /// Whether a switch happened since the last call.
+    pub fn take_switched(&mut self) -> bool {
+        std::mem::take(&mut self.switched)
+    }
+}
+
+/// Most recently modified file in `dir` whose name matches `pattern`,
+/// leaving out `skip`.
+fn newest_match(dir: &Path, pattern: &str, skip: &[PathBuf]) -> Option<PathBuf> {
+    std::fs::read_dir(dir)
+        .ok()?
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| glob_match(&entry.file_name().to_string_lossy(), pattern))
+        .filter_map(|entry| {
+            let metadata = entry.metadata().ok()?;
+            let path = entry.path();
+            (metadata.is_file() && !skip.contains(&path))
+                .then_some((metadata.modified().ok()?, path))
+        })
+        .max()
+        .map(|(_, path)| path)
+}
+
+/// Interleave per-file batches of lines by timestamp.
+///
+/// Each batch is split into entries: a timestamped line plus the lines
+/// without a timestamp that follow it. Entries are then merged by
+/// (timestamp, batch, position in batch), which is a total order: equal
+/// timestamps go to the earlier batch and each batch keeps its own order.
+/// Leading lines without a timestamp continue an entry from an earlier poll,
+/// so they go first.
+pub fn merge_by_timestamp(batches: Vec<Vec<FollowedLine>>) -> Vec<FollowedLine> {
+    let mut queues: Vec<std::vec::IntoIter<Vec<FollowedLine>>> = batches
+        .into_iter()
+        .map(|batch| group_entries(batch).into_iter())
+        .collect();
+    let mut heads: Vec<Option<Vec<FollowedLine>>> =
+        queues.iter_mut().map(|queue| queue.next()).collect();
+
+    let mut merged = Vec::new();
+    loop {
+        let next = heads
+            .iter()
+            .enumerate()
+            .filter_map(|(source, head)| Some((source, head.as_ref()?[0].timestamp)))
+            // `None` sorts before any timestamp, so untimestamped entries go first
+            .min_by_key(|&(source, timestamp)| (timestamp, source));
+        let Some((source, _)) = next else {
+            break;
+        };
+        merged.extend(heads[source].take().unwrap_or_default());
+        heads[source] = queues[source].next();
+    }
+    merged
+}
+
+/// Split a batch into entries that each start at a timestamped line.
+fn group_entries(batch: Vec<FollowedLine>) -> Vec<Vec<FollowedLine>> {
+    let mut entries: Vec<Vec<FollowedLine>> = Vec::new();
+    for line in batch {
+        match entries.last_mut() {
+            Some(entry) if line.timestamp.is_none() => entry.push(line),
+            _ => entries.push(vec![line]),
+        }
+    }
+    entries
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+    use tempfile::NamedTempFile;
+
+    fn line(file_index: usize, text: &str) -> FollowedLine {
+        FollowedLine {
+            file_index,
+            text: text.as_bytes().to_vec(),
+            timestamp: detect_timestamp(text),
+        }
+    }
+
+    fn texts(lines: &[FollowedLine]) -> Vec<String> {
+        lines
+            .iter()
+            .map(|l| String::from_utf8_lossy(&l.text).into_owned())
+            .collect()
+    }
+
+    #[test]
+    fn test_merge_interleaves_by_timestamp() {
+        let app = vec![
+            line(0, "2024-01-01 10:00:01 app one"),
+            line(0, "    at app.Main"),
+            line(0, "2024-01-01 10:00:03 app two"),
+        ];
+        let worker = vec![
+            line(1, "continued"),
+            line(1, "2024-01-01 10:00:02 worker one"),
+            line(1, "2024-01-01 10:00:03 worker two"),
+        ];
+
+        let merged = merge_by_timestamp(vec![app, worker]);
+
+        assert_eq!(
+            texts(&merged),
+            vec![
+                "continued",
+                "2024-01-01 10:00:01 app one",
+                "    at app.Main",
+                "2024-01-01 10:00:02 worker one",
+                "2024-01-01 10:00:03 app two",
+                "2024-01-01 10:00:03 worker two",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_merge_order_is_deterministic() {
+        let a = vec![
+            line(0, "2024-01-01 10:00:01 a one"),
+            line(0, "2024-01-01 10:00:01 a two"),
+        ];
+        let b = vec![line(1, "b continued"), line(1, "2024-01-01 10:00:01 b one")];
+        let c = vec![line(2, "c continued")];
+
+        let expected = vec![
+            "b continued",
+            "c continued",
+            "2024-01-01 10:00:01 a one",
+            "2024-01-01 10:00:01 a two",
+            "2024-01-01 10:00:01 b one",
+        ];
+        for _ in 0..3 {
+            let merged = merge_by_timestamp(vec![a.clone(), b.clone(), c.clone()]);
+            assert_eq!(texts(&merged), expected);
+        }
+    }
+
+    #[test]
+    fn test_inherit_timestamps_across_polls() {
+        let dir = tempfile::tempdir().unwrap();
+        let app = dir.path().join("app.log");
+        let worker = dir.path().join("worker.log");
+        File::create(&app).unwrap();
+        File::create(&worker).unwrap();
+        let storage = LogStorage::merge(vec![
+            LogStorage::from_file(&app).unwrap(),
+            LogStorage::from_file(&worker).unwrap(),
+        ]);
+        let config = FollowConfig {
+            inherit_timestamps: true,
+            ..FollowConfig::default()
+        };
+        let mut follower = Follower::new(&storage, &config);
+
+        append(&app, "2024-01-01 10:00:05 app failed\n");
+        append(&worker, "2024-01-01 10:00:01 worker started\n");
+        follower.poll();
+
+        // The trace continues the app entry, so it goes after the worker's line
+        append(&app, "    at app.Main\n");
+        append(&worker, "2024-01-01 10:00:02 worker busy\n");
+        let lines = follower.poll().lines;
+        assert_eq!(
+            texts(&lines),
+            vec!["2024-01-01 10:00:02 worker busy", "    at app.Main"]
+        );
+        assert_eq!(lines[1].timestamp, detect_timestamp("2024-01-01 10:00:05"));
+    }
+
+    #[test]
+    fn test_poll_reads_only_complete_new_lines() {
+        let mut file = NamedTempFile::new().unwrap();
+        writeln!(file, "2024-01-01 10:00:00 loaded").unwrap();
+        let storage = LogStorage::from_file(file.path()).unwrap();
+        let mut follower = Follower::new(&storage, &FollowConfig::default());
+        assert_eq!(follower.source_count(), 1);
+        assert!(follower.poll().lines.is_empty());
+
+        write!(file, "2024-01-01 10:00:01 new\r\n2024-01-01 10:00").unwrap();
+        file.flush().unwrap();
+        assert_eq!(
+            texts(&follower.poll().lines),
+            vec!["2024-01-01 10:00:01 new"]
+        );
+
+        writeln!(file, ":02 rest").unwrap();
+        file.flush().unwrap();
+        let lines = follower.poll().lines;
+        assert_eq!(texts(&lines), vec!["2024-01-01 10:00:02 rest"]);
+        assert!(lines[0].timestamp.is_some());
+    }
+
+    #[test]
+    fn test_watched_follower_reads_after_change() {
+        let dir = tempfile::tempdir().unwrap();
+        let (path, mut follower) = follow_file(dir.path(), &["2024-01-01 10:00:00 loaded"]);
+        follower.watch();
+        assert!(follower.is_watching());
+        // The first poll always reads
+        assert!(follower.poll().lines.is_empty());
+
+        append(&path, "2024-01-01 10:00:01 new\n");
+        let started = Instant::now();
+        let mut lines = Vec::new();
+        while lines.is_empty() && started.elapsed() < Duration::from_secs(5) {
+            lines = follower.poll().lines;
+            std::thread::sleep(Duration::from_millis(10));
+        }
+        assert_eq!(texts(&lines), vec!["2024-01-01 10:00:01 new"]);
+    }
+
+    fn follow_file(dir: &Path, lines: &[&str]) -> (PathBuf, Follower) {
+        let path = dir.join("app.log");
+        let mut file = File::create(&path).unwrap();
+        for line in lines {
+            writeln!(file, "{}", line).unwrap();
+        }
+        let storage = LogStorage::from_file(&path).unwrap();
+        let follower = Follower::new(&storage, &FollowConfig::default());
+        (path, follower)
+    }
+
+    fn append(path: &Path, text: &str) {
+        let mut file = std::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)
+            .unwrap();
+        write!(file, "{}", text).unwrap();
+    }
+
+    #[test]
+    fn test_poll_restarts_after_truncation() {
+        let dir = tempfile::tempdir().unwrap();
+        let (path, mut follower) = follow_file(dir.path(), &["old line one", "old line two"]);
+
+        File::create(&path).unwrap();
+        append(&path, "fresh\n");
+
+        let update = follower.poll();
+        assert_eq!(texts(&update.lines), vec!["fresh"]);
+        assert_eq!(update.rotations, vec![(path, Rotation::Truncated)]);
+    }
+
+    #[test]
+    fn test_copytruncate_reads_missed_lines_from_copy() {
+        let dir = tempfile::tempdir().unwrap();
+        let (path, mut follower) =
+            follow_file(dir.path(), &["first line, longer than what follows"]);
+
+        append(&path, "two\n");
+        std::fs::copy(&path, dir.path().join("app.log.1")).unwrap();
+        File::create(&path).unwrap();
+        append(&path, "three\n");
+
+        let update = follower.poll();
+        assert_eq!(texts(&update.lines), vec!["two", "three"]);
+        assert_eq!(update.rotations[0].1, Rotation::CopyTruncated);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_rename_and_recreate_drains_old_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let (path, mut follower) =
+            follow_file(dir.path(), &["first line, longer than what follows"]);
+
+        append(&path, "two\nunterminated");
+        std::fs::rename(&path, dir.path().join("app.log.1")).unwrap();
+        append(&path, "three\n");
+
+        let update = follower.poll();
+        assert_eq!(texts(&update.lines), vec!["two", "unterminated", "three"]);
+        assert_eq!(update.rotations[0].1, Rotation::Recreated);
+
+        append(&path, "four\n");
+        assert_eq!(texts(&follower.poll().lines), vec!["four"]);
+    }
+
+    #[test]
+    fn test_latest_file_switches_to_newer_match() {
+        let dir = tempfile::tempdir().unwrap();
+        let touch = |name: &str, age: u64| {
+            let file = File::create(dir.path().join(name)).unwrap();
+            let modified = std::time::SystemTime::now() - Duration::from_secs(age);
+            file.set_modified(modified).unwrap();
+        };
+        touch("app-01.log", 60);
+        touch("app-02.log", 30);
+        touch("other.log", 0);
+
+        let spec = format!("{}/app-*.log", dir.path().display());
+        let mut latest = LatestFile::new(&spec).unwrap();
+        assert_eq!(latest.current(), dir.path().join("app-02.log"));
+        let mut at = Instant::now() + LATEST_CHECK_INTERVAL;
+        assert_eq!(latest.check(at), None);
+
+        touch("app-03.log", 0);
+        assert_eq!(latest.check(at), None, "checked too soon");
+        at += LATEST_CHECK_INTERVAL;
+        assert_eq!(latest.check(at), Some(dir.path().join("app-03.log")));
+        assert!(latest.take_switched());
+        assert!(!latest.take_switched());
+
+        // A late write to an earlier file does not switch back
+        touch("app-03.log", 10);
+        touch("app-02.log", 0);
+        assert_eq!(latest.check(at + LATEST_CHECK_INTERVAL), None);
+        assert_eq!(latest.current(), dir.path().join("app-03.log"));
+    }
+
+    #[test]
+    fn test_latest_file_errors() {
+        let dir = tempfile::tempdir().unwrap();
+        let spec = format!("{}/app-*.log", dir.path().display());
+        assert!(LatestFile::new(&spec)
+            .unwrap_err()
+            .starts_with("no file matches"));
+        assert!(LatestFile::new("logs-*/app.log").is_err());
+    }
+
+    #[test]
+    fn test_rotation_without_loading_rotated_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let (path, _) = follow_file(dir.path(), &["first line, longer than what follows"]);
+        let storage = LogStorage::from_file(&path).unwrap();
+        let config = FollowConfig {
+            load_rotated: false,
+            ..FollowConfig::default()
+        };
+        let mut follower = Follower::new(&storage, &config);
+
+        append(&path, "two\n");
+        std::fs::copy(&path, dir.path().join("app.log.1")).unwrap();
+        File::create(&path).unwrap();
+        append(&path, "three\n");
+
+        assert_eq!(texts(&follower.poll().lines), vec!["three"]);
+    }
+}
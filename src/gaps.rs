@@ -0,0 +1,171 @@
+//! Time between consecutive matches, shown by `:gaps`.
+//!
+//! The gaps between the timestamps of the search matches (or of the lines
+//! in view, without a search) are summed up as min, median, p95 and max and
+//! counted in buckets a power of ten apart. A periodic job shows as one
+//! tall bar; a retry storm as a pile of short gaps.
+
+use chrono::Duration;
+use rayon::prelude::*;
+
+use crate::model::BMHMatcher;
+use crate::search;
+use crate::source::LineSource;
+
+/// Upper bounds of the buckets, in milliseconds; longer gaps go in a last
+/// bucket of their own.
+const BUCKET_BOUNDS_MS: [i64; 7] = [10, 100, 1_000, 10_000, 60_000, 600_000, 3_600_000];
+
+/// Distribution of the gaps between consecutive timestamps.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Gaps {
+    /// Number of gaps (one less than the timestamps)
+    pub count: usize,
+    pub min: Duration,
+    pub median: Duration,
+    pub p95: Duration,
+    pub max: Duration,
+    /// Buckets from the shortest gaps to the longest, without the empty
+    /// buckets at either end
+    pub buckets: Vec<GapBucket>,
+}
+
+/// Gaps shorter than `upto`, and not shorter than the bucket before.
+#[derive(Debug, Clone, PartialEq)]
+pub struct GapBucket {
+    /// None for the last bucket, which has no upper bound
+    pub upto: Option<Duration>,
+    pub count: usize,
+}
+
+impl GapBucket {
+    /// `< 10s`, or `≥ 1h` for the last bucket.
+    pub fn label(&self) -> String {
+        match self.upto {
+            Some(upto) => format!("< {}", short_duration(upto)),
+            None => {
+                let longest = BUCKET_BOUNDS_MS[BUCKET_BOUNDS_MS.len() - 1];
+                format!("≥ {}", short_duration(Duration::milliseconds(longest)))
+            }
+        }
+    }
+}
+
+/// Gaps between `times` (nanoseconds since the epoch, in any order); None
+/// for fewer than two.
+pub fn compute(times: &[i64]) -> Option<Gaps> {
+    let mut times = times.to_vec();
+    times.sort_unstable();
+    let mut gaps: Vec<i64> = times.windows(2).map(|pair| pair[1] - pair[0]).collect();
+    if gaps.is_empty() {
+        return None;
+    }
+    gaps.sort_unstable();
+
+    let mut counts = [0usize; BUCKET_BOUNDS_MS.len() + 1];
+    for &gap in &gaps {
+        let bucket = BUCKET_BOUNDS_MS.partition_point(|&bound| bound * 1_000_000 <= gap);
+        counts[bucket] += 1;
+    }
+    let first = counts.iter().position(|&count| count > 0).unwrap_or(0);
+    let last = counts.iter().rposition(|&count| count > 0).unwrap_or(0);
+    let buckets = (first..=last)
+        .map(|bucket| GapBucket {
+            upto: BUCKET_BOUNDS_MS
+                .get(bucket)
+                .map(|&ms| Duration::milliseconds(ms)),
+            count: counts[bucket],
+        })
+        .collect();
+
+    let nanos = Duration::nanoseconds;
+    Some(Gaps {
+        count: gaps.len(),
+        min: nanos(gaps[0]),
+        median: nanos(percentile(&gaps, 50)),
+        p95: nanos(percentile(&gaps, 95)),
+        max: nanos(gaps[gaps.len() - 1]),
+        buckets,
+    })
+}
+
+/// Timestamps of the lines `indices` refer to, keeping only the lines the
+/// search (a matcher and the matchers it refines) matches, if given. Lines
+/// without a timestamp are left out.
+pub fn line_times(
+    source: &(dyn LineSource + Sync),
+    indices: &[usize],
+    search: Option<(&BMHMatcher, &[BMHMatcher])>,
+) -> Vec<i64> {
+    indices
+        .par_iter()
+        .filter_map(|&idx| {
+            let time = source.get_timestamp(idx)?.timestamp_nanos_opt()?;
+            if let Some((matcher, within)) = search {
+                let line = source.get_line(idx)?;
+                if search::line_matches(line.as_bytes(), matcher, within).is_empty() {
+                    return None;
+                }
+            }
+            Some(time)
+        })
+        .collect()
+}
+
+/// Nearest-rank percentile of sorted, non-empty `values`.
+fn percentile(values: &[i64], pct: usize) -> i64 {
+    let rank = (values.len() * pct).div_ceil(100).max(1);
+    values[rank - 1]
+}
+
+/// `10ms`, `1s`, `10m` or `1h`: bucket bounds are whole units.
+fn short_duration(duration: Duration) -> String {
+    let ms = duration.num_milliseconds();
+    match ms {
+        ms if ms < 1_000 => format!("{}ms", ms),
+        ms if ms < 60_000 => format!("{}s", ms / 1_000),
+        ms if ms < 3_600_000 => format!("{}m", ms / 60_000),
+        ms => format!("{}h", ms / 3_600_000),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SECOND: i64 = 1_000_000_000;
+
+    #[test]
+    fn test_gap_distribution() {
+        // A job every 60s, with a burst of retries 50ms apart
+        let mut times: Vec<i64> = (0..10).map(|n| n * 60 * SECOND).collect();
+        times.extend((1..=4).map(|n| 120 * SECOND + n * 50_000_000));
+        let gaps = compute(&times).unwrap();
+
+        assert_eq!(gaps.count, 13);
+        assert_eq!(gaps.min, Duration::milliseconds(50));
+        assert_eq!(gaps.median, Duration::seconds(60));
+        assert_eq!(gaps.p95, Duration::seconds(60));
+        assert_eq!(gaps.max, Duration::seconds(60));
+        let buckets: Vec<(String, usize)> = gaps
+            .buckets
+            .iter()
+            .map(|bucket| (bucket.label(), bucket.count))
+            .collect();
+        assert_eq!(
+            buckets,
+            [
+                ("< 100ms".to_string(), 4),
+                ("< 1s".to_string(), 0),
+                ("< 10s".to_string(), 0),
+                ("< 1m".to_string(), 1),
+                ("< 10m".to_string(), 8),
+            ]
+        );
+
+        assert_eq!(compute(&[SECOND]), None);
+        let long = compute(&[0, 7200 * SECOND]).unwrap();
+        assert_eq!(long.buckets.len(), 1);
+        assert_eq!(long.buckets[0].label(), "≥ 1h");
+    }
+}
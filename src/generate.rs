@@ -0,0 +1,512 @@
+//! Synthetic logs (`qlog generate`).
+//!
+//! Writes a log of an imagined order service: requests, payments, health
+//! checks and background jobs from a handful of sources, with warnings and
+//! errors (some with a stack trace) at a chosen rate. The same options and
+//! seed always give the same log, so a generated file can stand in for a
+//! real one in demos, benchmarks and bug reports, and the tutorial is
+//! played on one.
+
+use std::fmt;
+use std::io::{self, Write};
+
+use chrono::{DateTime, Duration, TimeZone, Utc};
+use serde_json::{Map, Value};
+
+/// Seed used when none is given.
+pub const DEFAULT_SEED: u64 = 42;
+
+/// Layout of the generated lines.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum LogFormat {
+    /// `2026-03-02 09:00:00.123 INFO  [Source] message`, stack traces on
+    /// the lines below
+    #[default]
+    Plain,
+    /// Serilog JSON events (`Timestamp`, `Level`, `MessageTemplate`,
+    /// `Properties`, `Exception`)
+    Serilog,
+    /// One JSON object per line (`time`, `level`, `logger`, `msg`)
+    Json,
+    /// `key=value` pairs (`time=... level=... msg="..."`)
+    Logfmt,
+}
+
+impl LogFormat {
+    pub const NAMES: [&'static str; 4] = ["plain", "serilog", "json", "logfmt"];
+
+    /// Parse a format name (`plain`, `serilog`, `json`, `logfmt`).
+    pub fn parse(name: &str) -> Option<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "plain" | "text" => Some(Self::Plain),
+            "serilog" => Some(Self::Serilog),
+            "json" => Some(Self::Json),
+            "logfmt" => Some(Self::Logfmt),
+            _ => None,
+        }
+    }
+}
+
+/// What to generate.
+#[derive(Debug, Clone)]
+pub struct GenerateOptions {
+    /// Log entries to write; stack traces of the plain format add lines
+    pub lines: usize,
+    pub format: LogFormat,
+    /// Share of entries that are errors; warnings are twice as common
+    pub error_rate: f64,
+    pub seed: u64,
+    /// Time of the first entry
+    pub start: DateTime<Utc>,
+}
+
+impl Default for GenerateOptions {
+    fn default() -> Self {
+        Self {
+            lines: 10_000,
+            format: LogFormat::Plain,
+            error_rate: 0.02,
+            seed: DEFAULT_SEED,
+            start: Utc.with_ymd_and_hms(2026, 3, 2, 9, 0, 0).unwrap(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Level {
+    Debug,
+    Info,
+    Warn,
+    Error,
+}
+
+impl Level {
+    fn name(self, format: LogFormat) -> &'static str {
+        match (format, self) {
+            (LogFormat::Plain, Level::Debug) => "DEBUG",
+            (LogFormat::Plain, Level::Info) => "INFO ",
+            (LogFormat::Plain, Level::Warn) => "WARN ",
+            (LogFormat::Plain, Level::Error) => "ERROR",
+            (LogFormat::Serilog, Level::Debug) => "Debug",
+            (LogFormat::Serilog, Level::Info) => "Information",
+            (LogFormat::Serilog, Level::Warn) => "Warning",
+            (LogFormat::Serilog, Level::Error) => "Error",
+            (_, Level::Debug) => "debug",
+            (_, Level::Info) => "info",
+            (_, Level::Warn) => "warn",
+            (_, Level::Error) => "error",
+        }
+    }
+}
+
+/// A kind of entry: its source and message template. Errors name the
+/// exception they carry.
+struct Template {
+    source: &'static str,
+    message: &'static str,
+    exception: Option<&'static str>,
+}
+
+const fn template(source: &'static str, message: &'static str) -> Template {
+    Template {
+        source,
+        message,
+        exception: None,
+    }
+}
+
+const DEBUG_TEMPLATES: &[Template] = &[
+    template("Health.Probe", "healthcheck ok in {Elapsed}ms"),
+    template("Cache.Store", "cache hit ratio {Ratio}%"),
+];
+
+const INFO_TEMPLATES: &[Template] = &[
+    template(
+        "Api.OrdersController",
+        "GET /orders/{OrderId} 200 in {Elapsed}ms",
+    ),
+    template(
+        "Orders.OrderService",
+        "order {OrderId} created by {UserName} ({Items} items)",
+    ),
+    template(
+        "Payments.GatewayClient",
+        "payment for order {OrderId} authorized amount={Amount} EUR",
+    ),
+    template("Shipping.LabelJob", "label printed for order {OrderId}"),
+    template("Auth.TokenService", "token issued for {UserName}"),
+];
+
+const WARN_TEMPLATES: &[Template] = &[
+    template(
+        "Payments.GatewayClient",
+        "payment gateway slow: {Seconds}s for order {OrderId}",
+    ),
+    template(
+        "Data.OrderRepository",
+        "query timeout on orders_by_user, retrying (attempt {Attempt})",
+    ),
+];
+
+const ERROR_TEMPLATES: &[Template] = &[
+    Template {
+        source: "Payments.GatewayClient",
+        message: "payment for order {OrderId} failed: gateway timeout after 5000ms",
+        exception: Some("System.TimeoutException: The operation has timed out."),
+    },
+    Template {
+        source: "Data.OrderRepository",
+        message: "could not save order {OrderId}: deadlock detected",
+        exception: Some("Npgsql.PostgresException: 40P01: deadlock detected"),
+    },
+    Template {
+        source: "Api.OrdersController",
+        message: "unhandled exception for GET /orders/{OrderId}",
+        exception: Some(
+            "System.NullReferenceException: Object reference not set to an instance of an object.",
+        ),
+    },
+];
+
+const USERS: &[&str] = &["alice", "bob", "carol", "dave", "erin", "frank"];
+const METHODS: &[&str] = &["Handle", "SaveAsync", "SendAsync", "Execute", "Invoke"];
+
+/// SplitMix64: small, fast and the same on every platform.
+struct Rng(u64);
+
+impl Rng {
+    fn next(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    /// A number in `0..n`.
+    fn below(&mut self, n: u64) -> u64 {
+        self.next() % n
+    }
+
+    /// True with probability `p`.
+    fn chance(&mut self, p: f64) -> bool {
+        ((self.next() >> 11) as f64 / (1u64 << 53) as f64) < p
+    }
+
+    fn pick<'a, T>(&mut self, items: &'a [T]) -> &'a T {
+        &items[self.below(items.len() as u64) as usize]
+    }
+}
+
+/// One generated entry.
+struct Entry {
+    time: DateTime<Utc>,
+    level: Level,
+    template: &'static Template,
+    /// Values of the placeholders of the template, in order
+    properties: Vec<(&'static str, Value)>,
+    /// Exception message and stack frames, for some errors
+    exception: Option<(String, Vec<String>)>,
+}
+
+impl Entry {
+    fn message(&self) -> String {
+        self.properties.iter().fold(
+            self.template.message.to_string(),
+            |message, (name, value)| {
+                let text = match value {
+                    Value::String(s) => s.clone(),
+                    other => other.to_string(),
+                };
+                message.replace(&format!("{{{}}}", name), &text)
+            },
+        )
+    }
+}
+
+/// Write `options.lines` entries to `out`. Returns the number of lines
+/// written, stack trace lines included.
+pub fn generate(options: &GenerateOptions, mut out: impl Write) -> io::Result<usize> {
+    let mut rng = Rng(options.seed);
+    let mut time = options.start;
+    let mut order_id = 4100u64;
+    let mut written = 0;
+    for _ in 0..options.lines {
+        time += Duration::milliseconds(1 + rng.below(900) as i64);
+        if rng.chance(0.3) {
+            order_id += 1;
+        }
+        let entry = next_entry(&mut rng, options.error_rate, time, order_id);
+        written += write_entry(&mut out, &entry, options.format)?;
+    }
+    out.flush()?;
+    Ok(written)
+}
+
+fn next_entry(rng: &mut Rng, error_rate: f64, time: DateTime<Utc>, order_id: u64) -> Entry {
+    let level = if rng.chance(error_rate) {
+        Level::Error
+    } else if rng.chance((error_rate * 2.0).min(1.0)) {
+        Level::Warn
+    } else if rng.chance(0.25) {
+        Level::Debug
+    } else {
+        Level::Info
+    };
+    let template = rng.pick(match level {
+        Level::Debug => DEBUG_TEMPLATES,
+        Level::Info => INFO_TEMPLATES,
+        Level::Warn => WARN_TEMPLATES,
+        Level::Error => ERROR_TEMPLATES,
+    });
+    let properties = placeholders(template.message)
+        .map(|name| {
+            let value = match name {
+                "OrderId" => Value::from(order_id),
+                "UserName" => Value::from(*rng.pick(USERS)),
+                "Elapsed" => Value::from(2 + rng.below(120)),
+                "Items" => Value::from(1 + rng.below(6)),
+                "Amount" => Value::from(format!("{}.{:02}", 5 + rng.below(400), rng.below(100))),
+                "Ratio" => Value::from(60 + rng.below(40)),
+                "Seconds" => Value::from(format!("2.{}", 1 + rng.below(9))),
+                "Attempt" => Value::from(2 + rng.below(3)),
+                _ => Value::Null,
+            };
+            (name, value)
+        })
+        .collect();
+    let exception = template
+        .exception
+        .filter(|_| rng.chance(0.7))
+        .map(|message| {
+            let frames = (0..3 + rng.below(4))
+                .map(|depth| {
+                    format!(
+                        "   at {}.{}() in /src/{}.cs:line {}",
+                        template.source,
+                        rng.pick(METHODS),
+                        template.source.replace('.', "/"),
+                        20 + depth * 17 + rng.below(15)
+                    )
+                })
+                .collect();
+            (message.to_string(), frames)
+        });
+    Entry {
+        time,
+        level,
+        template,
+        properties,
+        exception,
+    }
+}
+
+/// Names of the `{Name}` placeholders of a message template.
+fn placeholders(message: &'static str) -> impl Iterator<Item = &'static str> {
+    message
+        .split('{')
+        .skip(1)
+        .filter_map(|part| part.split_once('}').map(|(name, _)| name))
+}
+
+/// Write one entry; returns the number of lines it took.
+fn write_entry(out: &mut impl Write, entry: &Entry, format: LogFormat) -> io::Result<usize> {
+    let level = entry.level.name(format);
+    let source = entry.template.source;
+    match format {
+        LogFormat::Plain => {
+            writeln!(
+                out,
+                "{} {} [{}] {}",
+                entry.time.format("%Y-%m-%d %H:%M:%S%.3f"),
+                level,
+                source,
+                entry.message()
+            )?;
+            let Some((message, frames)) = &entry.exception else {
+                return Ok(1);
+            };
+            writeln!(out, "{}", message)?;
+            for frame in frames {
+                writeln!(out, "{}", frame)?;
+            }
+            Ok(2 + frames.len())
+        }
+        LogFormat::Serilog => {
+            let mut event = Map::new();
+            event.insert("Timestamp".into(), Value::from(rfc3339(entry.time)));
+            event.insert("Level".into(), Value::from(level));
+            event.insert(
+                "MessageTemplate".into(),
+                Value::from(entry.template.message),
+            );
+            event.insert("RenderedMessage".into(), Value::from(entry.message()));
+            if let Some((message, frames)) = &entry.exception {
+                let trace = std::iter::once(message.as_str())
+                    .chain(frames.iter().map(String::as_str))
+                    .collect::<Vec<_>>()
+                    .join("\n");
+                event.insert("Exception".into(), Value::from(trace));
+            }
+            let mut properties: Map<String, Value> = entry
+                .properties
+                .iter()
+                .map(|(name, value)| (name.to_string(), value.clone()))
+                .collect();
+            properties.insert("SourceContext".into(), Value::from(source));
+            event.insert("Properties".into(), Value::Object(properties));
+            writeln!(out, "{}", Value::Object(event))?;
+            Ok(1)
+        }
+        LogFormat::Json => {
+            let mut object = Map::new();
+            object.insert("time".into(), Value::from(rfc3339(entry.time)));
+            object.insert("level".into(), Value::from(level));
+            object.insert("logger".into(), Value::from(source));
+            object.insert("msg".into(), Value::from(entry.message()));
+            for (name, value) in &entry.properties {
+                object.insert(snake_case(name), value.clone());
+            }
+            if let Some((message, frames)) = &entry.exception {
+                object.insert("error".into(), Value::from(message.as_str()));
+                object.insert("stack".into(), Value::from(frames.join("\n")));
+            }
+            writeln!(out, "{}", Value::Object(object))?;
+            Ok(1)
+        }
+        LogFormat::Logfmt => {
+            write!(
+                out,
+                "time={} level={} source={} msg={}",
+                rfc3339(entry.time),
+                level,
+                source,
+                Logfmt(&entry.message())
+            )?;
+            for (name, value) in &entry.properties {
+                let text = match value {
+                    Value::String(s) => s.clone(),
+                    other => other.to_string(),
+                };
+                write!(out, " {}={}", snake_case(name), Logfmt(&text))?;
+            }
+            if let Some((message, _)) = &entry.exception {
+                write!(out, " error={}", Logfmt(message))?;
+            }
+            writeln!(out)?;
+            Ok(1)
+        }
+    }
+}
+
+fn rfc3339(time: DateTime<Utc>) -> String {
+    time.format("%Y-%m-%dT%H:%M:%S%.3fZ").to_string()
+}
+
+/// `OrderId` as `order_id`.
+fn snake_case(name: &str) -> String {
+    let mut out = String::with_capacity(name.len() + 2);
+    for (i, c) in name.chars().enumerate() {
+        if c.is_ascii_uppercase() && i > 0 {
+            out.push('_');
+        }
+        out.push(c.to_ascii_lowercase());
+    }
+    out
+}
+
+/// A logfmt value, quoted when it contains spaces, quotes or `=`.
+struct Logfmt<'a>(&'a str);
+
+impl fmt::Display for Logfmt<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.0.is_empty() || self.0.contains([' ', '"', '=']) {
+            write!(f, "\"{}\"", self.0.replace('"', "\\\""))
+        } else {
+            f.write_str(self.0)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::detect_level;
+
+    fn generated(format: LogFormat, lines: usize, error_rate: f64) -> (String, usize) {
+        let options = GenerateOptions {
+            lines,
+            format,
+            error_rate,
+            ..GenerateOptions::default()
+        };
+        let mut out = Vec::new();
+        let written = generate(&options, &mut out).unwrap();
+        (String::from_utf8(out).unwrap(), written)
+    }
+
+    #[test]
+    fn test_plain_logs_are_reproducible_with_stack_traces() {
+        let (text, written) = generated(LogFormat::Plain, 2000, 0.05);
+        assert_eq!(generated(LogFormat::Plain, 2000, 0.05).0, text);
+        assert_eq!(text.lines().count(), written);
+        assert!(written > 2000);
+
+        let entries: Vec<&str> = text
+            .lines()
+            .filter(|line| line.starts_with("2026-"))
+            .collect();
+        assert_eq!(entries.len(), 2000);
+        let errors = entries
+            .iter()
+            .filter(|line| detect_level(line).is_some_and(|level| level.name() == "error"))
+            .count();
+        // 5% of 2000, give or take
+        assert!((60..140).contains(&errors), "{} errors", errors);
+        assert!(text.contains("\n   at "));
+        assert!(entries
+            .windows(2)
+            .all(|pair| pair[0][..23] <= pair[1][..23]));
+
+        let other_seed = GenerateOptions {
+            lines: 2000,
+            error_rate: 0.05,
+            seed: 7,
+            ..GenerateOptions::default()
+        };
+        let mut out = Vec::new();
+        generate(&other_seed, &mut out).unwrap();
+        assert_ne!(String::from_utf8(out).unwrap(), text);
+    }
+
+    #[test]
+    fn test_structured_formats() {
+        let (serilog, written) = generated(LogFormat::Serilog, 200, 0.2);
+        assert_eq!(written, 200);
+        let events: Vec<Value> = serilog
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        assert!(events
+            .iter()
+            .all(|event| event["Properties"]["SourceContext"].is_string()));
+        assert!(events.iter().any(|event| event["Exception"]
+            .as_str()
+            .is_some_and(|trace| trace.contains("\n   at "))));
+
+        let (json, _) = generated(LogFormat::Json, 50, 0.0);
+        let first: Value = serde_json::from_str(json.lines().next().unwrap()).unwrap();
+        assert!(first["time"]
+            .as_str()
+            .unwrap()
+            .starts_with("2026-03-02T09:00:"));
+        assert!(first["msg"].is_string() && first["logger"].is_string());
+
+        let (logfmt, _) = generated(LogFormat::Logfmt, 50, 0.0);
+        assert!(logfmt
+            .lines()
+            .all(|line| line.starts_with("time=2026-03-02T") && line.contains(" msg=\"")));
+        assert_eq!(Logfmt("a b").to_string(), "\"a b\"");
+        assert_eq!(snake_case("OrderId"), "order_id");
+    }
+}
@@ -0,0 +1,40 @@
+//! Shell-style `*` and `?` patterns for file names.
+
+use std::path::Path;
+
+/// Check whether `path` matches `pattern`: the whole path if the pattern
+/// contains `/`, otherwise just the file name.
+pub fn matches_path(path: &Path, pattern: &str) -> bool {
+    if pattern.contains('/') {
+        glob_match(&path.to_string_lossy(), pattern)
+    } else {
+        let file_name = path
+            .file_name()
+            .map(|n| n.to_string_lossy())
+            .unwrap_or_default();
+        glob_match(&file_name, pattern)
+    }
+}
+
+/// Match `text` against a pattern where `*` stands for any run of
+/// characters and `?` for one character.
+pub fn glob_match(text: &str, pattern: &str) -> bool {
+    let text_chars: Vec<char> = text.chars().collect();
+    let pattern_chars: Vec<char> = pattern.chars().collect();
+
+    fn match_helper(text: &[char], pattern: &[char]) -> bool {
+        match (text.first(), pattern.first()) {
+            (None, None) => true,
+            (Some(_), None) => false,
+            (None, Some('*')) => match_helper(&[], &pattern[1..]),
+            (None, Some(_)) => false,
+            (Some(_), Some('*')) => {
+                match_helper(text, &pattern[1..]) || match_helper(&text[1..], pattern)
+            }
+            (Some(t), Some(p)) if *p == '?' || t == p => match_helper(&text[1..], &pattern[1..]),
+            (Some(_), Some(_)) => false,
+        }
+    }
+
+    match_helper(&text_chars, &pattern_chars)
+}
@@ -0,0 +1,220 @@
+//! The help overlay (`?`).
+//!
+//! Nothing here is written by hand: every key is run through the bindings
+//! of each mode and the messages it gives are listed with
+//! [`Msg::description`], and the commands come from the command table, so
+//! a key or command added later shows up without further work.
+
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+
+use crate::command;
+use crate::key_bindings::{translate, translate_bracket, translate_g, Mode, Msg};
+
+/// Modes listed, in order, with their headings.
+const MODES: [(Mode, &str); 18] = [
+    (Mode::Normal, "Log view"),
+    (Mode::SearchInput, "Search input"),
+    (Mode::Command, "Command line"),
+    (Mode::FilterList, "Filter list"),
+    (Mode::Detail, "Detail pane"),
+    (Mode::Hex, "Hex view"),
+    (Mode::Bookmarks, "Bookmark list"),
+    (Mode::Pins, "Pin list"),
+    (Mode::Errors, "Error list"),
+    (Mode::Pods, "Pod list"),
+    (Mode::Sql, "SQL results"),
+    (Mode::Stats, "Stats"),
+    (Mode::Exceptions, "Exception groups"),
+    (Mode::Gaps, "Gap histogram"),
+    (Mode::Messages, "Messages"),
+    (Mode::Recent, "Recent files"),
+    (Mode::Prompt, "Prompt"),
+    (Mode::Help, "Help"),
+];
+
+/// Keys without a character that bindings use.
+const SPECIAL_KEYS: [KeyCode; 13] = [
+    KeyCode::Enter,
+    KeyCode::Esc,
+    KeyCode::Tab,
+    KeyCode::BackTab,
+    KeyCode::Backspace,
+    KeyCode::Up,
+    KeyCode::Down,
+    KeyCode::Left,
+    KeyCode::Right,
+    KeyCode::PageUp,
+    KeyCode::PageDown,
+    KeyCode::Home,
+    KeyCode::End,
+];
+
+/// A heading and its keys or commands with what they do.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Section {
+    pub title: String,
+    pub entries: Vec<(String, String)>,
+}
+
+/// Every section of the help: the keys of each mode, the commands and the
+/// `aliases` from the config.
+pub fn sections(aliases: &[(String, String)]) -> Vec<Section> {
+    let mut sections: Vec<Section> = MODES
+        .iter()
+        .map(|&(mode, title)| Section {
+            title: title.to_string(),
+            entries: bindings(mode),
+        })
+        .collect();
+    sections.push(Section {
+        title: "Commands".to_string(),
+        entries: command::help()
+            .map(|(usage, summary)| (format!(":{}", usage), summary.to_string()))
+            .collect(),
+    });
+    if !aliases.is_empty() {
+        sections.push(Section {
+            title: "Aliases".to_string(),
+            entries: aliases
+                .iter()
+                .map(|(name, expansion)| (format!(":{}", name), expansion.clone()))
+                .collect(),
+        });
+    }
+    sections
+}
+
+/// Rows the sections take when drawn: a heading and a blank row after
+/// each, and a row per entry.
+pub fn rows(sections: &[Section]) -> usize {
+    sections
+        .iter()
+        .map(|section| section.entries.len() + 2)
+        .sum()
+}
+
+/// The keys bound in `mode`, grouped by what they do, in the order the
+/// first key of each is found.
+fn bindings(mode: Mode) -> Vec<(String, String)> {
+    let mut keys: Vec<(KeyEvent, Msg)> = Vec::new();
+    for key in candidates() {
+        // Ctrl+C quits from anywhere; it is listed once
+        let Some(msg) =
+            translate(key, mode).filter(|msg| mode == Mode::Normal || *msg != Msg::Quit)
+        else {
+            continue;
+        };
+        keys.push((key, msg));
+    }
+
+    let mut entries: Vec<(String, String)> = Vec::new();
+    let mut add = |name: String, msg: &Msg| {
+        let Some(text) = msg.description() else {
+            return;
+        };
+        match entries.iter_mut().find(|(_, existing)| *existing == text) {
+            Some((names, _)) => {
+                names.push_str(", ");
+                names.push_str(&name);
+            }
+            None => entries.push((name, text)),
+        }
+    };
+    for (key, msg) in &keys {
+        add(key_name(key), msg);
+        // Keys that wait for a second key
+        let prefix = match (msg, key.code) {
+            (Msg::Bracket(prefix), _) => Some(*prefix),
+            (Msg::GoToTop, KeyCode::Char('g')) => Some('g'),
+            _ => None,
+        };
+        let Some(prefix) = prefix else {
+            continue;
+        };
+        for second in candidates() {
+            let msg = match prefix {
+                'g' => translate_g(second),
+                _ => translate_bracket(prefix, second),
+            };
+            if let Some(msg) = msg {
+                add(format!("{}{}", prefix, key_name(&second)), &msg);
+            }
+        }
+    }
+    entries
+}
+
+/// Every key that is tried: letters, digits and the other printable
+/// characters, the special keys, the function keys and Ctrl+C.
+fn candidates() -> impl Iterator<Item = KeyEvent> {
+    ('a'..='z')
+        .chain('A'..='Z')
+        .chain('0'..='9')
+        .chain((' '..='~').filter(|c| !c.is_ascii_alphanumeric()))
+        .map(KeyCode::Char)
+        .chain(SPECIAL_KEYS)
+        .chain((1..=12).map(KeyCode::F))
+        .map(KeyEvent::from)
+        .chain([KeyEvent::new(KeyCode::Char('c'), KeyModifiers::CONTROL)])
+}
+
+fn key_name(key: &KeyEvent) -> String {
+    let name = match key.code {
+        KeyCode::Char(' ') => "Space".to_string(),
+        KeyCode::Char(c) => c.to_string(),
+        KeyCode::F(n) => format!("F{}", n),
+        KeyCode::BackTab => "Shift+Tab".to_string(),
+        KeyCode::PageUp => "PgUp".to_string(),
+        KeyCode::PageDown => "PgDn".to_string(),
+        code => format!("{:?}", code),
+    };
+    if key.modifiers.contains(KeyModifiers::CONTROL) {
+        format!("Ctrl+{}", name)
+    } else {
+        name
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry<'a>(sections: &'a [Section], title: &str, text: &str) -> Option<&'a str> {
+        let section = sections.iter().find(|section| section.title == title)?;
+        section
+            .entries
+            .iter()
+            .find(|(_, t)| t == text)
+            .map(|(keys, _)| keys.as_str())
+    }
+
+    #[test]
+    fn test_sections_come_from_the_bindings_and_commands() {
+        let sections = sections(&[("errs".to_string(), "level error".to_string())]);
+        assert_eq!(entry(&sections, "Log view", "Move down"), Some("j, Down"));
+        assert_eq!(entry(&sections, "Log view", "Quit"), Some("Ctrl+c"));
+        assert_eq!(entry(&sections, "Log view", "Next tab"), Some("gt"));
+        assert_eq!(
+            entry(&sections, "Log view", "Next error in view"),
+            Some("]q")
+        );
+        assert_eq!(
+            entry(&sections, "Log view", "Show or hide error lines"),
+            Some("1")
+        );
+        assert_eq!(entry(&sections, "Help", "Close"), Some("q, ?, Esc"));
+        assert_eq!(entry(&sections, "Help", "Quit"), None);
+        // Typed characters are not listed one by one
+        assert_eq!(sections[1].entries.len(), 3);
+
+        assert_eq!(
+            entry(
+                &sections,
+                "Commands",
+                "Show only lines matching the pattern"
+            ),
+            Some(":filter <pattern>")
+        );
+        assert_eq!(entry(&sections, "Aliases", "level error"), Some(":errs"));
+    }
+}
@@ -0,0 +1,198 @@
+//! Byte-level view of a single line.
+//!
+//! `X` (or `:hex`) shows the exact bytes of the line under the cursor as a
+//! hex dump with an ASCII column, addressed by their offset in the file,
+//! together with how the line ends (`\n`, `\r\n` or not at all). Control
+//! characters, non-ASCII bytes and invalid UTF-8 are picked out, which is
+//! what encoding problems, stray escape sequences and truncated writes look
+//! like.
+
+use std::ops::Range;
+
+/// Bytes shown per row of the dump.
+pub const BYTES_PER_ROW: usize = 16;
+
+/// What kind of byte a dumped byte is, for coloring.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ByteClass {
+    /// Printable ASCII
+    Printable,
+    /// Space, tab and the line terminator
+    Whitespace,
+    /// Other ASCII control characters (escape, NUL, ...)
+    Control,
+    /// Part of a valid multi-byte UTF-8 character
+    NonAscii,
+    /// Not valid UTF-8
+    Invalid,
+}
+
+/// Hex dump of one line.
+#[derive(Debug, Clone)]
+pub struct HexView {
+    bytes: Vec<u8>,
+    /// Offset of the first byte in its file (in the decompressed data for
+    /// compressed files); None for lines received while following
+    offset: Option<u64>,
+    /// Where the line is from, e.g. `app.log line 12`
+    pub source: String,
+    /// Position of each byte that is not valid UTF-8
+    invalid: Vec<bool>,
+    /// First row shown
+    pub scroll: usize,
+}
+
+impl HexView {
+    /// Dump `bytes`, the line including its terminator if it has one.
+    pub fn new(bytes: Vec<u8>, offset: Option<u64>, source: String) -> Self {
+        let invalid = invalid_utf8(&bytes);
+        Self {
+            bytes,
+            offset,
+            source,
+            invalid,
+            scroll: 0,
+        }
+    }
+
+    pub fn bytes(&self) -> &[u8] {
+        &self.bytes
+    }
+
+    pub fn offset(&self) -> Option<u64> {
+        self.offset
+    }
+
+    /// Number of rows of the dump.
+    pub fn rows(&self) -> usize {
+        self.bytes.len().div_ceil(BYTES_PER_ROW).max(1)
+    }
+
+    /// Address and byte range of row `row`. Addresses are file offsets,
+    /// or positions within the line if its offset is unknown.
+    pub fn row(&self, row: usize) -> (u64, Range<usize>) {
+        let start = (row * BYTES_PER_ROW).min(self.bytes.len());
+        let end = (start + BYTES_PER_ROW).min(self.bytes.len());
+        (self.offset.unwrap_or(0) + start as u64, start..end)
+    }
+
+    pub fn class(&self, pos: usize) -> ByteClass {
+        let byte = self.bytes[pos];
+        if self.invalid[pos] {
+            ByteClass::Invalid
+        } else if !byte.is_ascii() {
+            ByteClass::NonAscii
+        } else if matches!(byte, b' ' | b'\t' | b'\r' | b'\n') {
+            ByteClass::Whitespace
+        } else if byte.is_ascii_control() {
+            ByteClass::Control
+        } else {
+            ByteClass::Printable
+        }
+    }
+
+    /// Length of the line without its terminator.
+    fn text_len(&self) -> usize {
+        let text = self.bytes.strip_suffix(b"\n").unwrap_or(&self.bytes);
+        text.strip_suffix(b"\r").unwrap_or(text).len()
+    }
+
+    /// One-line description: size, terminator and anything unusual, e.g.
+    /// `57 bytes + \r\n, 1 control, invalid UTF-8 at byte 12`.
+    pub fn summary(&self) -> String {
+        let text_len = self.text_len();
+        let mut parts = vec![match (&self.bytes[text_len..], self.offset) {
+            (b"\n", _) => format!("{} bytes + \\n", text_len),
+            (b"\r\n", _) => format!("{} bytes + \\r\\n", text_len),
+            (b"\r", _) => format!("{} bytes + \\r, no \\n", text_len),
+            (_, None) => format!("{} bytes", text_len),
+            _ => format!("{} bytes, no line terminator", text_len),
+        }];
+        let controls = (0..text_len)
+            .filter(|&pos| self.class(pos) == ByteClass::Control)
+            .count();
+        if controls > 0 {
+            parts.push(format!("{} control", controls));
+        }
+        let non_ascii = (0..text_len)
+            .filter(|&pos| self.class(pos) == ByteClass::NonAscii)
+            .count();
+        if non_ascii > 0 {
+            parts.push(format!("{} non-ASCII", non_ascii));
+        }
+        if let Some(pos) = self.invalid.iter().position(|&invalid| invalid) {
+            parts.push(format!("invalid UTF-8 at byte {}", pos));
+        }
+        parts.join(", ")
+    }
+
+    pub fn scroll_down(&mut self) {
+        if self.scroll + 1 < self.rows() {
+            self.scroll += 1;
+        }
+    }
+
+    pub fn scroll_up(&mut self) {
+        self.scroll = self.scroll.saturating_sub(1);
+    }
+
+    pub fn scroll_to_bottom(&mut self) {
+        self.scroll = self.rows() - 1;
+    }
+}
+
+/// Mark the bytes of `bytes` that are not part of valid UTF-8.
+fn invalid_utf8(bytes: &[u8]) -> Vec<bool> {
+    let mut invalid = vec![false; bytes.len()];
+    let mut pos = 0;
+    while pos < bytes.len() {
+        match std::str::from_utf8(&bytes[pos..]) {
+            Ok(_) => break,
+            Err(e) => {
+                let bad = pos + e.valid_up_to();
+                // A sequence cut off at the end has no error length
+                let len = e.error_len().unwrap_or(bytes.len() - bad);
+                invalid[bad..bad + len].fill(true);
+                pos = bad + len;
+            }
+        }
+    }
+    invalid
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_classes_and_summary() {
+        let view = HexView::new(
+            b"caf\xc3\xa9 \x1b[31m\xff\r\n".to_vec(),
+            Some(0x100),
+            "app.log line 3".to_string(),
+        );
+        let classes: Vec<ByteClass> = (0..view.bytes().len()).map(|pos| view.class(pos)).collect();
+        assert_eq!(classes[0], ByteClass::Printable);
+        assert_eq!(classes[3..5], [ByteClass::NonAscii, ByteClass::NonAscii]);
+        assert_eq!(classes[5], ByteClass::Whitespace);
+        assert_eq!(classes[6], ByteClass::Control);
+        assert_eq!(classes[11], ByteClass::Invalid);
+        assert_eq!(
+            view.summary(),
+            "12 bytes + \\r\\n, 1 control, 2 non-ASCII, invalid UTF-8 at byte 11"
+        );
+        assert_eq!(view.rows(), 1);
+        assert_eq!(view.row(0), (0x100, 0..14));
+    }
+
+    #[test]
+    fn test_truncated_line_and_rows() {
+        let view = HexView::new(vec![b'a'; 20], Some(32), String::new());
+        assert_eq!(view.summary(), "20 bytes, no line terminator");
+        assert_eq!(view.rows(), 2);
+        assert_eq!(view.row(1), (48, 16..20));
+
+        let cut = HexView::new(b"ab\xe2\x82".to_vec(), None, String::new());
+        assert_eq!(cut.summary(), "4 bytes, invalid UTF-8 at byte 2");
+    }
+}
@@ -0,0 +1,176 @@
+//! History of `:` commands.
+//!
+//! Up and Down in the command line step through earlier commands, newest
+//! first. With text typed, only commands starting with it are visited, as
+//! in a shell's history search. With `[history] save = true` the history is
+//! kept in `~/.qlog/history`, one command per line.
+
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// Maximum number of commands kept.
+const MAX_HISTORY: usize = 500;
+
+/// Executed commands, oldest first, and the position while stepping
+/// through them.
+#[derive(Debug, Clone, Default)]
+pub struct CommandHistory {
+    entries: Vec<String>,
+    /// Entry shown in the command line; None while not stepping
+    position: Option<usize>,
+    /// Text typed before stepping started; only entries starting with it
+    /// are visited
+    prefix: String,
+    /// File the history is kept in, if saved
+    path: Option<PathBuf>,
+}
+
+impl CommandHistory {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Location of the history file (`~/.qlog/history`).
+    pub fn default_path() -> Option<PathBuf> {
+        dirs::home_dir().map(|home| home.join(".qlog/history"))
+    }
+
+    /// History kept in `path`: earlier commands are read from it and new
+    /// ones written to it. A missing file starts an empty history.
+    pub fn load_from_path(path: &Path) -> Self {
+        let mut entries: Vec<String> = fs::read_to_string(path)
+            .unwrap_or_default()
+            .lines()
+            .filter(|line| !line.trim().is_empty())
+            .map(str::to_string)
+            .collect();
+        let excess = entries.len().saturating_sub(MAX_HISTORY);
+        entries.drain(..excess);
+        Self {
+            entries,
+            path: Some(path.to_path_buf()),
+            ..Self::default()
+        }
+    }
+
+    /// Add an executed command as the newest entry, moving it there if it
+    /// was run before, and write the history file if there is one.
+    pub fn record(&mut self, command: &str) -> io::Result<()> {
+        self.reset();
+        let command = command.trim();
+        if command.is_empty() {
+            return Ok(());
+        }
+        self.entries.retain(|entry| entry != command);
+        self.entries.push(command.to_string());
+        let excess = self.entries.len().saturating_sub(MAX_HISTORY);
+        self.entries.drain(..excess);
+
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let mut content = self.entries.join("\n");
+        content.push('\n');
+        fs::write(path, content)
+    }
+
+    /// Step to the next older command starting with the text typed before
+    /// stepping began (`input` on the first step). None if there is none.
+    pub fn older(&mut self, input: &str) -> Option<&str> {
+        if self.position.is_none() {
+            self.prefix = input.to_string();
+        }
+        let end = self.position.unwrap_or(self.entries.len());
+        let found = self.entries[..end]
+            .iter()
+            .rposition(|entry| entry.starts_with(&self.prefix))?;
+        self.position = Some(found);
+        Some(&self.entries[found])
+    }
+
+    /// Step to the next newer matching command; past the newest one, back
+    /// to the text typed before stepping began. None if not stepping.
+    pub fn newer(&mut self) -> Option<&str> {
+        let position = self.position?;
+        let found = self.entries[position + 1..]
+            .iter()
+            .position(|entry| entry.starts_with(&self.prefix))
+            .map(|offset| position + 1 + offset);
+        self.position = found;
+        match found {
+            Some(found) => Some(&self.entries[found]),
+            None => Some(&self.prefix),
+        }
+    }
+
+    /// Stop stepping; the next step starts from the newest command again.
+    pub fn reset(&mut self) {
+        self.position = None;
+        self.prefix.clear();
+    }
+
+    pub fn entries(&self) -> &[String] {
+        &self.entries
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn history(commands: &[&str]) -> CommandHistory {
+        let mut history = CommandHistory::new();
+        for command in commands {
+            history.record(command).unwrap();
+        }
+        history
+    }
+
+    #[test]
+    fn test_steps_through_commands_with_prefix() {
+        let mut history = history(&["filter error", "theme dark", "filter-out retry", "stats"]);
+
+        assert_eq!(history.older("fil"), Some("filter-out retry"));
+        assert_eq!(history.older("ignored"), Some("filter error"));
+        assert_eq!(history.older(""), None);
+        assert_eq!(history.newer(), Some("filter-out retry"));
+        assert_eq!(history.newer(), Some("fil"));
+        assert_eq!(history.newer(), None);
+
+        assert_eq!(history.older(""), Some("stats"));
+        history.reset();
+        assert_eq!(history.older(""), Some("stats"));
+    }
+
+    #[test]
+    fn test_repeated_command_moves_to_newest() {
+        let history = history(&["stats", "errors", "stats", "  "]);
+        assert_eq!(history.entries(), ["errors", "stats"]);
+    }
+
+    #[test]
+    fn test_saved_history_is_read_back() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join(".qlog/history");
+        let mut history = CommandHistory::load_from_path(&path);
+        assert!(history.is_empty());
+        history.record("filter error").unwrap();
+        history.record("stats").unwrap();
+
+        let reopened = CommandHistory::load_from_path(&path);
+        assert_eq!(reopened.entries(), ["filter error", "stats"]);
+    }
+}
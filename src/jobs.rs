@@ -0,0 +1,209 @@
+//! Long-running work on worker threads.
+//!
+//! A [`Job`] runs a closure on its own thread. The closure reports progress
+//! and sends updates through its [`JobContext`] and checks for cancellation
+//! between chunks of work; the UI thread polls the job once per tick, so the
+//! interface never blocks on a scan or an export.
+
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::mpsc::{channel, Receiver, Sender};
+use std::sync::Arc;
+use std::thread::{self, JoinHandle};
+use std::time::Instant;
+
+/// Handle given to the work closure of a job.
+#[derive(Debug)]
+pub struct JobContext<U> {
+    cancel: Arc<AtomicBool>,
+    done: Arc<AtomicUsize>,
+    tx: Sender<U>,
+}
+
+impl<U> JobContext<U> {
+    /// Whether the job was cancelled; check between chunks of work.
+    pub fn is_cancelled(&self) -> bool {
+        self.cancel.load(Ordering::Relaxed)
+    }
+
+    /// Record that `amount` more units of work are done.
+    pub fn advance(&self, amount: usize) {
+        self.done.fetch_add(amount, Ordering::Relaxed);
+    }
+
+    /// Send an update to the UI thread. Returns false once the job has been
+    /// dropped, after which the work can stop.
+    pub fn send(&self, update: U) -> bool {
+        self.tx.send(update).is_ok()
+    }
+}
+
+/// Work running on a worker thread.
+#[derive(Debug)]
+pub struct Job<U> {
+    name: String,
+    started: Instant,
+    total: usize,
+    cancel: Arc<AtomicBool>,
+    done: Arc<AtomicUsize>,
+    rx: Receiver<U>,
+    handle: Option<JoinHandle<()>>,
+    finished: bool,
+}
+
+impl<U: Send + 'static> Job<U> {
+    /// Run `work` on a new thread. `total` is the number of work units it
+    /// reports through [`JobContext::advance`].
+    pub fn spawn(
+        name: impl Into<String>,
+        total: usize,
+        work: impl FnOnce(JobContext<U>) + Send + 'static,
+    ) -> Self {
+        let cancel = Arc::new(AtomicBool::new(false));
+        let done = Arc::new(AtomicUsize::new(0));
+        let (tx, rx) = channel();
+        let context = JobContext {
+            cancel: Arc::clone(&cancel),
+            done: Arc::clone(&done),
+            tx,
+        };
+        let handle = thread::spawn(move || work(context));
+
+        Self {
+            name: name.into(),
+            started: Instant::now(),
+            total,
+            cancel,
+            done,
+            rx,
+            handle: Some(handle),
+            finished: false,
+        }
+    }
+}
+
+impl<U> Job<U> {
+    /// Run `work` on the calling thread instead and return the updates it
+    /// sent, for work too small to be worth a thread.
+    pub fn run_here(work: impl FnOnce(JobContext<U>)) -> Vec<U> {
+        let (tx, rx) = channel();
+        work(JobContext {
+            cancel: Arc::new(AtomicBool::new(false)),
+            done: Arc::new(AtomicUsize::new(0)),
+            tx,
+        });
+        rx.try_iter().collect()
+    }
+
+    /// Name shown in the jobs overlay.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// When the job was started.
+    pub fn started(&self) -> Instant {
+        self.started
+    }
+
+    /// Drain updates sent since the last call.
+    ///
+    /// Once the worker has exited and its last updates are returned here,
+    /// [`Job::is_finished`] turns true.
+    pub fn poll(&mut self) -> Vec<U> {
+        let exited = self
+            .handle
+            .as_ref()
+            .is_none_or(|handle| handle.is_finished());
+        let updates = self.rx.try_iter().collect();
+        self.finished = exited;
+        updates
+    }
+
+    /// Whether the work is done and every update has been polled.
+    pub fn is_finished(&self) -> bool {
+        self.finished
+    }
+
+    /// Units of work done so far.
+    pub fn done(&self) -> usize {
+        self.done.load(Ordering::Relaxed)
+    }
+
+    /// Progress in percent.
+    pub fn percent(&self) -> usize {
+        (self.done().min(self.total) * 100)
+            .checked_div(self.total)
+            .unwrap_or(100)
+    }
+
+    /// Stop the worker and wait for it to exit.
+    pub fn cancel(mut self) {
+        self.stop();
+    }
+
+    fn stop(&mut self) {
+        self.cancel.store(true, Ordering::Relaxed);
+        if let Some(handle) = self.handle.take() {
+            let _ = handle.join();
+        }
+    }
+}
+
+impl<U> Drop for Job<U> {
+    fn drop(&mut self) {
+        self.stop();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    fn wait<U>(job: &mut Job<U>) -> Vec<U> {
+        let mut updates = Vec::new();
+        while !job.is_finished() {
+            updates.extend(job.poll());
+            thread::sleep(Duration::from_millis(1));
+        }
+        updates
+    }
+
+    #[test]
+    fn test_job_reports_progress_and_updates() {
+        let mut job = Job::spawn("count", 10, |ctx| {
+            for i in 0..10 {
+                ctx.advance(1);
+                ctx.send(i);
+            }
+        });
+        assert_eq!(job.name(), "count");
+
+        let updates = wait(&mut job);
+        assert_eq!(updates, (0..10).collect::<Vec<_>>());
+        assert_eq!(job.percent(), 100);
+    }
+
+    #[test]
+    fn test_run_here_returns_updates() {
+        let updates = Job::run_here(|ctx| {
+            ctx.advance(1);
+            ctx.send("done");
+        });
+        assert_eq!(updates, vec!["done"]);
+    }
+
+    #[test]
+    fn test_cancel_stops_worker() {
+        let (started_tx, started_rx) = channel();
+        let job: Job<()> = Job::spawn("spin", 1, move |ctx| {
+            started_tx.send(()).unwrap();
+            while !ctx.is_cancelled() {
+                thread::sleep(Duration::from_millis(1));
+            }
+        });
+        started_rx.recv().unwrap();
+        assert_eq!(job.percent(), 0);
+        // Returns once the worker has seen the flag
+        job.cancel();
+    }
+}
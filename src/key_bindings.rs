@@ -1,4 +1,6 @@
-use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers, MouseButton, MouseEvent, MouseEventKind};
+
+use crate::model::{FilterKind, LogLevel};
 
 /// Application input modes.
 #[derive(Debug, Clone, Copy, PartialEq)]
@@ -7,6 +9,20 @@ pub enum Mode {
     FilterList,
     Command,
     SearchInput,
+    Recent,
+    Prompt,
+    Pins,
+    Bookmarks,
+    Errors,
+    Pods,
+    Detail,
+    Hex,
+    Sql,
+    Stats,
+    Exceptions,
+    Gaps,
+    Messages,
+    Help,
 }
 
 /// Messages representing user actions.
@@ -19,6 +35,7 @@ pub enum Msg {
     ScrollLeft,
     GoToBottom,
     GoToTop,
+    JumpToLastRead,
 
     // Command mode
     EnterCommand,
@@ -27,6 +44,10 @@ pub enum Msg {
     CommandTypeChar(char),
     CommandBackspace,
     CommandComplete,
+    /// Put the previous matching command from the history in the command line
+    CommandHistoryOlder,
+    /// Put the next matching command from the history in the command line
+    CommandHistoryNewer,
 
     // Search
     EnterSearch,
@@ -37,26 +58,304 @@ pub enum Msg {
     NextMatch,
     PrevMatch,
     ClearSearch,
+    PopSearch,
 
     // Selection
     ToggleSelection,
+    /// Show or hide the lines of a level
+    ToggleLevel(LogLevel),
+    /// Select every line with a match of the current search
+    SelectMatches,
     YankSelection,
     ClearSelection,
 
+    // Bookmarks
+    ToggleBookmark,
+    OpenBookmarkList,
+    BookmarkListDown,
+    BookmarkListUp,
+    RemoveBookmark,
+    JumpToBookmark,
+    CloseBookmarkList,
+
+    // Error list
+    /// `[` or `]`, completed by the next key
+    Bracket(char),
+    NextError,
+    PrevError,
+    ErrorListDown,
+    ErrorListUp,
+    JumpToError,
+    CloseErrorList,
+
+    // Tabs
+    NextTab,
+    PrevTab,
+
+    // Pod list
+    PodListDown,
+    PodListUp,
+    TogglePodPause,
+    TogglePodMute,
+    ClosePodList,
+
+    // Pins
+    TogglePin,
+    PinListDown,
+    PinListUp,
+    MovePinDown,
+    MovePinUp,
+    RemovePin,
+    JumpToPin,
+    ClosePinList,
+
+    // Detail pane
+    OpenDetail,
+    DetailDown,
+    DetailUp,
+    ToggleFold,
+    ExpandAll,
+    CollapseAll,
+    YankField,
+    FilterField(FilterKind),
+    CloseDetail,
+
+    // Hex view
+    OpenHex,
+    HexDown,
+    HexUp,
+    HexTop,
+    HexBottom,
+    CloseHex,
+
+    // SQL query results
+    SqlDown,
+    SqlUp,
+    JumpToSqlRow,
+    FilterSqlRow(FilterKind),
+    CloseSql,
+
+    // Stats overlay
+    StatsDown,
+    StatsUp,
+    DrillDown,
+    CloseStats,
+
+    // Exception groups
+    ExceptionsDown,
+    ExceptionsUp,
+    JumpToException,
+    CloseExceptions,
+
+    // Gap histogram
+    CloseGaps,
+
+    // Message log
+    MessagesDown,
+    MessagesUp,
+    CloseMessages,
+
+    // Help overlay
+    OpenHelp,
+    HelpDown,
+    HelpUp,
+    HelpPageDown,
+    HelpPageUp,
+    CloseHelp,
+
+    // Hidden lines
+    HideLines,
+    RestoreHidden,
+
+    // Filter proposed from the selection
+    ProposeFilter(FilterKind),
+
     // Filter list
     FilterListDown,
     FilterListUp,
     DeleteSelectedFilter,
+    ToggleSelectedFilter,
     CloseFilterList,
 
+    // Recent files
+    RecentDown,
+    RecentUp,
+    OpenRecent,
+    CloseRecent,
+
+    // Prompt overlay
+    PromptDown,
+    PromptUp,
+    PromptAccept,
+    /// Key of a choice, which picks it
+    PromptKey(char),
+    PromptCancel,
+
+    // Mouse
+    WheelDown,
+    WheelUp,
+    /// Left button pressed at a screen position
+    Click {
+        column: u16,
+        row: u16,
+    },
+    /// Mouse moved with the left button held
+    Drag {
+        column: u16,
+        row: u16,
+    },
+
     // View options
     ToggleWrap,
+    ToggleFollow,
+    /// Cycle how searches treat case: ignore, smart, match
+    ToggleCase,
+    TogglePerf,
 
     // Application
     Quit,
     NoOp,
 }
 
+impl Msg {
+    /// What the message does, as the help overlay lists it. None for
+    /// typed characters, key prefixes and mouse events, which have no key
+    /// of their own to list.
+    pub fn description(&self) -> Option<String> {
+        let text = match self {
+            Msg::ScrollDown => "Move down",
+            Msg::ScrollUp => "Move up",
+            Msg::ScrollRight => "Scroll right",
+            Msg::ScrollLeft => "Scroll left",
+            Msg::GoToBottom => "Go to the bottom",
+            Msg::GoToTop => "Go to the top",
+            Msg::JumpToLastRead => "Jump below the last-read marker",
+            Msg::EnterCommand => "Enter a command",
+            Msg::CancelCommand => "Cancel",
+            Msg::SubmitCommand => "Run the command",
+            Msg::CommandBackspace => "Delete a character",
+            Msg::CommandComplete => "Complete the command name",
+            Msg::CommandHistoryOlder => "Earlier command from the history",
+            Msg::CommandHistoryNewer => "Later command from the history",
+            Msg::EnterSearch => "Search",
+            Msg::CancelSearch => "Cancel",
+            Msg::SubmitSearch => "Run the search",
+            Msg::SearchBackspace => "Delete a character",
+            Msg::NextMatch => "Next match",
+            Msg::PrevMatch => "Previous match",
+            Msg::ClearSearch => "Clear the search",
+            Msg::PopSearch => "Go back one level of a refined search",
+            Msg::ToggleSelection => "Start or extend the selection",
+            Msg::ToggleLevel(level) => {
+                return Some(format!("Show or hide {} lines", level.name()));
+            }
+            Msg::SelectMatches => "Select every line with a search match",
+            Msg::YankSelection => "Copy the selection or the line",
+            Msg::ClearSelection => "Clear the selection, cancel the newest job",
+            Msg::ToggleBookmark => "Bookmark the line or the selection",
+            Msg::OpenBookmarkList => "Open the bookmark list",
+            Msg::BookmarkListDown => "Next bookmark",
+            Msg::BookmarkListUp => "Previous bookmark",
+            Msg::RemoveBookmark => "Remove the bookmark",
+            Msg::JumpToBookmark => "Go to the line",
+            Msg::CloseBookmarkList => "Close",
+            Msg::NextError => "Next error in view",
+            Msg::PrevError => "Previous error in view",
+            Msg::ErrorListDown => "Next error",
+            Msg::ErrorListUp => "Previous error",
+            Msg::JumpToError => "Go to the line",
+            Msg::CloseErrorList => "Close",
+            Msg::NextTab => "Next tab",
+            Msg::PrevTab => "Previous tab",
+            Msg::PodListDown => "Next pod",
+            Msg::PodListUp => "Previous pod",
+            Msg::TogglePodPause => "Pause or resume the pod",
+            Msg::TogglePodMute => "Mute or unmute the pod",
+            Msg::ClosePodList => "Close",
+            Msg::TogglePin => "Pin or unpin the line or the selection",
+            Msg::PinListDown => "Next pin",
+            Msg::PinListUp => "Previous pin",
+            Msg::MovePinDown => "Move the pin down",
+            Msg::MovePinUp => "Move the pin up",
+            Msg::RemovePin => "Unpin",
+            Msg::JumpToPin => "Go to the line",
+            Msg::ClosePinList => "Close",
+            Msg::OpenDetail => "Open the detail pane",
+            Msg::DetailDown => "Next field",
+            Msg::DetailUp => "Previous field",
+            Msg::ToggleFold => "Fold or unfold the value",
+            Msg::ExpandAll => "Expand all",
+            Msg::CollapseAll => "Collapse all",
+            Msg::YankField => "Copy the field's value",
+            Msg::FilterField(FilterKind::Include) => "Filter by the field",
+            Msg::FilterField(_) => "Filter out the field",
+            Msg::CloseDetail => "Close",
+            Msg::OpenHex => "Open the hex view",
+            Msg::HexDown => "Scroll down",
+            Msg::HexUp => "Scroll up",
+            Msg::HexTop => "Go to the top",
+            Msg::HexBottom => "Go to the bottom",
+            Msg::CloseHex => "Close",
+            Msg::SqlDown => "Next row",
+            Msg::SqlUp => "Previous row",
+            Msg::JumpToSqlRow => "Go to the row's line",
+            Msg::FilterSqlRow(FilterKind::Include) => "Filter by the row",
+            Msg::FilterSqlRow(_) => "Filter out the row",
+            Msg::CloseSql => "Close",
+            Msg::StatsDown => "Next row",
+            Msg::StatsUp => "Previous row",
+            Msg::DrillDown => "Show these lines",
+            Msg::CloseStats => "Close",
+            Msg::ExceptionsDown => "Next exception",
+            Msg::ExceptionsUp => "Previous exception",
+            Msg::JumpToException => "Go to the latest instance",
+            Msg::CloseExceptions => "Close",
+            Msg::CloseGaps => "Close",
+            Msg::MessagesDown => "Scroll down",
+            Msg::MessagesUp => "Scroll up",
+            Msg::CloseMessages => "Close",
+            Msg::OpenHelp => "Show this help",
+            Msg::HelpDown => "Scroll down",
+            Msg::HelpUp => "Scroll up",
+            Msg::HelpPageDown => "Page down",
+            Msg::HelpPageUp => "Page up",
+            Msg::CloseHelp => "Close",
+            Msg::HideLines => "Hide the line or the selection",
+            Msg::RestoreHidden => "Restore hidden lines",
+            Msg::ProposeFilter(FilterKind::Include) => "Propose a filter from the selection",
+            Msg::ProposeFilter(_) => "Propose an exclude filter from the selection",
+            Msg::FilterListDown => "Next filter",
+            Msg::FilterListUp => "Previous filter",
+            Msg::DeleteSelectedFilter => "Delete the filter",
+            Msg::ToggleSelectedFilter => "Enable or disable the filter",
+            Msg::CloseFilterList => "Close",
+            Msg::RecentDown => "Next file set",
+            Msg::RecentUp => "Previous file set",
+            Msg::OpenRecent => "Open the files",
+            Msg::CloseRecent => "Close",
+            Msg::PromptDown => "Next choice",
+            Msg::PromptUp => "Previous choice",
+            Msg::PromptAccept => "Pick the highlighted choice",
+            Msg::PromptCancel => "Cancel",
+            Msg::ToggleWrap => "Toggle wrap mode",
+            Msg::ToggleFollow => "Toggle follow mode",
+            Msg::ToggleCase => "Cycle search case: ignore, smart, match",
+            Msg::TogglePerf => "Toggle the performance overlay",
+            Msg::Quit => "Quit",
+            Msg::CommandTypeChar(_)
+            | Msg::SearchTypeChar(_)
+            | Msg::PromptKey(_)
+            | Msg::Bracket(_)
+            | Msg::WheelDown
+            | Msg::WheelUp
+            | Msg::Click { .. }
+            | Msg::Drag { .. }
+            | Msg::NoOp => return None,
+        };
+        Some(text.to_string())
+    }
+}
+
 /// Translate a key event into a message based on current mode.
 pub fn translate(key: KeyEvent, mode: Mode) -> Option<Msg> {
     match mode {
@@ -64,6 +363,20 @@ pub fn translate(key: KeyEvent, mode: Mode) -> Option<Msg> {
         Mode::Command => translate_command(key),
         Mode::FilterList => translate_filter_list(key),
         Mode::SearchInput => translate_search(key),
+        Mode::Recent => translate_recent(key),
+        Mode::Prompt => translate_prompt(key),
+        Mode::Pins => translate_pins(key),
+        Mode::Bookmarks => translate_bookmarks(key),
+        Mode::Errors => translate_errors(key),
+        Mode::Pods => translate_pods(key),
+        Mode::Detail => translate_detail(key),
+        Mode::Hex => translate_hex(key),
+        Mode::Sql => translate_sql(key),
+        Mode::Stats => translate_stats(key),
+        Mode::Exceptions => translate_exceptions(key),
+        Mode::Gaps => translate_gaps(key),
+        Mode::Messages => translate_messages(key),
+        Mode::Help => translate_help(key),
     }
 }
 
@@ -85,14 +398,34 @@ fn translate_normal(key: KeyEvent) -> Option<Msg> {
         KeyCode::Char('h') | KeyCode::Left => Some(Msg::ScrollLeft),
         KeyCode::Char('G') => Some(Msg::GoToBottom),
         KeyCode::Char('g') => Some(Msg::GoToTop),
+        KeyCode::Char('`') => Some(Msg::JumpToLastRead),
+        KeyCode::Char('\'') => Some(Msg::OpenBookmarkList),
         KeyCode::Char(':') => Some(Msg::EnterCommand),
         KeyCode::Char('w') => Some(Msg::ToggleWrap),
+        KeyCode::Char('F') => Some(Msg::ToggleFollow),
         KeyCode::Char('x') => Some(Msg::ToggleSelection),
+        KeyCode::Char('%') => Some(Msg::SelectMatches),
+        KeyCode::Char(c @ '1'..='5') => {
+            Some(Msg::ToggleLevel(LogLevel::ALL[c as usize - '1' as usize]))
+        }
         KeyCode::Char('y') => Some(Msg::YankSelection),
         KeyCode::Esc => Some(Msg::ClearSelection),
         KeyCode::Char('/') => Some(Msg::EnterSearch),
         KeyCode::Char('n') => Some(Msg::NextMatch),
         KeyCode::Char('N') => Some(Msg::PrevMatch),
+        KeyCode::Backspace => Some(Msg::PopSearch),
+        KeyCode::Char(c @ ('[' | ']')) => Some(Msg::Bracket(c)),
+        KeyCode::Char('m') => Some(Msg::ToggleBookmark),
+        KeyCode::Char('d') => Some(Msg::HideLines),
+        KeyCode::Char('p') => Some(Msg::TogglePin),
+        KeyCode::Enter | KeyCode::Char('i') => Some(Msg::OpenDetail),
+        KeyCode::Char('X') => Some(Msg::OpenHex),
+        KeyCode::Char('U') => Some(Msg::RestoreHidden),
+        KeyCode::Char('+') => Some(Msg::ProposeFilter(FilterKind::Include)),
+        KeyCode::Char('-') => Some(Msg::ProposeFilter(FilterKind::Exclude)),
+        KeyCode::F(12) => Some(Msg::TogglePerf),
+        KeyCode::Char('?') => Some(Msg::OpenHelp),
+        KeyCode::Char('c') => Some(Msg::ToggleCase),
         _ => None,
     }
 }
@@ -108,6 +441,8 @@ fn translate_command(key: KeyEvent) -> Option<Msg> {
         KeyCode::Enter => Some(Msg::SubmitCommand),
         KeyCode::Backspace => Some(Msg::CommandBackspace),
         KeyCode::Tab => Some(Msg::CommandComplete),
+        KeyCode::Up => Some(Msg::CommandHistoryOlder),
+        KeyCode::Down => Some(Msg::CommandHistoryNewer),
         KeyCode::Char(c) => Some(Msg::CommandTypeChar(c)),
         _ => None,
     }
@@ -128,11 +463,337 @@ fn translate_filter_list(key: KeyEvent) -> Option<Msg> {
         KeyCode::Char('j') | KeyCode::Down => Some(Msg::FilterListDown),
         KeyCode::Char('k') | KeyCode::Up => Some(Msg::FilterListUp),
         KeyCode::Char('d') => Some(Msg::DeleteSelectedFilter),
+        KeyCode::Char(' ') => Some(Msg::ToggleSelectedFilter),
         KeyCode::Char('q') | KeyCode::Esc | KeyCode::Enter => Some(Msg::CloseFilterList),
         _ => None,
     }
 }
 
+fn translate_recent(key: KeyEvent) -> Option<Msg> {
+    // Handle Ctrl+C for quit (consistent with Normal mode)
+    if key.code == KeyCode::Char('c') && key.modifiers.contains(KeyModifiers::CONTROL) {
+        return Some(Msg::Quit);
+    }
+
+    // Only process keys without modifiers (except Shift)
+    if !key.modifiers.is_empty() && !key.modifiers.contains(KeyModifiers::SHIFT) {
+        return None;
+    }
+
+    match key.code {
+        KeyCode::Char('j') | KeyCode::Down => Some(Msg::RecentDown),
+        KeyCode::Char('k') | KeyCode::Up => Some(Msg::RecentUp),
+        KeyCode::Enter => Some(Msg::OpenRecent),
+        KeyCode::Char('q') | KeyCode::Esc => Some(Msg::CloseRecent),
+        _ => None,
+    }
+}
+
+fn translate_pins(key: KeyEvent) -> Option<Msg> {
+    // Handle Ctrl+C for quit (consistent with Normal mode)
+    if key.code == KeyCode::Char('c') && key.modifiers.contains(KeyModifiers::CONTROL) {
+        return Some(Msg::Quit);
+    }
+
+    // Only process keys without modifiers (except Shift)
+    if !key.modifiers.is_empty() && !key.modifiers.contains(KeyModifiers::SHIFT) {
+        return None;
+    }
+
+    match key.code {
+        KeyCode::Char('j') | KeyCode::Down => Some(Msg::PinListDown),
+        KeyCode::Char('k') | KeyCode::Up => Some(Msg::PinListUp),
+        KeyCode::Char('J') => Some(Msg::MovePinDown),
+        KeyCode::Char('K') => Some(Msg::MovePinUp),
+        KeyCode::Char('d') => Some(Msg::RemovePin),
+        KeyCode::Enter => Some(Msg::JumpToPin),
+        KeyCode::Char('q') | KeyCode::Esc => Some(Msg::ClosePinList),
+        _ => None,
+    }
+}
+
+fn translate_bookmarks(key: KeyEvent) -> Option<Msg> {
+    // Handle Ctrl+C for quit (consistent with Normal mode)
+    if key.code == KeyCode::Char('c') && key.modifiers.contains(KeyModifiers::CONTROL) {
+        return Some(Msg::Quit);
+    }
+
+    // Only process keys without modifiers (except Shift)
+    if !key.modifiers.is_empty() && !key.modifiers.contains(KeyModifiers::SHIFT) {
+        return None;
+    }
+
+    match key.code {
+        KeyCode::Char('j') | KeyCode::Down => Some(Msg::BookmarkListDown),
+        KeyCode::Char('k') | KeyCode::Up => Some(Msg::BookmarkListUp),
+        KeyCode::Char('d') => Some(Msg::RemoveBookmark),
+        KeyCode::Enter => Some(Msg::JumpToBookmark),
+        KeyCode::Char('q') | KeyCode::Esc | KeyCode::Char('\'') => Some(Msg::CloseBookmarkList),
+        _ => None,
+    }
+}
+
+/// Translate a mouse event into a message based on current mode.
+///
+/// The wheel scrolls the log view or moves through the filter list; clicks
+/// and drags carry their position, which the app maps to a line or filter.
+pub fn translate_mouse(event: MouseEvent, mode: Mode) -> Option<Msg> {
+    let (column, row) = (event.column, event.row);
+    match (mode, event.kind) {
+        (Mode::Normal, MouseEventKind::ScrollDown) => Some(Msg::WheelDown),
+        (Mode::Normal, MouseEventKind::ScrollUp) => Some(Msg::WheelUp),
+        (Mode::FilterList, MouseEventKind::ScrollDown) => Some(Msg::FilterListDown),
+        (Mode::FilterList, MouseEventKind::ScrollUp) => Some(Msg::FilterListUp),
+        (Mode::Normal | Mode::FilterList, MouseEventKind::Down(MouseButton::Left)) => {
+            Some(Msg::Click { column, row })
+        }
+        (Mode::Normal, MouseEventKind::Drag(MouseButton::Left)) => Some(Msg::Drag { column, row }),
+        _ => None,
+    }
+}
+
+/// Translate the key after `g` in normal mode: `gt`/`gT` switch to the
+/// next and previous tab.
+pub fn translate_g(key: KeyEvent) -> Option<Msg> {
+    match key.code {
+        KeyCode::Char('t') => Some(Msg::NextTab),
+        KeyCode::Char('T') => Some(Msg::PrevTab),
+        _ => None,
+    }
+}
+
+/// Translate the key after `[` or `]` in normal mode: `]q`/`[q` step
+/// through the error list.
+pub fn translate_bracket(prefix: char, key: KeyEvent) -> Option<Msg> {
+    match (prefix, key.code) {
+        (']', KeyCode::Char('q')) => Some(Msg::NextError),
+        ('[', KeyCode::Char('q')) => Some(Msg::PrevError),
+        _ => None,
+    }
+}
+
+fn translate_errors(key: KeyEvent) -> Option<Msg> {
+    // Handle Ctrl+C for quit (consistent with Normal mode)
+    if key.code == KeyCode::Char('c') && key.modifiers.contains(KeyModifiers::CONTROL) {
+        return Some(Msg::Quit);
+    }
+
+    // Only process keys without modifiers (except Shift)
+    if !key.modifiers.is_empty() && !key.modifiers.contains(KeyModifiers::SHIFT) {
+        return None;
+    }
+
+    match key.code {
+        KeyCode::Char('j') | KeyCode::Down => Some(Msg::ErrorListDown),
+        KeyCode::Char('k') | KeyCode::Up => Some(Msg::ErrorListUp),
+        KeyCode::Enter => Some(Msg::JumpToError),
+        KeyCode::Char('q') | KeyCode::Esc => Some(Msg::CloseErrorList),
+        _ => None,
+    }
+}
+
+fn translate_pods(key: KeyEvent) -> Option<Msg> {
+    // Handle Ctrl+C for quit (consistent with Normal mode)
+    if key.code == KeyCode::Char('c') && key.modifiers.contains(KeyModifiers::CONTROL) {
+        return Some(Msg::Quit);
+    }
+
+    // Only process keys without modifiers (except Shift)
+    if !key.modifiers.is_empty() && !key.modifiers.contains(KeyModifiers::SHIFT) {
+        return None;
+    }
+
+    match key.code {
+        KeyCode::Char('j') | KeyCode::Down => Some(Msg::PodListDown),
+        KeyCode::Char('k') | KeyCode::Up => Some(Msg::PodListUp),
+        KeyCode::Char('p') | KeyCode::Char(' ') => Some(Msg::TogglePodPause),
+        KeyCode::Char('m') => Some(Msg::TogglePodMute),
+        KeyCode::Char('q') | KeyCode::Esc | KeyCode::Enter => Some(Msg::ClosePodList),
+        _ => None,
+    }
+}
+
+fn translate_hex(key: KeyEvent) -> Option<Msg> {
+    // Handle Ctrl+C for quit (consistent with Normal mode)
+    if key.code == KeyCode::Char('c') && key.modifiers.contains(KeyModifiers::CONTROL) {
+        return Some(Msg::Quit);
+    }
+
+    // Only process keys without modifiers (except Shift)
+    if !key.modifiers.is_empty() && !key.modifiers.contains(KeyModifiers::SHIFT) {
+        return None;
+    }
+
+    match key.code {
+        KeyCode::Char('j') | KeyCode::Down => Some(Msg::HexDown),
+        KeyCode::Char('k') | KeyCode::Up => Some(Msg::HexUp),
+        KeyCode::Char('g') => Some(Msg::HexTop),
+        KeyCode::Char('G') => Some(Msg::HexBottom),
+        KeyCode::Char('q') | KeyCode::Char('X') | KeyCode::Esc => Some(Msg::CloseHex),
+        _ => None,
+    }
+}
+
+fn translate_sql(key: KeyEvent) -> Option<Msg> {
+    // Handle Ctrl+C for quit (consistent with Normal mode)
+    if key.code == KeyCode::Char('c') && key.modifiers.contains(KeyModifiers::CONTROL) {
+        return Some(Msg::Quit);
+    }
+
+    // Only process keys without modifiers (except Shift)
+    if !key.modifiers.is_empty() && !key.modifiers.contains(KeyModifiers::SHIFT) {
+        return None;
+    }
+
+    match key.code {
+        KeyCode::Char('j') | KeyCode::Down => Some(Msg::SqlDown),
+        KeyCode::Char('k') | KeyCode::Up => Some(Msg::SqlUp),
+        KeyCode::Enter => Some(Msg::JumpToSqlRow),
+        KeyCode::Char('+') => Some(Msg::FilterSqlRow(FilterKind::Include)),
+        KeyCode::Char('-') => Some(Msg::FilterSqlRow(FilterKind::Exclude)),
+        KeyCode::Char('q') | KeyCode::Esc => Some(Msg::CloseSql),
+        _ => None,
+    }
+}
+
+fn translate_stats(key: KeyEvent) -> Option<Msg> {
+    // Handle Ctrl+C for quit (consistent with Normal mode)
+    if key.code == KeyCode::Char('c') && key.modifiers.contains(KeyModifiers::CONTROL) {
+        return Some(Msg::Quit);
+    }
+
+    // Only process keys without modifiers (except Shift)
+    if !key.modifiers.is_empty() && !key.modifiers.contains(KeyModifiers::SHIFT) {
+        return None;
+    }
+
+    match key.code {
+        KeyCode::Char('j') | KeyCode::Down => Some(Msg::StatsDown),
+        KeyCode::Char('k') | KeyCode::Up => Some(Msg::StatsUp),
+        KeyCode::Enter => Some(Msg::DrillDown),
+        KeyCode::Char('q') | KeyCode::Esc => Some(Msg::CloseStats),
+        _ => None,
+    }
+}
+
+fn translate_exceptions(key: KeyEvent) -> Option<Msg> {
+    // Handle Ctrl+C for quit (consistent with Normal mode)
+    if key.code == KeyCode::Char('c') && key.modifiers.contains(KeyModifiers::CONTROL) {
+        return Some(Msg::Quit);
+    }
+
+    // Only process keys without modifiers (except Shift)
+    if !key.modifiers.is_empty() && !key.modifiers.contains(KeyModifiers::SHIFT) {
+        return None;
+    }
+
+    match key.code {
+        KeyCode::Char('j') | KeyCode::Down => Some(Msg::ExceptionsDown),
+        KeyCode::Char('k') | KeyCode::Up => Some(Msg::ExceptionsUp),
+        KeyCode::Enter => Some(Msg::JumpToException),
+        KeyCode::Char('q') | KeyCode::Esc => Some(Msg::CloseExceptions),
+        _ => None,
+    }
+}
+
+fn translate_gaps(key: KeyEvent) -> Option<Msg> {
+    // Handle Ctrl+C for quit (consistent with Normal mode)
+    if key.code == KeyCode::Char('c') && key.modifiers.contains(KeyModifiers::CONTROL) {
+        return Some(Msg::Quit);
+    }
+
+    match key.code {
+        KeyCode::Char('q') | KeyCode::Esc => Some(Msg::CloseGaps),
+        _ => None,
+    }
+}
+
+fn translate_messages(key: KeyEvent) -> Option<Msg> {
+    // Handle Ctrl+C for quit (consistent with Normal mode)
+    if key.code == KeyCode::Char('c') && key.modifiers.contains(KeyModifiers::CONTROL) {
+        return Some(Msg::Quit);
+    }
+
+    // Only process keys without modifiers (except Shift)
+    if !key.modifiers.is_empty() && !key.modifiers.contains(KeyModifiers::SHIFT) {
+        return None;
+    }
+
+    match key.code {
+        KeyCode::Char('j') | KeyCode::Down => Some(Msg::MessagesDown),
+        KeyCode::Char('k') | KeyCode::Up => Some(Msg::MessagesUp),
+        KeyCode::Char('q') | KeyCode::Esc => Some(Msg::CloseMessages),
+        _ => None,
+    }
+}
+
+fn translate_help(key: KeyEvent) -> Option<Msg> {
+    // Handle Ctrl+C for quit (consistent with Normal mode)
+    if key.code == KeyCode::Char('c') && key.modifiers.contains(KeyModifiers::CONTROL) {
+        return Some(Msg::Quit);
+    }
+
+    // Only process keys without modifiers (except Shift)
+    if !key.modifiers.is_empty() && !key.modifiers.contains(KeyModifiers::SHIFT) {
+        return None;
+    }
+
+    match key.code {
+        KeyCode::Char('j') | KeyCode::Down => Some(Msg::HelpDown),
+        KeyCode::Char('k') | KeyCode::Up => Some(Msg::HelpUp),
+        KeyCode::Char(' ') | KeyCode::PageDown => Some(Msg::HelpPageDown),
+        KeyCode::PageUp => Some(Msg::HelpPageUp),
+        KeyCode::Char('q') | KeyCode::Char('?') | KeyCode::Esc => Some(Msg::CloseHelp),
+        _ => None,
+    }
+}
+
+fn translate_detail(key: KeyEvent) -> Option<Msg> {
+    // Handle Ctrl+C for quit (consistent with Normal mode)
+    if key.code == KeyCode::Char('c') && key.modifiers.contains(KeyModifiers::CONTROL) {
+        return Some(Msg::Quit);
+    }
+
+    // Only process keys without modifiers (except Shift)
+    if !key.modifiers.is_empty() && !key.modifiers.contains(KeyModifiers::SHIFT) {
+        return None;
+    }
+
+    match key.code {
+        KeyCode::Char('j') | KeyCode::Down => Some(Msg::DetailDown),
+        KeyCode::Char('k') | KeyCode::Up => Some(Msg::DetailUp),
+        KeyCode::Enter | KeyCode::Char(' ') => Some(Msg::ToggleFold),
+        KeyCode::Char('E') => Some(Msg::ExpandAll),
+        KeyCode::Char('C') => Some(Msg::CollapseAll),
+        KeyCode::Char('y') => Some(Msg::YankField),
+        KeyCode::Char('+') => Some(Msg::FilterField(FilterKind::Include)),
+        KeyCode::Char('-') => Some(Msg::FilterField(FilterKind::Exclude)),
+        KeyCode::Char('q') | KeyCode::Char('i') | KeyCode::Esc => Some(Msg::CloseDetail),
+        _ => None,
+    }
+}
+
+fn translate_prompt(key: KeyEvent) -> Option<Msg> {
+    // Handle Ctrl+C for quit (consistent with Normal mode)
+    if key.code == KeyCode::Char('c') && key.modifiers.contains(KeyModifiers::CONTROL) {
+        return Some(Msg::Quit);
+    }
+
+    // Only process keys without modifiers (except Shift)
+    if !key.modifiers.is_empty() && !key.modifiers.contains(KeyModifiers::SHIFT) {
+        return None;
+    }
+
+    match key.code {
+        KeyCode::Char('j') | KeyCode::Down | KeyCode::Tab => Some(Msg::PromptDown),
+        KeyCode::Char('k') | KeyCode::Up | KeyCode::BackTab => Some(Msg::PromptUp),
+        KeyCode::Enter => Some(Msg::PromptAccept),
+        KeyCode::Char('q') | KeyCode::Esc => Some(Msg::PromptCancel),
+        KeyCode::Char(c) => Some(Msg::PromptKey(c)),
+        _ => None,
+    }
+}
+
 fn translate_search(key: KeyEvent) -> Option<Msg> {
     // Handle Ctrl+C for quit (consistent with Normal mode)
     if key.code == KeyCode::Char('c') && key.modifiers.contains(KeyModifiers::CONTROL) {
@@ -169,6 +830,44 @@ mod tests {
         }
     }
 
+    fn mouse(kind: MouseEventKind, row: u16) -> MouseEvent {
+        MouseEvent {
+            kind,
+            column: 5,
+            row,
+            modifiers: KeyModifiers::NONE,
+        }
+    }
+
+    #[test]
+    fn test_mouse_events() {
+        let click = mouse(MouseEventKind::Down(MouseButton::Left), 7);
+        assert_eq!(
+            translate_mouse(click, Mode::Normal),
+            Some(Msg::Click { column: 5, row: 7 })
+        );
+        assert_eq!(
+            translate_mouse(click, Mode::FilterList),
+            Some(Msg::Click { column: 5, row: 7 })
+        );
+        assert_eq!(translate_mouse(click, Mode::Command), None);
+        assert_eq!(
+            translate_mouse(
+                mouse(MouseEventKind::Drag(MouseButton::Left), 9),
+                Mode::Normal
+            ),
+            Some(Msg::Drag { column: 5, row: 9 })
+        );
+        assert_eq!(
+            translate_mouse(mouse(MouseEventKind::ScrollDown, 0), Mode::Normal),
+            Some(Msg::WheelDown)
+        );
+        assert_eq!(
+            translate_mouse(mouse(MouseEventKind::ScrollUp, 0), Mode::FilterList),
+            Some(Msg::FilterListUp)
+        );
+    }
+
     #[test]
     fn test_normal_mode_navigation() {
         assert_eq!(
@@ -193,6 +892,10 @@ mod tests {
             Some(Msg::GoToBottom)
         );
         assert_eq!(translate(key_char('g'), Mode::Normal), Some(Msg::GoToTop));
+        assert_eq!(
+            translate(key_char('`'), Mode::Normal),
+            Some(Msg::JumpToLastRead)
+        );
     }
 
     #[test]
@@ -205,6 +908,8 @@ mod tests {
             translate(key_char('/'), Mode::Normal),
             Some(Msg::EnterSearch)
         );
+        assert_eq!(translate(key_char('?'), Mode::Normal), Some(Msg::OpenHelp));
+        assert_eq!(translate(key_char('?'), Mode::Help), Some(Msg::CloseHelp));
     }
 
     #[test]
@@ -230,6 +935,14 @@ mod tests {
             translate(key_code(KeyCode::Tab), Mode::Command),
             Some(Msg::CommandComplete)
         );
+        assert_eq!(
+            translate(key_code(KeyCode::Up), Mode::Command),
+            Some(Msg::CommandHistoryOlder)
+        );
+        assert_eq!(
+            translate(key_code(KeyCode::Down), Mode::Command),
+            Some(Msg::CommandHistoryNewer)
+        );
         assert_eq!(
             translate(key_char('a'), Mode::Command),
             Some(Msg::CommandTypeChar('a'))
@@ -270,6 +983,10 @@ mod tests {
             translate(key_char('d'), Mode::FilterList),
             Some(Msg::DeleteSelectedFilter)
         );
+        assert_eq!(
+            translate(key_char(' '), Mode::FilterList),
+            Some(Msg::ToggleSelectedFilter)
+        );
         assert_eq!(
             translate(key_char('q'), Mode::FilterList),
             Some(Msg::CloseFilterList)
@@ -280,12 +997,61 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_recent_mode() {
+        assert_eq!(
+            translate(key_char('j'), Mode::Recent),
+            Some(Msg::RecentDown)
+        );
+        assert_eq!(translate(key_char('k'), Mode::Recent), Some(Msg::RecentUp));
+        assert_eq!(
+            translate(key_code(KeyCode::Enter), Mode::Recent),
+            Some(Msg::OpenRecent)
+        );
+        assert_eq!(
+            translate(key_code(KeyCode::Esc), Mode::Recent),
+            Some(Msg::CloseRecent)
+        );
+    }
+
+    #[test]
+    fn test_prompt_mode() {
+        assert_eq!(
+            translate(key_char('t'), Mode::Prompt),
+            Some(Msg::PromptKey('t'))
+        );
+        assert_eq!(
+            translate(key_char('j'), Mode::Prompt),
+            Some(Msg::PromptDown)
+        );
+        assert_eq!(
+            translate(key_code(KeyCode::Up), Mode::Prompt),
+            Some(Msg::PromptUp)
+        );
+        assert_eq!(
+            translate(key_code(KeyCode::Enter), Mode::Prompt),
+            Some(Msg::PromptAccept)
+        );
+        assert_eq!(
+            translate(key_code(KeyCode::Esc), Mode::Prompt),
+            Some(Msg::PromptCancel)
+        );
+    }
+
     #[test]
     fn test_normal_mode_selection() {
         assert_eq!(
             translate(key_char('x'), Mode::Normal),
             Some(Msg::ToggleSelection)
         );
+        assert_eq!(
+            translate(key_char('%'), Mode::Normal),
+            Some(Msg::SelectMatches)
+        );
+        assert_eq!(
+            translate(key_char('2'), Mode::Normal),
+            Some(Msg::ToggleLevel(LogLevel::Warn))
+        );
         assert_eq!(
             translate(key_char('y'), Mode::Normal),
             Some(Msg::YankSelection)
@@ -294,12 +1060,66 @@ mod tests {
             translate(key_code(KeyCode::Esc), Mode::Normal),
             Some(Msg::ClearSelection)
         );
+        assert_eq!(
+            translate(key_char('+'), Mode::Normal),
+            Some(Msg::ProposeFilter(FilterKind::Include))
+        );
+        assert_eq!(
+            translate(key_char('-'), Mode::Normal),
+            Some(Msg::ProposeFilter(FilterKind::Exclude))
+        );
     }
 
     #[test]
     fn test_normal_mode_search_navigation() {
         assert_eq!(translate(key_char('n'), Mode::Normal), Some(Msg::NextMatch));
         assert_eq!(translate(key_char('N'), Mode::Normal), Some(Msg::PrevMatch));
+        assert_eq!(
+            translate(key_code(KeyCode::Backspace), Mode::Normal),
+            Some(Msg::PopSearch)
+        );
+    }
+
+    #[test]
+    fn test_normal_mode_bookmark() {
+        assert_eq!(
+            translate(key_char('m'), Mode::Normal),
+            Some(Msg::ToggleBookmark)
+        );
+        assert_eq!(
+            translate(key_char('\''), Mode::Normal),
+            Some(Msg::OpenBookmarkList)
+        );
+        assert_eq!(
+            translate(key_code(KeyCode::Enter), Mode::Bookmarks),
+            Some(Msg::JumpToBookmark)
+        );
+        assert_eq!(
+            translate(key_char('d'), Mode::Bookmarks),
+            Some(Msg::RemoveBookmark)
+        );
+        assert_eq!(
+            translate(key_char('\''), Mode::Bookmarks),
+            Some(Msg::CloseBookmarkList)
+        );
+    }
+
+    #[test]
+    fn test_bracket_q_steps_through_errors() {
+        assert_eq!(
+            translate(key_char(']'), Mode::Normal),
+            Some(Msg::Bracket(']'))
+        );
+        assert_eq!(translate_bracket(']', key_char('q')), Some(Msg::NextError));
+        assert_eq!(translate_bracket('[', key_char('q')), Some(Msg::PrevError));
+        assert_eq!(translate_bracket(']', key_char('j')), None);
+    }
+
+    #[test]
+    fn test_g_t_switches_tabs() {
+        assert_eq!(translate_g(key_char('t')), Some(Msg::NextTab));
+        assert_eq!(translate_g(key_char('T')), Some(Msg::PrevTab));
+        assert_eq!(translate_g(key_char('g')), None);
     }
 
     #[test]
@@ -308,11 +1128,15 @@ mod tests {
             translate(key_char('w'), Mode::Normal),
             Some(Msg::ToggleWrap)
         );
+        assert_eq!(
+            translate(key_code(KeyCode::F(12)), Mode::Normal),
+            Some(Msg::TogglePerf)
+        );
     }
 
     #[test]
     fn test_unknown_keys_return_none() {
         assert_eq!(translate(key_char('z'), Mode::Normal), None);
-        assert_eq!(translate(key_char('1'), Mode::Normal), None);
+        assert_eq!(translate(key_char('6'), Mode::Normal), None);
     }
 }
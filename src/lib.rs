@@ -1,12 +1,50 @@
 pub mod app;
+pub mod batch;
 pub mod clipboard;
+pub mod columns;
 pub mod command;
 pub mod config;
+pub mod crash;
+pub mod detail;
+pub mod embed;
+pub mod error;
+pub mod exceptions;
+pub mod export;
+pub mod follow;
+pub mod gaps;
+pub mod generate;
+pub mod glob;
+pub mod help;
+pub mod hex;
+pub mod history;
+pub mod jobs;
 pub mod key_bindings;
+pub mod loader;
 pub mod model;
+pub mod notify;
+pub mod perf;
+pub mod pods;
+pub mod prompt;
+pub mod query;
+pub mod quickfix;
+pub mod recent;
+pub mod replay;
+pub mod search;
+pub mod session;
+pub mod sidecar;
+pub mod source;
+pub mod stats;
 pub mod storage;
+pub mod summary;
+pub mod theme;
+pub mod tutor;
 pub mod ui;
+pub mod watch;
+pub mod workspace;
 
-pub use clipboard::{Clipboard, ClipboardError};
+pub use clipboard::{Clipboard, ClipboardBackend, ClipboardError};
 pub use command::{CommandEffect, CommandResult};
+pub use embed::{run_with_source, run_with_source_in};
+pub use error::QlogError;
 pub use key_bindings::Mode;
+pub use source::{ChannelSource, LineSource};
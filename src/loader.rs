@@ -0,0 +1,343 @@
+//! Background loading of a file set.
+//!
+//! [`spawn`] loads the files of an [`OpenRequest`] on a worker thread and
+//! reports everything that happens as one stream of [`LoadEvent`]s: a file
+//! starting, decompression and indexing progress, a file finished or failed,
+//! and finally the combined storage. The UI drains the stream each tick and
+//! folds the progress events into a [`LoadProgress`] for the loading screen;
+//! the loader itself never touches the terminal.
+
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, Receiver};
+use std::thread;
+use std::time::Duration;
+
+use crate::app::OpenRequest;
+use crate::error::QlogError;
+use crate::model::{IndexMode, LogStorage, OpenProgress};
+use crate::summary::LoadSummary;
+
+const MAX_RETRIES: usize = 3;
+const INITIAL_RETRY_MS: u64 = 100;
+
+/// Something that happened while loading a file set.
+#[derive(Debug)]
+pub enum LoadEvent {
+    /// Loading of file `file` (counted from 1) of `total` began
+    FileStarted {
+        file: usize,
+        total: usize,
+        path: PathBuf,
+    },
+    /// Compressed bytes read and the compressed size of the current file
+    Decompressed { read: u64, total: u64 },
+    /// Lines indexed in the current file so far, the bytes they cover and
+    /// the bytes to index
+    IndexedChunk {
+        lines: usize,
+        bytes: u64,
+        total: u64,
+    },
+    /// The current file is loaded
+    FileDone {
+        path: PathBuf,
+        lines: usize,
+        /// Loaded with a tail-only or on-demand index
+        partial: bool,
+    },
+    /// The current file could not be loaded; the others still are
+    Error { path: PathBuf, message: String },
+    /// Every file is done
+    Completed(Box<LoadResult>),
+}
+
+/// Counts of a finished load.
+#[derive(Debug, Clone, Default)]
+pub struct LoadStats {
+    pub files_loaded: usize,
+    pub files_failed: usize,
+    /// Files loaded with a tail-only or on-demand index
+    pub files_partial: usize,
+    pub entries_loaded: usize,
+}
+
+/// Final result of a load: combined storage, statistics and summary.
+#[derive(Debug)]
+pub struct LoadResult {
+    pub storage: LogStorage,
+    pub stats: LoadStats,
+    pub summary: LoadSummary,
+}
+
+/// Progress of a running load, as shown on the loading screen.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct LoadProgress {
+    /// Lines of the finished files and those indexed in the current one
+    pub lines: usize,
+    /// Lines of the finished files
+    finished_lines: usize,
+    /// Compressed bytes read and the compressed size of the current file
+    pub decompressed: Option<(u64, u64)>,
+    /// Bytes indexed and bytes to index in the current file
+    pub indexed: Option<(u64, u64)>,
+}
+
+impl LoadProgress {
+    /// Fold a progress event in; [`LoadEvent::Completed`] changes nothing.
+    pub fn apply(&mut self, event: &LoadEvent) {
+        match *event {
+            LoadEvent::FileStarted { .. } | LoadEvent::Error { .. } => {
+                self.lines = self.finished_lines;
+                self.decompressed = None;
+                self.indexed = None;
+            }
+            LoadEvent::Decompressed { read, total } => self.decompressed = Some((read, total)),
+            LoadEvent::IndexedChunk {
+                lines,
+                bytes,
+                total,
+            } => {
+                self.lines = self.finished_lines + lines;
+                self.indexed = Some((bytes, total));
+            }
+            LoadEvent::FileDone { lines, .. } => {
+                self.finished_lines += lines;
+                self.lines = self.finished_lines;
+                self.decompressed = None;
+                self.indexed = None;
+            }
+            LoadEvent::Completed(_) => {}
+        }
+    }
+}
+
+/// Load the requested files on a background thread, returning the stream
+/// of events of the load; the last one is [`LoadEvent::Completed`].
+///
+/// With `sidecar`, fully indexed files read and store their line index in
+/// their sidecar database.
+pub fn spawn(request: OpenRequest, sidecar: bool) -> Receiver<LoadEvent> {
+    let (tx, rx) = mpsc::channel();
+
+    thread::spawn(move || {
+        let mut storages: Vec<LogStorage> = Vec::new();
+        let mut stats = LoadStats::default();
+
+        let total = request.files.len();
+        let _span = tracing::info_span!("load", files = total).entered();
+
+        for (file_idx, (path, mode)) in request.files.into_iter().enumerate() {
+            let _ = tx.send(LoadEvent::FileStarted {
+                file: file_idx + 1,
+                total,
+                path: path.clone(),
+            });
+            let _file_span = tracing::debug_span!("load_file", path = %path.display()).entered();
+
+            check_fd_warning();
+
+            let mut report = |progress| {
+                let _ = tx.send(match progress {
+                    OpenProgress::Decompressed { read, total } => {
+                        LoadEvent::Decompressed { read, total }
+                    }
+                    OpenProgress::Indexed {
+                        lines,
+                        bytes,
+                        total,
+                    } => LoadEvent::IndexedChunk {
+                        lines,
+                        bytes,
+                        total,
+                    },
+                });
+            };
+
+            match load_file(&path, mode, sidecar, &mut report) {
+                Ok(storage) => {
+                    let lines = storage.len();
+                    let partial = mode != IndexMode::Full;
+                    stats.files_loaded += 1;
+                    stats.entries_loaded += lines;
+                    if partial {
+                        stats.files_partial += 1;
+                    }
+                    storages.push(storage);
+                    let _ = tx.send(LoadEvent::FileDone {
+                        path,
+                        lines,
+                        partial,
+                    });
+                }
+                Err(message) => {
+                    tracing::warn!("{}", message);
+                    stats.files_failed += 1;
+                    let _ = tx.send(LoadEvent::Error { path, message });
+                }
+            }
+        }
+
+        let storage = LogStorage::merge_by_time(storages);
+        let summary = LoadSummary::new(
+            &storage,
+            stats.files_loaded,
+            stats.files_failed,
+            stats.files_partial,
+        );
+        tracing::info!(
+            lines = storage.len(),
+            loaded = stats.files_loaded,
+            failed = stats.files_failed,
+            "load finished"
+        );
+
+        let _ = tx.send(LoadEvent::Completed(Box::new(LoadResult {
+            storage,
+            stats,
+            summary,
+        })));
+    });
+
+    rx
+}
+
+/// Load one file, retrying with a growing delay while the process is out
+/// of file descriptors.
+fn load_file(
+    path: &Path,
+    mode: IndexMode,
+    sidecar: bool,
+    report: &mut dyn FnMut(OpenProgress),
+) -> Result<LogStorage, String> {
+    let mut attempt = 0;
+    let mut delay = INITIAL_RETRY_MS;
+    loop {
+        let loaded = if sidecar && mode == IndexMode::Full {
+            crate::sidecar::load_storage_with_progress(path, report)
+        } else {
+            LogStorage::from_file_with_progress(path, mode, report)
+        };
+        match loaded {
+            Ok(storage) => return Ok(storage),
+            Err(e) if is_fd_exhaustion_error(&e) && attempt < MAX_RETRIES => {
+                tracing::warn!(
+                    "FD exhaustion on {}, retry {}/{} after {}ms",
+                    path.display(),
+                    attempt + 1,
+                    MAX_RETRIES,
+                    delay
+                );
+                thread::sleep(Duration::from_millis(delay));
+                delay *= 2;
+                attempt += 1;
+            }
+            Err(e) if is_fd_exhaustion_error(&e) => {
+                return Err(format!(
+                    "Failed to load {} after {} retries: FD limit reached. Try: ulimit -n 65536",
+                    path.display(),
+                    MAX_RETRIES
+                ));
+            }
+            Err(e) => return Err(e.to_string()),
+        }
+    }
+}
+
+fn is_fd_exhaustion_error(e: &QlogError) -> bool {
+    e.io_error()
+        .is_some_and(|e| matches!(e.raw_os_error(), Some(24) | Some(23)))
+}
+
+fn get_fd_limit() -> Option<usize> {
+    #[cfg(unix)]
+    {
+        unsafe {
+            let mut rlimit: libc::rlimit = std::mem::zeroed();
+            if libc::getrlimit(libc::RLIMIT_NOFILE, &mut rlimit) == 0 {
+                return Some(rlimit.rlim_cur as usize);
+            }
+        }
+    }
+    None
+}
+
+fn check_fd_warning() {
+    if let Some(limit) = get_fd_limit() {
+        let current = get_fd_count().unwrap_or(0);
+        if current > (limit as f64 * 0.8) as usize {
+            tracing::warn!(
+                "FD usage at {}% ({} of {}). Consider: ulimit -n 65536",
+                (current * 100 / limit),
+                current,
+                limit
+            );
+        }
+    }
+}
+
+#[cfg(unix)]
+fn get_fd_count() -> Option<usize> {
+    std::fs::read_dir("/proc/self/fd").ok().map(|d| d.count())
+}
+
+#[cfg(not(unix))]
+fn get_fd_count() -> Option<usize> {
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+    use tempfile::NamedTempFile;
+
+    #[test]
+    fn test_events_of_a_load() {
+        let mut file = NamedTempFile::new().unwrap();
+        writeln!(file, "one\ntwo\nthree").unwrap();
+        let missing = PathBuf::from("/nonexistent/qlog-test.log");
+        let request = OpenRequest {
+            files: vec![
+                (file.path().to_path_buf(), IndexMode::Full),
+                (missing.clone(), IndexMode::Full),
+            ],
+        };
+
+        let mut progress = LoadProgress::default();
+        let mut kinds = Vec::new();
+        let mut result = None;
+        for event in spawn(request, false) {
+            progress.apply(&event);
+            kinds.push(match &event {
+                LoadEvent::FileStarted { file, .. } => format!("started {}", file),
+                LoadEvent::Decompressed { .. } => "decompressed".to_string(),
+                LoadEvent::IndexedChunk { lines, .. } => format!("indexed {}", lines),
+                LoadEvent::FileDone { lines, .. } => format!("done {}", lines),
+                LoadEvent::Error { path, .. } => format!("error {}", path.display()),
+                LoadEvent::Completed(_) => "completed".to_string(),
+            });
+            if let LoadEvent::Completed(done) = event {
+                result = Some(done);
+            }
+        }
+
+        assert_eq!(
+            kinds,
+            [
+                "started 1",
+                "indexed 3",
+                "done 3",
+                "started 2",
+                "error /nonexistent/qlog-test.log",
+                "completed"
+            ]
+        );
+        assert_eq!(progress.lines, 3);
+        let result = result.unwrap();
+        assert_eq!(result.storage.len(), 3);
+        assert_eq!(
+            (result.stats.files_loaded, result.stats.files_failed),
+            (1, 1)
+        );
+    }
+}
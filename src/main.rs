@@ -1,28 +1,42 @@
 use std::env;
-use std::io;
+use std::fs::File;
+use std::io::{self, BufReader, BufWriter, IsTerminal, Read};
 use std::path::{Path, PathBuf};
-use std::sync::mpsc;
-use std::thread;
+use std::sync::{mpsc, Mutex};
 use std::time::Duration;
 
 use crossterm::{
-    event::{DisableMouseCapture, EnableMouseCapture, Event, KeyCode, KeyEventKind},
+    event::{
+        DisableFocusChange, DisableMouseCapture, EnableFocusChange, EnableMouseCapture, Event,
+        KeyCode, KeyEventKind,
+    },
     execute,
     terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
 };
 use ratatui::{backend::CrosstermBackend, Terminal};
+use tracing_subscriber::fmt::format::FmtSpan;
 use walkdir::WalkDir;
 
+use clap::{Parser, Subcommand};
 use qlog::{
-    app::{App, LoadingStatus},
-    model::LogStorage,
-    storage::loader::LogLoader,
+    app::{App, LoadingStatus, OpenRequest},
+    batch::{self, LineFilter},
+    config::AppConfig,
+    follow::LatestFile,
+    generate::{self, GenerateOptions, LogFormat},
+    key_bindings::Mode,
+    loader::{self, LoadEvent},
+    model::{Context, FilterKind, FilterList, IndexMode, LevelSet},
+    source::ChannelSource,
+    tutor::{self, Tutor},
+    workspace::Workspace,
 };
 
 const DEFAULT_MAX_OPEN_DIRS: usize = 10;
-const MMAP_THRESHOLD: u64 = 10 * 1024 * 1024;
-const MAX_RETRIES: usize = 3;
-const INITIAL_RETRY_MS: u64 = 100;
+/// Event loop tick while the terminal has focus.
+const TICK_RATE: Duration = Duration::from_millis(50);
+/// Slower tick while the terminal is unfocused, to save CPU on long follows.
+const UNFOCUSED_TICK_RATE: Duration = Duration::from_millis(500);
 
 fn get_max_open_dirs() -> usize {
     env::var("COMO_MAX_OPEN_DIRS")
@@ -31,211 +45,560 @@ fn get_max_open_dirs() -> usize {
         .unwrap_or(DEFAULT_MAX_OPEN_DIRS)
 }
 
-fn is_fd_exhaustion_error(e: &(dyn std::error::Error + 'static)) -> bool {
-    e.downcast_ref::<io::Error>()
-        .map(|e| matches!(e.raw_os_error(), Some(24) | Some(23)))
-        .unwrap_or(false)
+/// TUI log viewer with Helix-style keybindings for large log files.
+#[derive(Debug, Parser)]
+#[command(name = "qlog", version, about, args_conflicts_with_subcommands = true)]
+struct Cli {
+    #[command(subcommand)]
+    command: Option<Command>,
+
+    /// Log files, directories or glob patterns; `-` reads standard input
+    files: Vec<String>,
+
+    /// Keep reading new lines as they are written
+    #[arg(short, long)]
+    follow: bool,
+
+    /// Follow the newest file matching PATTERN, switching when a newer one appears
+    #[arg(long, value_name = "PATTERN")]
+    follow_latest: Option<String>,
+
+    /// Index only the end of each file: a number of lines, or a size such as 500M
+    #[arg(long, value_name = "LINES | SIZE", value_parser = parse_tail_value)]
+    tail: Option<IndexMode>,
+
+    /// Show only lines containing PATTERN (repeatable)
+    #[arg(long, value_name = "PATTERN")]
+    filter: Vec<String>,
+
+    /// Hide lines containing PATTERN (repeatable)
+    #[arg(long, value_name = "PATTERN")]
+    filter_out: Vec<String>,
+
+    /// Show only lines of LEVEL: error, warn, info, debug or trace; `warn+`
+    /// adds the more severe levels, `error,info` lists several
+    #[arg(long, value_parser = parse_level)]
+    level: Option<LevelSet>,
+
+    /// Also show LINES lines before and after each line that passes the filters
+    #[arg(short = 'C', long, value_name = "LINES")]
+    context: Option<usize>,
+
+    /// Start with the search for QUERY, or with --print, print only lines containing it
+    #[arg(long, value_name = "QUERY")]
+    search: Option<String>,
+
+    /// Print the lines that pass the filters, level and search to standard
+    /// output instead of opening the viewer
+    #[arg(long)]
+    print: bool,
+
+    /// Read the config from PATH instead of .qlog/qlog.toml or ~/.qlog/qlog.toml
+    #[arg(long, value_name = "PATH")]
+    config: Option<PathBuf>,
+
+    /// Start with theme NAME, built-in or from the config's [themes]
+    #[arg(long, value_name = "NAME")]
+    theme: Option<String>,
+
+    /// Leave the mouse to the terminal, for selecting text to copy
+    #[arg(long)]
+    no_mouse: bool,
+
+    /// Wrap long lines (the default)
+    #[arg(long, overrides_with = "no_wrap")]
+    wrap: bool,
+
+    /// Cut long lines at the window edge
+    #[arg(long, overrides_with = "wrap")]
+    no_wrap: bool,
+
+    /// Open the directories and starting filters of a workspace manifest
+    #[arg(long, value_name = "DIR")]
+    workspace: Option<PathBuf>,
+
+    /// Learn qlog in a guided tutorial on a sample log
+    #[arg(long, conflicts_with_all = ["files", "print", "workspace", "follow_latest"])]
+    tutor: bool,
+
+    /// Write load, filter, search and render timings to FILE
+    #[arg(long, value_name = "FILE")]
+    debug_log: Option<PathBuf>,
 }
 
-fn get_fd_limit() -> Option<usize> {
-    #[cfg(unix)]
-    {
-        unsafe {
-            let mut rlimit: libc::rlimit = std::mem::zeroed();
-            if libc::getrlimit(libc::RLIMIT_NOFILE, &mut rlimit) == 0 {
-                return Some(rlimit.rlim_cur as usize);
-            }
-        }
-    }
-    None
+#[derive(Debug, Subcommand)]
+enum Command {
+    /// Validate the config file without starting the UI
+    Check {
+        /// Config file to check instead of .qlog/qlog.toml or ~/.qlog/qlog.toml
+        config: Option<PathBuf>,
+    },
+    /// Write a synthetic log for demos, benchmarks and bug reports
+    Generate {
+        /// Log entries to write; stack traces add lines
+        #[arg(long, default_value_t = 10_000)]
+        lines: usize,
+        /// Line format: plain, serilog, json or logfmt
+        #[arg(long, default_value = "plain", value_parser = parse_log_format)]
+        format: LogFormat,
+        /// Share of entries that are errors, from 0 to 1
+        #[arg(long, default_value_t = 0.02, value_parser = parse_error_rate)]
+        error_rate: f64,
+        /// Seed for the generator; the same seed gives the same log
+        #[arg(long, default_value_t = generate::DEFAULT_SEED)]
+        seed: u64,
+        /// File to write; standard output if left out or `-`
+        output: Option<PathBuf>,
+    },
 }
 
-fn check_fd_warning() {
-    if let Some(limit) = get_fd_limit() {
-        let current = get_fd_count().unwrap_or(0);
-        if current > (limit as f64 * 0.8) as usize {
-            eprintln!(
-                "Warning: FD usage at {}% ({} of {}). Consider: ulimit -n 65536",
-                (current * 100 / limit),
-                current,
-                limit
-            );
-        }
+impl Cli {
+    /// Whether options set the view (filters, level, search, wrap mode),
+    /// which then takes the place of the saved session.
+    fn sets_view(&self) -> bool {
+        !self.filter.is_empty()
+            || !self.filter_out.is_empty()
+            || self.level.is_some()
+            || self.search.is_some()
+            || self.wrap
+            || self.no_wrap
     }
 }
 
-#[cfg(unix)]
-fn get_fd_count() -> Option<usize> {
-    std::fs::read_dir("/proc/self/fd").ok().map(|d| d.count())
-}
-
-#[cfg(not(unix))]
-fn get_fd_count() -> Option<usize> {
-    None
-}
-
-#[derive(Debug, Clone)]
-pub struct LoadProgress {
-    pub current_file: usize,
-    pub total_files: usize,
-    pub entries_loaded: usize,
-    pub current_path: Option<PathBuf>,
-}
-
-#[derive(Debug, Clone, Default)]
-pub struct LoadStats {
-    pub files_loaded: usize,
-    pub files_failed: usize,
-    pub entries_loaded: usize,
-    pub failed_paths: Vec<PathBuf>,
-}
-
 fn main() -> Result<(), Box<dyn std::error::Error>> {
-    let args: Vec<String> = std::env::args().collect();
-    let max_open_dirs = get_max_open_dirs();
-
-    let (progress_tx, progress_rx): (mpsc::Sender<LoadProgress>, mpsc::Receiver<LoadProgress>) =
-        mpsc::channel();
-    let (logs_tx, logs_rx): (
-        mpsc::Sender<(LogStorage, LoadStats)>,
-        mpsc::Receiver<(LogStorage, LoadStats)>,
-    ) = mpsc::channel();
-    let (incremental_tx, incremental_rx): (mpsc::Sender<LogStorage>, mpsc::Receiver<LogStorage>) =
-        mpsc::channel();
-
-    let args_clone = args.clone();
-    thread::spawn(move || {
-        let loader = LogLoader::new(0); // Will be updated with actual count
-        let mut all_storages: Vec<LogStorage> = Vec::new();
-        let mut stats = LoadStats::default();
-        let mut file_count = 0usize;
-
-        // First pass: collect all paths
-        let paths: Vec<PathBuf> = if args_clone.len() > 1 {
-            collect_paths(&args_clone[1..], max_open_dirs)
-        } else {
-            WalkDir::new(".")
-                .max_open(max_open_dirs)
-                .into_iter()
-                .filter_map(|e| e.ok())
-                .filter(|e| e.file_type().is_file())
-                .map(|e| e.path().to_path_buf())
-                .filter(|p| is_log_file(p))
-                .collect()
+    let cli = Cli::parse();
+    if let Some(Command::Check { config }) = &cli.command {
+        std::process::exit(run_check(config.as_deref()));
+    }
+    if let Some(Command::Generate {
+        lines,
+        format,
+        error_rate,
+        seed,
+        output,
+    }) = &cli.command
+    {
+        let options = GenerateOptions {
+            lines: *lines,
+            format: *format,
+            error_rate: *error_rate,
+            seed: *seed,
+            ..GenerateOptions::default()
         };
+        std::process::exit(run_generate(&options, output.as_deref()));
+    }
+    let max_open_dirs = get_max_open_dirs();
 
-        let total_files = paths.len();
-
-        // Send progress with total count
-        let _ = progress_tx.send(LoadProgress {
-            current_file: 0,
-            total_files,
-            entries_loaded: 0,
-            current_path: None,
-        });
+    qlog::crash::install_hook(restore_terminal);
+    if let Some(path) = &cli.debug_log {
+        if let Err(e) = init_debug_log(path) {
+            eprintln!("Error: cannot write debug log {}: {}", path.display(), e);
+            std::process::exit(2);
+        }
+    }
 
-        for path in paths {
-            file_count += 1;
+    let config = match &cli.config {
+        Some(path) => match AppConfig::load_file(path) {
+            Ok(config) => Some(config),
+            Err(e) => {
+                eprintln!("Error: {}", e);
+                std::process::exit(2);
+            }
+        },
+        None => AppConfig::load(),
+    };
+
+    let workspace = match cli.workspace.as_deref().map(Workspace::load).transpose() {
+        Ok(workspace) => workspace,
+        Err(e) => {
+            eprintln!("Error: {}", e);
+            std::process::exit(2);
+        }
+    };
 
-            let progress = LoadProgress {
-                current_file: file_count,
-                total_files,
-                entries_loaded: all_storages.iter().map(|s| s.len()).sum(),
-                current_path: Some(path.clone()),
-            };
-            let _ = progress_tx.send(progress);
+    let latest = match cli
+        .follow_latest
+        .as_deref()
+        .map(LatestFile::new)
+        .transpose()
+    {
+        Ok(latest) => latest,
+        Err(e) => {
+            eprintln!("Error: {}", e);
+            std::process::exit(2);
+        }
+    };
+    let follow = cli.follow || latest.is_some();
 
-            check_fd_warning();
+    let mut filters = FilterList::new();
+    for rule in workspace.iter().flat_map(Workspace::filter_rules) {
+        filters.add_rule(rule);
+    }
+    for pattern in &cli.filter {
+        filters.add(FilterKind::Include, pattern.clone());
+    }
+    for pattern in &cli.filter_out {
+        filters.add(FilterKind::Exclude, pattern.clone());
+    }
 
-            let mut attempt = 0;
-            let mut delay = INITIAL_RETRY_MS;
+    let args = &cli.files;
+    let streamed = streamed_input(args, workspace.is_some() || latest.is_some())?;
+    let explicit_paths = !args.is_empty() || workspace.is_some() || latest.is_some();
+    let paths: Vec<PathBuf> = if streamed.is_some() {
+        Vec::new()
+    } else if cli.tutor {
+        match tutor::write_sample(&env::temp_dir()) {
+            Ok(path) => vec![path],
+            Err(e) => {
+                eprintln!("Error: cannot write the tutorial log: {}", e);
+                std::process::exit(2);
+            }
+        }
+    } else if explicit_paths {
+        let mut paths = workspace
+            .as_ref()
+            .map(|workspace| workspace.files(max_open_dirs))
+            .unwrap_or_default();
+        paths.extend(collect_paths(args, max_open_dirs));
+        paths.extend(latest.as_ref().map(|latest| latest.current().to_path_buf()));
+        paths
+    } else {
+        discover_log_files(Path::new("."), max_open_dirs)
+    };
+
+    if cli.print {
+        let mut filter = LineFilter::new(filters, cli.level, cli.search.as_deref());
+        filter.context = cli.context.unwrap_or(0);
+        let code = match streamed {
+            Some(reader) => print_stream(reader, &filter),
+            None => print_files(paths, cli.tail, config.as_ref(), &filter),
+        };
+        std::process::exit(code);
+    }
 
-            loop {
-                match LogStorage::from_file(&path) {
-                    Ok(storage) => {
-                        let entry_count = storage.len();
-                        stats.files_loaded += 1;
-                        stats.entries_loaded += entry_count;
+    let mut app = App::with_config(config);
+    if let Some(name) = &cli.theme {
+        if !app.set_theme(name) {
+            eprintln!(
+                "Error: unknown theme '{}': expected one of {}",
+                name,
+                app.theme_names().join(", ")
+            );
+            std::process::exit(2);
+        }
+    }
+    app.mouse_capture = !cli.no_mouse;
+    app.filters = filters;
+    app.drill_down.levels = cli.level;
+    app.context = cli.context.filter(|&lines| lines > 0).map(Context::new);
+    if cli.wrap || cli.no_wrap {
+        app.wrap_mode = cli.wrap;
+    }
+    if cli.tutor {
+        app.tutor = Some(Tutor::new());
+    }
 
-                        // Store for later combination
-                        all_storages.push(storage);
-                        break;
-                    }
-                    Err(e) if is_fd_exhaustion_error(&*e) && attempt < MAX_RETRIES => {
-                        eprintln!(
-                            "FD exhaustion on {}, retry {}/{} after {}ms",
-                            path.display(),
-                            attempt + 1,
-                            MAX_RETRIES,
-                            delay
-                        );
-                        thread::sleep(Duration::from_millis(delay));
-                        delay *= 2;
-                        attempt += 1;
-                    }
-                    Err(e) if is_fd_exhaustion_error(&*e) => {
-                        eprintln!(
-                            "Failed to load {} after {} retries: FD limit reached. Try: ulimit -n 65536",
-                            path.display(),
-                            MAX_RETRIES
-                        );
-                        stats.files_failed += 1;
-                        if stats.failed_paths.len() < 5 {
-                            stats.failed_paths.push(path);
-                        }
-                        break;
-                    }
-                    Err(e) => {
-                        eprintln!("Error loading {}: {}", path.display(), e);
-                        stats.files_failed += 1;
-                        if stats.failed_paths.len() < 5 {
-                            stats.failed_paths.push(path);
-                        }
-                        break;
-                    }
-                }
-            }
+    if let Some(reader) = streamed {
+        if let Some(query) = cli.search {
+            app.init_search_state(query);
         }
+        qlog::run_with_source_in(app, ChannelSource::from_reader(reader))?;
+        return Ok(());
+    }
 
-        // Combine all storages into one
-        let combined_storage = combine_storages(all_storages);
+    app.follow_latest = latest;
+    if explicit_paths {
+        app.recent.record(&paths);
+        let _ = app.recent.save();
+    }
 
-        let _ = logs_tx.send((combined_storage, stats));
+    match cli.tail {
+        Some(mode) => app.request_open_with(paths, mode),
+        // Oversized files open the large-file prompt instead of loading right away
+        None => app.request_open(paths),
+    }
+    let loader = app.take_open_request().map(|request| {
+        app.prepare_reload(request.files.len());
+        loader::spawn(request, app.sidecar_enabled())
     });
 
+    if !explicit_paths && !cli.tutor && app.mode == Mode::Normal && !app.recent.is_empty() {
+        // No files given: offer the last sessions' files on top of auto-discovery
+        app.show_recent();
+    }
+
     enable_raw_mode()?;
     let mut stdout = io::stdout();
-    execute!(stdout, EnterAlternateScreen, EnableMouseCapture)?;
+    execute!(stdout, EnterAlternateScreen, EnableFocusChange)?;
+    if app.mouse_capture {
+        execute!(stdout, EnableMouseCapture)?;
+    }
     let backend = CrosstermBackend::new(stdout);
     let mut terminal = Terminal::new(backend)?;
 
-    let mut app = App::new();
     let res = run_app(
         &mut terminal,
         &mut app,
-        progress_rx,
-        logs_rx,
-        incremental_rx,
+        loader,
+        follow,
+        !cli.sets_view() && !cli.tutor,
+        cli.search,
     );
 
     disable_raw_mode()?;
     execute!(
         terminal.backend_mut(),
         LeaveAlternateScreen,
-        DisableMouseCapture
+        DisableMouseCapture,
+        DisableFocusChange
     )?;
     terminal.show_cursor()?;
 
     if let Err(err) = res {
         eprintln!("Error: {:?}", err);
     }
+    if let Err(err) = app.save_annotations() {
+        eprintln!("{}", err);
+    }
+    // The tutorial log is a throwaway copy; it gets no session
+    if !cli.tutor {
+        if let Err(err) = app.save_session() {
+            eprintln!("Error saving session: {}", err);
+        }
+    }
+
+    Ok(())
+}
+
+/// Leave raw mode and the alternate screen after a panic.
+fn restore_terminal() {
+    let _ = disable_raw_mode();
+    let _ = execute!(
+        io::stdout(),
+        LeaveAlternateScreen,
+        DisableMouseCapture,
+        DisableFocusChange,
+        crossterm::cursor::Show
+    );
+}
+
+/// `qlog check [CONFIG]`: validate the config file without starting the UI.
+///
+/// Returns the exit code: 0 if the config is valid (or there is none),
+/// 1 if problems were found, 2 if the file cannot be read.
+fn run_check(path: Option<&Path>) -> i32 {
+    let path = match path {
+        Some(path) => path.to_path_buf(),
+        None => {
+            match AppConfig::find_path() {
+                Some(path) => path,
+                None => {
+                    println!("No config file found (.qlog/qlog.toml or ~/.qlog/qlog.toml); defaults apply");
+                    return 0;
+                }
+            }
+        }
+    };
+    match AppConfig::check_file(&path) {
+        Ok(issues) if issues.is_empty() => {
+            println!("{}: OK", path.display());
+            0
+        }
+        Ok(issues) => {
+            eprintln!("{}: {} problem(s)", path.display(), issues.len());
+            for issue in &issues {
+                eprintln!("  {}", issue);
+            }
+            1
+        }
+        Err(e) => {
+            eprintln!("{}", e);
+            2
+        }
+    }
+}
+
+/// Write a synthetic log to `output`, or to standard output.
+fn run_generate(options: &GenerateOptions, output: Option<&Path>) -> i32 {
+    let path = output.filter(|path| *path != Path::new("-"));
+    let written = match path {
+        Some(path) => File::create(path)
+            .and_then(|file| generate::generate(options, io::BufWriter::new(file))),
+        None => generate::generate(options, io::BufWriter::new(io::stdout().lock())),
+    };
+    match (written, path) {
+        (Ok(lines), Some(path)) => {
+            eprintln!("Wrote {} lines to {}", lines, path.display());
+            0
+        }
+        (Ok(_), None) => 0,
+        // A closed pipe (`qlog generate | head`) is not an error
+        (Err(e), None) if e.kind() == io::ErrorKind::BrokenPipe => 0,
+        (Err(e), _) => {
+            eprintln!("Error: {}", e);
+            2
+        }
+    }
+}
 
+/// Write tracing spans (load, filter, search and render timings) to `path`.
+///
+/// Without `--debug-log` no subscriber is installed and the instrumentation
+/// is skipped.
+fn init_debug_log(path: &Path) -> io::Result<()> {
+    let file = File::create(path)?;
+    tracing_subscriber::fmt()
+        .with_writer(Mutex::new(file))
+        .with_ansi(false)
+        .with_thread_names(true)
+        .with_span_events(FmtSpan::CLOSE)
+        .with_max_level(tracing::Level::DEBUG)
+        .init();
+    tracing::info!(version = env!("CARGO_PKG_VERSION"), "debug log started");
     Ok(())
 }
 
-/// Combine multiple LogStorage instances into one.
-fn combine_storages(storages: Vec<LogStorage>) -> LogStorage {
-    LogStorage::merge(storages)
+/// Parse a `--tail` value: a plain number means lines (`--tail 100000`);
+/// a number with a `K`, `M` or `G` suffix means bytes (`--tail 500M`,
+/// `--tail 2GB`).
+fn parse_tail_value(value: &str) -> Result<IndexMode, String> {
+    const SIZE_SUFFIXES: [(&str, u64); 7] = [
+        ("KB", 1 << 10),
+        ("K", 1 << 10),
+        ("MB", 1 << 20),
+        ("M", 1 << 20),
+        ("GB", 1 << 30),
+        ("G", 1 << 30),
+        ("B", 1),
+    ];
+    let invalid = || "expected a line count or a size such as 500M".to_string();
+    let upper = value.trim().to_uppercase();
+
+    for (suffix, multiplier) in SIZE_SUFFIXES {
+        if let Some(number) = upper.strip_suffix(suffix) {
+            let n: u64 = number.trim().parse().map_err(|_| invalid())?;
+            let bytes = n
+                .checked_mul(multiplier)
+                .ok_or_else(|| format!("size {} is too large", value.trim()))?;
+            return Ok(IndexMode::Tail { bytes });
+        }
+    }
+
+    let lines: usize = upper.parse().map_err(|_| invalid())?;
+    Ok(IndexMode::TailLines { lines })
+}
+
+fn parse_log_format(value: &str) -> Result<LogFormat, String> {
+    LogFormat::parse(value)
+        .ok_or_else(|| format!("expected one of {}", LogFormat::NAMES.join(", ")))
+}
+
+fn parse_error_rate(value: &str) -> Result<f64, String> {
+    match value.trim().parse::<f64>() {
+        Ok(rate) if (0.0..=1.0).contains(&rate) => Ok(rate),
+        _ => Err("expected a number from 0 to 1, such as 0.02".to_string()),
+    }
+}
+
+fn parse_level(value: &str) -> Result<LevelSet, String> {
+    LevelSet::parse(value).ok_or_else(|| {
+        "expected error, warn, info, debug or trace, optionally with + (warn+)".to_string()
+    })
+}
+
+/// Auto-discover log files below `root` matching the built-in name pattern.
+fn discover_log_files(root: &Path, max_open_dirs: usize) -> Vec<PathBuf> {
+    WalkDir::new(root)
+        .max_open(max_open_dirs)
+        .into_iter()
+        .filter_map(|e| e.ok())
+        .filter(|e| e.file_type().is_file())
+        .map(|e| e.path().to_path_buf())
+        .filter(|p| is_log_file(p))
+        .collect()
+}
+
+/// Piped input: `-`, a named pipe given as the only file, or standard
+/// input when it is not a terminal and nothing else was given.
+///
+/// Keys are still read from the terminal: crossterm falls back to
+/// `/dev/tty` when standard input is not one.
+fn streamed_input(
+    args: &[String],
+    has_workspace: bool,
+) -> io::Result<Option<Box<dyn Read + Send>>> {
+    match args {
+        [arg] if arg == "-" => Ok(Some(Box::new(io::stdin()))),
+        [arg] if is_fifo(Path::new(arg)) => Ok(Some(Box::new(File::open(arg)?))),
+        [] if !has_workspace && !io::stdin().is_terminal() => Ok(Some(Box::new(io::stdin()))),
+        _ => Ok(None),
+    }
+}
+
+/// `qlog --print` on piped input: print the lines `filter` admits as they
+/// arrive. Returns the exit code, as `grep` does: 0 if lines were printed,
+/// 1 if none were, 2 on errors.
+fn print_stream(reader: Box<dyn Read + Send>, filter: &LineFilter) -> i32 {
+    let printed = batch::print_reader(BufReader::new(reader), filter, io::stdout().lock());
+    print_exit_code(printed, false)
+}
+
+/// `qlog --print` on files: load them, then print the lines `filter`
+/// admits, merged by time. Returns the exit code as [`print_stream`] does;
+/// files that fail to load are reported and give exit code 2.
+fn print_files(
+    paths: Vec<PathBuf>,
+    tail: Option<IndexMode>,
+    config: Option<&AppConfig>,
+    filter: &LineFilter,
+) -> i32 {
+    let mode = tail.unwrap_or(IndexMode::Full);
+    let request = OpenRequest {
+        files: paths.into_iter().map(|path| (path, mode)).collect(),
+    };
+    let sidecar = config.is_some_and(|c| c.index.sidecar);
+    let mut failed = false;
+    for event in loader::spawn(request, sidecar) {
+        match event {
+            LoadEvent::Error { message, .. } => {
+                eprintln!("Error: {}", message);
+                failed = true;
+            }
+            LoadEvent::Completed(result) => {
+                let verbatim = config.is_some_and(|c| c.export.verbatim);
+                let out = BufWriter::new(io::stdout().lock());
+                let printed = batch::print_storage(&result.storage, filter, verbatim, out);
+                return print_exit_code(printed, failed);
+            }
+            _ => {}
+        }
+    }
+    2
+}
+
+fn print_exit_code(printed: io::Result<usize>, failed: bool) -> i32 {
+    match printed {
+        _ if failed => 2,
+        Ok(0) => 1,
+        Ok(_) => 0,
+        // The reader went away (`qlog --print ... | head`)
+        Err(e) if e.kind() == io::ErrorKind::BrokenPipe => 0,
+        Err(e) => {
+            eprintln!("Error: {}", e);
+            2
+        }
+    }
+}
+
+#[cfg(unix)]
+fn is_fifo(path: &Path) -> bool {
+    use std::os::unix::fs::FileTypeExt;
+    std::fs::metadata(path).is_ok_and(|m| m.file_type().is_fifo())
+}
+
+#[cfg(not(unix))]
+fn is_fifo(_path: &Path) -> bool {
+    false
 }
 
 fn collect_paths(args: &[String], max_open_dirs: usize) -> Vec<PathBuf> {
@@ -262,7 +625,7 @@ fn collect_paths(args: &[String], max_open_dirs: usize) -> Vec<PathBuf> {
                     .filter_map(|e| e.ok())
                     .filter(|e| e.file_type().is_file())
                     .map(|e| e.path().to_path_buf())
-                    .filter(|p| matches_glob_pattern(p, &pattern)),
+                    .filter(|p| qlog::glob::matches_path(p, &pattern)),
             );
         } else if path.exists() {
             paths.push(path);
@@ -274,44 +637,14 @@ fn collect_paths(args: &[String], max_open_dirs: usize) -> Vec<PathBuf> {
     paths
 }
 
-fn matches_glob_pattern(path: &Path, pattern: &str) -> bool {
-    let path_str = path.to_string_lossy();
-    let file_name = path
-        .file_name()
-        .map(|n| n.to_string_lossy())
-        .unwrap_or_default();
-
-    if pattern.contains('/') {
-        glob_match(&path_str, pattern)
-    } else {
-        glob_match(&file_name, pattern)
-    }
-}
-
-fn glob_match(text: &str, pattern: &str) -> bool {
-    let text_chars: Vec<char> = text.chars().collect();
-    let pattern_chars: Vec<char> = pattern.chars().collect();
-
-    fn match_helper(text: &[char], pattern: &[char]) -> bool {
-        match (text.first(), pattern.first()) {
-            (None, None) => true,
-            (Some(_), None) => false,
-            (None, Some('*')) => match_helper(&[], &pattern[1..]),
-            (None, Some(_)) => false,
-            (Some(_), Some('*')) => {
-                match_helper(text, &pattern[1..]) || match_helper(&text[1..], pattern)
-            }
-            (Some(t), Some(p)) if *p == '?' || t == p => match_helper(&text[1..], &pattern[1..]),
-            (Some(_), Some(_)) => false,
-        }
-    }
-
-    match_helper(&text_chars, &pattern_chars)
-}
-
 fn is_log_file(path: &Path) -> bool {
     if let Some(name) = path.file_name() {
         let name = name.to_string_lossy();
+        // Rotated logs are often compressed
+        let name = [".gz", ".zst"]
+            .iter()
+            .find_map(|ext| name.strip_suffix(ext))
+            .unwrap_or(&name);
         name.starts_with("como-data-center") && name.ends_with(".log")
     } else {
         false
@@ -321,70 +654,101 @@ fn is_log_file(path: &Path) -> bool {
 fn run_app(
     terminal: &mut Terminal<CrosstermBackend<io::Stdout>>,
     app: &mut App,
-    progress_rx: mpsc::Receiver<LoadProgress>,
-    logs_rx: mpsc::Receiver<(LogStorage, LoadStats)>,
-    incremental_rx: mpsc::Receiver<LogStorage>,
+    mut loader: Option<mpsc::Receiver<LoadEvent>>,
+    mut follow_on_load: bool,
+    restore_session: bool,
+    mut search: Option<String>,
 ) -> io::Result<()> {
     let mut last_tick = std::time::Instant::now();
-    let tick_rate = Duration::from_millis(50);
-    let mut stats: Option<LoadStats> = None;
+    // The saved session is only put back for the files opened at launch,
+    // unless options set the view
+    let mut first_load = restore_session;
+    // Input always redraws; unfocused, nothing else does
+    let mut redraw = true;
 
     while !app.should_quit {
-        while let Ok(progress) = progress_rx.try_recv() {
-            app.loading_status = LoadingStatus::Loading {
-                current: progress.current_file,
-                total: if progress.total_files > 0 {
-                    progress.total_files
-                } else {
-                    progress.current_file
-                },
-            };
-        }
-
-        while let Ok(storage) = incremental_rx.try_recv() {
-            app.set_storage(storage);
+        if let Some(request) = app.take_open_request() {
+            app.prepare_reload(request.files.len());
+            app.recent.record(&request.paths());
+            let _ = app.recent.save();
+            loader = Some(loader::spawn(request, app.sidecar_enabled()));
         }
 
-        if let Ok((final_storage, final_stats)) = logs_rx.try_recv() {
-            stats = Some(final_stats);
+        while let Some(event) = loader.as_ref().and_then(|rx| rx.try_recv().ok()) {
+            let LoadEvent::Completed(result) = event else {
+                app.on_load_event(&event);
+                continue;
+            };
+            loader = None;
             app.loading_status = LoadingStatus::Complete;
-            app.set_storage(final_storage);
-        }
-
-        if let Some(ref s) = stats {
-            if app.status_message.is_empty() {
-                app.status_message = format!(
-                    "Loaded {} entries from {} files ({} failed)",
-                    s.entries_loaded, s.files_loaded, s.files_failed
-                );
-                if !s.failed_paths.is_empty() {
-                    eprintln!("Failed files: {:?}", s.failed_paths);
-                }
+            app.set_storage(result.storage);
+            app.load_annotations();
+            app.show_load_summary(result.summary);
+            let first = std::mem::take(&mut first_load);
+            let restored = app.restore_anchors() || (first && app.restore_session());
+            if !restored {
+                app.apply_startup_position();
+            }
+            if let Some(query) = search.take() {
+                app.init_search_state(query);
+            }
+            if std::mem::take(&mut follow_on_load) {
+                app.start_follow();
+            } else {
+                app.finish_latest_switch();
             }
         }
 
-        terminal.draw(|f| qlog::ui::draw(f, app))?;
+        app.poll_follow();
+        app.poll_replay();
+        app.poll_search_preview();
+        app.poll_jobs();
+        app.expire_load_summary();
+        app.update_notifications();
+
+        let size = terminal.size()?;
+        qlog::crash::update_view(
+            (size.width, size.height),
+            app.mode,
+            app.total_lines(),
+            app.filtered_len(),
+            app.filters.len(),
+        );
+        if std::mem::take(&mut redraw) || app.is_focused() {
+            let _frame = tracing::debug_span!("render").entered();
+            let started = std::time::Instant::now();
+            terminal.draw(|f| qlog::ui::draw(f, app))?;
+            app.perf.frame = Some(started.elapsed());
+        }
 
+        let tick_rate = if app.is_focused() {
+            TICK_RATE
+        } else {
+            UNFOCUSED_TICK_RATE
+        };
         let timeout = tick_rate
             .checked_sub(last_tick.elapsed())
             .unwrap_or_else(|| Duration::from_secs(0));
 
         if crossterm::event::poll(timeout)? {
-            if let Event::Key(key) = crossterm::event::read()? {
-                if key.kind == KeyEventKind::Press {
-                    match key.code {
-                        KeyCode::Char('c')
-                            if key
-                                .modifiers
-                                .contains(crossterm::event::KeyModifiers::CONTROL) =>
-                        {
-                            app.should_quit = true;
-                        }
-                        _ => {
-                            app.handle_key(key);
-                        }
+            redraw = true;
+            match crossterm::event::read()? {
+                Event::Mouse(mouse) => app.handle_mouse(mouse),
+                Event::FocusGained => app.set_focused(true),
+                Event::FocusLost => app.set_focused(false),
+                Event::Key(key) if key.kind == KeyEventKind::Press => match key.code {
+                    KeyCode::Char('c')
+                        if key
+                            .modifiers
+                            .contains(crossterm::event::KeyModifiers::CONTROL) =>
+                    {
+                        app.should_quit = true;
                     }
-                }
+                    _ => {
+                        app.handle_key(key);
+                    }
+                },
+                _ => {}
             }
         }
 
@@ -395,3 +759,25 @@ fn run_app(
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_tail_value() {
+        assert_eq!(
+            parse_tail_value("500M"),
+            Ok(IndexMode::Tail { bytes: 500 << 20 })
+        );
+        assert_eq!(
+            parse_tail_value("2000"),
+            Ok(IndexMode::TailLines { lines: 2000 })
+        );
+        assert_eq!(
+            parse_tail_value("20000000000G").unwrap_err(),
+            "size 20000000000G is too large"
+        );
+        assert!(parse_tail_value("lots").is_err());
+    }
+}
@@ -0,0 +1,126 @@
+use std::path::PathBuf;
+
+use crate::model::LogStorage;
+
+/// Lines searched on each side of the expected position when resolving an anchor.
+const ANCHOR_SEARCH_LINES: usize = 1_000_000;
+
+/// A line identified by its content and source file rather than its index.
+///
+/// Indices shift when files are reloaded, grow or rotate; an anchor is
+/// resolved again by looking for a line with the same content hash near
+/// where the line used to be, preferring its original file.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LineAnchor {
+    /// Source file of the line
+    pub path: Option<PathBuf>,
+    /// Hash of the line's bytes
+    pub hash: u64,
+    /// Position of the line relative to the first line of its file
+    pub offset_in_file: usize,
+}
+
+impl LineAnchor {
+    /// Anchor the line at storage index `idx`.
+    pub fn capture(storage: &LogStorage, idx: usize) -> Option<Self> {
+        let line = storage.get_line(idx)?;
+        Some(Self {
+            path: storage.line_path(idx).map(|p| p.to_path_buf()),
+            hash: line_hash(line.as_bytes()),
+            offset_in_file: storage.locate(idx).map_or(idx, |(_, local)| local),
+        })
+    }
+
+    /// Find the storage index of the anchored line.
+    ///
+    /// Looks in the original file first, closest to the old position; if
+    /// the line is not there (the file is gone or was rotated away), any
+    /// file is searched.
+    pub fn resolve(&self, storage: &LogStorage) -> Option<usize> {
+        let file = self.path.as_deref().and_then(|path| {
+            (0..storage.file_count()).find(|&f| storage.file_path(f) == Some(path))
+        });
+        let hint = file
+            .and_then(|f| {
+                let last = storage.file_len(f)?.checked_sub(1)?;
+                storage.file_line_index(f, self.offset_in_file.min(last))
+            })
+            .unwrap_or(self.offset_in_file);
+
+        let hash_matches = |idx: usize| {
+            storage
+                .get_line(idx)
+                .is_some_and(|line| line_hash(line.as_bytes()) == self.hash)
+        };
+        if let Some(file) = file {
+            let found = search_outward(storage.len(), hint, |idx| {
+                storage.line_file(idx) == Some(file) && hash_matches(idx)
+            });
+            if found.is_some() {
+                return found;
+            }
+        }
+        search_outward(storage.len(), hint, hash_matches)
+    }
+}
+
+/// Find the index closest to `hint` that satisfies `matches`, looking at
+/// most `ANCHOR_SEARCH_LINES` lines in each direction.
+fn search_outward(len: usize, hint: usize, matches: impl Fn(usize) -> bool) -> Option<usize> {
+    if len == 0 {
+        return None;
+    }
+    let hint = hint.min(len - 1);
+    for distance in 0..=ANCHOR_SEARCH_LINES {
+        let after = hint + distance;
+        let before = hint.checked_sub(distance).filter(|_| distance > 0);
+        if after >= len && distance > hint {
+            break;
+        }
+        if after < len && matches(after) {
+            return Some(after);
+        }
+        if before.is_some_and(&matches) {
+            return before;
+        }
+    }
+    None
+}
+
+/// FNV-1a hash of a line, stable across runs.
+pub fn line_hash(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf2_9ce4_8422_2325, |hash, &b| {
+        (hash ^ b as u64).wrapping_mul(0x0100_0000_01b3)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::log_storage::storage_from;
+    use std::io::Write;
+
+    #[test]
+    fn test_resolve_after_lines_shift() {
+        let (file, storage) = storage_from(&["a", "target", "b"]);
+        let anchor = LineAnchor::capture(&storage, 1).unwrap();
+        drop(storage);
+
+        // Rewrite the file with lines inserted before the target
+        let mut rewritten = std::fs::File::create(file.path()).unwrap();
+        writeln!(rewritten, "new 1\nnew 2\na\ntarget\nb").unwrap();
+        let reloaded = LogStorage::from_file(file.path()).unwrap();
+
+        assert_eq!(anchor.resolve(&reloaded), Some(3));
+    }
+
+    #[test]
+    fn test_resolve_prefers_closest_duplicate() {
+        let (_file, storage) = storage_from(&["dup", "x", "x", "x", "dup", "y"]);
+        let anchor = LineAnchor::capture(&storage, 4).unwrap();
+        assert_eq!(anchor.resolve(&storage), Some(4));
+
+        let (_other, missing) = storage_from(&["nothing", "here"]);
+        assert_eq!(anchor.resolve(&missing), None);
+    }
+}
@@ -0,0 +1,157 @@
+use std::collections::BTreeMap;
+
+/// A bookmarked line with an optional free-form note.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Bookmark {
+    pub note: Option<String>,
+}
+
+/// Bookmarked lines keyed by storage index.
+///
+/// Keys are indices into `LogStorage`, not into the filtered view, so
+/// bookmarks survive re-filtering.
+#[derive(Debug, Clone, Default)]
+pub struct Bookmarks {
+    marks: BTreeMap<usize, Bookmark>,
+}
+
+impl Bookmarks {
+    pub fn new() -> Self {
+        Self {
+            marks: BTreeMap::new(),
+        }
+    }
+
+    /// Toggle the bookmark on a line. Returns true if the line is now bookmarked.
+    pub fn toggle(&mut self, storage_idx: usize) -> bool {
+        if self.marks.remove(&storage_idx).is_some() {
+            false
+        } else {
+            self.marks.insert(storage_idx, Bookmark::default());
+            true
+        }
+    }
+
+    /// Bookmark a line, keeping the note of an existing bookmark.
+    pub fn add(&mut self, storage_idx: usize) {
+        self.marks.entry(storage_idx).or_default();
+    }
+
+    /// Attach a note to a line, bookmarking it if necessary.
+    /// An empty note removes the existing note but keeps the bookmark.
+    pub fn set_note(&mut self, storage_idx: usize, note: impl Into<String>) {
+        let note = note.into();
+        let entry = self.marks.entry(storage_idx).or_default();
+        entry.note = if note.is_empty() { None } else { Some(note) };
+    }
+
+    /// Bookmark a line with an existing bookmark, replacing any on that line.
+    pub fn insert(&mut self, storage_idx: usize, mark: Bookmark) {
+        self.marks.insert(storage_idx, mark);
+    }
+
+    pub fn contains(&self, storage_idx: usize) -> bool {
+        self.marks.contains_key(&storage_idx)
+    }
+
+    pub fn get(&self, storage_idx: usize) -> Option<&Bookmark> {
+        self.marks.get(&storage_idx)
+    }
+
+    pub fn remove(&mut self, storage_idx: usize) -> Option<Bookmark> {
+        self.marks.remove(&storage_idx)
+    }
+
+    pub fn clear(&mut self) {
+        self.marks.clear();
+    }
+
+    pub fn len(&self) -> usize {
+        self.marks.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.marks.is_empty()
+    }
+
+    /// Shift bookmarks at or after `start` by `count` lines, after lines
+    /// were inserted into storage at `start`.
+    pub fn shift_from(&mut self, start: usize, count: usize) {
+        let moved = self.marks.split_off(&start);
+        self.marks
+            .extend(moved.into_iter().map(|(idx, mark)| (idx + count, mark)));
+    }
+
+    /// Drop bookmarks on the `count` lines removed from storage at `start`
+    /// and move later ones back.
+    pub fn remove_lines(&mut self, start: usize, count: usize) {
+        let mut removed = self.marks.split_off(&start);
+        let later = removed.split_off(&(start + count));
+        self.marks
+            .extend(later.into_iter().map(|(idx, mark)| (idx - count, mark)));
+    }
+
+    /// Iterate over bookmarks in storage order.
+    pub fn iter(&self) -> impl Iterator<Item = (usize, &Bookmark)> {
+        self.marks.iter().map(|(&idx, mark)| (idx, mark))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_toggle_adds_and_removes() {
+        let mut marks = Bookmarks::new();
+        assert!(marks.toggle(5));
+        assert!(marks.contains(5));
+        assert!(!marks.toggle(5));
+        assert!(marks.is_empty());
+    }
+
+    #[test]
+    fn test_set_note_bookmarks_line() {
+        let mut marks = Bookmarks::new();
+        marks.set_note(3, "root cause");
+        assert!(marks.contains(3));
+        assert_eq!(marks.get(3).unwrap().note.as_deref(), Some("root cause"));
+
+        marks.set_note(3, "");
+        assert!(marks.contains(3));
+        assert!(marks.get(3).unwrap().note.is_none());
+    }
+
+    #[test]
+    fn test_shift_from_moves_later_marks() {
+        let mut marks = Bookmarks::new();
+        marks.toggle(1);
+        marks.set_note(4, "after insert");
+        marks.shift_from(3, 10);
+        assert!(marks.contains(1));
+        assert!(!marks.contains(4));
+        assert_eq!(marks.get(14).unwrap().note.as_deref(), Some("after insert"));
+    }
+
+    #[test]
+    fn test_remove_lines_moves_later_marks_back() {
+        let mut marks = Bookmarks::new();
+        marks.toggle(1);
+        marks.toggle(4);
+        marks.set_note(9, "after prune");
+        marks.remove_lines(3, 5);
+        let order: Vec<usize> = marks.iter().map(|(idx, _)| idx).collect();
+        assert_eq!(order, vec![1, 4]);
+        assert_eq!(marks.get(4).unwrap().note.as_deref(), Some("after prune"));
+    }
+
+    #[test]
+    fn test_iter_in_storage_order() {
+        let mut marks = Bookmarks::new();
+        marks.toggle(10);
+        marks.toggle(2);
+        marks.toggle(7);
+        let order: Vec<usize> = marks.iter().map(|(idx, _)| idx).collect();
+        assert_eq!(order, vec![2, 7, 10]);
+    }
+}
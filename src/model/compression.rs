@@ -0,0 +1,132 @@
+//! Gzip and zstd compressed log files.
+//!
+//! Compressed files are recognized by their magic bytes rather than their
+//! name and decompressed into anonymous memory, so the line index and
+//! everything above it see them like any other mapped file.
+
+use std::io::{self, Read};
+
+use memmap2::{Mmap, MmapMut};
+
+const GZIP_MAGIC: [u8; 2] = [0x1f, 0x8b];
+const ZSTD_MAGIC: [u8; 4] = [0x28, 0xb5, 0x2f, 0xfd];
+
+/// Compressed bytes read between progress reports.
+const PROGRESS_STEP: u64 = 4 * 1024 * 1024;
+
+/// Compression format of a log file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Compression {
+    Gzip,
+    Zstd,
+}
+
+impl Compression {
+    /// Recognize the format from the first bytes of a file.
+    pub fn detect(data: &[u8]) -> Option<Self> {
+        if data.starts_with(&GZIP_MAGIC) {
+            Some(Compression::Gzip)
+        } else if data.starts_with(&ZSTD_MAGIC) {
+            Some(Compression::Zstd)
+        } else {
+            None
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Compression::Gzip => "gzip",
+            Compression::Zstd => "zstd",
+        }
+    }
+
+    /// Decompress `compressed` into anonymous memory.
+    ///
+    /// `progress` is called with the number of compressed bytes read so far
+    /// and the compressed size.
+    pub fn decompress(
+        self,
+        compressed: &[u8],
+        progress: &mut dyn FnMut(u64, u64),
+    ) -> io::Result<Mmap> {
+        let reader = ProgressReader {
+            inner: compressed,
+            read: 0,
+            reported: 0,
+            total: compressed.len() as u64,
+            progress,
+        };
+        let mut data = Vec::new();
+        match self {
+            // Concatenated gzip streams (`cat a.gz b.gz`) are still one file
+            Compression::Gzip => {
+                flate2::read::MultiGzDecoder::new(reader).read_to_end(&mut data)?
+            }
+            Compression::Zstd => {
+                zstd::stream::read::Decoder::new(reader)?.read_to_end(&mut data)?
+            }
+        };
+
+        let mut mmap = MmapMut::map_anon(data.len())?;
+        mmap.copy_from_slice(&data);
+        mmap.make_read_only()
+    }
+}
+
+/// Reader that reports how far into its input it got.
+struct ProgressReader<'a, R> {
+    inner: R,
+    read: u64,
+    reported: u64,
+    total: u64,
+    progress: &'a mut dyn FnMut(u64, u64),
+}
+
+impl<R: Read> Read for ProgressReader<'_, R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let n = self.inner.read(buf)?;
+        self.read += n as u64;
+        if self.read - self.reported >= PROGRESS_STEP || (n == 0 && self.read > self.reported) {
+            self.reported = self.read;
+            (self.progress)(self.read, self.total);
+        }
+        Ok(n)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+
+    const TEXT: &[u8] = b"2024-01-01 10:00:00 INFO start\n2024-01-01 10:00:01 ERROR failed\n";
+
+    #[test]
+    fn test_gzip_and_zstd_round_trip() {
+        let mut gzip = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::fast());
+        gzip.write_all(TEXT).unwrap();
+        let gzip = gzip.finish().unwrap();
+        let zstd = zstd::encode_all(TEXT, 1).unwrap();
+
+        for (data, format) in [(gzip, Compression::Gzip), (zstd, Compression::Zstd)] {
+            assert_eq!(Compression::detect(&data), Some(format));
+            let mut reports = Vec::new();
+            let mmap = format
+                .decompress(&data, &mut |read, total| reports.push((read, total)))
+                .unwrap();
+            assert_eq!(&mmap[..], TEXT);
+            assert_eq!(
+                reports.last(),
+                Some(&(data.len() as u64, data.len() as u64))
+            );
+        }
+        assert_eq!(Compression::detect(TEXT), None);
+    }
+
+    #[test]
+    fn test_corrupt_data_is_an_error() {
+        let mut data = GZIP_MAGIC.to_vec();
+        data.extend_from_slice(b"not really gzip");
+        assert!(Compression::Gzip.decompress(&data, &mut |_, _| {}).is_err());
+    }
+}
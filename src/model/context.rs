@@ -0,0 +1,152 @@
+use std::ops::Range;
+
+/// Lines shown around the lines that pass the filters, like `grep -C`.
+///
+/// Matches are offered in storage order; each brings up to `lines` lines
+/// before and after it into view. Entries are storage indices, so the
+/// lines that are only context can be drawn dimmed.
+#[derive(Debug, Clone, Default)]
+pub struct Context {
+    lines: usize,
+    /// Lines shown only as context, in ascending order
+    context: Vec<usize>,
+    last_match: Option<usize>,
+    /// Last line shown, match or context
+    last_shown: Option<usize>,
+}
+
+impl Context {
+    pub fn new(lines: usize) -> Self {
+        Self {
+            lines,
+            ..Self::default()
+        }
+    }
+
+    pub fn lines(&self) -> usize {
+        self.lines
+    }
+
+    /// Forget all lines shown, before the view is rebuilt.
+    pub fn reset(&mut self) {
+        self.context.clear();
+        self.last_match = None;
+        self.last_shown = None;
+    }
+
+    /// Forget lines `start..start + count`, which were removed from the
+    /// storage; later lines move back by `count`.
+    pub fn remove_lines(&mut self, start: usize, count: usize) {
+        let end = start + count;
+        let shift = |idx: usize| {
+            if idx >= end {
+                Some(idx - count)
+            } else {
+                (idx < start).then_some(idx)
+            }
+        };
+        let from = self.context.partition_point(|&idx| idx < start);
+        let to = self.context.partition_point(|&idx| idx < end);
+        self.context.drain(from..to);
+        for idx in &mut self.context[from..] {
+            *idx -= count;
+        }
+        self.last_match = self.last_match.and_then(shift);
+        // Lines after the removed ones are still unshown
+        self.last_shown = self
+            .last_shown
+            .and_then(|idx| shift(idx).or(start.checked_sub(1)));
+    }
+
+    /// Whether a line is shown only as context of a match.
+    pub fn is_context(&self, storage_idx: usize) -> bool {
+        self.context.binary_search(&storage_idx).is_ok()
+    }
+
+    /// Add the match `storage_idx`, which comes after every line shown, to
+    /// `shown` after the context lines before it. Lines `keep` rejects are
+    /// never context.
+    pub fn push_match(
+        &mut self,
+        shown: &mut Vec<usize>,
+        storage_idx: usize,
+        keep: impl Fn(usize) -> bool,
+    ) {
+        self.fill_after(shown, storage_idx, &keep);
+        let from = storage_idx
+            .saturating_sub(self.lines)
+            .max(self.next_unshown());
+        self.push_context(shown, from..storage_idx, &keep);
+        shown.push(storage_idx);
+        self.last_match = Some(storage_idx);
+        self.last_shown = Some(storage_idx);
+    }
+
+    /// Add the context lines after the last match that come before `end`.
+    pub fn fill_after(&mut self, shown: &mut Vec<usize>, end: usize, keep: impl Fn(usize) -> bool) {
+        let Some(last) = self.last_match else {
+            return;
+        };
+        let to = (last + self.lines + 1).min(end);
+        self.push_context(shown, self.next_unshown()..to, &keep);
+    }
+
+    fn next_unshown(&self) -> usize {
+        self.last_shown.map_or(0, |idx| idx + 1)
+    }
+
+    fn push_context(
+        &mut self,
+        shown: &mut Vec<usize>,
+        range: Range<usize>,
+        keep: &impl Fn(usize) -> bool,
+    ) {
+        for idx in range.filter(|&idx| keep(idx)) {
+            shown.push(idx);
+            self.context.push(idx);
+            self.last_shown = Some(idx);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn expand(context: &mut Context, matches: &[usize], len: usize) -> Vec<usize> {
+        let mut shown = Vec::new();
+        for &idx in matches {
+            context.push_match(&mut shown, idx, |idx| idx != 9);
+        }
+        context.fill_after(&mut shown, len, |idx| idx != 9);
+        shown
+    }
+
+    #[test]
+    fn test_context_around_matches() {
+        let mut context = Context::new(2);
+        let shown = expand(&mut context, &[1, 5, 6, 12], 14);
+        assert_eq!(shown, vec![0, 1, 2, 3, 4, 5, 6, 7, 8, 10, 11, 12, 13]);
+        assert!(context.is_context(3) && context.is_context(13));
+        assert!(!context.is_context(5) && !context.is_context(12));
+
+        context.reset();
+        assert_eq!(
+            expand(&mut context, &[0, 20], 40),
+            vec![0, 1, 2, 18, 19, 20, 21, 22]
+        );
+        assert!(context.is_context(18));
+    }
+
+    #[test]
+    fn test_lines_arriving_after_a_match() {
+        let mut context = Context::new(1);
+        let mut shown = Vec::new();
+        context.push_match(&mut shown, 4, |_| true);
+        context.fill_after(&mut shown, 5, |_| true);
+        assert_eq!(shown, vec![3, 4]);
+        // The next line arrives later and is still context of the match
+        context.fill_after(&mut shown, 8, |_| true);
+        assert_eq!(shown, vec![3, 4, 5]);
+    }
+}
@@ -1,21 +1,58 @@
 /// Boyer-Moore-Horspool string matcher for fast substring search.
 /// Uses O(m) preprocessing and O(n/m) average-case search time.
 use std::cell::RefCell;
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
 
 #[derive(Debug, Clone)]
 pub struct BMHMatcher {
-    /// The pattern to search for (lowercase bytes)
+    /// The pattern to search for (lowercase bytes unless case-sensitive)
     pattern: Vec<u8>,
     /// Skip table: for each byte value, stores how far to shift
     skip_table: [usize; 256],
     /// Pattern length (cached for performance)
     pattern_len: usize,
+    /// Whether the text is meant to be lowercased before matching
+    ignore_case: bool,
+    /// Whether matches must be whole words
+    whole_word: bool,
 }
 
 impl BMHMatcher {
     /// Create a new BMH matcher for the given pattern.
     /// Pattern should already be in lowercase for case-insensitive matching.
     pub fn new(pattern: Vec<u8>) -> Self {
+        Self::build(pattern, true)
+    }
+
+    /// Create a matcher for a search query, matching case as `case` says.
+    pub fn for_query(query: &str, case: CaseMode) -> Self {
+        if case.ignores_case(query) {
+            Self::build(query.to_lowercase().into_bytes(), true)
+        } else {
+            Self::build(query.as_bytes().to_vec(), false)
+        }
+    }
+
+    /// Check whether text must be lowercased (ASCII) before it is matched.
+    pub fn ignores_case(&self) -> bool {
+        self.ignore_case
+    }
+
+    /// Only match where the pattern is not joined to a letter, digit or
+    /// `_` on either side, like `\bpattern\b`. Bytes of non-ASCII
+    /// characters count as letters.
+    pub fn whole_word(mut self, whole_word: bool) -> Self {
+        self.whole_word = whole_word;
+        self
+    }
+
+    pub fn matches_whole_words(&self) -> bool {
+        self.whole_word
+    }
+
+    fn build(pattern: Vec<u8>, ignore_case: bool) -> Self {
         let pattern_len = pattern.len();
         let mut skip_table = [pattern_len; 256];
 
@@ -31,13 +68,39 @@ impl BMHMatcher {
             pattern,
             skip_table,
             pattern_len,
+            ignore_case,
+            whole_word: false,
         }
     }
 
+    /// Check whether `text[start..end]` stands as a word of its own.
+    fn is_word_at(text: &[u8], start: usize, end: usize) -> bool {
+        let is_word = |b: &u8| b.is_ascii_alphanumeric() || *b == b'_' || !b.is_ascii();
+        let before = start.checked_sub(1).and_then(|before| text.get(before));
+        !before.is_some_and(is_word) && !text.get(end).is_some_and(is_word)
+    }
+
     /// Find the pattern in the given text using BMH algorithm.
     /// Returns the starting position if found, None otherwise.
     /// Text should be converted to lowercase for case-insensitive matching.
     pub fn find(&self, text: &[u8]) -> Option<usize> {
+        if !self.whole_word || self.pattern_len == 0 {
+            return self.find_any(text);
+        }
+        // Step past matches inside longer words
+        let mut offset = 0;
+        while let Some(pos) = self.find_any(&text[offset..]) {
+            let start = offset + pos;
+            if Self::is_word_at(text, start, start + self.pattern_len) {
+                return Some(start);
+            }
+            offset = start + 1;
+        }
+        None
+    }
+
+    /// Find the pattern anywhere, whole word or not.
+    fn find_any(&self, text: &[u8]) -> Option<usize> {
         if self.pattern_len == 0 {
             return Some(0);
         }
@@ -93,6 +156,15 @@ impl BMHMatcher {
     /// Find all match positions in text.
     /// Returns vector of (start, end) byte positions.
     pub fn find_all(&self, text: &[u8]) -> Vec<(usize, usize)> {
+        let mut matches = self.find_all_any(text);
+        if self.whole_word {
+            matches.retain(|&(start, end)| Self::is_word_at(text, start, end));
+        }
+        matches
+    }
+
+    /// Find all matches, whole words or not.
+    fn find_all_any(&self, text: &[u8]) -> Vec<(usize, usize)> {
         let mut matches = Vec::new();
 
         if self.pattern_len == 0 {
@@ -146,31 +218,136 @@ impl BMHMatcher {
     }
 }
 
-/// Filter kind - include or exclude
-#[derive(Debug, Clone, Copy, PartialEq)]
+/// How searches treat case.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum CaseMode {
+    /// Ignore case (the default)
+    #[default]
+    Ignore,
+    /// Ignore case unless the query has an uppercase letter
+    Smart,
+    /// Match case exactly
+    Sensitive,
+}
+
+impl CaseMode {
+    /// Check whether a search for `query` ignores case.
+    pub fn ignores_case(self, query: &str) -> bool {
+        match self {
+            CaseMode::Ignore => true,
+            CaseMode::Smart => !query.chars().any(char::is_uppercase),
+            CaseMode::Sensitive => false,
+        }
+    }
+
+    /// The mode after this one, for the toggle key.
+    pub fn next(self) -> Self {
+        match self {
+            CaseMode::Ignore => CaseMode::Smart,
+            CaseMode::Smart => CaseMode::Sensitive,
+            CaseMode::Sensitive => CaseMode::Ignore,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            CaseMode::Ignore => "ignore case",
+            CaseMode::Smart => "smart case",
+            CaseMode::Sensitive => "match case",
+        }
+    }
+}
+
+/// Filter kind - include, exclude, or dim (keep visible but de-emphasized)
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
 pub enum FilterKind {
     Include,
     Exclude,
+    Dim,
 }
 
+impl FilterKind {
+    pub fn name(self) -> &'static str {
+        match self {
+            FilterKind::Include => "include",
+            FilterKind::Exclude => "exclude",
+            FilterKind::Dim => "dim",
+        }
+    }
+}
+
+/// Separates a pattern from its exceptions, as in `error unless retry`.
+const UNLESS: &str = " unless ";
+
 /// New command-based filter system
 /// Replaces FilterSet/FilterGroup/Filter with flat list
 #[derive(Debug, Clone)]
 pub struct FilterRule {
     pub pattern: String,
     pub kind: FilterKind,
+    /// Disabled rules stay in the list but do not affect the view
+    pub enabled: bool,
+    /// Source file the rule is limited to; lines from other files are
+    /// neither required to match nor hidden or dimmed by it
+    pub file: Option<String>,
     matcher: BMHMatcher,
+    /// Lines matching any of these are not matched by the rule
+    exceptions: Vec<BMHMatcher>,
 }
 
 impl FilterRule {
+    /// Create a rule; `a unless b unless c` matches lines containing `a`
+    /// but neither `b` nor `c`.
     pub fn new(pattern: impl Into<String>, kind: FilterKind) -> Self {
         let pattern = pattern.into();
         let pattern_lower = pattern.to_lowercase();
-        let matcher = BMHMatcher::new(pattern_lower.into_bytes());
+        let mut parts = pattern_lower.split(UNLESS);
+        let matcher = BMHMatcher::new(parts.next().unwrap_or_default().as_bytes().to_vec());
+        let exceptions = parts
+            .map(str::trim)
+            .filter(|exception| !exception.is_empty())
+            .map(|exception| BMHMatcher::new(exception.as_bytes().to_vec()))
+            .collect();
         Self {
             pattern,
             kind,
+            enabled: true,
+            file: None,
             matcher,
+            exceptions,
+        }
+    }
+
+    /// Match the pattern and its exceptions as whole words only.
+    pub fn whole_word(mut self, whole_word: bool) -> Self {
+        self.matcher = self.matcher.whole_word(whole_word);
+        self.exceptions = self
+            .exceptions
+            .into_iter()
+            .map(|exception| exception.whole_word(whole_word))
+            .collect();
+        self
+    }
+
+    pub fn matches_whole_words(&self) -> bool {
+        self.matcher.matches_whole_words()
+    }
+
+    /// Limit the rule to lines from files whose path, or the path of a
+    /// directory they are in, ends with `file` (`worker.log`,
+    /// `logs/worker.log` or `api`).
+    pub fn with_file(mut self, file: Option<String>) -> Self {
+        self.file = file;
+        self
+    }
+
+    /// Check whether the rule applies to lines from `path`.
+    pub fn applies_to(&self, path: Option<&Path>) -> bool {
+        match (&self.file, path) {
+            (None, _) => true,
+            (Some(file), Some(path)) => path.ancestors().any(|p| p.ends_with(file)),
+            (Some(_), None) => false,
         }
     }
 
@@ -196,6 +373,10 @@ impl FilterRule {
             buf.clear();
             buf.extend(text.iter().map(|&b| Self::ascii_lower(b)));
             self.matcher.contains(&buf)
+                && !self
+                    .exceptions
+                    .iter()
+                    .any(|exception| exception.contains(&buf))
         })
     }
 
@@ -206,12 +387,19 @@ impl FilterRule {
     pub fn kind(&self) -> FilterKind {
         self.kind
     }
+
+    pub fn is_enabled(&self) -> bool {
+        self.enabled
+    }
 }
 
 #[derive(Debug, Clone)]
 pub struct FilterList {
     includes: Vec<FilterRule>,
     excludes: Vec<FilterRule>,
+    dims: Vec<FilterRule>,
+    /// Whether the rules match whole words only (`:set word`)
+    whole_word: bool,
 }
 
 impl FilterList {
@@ -219,22 +407,60 @@ impl FilterList {
         Self {
             includes: Vec::new(),
             excludes: Vec::new(),
+            dims: Vec::new(),
+            whole_word: false,
         }
     }
 
     pub fn add_include(&mut self, pattern: impl Into<String>) {
-        self.includes
-            .push(FilterRule::new(pattern, FilterKind::Include));
+        self.add(FilterKind::Include, pattern);
     }
 
     pub fn add_exclude(&mut self, pattern: impl Into<String>) {
-        self.excludes
-            .push(FilterRule::new(pattern, FilterKind::Exclude));
+        self.add(FilterKind::Exclude, pattern);
+    }
+
+    pub fn add_dim(&mut self, pattern: impl Into<String>) {
+        self.add(FilterKind::Dim, pattern);
+    }
+
+    /// Add a filter of the given kind.
+    pub fn add(&mut self, kind: FilterKind, pattern: impl Into<String>) {
+        self.add_rule(FilterRule::new(pattern, kind));
+    }
+
+    /// Add a rule to the list for its kind.
+    pub fn add_rule(&mut self, rule: FilterRule) {
+        let rule = rule.whole_word(self.whole_word);
+        match rule.kind {
+            FilterKind::Include => self.includes.push(rule),
+            FilterKind::Exclude => self.excludes.push(rule),
+            FilterKind::Dim => self.dims.push(rule),
+        }
+    }
+
+    /// Make every rule, and the rules added later, match whole words only
+    /// or anywhere.
+    pub fn set_whole_word(&mut self, whole_word: bool) {
+        self.whole_word = whole_word;
+        for rule in self
+            .includes
+            .iter_mut()
+            .chain(&mut self.excludes)
+            .chain(&mut self.dims)
+        {
+            *rule = rule.clone().whole_word(whole_word);
+        }
+    }
+
+    pub fn whole_word(&self) -> bool {
+        self.whole_word
     }
 
     pub fn clear(&mut self) {
         self.includes.clear();
         self.excludes.clear();
+        self.dims.clear();
     }
 
     pub fn remove_include(&mut self, index: usize) -> Option<FilterRule> {
@@ -253,6 +479,36 @@ impl FilterList {
         }
     }
 
+    pub fn remove_dim(&mut self, index: usize) -> Option<FilterRule> {
+        (index < self.dims.len()).then(|| self.dims.remove(index))
+    }
+
+    /// Remove the filter at `index` in the combined list (see `iter`).
+    pub fn remove(&mut self, index: usize) -> Option<FilterRule> {
+        let includes = self.includes.len();
+        let excludes = self.excludes.len();
+        if index < includes {
+            self.remove_include(index)
+        } else if index < includes + excludes {
+            self.remove_exclude(index - includes)
+        } else {
+            self.remove_dim(index - includes - excludes)
+        }
+    }
+
+    /// Enable or disable the filter at `index` in the combined list.
+    /// Returns the new state, or None if there is no such filter.
+    pub fn toggle(&mut self, index: usize) -> Option<bool> {
+        let rule = self
+            .includes
+            .iter_mut()
+            .chain(&mut self.excludes)
+            .chain(&mut self.dims)
+            .nth(index)?;
+        rule.enabled = !rule.enabled;
+        Some(rule.enabled)
+    }
+
     pub fn includes(&self) -> &[FilterRule] {
         &self.includes
     }
@@ -261,36 +517,56 @@ impl FilterList {
         &self.excludes
     }
 
+    pub fn dims(&self) -> &[FilterRule] {
+        &self.dims
+    }
+
     pub fn is_empty(&self) -> bool {
-        self.includes.is_empty() && self.excludes.is_empty()
+        self.includes.is_empty() && self.excludes.is_empty() && self.dims.is_empty()
+    }
+
+    /// Returns true if any enabled filter can hide lines (dim filters never do).
+    pub fn hides_lines(&self) -> bool {
+        self.includes
+            .iter()
+            .chain(&self.excludes)
+            .any(FilterRule::is_enabled)
     }
 
     pub fn len(&self) -> usize {
-        self.includes.len() + self.excludes.len()
+        self.includes.len() + self.excludes.len() + self.dims.len()
     }
 
-    /// Iterate over all filters (includes first, then excludes)
+    /// Iterate over all filters (includes first, then excludes, then dims)
     /// Returns (index, &FilterRule) where index is the position in the combined list
     pub fn iter(&self) -> impl Iterator<Item = (usize, &FilterRule)> {
-        self.includes.iter().enumerate().chain(
-            self.excludes
-                .iter()
-                .enumerate()
-                .map(|(i, f)| (i + self.includes.len(), f)),
-        )
+        self.includes
+            .iter()
+            .chain(&self.excludes)
+            .chain(&self.dims)
+            .enumerate()
     }
 
-    /// Returns true if the text matches all include filters and none of the exclude filters
+    /// Returns true if the text matches all enabled include filters and none of
+    /// the enabled exclude filters
     pub fn matches(&self, text: &[u8]) -> bool {
+        self.matches_from(text, None)
+    }
+
+    /// Like `matches`, for a line from `path`; rules limited to other files
+    /// are skipped.
+    pub fn matches_from(&self, text: &[u8], path: Option<&Path>) -> bool {
+        let active = |rule: &&FilterRule| rule.enabled && rule.applies_to(path);
+
         // Must match ALL includes
-        for include in &self.includes {
+        for include in self.includes.iter().filter(active) {
             if !include.matches(text) {
                 return false;
             }
         }
 
         // Must NOT match ANY excludes
-        for exclude in &self.excludes {
+        for exclude in self.excludes.iter().filter(active) {
             if exclude.matches(text) {
                 return false;
             }
@@ -298,6 +574,38 @@ impl FilterList {
 
         true
     }
+
+    /// Returns true if the text matches any dim filter.
+    ///
+    /// Dimmed lines stay in the view; they are only rendered de-emphasized.
+    pub fn is_dimmed(&self, text: &[u8]) -> bool {
+        self.is_dimmed_from(text, None)
+    }
+
+    /// Like `is_dimmed`, for a line from `path`.
+    pub fn is_dimmed_from(&self, text: &[u8], path: Option<&Path>) -> bool {
+        self.dims
+            .iter()
+            .any(|dim| dim.enabled && dim.applies_to(path) && dim.matches(text))
+    }
+}
+
+/// Parse a filter file: one pattern per line, `!` marks an exclude, `#`
+/// starts a comment and blank lines are skipped. A leading `\` keeps a
+/// literal `!` or `#` (`\#123` includes "#123").
+pub fn parse_filter_file(text: &str) -> Vec<(FilterKind, String)> {
+    text.lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(|line| match line.strip_prefix('!') {
+            Some(pattern) => (FilterKind::Exclude, pattern.trim_start()),
+            None => (FilterKind::Include, line),
+        })
+        .filter_map(|(kind, pattern)| {
+            let pattern = pattern.strip_prefix('\\').unwrap_or(pattern);
+            (!pattern.is_empty()).then(|| (kind, pattern.to_string()))
+        })
+        .collect()
 }
 
 impl Default for FilterList {
@@ -310,6 +618,44 @@ impl Default for FilterList {
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_query_matchers_follow_case_mode() {
+        let smart = BMHMatcher::for_query("Timeout", CaseMode::Smart);
+        assert!(!smart.ignores_case());
+        assert!(smart.contains(b"db Timeout"));
+        assert!(!smart.contains(b"db timeout"));
+        assert!(BMHMatcher::for_query("timeout", CaseMode::Smart).ignores_case());
+
+        let ignore = BMHMatcher::for_query("Timeout", CaseMode::Ignore);
+        assert!(ignore.ignores_case() && ignore.contains(b"db timeout"));
+        assert!(!BMHMatcher::for_query("timeout", CaseMode::Sensitive).ignores_case());
+        assert_eq!(CaseMode::Sensitive.next(), CaseMode::Ignore);
+    }
+
+    #[test]
+    fn test_whole_word_matches() {
+        let matcher = BMHMatcher::new(b"error".to_vec()).whole_word(true);
+        assert_eq!(
+            matcher.find_all(b"errors: error, error_count=1 (error)"),
+            [(8, 13), (30, 35)]
+        );
+        assert_eq!(matcher.find(b"errors then error"), Some(12));
+        assert!(!matcher.contains(b"suberror errors"));
+        assert!(!matcher.contains("erroré".as_bytes()));
+        assert!(matcher.contains(b"error"));
+
+        let mut filters = FilterList::new();
+        filters.add_include("error unless retry");
+        assert!(filters.matches(b"errors"));
+        filters.set_whole_word(true);
+        assert!(!filters.matches(b"Errors"));
+        assert!(filters.matches(b"ERROR retrying"));
+        assert!(!filters.matches(b"ERROR: retry"));
+        filters.add_exclude("db");
+        assert!(filters.matches(b"error from dbpool"));
+        assert!(!filters.matches(b"error from db pool"));
+    }
+
     #[test]
     fn test_bmh_matcher_empty_pattern() {
         let matcher = BMHMatcher::new(vec![]);
@@ -553,6 +899,22 @@ mod tests {
         assert!(list.remove_include(100).is_none());
     }
 
+    #[test]
+    fn test_filter_list_dim_keeps_lines() {
+        let mut list = FilterList::new();
+        list.add_include("request");
+        list.add(FilterKind::Dim, "HEALTHCHECK");
+
+        assert!(list.matches(b"request /healthcheck"));
+        assert!(list.is_dimmed(b"request /healthcheck"));
+        assert!(list.hides_lines());
+        assert!(!list.is_dimmed(b"request /orders"));
+        assert_eq!(list.len(), 2);
+
+        assert_eq!(list.remove(1).map(|r| r.kind()), Some(FilterKind::Dim));
+        assert!(!list.is_dimmed(b"request /healthcheck"));
+    }
+
     #[test]
     fn test_filter_list_case_insensitive() {
         let mut list = FilterList::new();
@@ -614,4 +976,91 @@ mod tests {
         assert!(rule.matches(b"anything"));
         assert!(rule.matches(b""));
     }
+
+    #[test]
+    fn test_filter_rule_unless() {
+        let rule = FilterRule::new("error unless retry unless Timeout", FilterKind::Include);
+        assert!(rule.matches(b"ERROR db down"));
+        assert!(!rule.matches(b"error, will retry"));
+        assert!(!rule.matches(b"error: timeout"));
+        assert!(!rule.matches(b"all good"));
+
+        // Exceptions only apply to their own rule
+        let mut list = FilterList::new();
+        list.add_include("error unless retry");
+        list.add_include("db");
+        assert!(list.matches(b"db error"));
+        assert!(!list.matches(b"db error, retry"));
+        list.add_exclude("debug unless payment");
+        assert!(!list.matches(b"db error debug"));
+        assert!(list.matches(b"db error debug payment"));
+    }
+
+    #[test]
+    fn test_filter_scoped_to_file() {
+        let worker = Path::new("/var/log/worker.log");
+        let api = Path::new("/var/log/api.log");
+        let mut list = FilterList::new();
+        list.add_rule(
+            FilterRule::new("timeout", FilterKind::Exclude).with_file(Some("worker.log".into())),
+        );
+        list.add_rule(
+            FilterRule::new("job", FilterKind::Include).with_file(Some("log/worker.log".into())),
+        );
+
+        assert!(!list.matches_from(b"job timeout", Some(worker)));
+        assert!(list.matches_from(b"job done", Some(worker)));
+        assert!(!list.matches_from(b"request done", Some(worker)));
+        assert!(list.matches_from(b"request timeout", Some(api)));
+        assert!(list.matches(b"request timeout"));
+
+        let dir = FilterRule::new("x", FilterKind::Include).with_file(Some("var/log".into()));
+        assert!(dir.applies_to(Some(worker)));
+        assert!(!dir.applies_to(Some(Path::new("/var/logs/api.log"))));
+    }
+
+    #[test]
+    fn test_parse_filter_file() {
+        let text = "# shared noise list\n\
+                    payment\n\
+                    \n\
+                    !healthcheck\n\
+                    ! GET /metrics \n\
+                    \\#1234\n\
+                    \\!important\n\
+                    !\n";
+        assert_eq!(
+            parse_filter_file(text),
+            vec![
+                (FilterKind::Include, "payment".to_string()),
+                (FilterKind::Exclude, "healthcheck".to_string()),
+                (FilterKind::Exclude, "GET /metrics".to_string()),
+                (FilterKind::Include, "#1234".to_string()),
+                (FilterKind::Include, "!important".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_filter_list_toggle() {
+        let mut list = FilterList::new();
+        list.add_include("error");
+        list.add_exclude("debug");
+        list.add_dim("health");
+
+        assert_eq!(list.toggle(0), Some(false));
+        assert!(list.matches(b"info message"));
+        assert!(!list.matches(b"error in debug mode"));
+        assert!(list.hides_lines());
+
+        assert_eq!(list.toggle(1), Some(false));
+        assert!(!list.hides_lines());
+        assert!(list.matches(b"error in debug mode"));
+
+        assert_eq!(list.toggle(2), Some(false));
+        assert!(!list.is_dimmed(b"health check"));
+        assert_eq!(list.toggle(2), Some(true));
+        assert!(list.is_dimmed(b"health check"));
+        assert_eq!(list.toggle(3), None);
+    }
 }
@@ -0,0 +1,320 @@
+//! Per-rule match bitmaps for the filter list.
+//!
+//! Each filter pattern is evaluated once against the storage and the indices
+//! of its matching lines are kept as a roaring bitmap. The visible lines are
+//! composed from those bitmaps (AND of includes, ANDNOT of excludes), so
+//! adding, removing or toggling a filter does not rescan the file.
+
+use std::collections::HashMap;
+
+use rayon::prelude::*;
+use roaring::RoaringBitmap;
+
+use super::filter::{FilterKind, FilterList, FilterRule};
+use super::log_storage::LogStorage;
+
+/// Bitmaps kept for patterns that are no longer in the filter list, so a
+/// removed filter can be added back without a rescan.
+const MAX_CACHED: usize = 32;
+
+/// Lines selected by one rule.
+#[derive(Debug)]
+struct Entry {
+    rule: FilterRule,
+    /// Lines the rule keeps (includes) or acts on (excludes, dims)
+    bitmap: RoaringBitmap,
+    /// Lines matching the pattern within the rule's scope
+    matches: u64,
+}
+
+/// Matching line indices per filter pattern.
+#[derive(Debug, Default)]
+pub struct FilterIndex {
+    /// Entries per rule key
+    entries: HashMap<String, Entry>,
+    /// Number of storage lines the bitmaps cover
+    covered: usize,
+}
+
+/// Patterns match case-insensitively, so rules differing only in case share
+/// a bitmap. A rule limited to one file keeps the lines of other files in
+/// its bitmap if it is an include, so its kind is part of the key. Rules
+/// matching whole words (`:set word`) get a key of their own, marked with a
+/// NUL no pattern contains.
+fn key(rule: &FilterRule) -> String {
+    let key = match &rule.file {
+        Some(file) => format!(
+            "{}@{}:{}",
+            rule.kind.name(),
+            file,
+            rule.pattern().to_lowercase()
+        ),
+        None => rule.pattern().to_lowercase(),
+    };
+    if rule.matches_whole_words() {
+        format!("{}\0word", key)
+    } else {
+        key
+    }
+}
+
+/// Bitmap and in-scope match count of `rule` over lines `start..end`.
+fn scan(storage: &LogStorage, rule: &FilterRule, start: usize, end: usize) -> (RoaringBitmap, u64) {
+    let in_scope: Vec<bool> = (0..storage.file_count())
+        .map(|file| rule.applies_to(storage.file_path(file)))
+        .collect();
+    let keeps_others = rule.kind == FilterKind::Include;
+    (start..end)
+        .into_par_iter()
+        .fold(
+            || (RoaringBitmap::new(), 0),
+            |(mut bitmap, mut matches), idx| {
+                let scoped = rule.file.is_none()
+                    || storage
+                        .line_file(idx)
+                        .is_some_and(|file| in_scope.get(file) == Some(&true));
+                if !scoped {
+                    if keeps_others {
+                        bitmap.insert(idx as u32);
+                    }
+                } else if storage
+                    .get_line(idx)
+                    .is_some_and(|line| rule.matches(line.as_bytes()))
+                {
+                    bitmap.insert(idx as u32);
+                    matches += 1;
+                }
+                (bitmap, matches)
+            },
+        )
+        .reduce(
+            || (RoaringBitmap::new(), 0),
+            |(a, a_matches), (b, b_matches)| (a | b, a_matches + b_matches),
+        )
+}
+
+impl FilterIndex {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Forget all bitmaps; call when the storage is replaced.
+    pub fn clear(&mut self) {
+        self.entries.clear();
+        self.covered = 0;
+    }
+
+    /// Bring the bitmaps up to date with `storage` and `filters`.
+    ///
+    /// Lines appended since the last update are matched against every cached
+    /// pattern; patterns seen for the first time scan the whole storage.
+    pub fn update(&mut self, storage: &LogStorage, filters: &FilterList) {
+        if storage.len() < self.covered {
+            self.clear();
+        }
+        let len = storage.len();
+        let missing: Vec<&FilterRule> = filters
+            .iter()
+            .map(|(_, rule)| rule)
+            .filter(|rule| !self.entries.contains_key(&key(rule)))
+            .collect();
+        if missing.is_empty() && len == self.covered {
+            return;
+        }
+        // Every line is read below; build lazy indexes in parallel first
+        storage.index_all();
+
+        if len > self.covered {
+            for entry in self.entries.values_mut() {
+                let (bitmap, matches) = scan(storage, &entry.rule, self.covered, len);
+                entry.bitmap |= bitmap;
+                entry.matches += matches;
+            }
+        }
+        for rule in missing {
+            let (bitmap, matches) = scan(storage, rule, 0, len);
+            let entry = Entry {
+                rule: rule.clone(),
+                bitmap,
+                matches,
+            };
+            self.entries.insert(key(rule), entry);
+        }
+        self.covered = len;
+
+        if self.entries.len() > MAX_CACHED {
+            let active: Vec<String> = filters.iter().map(|(_, rule)| key(rule)).collect();
+            self.entries.retain(|key, _| active.contains(key));
+        }
+    }
+
+    /// Drop lines `start..start + count` from the bitmaps and move later
+    /// lines back. Call before the lines are removed from `storage`.
+    pub fn remove_lines(&mut self, storage: &LogStorage, start: usize, count: usize) {
+        if start >= self.covered {
+            return;
+        }
+        let end = (start + count).min(self.covered);
+        let removed = (end - start) as u32;
+        for entry in self.entries.values_mut() {
+            entry.matches -= scan(storage, &entry.rule, start, end).1;
+            let moved: Vec<u32> = entry.bitmap.range(end as u32..).collect();
+            entry.bitmap.remove_range(start as u32..);
+            entry
+                .bitmap
+                .extend(moved.into_iter().map(|idx| idx - removed));
+        }
+        self.covered -= end - start;
+    }
+
+    /// Number of lines matching `rule`, if its bitmap has been built.
+    pub fn count(&self, rule: &FilterRule) -> Option<u64> {
+        self.entries.get(&key(rule)).map(|entry| entry.matches)
+    }
+
+    fn bitmap(&self, rule: &FilterRule) -> &RoaringBitmap {
+        &self
+            .entries
+            .get(&key(rule))
+            .expect("filter index is updated before it is queried")
+            .bitmap
+    }
+
+    /// Lines in `0..len` shown by the enabled include and exclude filters.
+    pub fn visible(&self, filters: &FilterList, len: usize) -> RoaringBitmap {
+        let mut includes = filters.includes().iter().filter(|rule| rule.enabled);
+        let mut visible = match includes.next() {
+            Some(rule) => self.bitmap(rule).clone(),
+            None => {
+                let mut all = RoaringBitmap::new();
+                all.insert_range(0..len as u32);
+                all
+            }
+        };
+        for rule in includes {
+            visible &= self.bitmap(rule);
+        }
+        for rule in filters.excludes().iter().filter(|rule| rule.enabled) {
+            visible -= self.bitmap(rule);
+        }
+        visible
+    }
+
+    /// Check whether line `idx` passes the enabled include and exclude filters.
+    pub fn is_visible(&self, filters: &FilterList, idx: usize) -> bool {
+        let idx = idx as u32;
+        filters
+            .includes()
+            .iter()
+            .filter(|rule| rule.enabled)
+            .all(|rule| self.bitmap(rule).contains(idx))
+            && !filters
+                .excludes()
+                .iter()
+                .filter(|rule| rule.enabled)
+                .any(|rule| self.bitmap(rule).contains(idx))
+    }
+
+    /// Check whether line `idx` matches an enabled dim filter.
+    ///
+    /// Returns None if the line or a dim filter has not been indexed yet.
+    pub fn is_dimmed(&self, filters: &FilterList, idx: usize) -> Option<bool> {
+        if idx >= self.covered {
+            return None;
+        }
+        let idx = idx as u32;
+        let mut dimmed = false;
+        for rule in filters.dims().iter().filter(|rule| rule.enabled) {
+            dimmed |= self.entries.get(&key(rule))?.bitmap.contains(idx);
+        }
+        Some(dimmed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::log_storage::storage_from;
+
+    #[test]
+    fn test_compose_rules() {
+        let (_file, storage) = storage_from(&[
+            "ERROR db timeout",
+            "INFO db connected",
+            "ERROR cache miss",
+            "DEBUG error retry",
+        ]);
+        let mut filters = FilterList::new();
+        filters.add_include("error");
+        filters.add_exclude("debug");
+        filters.add_dim("cache");
+
+        let mut index = FilterIndex::new();
+        index.update(&storage, &filters);
+        let visible: Vec<u32> = index.visible(&filters, storage.len()).iter().collect();
+        assert_eq!(visible, vec![0, 2]);
+        assert_eq!(index.count(&filters.includes()[0]), Some(3));
+        assert_eq!(index.count(&filters.excludes()[0]), Some(1));
+        assert!(index.is_visible(&filters, 2));
+        assert!(!index.is_visible(&filters, 3));
+        assert_eq!(index.is_dimmed(&filters, 2), Some(true));
+        assert_eq!(index.is_dimmed(&filters, 0), Some(false));
+
+        // Toggling only recomposes the existing bitmaps
+        filters.toggle(0);
+        let visible: Vec<u32> = index.visible(&filters, storage.len()).iter().collect();
+        assert_eq!(visible, vec![0, 1, 2]);
+    }
+
+    #[test]
+    fn test_removed_pattern_stays_cached() {
+        let (_file, storage) = storage_from(&["a", "b", "ab"]);
+        let mut filters = FilterList::new();
+        filters.add_include("A");
+        let mut index = FilterIndex::new();
+        index.update(&storage, &filters);
+
+        filters.remove(0);
+        filters.add_exclude("a");
+        index.update(&storage, &filters);
+        assert_eq!(index.entries.len(), 1);
+        let visible: Vec<u32> = index.visible(&filters, storage.len()).iter().collect();
+        assert_eq!(visible, vec![1]);
+    }
+
+    #[test]
+    fn test_update_covers_appended_lines() {
+        let (_file, mut storage) = storage_from(&["error one", "info two"]);
+        let mut filters = FilterList::new();
+        filters.add_include("error");
+        let mut index = FilterIndex::new();
+        index.update(&storage, &filters);
+
+        storage.append_line(0, b"error three", None);
+        storage.append_line(0, b"info four", None);
+        index.update(&storage, &filters);
+        assert_eq!(index.count(&filters.includes()[0]), Some(2));
+        assert!(index.is_visible(&filters, 2));
+        assert!(!index.is_visible(&filters, 3));
+    }
+
+    #[test]
+    fn test_removed_lines_move_later_lines_back() {
+        let (_file, mut storage) = storage_from(&["error one"]);
+        for line in ["error two", "info three", "error four"] {
+            storage.append_line(0, line.as_bytes(), None);
+        }
+        let mut filters = FilterList::new();
+        filters.add_include("error");
+        let mut index = FilterIndex::new();
+        index.update(&storage, &filters);
+
+        index.remove_lines(&storage, 1, 2);
+        storage.prune_live(2);
+        index.update(&storage, &filters);
+        let visible: Vec<u32> = index.visible(&filters, storage.len()).iter().collect();
+        assert_eq!(visible, vec![0, 1]);
+        assert_eq!(index.count(&filters.includes()[0]), Some(2));
+        assert_eq!(storage.get_line(1).unwrap().as_bytes(), b"error four");
+    }
+}
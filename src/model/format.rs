@@ -0,0 +1,92 @@
+//! Log format recognition from line shape.
+
+/// Broad family of a log line's layout, used to describe loaded files.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum LogFormat {
+    /// One JSON object per line
+    Json,
+    /// `2024-01-01T10:00:00Z ...`
+    Iso8601,
+    /// `2024-01-01 10:00:00 ...` or `2024/01/01 10:00:00 ...`
+    DateTime,
+    /// `Jan 01 10:00:00 host ...`
+    Syslog,
+    /// `127.0.0.1 - - [01/Jan/2024:10:00:00 +0000] "GET ..."`
+    CommonLog,
+    /// Anything else
+    Plain,
+}
+
+impl LogFormat {
+    pub fn name(self) -> &'static str {
+        match self {
+            LogFormat::Json => "JSON",
+            LogFormat::Iso8601 => "ISO 8601",
+            LogFormat::DateTime => "date/time",
+            LogFormat::Syslog => "syslog",
+            LogFormat::CommonLog => "Common Log Format",
+            LogFormat::Plain => "plain text",
+        }
+    }
+}
+
+const MONTHS: [&str; 12] = [
+    "Jan", "Feb", "Mar", "Apr", "May", "Jun", "Jul", "Aug", "Sep", "Oct", "Nov", "Dec",
+];
+
+/// Recognize the format of one log line.
+pub fn detect_format(line: &str) -> LogFormat {
+    let line = line.trim_start();
+    let line = line.strip_prefix('[').unwrap_or(line);
+    let bytes = line.as_bytes();
+    let digits = |range: std::ops::Range<usize>| {
+        bytes.len() >= range.end && bytes[range].iter().all(u8::is_ascii_digit)
+    };
+
+    if line.starts_with('{') {
+        return LogFormat::Json;
+    }
+    if digits(0..4) && matches!(bytes.get(4), Some(b'-') | Some(b'/')) && digits(5..7) {
+        return match bytes.get(10) {
+            Some(b'T') => LogFormat::Iso8601,
+            _ => LogFormat::DateTime,
+        };
+    }
+    if MONTHS.iter().any(|m| line.starts_with(m)) && bytes.get(3) == Some(&b' ') {
+        return LogFormat::Syslog;
+    }
+    if let Some(pos) = line.find(" [") {
+        let rest = &line.as_bytes()[pos + 2..];
+        if rest.len() > 11 && rest[2] == b'/' && rest[6] == b'/' && rest[11] == b':' {
+            return LogFormat::CommonLog;
+        }
+    }
+    LogFormat::Plain
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_detect_format() {
+        assert_eq!(detect_format(r#"{"level":"info"}"#), LogFormat::Json);
+        assert_eq!(
+            detect_format("2024-01-01T10:00:00Z INFO ok"),
+            LogFormat::Iso8601
+        );
+        assert_eq!(
+            detect_format("[2024-01-01 10:00:00] ERROR x"),
+            LogFormat::DateTime
+        );
+        assert_eq!(
+            detect_format("Jan 01 10:00:00 host sshd[1]: hi"),
+            LogFormat::Syslog
+        );
+        assert_eq!(
+            detect_format(r#"127.0.0.1 - - [01/Jan/2024:10:00:00 +0000] "GET / HTTP/1.1" 200"#),
+            LogFormat::CommonLog
+        );
+        assert_eq!(detect_format("just some text"), LogFormat::Plain);
+    }
+}
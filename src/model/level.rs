@@ -0,0 +1,325 @@
+//! Log level detection from line text.
+//!
+//! Built-in level words (`ERROR`, `WRN`, `Information`, ...) can be extended
+//! by the config's `[levels]` section, e.g. `"W" = "warn"` or
+//! `'re:sev=[45]' = "warn"`, so filters, the gutter and `:stats` treat
+//! heterogeneous logs alike.
+
+use std::sync::{PoisonError, RwLock};
+
+use regex::{Regex, RegexBuilder};
+
+/// Severity of a log line.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum LogLevel {
+    Error,
+    Warn,
+    Info,
+    Debug,
+    Trace,
+}
+
+/// Number of leading words searched for a level keyword.
+///
+/// Levels sit near the start of a line (after the timestamp and maybe a
+/// thread or logger name); looking further would pick up words like "error"
+/// from the message itself. Words starting with a digit (timestamp parts)
+/// are not counted.
+const LEVEL_SEARCH_WORDS: usize = 8;
+
+impl LogLevel {
+    /// All levels, most severe first.
+    pub const ALL: [LogLevel; 5] = [
+        LogLevel::Error,
+        LogLevel::Warn,
+        LogLevel::Info,
+        LogLevel::Debug,
+        LogLevel::Trace,
+    ];
+
+    /// Parse a level keyword such as `ERROR`, `warn`, `Information` or `DBG`.
+    pub fn from_keyword(word: &str) -> Option<Self> {
+        let level = match word.to_ascii_uppercase().as_str() {
+            "ERROR" | "ERR" | "FATAL" | "FTL" | "CRITICAL" | "CRIT" | "PANIC" | "SEVERE" => {
+                LogLevel::Error
+            }
+            "WARN" | "WARNING" | "WRN" => LogLevel::Warn,
+            "INFO" | "INFORMATION" | "INF" | "NOTICE" => LogLevel::Info,
+            "DEBUG" | "DBG" => LogLevel::Debug,
+            "TRACE" | "TRC" | "VERBOSE" | "VRB" => LogLevel::Trace,
+            _ => return None,
+        };
+        Some(level)
+    }
+
+    /// Lowercase display name.
+    pub fn name(self) -> &'static str {
+        match self {
+            LogLevel::Error => "error",
+            LogLevel::Warn => "warn",
+            LogLevel::Info => "info",
+            LogLevel::Debug => "debug",
+            LogLevel::Trace => "trace",
+        }
+    }
+
+    /// Single-letter gutter indicator.
+    pub fn letter(self) -> &'static str {
+        match self {
+            LogLevel::Error => "E",
+            LogLevel::Warn => "W",
+            LogLevel::Info => "I",
+            LogLevel::Debug => "D",
+            LogLevel::Trace => "T",
+        }
+    }
+
+    /// Gutter symbol; each level has a distinct shape so levels can be told
+    /// apart without color.
+    pub fn symbol(self) -> &'static str {
+        match self {
+            LogLevel::Error => "✖",
+            LogLevel::Warn => "▲",
+            LogLevel::Info => "◆",
+            LogLevel::Debug => "◇",
+            LogLevel::Trace => "·",
+        }
+    }
+}
+
+/// Levels the view is limited to, e.g. `error+` for errors and warnings.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct LevelSet {
+    /// Bit `level as u8` is set for each level in the set
+    bits: u8,
+}
+
+impl LevelSet {
+    /// Every level.
+    pub const ALL: LevelSet = LevelSet {
+        bits: (1 << LogLevel::ALL.len()) - 1,
+    };
+
+    /// Only `level`.
+    pub fn only(level: LogLevel) -> Self {
+        Self {
+            bits: 1 << level as u8,
+        }
+    }
+
+    /// `level` and every level more severe than it.
+    pub fn at_least(level: LogLevel) -> Self {
+        Self {
+            bits: (2 << level as u8) - 1,
+        }
+    }
+
+    /// Parse `error+` (a level and those more severe), `warn` or a
+    /// comma-separated list such as `error,warn`.
+    pub fn parse(spec: &str) -> Option<Self> {
+        let mut set = LevelSet::default();
+        for part in spec.split(',').map(str::trim) {
+            set.bits |= match part.strip_suffix('+') {
+                Some(level) => LevelSet::at_least(LogLevel::from_keyword(level)?).bits,
+                None => LevelSet::only(LogLevel::from_keyword(part)?).bits,
+            };
+        }
+        Some(set)
+    }
+
+    pub fn contains(self, level: LogLevel) -> bool {
+        self.bits & (1 << level as u8) != 0
+    }
+
+    pub fn is_empty(self) -> bool {
+        self.bits == 0
+    }
+
+    /// Add `level` if it is not in the set, remove it otherwise.
+    pub fn toggle(&mut self, level: LogLevel) {
+        self.bits ^= 1 << level as u8;
+    }
+
+    /// Levels in the set, most severe first.
+    pub fn iter(self) -> impl Iterator<Item = LogLevel> {
+        LogLevel::ALL
+            .into_iter()
+            .filter(move |&level| self.contains(level))
+    }
+
+    /// Short form for the filter bar: `error+`, `warn` or `error,info`.
+    pub fn label(self) -> String {
+        if let Some(least) = self.iter().last() {
+            if self == LevelSet::at_least(least) && self.bits.count_ones() > 1 {
+                return format!("{}+", least.name());
+            }
+        }
+        self.iter()
+            .map(LogLevel::name)
+            .collect::<Vec<_>>()
+            .join(",")
+    }
+}
+
+/// Level mapping of the config's `[levels]` section.
+#[derive(Debug, Clone, Default)]
+pub struct LevelMap {
+    /// Level words (uppercase) and their levels, tried before the built-in words
+    words: Vec<(String, LogLevel)>,
+    /// Patterns matched anywhere in the line, in config order
+    patterns: Vec<(Regex, LogLevel)>,
+}
+
+/// Mapping used by [`detect_level`]; set once the config is loaded.
+static LEVEL_MAP: RwLock<LevelMap> = RwLock::new(LevelMap::new());
+
+impl LevelMap {
+    pub const fn new() -> Self {
+        Self {
+            words: Vec::new(),
+            patterns: Vec::new(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.words.is_empty() && self.patterns.is_empty()
+    }
+
+    /// Map a `[levels]` key to `level`: a level word such as `WRN`, a
+    /// case-insensitive regex with a `re:` prefix, or other text such as
+    /// `sev=4`, matched literally anywhere in the line.
+    pub fn add(&mut self, key: &str, level: LogLevel) -> Result<(), regex::Error> {
+        let pattern = match key.strip_prefix("re:") {
+            Some(regex) => regex.to_string(),
+            None if is_word(key) => {
+                self.words.push((key.to_ascii_uppercase(), level));
+                return Ok(());
+            }
+            None => regex::escape(key),
+        };
+        let regex = RegexBuilder::new(&pattern).case_insensitive(true).build()?;
+        self.patterns.push((regex, level));
+        Ok(())
+    }
+
+    /// Level of `line`: the first matching pattern, else the first mapped
+    /// or built-in level word near the start.
+    pub fn detect(&self, line: &str) -> Option<LogLevel> {
+        if let Some(&(_, level)) = self.patterns.iter().find(|(regex, _)| regex.is_match(line)) {
+            return Some(level);
+        }
+        line.split(|c: char| !c.is_ascii_alphanumeric())
+            .filter(|word| is_word(word))
+            .take(LEVEL_SEARCH_WORDS)
+            .find_map(|word| {
+                self.words
+                    .iter()
+                    .find(|(mapped, _)| mapped.eq_ignore_ascii_case(word))
+                    .map(|&(_, level)| level)
+                    .or_else(|| LogLevel::from_keyword(word))
+            })
+    }
+}
+
+/// Check whether `text` counts as a level word: letters and digits,
+/// starting with a letter.
+fn is_word(text: &str) -> bool {
+    text.starts_with(|c: char| c.is_ascii_alphabetic())
+        && text.chars().all(|c| c.is_ascii_alphanumeric())
+}
+
+/// Use `map` for every later [`detect_level`] call.
+pub fn set_level_map(map: LevelMap) {
+    *LEVEL_MAP.write().unwrap_or_else(PoisonError::into_inner) = map;
+}
+
+/// Detect the level of a log line from the first level keyword near its
+/// start, with the configured `[levels]` mapping applied.
+pub fn detect_level(line: &str) -> Option<LogLevel> {
+    LEVEL_MAP
+        .read()
+        .unwrap_or_else(PoisonError::into_inner)
+        .detect(line)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_detect_common_formats() {
+        assert_eq!(
+            detect_level("2024-01-01 10:00:00 ERROR db timeout"),
+            Some(LogLevel::Error)
+        );
+        assert_eq!(
+            detect_level("[2024-01-01T10:00:00Z] [WRN] disk almost full"),
+            Some(LogLevel::Warn)
+        );
+        assert_eq!(
+            detect_level("2024-01-01 10:00:00.123 [Information] started"),
+            Some(LogLevel::Info)
+        );
+        assert_eq!(detect_level("level=debug msg=hello"), Some(LogLevel::Debug));
+        assert_eq!(detect_level("no level here"), None);
+    }
+
+    #[test]
+    fn test_level_map_words_and_patterns() {
+        let mut map = LevelMap::new();
+        map.add("W", LogLevel::Warn).unwrap();
+        map.add("notice", LogLevel::Warn).unwrap();
+        map.add("sev=4", LogLevel::Warn).unwrap();
+        map.add(r"re:\bsev=[0-3]\b", LogLevel::Error).unwrap();
+        assert!(map.add("re:(", LogLevel::Error).is_err());
+
+        assert_eq!(map.detect("10:00:00 [W] disk"), Some(LogLevel::Warn));
+        assert_eq!(map.detect("NOTICE rotated"), Some(LogLevel::Warn));
+        assert_eq!(map.detect("ts=1 sev=4 msg=slow"), Some(LogLevel::Warn));
+        assert_eq!(map.detect("ts=1 SEV=2 msg=down"), Some(LogLevel::Error));
+        assert_eq!(map.detect("DBG still built in"), Some(LogLevel::Debug));
+        assert_eq!(
+            LevelMap::new().detect("NOTICE rotated"),
+            Some(LogLevel::Info)
+        );
+    }
+
+    #[test]
+    fn test_level_sets() {
+        let set = LevelSet::parse("warn+").unwrap();
+        assert!(set.contains(LogLevel::Error) && set.contains(LogLevel::Warn));
+        assert!(!set.contains(LogLevel::Info));
+        assert_eq!(set.label(), "warn+");
+        assert_eq!(LevelSet::parse("trace+"), Some(LevelSet::ALL));
+
+        let mut set = LevelSet::parse("ERR, info").unwrap();
+        assert_eq!(set.label(), "error,info");
+        set.toggle(LogLevel::Warn);
+        assert_eq!(set.label(), "info+");
+        set.toggle(LogLevel::Info);
+        assert_eq!(set.label(), "warn+");
+        assert_eq!(LevelSet::only(LogLevel::Error).label(), "error");
+        assert_eq!(LevelSet::parse("loud"), None);
+    }
+
+    #[test]
+    fn test_symbols_are_distinct() {
+        for (i, a) in LogLevel::ALL.iter().enumerate() {
+            for b in &LogLevel::ALL[i + 1..] {
+                assert_ne!(a.symbol(), b.symbol());
+            }
+        }
+    }
+
+    #[test]
+    fn test_first_keyword_wins_and_message_is_ignored() {
+        assert_eq!(
+            detect_level("INFO user clicked the error button"),
+            Some(LogLevel::Info)
+        );
+        assert_eq!(
+            detect_level("one two three four five six seven eight nine error"),
+            None
+        );
+    }
+}
@@ -1,13 +1,27 @@
 use chrono::{DateTime, Utc};
 
+use super::LogLevel;
+
+/// Stored timestamp of a line without one.
+const NO_TIMESTAMP: i64 = i64::MIN;
+
+/// The level is kept in the top bits of the file index word.
+const LEVEL_SHIFT: u32 = 29;
+
+/// Bits of the file index word that hold the file index.
+const FILE_INDEX_MASK: u32 = (1 << LEVEL_SHIFT) - 1;
+
 /// Information about a single line in the log file.
-/// Stores only metadata (16 bytes per line) instead of full content.
+/// Stores only metadata (24 bytes per line) instead of full content.
 #[derive(Debug, Clone, Copy)]
 pub struct LineInfo {
     pub offset: u64,
     pub length: u32,
-    pub file_index: u32,
-    pub timestamp: Option<DateTime<Utc>>,
+    /// File index, with the level code (0 for none, else 1 + the level) in
+    /// the bits from `LEVEL_SHIFT` up
+    file_and_level: u32,
+    /// Nanoseconds since the Unix epoch, or `NO_TIMESTAMP`
+    timestamp: i64,
 }
 
 impl LineInfo {
@@ -16,12 +30,15 @@ impl LineInfo {
         Self {
             offset,
             length,
-            file_index,
-            timestamp: None,
+            file_and_level: file_index & FILE_INDEX_MASK,
+            timestamp: NO_TIMESTAMP,
         }
     }
 
     /// Create a new LineInfo with timestamp.
+    ///
+    /// Timestamps outside the nanosecond range (years 1677 to 2262) are
+    /// dropped.
     pub fn with_timestamp(
         file_index: u32,
         offset: u64,
@@ -31,11 +48,48 @@ impl LineInfo {
         Self {
             offset,
             length,
-            file_index,
-            timestamp,
+            file_and_level: file_index & FILE_INDEX_MASK,
+            timestamp: timestamp
+                .and_then(|ts| ts.timestamp_nanos_opt())
+                .unwrap_or(NO_TIMESTAMP),
+        }
+    }
+
+    /// The same line with its detected level.
+    pub fn with_level(mut self, level: Option<LogLevel>) -> Self {
+        let code = level.map_or(0, |level| level as u32 + 1);
+        self.file_and_level = (self.file_and_level & FILE_INDEX_MASK) | code << LEVEL_SHIFT;
+        self
+    }
+
+    /// Index of the file the line is in.
+    pub fn file_index(&self) -> u32 {
+        self.file_and_level & FILE_INDEX_MASK
+    }
+
+    pub fn set_file_index(&mut self, file_index: u32) {
+        self.file_and_level =
+            (self.file_and_level & !FILE_INDEX_MASK) | (file_index & FILE_INDEX_MASK);
+    }
+
+    /// Level of the line, if one was detected when it was indexed.
+    pub fn level(&self) -> Option<LogLevel> {
+        match self.file_and_level >> LEVEL_SHIFT {
+            0 => None,
+            code => LogLevel::ALL.get(code as usize - 1).copied(),
         }
     }
 
+    /// Timestamp of the line, if one was detected.
+    pub fn timestamp(&self) -> Option<DateTime<Utc>> {
+        self.timestamp_nanos().map(DateTime::from_timestamp_nanos)
+    }
+
+    /// Timestamp as nanoseconds since the Unix epoch, for cheap comparisons.
+    pub fn timestamp_nanos(&self) -> Option<i64> {
+        (self.timestamp != NO_TIMESTAMP).then_some(self.timestamp)
+    }
+
     /// Get the end offset (exclusive) of this line.
     pub fn end_offset(&self) -> u64 {
         self.offset + self.length as u64
@@ -50,11 +104,13 @@ mod tests {
     fn test_line_info_basic() {
         let info = LineInfo::new(0, 100, 50);
 
-        assert_eq!(info.file_index, 0);
+        assert_eq!(info.file_index(), 0);
         assert_eq!(info.offset, 100);
         assert_eq!(info.length, 50);
         assert_eq!(info.end_offset(), 150);
-        assert!(info.timestamp.is_none());
+        assert!(info.timestamp().is_none());
+        assert!(info.timestamp_nanos().is_none());
+        assert_eq!(std::mem::size_of::<LineInfo>(), 24);
     }
 
     #[test]
@@ -62,9 +118,26 @@ mod tests {
         let timestamp = Utc::now();
         let info = LineInfo::with_timestamp(1, 200, 100, Some(timestamp));
 
-        assert_eq!(info.file_index, 1);
+        assert_eq!(info.file_index(), 1);
         assert_eq!(info.offset, 200);
         assert_eq!(info.length, 100);
-        assert_eq!(info.timestamp, Some(timestamp));
+        assert_eq!(info.timestamp(), Some(timestamp));
+        assert_eq!(info.timestamp_nanos(), timestamp.timestamp_nanos_opt());
+
+        let ancient = DateTime::parse_from_rfc3339("1500-01-01T00:00:00Z").unwrap();
+        let info = LineInfo::with_timestamp(0, 0, 1, Some(ancient.to_utc()));
+        assert_eq!(info.timestamp(), None);
+    }
+
+    #[test]
+    fn test_line_info_level_and_file_index() {
+        let mut info = LineInfo::new(7, 0, 10).with_level(Some(LogLevel::Trace));
+        assert_eq!(info.level(), Some(LogLevel::Trace));
+        assert_eq!(info.file_index(), 7);
+
+        info.set_file_index(FILE_INDEX_MASK);
+        assert_eq!(info.file_index(), FILE_INDEX_MASK);
+        assert_eq!(info.level(), Some(LogLevel::Trace));
+        assert_eq!(info.with_level(None).level(), None);
     }
 }
@@ -1,9 +1,333 @@
+use memchr::{memchr_iter, memrchr_iter};
 use memmap2::Mmap;
-use std::path::Path;
+use rayon::prelude::*;
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
 
+use chrono::{DateTime, NaiveDate, Utc};
+
+use crate::error::QlogError;
+use crate::model::compression::Compression;
+use crate::model::level::{detect_level, LogLevel};
 use crate::model::line_info::LineInfo;
 use crate::model::mmap_str::MmapStr;
-use crate::model::timestamp::detect_timestamp;
+use crate::model::timestamp::detect_timestamp_near;
+
+/// Block size of on-demand indexes: newlines are counted per block up front.
+pub const ON_DEMAND_BLOCK_SIZE: usize = 1024 * 1024;
+
+/// Full indexes report progress after every this many bytes.
+const INDEX_CHUNK_BYTES: u64 = 16 * 1024 * 1024;
+
+/// Progress reported while a file is opened.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OpenProgress {
+    /// Compressed bytes read and the compressed size, while a gzip or zstd
+    /// file is decompressed
+    Decompressed { read: u64, total: u64 },
+    /// Lines indexed so far, the bytes they cover and the bytes to index,
+    /// while a full index is built
+    Indexed {
+        lines: usize,
+        bytes: u64,
+        total: u64,
+    },
+}
+
+/// How the line index of a file is built.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IndexMode {
+    /// Index every line up front.
+    Full,
+    /// Count lines per `block_size` bytes up front and compute exact line
+    /// offsets per block the first time a line in it is accessed.
+    OnDemand { block_size: usize },
+    /// Index only the last `bytes` bytes of the file.
+    Tail { bytes: u64 },
+    /// Index only the last `lines` lines of the file.
+    TailLines { lines: usize },
+}
+
+/// Line index of a single memory-mapped file.
+#[derive(Debug)]
+enum FileLines {
+    /// Every line of the file.
+    Full(Vec<LineInfo>),
+    /// The trailing lines of the file; earlier lines are not indexed.
+    Tail(Vec<LineInfo>),
+    /// Checkpoints with lazily refined blocks.
+    Sparse(SparseIndex),
+}
+
+impl FileLines {
+    fn len(&self) -> usize {
+        match self {
+            FileLines::Full(lines) | FileLines::Tail(lines) => lines.len(),
+            FileLines::Sparse(sparse) => sparse.len,
+        }
+    }
+
+    fn get<'a>(&'a self, mmap: &[u8], local: usize) -> Option<&'a LineInfo> {
+        match self {
+            FileLines::Full(lines) | FileLines::Tail(lines) => lines.get(local),
+            FileLines::Sparse(sparse) => sparse.get(mmap, local),
+        }
+    }
+
+    fn set_file_index(&mut self, file_index: u32) {
+        match self {
+            FileLines::Full(lines) | FileLines::Tail(lines) => {
+                for line in lines {
+                    line.set_file_index(file_index);
+                }
+            }
+            FileLines::Sparse(sparse) => {
+                sparse.file_index = file_index;
+                for block in sparse.blocks.iter_mut().filter_map(|b| b.get_mut()) {
+                    for line in block {
+                        line.set_file_index(file_index);
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Two-phase line index: newline counts per fixed-size byte block, with
+/// exact line offsets built per block on first access.
+#[derive(Debug)]
+struct SparseIndex {
+    file_index: u32,
+    block_size: usize,
+    /// Index (within the file) of the first line starting in each block
+    block_starts: Vec<usize>,
+    /// Total number of lines in the file
+    len: usize,
+    /// Exact line index per block, built on first access
+    blocks: Vec<OnceLock<Vec<LineInfo>>>,
+    /// Date the file was last modified, for year-less timestamps
+    modified: NaiveDate,
+}
+
+impl SparseIndex {
+    /// Count line starts per block in parallel.
+    ///
+    /// A line belongs to the block holding the newline before it (the first
+    /// line belongs to block 0), so blocks never need to agree on boundaries.
+    fn build(data: &[u8], file_index: u32, block_size: usize, modified: NaiveDate) -> Self {
+        let block_size = block_size.max(1);
+        let mut counts: Vec<usize> = data
+            .par_chunks(block_size)
+            .map(|chunk| memchr_iter(b'\n', chunk).count())
+            .collect();
+
+        if let (Some(first), Some(last)) = (counts.first_mut(), data.last()) {
+            // The first line has no preceding newline...
+            *first += 1;
+            // ...and a trailing newline does not start another line
+            if *last == b'\n' {
+                *counts.last_mut().unwrap() -= 1;
+            }
+        }
+
+        let mut block_starts = Vec::with_capacity(counts.len());
+        let mut len = 0;
+        for count in counts {
+            block_starts.push(len);
+            len += count;
+        }
+
+        let blocks = (0..block_starts.len()).map(|_| OnceLock::new()).collect();
+        Self {
+            file_index,
+            block_size,
+            block_starts,
+            len,
+            blocks,
+            modified,
+        }
+    }
+
+    fn get<'a>(&'a self, data: &[u8], local: usize) -> Option<&'a LineInfo> {
+        if local >= self.len {
+            return None;
+        }
+        let block = self.block_starts.partition_point(|&start| start <= local) - 1;
+        self.block_lines(data, block)
+            .get(local - self.block_starts[block])
+    }
+
+    /// Exact lines starting in a block, building them if needed.
+    fn block_lines<'a>(&'a self, data: &[u8], block: usize) -> &'a [LineInfo] {
+        self.blocks[block].get_or_init(|| {
+            let lo = block * self.block_size;
+            let hi = (lo + self.block_size).min(data.len());
+            let first_start = if block == 0 {
+                Some(0)
+            } else {
+                memchr::memchr(b'\n', &data[lo..hi]).map(|pos| (lo + pos + 1) as u64)
+            };
+            let Some(first_start) = first_start else {
+                return Vec::new();
+            };
+            // The last line starting in this block ends at the first newline at or after `hi`
+            let end = memchr::memchr(b'\n', &data[hi..])
+                .map(|pos| hi + pos + 1)
+                .unwrap_or(data.len());
+            index_range(
+                data,
+                first_start,
+                end as u64,
+                self.file_index,
+                self.modified,
+            )
+        })
+    }
+
+    /// Build every block that has not been built yet, in parallel.
+    fn build_all(&self, data: &[u8]) {
+        (0..self.blocks.len()).into_par_iter().for_each(|block| {
+            self.block_lines(data, block);
+        });
+    }
+}
+
+/// Build line infos for the lines starting in `data[start..end]`, with
+/// their timestamps and levels.
+///
+/// Year-less timestamps are resolved against `modified`, the date the file
+/// was last modified.
+fn index_range(
+    data: &[u8],
+    start: u64,
+    end: u64,
+    file_index: u32,
+    modified: NaiveDate,
+) -> Vec<LineInfo> {
+    let mut lines = Vec::new();
+    let mut line_start = start;
+    let range = &data[start as usize..end as usize];
+
+    for pos in memchr_iter(b'\n', range) {
+        let offset = start + pos as u64;
+        let line_data = &data[line_start as usize..offset as usize];
+        lines.push(line_info(line_data, file_index, line_start, modified));
+        line_start = offset + 1;
+    }
+
+    // Handle last line if the range doesn't end with a newline
+    if line_start < end {
+        let line_data = &data[line_start as usize..end as usize];
+        lines.push(line_info(line_data, file_index, line_start, modified));
+    }
+
+    // The index lives as long as the file is open; drop growth slack
+    lines.shrink_to_fit();
+    lines
+}
+
+/// Line info of the line `bytes` that starts at `offset`.
+fn line_info(bytes: &[u8], file_index: u32, offset: u64, modified: NaiveDate) -> LineInfo {
+    let text = String::from_utf8_lossy(bytes);
+    LineInfo::with_timestamp(
+        file_index,
+        offset,
+        bytes.len() as u32,
+        detect_timestamp_near(&text, modified),
+    )
+    .with_level(detect_level(&text))
+}
+
+/// Build line infos for every line of `data`, reporting progress after
+/// each `INDEX_CHUNK_BYTES` chunk (cut at a line end).
+fn index_full(
+    data: &[u8],
+    modified: NaiveDate,
+    progress: &mut dyn FnMut(OpenProgress),
+) -> Vec<LineInfo> {
+    let total = data.len() as u64;
+    let mut lines = Vec::new();
+    let mut start = 0;
+    while start < total {
+        let cut = (start + INDEX_CHUNK_BYTES).min(total);
+        let end =
+            memchr::memchr(b'\n', &data[cut as usize..]).map_or(total, |pos| cut + pos as u64 + 1);
+        lines.extend(index_range(data, start, end, 0, modified));
+        start = end;
+        progress(OpenProgress::Indexed {
+            lines: lines.len(),
+            bytes: end,
+            total,
+        });
+    }
+    lines.shrink_to_fit();
+    lines
+}
+
+/// Start of the first complete line within the last `bytes` bytes of `data`.
+fn tail_start(data: &[u8], bytes: u64) -> u64 {
+    let start = (data.len() as u64).saturating_sub(bytes);
+    if start == 0 || data[start as usize - 1] == b'\n' {
+        return start;
+    }
+    memchr::memchr(b'\n', &data[start as usize..])
+        .map(|pos| start + pos as u64 + 1)
+        .unwrap_or(data.len() as u64)
+}
+
+/// Start of the `count`-th line counted back from the end of `data`.
+fn tail_start_lines(data: &[u8], count: usize) -> u64 {
+    if count == 0 {
+        return data.len() as u64;
+    }
+    // A trailing newline terminates the last line rather than starting a new one
+    let end = match data.last() {
+        Some(b'\n') => data.len() - 1,
+        _ => data.len(),
+    };
+    memrchr_iter(b'\n', &data[..end])
+        .nth(count - 1)
+        .map(|pos| pos as u64 + 1)
+        .unwrap_or(0)
+}
+
+/// Memory-map a file, decompressing gzip and zstd files into anonymous
+/// memory. Returns the map, the date the file was last modified and the
+/// format it was decompressed from.
+fn map_file(
+    path: &Path,
+    progress: &mut dyn FnMut(OpenProgress),
+) -> Result<(Mmap, NaiveDate, Option<Compression>), QlogError> {
+    let file = std::fs::File::open(path).map_err(|source| QlogError::Open {
+        path: path.to_path_buf(),
+        source,
+    })?;
+    let mmap = unsafe { Mmap::map(&file) }.map_err(|source| QlogError::Map {
+        path: path.to_path_buf(),
+        source,
+    })?;
+    let modified = file
+        .metadata()
+        .and_then(|metadata| metadata.modified())
+        .map(DateTime::<Utc>::from)
+        .unwrap_or_else(|_| Utc::now())
+        .date_naive();
+
+    let Some(compression) = Compression::detect(&mmap) else {
+        return Ok((mmap, modified, None));
+    };
+    let data = compression
+        .decompress(&mmap, &mut |read, total| {
+            progress(OpenProgress::Decompressed { read, total })
+        })
+        .map_err(|source| QlogError::Decompress {
+            path: path.to_path_buf(),
+            source,
+        })?;
+    Ok((data, modified, Some(compression)))
+}
 
 /// Memory-mapped log storage with line index.
 /// Supports multiple files - each line stores which file (mmap) it belongs to.
@@ -11,8 +335,27 @@ use crate::model::timestamp::detect_timestamp;
 pub struct LogStorage {
     /// Multiple memory-mapped files
     mmaps: Vec<Mmap>,
-    /// Index of line positions across all files
-    lines: Vec<LineInfo>,
+    /// Line index of each file (parallel to `mmaps`)
+    files: Vec<FileLines>,
+    /// Global index of each file's first line (parallel to `mmaps`)
+    starts: Vec<usize>,
+    /// Total number of indexed lines across all files
+    len: usize,
+    /// Source path of each memory-mapped file (parallel to `mmaps`)
+    paths: Vec<PathBuf>,
+    /// Lines appended while following, placed after every file-backed line
+    live: Vec<LineInfo>,
+    /// Content of appended lines; `live` offsets point into this buffer
+    live_data: Vec<u8>,
+    /// Date each file was last modified (parallel to `mmaps`)
+    modified: Vec<NaiveDate>,
+    /// Format each file was decompressed from (parallel to `mmaps`)
+    compression: Vec<Option<Compression>>,
+    /// Concatenated index of each file-backed line when the files are
+    /// interleaved by time; empty while lines are in file order
+    order: Vec<u32>,
+    /// Inverse of `order`: global index of each concatenated line
+    position: Vec<u32>,
 }
 
 impl LogStorage {
@@ -20,98 +363,276 @@ impl LogStorage {
     pub fn empty() -> Self {
         Self {
             mmaps: Vec::new(),
-            lines: Vec::new(),
+            files: Vec::new(),
+            starts: Vec::new(),
+            len: 0,
+            paths: Vec::new(),
+            live: Vec::new(),
+            live_data: Vec::new(),
+            modified: Vec::new(),
+            compression: Vec::new(),
+            order: Vec::new(),
+            position: Vec::new(),
         }
     }
 
     /// Create a new LogStorage by memory-mapping a file and building the line index.
-    pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Self, Box<dyn std::error::Error>> {
-        let file = std::fs::File::open(path)?;
-        let mmap = unsafe { Mmap::map(&file)? };
-        let lines = Self::build_line_index(&mmap, 0); // file_index = 0 for single file
-
-        Ok(Self {
-            mmaps: vec![mmap],
-            lines,
-        })
+    pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Self, QlogError> {
+        Self::from_file_with(path, IndexMode::Full)
     }
 
-    /// Build the line index by scanning for newlines.
-    fn build_line_index(mmap: &Mmap, file_index: u32) -> Vec<LineInfo> {
-        let mut lines = Vec::new();
-        let mut offset: u64 = 0;
-        let mut line_start: u64 = 0;
+    /// Create a new LogStorage by memory-mapping a file, indexing it as `mode` requests.
+    pub fn from_file_with<P: AsRef<Path>>(path: P, mode: IndexMode) -> Result<Self, QlogError> {
+        Self::from_file_with_progress(path, mode, &mut |_| {})
+    }
 
-        for &byte in mmap.iter() {
-            if byte == b'\n' {
-                let length = (offset - line_start) as u32;
-                let line_data = &mmap[line_start as usize..offset as usize];
-                let timestamp = detect_timestamp(&String::from_utf8_lossy(line_data));
+    /// Like [`LogStorage::from_file_with`], calling `progress` while a gzip
+    /// or zstd file is decompressed and while a full index is built.
+    pub fn from_file_with_progress<P: AsRef<Path>>(
+        path: P,
+        mode: IndexMode,
+        progress: &mut dyn FnMut(OpenProgress),
+    ) -> Result<Self, QlogError> {
+        let path = path.as_ref();
+        let (mmap, modified, compression) = map_file(path, progress)?;
 
-                lines.push(LineInfo::with_timestamp(
-                    file_index, line_start, length, timestamp,
-                ));
-                line_start = offset + 1;
+        // file_index = 0 for single file
+        let lines = match mode {
+            IndexMode::Full => FileLines::Full(index_full(&mmap, modified, progress)),
+            IndexMode::OnDemand { block_size } => {
+                FileLines::Sparse(SparseIndex::build(&mmap, 0, block_size, modified))
             }
-            offset += 1;
-        }
+            IndexMode::Tail { bytes } => {
+                Self::tail_lines(&mmap, tail_start(&mmap, bytes), modified)
+            }
+            IndexMode::TailLines { lines } => {
+                Self::tail_lines(&mmap, tail_start_lines(&mmap, lines), modified)
+            }
+        };
+
+        let mut storage = Self::empty();
+        storage.push_file(mmap, path.to_path_buf(), lines, modified, compression);
+        Ok(storage)
+    }
 
-        // Handle last line if file doesn't end with newline
-        if line_start < mmap.len() as u64 {
-            let length = (mmap.len() as u64 - line_start) as u32;
-            let line_data = &mmap[line_start as usize..];
-            let timestamp = detect_timestamp(&String::from_utf8_lossy(line_data));
+    /// Create a new LogStorage by memory-mapping a file, reusing a line index
+    /// built earlier (e.g. read from a sidecar database).
+    ///
+    /// The last indexed line and anything written after it are indexed
+    /// again, so an index of an earlier, shorter state of the file still
+    /// covers all of it. The index is rebuilt if it does not fit the file as
+    /// mapped. `progress` is called as in
+    /// [`LogStorage::from_file_with_progress`].
+    pub fn from_file_indexed<P: AsRef<Path>>(
+        path: P,
+        mut lines: Vec<LineInfo>,
+        progress: &mut dyn FnMut(OpenProgress),
+    ) -> Result<Self, QlogError> {
+        let path = path.as_ref();
+        let (mmap, modified, compression) = map_file(path, progress)?;
+        let end = mmap.len() as u64;
 
-            lines.push(LineInfo::with_timestamp(
-                file_index, line_start, length, timestamp,
-            ));
+        let fits = lines.last().is_none_or(|last| last.end_offset() <= end);
+        let lines = if fits && (!lines.is_empty() || mmap.is_empty()) {
+            if let Some(last) = lines.pop() {
+                lines.extend(index_range(&mmap, last.offset, end, 0, modified));
+            }
+            lines
+        } else {
+            index_range(&mmap, 0, mmap.len() as u64, 0, modified)
+        };
+
+        let mut storage = Self::empty();
+        storage.push_file(
+            mmap,
+            path.to_path_buf(),
+            FileLines::Full(lines),
+            modified,
+            compression,
+        );
+        Ok(storage)
+    }
+
+    /// Index the lines from `start` to the end of the file.
+    fn tail_lines(mmap: &[u8], start: u64, modified: NaiveDate) -> FileLines {
+        let lines = index_range(mmap, start, mmap.len() as u64, 0, modified);
+        if start == 0 {
+            FileLines::Full(lines)
+        } else {
+            FileLines::Tail(lines)
         }
+    }
+
+    /// Append a file and its line index, assigning it the next file index.
+    fn push_file(
+        &mut self,
+        mmap: Mmap,
+        path: PathBuf,
+        mut lines: FileLines,
+        modified: NaiveDate,
+        compression: Option<Compression>,
+    ) {
+        lines.set_file_index(self.mmaps.len() as u32);
+        self.starts.push(self.len);
+        self.len += lines.len();
+        self.mmaps.push(mmap);
+        self.paths.push(path);
+        self.files.push(lines);
+        self.modified.push(modified);
+        self.compression.push(compression);
+    }
 
-        lines
+    /// Map a global line index to its file and the index within that file.
+    /// Returns `None` for lines appended while following.
+    pub fn locate(&self, idx: usize) -> Option<(usize, usize)> {
+        if idx >= self.len {
+            return None;
+        }
+        let idx = self.order.get(idx).map_or(idx, |&concat| concat as usize);
+        let file = self.starts.partition_point(|&start| start <= idx) - 1;
+        Some((file, idx - self.starts[file]))
     }
 
     /// Get the number of lines in the storage.
     pub fn len(&self) -> usize {
-        self.lines.len()
+        self.len + self.live.len()
     }
 
     /// Check if the storage is empty.
     pub fn is_empty(&self) -> bool {
-        self.lines.is_empty()
+        self.len() == 0
     }
 
     /// Get a zero-copy view of the line at the given index.
     pub fn get_line(&self, idx: usize) -> Option<MmapStr<'_>> {
-        let info = self.lines.get(idx)?;
-        let mmap = self.mmaps.get(info.file_index as usize)?;
+        let info = self.get_line_info(idx)?;
+        let data: &[u8] = if idx >= self.len {
+            &self.live_data
+        } else {
+            self.mmaps.get(info.file_index() as usize)?
+        };
         let start = info.offset as usize;
         let end = start + info.length as usize;
-        Some(MmapStr::new(&mmap[start..end]))
+        Some(MmapStr::new(&data[start..end]))
+    }
+
+    /// Bytes of the line at the given index as stored in its file,
+    /// including the `\n` that ends it if there is one. Lines appended
+    /// while following come without one; their terminator is not kept.
+    pub fn raw_line(&self, idx: usize) -> Option<&[u8]> {
+        let info = self.get_line_info(idx)?;
+        let data: &[u8] = if idx >= self.len {
+            &self.live_data
+        } else {
+            self.mmaps.get(info.file_index() as usize)?
+        };
+        let start = info.offset as usize;
+        let mut end = start + info.length as usize;
+        if idx < self.len && data.get(end) == Some(&b'\n') {
+            end += 1;
+        }
+        Some(&data[start..end])
     }
 
     /// Get the LineInfo at the given index.
     pub fn get_line_info(&self, idx: usize) -> Option<&LineInfo> {
-        self.lines.get(idx)
+        match self.locate(idx) {
+            Some((file, local)) => self.files[file].get(&self.mmaps[file], local),
+            None => self.live.get(idx.checked_sub(self.len)?),
+        }
+    }
+
+    /// Append a line that was written to file `file_idx` after it was loaded.
+    ///
+    /// The line goes after every existing line; returns its global index.
+    pub fn append_line(
+        &mut self,
+        file_idx: usize,
+        text: &[u8],
+        timestamp: Option<DateTime<Utc>>,
+    ) -> usize {
+        let offset = self.live_data.len() as u64;
+        self.live_data.extend_from_slice(text);
+        self.live.push(
+            LineInfo::with_timestamp(file_idx as u32, offset, text.len() as u32, timestamp)
+                .with_level(detect_level(&String::from_utf8_lossy(text))),
+        );
+        self.len() - 1
+    }
+
+    /// Number of lines appended while following.
+    pub fn live_len(&self) -> usize {
+        self.live.len()
+    }
+
+    /// Global index of the first line appended while following.
+    pub fn live_start(&self) -> usize {
+        self.len
+    }
+
+    /// Drop the `count` oldest lines appended while following.
+    ///
+    /// The global index of every later line moves back by `count`.
+    pub fn prune_live(&mut self, count: usize) {
+        let count = count.min(self.live.len());
+        let cut = self
+            .live
+            .get(count)
+            .map_or(self.live_data.len() as u64, |info| info.offset);
+        self.live.drain(..count);
+        self.live_data.drain(..cut as usize);
+        for info in &mut self.live {
+            info.offset -= cut;
+        }
+    }
+
+    /// Get the size in bytes of a memory-mapped file as it was loaded.
+    pub fn mapped_len(&self, file_idx: usize) -> Option<u64> {
+        self.mmaps.get(file_idx).map(|mmap| mmap.len() as u64)
+    }
+
+    /// Format a file was decompressed from, if it was compressed.
+    pub fn compression(&self, file_idx: usize) -> Option<Compression> {
+        self.compression.get(file_idx).copied().flatten()
+    }
+
+    /// Total size in bytes of all memory-mapped files.
+    pub fn mapped_bytes(&self) -> u64 {
+        self.mmaps.iter().map(|mmap| mmap.len() as u64).sum()
+    }
+
+    /// Approximate heap size in bytes of the line index, including lines
+    /// appended while following.
+    pub fn index_bytes(&self) -> usize {
+        let line = std::mem::size_of::<LineInfo>();
+        let files: usize = self
+            .files
+            .iter()
+            .map(|lines| match lines {
+                FileLines::Full(lines) | FileLines::Tail(lines) => lines.capacity() * line,
+                FileLines::Sparse(sparse) => {
+                    sparse.block_starts.capacity() * std::mem::size_of::<usize>()
+                        + sparse
+                            .blocks
+                            .iter()
+                            .filter_map(|block| block.get())
+                            .map(|block| block.capacity() * line)
+                            .sum::<usize>()
+                }
+            })
+            .sum();
+        let order = (self.order.capacity() + self.position.capacity()) * std::mem::size_of::<u32>();
+        files + order + self.live.capacity() * line + self.live_data.capacity()
     }
 
     /// Iterate over all lines as MmapStr views.
-    pub fn iter(&self) -> impl Iterator<Item = MmapStr> + '_ {
-        self.lines.iter().map(move |info| {
-            let mmap = &self.mmaps[info.file_index as usize];
-            let start = info.offset as usize;
-            let end = start + info.length as usize;
-            MmapStr::new(&mmap[start..end])
-        })
+    pub fn iter(&self) -> impl Iterator<Item = MmapStr<'_>> + '_ {
+        self.iter_enumerated().map(|(_, line)| line)
     }
 
     /// Iterate over lines with their indices.
-    pub fn iter_enumerated(&self) -> impl Iterator<Item = (usize, MmapStr)> + '_ {
-        self.lines.iter().enumerate().map(move |(idx, info)| {
-            let mmap = &self.mmaps[info.file_index as usize];
-            let start = info.offset as usize;
-            let end = start + info.length as usize;
-            (idx, MmapStr::new(&mmap[start..end]))
-        })
+    pub fn iter_enumerated(&self) -> impl Iterator<Item = (usize, MmapStr<'_>)> + '_ {
+        (0..self.len()).filter_map(move |idx| Some((idx, self.get_line(idx)?)))
     }
 
     /// Get raw bytes from the mmap at the given offset and length.
@@ -128,44 +649,232 @@ impl LogStorage {
         self.mmaps.len()
     }
 
-    /// Get the line index (for advanced use).
-    pub fn line_index(&self) -> &[LineInfo] {
-        &self.lines
+    /// Get the source path of a memory-mapped file.
+    pub fn file_path(&self, file_idx: usize) -> Option<&Path> {
+        self.paths.get(file_idx).map(|p| p.as_path())
+    }
+
+    /// Build the exact line index of every on-demand file, in parallel.
+    /// Useful before a pass that reads every line anyway (filtering, search).
+    pub fn index_all(&self) {
+        for (file, lines) in self.files.iter().enumerate() {
+            if let FileLines::Sparse(sparse) = lines {
+                sparse.build_all(&self.mmaps[file]);
+            }
+        }
+    }
+
+    /// Check whether any on-demand file still has blocks without exact line offsets.
+    pub fn has_unindexed_blocks(&self) -> bool {
+        self.files.iter().any(|lines| match lines {
+            FileLines::Sparse(sparse) => sparse.blocks.iter().any(|b| b.get().is_none()),
+            _ => false,
+        })
+    }
+
+    /// Line index of a file whose every line is indexed, or None for
+    /// tail-only and on-demand files.
+    pub fn file_lines(&self, file_idx: usize) -> Option<&[LineInfo]> {
+        match self.files.get(file_idx) {
+            Some(FileLines::Full(lines)) => Some(lines),
+            _ => None,
+        }
+    }
+
+    /// Check whether a file only has part of its lines indexed (tail-only).
+    pub fn is_partial(&self, file_idx: usize) -> bool {
+        matches!(self.files.get(file_idx), Some(FileLines::Tail(_)))
+    }
+
+    /// Get the index of the file a line belongs to.
+    pub fn line_file(&self, idx: usize) -> Option<usize> {
+        Some(self.get_line_info(idx)?.file_index() as usize)
+    }
+
+    /// Get the level detected on a line when it was indexed.
+    pub fn line_level(&self, idx: usize) -> Option<LogLevel> {
+        self.get_line_info(idx)?.level()
+    }
+
+    /// Get the global index of a file's first indexed line while files are
+    /// concatenated; see [`Self::file_line_index`] for merged-by-time views.
+    pub fn file_start(&self, file_idx: usize) -> Option<usize> {
+        self.starts.get(file_idx).copied()
+    }
+
+    /// Get the number of indexed lines of a file.
+    pub fn file_len(&self, file_idx: usize) -> Option<usize> {
+        self.files.get(file_idx).map(FileLines::len)
+    }
+
+    /// Get the global index of line `local` of a file (the inverse of
+    /// [`Self::locate`]).
+    pub fn file_line_index(&self, file_idx: usize, local: usize) -> Option<usize> {
+        if local >= self.file_len(file_idx)? {
+            return None;
+        }
+        let concat = self.starts[file_idx] + local;
+        Some(
+            self.position
+                .get(concat)
+                .map_or(concat, |&idx| idx as usize),
+        )
+    }
+
+    /// Index up to `count` more lines before the start of a tail-only file.
+    ///
+    /// The new lines are inserted at the file's start, shifting the global
+    /// index of every later line. Returns the number of lines added.
+    pub fn extend_tail(&mut self, file_idx: usize, count: usize) -> usize {
+        // Merging by time leaves files with tails concatenated
+        debug_assert!(self.order.is_empty());
+        let Some(FileLines::Tail(lines)) = self.files.get_mut(file_idx) else {
+            return 0;
+        };
+        let mmap = &self.mmaps[file_idx];
+        let old_start = lines.first().map(|l| l.offset).unwrap_or(mmap.len() as u64);
+        let new_start = tail_start_lines(&mmap[..old_start as usize], count);
+
+        let mut extended = index_range(
+            mmap,
+            new_start,
+            old_start,
+            file_idx as u32,
+            self.modified[file_idx],
+        );
+        let added = extended.len();
+        extended.append(lines);
+        self.files[file_idx] = if new_start == 0 {
+            FileLines::Full(extended)
+        } else {
+            FileLines::Tail(extended)
+        };
+
+        for start in &mut self.starts[file_idx + 1..] {
+            *start += added;
+        }
+        self.len += added;
+        added
+    }
+
+    /// Get the source path of the file a line belongs to.
+    pub fn line_path(&self, idx: usize) -> Option<&Path> {
+        self.file_path(self.line_file(idx)?)
+    }
+
+    /// Get the 1-based line number of a line within its own source file.
+    /// Returns `None` for tail-only files, where earlier lines were never
+    /// counted, and for lines appended while following.
+    pub fn line_number_in_file(&self, idx: usize) -> Option<usize> {
+        let (file, local) = self.locate(idx)?;
+        match self.files[file] {
+            FileLines::Tail(_) => None,
+            _ => Some(local + 1),
+        }
     }
 
     /// Merge multiple LogStorage instances into one.
     /// All lines are combined with updated file indices.
     pub fn merge(storages: Vec<LogStorage>) -> Self {
-        if storages.is_empty() {
-            return Self::empty();
+        let mut merged = Self::empty();
+        for storage in storages {
+            let files = storage
+                .mmaps
+                .into_iter()
+                .zip(storage.paths)
+                .zip(storage.files)
+                .zip(storage.modified)
+                .zip(storage.compression);
+            for ((((mmap, path), lines), modified), compression) in files {
+                merged.push_file(mmap, path, lines, modified, compression);
+            }
+        }
+        merged
+    }
+
+    /// Merge multiple LogStorage instances into one, interleaving the lines
+    /// of the files by timestamp.
+    ///
+    /// Each file keeps its own line order: a line without a timestamp sorts
+    /// with the dated line above it, and a file without any timestamps stays
+    /// after the file before it. Tail-only files leave the files
+    /// concatenated, since extending a tail inserts lines.
+    pub fn merge_by_time(storages: Vec<LogStorage>) -> Self {
+        let mut merged = Self::merge(storages);
+        let tails = merged
+            .files
+            .iter()
+            .any(|lines| matches!(lines, FileLines::Tail(_)));
+        if merged.file_count() > 1 && !tails && merged.len <= u32::MAX as usize {
+            merged.order_by_time();
+        }
+        merged
+    }
+
+    /// K-way merge of the files by the timestamp each line sorts with.
+    fn order_by_time(&mut self) {
+        self.index_all();
+        let timestamp = |file: usize, local: usize| {
+            self.files[file]
+                .get(&self.mmaps[file], local)?
+                .timestamp_nanos()
+        };
+
+        let mut heads = BinaryHeap::new();
+        let mut carried = i64::MIN;
+        for file in 0..self.files.len() {
+            let len = self.files[file].len();
+            let first = (0..len).find_map(|local| timestamp(file, local));
+            let last = (0..len).rev().find_map(|local| timestamp(file, local));
+            if len > 0 {
+                heads.push(Reverse((first.unwrap_or(carried), file, 0)));
+            }
+            carried = last.unwrap_or(carried);
         }
 
-        let total_lines: usize = storages.iter().map(|s| s.lines.len()).sum();
-        let mut mmaps = Vec::with_capacity(storages.len());
-        let mut lines = Vec::with_capacity(total_lines);
-
-        for (file_idx, storage) in storages.into_iter().enumerate() {
-            // Add all mmaps from this storage
-            mmaps.extend(storage.mmaps);
-
-            // Re-index lines to use the new file index
-            for line in storage.lines {
-                lines.push(LineInfo::with_timestamp(
-                    file_idx as u32,
-                    line.offset,
-                    line.length,
-                    line.timestamp,
-                ));
+        let mut order = Vec::with_capacity(self.len);
+        while let Some(Reverse((time, file, local))) = heads.pop() {
+            order.push((self.starts[file] + local) as u32);
+            if local + 1 < self.files[file].len() {
+                let next = timestamp(file, local + 1).unwrap_or(time);
+                heads.push(Reverse((next, file, local + 1)));
             }
         }
+        if order
+            .iter()
+            .enumerate()
+            .all(|(idx, &concat)| idx == concat as usize)
+        {
+            return;
+        }
 
-        Self { mmaps, lines }
+        let mut position = vec![0; order.len()];
+        for (idx, &concat) in order.iter().enumerate() {
+            position[concat as usize] = idx as u32;
+        }
+        self.order = order;
+        self.position = position;
     }
 }
 
+/// Storage of `lines` written to a temporary file, for tests; the file
+/// must outlive the storage.
+#[cfg(test)]
+pub(crate) fn storage_from(lines: &[&str]) -> (tempfile::NamedTempFile, LogStorage) {
+    use std::io::Write;
+
+    let mut temp_file = tempfile::NamedTempFile::new().unwrap();
+    for line in lines {
+        writeln!(temp_file, "{}", line).unwrap();
+    }
+    let storage = LogStorage::from_file(temp_file.path()).unwrap();
+    (temp_file, storage)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use chrono::Datelike;
     use std::io::Write;
     use tempfile::NamedTempFile;
 
@@ -191,6 +900,27 @@ mod tests {
         assert_eq!(line2.as_str_lossy().trim(), "Line 3");
     }
 
+    #[test]
+    fn test_compressed_file_opens_like_plain() {
+        let mut gzip = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::fast());
+        gzip.write_all(b"Line 1\nLine 2\n").unwrap();
+        let mut temp_file = NamedTempFile::new().unwrap();
+        temp_file.write_all(&gzip.finish().unwrap()).unwrap();
+
+        let storage = LogStorage::from_file(temp_file.path()).unwrap();
+        assert_eq!(storage.len(), 2);
+        assert_eq!(storage.get_line(1).unwrap().as_bytes(), b"Line 2");
+        assert_eq!(storage.compression(0), Some(Compression::Gzip));
+
+        let mut empty = NamedTempFile::new().unwrap();
+        empty
+            .write_all(&zstd::encode_all(&b""[..], 1).unwrap())
+            .unwrap();
+        let storage = LogStorage::from_file(empty.path()).unwrap();
+        assert!(storage.is_empty());
+        assert_eq!(storage.compression(0), Some(Compression::Zstd));
+    }
+
     #[test]
     fn test_log_storage_no_trailing_newline() {
         let mut temp_file = NamedTempFile::new().unwrap();
@@ -284,6 +1014,222 @@ mod tests {
         assert_eq!(line2.as_str_lossy().trim(), "File2-Line1");
     }
 
+    #[test]
+    fn test_merge_by_time_interleaves_files() {
+        let mut older = NamedTempFile::new().unwrap();
+        writeln!(older, "2024-01-01 10:00:00 INFO a1").unwrap();
+        writeln!(older, "2024-01-01 10:00:02 ERROR a2").unwrap();
+        writeln!(older, "    at a2").unwrap();
+        let mut newer = NamedTempFile::new().unwrap();
+        writeln!(newer, "2024-01-01 10:00:01 INFO b1").unwrap();
+        writeln!(newer, "2024-01-01 10:00:03 INFO b2").unwrap();
+        let mut undated = NamedTempFile::new().unwrap();
+        writeln!(undated, "c1").unwrap();
+
+        let merged = LogStorage::merge_by_time(
+            [&older, &newer, &undated]
+                .iter()
+                .map(|file| LogStorage::from_file(file.path()).unwrap())
+                .collect(),
+        );
+
+        let lines: Vec<String> = merged
+            .iter()
+            .map(|line| line.as_str_lossy().rsplit(' ').next().unwrap().to_string())
+            .collect();
+        assert_eq!(lines, vec!["a1", "b1", "a2", "a2", "b2", "c1"]);
+        assert_eq!(merged.locate(1), Some((1, 0)));
+        assert_eq!(merged.file_line_index(0, 2), Some(3));
+        assert_eq!(merged.line_number_in_file(4), Some(2));
+        assert_eq!(merged.line_path(5), Some(undated.path()));
+    }
+
+    #[test]
+    fn test_log_storage_paths_and_file_line_numbers() {
+        let mut temp1 = NamedTempFile::new().unwrap();
+        writeln!(temp1, "a1").unwrap();
+        writeln!(temp1, "a2").unwrap();
+        let mut temp2 = NamedTempFile::new().unwrap();
+        writeln!(temp2, "b1").unwrap();
+
+        let merged = LogStorage::merge(vec![
+            LogStorage::from_file(temp1.path()).unwrap(),
+            LogStorage::from_file(temp2.path()).unwrap(),
+        ]);
+
+        assert_eq!(merged.file_path(0), Some(temp1.path()));
+        assert_eq!(merged.line_path(2), Some(temp2.path()));
+        assert_eq!(merged.line_number_in_file(1), Some(2));
+        assert_eq!(merged.line_number_in_file(2), Some(1));
+        assert_eq!(merged.line_number_in_file(3), None);
+    }
+
+    #[test]
+    fn test_on_demand_index_matches_full_index() {
+        let mut temp_file = NamedTempFile::new().unwrap();
+        for i in 0..25 {
+            writeln!(temp_file, "2026-02-13 10:30:{:02} line {}", i, i).unwrap();
+        }
+        write!(temp_file, "unterminated").unwrap();
+
+        let full = LogStorage::from_file(temp_file.path()).unwrap();
+        let sparse =
+            LogStorage::from_file_with(temp_file.path(), IndexMode::OnDemand { block_size: 16 })
+                .unwrap();
+
+        assert_eq!(sparse.len(), full.len());
+        for idx in (0..full.len()).rev() {
+            assert_eq!(
+                sparse.get_line(idx).unwrap().as_bytes(),
+                full.get_line(idx).unwrap().as_bytes()
+            );
+            assert_eq!(
+                sparse.get_line_info(idx).unwrap().timestamp(),
+                full.get_line_info(idx).unwrap().timestamp()
+            );
+        }
+        assert_eq!(sparse.line_number_in_file(9), Some(10));
+        assert!(sparse.get_line(full.len()).is_none());
+    }
+
+    #[test]
+    fn test_syslog_year_from_file_mtime() {
+        let mut temp_file = NamedTempFile::new().unwrap();
+        writeln!(temp_file, "Dec 31 23:59:58 host kernel: old year").unwrap();
+        writeln!(temp_file, "Jan  1 00:00:02 host kernel: new year").unwrap();
+        let modified = DateTime::parse_from_rfc3339("2026-01-01T08:00:00Z").unwrap();
+        temp_file
+            .as_file()
+            .set_modified(std::time::SystemTime::from(modified))
+            .unwrap();
+
+        for mode in [IndexMode::Full, IndexMode::OnDemand { block_size: 16 }] {
+            let storage = LogStorage::from_file_with(temp_file.path(), mode).unwrap();
+            let year = |idx| {
+                storage
+                    .get_line_info(idx)
+                    .unwrap()
+                    .timestamp()
+                    .unwrap()
+                    .year()
+            };
+            assert_eq!((year(0), year(1)), (2025, 2026));
+        }
+    }
+
+    #[test]
+    fn test_on_demand_block_boundaries() {
+        // Empty lines, a line longer than several blocks and a trailing newline
+        let content = "a\n\n\nbb\nccccccccccccccccccccccc\nd\n\neee\n";
+        let mut temp_file = NamedTempFile::new().unwrap();
+        write!(temp_file, "{}", content).unwrap();
+        let expected: Vec<&str> = content.strip_suffix('\n').unwrap().split('\n').collect();
+
+        for block_size in 1..=content.len() + 1 {
+            let storage =
+                LogStorage::from_file_with(temp_file.path(), IndexMode::OnDemand { block_size })
+                    .unwrap();
+            storage.index_all();
+            let lines: Vec<String> = storage
+                .iter()
+                .map(|l| l.as_str_lossy().into_owned())
+                .collect();
+            assert_eq!(lines, expected, "block_size {}", block_size);
+        }
+    }
+
+    #[test]
+    fn test_tail_mode_starts_at_line_boundary() {
+        let mut temp_file = NamedTempFile::new().unwrap();
+        writeln!(temp_file, "first line").unwrap();
+        writeln!(temp_file, "second line").unwrap();
+        writeln!(temp_file, "third").unwrap();
+
+        // The last 15 bytes cut "second line" in half; it must be skipped.
+        let storage =
+            LogStorage::from_file_with(temp_file.path(), IndexMode::Tail { bytes: 15 }).unwrap();
+
+        assert_eq!(storage.len(), 1);
+        assert_eq!(storage.get_line(0).unwrap().as_str_lossy(), "third");
+        assert!(storage.is_partial(0));
+        assert_eq!(storage.line_number_in_file(0), None);
+
+        let whole =
+            LogStorage::from_file_with(temp_file.path(), IndexMode::Tail { bytes: 1024 }).unwrap();
+        assert_eq!(whole.len(), 3);
+        assert!(!whole.is_partial(0));
+    }
+
+    #[test]
+    fn test_tail_lines_and_extend_backwards() {
+        let mut temp1 = NamedTempFile::new().unwrap();
+        writeln!(temp1, "other").unwrap();
+        let mut temp2 = NamedTempFile::new().unwrap();
+        for i in 1..=5 {
+            writeln!(temp2, "line {}", i).unwrap();
+        }
+
+        let mut merged = LogStorage::merge(vec![
+            LogStorage::from_file_with(temp2.path(), IndexMode::TailLines { lines: 2 }).unwrap(),
+            LogStorage::from_file(temp1.path()).unwrap(),
+        ]);
+        assert_eq!(merged.len(), 3);
+        assert_eq!(merged.get_line(0).unwrap().as_str_lossy(), "line 4");
+        assert!(merged.is_partial(0));
+
+        assert_eq!(merged.extend_tail(0, 2), 2);
+        assert_eq!(merged.len(), 5);
+        assert_eq!(merged.get_line(0).unwrap().as_str_lossy(), "line 2");
+        assert_eq!(merged.get_line(4).unwrap().as_str_lossy(), "other");
+        assert_eq!(merged.file_start(1), Some(4));
+
+        // Reaching the beginning turns the file into a fully indexed one
+        assert_eq!(merged.extend_tail(0, 10), 1);
+        assert!(!merged.is_partial(0));
+        assert_eq!(merged.line_number_in_file(0), Some(1));
+        assert_eq!(merged.extend_tail(0, 10), 0);
+    }
+
+    #[test]
+    fn test_merge_mixed_index_modes() {
+        let mut temp1 = NamedTempFile::new().unwrap();
+        writeln!(temp1, "a1").unwrap();
+        writeln!(temp1, "a2").unwrap();
+        let mut temp2 = NamedTempFile::new().unwrap();
+        writeln!(temp2, "b1").unwrap();
+        writeln!(temp2, "b2").unwrap();
+        writeln!(temp2, "b3").unwrap();
+
+        let merged = LogStorage::merge(vec![
+            LogStorage::from_file(temp1.path()).unwrap(),
+            LogStorage::from_file_with(temp2.path(), IndexMode::OnDemand { block_size: 4 })
+                .unwrap(),
+        ]);
+
+        assert_eq!(merged.len(), 5);
+        assert_eq!(merged.get_line(4).unwrap().as_str_lossy(), "b3");
+        assert_eq!(merged.get_line_info(4).unwrap().file_index(), 1);
+        assert_eq!(merged.line_number_in_file(3), Some(2));
+    }
+
+    #[test]
+    fn test_append_line_after_file_lines() {
+        let mut temp_file = NamedTempFile::new().unwrap();
+        writeln!(temp_file, "Line 1").unwrap();
+        let mut storage = LogStorage::from_file(temp_file.path()).unwrap();
+        assert_eq!(storage.mapped_len(0), Some(7));
+
+        let idx = storage.append_line(0, b"Line 2", None);
+
+        assert_eq!(idx, 1);
+        assert_eq!(storage.len(), 2);
+        assert_eq!(storage.get_line(1).unwrap().as_str_lossy(), "Line 2");
+        assert_eq!(storage.line_file(1), Some(0));
+        assert_eq!(storage.line_path(1), Some(temp_file.path()));
+        assert_eq!(storage.line_number_in_file(1), None);
+        assert_eq!(storage.iter_enumerated().count(), 2);
+    }
+
     #[test]
     fn test_log_storage_merge_empty() {
         let merged = LogStorage::merge(vec![]);
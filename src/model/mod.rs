@@ -1,17 +1,38 @@
+pub mod anchor;
+pub mod bookmark;
+pub mod compression;
+pub mod context;
 pub mod filter;
+pub mod filter_index;
+pub mod format;
+pub mod level;
 pub mod line_info;
 pub mod log_entry;
 pub mod log_storage;
 pub mod mmap_str;
+pub mod pin;
+pub mod retention;
 pub mod selection;
+pub mod template;
+pub mod text;
 pub mod timestamp;
 pub mod visual_line_cache;
 
-pub use filter::{BMHMatcher, FilterKind, FilterList};
+pub use anchor::LineAnchor;
+pub use bookmark::{Bookmark, Bookmarks};
+pub use compression::Compression;
+pub use context::Context;
+pub use filter::{parse_filter_file, BMHMatcher, CaseMode, FilterKind, FilterList, FilterRule};
+pub use filter_index::FilterIndex;
+pub use format::{detect_format, LogFormat};
+pub use level::{detect_level, set_level_map, LevelMap, LevelSet, LogLevel};
 pub use line_info::LineInfo;
 pub use log_entry::LogEntry;
-pub use log_storage::LogStorage;
+pub use log_storage::{IndexMode, LogStorage, OpenProgress, ON_DEMAND_BLOCK_SIZE};
 pub use mmap_str::MmapStr;
-pub use selection::{Direction, Selection};
-pub use timestamp::detect_timestamp;
+pub use pin::Pins;
+pub use retention::Retention;
+pub use selection::{Direction, Selection, SelectionBytes};
+pub use template::{filter_proposal, template_key, Throttle};
+pub use timestamp::{detect_timestamp, detect_timestamp_near, parse_time_bound};
 pub use visual_line_cache::{CachedVisualInfo, VisualLineCache};
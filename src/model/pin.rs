@@ -0,0 +1,140 @@
+/// Lines pinned to the pane above the log view, in display order.
+///
+/// Entries are storage indices, so pins survive re-filtering; the order is
+/// chosen by the user rather than following the log.
+#[derive(Debug, Clone, Default)]
+pub struct Pins {
+    lines: Vec<usize>,
+}
+
+impl Pins {
+    pub fn new() -> Self {
+        Self { lines: Vec::new() }
+    }
+
+    /// Pin a line at the end, or unpin it if already pinned.
+    /// Returns true if the line is now pinned.
+    pub fn toggle(&mut self, storage_idx: usize) -> bool {
+        match self.position(storage_idx) {
+            Some(pos) => {
+                self.lines.remove(pos);
+                false
+            }
+            None => {
+                self.lines.push(storage_idx);
+                true
+            }
+        }
+    }
+
+    /// Pin a line at the end unless it is already pinned.
+    pub fn add(&mut self, storage_idx: usize) {
+        if !self.contains(storage_idx) {
+            self.lines.push(storage_idx);
+        }
+    }
+
+    /// Unpin the line at display position `pos`.
+    pub fn remove(&mut self, pos: usize) -> Option<usize> {
+        (pos < self.lines.len()).then(|| self.lines.remove(pos))
+    }
+
+    /// Swap the pin at `pos` with the one above it. Returns true if it moved.
+    pub fn move_up(&mut self, pos: usize) -> bool {
+        if pos == 0 || pos >= self.lines.len() {
+            return false;
+        }
+        self.lines.swap(pos - 1, pos);
+        true
+    }
+
+    /// Swap the pin at `pos` with the one below it. Returns true if it moved.
+    pub fn move_down(&mut self, pos: usize) -> bool {
+        if pos + 1 >= self.lines.len() {
+            return false;
+        }
+        self.lines.swap(pos, pos + 1);
+        true
+    }
+
+    pub fn position(&self, storage_idx: usize) -> Option<usize> {
+        self.lines.iter().position(|&idx| idx == storage_idx)
+    }
+
+    pub fn contains(&self, storage_idx: usize) -> bool {
+        self.position(storage_idx).is_some()
+    }
+
+    pub fn get(&self, pos: usize) -> Option<usize> {
+        self.lines.get(pos).copied()
+    }
+
+    pub fn clear(&mut self) {
+        self.lines.clear();
+    }
+
+    pub fn len(&self) -> usize {
+        self.lines.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.lines.is_empty()
+    }
+
+    /// Shift pins at or after `start` by `count` lines, after lines were
+    /// inserted into storage at `start`.
+    pub fn shift_from(&mut self, start: usize, count: usize) {
+        for idx in self.lines.iter_mut().filter(|idx| **idx >= start) {
+            *idx += count;
+        }
+    }
+
+    /// Unpin the `count` lines removed from storage at `start` and move
+    /// later pins back.
+    pub fn remove_lines(&mut self, start: usize, count: usize) {
+        self.lines
+            .retain(|idx| !(start..start + count).contains(idx));
+        for idx in self.lines.iter_mut().filter(|idx| **idx >= start) {
+            *idx -= count;
+        }
+    }
+
+    /// Iterate over pinned storage indices in display order.
+    pub fn iter(&self) -> impl Iterator<Item = usize> + '_ {
+        self.lines.iter().copied()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_toggle_keeps_pin_order() {
+        let mut pins = Pins::new();
+        assert!(pins.toggle(9));
+        assert!(pins.toggle(2));
+        assert_eq!(pins.iter().collect::<Vec<_>>(), vec![9, 2]);
+        assert!(!pins.toggle(9));
+        assert_eq!(pins.iter().collect::<Vec<_>>(), vec![2]);
+    }
+
+    #[test]
+    fn test_reorder_and_remove() {
+        let mut pins = Pins::new();
+        pins.add(1);
+        pins.add(2);
+        pins.add(3);
+        pins.add(3);
+
+        assert!(pins.move_up(2));
+        assert!(!pins.move_up(0));
+        assert!(!pins.move_down(2));
+        assert_eq!(pins.iter().collect::<Vec<_>>(), vec![1, 3, 2]);
+
+        assert_eq!(pins.remove(0), Some(1));
+        assert_eq!(pins.remove(5), None);
+        pins.shift_from(3, 10);
+        assert_eq!(pins.iter().collect::<Vec<_>>(), vec![13, 2]);
+    }
+}
@@ -0,0 +1,127 @@
+//! Retention of lines appended while following.
+//!
+//! Loaded files are memory-mapped and cost only their line index, but every
+//! followed (or piped) line is copied into memory. A retention limit keeps
+//! the newest lines by count or by age and drops the older ones.
+
+use chrono::Duration;
+
+use super::log_storage::LogStorage;
+
+/// Once a limit is exceeded, lines are dropped down to this fraction below
+/// it (a tenth), so the next prune waits for that many new lines and the
+/// storage is not compacted on every poll.
+const PRUNE_MARGIN: i32 = 10;
+
+/// How many appended lines to keep.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Retention {
+    /// Keep at most this many appended lines
+    pub lines: Option<usize>,
+    /// Keep appended lines at most this much older than the newest one
+    pub max_age: Option<Duration>,
+}
+
+impl Retention {
+    pub fn is_unlimited(&self) -> bool {
+        self.lines.is_none() && self.max_age.is_none()
+    }
+
+    /// Number of oldest appended lines beyond the limits.
+    ///
+    /// Lines without a timestamp are dropped together with the line above
+    /// them; age is measured from the newest appended timestamp.
+    pub fn excess(&self, storage: &LogStorage) -> usize {
+        let live = storage.live_start()..storage.len();
+        let mut excess = self
+            .lines
+            .map_or(0, |keep| storage.live_len().saturating_sub(keep));
+
+        let newest = self.max_age.and_then(|_| {
+            live.clone()
+                .rev()
+                .find_map(|idx| storage.get_line_info(idx)?.timestamp())
+        });
+        if let (Some(max_age), Some(newest)) = (self.max_age, newest) {
+            let cutoff = newest - max_age;
+            let expired = live
+                .clone()
+                .position(|idx| {
+                    storage
+                        .get_line_info(idx)
+                        .and_then(|info| info.timestamp())
+                        .is_some_and(|time| time >= cutoff)
+                })
+                .unwrap_or(0);
+            excess = excess.max(expired);
+        }
+        excess
+    }
+
+    /// Number of appended lines to drop now: none within the limits, and
+    /// enough to get a tenth below them once they are exceeded.
+    pub fn due(&self, storage: &LogStorage) -> usize {
+        if self.is_unlimited() || self.excess(storage) == 0 {
+            return 0;
+        }
+        let below = Retention {
+            lines: self.lines.map(|keep| keep - keep / PRUNE_MARGIN as usize),
+            max_age: self.max_age.map(|age| age - age / PRUNE_MARGIN),
+        };
+        below.excess(storage)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::NaiveDateTime;
+
+    fn storage_with(lines: &[&str]) -> LogStorage {
+        let mut storage = LogStorage::empty();
+        for line in lines {
+            let time = NaiveDateTime::parse_from_str(&line[..19], "%Y-%m-%d %H:%M:%S")
+                .ok()
+                .map(|time| time.and_utc());
+            storage.append_line(0, line.as_bytes(), time);
+        }
+        storage
+    }
+
+    #[test]
+    fn test_excess_by_lines_and_age() {
+        let storage = storage_with(&[
+            "2024-01-01 08:00:00 INFO old",
+            "    continuation of old",
+            "2024-01-01 09:30:00 INFO recent",
+            "2024-01-01 10:00:00 INFO newest",
+        ]);
+        let by_lines = Retention {
+            lines: Some(3),
+            max_age: None,
+        };
+        assert_eq!(by_lines.excess(&storage), 1);
+        let by_age = Retention {
+            lines: None,
+            max_age: Some(Duration::hours(1)),
+        };
+        assert_eq!(by_age.excess(&storage), 2);
+        assert_eq!(Retention::default().due(&storage), 0);
+    }
+
+    #[test]
+    fn test_prune_goes_a_tenth_below_the_limit() {
+        let lines: Vec<String> = (0..100)
+            .map(|i| format!("2024-01-01 10:00:00 INFO line {}", i))
+            .collect();
+        let lines: Vec<&str> = lines.iter().map(String::as_str).collect();
+        let storage = storage_with(&lines);
+        let keep = |lines| Retention {
+            lines: Some(lines),
+            max_age: None,
+        };
+        assert_eq!(keep(100).due(&storage), 0);
+        assert_eq!(keep(95).due(&storage), 14);
+        assert_eq!(keep(90).due(&storage), 19);
+    }
+}
@@ -6,12 +6,17 @@ pub enum Direction {
 }
 
 /// Tracks selection state for Helix-style selection
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+///
+/// A selection is either the range from an anchor to the cursor, or a set
+/// of lines that need not be adjacent (every search match, `%`).
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct Selection {
     /// Anchor point - start of selection (None = no selection)
     anchor: Option<usize>,
     /// Direction of last extension for repeat-x behavior
     direction: Option<Direction>,
+    /// Selected lines in ascending order, for a set selection
+    lines: Vec<usize>,
 }
 
 impl Selection {
@@ -20,18 +25,34 @@ impl Selection {
         Self {
             anchor: None,
             direction: None,
+            lines: Vec::new(),
         }
     }
 
-    /// Check if selection is active (anchor is set)
+    /// Check if selection is active (anchor is set, or lines are selected)
     pub fn is_active(&self) -> bool {
-        self.anchor.is_some()
+        self.anchor.is_some() || !self.lines.is_empty()
+    }
+
+    /// Check if the selection is a set of lines rather than a range
+    pub fn is_set(&self) -> bool {
+        !self.lines.is_empty()
     }
 
     /// Start selection at cursor position
     pub fn start(&mut self, cursor: usize) {
         self.anchor = Some(cursor);
         self.direction = None;
+        self.lines.clear();
+    }
+
+    /// Select exactly `lines`, which need not be adjacent
+    pub fn select_lines(&mut self, mut lines: Vec<usize>) {
+        lines.sort_unstable();
+        lines.dedup();
+        self.anchor = None;
+        self.direction = None;
+        self.lines = lines;
     }
 
     /// Extend selection toward cursor, recording direction
@@ -45,11 +66,15 @@ impl Selection {
     pub fn clear(&mut self) {
         self.anchor = None;
         self.direction = None;
+        self.lines.clear();
     }
 
     /// Check if index is within selection range
     /// Takes the current cursor position to determine the active selection range
     pub fn contains(&self, idx: usize, cursor: usize) -> bool {
+        if self.is_set() {
+            return self.lines.binary_search(&idx).is_ok();
+        }
         let Some(anchor) = self.anchor else {
             return false;
         };
@@ -64,8 +89,12 @@ impl Selection {
     }
 
     /// Get selection range (min, max) or None
-    /// Takes the current cursor position to determine the active selection range
+    /// Takes the current cursor position to determine the active selection range;
+    /// for a set selection, the first and last selected lines
     pub fn range(&self, cursor: usize) -> Option<(usize, usize)> {
+        if let (Some(&first), Some(&last)) = (self.lines.first(), self.lines.last()) {
+            return Some((first, last));
+        }
         let anchor = self.anchor?;
 
         let (start, end) = if anchor <= cursor {
@@ -76,6 +105,16 @@ impl Selection {
 
         Some((start, end))
     }
+
+    /// Selected lines in ascending order; empty without a selection
+    pub fn lines(&self, cursor: usize) -> Vec<usize> {
+        if self.is_set() {
+            return self.lines.clone();
+        }
+        self.range(cursor)
+            .map(|(start, end)| (start..=end).collect())
+            .unwrap_or_default()
+    }
 }
 
 impl Default for Selection {
@@ -84,6 +123,49 @@ impl Default for Selection {
     }
 }
 
+/// Total size of the selected lines, kept up to date incrementally so the
+/// status bar can show it every frame while the selection grows.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SelectionBytes {
+    /// Range the total was computed for
+    range: Option<(usize, usize)>,
+    bytes: u64,
+}
+
+impl SelectionBytes {
+    /// Forget the cached total; call when the lines behind the indices change.
+    pub fn reset(&mut self) {
+        *self = Self::default();
+    }
+
+    /// Total of `line_len` over `start..=end`.
+    ///
+    /// When one end of the range is unchanged since the last call, only the
+    /// lines added or removed at the other end are measured.
+    pub fn total(&mut self, start: usize, end: usize, line_len: impl Fn(usize) -> u64) -> u64 {
+        let sum = |from: usize, to: usize| (from..to).map(&line_len).sum::<u64>();
+        self.bytes = match self.range {
+            Some((old_start, old_end)) if old_start == start => {
+                if end >= old_end {
+                    self.bytes + sum(old_end + 1, end + 1)
+                } else {
+                    self.bytes - sum(end + 1, old_end + 1)
+                }
+            }
+            Some((old_start, old_end)) if old_end == end => {
+                if start <= old_start {
+                    self.bytes + sum(start, old_start)
+                } else {
+                    self.bytes - sum(old_start, start)
+                }
+            }
+            _ => sum(start, end + 1),
+        };
+        self.range = Some((start, end));
+        self.bytes
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -158,4 +240,43 @@ mod tests {
         // Direction is tracked but doesn't change the range logic
         assert_eq!(sel.range(6), Some((5, 6)));
     }
+
+    #[test]
+    fn test_set_selection() {
+        let mut sel = Selection::new();
+        sel.select_lines(vec![9, 2, 5, 2]);
+        assert!(sel.is_active() && sel.is_set());
+        assert!(sel.contains(5, 0));
+        assert!(!sel.contains(4, 0));
+        assert_eq!(sel.range(0), Some((2, 9)));
+        assert_eq!(sel.lines(0), vec![2, 5, 9]);
+
+        sel.start(3);
+        assert!(!sel.is_set());
+        assert_eq!(sel.lines(5), vec![3, 4, 5]);
+        sel.select_lines(vec![1]);
+        sel.clear();
+        assert!(!sel.is_active());
+    }
+
+    #[test]
+    fn test_selection_bytes_updates_incrementally() {
+        let lengths = [10u64, 20, 30, 40, 50];
+        let calls = std::cell::Cell::new(0);
+        let line_len = |idx: usize| {
+            calls.set(calls.get() + 1);
+            lengths[idx]
+        };
+        let mut bytes = SelectionBytes::default();
+
+        assert_eq!(bytes.total(1, 1, line_len), 20);
+        assert_eq!(bytes.total(1, 3, line_len), 90);
+        assert_eq!(bytes.total(1, 2, line_len), 50);
+        assert_eq!(bytes.total(0, 2, line_len), 60);
+        assert_eq!(calls.get(), 5);
+
+        assert_eq!(bytes.total(3, 4, line_len), 90);
+        bytes.reset();
+        assert_eq!(bytes.total(0, 4, line_len), 150);
+    }
 }
@@ -0,0 +1,194 @@
+use std::collections::HashMap;
+
+/// Placeholder for a variable token in a template.
+const VARIABLE: &[u8] = b"<*>";
+
+/// Key identifying the message template of a line.
+///
+/// Lines are split on whitespace and every token containing a digit
+/// (numbers, ids, timestamps, durations) is treated as a variable, so
+/// "user 42 logged in" and "user 7 logged in" share a key.
+pub fn template_key(line: &[u8]) -> u64 {
+    line.split(|b| b.is_ascii_whitespace())
+        .filter(|token| !token.is_empty())
+        .map(|token| {
+            if token.iter().any(u8::is_ascii_digit) {
+                VARIABLE
+            } else {
+                token
+            }
+        })
+        .flat_map(|token| token.iter().chain(b" "))
+        .fold(0xcbf2_9ce4_8422_2325, |hash, &b| {
+            (hash ^ b as u64).wrapping_mul(0x0100_0000_01b3)
+        })
+}
+
+/// Check whether a token is part of the constant text of a message.
+///
+/// Tokens with digits are variables, as in `template_key`; quotes and
+/// command separators are left out so the text can be typed into a
+/// `:filter` command unchanged.
+fn is_constant(token: &str) -> bool {
+    !token
+        .bytes()
+        .any(|b| b.is_ascii_digit() || matches!(b, b';' | b'|' | b'"' | b'\'' | b'\\'))
+}
+
+/// Propose filter text shared by `lines`.
+///
+/// Candidates are runs of consecutive constant tokens of the first line;
+/// the longest one found in every line (ignoring case) wins. For a single
+/// line this is the longest stretch of its message between variables.
+pub fn filter_proposal(lines: &[&str]) -> Option<String> {
+    let (first, rest) = lines.split_first()?;
+    let rest: Vec<String> = rest.iter().map(|line| line.to_lowercase()).collect();
+    let tokens: Vec<(usize, &str)> = first
+        .split_whitespace()
+        .map(|token| (token.as_ptr() as usize - first.as_ptr() as usize, token))
+        .collect();
+
+    let mut best: Option<&str> = None;
+    for (i, &(start, _)) in tokens.iter().enumerate() {
+        for &(offset, token) in tokens[i..].iter().take_while(|(_, t)| is_constant(t)) {
+            let candidate = &first[start..offset + token.len()];
+            if best.is_some_and(|best| best.len() >= candidate.len())
+                || !candidate.chars().any(char::is_alphabetic)
+            {
+                continue;
+            }
+            let lower = candidate.to_lowercase();
+            if !rest.iter().all(|line| line.contains(&lower)) {
+                // Longer runs contain this one, so they cannot match either
+                break;
+            }
+            best = Some(candidate);
+        }
+    }
+    best.map(str::to_string)
+}
+
+/// Keeps only the first few lines of each message template.
+///
+/// Lines are offered in view order; the last line kept for a template
+/// carries the count of the lines dropped after it.
+#[derive(Debug, Clone)]
+pub struct Throttle {
+    limit: usize,
+    /// Lines seen per template
+    seen: HashMap<u64, usize>,
+    /// Template of the last kept line, by storage index
+    last_kept: HashMap<usize, u64>,
+}
+
+impl Throttle {
+    pub fn new(limit: usize) -> Self {
+        Self {
+            limit: limit.max(1),
+            seen: HashMap::new(),
+            last_kept: HashMap::new(),
+        }
+    }
+
+    pub fn limit(&self) -> usize {
+        self.limit
+    }
+
+    /// Forget all lines seen, before the view is rebuilt.
+    pub fn reset(&mut self) {
+        self.seen.clear();
+        self.last_kept.clear();
+    }
+
+    /// Record a line; returns true if it should be shown.
+    pub fn admit(&mut self, storage_idx: usize, line: &[u8]) -> bool {
+        let key = template_key(line);
+        let seen = self.seen.entry(key).or_insert(0);
+        *seen += 1;
+        if *seen == self.limit {
+            self.last_kept.insert(storage_idx, key);
+        }
+        *seen <= self.limit
+    }
+
+    /// Forget lines `start..start + count`, which were removed from the
+    /// storage; later lines move back by `count`.
+    pub fn remove_lines(&mut self, start: usize, count: usize) {
+        self.last_kept = std::mem::take(&mut self.last_kept)
+            .into_iter()
+            .filter(|&(idx, _)| idx < start || idx >= start + count)
+            .map(|(idx, key)| (if idx >= start { idx - count } else { idx }, key))
+            .collect();
+    }
+
+    /// Number of lines dropped after this line, if it is the last one kept
+    /// for its template.
+    pub fn more_after(&self, storage_idx: usize) -> Option<usize> {
+        let key = self.last_kept.get(&storage_idx)?;
+        let more = self.seen[key] - self.limit;
+        (more > 0).then_some(more)
+    }
+
+    /// Total number of lines dropped.
+    pub fn dropped(&self) -> usize {
+        self.seen
+            .values()
+            .map(|&seen| seen.saturating_sub(self.limit))
+            .sum()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_template_key_masks_variables() {
+        assert_eq!(
+            template_key(b"2024-01-01 user 42 logged in"),
+            template_key(b"2024-03-09  user 7 logged in")
+        );
+        assert_ne!(
+            template_key(b"user 42 logged in"),
+            template_key(b"user 42 logged out")
+        );
+    }
+
+    #[test]
+    fn test_throttle_keeps_first_lines_per_template() {
+        let mut throttle = Throttle::new(2);
+        let lines: [&[u8]; 5] = [b"ping 1", b"ping 2", b"error", b"ping 3", b"ping 4"];
+        let kept: Vec<usize> = (0..lines.len())
+            .filter(|&idx| throttle.admit(idx, lines[idx]))
+            .collect();
+
+        assert_eq!(kept, vec![0, 1, 2]);
+        assert_eq!(throttle.more_after(1), Some(2));
+        assert_eq!(throttle.more_after(0), None);
+        assert_eq!(throttle.dropped(), 2);
+
+        throttle.reset();
+        assert!(throttle.admit(3, b"ping 3"));
+    }
+
+    #[test]
+    fn test_filter_proposal() {
+        assert_eq!(
+            filter_proposal(&["2024-01-01 10:00:00 WARN user 42 failed to connect to db"]),
+            Some("failed to connect to db".to_string())
+        );
+        assert_eq!(
+            filter_proposal(&[
+                "10:00:01 INFO request handled in 12ms",
+                "10:00:02 info Request handled in 7ms",
+            ]),
+            Some("INFO request handled in".to_string())
+        );
+        assert_eq!(filter_proposal(&["GET /a ok", "POST /b error"]), None);
+        assert_eq!(
+            filter_proposal(&["id=1; retry \"x\""]),
+            Some("retry".to_string())
+        );
+        assert_eq!(filter_proposal(&[]), None);
+    }
+}
@@ -0,0 +1,48 @@
+//! Boundary-safe slicing of line text.
+//!
+//! Match and region offsets are byte offsets into the raw line, while the
+//! views render its lossy UTF-8 text; slicing goes through these helpers
+//! so an offset inside a multi-byte character (or past the end of the
+//! text) never panics or splits the character.
+
+use std::ops::Range;
+
+/// Largest character boundary of `text` at or before `byte`.
+pub fn floor_char_boundary(text: &str, byte: usize) -> usize {
+    let mut byte = byte.min(text.len());
+    while !text.is_char_boundary(byte) {
+        byte -= 1;
+    }
+    byte
+}
+
+/// The part of `text` in the byte range `range`, with both ends moved back
+/// to the nearest character boundary.
+pub fn slice_bytes(text: &str, range: Range<usize>) -> &str {
+    let start = floor_char_boundary(text, range.start);
+    let end = floor_char_boundary(text, range.end).max(start);
+    &text[start..end]
+}
+
+/// Number of characters before byte offset `byte` of `text`.
+pub fn byte_to_char_offset(text: &str, byte: usize) -> usize {
+    text.char_indices()
+        .take_while(|(idx, _)| *idx < byte)
+        .count()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_slices_stay_on_char_boundaries() {
+        let text = "héllo → wörld";
+        // Byte 2 is inside 'é', byte 8 inside '→'
+        assert_eq!(floor_char_boundary(text, 2), 1);
+        assert_eq!(slice_bytes(text, 2..8), "éllo ");
+        assert_eq!(slice_bytes(text, 7..100), "→ wörld");
+        assert_eq!(slice_bytes(text, 100..200), "");
+        assert_eq!(byte_to_char_offset(text, 10), 7);
+    }
+}
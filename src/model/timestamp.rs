@@ -1,6 +1,30 @@
-use chrono::{DateTime, TimeZone, Utc};
+use chrono::{DateTime, Datelike, NaiveDate, NaiveTime, TimeZone, Utc};
 
+/// Month abbreviations accepted in syslog-style timestamps, January first:
+/// English, German, French, Spanish, Italian, Portuguese and Dutch.
+const MONTH_NAMES: [&[&str]; 12] = [
+    &["jan", "jän", "janv", "ene", "gen"],
+    &["feb", "fév", "févr", "fev"],
+    &["mar", "mär", "mars", "mrt"],
+    &["apr", "avr", "abr"],
+    &["may", "mai", "mag", "mei"],
+    &["jun", "juin", "giu"],
+    &["jul", "juil", "lug"],
+    &["aug", "août", "ago"],
+    &["sep", "sept", "set"],
+    &["oct", "okt", "ott", "out"],
+    &["nov"],
+    &["dec", "dez", "déc", "dic"],
+];
+
+/// Detect a timestamp, resolving year-less (syslog) timestamps against today.
 pub fn detect_timestamp(line: &str) -> Option<DateTime<Utc>> {
+    detect_timestamp_near(line, Utc::now().date_naive())
+}
+
+/// Detect a timestamp, resolving year-less (syslog) timestamps against
+/// `reference`, usually the date the file was last modified.
+pub fn detect_timestamp_near(line: &str, reference: NaiveDate) -> Option<DateTime<Utc>> {
     let patterns = [
         "%Y-%m-%dT%H:%M:%S%.f%:z",
         "%Y-%m-%dT%H:%M:%S%.3f%:z",
@@ -10,7 +34,6 @@ pub fn detect_timestamp(line: &str) -> Option<DateTime<Utc>> {
         "%Y-%m-%d %H:%M:%S",
         "%d/%b/%Y:%H:%M:%S %z",
         "%Y/%m/%d %H:%M:%S",
-        "%b %d %H:%M:%S",
     ];
 
     for pattern in patterns {
@@ -51,8 +74,8 @@ pub fn detect_timestamp(line: &str) -> Option<DateTime<Utc>> {
         }
     }
 
-    if let Some(pos) = line.find("+") {
-        let prefix = &line[..pos + 6];
+    // Offsets like +05:00 are 6 bytes; the line may end before that
+    if let Some(prefix) = line.find("+").and_then(|pos| line.get(..pos + 6)) {
         for pattern in &patterns {
             if let Ok(dt) = DateTime::parse_from_str(prefix, pattern) {
                 return Some(dt.with_timezone(&Utc));
@@ -60,8 +83,7 @@ pub fn detect_timestamp(line: &str) -> Option<DateTime<Utc>> {
         }
     }
     if let Some(pos) = line.rfind("-") {
-        if pos > 10 {
-            let prefix = &line[..pos + 6];
+        if let Some(prefix) = line.get(..pos + 6).filter(|_| pos > 10) {
             for pattern in &patterns {
                 if let Ok(dt) = DateTime::parse_from_str(prefix, pattern) {
                     return Some(dt.with_timezone(&Utc));
@@ -70,7 +92,58 @@ pub fn detect_timestamp(line: &str) -> Option<DateTime<Utc>> {
         }
     }
 
-    extract_iso_timestamp_prefix(line)
+    extract_iso_timestamp_prefix(line).or_else(|| parse_syslog_timestamp(line, reference))
+}
+
+/// Parse a time typed by the user, such as an end of `:range`.
+///
+/// Accepts a time of day (`10:30`, `10:30:15.250`) on `reference`'s date, a
+/// date and time without seconds (`2026-02-13 10:30`) or any full timestamp
+/// `detect_timestamp_near` recognizes.
+pub fn parse_time_bound(text: &str, reference: NaiveDate) -> Option<DateTime<Utc>> {
+    let text = text.trim();
+    for pattern in ["%H:%M:%S%.f", "%H:%M"] {
+        if let Ok(time) = NaiveTime::parse_from_str(text, pattern) {
+            return Some(Utc.from_utc_datetime(&reference.and_time(time)));
+        }
+    }
+    for pattern in ["%Y-%m-%d %H:%M", "%Y-%m-%dT%H:%M"] {
+        if let Ok(naive) = chrono::NaiveDateTime::parse_from_str(text, pattern) {
+            return Some(Utc.from_utc_datetime(&naive));
+        }
+    }
+    detect_timestamp_near(text, reference)
+}
+
+/// Month number (1-12) of a month abbreviation in any supported language.
+fn month_number(token: &str) -> Option<u32> {
+    let token = token.trim_end_matches('.').to_lowercase();
+    MONTH_NAMES
+        .iter()
+        .position(|names| names.contains(&token.as_str()))
+        .map(|idx| idx as u32 + 1)
+}
+
+/// Parse a syslog timestamp such as `Jan  5 14:03:22` at the start of `line`.
+///
+/// The year is not logged, so it is the latest year that does not put the
+/// timestamp after `reference`. Lines are written before the file is last
+/// modified, so this also places lines before a new year in the previous
+/// year. One day of slack covers local-time stamps ahead of UTC.
+fn parse_syslog_timestamp(line: &str, reference: NaiveDate) -> Option<DateTime<Utc>> {
+    let mut tokens = line.split_whitespace();
+    let month = month_number(tokens.next()?)?;
+    let day: u32 = tokens.next()?.parse().ok()?;
+    let time = NaiveTime::parse_from_str(tokens.next()?, "%H:%M:%S%.f").ok()?;
+
+    let latest = reference.succ_opt().unwrap_or(reference);
+    let year = if (month, day) > (latest.month(), latest.day()) {
+        latest.year() - 1
+    } else {
+        latest.year()
+    };
+    let date = NaiveDate::from_ymd_opt(year, month, day)?;
+    Some(Utc.from_utc_datetime(&date.and_time(time)))
 }
 
 fn extract_iso_timestamp_prefix(line: &str) -> Option<DateTime<Utc>> {
@@ -154,6 +227,70 @@ mod tests {
         assert!(result.is_none());
     }
 
+    #[test]
+    fn test_dash_or_plus_near_line_end() {
+        assert!(detect_timestamp(r#"{"level":"error","RequestId":"abc-1"}"#).is_none());
+        assert!(detect_timestamp("retrying, attempt 2+").is_none());
+    }
+
+    #[test]
+    fn test_syslog_year_from_reference() {
+        let reference = NaiveDate::from_ymd_opt(2026, 2, 13).unwrap();
+        let ts = |line| detect_timestamp_near(line, reference).map(|t| t.to_rfc3339());
+
+        assert_eq!(
+            ts("Feb 13 10:30:45 host sshd[42]: Accepted key"),
+            Some("2026-02-13T10:30:45+00:00".to_string())
+        );
+        // One day of slack for local time ahead of UTC
+        assert_eq!(
+            ts("Feb 14 00:30:00 host cron[1]: job"),
+            Some("2026-02-14T00:30:00+00:00".to_string())
+        );
+        // Later in the year than the file was modified: last year
+        assert_eq!(
+            ts("Dec 31 23:59:59 host kernel: tick"),
+            Some("2025-12-31T23:59:59+00:00".to_string())
+        );
+        assert_eq!(
+            ts("Jan  5 08:00:00.250 host app: padded day"),
+            Some("2026-01-05T08:00:00.250+00:00".to_string())
+        );
+    }
+
+    #[test]
+    fn test_syslog_localized_months() {
+        let reference = NaiveDate::from_ymd_opt(2026, 12, 31).unwrap();
+        let month = |line| detect_timestamp_near(line, reference).map(|t| t.month());
+
+        assert_eq!(month("Mär 3 10:00:00 host app: de"), Some(3));
+        assert_eq!(month("déc. 24 10:00:00 host app: fr"), Some(12));
+        assert_eq!(month("ago 15 10:00:00 host app: es"), Some(8));
+        assert_eq!(month("MRT 1 10:00:00 host app: nl"), Some(3));
+        assert_eq!(month("Foo 1 10:00:00 host app"), None);
+        assert_eq!(month("May the force be with you"), None);
+    }
+
+    #[test]
+    fn test_parse_time_bound() {
+        let reference = NaiveDate::from_ymd_opt(2026, 2, 13).unwrap();
+        let at = |h, m, s| Utc.from_utc_datetime(&reference.and_hms_opt(h, m, s).unwrap());
+        assert_eq!(parse_time_bound("10:30", reference), Some(at(10, 30, 0)));
+        assert_eq!(
+            parse_time_bound("10:30:15", reference),
+            Some(at(10, 30, 15))
+        );
+        assert_eq!(
+            parse_time_bound("2026-02-13 10:30", reference),
+            Some(at(10, 30, 0))
+        );
+        assert_eq!(
+            parse_time_bound("2026-02-13T10:30:15Z", reference),
+            Some(at(10, 30, 15))
+        );
+        assert_eq!(parse_time_bound("soon", reference), None);
+    }
+
     #[test]
     fn test_datetime_space_separated() {
         let line = "2026-02-13 10:30:45";
@@ -1,5 +1,7 @@
 use std::collections::HashMap;
 
+use crate::perf::CacheStats;
+
 /// Cached visual line information for a single logical line.
 #[derive(Debug, Clone, Copy)]
 pub struct CachedVisualInfo {
@@ -23,6 +25,8 @@ pub struct VisualLineCache {
     wrap_mode: bool,
     /// Total number of visual lines (cached for quick access)
     total_visual_lines: usize,
+    /// Lookup hits and misses since creation
+    stats: CacheStats,
 }
 
 impl VisualLineCache {
@@ -34,6 +38,7 @@ impl VisualLineCache {
             viewport_width,
             wrap_mode: true,
             total_visual_lines: 0,
+            stats: CacheStats::default(),
         }
     }
 
@@ -60,7 +65,7 @@ impl VisualLineCache {
         }
 
         let text_width = text.chars().count();
-        ((text_width + self.viewport_width - 1) / self.viewport_width).max(1)
+        text_width.div_ceil(self.viewport_width).max(1)
     }
 
     /// Calculate the number of visual lines for bytes (for filtered indices calculation).
@@ -76,7 +81,7 @@ impl VisualLineCache {
             Err(_) => bytes.len(), // Fallback to byte count for invalid UTF-8
         };
 
-        ((char_count + self.viewport_width - 1) / self.viewport_width).max(1)
+        char_count.div_ceil(self.viewport_width).max(1)
     }
 
     /// Get cached visual info for a line, or calculate if not cached.
@@ -86,8 +91,10 @@ impl VisualLineCache {
         F: FnOnce() -> String,
     {
         if let Some(&info) = self.cache.get(&line_idx) {
+            self.stats.record(true);
             return info;
         }
+        self.stats.record(false);
 
         // Calculate
         let text = line_text_fn();
@@ -130,8 +137,10 @@ impl VisualLineCache {
             .take(end_idx - start_idx)
         {
             let count = if let Some(&info) = self.cache.get(&line_idx) {
+                self.stats.record(true);
                 info.count
             } else {
+                self.stats.record(false);
                 let text = line_text_fn(line_idx).unwrap_or_default();
                 let count = self.calculate_visual_lines(&text);
 
@@ -187,6 +196,11 @@ impl VisualLineCache {
         self.cache.is_empty()
     }
 
+    /// Get lookup hit and miss counts.
+    pub fn stats(&self) -> CacheStats {
+        self.stats
+    }
+
     /// Get cache capacity.
     pub fn capacity(&self) -> usize {
         self.capacity
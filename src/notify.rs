@@ -0,0 +1,146 @@
+//! Status-bar notifications.
+//!
+//! Status messages are queued instead of overwriting each other: the newest
+//! is shown, older ones that have not expired are counted (`+2 more`) and
+//! come back when it expires. Each message expires on its own, info after a
+//! few seconds and warnings and errors later. Every message also goes to a
+//! log that `:messages` shows.
+
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+
+use chrono::{DateTime, Local};
+
+/// Most messages kept for `:messages`; older ones are dropped.
+const MAX_LOG: usize = 500;
+
+/// How bad a message is, from least to most severe.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Severity {
+    Info,
+    Warning,
+    Error,
+}
+
+impl Severity {
+    pub fn name(self) -> &'static str {
+        match self {
+            Severity::Info => "info",
+            Severity::Warning => "warning",
+            Severity::Error => "error",
+        }
+    }
+
+    /// How long a message stays in the status bar.
+    pub fn timeout(self) -> Duration {
+        match self {
+            Severity::Info => Duration::from_secs(5),
+            Severity::Warning => Duration::from_secs(8),
+            Severity::Error => Duration::from_secs(15),
+        }
+    }
+}
+
+/// One status message.
+#[derive(Debug, Clone)]
+pub struct Notification {
+    pub text: String,
+    pub severity: Severity,
+    /// Wall-clock time, for `:messages`
+    pub time: DateTime<Local>,
+    posted: Instant,
+}
+
+impl Notification {
+    fn expired(&self, now: Instant) -> bool {
+        now.duration_since(self.posted) >= self.severity.timeout()
+    }
+}
+
+/// Messages waiting in the status bar, and the log of all of them.
+#[derive(Debug, Clone, Default)]
+pub struct Notifications {
+    /// Messages not expired or dismissed yet, oldest first
+    active: Vec<Notification>,
+    log: VecDeque<Notification>,
+}
+
+impl Notifications {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Queue `text`, posted at `now`; it is shown until it expires or a
+    /// newer message comes.
+    pub fn push(&mut self, text: String, severity: Severity, now: Instant) {
+        let notification = Notification {
+            text,
+            severity,
+            time: Local::now(),
+            posted: now,
+        };
+        if self.log.len() == MAX_LOG {
+            self.log.pop_front();
+        }
+        self.log.push_back(notification.clone());
+        self.active.push(notification);
+    }
+
+    /// Drop the messages that have been up long enough.
+    pub fn expire(&mut self, now: Instant) {
+        self.active
+            .retain(|notification| !notification.expired(now));
+    }
+
+    /// Take every message off the status bar; they stay in the log.
+    pub fn dismiss(&mut self) {
+        self.active.clear();
+    }
+
+    /// The message shown: the newest one not expired.
+    pub fn current(&self) -> Option<&Notification> {
+        self.active.last()
+    }
+
+    /// Messages waiting behind the one shown.
+    pub fn more(&self) -> usize {
+        self.active.len().saturating_sub(1)
+    }
+
+    /// Every message, oldest first.
+    pub fn log(&self) -> &VecDeque<Notification> {
+        &self.log
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_queue_shows_newest_until_it_expires() {
+        let start = Instant::now();
+        let mut notifications = Notifications::new();
+        notifications.push("Saved".into(), Severity::Error, start);
+        notifications.push("Copied".into(), Severity::Info, start);
+        assert_eq!(notifications.current().unwrap().text, "Copied");
+        assert_eq!(notifications.more(), 1);
+
+        // The info message goes first; the error is shown again
+        notifications.expire(start + Duration::from_secs(6));
+        assert_eq!(notifications.current().unwrap().text, "Saved");
+        assert_eq!(notifications.more(), 0);
+        notifications.expire(start + Duration::from_secs(16));
+        assert!(notifications.current().is_none());
+
+        notifications.push("Muted api".into(), Severity::Info, start);
+        notifications.dismiss();
+        assert!(notifications.current().is_none());
+        let log: Vec<&str> = notifications
+            .log()
+            .iter()
+            .map(|n| n.text.as_str())
+            .collect();
+        assert_eq!(log, ["Saved", "Copied", "Muted api"]);
+    }
+}
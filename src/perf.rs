@@ -0,0 +1,98 @@
+//! Timings and counters shown in the performance overlay (F12).
+
+use std::time::Duration;
+
+/// Hit and miss counts of a cache.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct CacheStats {
+    pub hits: u64,
+    pub misses: u64,
+}
+
+impl CacheStats {
+    /// Record one lookup.
+    pub fn record(&mut self, hit: bool) {
+        if hit {
+            self.hits += 1;
+        } else {
+            self.misses += 1;
+        }
+    }
+
+    /// Fraction of lookups served from the cache, if there were any.
+    pub fn hit_rate(&self) -> Option<f64> {
+        let lookups = self.hits + self.misses;
+        (lookups > 0).then(|| self.hits as f64 / lookups as f64)
+    }
+
+    /// Hit rate as shown in the overlay, e.g. `93% (1200 lookups)`.
+    pub fn label(&self) -> String {
+        match self.hit_rate() {
+            Some(rate) => format!("{:.0}% ({} lookups)", rate * 100.0, self.hits + self.misses),
+            None => "no lookups".to_string(),
+        }
+    }
+}
+
+/// Timings of the most recent frame and refilter.
+#[derive(Debug, Clone, Default)]
+pub struct PerfStats {
+    /// Time spent drawing the last frame
+    pub frame: Option<Duration>,
+    /// Time spent rebuilding the filtered view the last time
+    pub filter: Option<Duration>,
+    /// Lookups in the search match cache
+    pub match_cache: CacheStats,
+}
+
+/// Short elapsed time, e.g. `850µs`, `12.4ms` or `1.25s`.
+pub fn format_elapsed(elapsed: Duration) -> String {
+    let micros = elapsed.as_micros();
+    if micros < 1000 {
+        format!("{}µs", micros)
+    } else if micros < 1_000_000 {
+        format!("{:.1}ms", micros as f64 / 1000.0)
+    } else {
+        format!("{:.2}s", elapsed.as_secs_f64())
+    }
+}
+
+/// Resident set size of this process.
+#[cfg(target_os = "linux")]
+pub fn resident_bytes() -> Option<u64> {
+    let statm = std::fs::read_to_string("/proc/self/statm").ok()?;
+    let pages: u64 = statm.split_whitespace().nth(1)?.parse().ok()?;
+    let page_size = unsafe { libc::sysconf(libc::_SC_PAGESIZE) };
+    u64::try_from(page_size).ok().map(|size| pages * size)
+}
+
+#[cfg(not(target_os = "linux"))]
+pub fn resident_bytes() -> Option<u64> {
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cache_stats_hit_rate() {
+        let mut stats = CacheStats::default();
+        assert_eq!(stats.hit_rate(), None);
+        assert_eq!(stats.label(), "no lookups");
+
+        stats.record(true);
+        stats.record(true);
+        stats.record(true);
+        stats.record(false);
+        assert_eq!(stats.hit_rate(), Some(0.75));
+        assert_eq!(stats.label(), "75% (4 lookups)");
+    }
+
+    #[test]
+    fn test_format_elapsed() {
+        assert_eq!(format_elapsed(Duration::from_micros(850)), "850µs");
+        assert_eq!(format_elapsed(Duration::from_micros(12_400)), "12.4ms");
+        assert_eq!(format_elapsed(Duration::from_millis(1250)), "1.25s");
+    }
+}
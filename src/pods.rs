@@ -0,0 +1,228 @@
+//! Pods of a multi-replica stream.
+//!
+//! `kubectl logs -f deploy/api --all-pods --prefix | qlog` starts every
+//! line with the pod and container it came from:
+//! `[pod/api-7d9f8b-x2k4p/api] GET /health 200`. Lines with such a prefix
+//! are counted per pod; the viewer draws the prefix as a badge in the pod's
+//! color, and `:pods` lists the pods with their line rates. A paused pod's
+//! lines are held back until it is resumed; a muted pod is hidden by an
+//! exclude filter on its prefix, so it shows up in the filter list too.
+
+use std::collections::VecDeque;
+use std::ops::Range;
+use std::time::{Duration, Instant};
+
+use ratatui::style::Color;
+
+use crate::follow::FollowedLine;
+
+/// Line rates are averaged over this long.
+pub const RATE_WINDOW: Duration = Duration::from_secs(10);
+
+/// Badge colors, assigned to pods in the order they first appear.
+const BADGE_COLORS: [Color; 6] = [
+    Color::Cyan,
+    Color::Magenta,
+    Color::Green,
+    Color::Yellow,
+    Color::LightBlue,
+    Color::LightRed,
+];
+
+/// Pod name and byte range of a `[pod/<name>/<container>] ` prefix.
+pub fn parse_prefix(line: &[u8]) -> Option<(&str, Range<usize>)> {
+    let rest = line.strip_prefix(b"[pod/")?;
+    let end = rest.iter().position(|&b| b == b']')?;
+    let name_len = rest[..end].iter().position(|&b| b == b'/')?;
+    let name = std::str::from_utf8(&rest[..name_len]).ok()?;
+    let prefix_len = "[pod/".len() + end + 1;
+    let prefix_len = match line.get(prefix_len) {
+        Some(b' ') => prefix_len + 1,
+        _ => prefix_len,
+    };
+    (!name.is_empty()).then_some((name, 0..prefix_len))
+}
+
+/// Exclude filter pattern that hides the lines of pod `name`.
+pub fn mute_pattern(name: &str) -> String {
+    format!("[pod/{}/", name)
+}
+
+/// One pod seen in the stream.
+#[derive(Debug, Clone)]
+pub struct Pod {
+    pub name: String,
+    pub color: Color,
+    /// Lines received, including held ones
+    pub lines: usize,
+    /// Whether new lines are held back instead of shown
+    pub paused: bool,
+    /// Lines received while paused, in arrival order
+    held: Vec<FollowedLine>,
+    /// Arrival time and line count of recent batches
+    arrivals: VecDeque<(Instant, usize)>,
+}
+
+impl Pod {
+    /// Lines per second over the last [`RATE_WINDOW`].
+    pub fn rate(&self, now: Instant) -> f64 {
+        let recent: usize = self
+            .arrivals
+            .iter()
+            .filter(|(at, _)| now.duration_since(*at) <= RATE_WINDOW)
+            .map(|(_, count)| count)
+            .sum();
+        recent as f64 / RATE_WINDOW.as_secs_f64()
+    }
+
+    /// Number of lines waiting for the pod to be resumed.
+    pub fn held(&self) -> usize {
+        self.held.len()
+    }
+}
+
+/// Pods seen in the stream, in order of their first line.
+#[derive(Debug, Clone, Default)]
+pub struct Pods {
+    pods: Vec<Pod>,
+}
+
+impl Pods {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Count arriving lines per pod and return the ones to show now; lines
+    /// of paused pods are held back.
+    pub fn admit(&mut self, lines: Vec<FollowedLine>, now: Instant) -> Vec<FollowedLine> {
+        let mut shown = Vec::with_capacity(lines.len());
+        let mut batch: Vec<usize> = Vec::new();
+        for line in lines {
+            let Some((name, _)) = parse_prefix(&line.text) else {
+                shown.push(line);
+                continue;
+            };
+            let idx = match self.position(name) {
+                Some(idx) => idx,
+                None => {
+                    self.pods.push(Pod {
+                        name: name.to_string(),
+                        color: BADGE_COLORS[self.pods.len() % BADGE_COLORS.len()],
+                        lines: 0,
+                        paused: false,
+                        held: Vec::new(),
+                        arrivals: VecDeque::new(),
+                    });
+                    self.pods.len() - 1
+                }
+            };
+            batch.resize(self.pods.len(), 0);
+            batch[idx] += 1;
+            let pod = &mut self.pods[idx];
+            pod.lines += 1;
+            if pod.paused {
+                pod.held.push(line);
+            } else {
+                shown.push(line);
+            }
+        }
+        for (pod, count) in self.pods.iter_mut().zip(batch) {
+            if count > 0 {
+                pod.arrivals.push_back((now, count));
+            }
+            while pod
+                .arrivals
+                .front()
+                .is_some_and(|(at, _)| now.duration_since(*at) > RATE_WINDOW)
+            {
+                pod.arrivals.pop_front();
+            }
+        }
+        shown
+    }
+
+    /// Pause or resume pod `idx`. Resuming returns the lines held while it
+    /// was paused.
+    pub fn toggle_pause(&mut self, idx: usize) -> Option<Vec<FollowedLine>> {
+        let pod = self.pods.get_mut(idx)?;
+        pod.paused = !pod.paused;
+        Some(if pod.paused {
+            Vec::new()
+        } else {
+            std::mem::take(&mut pod.held)
+        })
+    }
+
+    fn position(&self, name: &str) -> Option<usize> {
+        self.pods.iter().position(|pod| pod.name == name)
+    }
+
+    /// The pod a line came from and the byte range of its prefix.
+    pub fn of_line(&self, line: &[u8]) -> Option<(&Pod, Range<usize>)> {
+        let (name, prefix) = parse_prefix(line)?;
+        Some((&self.pods[self.position(name)?], prefix))
+    }
+
+    pub fn get(&self, idx: usize) -> Option<&Pod> {
+        self.pods.get(idx)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &Pod> {
+        self.pods.iter()
+    }
+
+    pub fn len(&self) -> usize {
+        self.pods.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.pods.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn line(text: &str) -> FollowedLine {
+        FollowedLine {
+            file_index: 0,
+            text: text.as_bytes().to_vec(),
+            timestamp: None,
+        }
+    }
+
+    #[test]
+    fn test_parse_prefix() {
+        assert_eq!(
+            parse_prefix(b"[pod/api-7d9f8b-x2k4p/api] GET /health"),
+            Some(("api-7d9f8b-x2k4p", 0..27))
+        );
+        assert_eq!(parse_prefix(b"[pod/api-0/api]"), Some(("api-0", 0..15)));
+        assert_eq!(parse_prefix(b"[pod/no-container] x"), None);
+        assert_eq!(parse_prefix(b"2024-01-01 INFO [pod/a/b] x"), None);
+    }
+
+    #[test]
+    fn test_paused_pod_lines_are_held() {
+        let mut pods = Pods::new();
+        let now = Instant::now();
+        let shown = pods.admit(
+            vec![line("[pod/a/c] one"), line("plain"), line("[pod/b/c] two")],
+            now,
+        );
+        assert_eq!(shown.len(), 3);
+        assert_eq!(pods.len(), 2);
+
+        pods.toggle_pause(0);
+        let shown = pods.admit(vec![line("[pod/a/c] held"), line("[pod/b/c] three")], now);
+        assert_eq!(shown, vec![line("[pod/b/c] three")]);
+        assert_eq!(pods.get(0).unwrap().held(), 1);
+        assert_eq!(pods.get(0).unwrap().lines, 2);
+        assert_eq!(pods.get(1).unwrap().rate(now), 0.2);
+
+        assert_eq!(pods.toggle_pause(0), Some(vec![line("[pod/a/c] held")]));
+        assert_eq!(pods.get(0).unwrap().held(), 0);
+        assert_eq!(pods.get(0).unwrap().rate(now + RATE_WINDOW * 2), 0.0);
+    }
+}
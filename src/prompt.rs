@@ -0,0 +1,108 @@
+//! Modal prompts.
+//!
+//! Decisions (overwrite a file? how to index a large one?) are asked in one
+//! overlay: a title, a few lines saying what is at stake and a list of
+//! choices. Each choice has a key that picks it at once; j/k or the arrow
+//! keys move the highlight and Enter picks the highlighted choice. Esc
+//! cancels. What a choice does is up to the caller, which gets the key of
+//! the choice picked. Choice keys other than j, k and q are free to use.
+
+/// One answer to a prompt.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Choice {
+    /// Key that picks the choice
+    pub key: char,
+    pub label: String,
+}
+
+impl Choice {
+    pub fn new(key: char, label: impl Into<String>) -> Self {
+        Self {
+            key,
+            label: label.into(),
+        }
+    }
+}
+
+/// A question waiting for an answer.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Prompt {
+    pub title: String,
+    /// Lines shown above the choices
+    pub message: Vec<String>,
+    pub choices: Vec<Choice>,
+    /// Highlighted choice
+    pub selected: usize,
+}
+
+impl Prompt {
+    /// Ask to pick one of `choices`; the first is highlighted.
+    pub fn select(title: impl Into<String>, message: Vec<String>, choices: Vec<Choice>) -> Self {
+        Self {
+            title: title.into(),
+            message,
+            choices,
+            selected: 0,
+        }
+    }
+
+    /// Ask yes or no, with `yes` naming what yes does. No is highlighted,
+    /// so Enter alone does nothing harmful.
+    pub fn confirm(title: impl Into<String>, message: Vec<String>, yes: &str) -> Self {
+        let mut prompt = Self::select(
+            title,
+            message,
+            vec![Choice::new('y', yes), Choice::new('n', "No")],
+        );
+        prompt.selected = 1;
+        prompt
+    }
+
+    pub fn move_down(&mut self) {
+        self.selected = (self.selected + 1).min(self.choices.len().saturating_sub(1));
+    }
+
+    pub fn move_up(&mut self) {
+        self.selected = self.selected.saturating_sub(1);
+    }
+
+    /// Key of the highlighted choice.
+    pub fn selected_key(&self) -> Option<char> {
+        self.choices.get(self.selected).map(|choice| choice.key)
+    }
+
+    /// Whether `key` picks one of the choices.
+    pub fn has_key(&self, key: char) -> bool {
+        self.choices.iter().any(|choice| choice.key == key)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_choices_by_key_and_highlight() {
+        let mut prompt = Prompt::select(
+            "Large file",
+            vec![],
+            vec![
+                Choice::new('f', "Full"),
+                Choice::new('o', "On demand"),
+                Choice::new('t', "Tail"),
+            ],
+        );
+        assert!(prompt.has_key('t'));
+        assert!(!prompt.has_key('x'));
+        prompt.move_up();
+        assert_eq!(prompt.selected_key(), Some('f'));
+        for _ in 0..5 {
+            prompt.move_down();
+        }
+        assert_eq!(prompt.selected_key(), Some('t'));
+
+        let confirm = Prompt::confirm("Overwrite?", vec!["a.log exists".into()], "Overwrite");
+        assert_eq!(confirm.selected_key(), Some('n'));
+        assert!(confirm.has_key('y'));
+    }
+}
@@ -0,0 +1,297 @@
+//! `:sql` queries over the filtered lines.
+//!
+//! The filtered lines are loaded into an in-memory SQLite table `logs`, one
+//! row per line:
+//!
+//! - `line`: line number as shown in the viewer
+//! - `time`: `2024-01-01 10:00:00.000` (usable with SQLite date functions)
+//! - `level`, `message`, `text`: level name, the message (the JSON message
+//!   or the whole line) and the line text
+//! - one column per field found in the lines: top-level JSON keys, or
+//!   `key=value` pairs of plain lines; numbers are stored as numbers
+//!
+//! With the sidecar index on, the file's database is attached as `sidecar`,
+//! so its `lines` and `bookmarks` tables can be queried and joined as well.
+//! Only statements that write nothing are run.
+
+use std::path::Path;
+
+use rusqlite::types::{Value as SqlValue, ValueRef};
+use rusqlite::{params_from_iter, Connection};
+use serde_json::Value;
+
+use crate::export::LineRecord;
+use crate::jobs::JobContext;
+use crate::source::LineSource;
+
+/// Maximum number of rows kept from a query.
+pub const MAX_QUERY_ROWS: usize = 1000;
+
+/// Maximum number of filtered lines loaded into the `logs` table.
+pub const MAX_TABLE_LINES: usize = 200_000;
+
+/// Maximum number of field columns; rarer fields beyond it are left out.
+const MAX_FIELD_COLUMNS: usize = 64;
+
+/// Lines loaded into the `logs` table by a job between checks for
+/// cancellation.
+const LOAD_CHUNK_LINES: usize = 10_000;
+
+/// Columns every row has, before the field columns.
+const FIXED_COLUMNS: [&str; 5] = ["line", "time", "level", "message", "text"];
+
+/// Rows returned by a query, as display text.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct QueryResult {
+    pub columns: Vec<String>,
+    pub rows: Vec<Vec<String>>,
+    /// Whether rows beyond `MAX_QUERY_ROWS` were dropped
+    pub truncated: bool,
+}
+
+/// Run `sql` over the lines `indices` refer to (at most `MAX_TABLE_LINES`
+/// of them), with the sidecar database at `sidecar` attached.
+pub fn run(
+    source: &dyn LineSource,
+    indices: &[usize],
+    sidecar: Option<&Path>,
+    sql: &str,
+) -> Result<QueryResult, String> {
+    execute(source, indices, sidecar, sql, |_| true).map(Option::unwrap_or_default)
+}
+
+/// [`run`] on the thread of a job, reporting the lines loaded into the
+/// `logs` table as progress; None once the job is cancelled.
+pub fn run_in_job<U>(
+    source: &dyn LineSource,
+    indices: &[usize],
+    sidecar: Option<&Path>,
+    sql: &str,
+    ctx: &JobContext<U>,
+) -> Option<Result<QueryResult, String>> {
+    let loaded = |lines| {
+        ctx.advance(lines);
+        !ctx.is_cancelled()
+    };
+    execute(source, indices, sidecar, sql, loaded).transpose()
+}
+
+/// Load the lines and run `sql`; None if `loaded` stopped the loading.
+fn execute(
+    source: &dyn LineSource,
+    indices: &[usize],
+    sidecar: Option<&Path>,
+    sql: &str,
+    loaded: impl FnMut(usize) -> bool,
+) -> Result<Option<QueryResult>, String> {
+    let mut conn = Connection::open_in_memory().map_err(|e| e.to_string())?;
+    let indices = &indices[..indices.len().min(MAX_TABLE_LINES)];
+    if !load_lines(&mut conn, source, indices, loaded).map_err(|e| e.to_string())? {
+        return Ok(None);
+    }
+    if let Some(path) = sidecar {
+        conn.execute("ATTACH DATABASE ?1 AS sidecar", [path.to_string_lossy()])
+            .map_err(|e| e.to_string())?;
+    }
+    query(&conn, sql).map(Some)
+}
+
+/// Create the `logs` table and fill it with the given lines, calling
+/// `loaded` with the number of lines after each chunk. Returns false if
+/// `loaded` returned false to stop.
+fn load_lines(
+    conn: &mut Connection,
+    source: &dyn LineSource,
+    indices: &[usize],
+    mut loaded: impl FnMut(usize) -> bool,
+) -> rusqlite::Result<bool> {
+    // First pass: field columns in order of first appearance
+    let mut fields: Vec<String> = Vec::new();
+    for &idx in indices {
+        if fields.len() == MAX_FIELD_COLUMNS {
+            break;
+        }
+        let Some(record) = LineRecord::read(source, idx) else {
+            continue;
+        };
+        for (key, _) in record.fields() {
+            if fields.len() < MAX_FIELD_COLUMNS
+                && column_position(&FIXED_COLUMNS, &key).is_none()
+                && column_position(&fields, &key).is_none()
+            {
+                fields.push(key);
+            }
+        }
+    }
+
+    let columns: Vec<String> = FIXED_COLUMNS
+        .iter()
+        .map(|c| c.to_string())
+        .chain(fields.iter().cloned())
+        .collect();
+    let quoted: Vec<String> = columns.iter().map(|c| quote_identifier(c)).collect();
+    conn.execute(&format!("CREATE TABLE logs ({})", quoted.join(", ")), [])?;
+
+    let tx = conn.transaction()?;
+    {
+        let placeholders = vec!["?"; columns.len()].join(", ");
+        let mut insert = tx.prepare(&format!(
+            "INSERT INTO logs ({}) VALUES ({})",
+            quoted.join(", "),
+            placeholders
+        ))?;
+        for chunk in indices.chunks(LOAD_CHUNK_LINES) {
+            for &idx in chunk {
+                let Some(record) = LineRecord::read(source, idx) else {
+                    continue;
+                };
+                let mut row = vec![
+                    SqlValue::Integer(idx as i64 + 1),
+                    text_or_null(&record.time),
+                    text_or_null(&record.level),
+                    SqlValue::Text(record.message.clone()),
+                    SqlValue::Text(record.text.clone()),
+                ];
+                row.resize(columns.len(), SqlValue::Null);
+                for (key, value) in record.fields() {
+                    if let Some(col) = column_position(&fields, &key) {
+                        row[FIXED_COLUMNS.len() + col] = sql_value(&value);
+                    }
+                }
+                insert.execute(params_from_iter(row))?;
+            }
+            if !loaded(chunk.len()) {
+                return Ok(false);
+            }
+        }
+    }
+    tx.commit().map(|_| true)
+}
+
+/// Run a read-only statement, keeping at most `MAX_QUERY_ROWS` rows.
+fn query(conn: &Connection, sql: &str) -> Result<QueryResult, String> {
+    let mut stmt = conn.prepare(sql).map_err(|e| e.to_string())?;
+    if !stmt.readonly() {
+        return Err("Only queries that change nothing can run".to_string());
+    }
+    let columns: Vec<String> = stmt.column_names().iter().map(|c| c.to_string()).collect();
+    let mut rows = stmt.query([]).map_err(|e| e.to_string())?;
+
+    let mut result = QueryResult {
+        columns,
+        ..QueryResult::default()
+    };
+    while let Some(row) = rows.next().map_err(|e| e.to_string())? {
+        if result.rows.len() == MAX_QUERY_ROWS {
+            result.truncated = true;
+            break;
+        }
+        let cells = (0..result.columns.len())
+            .map(|col| row.get_ref(col).map(display_value))
+            .collect::<Result<_, _>>()
+            .map_err(|e| e.to_string())?;
+        result.rows.push(cells);
+    }
+    Ok(result)
+}
+
+/// Position of a column name, compared as SQLite does (ignoring ASCII case).
+fn column_position(columns: &[impl AsRef<str>], name: &str) -> Option<usize> {
+    columns
+        .iter()
+        .position(|column| column.as_ref().eq_ignore_ascii_case(name))
+}
+
+fn quote_identifier(name: &str) -> String {
+    format!("\"{}\"", name.replace('"', "\"\""))
+}
+
+fn text_or_null(text: &str) -> SqlValue {
+    if text.is_empty() {
+        SqlValue::Null
+    } else {
+        SqlValue::Text(text.to_string())
+    }
+}
+
+/// Field value as stored in the table; numeric text becomes a number so
+/// `duration_ms > 500` compares numerically.
+fn sql_value(value: &Value) -> SqlValue {
+    match value {
+        Value::Null => SqlValue::Null,
+        Value::Bool(b) => SqlValue::Integer(*b as i64),
+        Value::Number(n) => n
+            .as_i64()
+            .map(SqlValue::Integer)
+            .or_else(|| n.as_f64().map(SqlValue::Real))
+            .unwrap_or(SqlValue::Null),
+        Value::String(s) => s
+            .parse::<i64>()
+            .map(SqlValue::Integer)
+            .or_else(|_| s.parse::<f64>().map(SqlValue::Real))
+            .unwrap_or_else(|_| SqlValue::Text(s.clone())),
+        other => SqlValue::Text(other.to_string()),
+    }
+}
+
+fn display_value(value: ValueRef<'_>) -> String {
+    match value {
+        ValueRef::Null => "NULL".to_string(),
+        ValueRef::Integer(n) => n.to_string(),
+        ValueRef::Real(x) => x.to_string(),
+        ValueRef::Text(text) => String::from_utf8_lossy(text).into_owned(),
+        ValueRef::Blob(blob) => format!("<{} bytes>", blob.len()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lines(texts: &[&str]) -> Vec<String> {
+        texts.iter().map(|t| t.to_string()).collect()
+    }
+
+    #[test]
+    fn test_fields_become_columns() {
+        let source = lines(&[
+            r#"{"level":"error","msg":"slow","duration_ms":900,"user":"ann"}"#,
+            r#"{"level":"info","msg":"fast","duration_ms":12,"user":"bob"}"#,
+            "plain line duration_ms=700 user=cid",
+        ]);
+        let result = run(
+            &source,
+            &[0, 1, 2],
+            None,
+            "SELECT line, user FROM logs WHERE duration_ms > 500 ORDER BY line",
+        )
+        .unwrap();
+        assert_eq!(result.columns, vec!["line", "user"]);
+        assert_eq!(result.rows, vec![vec!["1", "ann"], vec!["3", "cid"]]);
+    }
+
+    #[test]
+    fn test_only_filtered_lines_are_loaded() {
+        let source = lines(&["INFO a", "ERROR b", "ERROR c"]);
+        let result = run(
+            &source,
+            &[1, 2],
+            None,
+            "SELECT level, count(*) FROM logs GROUP BY level",
+        )
+        .unwrap();
+        assert_eq!(result.rows, vec![vec!["error", "2"]]);
+    }
+
+    #[test]
+    fn test_writes_are_refused() {
+        let source = lines(&["INFO a"]);
+        assert_eq!(
+            run(&source, &[0], None, "DELETE FROM logs").unwrap_err(),
+            "Only queries that change nothing can run"
+        );
+        assert!(run(&source, &[0], None, "SELEC 1")
+            .unwrap_err()
+            .contains("syntax error"));
+    }
+}
@@ -0,0 +1,286 @@
+//! Quickfix-style error list of build and test output.
+//!
+//! CI logs mix service output with compiler and test-runner diagnostics.
+//! `:errors` collects the diagnostics into a list with their file and line
+//! references; `]q` and `[q` step through them in the log view.
+//!
+//! Recognized formats:
+//!
+//! - rustc/cargo: `error[E0308]: mismatched types` followed by
+//!   `  --> src/main.rs:10:5`
+//! - pytest: `tests/test_api.py:42: AssertionError` and
+//!   `FAILED tests/test_api.py::test_login - AssertionError: ...`
+//! - dotnet/MSBuild: `Foo.cs(12,5): error CS1002: ; expected [Foo.csproj]`
+
+use std::ops::Range;
+
+use crate::source::LineSource;
+
+/// Lines between a rustc `error:` header and its `-->` location.
+const RUSTC_HEADER_DISTANCE: usize = 3;
+
+/// Severity of a diagnostic.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Severity {
+    Error,
+    Warning,
+}
+
+impl Severity {
+    pub fn name(self) -> &'static str {
+        match self {
+            Severity::Error => "error",
+            Severity::Warning => "warning",
+        }
+    }
+}
+
+/// One diagnostic found in the log.
+#[derive(Debug, Clone, PartialEq)]
+pub struct QuickfixEntry {
+    /// Storage index of the line the diagnostic starts on
+    pub line: usize,
+    pub severity: Severity,
+    /// Source file the diagnostic refers to
+    pub path: String,
+    /// 1-based line in `path`, if given
+    pub row: Option<usize>,
+    /// 1-based column in `path`, if given
+    pub column: Option<usize>,
+    pub message: String,
+}
+
+impl QuickfixEntry {
+    /// `path:row:column`, leaving out what the diagnostic does not give.
+    pub fn location(&self) -> String {
+        let mut location = self.path.clone();
+        for part in [self.row, self.column].into_iter().flatten() {
+            location.push_str(&format!(":{}", part));
+        }
+        location
+    }
+}
+
+/// Collect the diagnostics on lines `lines` of `source`.
+pub fn scan(source: &dyn LineSource, lines: Range<usize>) -> Vec<QuickfixEntry> {
+    let mut entries = Vec::new();
+    // Last rustc `error:`/`warning:` header, waiting for its `-->` line
+    let mut header: Option<(usize, Severity, String)> = None;
+
+    for idx in lines {
+        let Some(line) = source.get_line(idx) else {
+            continue;
+        };
+        let text = line.as_str_lossy();
+        let text = strip_ci_prefix(&text);
+
+        if let Some(parsed) = rustc_header(text) {
+            header = Some((idx, parsed.0, parsed.1));
+            continue;
+        }
+        if let Some(location) = text.trim_start().strip_prefix("--> ") {
+            let (path, row, column) = split_location(location.trim());
+            let (line, severity, message) = match header.take() {
+                Some((start, severity, message)) if idx - start <= RUSTC_HEADER_DISTANCE => {
+                    (start, severity, message)
+                }
+                _ => (idx, Severity::Error, String::new()),
+            };
+            entries.push(QuickfixEntry {
+                line,
+                severity,
+                path,
+                row,
+                column,
+                message,
+            });
+            continue;
+        }
+        if let Some(mut entry) = dotnet(text).or_else(|| pytest(text)) {
+            entry.line = idx;
+            entries.push(entry);
+        }
+    }
+    entries
+}
+
+/// Drop a CI runner's leading timestamp (`2024-01-01T10:00:00.123Z `).
+fn strip_ci_prefix(text: &str) -> &str {
+    match text.split_once(' ') {
+        Some((stamp, rest))
+            if stamp.ends_with('Z')
+                && stamp.contains('T')
+                && stamp.starts_with(|c: char| c.is_ascii_digit()) =>
+        {
+            rest
+        }
+        _ => text,
+    }
+}
+
+/// `error[E0308]: message`, `error: message` or `warning: message`.
+fn rustc_header(text: &str) -> Option<(Severity, String)> {
+    let (severity, rest) = if let Some(rest) = text.strip_prefix("error") {
+        (Severity::Error, rest)
+    } else {
+        (Severity::Warning, text.strip_prefix("warning")?)
+    };
+    let rest = match rest.strip_prefix('[') {
+        Some(code) => code.split_once(']')?.1,
+        None => rest,
+    };
+    let message = rest.strip_prefix(": ")?;
+    Some((severity, message.trim().to_string()))
+}
+
+/// Split `path:row:column` (row and column optional).
+fn split_location(location: &str) -> (String, Option<usize>, Option<usize>) {
+    let mut parts = location.rsplitn(3, ':');
+    let last = parts.next().unwrap_or_default();
+    let middle = parts.next();
+    let first = parts.next();
+    match (
+        first,
+        middle.map(str::parse::<usize>),
+        last.parse::<usize>(),
+    ) {
+        (Some(path), Some(Ok(row)), Ok(column)) => (path.to_string(), Some(row), Some(column)),
+        (_, _, Ok(row)) if middle.is_some() => {
+            let path = location.rsplit_once(':').map_or(location, |(path, _)| path);
+            (path.to_string(), Some(row), None)
+        }
+        _ => (location.to_string(), None, None),
+    }
+}
+
+/// `Foo.cs(12,5): error CS1002: ; expected [Foo.csproj]`
+fn dotnet(text: &str) -> Option<QuickfixEntry> {
+    let (location, rest) = text.trim_start().split_once("): ")?;
+    let (severity, rest) = if let Some(rest) = rest.strip_prefix("error ") {
+        (Severity::Error, rest)
+    } else {
+        (Severity::Warning, rest.strip_prefix("warning ")?)
+    };
+    let (path, position) = location.rsplit_once('(')?;
+    let mut numbers = position.split(',').map(|n| n.trim().parse::<usize>());
+    let row = numbers.next()?.ok()?;
+    let column = numbers.next().and_then(Result::ok);
+    // The project in brackets only repeats what the path says
+    let message = match rest.rfind(" [") {
+        Some(end) if rest.ends_with(']') => &rest[..end],
+        _ => rest,
+    };
+    Some(QuickfixEntry {
+        line: 0,
+        severity,
+        path: path.to_string(),
+        row: Some(row),
+        column,
+        message: message.trim().to_string(),
+    })
+}
+
+/// `tests/test_api.py:42: AssertionError` or
+/// `FAILED tests/test_api.py::test_login - AssertionError: ...`
+fn pytest(text: &str) -> Option<QuickfixEntry> {
+    if let Some(rest) = text
+        .strip_prefix("FAILED ")
+        .or_else(|| text.strip_prefix("ERROR "))
+    {
+        let (test, message) = rest.split_once(" - ").unwrap_or((rest, ""));
+        let path = test.split("::").next()?;
+        return path.ends_with(".py").then(|| QuickfixEntry {
+            line: 0,
+            severity: Severity::Error,
+            path: path.to_string(),
+            row: None,
+            column: None,
+            message: message.trim().to_string(),
+        });
+    }
+    let (path, rest) = text.split_once(".py:")?;
+    let (row, message) = rest.split_once(": ")?;
+    Some(QuickfixEntry {
+        line: 0,
+        severity: Severity::Error,
+        path: format!("{}.py", path),
+        row: Some(row.parse().ok()?),
+        column: None,
+        message: message.trim().to_string(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn scan_lines(lines: &[&str]) -> Vec<QuickfixEntry> {
+        let lines: Vec<String> = lines.iter().map(|line| line.to_string()).collect();
+        scan(&lines, 0..lines.len())
+    }
+
+    #[test]
+    fn test_rustc_dotnet_and_pytest_diagnostics() {
+        let entries = scan_lines(&[
+            "   Compiling app v0.1.0",
+            "2024-01-01T10:00:00.1234567Z error[E0308]: mismatched types",
+            "  --> src/main.rs:10:5",
+            "warning: unused variable: `x`",
+            " --> src/lib.rs:3:9",
+            r"C:\src\Foo.cs(12,5): error CS1002: ; expected [C:\src\Foo.csproj]",
+            "tests/test_api.py:42: AssertionError",
+            "FAILED tests/test_api.py::test_login - assert 500 == 200",
+            "error: aborting due to 1 previous error",
+        ]);
+
+        let summary: Vec<(usize, Severity, String, &str)> = entries
+            .iter()
+            .map(|e| (e.line, e.severity, e.location(), e.message.as_str()))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                (
+                    1,
+                    Severity::Error,
+                    "src/main.rs:10:5".to_string(),
+                    "mismatched types"
+                ),
+                (
+                    3,
+                    Severity::Warning,
+                    "src/lib.rs:3:9".to_string(),
+                    "unused variable: `x`"
+                ),
+                (
+                    5,
+                    Severity::Error,
+                    r"C:\src\Foo.cs:12:5".to_string(),
+                    "CS1002: ; expected"
+                ),
+                (
+                    6,
+                    Severity::Error,
+                    "tests/test_api.py:42".to_string(),
+                    "AssertionError"
+                ),
+                (
+                    7,
+                    Severity::Error,
+                    "tests/test_api.py".to_string(),
+                    "assert 500 == 200"
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_plain_log_lines_are_not_diagnostics() {
+        let entries = scan_lines(&[
+            "2024-01-01 10:00:00 ERROR db: connection refused",
+            "errors: 0",
+            "see config.py for details",
+        ]);
+        assert!(entries.is_empty());
+    }
+}
@@ -0,0 +1,186 @@
+//! Recently opened file sets.
+//!
+//! Every launch records the set of files it opened in `~/.qlog/recent.toml`
+//! so a later session can reopen them from the `:recent` overlay.
+
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use chrono::{DateTime, Local};
+use serde::{Deserialize, Serialize};
+
+/// Maximum number of file sets kept in the history.
+const MAX_RECENT_ENTRIES: usize = 10;
+
+/// One previously opened set of files.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RecentEntry {
+    pub paths: Vec<PathBuf>,
+    pub opened_at: DateTime<Local>,
+}
+
+impl RecentEntry {
+    /// Short human-readable description, e.g. `app.log, worker.log (+3 more)`.
+    pub fn label(&self) -> String {
+        const SHOWN: usize = 2;
+        let names: Vec<String> = self
+            .paths
+            .iter()
+            .take(SHOWN)
+            .map(|p| {
+                p.file_name()
+                    .map(|n| n.to_string_lossy().to_string())
+                    .unwrap_or_else(|| p.display().to_string())
+            })
+            .collect();
+        let mut label = names.join(", ");
+        if self.paths.len() > SHOWN {
+            label.push_str(&format!(" (+{} more)", self.paths.len() - SHOWN));
+        }
+        label
+    }
+}
+
+/// Most-recently-used list of opened file sets, newest first.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RecentFiles {
+    #[serde(default)]
+    entries: Vec<RecentEntry>,
+}
+
+impl RecentFiles {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Location of the history file (`~/.qlog/recent.toml`).
+    pub fn default_path() -> Option<PathBuf> {
+        dirs::home_dir().map(|home| home.join(".qlog/recent.toml"))
+    }
+
+    /// Load the history from the default location. Missing or invalid files yield an empty list.
+    pub fn load() -> Self {
+        Self::default_path()
+            .map(|path| Self::load_from_path(&path))
+            .unwrap_or_default()
+    }
+
+    /// Load the history from a specific path.
+    pub fn load_from_path(path: &Path) -> Self {
+        fs::read_to_string(path)
+            .ok()
+            .and_then(|content| toml::from_str(&content).ok())
+            .unwrap_or_default()
+    }
+
+    /// Save the history to the default location.
+    pub fn save(&self) -> io::Result<()> {
+        match Self::default_path() {
+            Some(path) => self.save_to_path(&path),
+            None => Ok(()),
+        }
+    }
+
+    /// Save the history to a specific path, creating parent directories.
+    pub fn save_to_path(&self, path: &Path) -> io::Result<()> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let content =
+            toml::to_string(self).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+        fs::write(path, content)
+    }
+
+    /// Record a newly opened file set, moving it to the front if already present.
+    pub fn record(&mut self, paths: &[PathBuf]) {
+        if paths.is_empty() {
+            return;
+        }
+        let paths: Vec<PathBuf> = paths
+            .iter()
+            .map(|p| fs::canonicalize(p).unwrap_or_else(|_| p.clone()))
+            .collect();
+
+        self.entries.retain(|entry| entry.paths != paths);
+        self.entries.insert(
+            0,
+            RecentEntry {
+                paths,
+                opened_at: Local::now(),
+            },
+        );
+        self.entries.truncate(MAX_RECENT_ENTRIES);
+    }
+
+    pub fn entries(&self) -> &[RecentEntry] {
+        &self.entries
+    }
+
+    pub fn get(&self, index: usize) -> Option<&RecentEntry> {
+        self.entries.get(index)
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_record_moves_existing_to_front() {
+        let mut recent = RecentFiles::new();
+        recent.record(&[PathBuf::from("/logs/a.log")]);
+        recent.record(&[PathBuf::from("/logs/b.log")]);
+        recent.record(&[PathBuf::from("/logs/a.log")]);
+
+        assert_eq!(recent.len(), 2);
+        assert_eq!(
+            recent.entries()[0].paths,
+            vec![PathBuf::from("/logs/a.log")]
+        );
+    }
+
+    #[test]
+    fn test_record_truncates_history() {
+        let mut recent = RecentFiles::new();
+        for i in 0..(MAX_RECENT_ENTRIES + 5) {
+            recent.record(&[PathBuf::from(format!("/logs/{}.log", i))]);
+        }
+        assert_eq!(recent.len(), MAX_RECENT_ENTRIES);
+    }
+
+    #[test]
+    fn test_save_and_load_roundtrip() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join(".qlog/recent.toml");
+
+        let mut recent = RecentFiles::new();
+        recent.record(&[PathBuf::from("/logs/a.log"), PathBuf::from("/logs/b.log")]);
+        recent.save_to_path(&path).unwrap();
+
+        let loaded = RecentFiles::load_from_path(&path);
+        assert_eq!(loaded.entries(), recent.entries());
+    }
+
+    #[test]
+    fn test_entry_label() {
+        let entry = RecentEntry {
+            paths: vec![
+                PathBuf::from("/logs/app.log"),
+                PathBuf::from("/logs/worker.log"),
+                PathBuf::from("/logs/db.log"),
+            ],
+            opened_at: Local::now(),
+        };
+        assert_eq!(entry.label(), "app.log, worker.log (+1 more)");
+    }
+}
@@ -0,0 +1,144 @@
+//! Replay of a loaded log in real time (`:replay`).
+//!
+//! Replay hides the lines after a start time and reveals them as a clock
+//! runs on from there, at the log's own pace or faster (`10x`, `60x`); the
+//! view stays on the newest line as in follow mode. Lines without a
+//! timestamp come with the entry above them.
+
+use std::ops::Range;
+use std::time::Instant;
+
+use chrono::{DateTime, Utc};
+
+use crate::model::LogStorage;
+
+/// A running replay.
+#[derive(Debug, Clone)]
+pub struct Replay {
+    speed: u32,
+    /// Log time on the clock at `since`
+    start: DateTime<Utc>,
+    since: Instant,
+    paused: bool,
+    /// First line not revealed yet
+    end: usize,
+}
+
+impl Replay {
+    /// Replay from log time `start` at `speed` times real time.
+    pub fn new(start: DateTime<Utc>, speed: u32, now: Instant) -> Self {
+        Self {
+            speed: speed.max(1),
+            start,
+            since: now,
+            paused: false,
+            end: 0,
+        }
+    }
+
+    /// Parse a speed such as `10x` (or `10`).
+    pub fn parse_speed(text: &str) -> Option<u32> {
+        let text = text.trim();
+        let digits = text.strip_suffix(['x', 'X']).unwrap_or(text);
+        digits.parse().ok().filter(|&speed| speed > 0)
+    }
+
+    pub fn speed(&self) -> u32 {
+        self.speed
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.paused
+    }
+
+    /// First line not revealed yet; the lines before it are shown.
+    pub fn end(&self) -> usize {
+        self.end
+    }
+
+    /// Log time the replay has reached at `now`.
+    pub fn clock(&self, now: Instant) -> DateTime<Utc> {
+        if self.paused {
+            return self.start;
+        }
+        let elapsed = now.saturating_duration_since(self.since) * self.speed;
+        self.start + chrono::Duration::from_std(elapsed).unwrap_or_default()
+    }
+
+    /// Play at `speed` times real time from `now` on.
+    pub fn set_speed(&mut self, speed: u32, now: Instant) {
+        self.rebase(now);
+        self.speed = speed.max(1);
+    }
+
+    /// Pause or resume the clock. Returns true if it is paused now.
+    pub fn toggle_pause(&mut self, now: Instant) -> bool {
+        self.rebase(now);
+        self.paused = !self.paused;
+        self.paused
+    }
+
+    /// Reveal the lines up to the clock at `now`; returns the range of
+    /// lines revealed by this call.
+    pub fn advance(&mut self, storage: &LogStorage, now: Instant) -> Range<usize> {
+        let clock = self.clock(now);
+        let first = self.end;
+        while self.end < storage.len() {
+            match storage
+                .get_line_info(self.end)
+                .and_then(|info| info.timestamp())
+            {
+                Some(time) if time > clock => break,
+                _ => self.end += 1,
+            }
+        }
+        first..self.end
+    }
+
+    fn rebase(&mut self, now: Instant) {
+        self.start = self.clock(now);
+        self.since = now;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::log_storage::storage_from;
+    use chrono::TimeZone;
+    use std::time::Duration;
+
+    #[test]
+    fn test_reveals_lines_as_the_clock_runs() {
+        let (_file, storage) = storage_from(&[
+            "2024-01-01 10:00:00 INFO start",
+            "2024-01-01 10:00:10 ERROR boom",
+            "  at frame",
+            "2024-01-01 10:01:00 INFO recovered",
+        ]);
+        let start = Utc.with_ymd_and_hms(2024, 1, 1, 10, 0, 0).unwrap();
+        let now = Instant::now();
+
+        let mut replay = Replay::new(start, 10, now);
+        assert_eq!(replay.advance(&storage, now), 0..1);
+        // One second at 10x is ten seconds of log; the stack frame comes along
+        assert_eq!(replay.advance(&storage, now + Duration::from_secs(1)), 1..3);
+        assert!(replay.toggle_pause(now + Duration::from_secs(1)));
+        assert_eq!(
+            replay.advance(&storage, now + Duration::from_secs(60)),
+            3..3
+        );
+        assert!(!replay.toggle_pause(now + Duration::from_secs(60)));
+        replay.set_speed(60, now + Duration::from_secs(60));
+        assert_eq!(
+            replay.advance(&storage, now + Duration::from_secs(61)),
+            3..4
+        );
+        assert_eq!(replay.end(), storage.len());
+
+        assert_eq!(Replay::parse_speed("60x"), Some(60));
+        assert_eq!(Replay::parse_speed("2"), Some(2));
+        assert_eq!(Replay::parse_speed("0x"), None);
+        assert_eq!(Replay::parse_speed("fast"), None);
+    }
+}
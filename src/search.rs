@@ -0,0 +1,252 @@
+//! Background search over large or lazily indexed logs.
+//!
+//! Scanning every line of a huge file (or one whose line index is still being
+//! built on demand) can take a long time, so the scan runs on a worker thread
+//! that reports progress and match counts in chunks and can be cancelled.
+//!
+//! A search can refine the one before it (`/&term`): it then only matches
+//! on lines that also contain every earlier term of the stack. A search
+//! starting with `w ` (`/w error`) matches whole words only.
+
+use std::sync::Arc;
+
+use crate::app::MatchPosition;
+use crate::jobs::Job;
+use crate::model::{BMHMatcher, CaseMode};
+use crate::source::LineSource;
+
+/// Number of filtered lines scanned between progress updates and cancel checks.
+const SEARCH_CHUNK_LINES: usize = 10_000;
+
+/// Prefix of a search for whole words only.
+pub const WHOLE_WORD_PREFIX: &str = "w ";
+
+/// Matcher of a search query, treating case as `case` says; it matches
+/// whole words only if `whole_word` is set or the query has the `w `
+/// prefix.
+pub fn query_matcher(query: &str, case: CaseMode, whole_word: bool) -> BMHMatcher {
+    match query.strip_prefix(WHOLE_WORD_PREFIX).map(str::trim_start) {
+        Some(term) if !term.is_empty() => BMHMatcher::for_query(term, case).whole_word(true),
+        _ => BMHMatcher::for_query(query, case).whole_word(whole_word),
+    }
+}
+
+/// Matches of `matcher` in a line; none unless the line also contains every
+/// term in `within` (the searches this one refines). The line is lowercased
+/// for the matchers that ignore case.
+pub fn line_matches(
+    line: &[u8],
+    matcher: &BMHMatcher,
+    within: &[BMHMatcher],
+) -> Vec<(usize, usize)> {
+    let lower = (matcher.ignores_case() || within.iter().any(BMHMatcher::ignores_case))
+        .then(|| line.to_ascii_lowercase());
+    let text = |m: &BMHMatcher| match &lower {
+        Some(lower) if m.ignores_case() => lower.as_slice(),
+        _ => line,
+    };
+    if within.iter().all(|outer| outer.contains(text(outer))) {
+        matcher.find_all(text(matcher))
+    } else {
+        Vec::new()
+    }
+}
+
+/// Count matches in the lines that `indices` refer to.
+///
+/// `first_filtered_idx` is the filtered index of `indices[0]`; returns the
+/// match count and the position of the first match.
+pub fn scan_matches(
+    source: &dyn LineSource,
+    indices: &[usize],
+    first_filtered_idx: usize,
+    matcher: &BMHMatcher,
+    within: &[BMHMatcher],
+) -> (usize, Option<MatchPosition>) {
+    let mut total = 0;
+    let mut first_position = None;
+
+    for (offset, &line_idx) in indices.iter().enumerate() {
+        let Some(line) = source.get_line(line_idx) else {
+            continue;
+        };
+        let matches = line_matches(line.as_bytes(), matcher, within);
+
+        if let (None, Some(&(start, end))) = (first_position, matches.first()) {
+            first_position = Some(MatchPosition {
+                filtered_idx: first_filtered_idx + offset,
+                byte_offset: start,
+                match_len: end - start,
+            });
+        }
+        total += matches.len();
+    }
+
+    (total, first_position)
+}
+
+/// Filtered indices of the lines in `indices` that have a match.
+///
+/// `first_filtered_idx` is the filtered index of `indices[0]`.
+pub fn matching_lines(
+    source: &dyn LineSource,
+    indices: &[usize],
+    first_filtered_idx: usize,
+    matcher: &BMHMatcher,
+    within: &[BMHMatcher],
+) -> Vec<usize> {
+    indices
+        .iter()
+        .enumerate()
+        .filter(|&(_, &line_idx)| {
+            source
+                .get_line(line_idx)
+                .is_some_and(|line| !line_matches(line.as_bytes(), matcher, within).is_empty())
+        })
+        .map(|(offset, _)| first_filtered_idx + offset)
+        .collect()
+}
+
+/// Result of scanning one chunk of lines.
+#[derive(Debug, Clone)]
+pub struct SearchProgress {
+    /// Lines scanned so far (including this chunk)
+    pub scanned: usize,
+    /// Matches found in this chunk
+    pub matches: usize,
+    /// First match in this chunk
+    pub first: Option<MatchPosition>,
+}
+
+/// A search running on a worker thread.
+pub type SearchJob = Job<SearchProgress>;
+
+/// Start scanning the lines in `indices` for `matcher`.
+///
+/// `first_filtered_idx` is the filtered index of `indices[0]`.
+pub fn spawn(
+    name: String,
+    source: Arc<dyn LineSource + Send + Sync>,
+    indices: Vec<usize>,
+    first_filtered_idx: usize,
+    matcher: BMHMatcher,
+    within: Vec<BMHMatcher>,
+) -> SearchJob {
+    let span = tracing::debug_span!("search_job", lines = indices.len());
+    Job::spawn(name, indices.len(), move |ctx| {
+        let _span = span.entered();
+        let mut scanned = 0;
+        let mut found = 0;
+        for chunk in indices.chunks(SEARCH_CHUNK_LINES) {
+            if ctx.is_cancelled() {
+                tracing::debug!(scanned, found, "search cancelled");
+                return;
+            }
+            let (matches, first) = scan_matches(
+                &*source,
+                chunk,
+                first_filtered_idx + scanned,
+                &matcher,
+                &within,
+            );
+            scanned += chunk.len();
+            found += matches;
+            ctx.advance(chunk.len());
+            let progress = SearchProgress {
+                scanned,
+                matches,
+                first,
+            };
+            if !ctx.send(progress) {
+                return;
+            }
+        }
+        tracing::debug!(scanned, found, "search finished");
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::log_storage::storage_from;
+    use crate::model::LogStorage;
+    use tempfile::NamedTempFile;
+
+    fn storage_with_lines(count: usize) -> (NamedTempFile, Arc<LogStorage>) {
+        let lines: Vec<String> = (0..count)
+            .map(|i| {
+                let level = if i % 1000 == 0 { "ERROR" } else { "INFO" };
+                format!("{} line {}", level, i)
+            })
+            .collect();
+        let lines: Vec<&str> = lines.iter().map(String::as_str).collect();
+        let (temp_file, storage) = storage_from(&lines);
+        (temp_file, Arc::new(storage))
+    }
+
+    #[test]
+    fn test_scan_matches_reports_first_position() {
+        let (_file, storage) = storage_with_lines(3);
+        let matcher = BMHMatcher::new(b"info".to_vec());
+
+        let (total, first) = scan_matches(&*storage, &[0, 1, 2], 5, &matcher, &[]);
+
+        assert_eq!(total, 2);
+        let first = first.unwrap();
+        assert_eq!(first.filtered_idx, 6);
+        assert_eq!(first.byte_offset, 0);
+    }
+
+    #[test]
+    fn test_scan_matches_reads_any_source() {
+        let lines = vec!["INFO ok".to_string(), "ERROR failed".to_string()];
+        let matcher = BMHMatcher::new(b"error".to_vec());
+
+        let (total, first) = scan_matches(&lines, &[0, 1], 0, &matcher, &[]);
+
+        assert_eq!(total, 1);
+        assert_eq!(first.unwrap().filtered_idx, 1);
+    }
+
+    #[test]
+    fn test_refined_search_needs_every_term() {
+        let lines = vec![
+            "retry after error".to_string(),
+            "timeout, retry 1".to_string(),
+            "timeout, giving up".to_string(),
+        ];
+        let within = [BMHMatcher::new(b"timeout".to_vec())];
+        let matcher = BMHMatcher::new(b"retry".to_vec());
+
+        let (total, first) = scan_matches(&lines, &[0, 1, 2], 0, &matcher, &within);
+
+        assert_eq!(total, 1);
+        assert_eq!(first.unwrap().filtered_idx, 1);
+    }
+
+    #[test]
+    fn test_job_reports_incremental_matches() {
+        let (_file, storage) = storage_with_lines(25_000);
+        let indices: Vec<usize> = (0..storage.len()).collect();
+        let matcher = BMHMatcher::new(b"error".to_vec());
+        let mut job = spawn(
+            "search".to_string(),
+            storage,
+            indices,
+            0,
+            matcher,
+            Vec::new(),
+        );
+
+        let mut updates = Vec::new();
+        while !job.is_finished() {
+            updates.extend(job.poll());
+            std::thread::sleep(std::time::Duration::from_millis(1));
+        }
+
+        assert_eq!(updates.len(), 3);
+        assert_eq!(updates.iter().map(|u| u.matches).sum::<usize>(), 25);
+        assert_eq!(updates[1].first.unwrap().filtered_idx, 10_000);
+        assert_eq!(job.percent(), 100);
+    }
+}
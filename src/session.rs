@@ -0,0 +1,283 @@
+//! Saved sessions.
+//!
+//! On quit the filters, search, wrap mode, position and bookmarks of the
+//! opened file set are stored in `session.toml`, keyed by the set's paths,
+//! and put back the next time the same files are opened. `:session
+//! save`, `:session load` and `:session clear` do the same by hand. Like
+//! the config file, the session file lives in the project's `./.qlog/` when
+//! that directory exists, and in `~/.qlog/` otherwise.
+//!
+//! Lines are stored as [`LineAnchor`]s, so the position and bookmarks come
+//! back on the right lines even if the files grew or rotated in between.
+
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use chrono::{DateTime, Local};
+use serde::{Deserialize, Serialize};
+
+use crate::model::{FilterKind, FilterRule, LineAnchor};
+
+/// Maximum number of file sets whose session is kept.
+const MAX_SESSIONS: usize = 10;
+
+/// A filter rule as stored in a session.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SavedFilter {
+    pub kind: FilterKind,
+    pub pattern: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub file: Option<String>,
+    #[serde(default = "enabled")]
+    pub enabled: bool,
+}
+
+fn enabled() -> bool {
+    true
+}
+
+impl SavedFilter {
+    pub fn from_rule(rule: &FilterRule) -> Self {
+        Self {
+            kind: rule.kind,
+            pattern: rule.pattern.clone(),
+            file: rule.file.clone(),
+            enabled: rule.enabled,
+        }
+    }
+
+    pub fn to_rule(&self) -> FilterRule {
+        let mut rule =
+            FilterRule::new(self.pattern.clone(), self.kind).with_file(self.file.clone());
+        rule.enabled = self.enabled;
+        rule
+    }
+}
+
+/// A line as stored in a session: its file, content hash and position.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SavedLine {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub path: Option<PathBuf>,
+    /// Content hash in hex; TOML integers cannot hold every u64
+    pub hash: String,
+    /// Line index within the file
+    pub line: usize,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub note: Option<String>,
+}
+
+impl SavedLine {
+    pub fn from_anchor(anchor: &LineAnchor) -> Self {
+        Self {
+            path: anchor.path.clone(),
+            hash: format!("{:016x}", anchor.hash),
+            line: anchor.offset_in_file,
+            note: None,
+        }
+    }
+
+    /// The anchor of the line, or None if the stored hash is malformed.
+    pub fn anchor(&self) -> Option<LineAnchor> {
+        Some(LineAnchor {
+            path: self.path.clone(),
+            hash: u64::from_str_radix(&self.hash, 16).ok()?,
+            offset_in_file: self.line,
+        })
+    }
+}
+
+/// View state of one file set.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Session {
+    /// Canonical paths of the file set, sorted
+    pub paths: Vec<PathBuf>,
+    pub saved_at: DateTime<Local>,
+    #[serde(default)]
+    pub wrap: bool,
+    /// Searches, outermost first; the last one is the current search
+    #[serde(default)]
+    pub search: Vec<String>,
+    /// Rows between the top of the view and the cursor
+    #[serde(default)]
+    pub cursor_row: usize,
+    #[serde(default)]
+    pub cursor: Option<SavedLine>,
+    #[serde(default)]
+    pub filters: Vec<SavedFilter>,
+    #[serde(default)]
+    pub bookmarks: Vec<SavedLine>,
+}
+
+/// The key a file set's session is stored under: its canonical paths, sorted.
+pub fn session_key(paths: &[PathBuf]) -> Vec<PathBuf> {
+    let mut key: Vec<PathBuf> = paths
+        .iter()
+        .map(|p| fs::canonicalize(p).unwrap_or_else(|_| p.clone()))
+        .collect();
+    key.sort();
+    key.dedup();
+    key
+}
+
+/// Saved sessions of the most recently used file sets, newest first.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Sessions {
+    #[serde(default, rename = "session")]
+    sessions: Vec<Session>,
+}
+
+impl Sessions {
+    /// Location of the session file: `./.qlog/session.toml` if the project
+    /// has a `.qlog` directory, otherwise `~/.qlog/session.toml`.
+    pub fn default_path() -> Option<PathBuf> {
+        Self::path_in(Path::new("."), dirs::home_dir())
+    }
+
+    /// Session file for a project in `project` and a home directory `home`.
+    fn path_in(project: &Path, home: Option<PathBuf>) -> Option<PathBuf> {
+        let local = project.join(".qlog");
+        if local.is_dir() {
+            return Some(local.join("session.toml"));
+        }
+        home.map(|home| home.join(".qlog/session.toml"))
+    }
+
+    /// Load the sessions from a specific path. Missing or invalid files yield none.
+    pub fn load_from_path(path: &Path) -> Self {
+        fs::read_to_string(path)
+            .ok()
+            .and_then(|content| toml::from_str(&content).ok())
+            .unwrap_or_default()
+    }
+
+    /// Save the sessions to a specific path, creating parent directories.
+    pub fn save_to_path(&self, path: &Path) -> io::Result<()> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let content =
+            toml::to_string(self).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+        fs::write(path, content)
+    }
+
+    /// Session of the file set with key `paths` (see [`session_key`]).
+    pub fn get(&self, paths: &[PathBuf]) -> Option<&Session> {
+        self.sessions.iter().find(|session| session.paths == paths)
+    }
+
+    /// Store a session, replacing the earlier one of its file set.
+    pub fn put(&mut self, session: Session) {
+        self.remove(&session.paths);
+        self.sessions.insert(0, session);
+        self.sessions.truncate(MAX_SESSIONS);
+    }
+
+    /// Forget the session of a file set. Returns true if there was one.
+    pub fn remove(&mut self, paths: &[PathBuf]) -> bool {
+        let before = self.sessions.len();
+        self.sessions.retain(|session| session.paths != paths);
+        self.sessions.len() != before
+    }
+
+    pub fn len(&self) -> usize {
+        self.sessions.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.sessions.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn session(paths: &[&str]) -> Session {
+        Session {
+            paths: paths.iter().map(PathBuf::from).collect(),
+            saved_at: Local::now(),
+            wrap: false,
+            search: vec!["timeout".to_string(), "retry".to_string()],
+            cursor_row: 5,
+            cursor: Some(SavedLine::from_anchor(&LineAnchor {
+                path: Some(PathBuf::from("/logs/a.log")),
+                hash: u64::MAX,
+                offset_in_file: 42,
+            })),
+            filters: vec![SavedFilter {
+                kind: FilterKind::Exclude,
+                pattern: "healthcheck".to_string(),
+                file: Some("api".to_string()),
+                enabled: false,
+            }],
+            bookmarks: vec![SavedLine {
+                note: Some("root cause".to_string()),
+                ..SavedLine::from_anchor(&LineAnchor {
+                    path: None,
+                    hash: 7,
+                    offset_in_file: 3,
+                })
+            }],
+        }
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join(".qlog/session.toml");
+        let mut sessions = Sessions::default();
+        sessions.put(session(&["/logs/a.log", "/logs/b.log"]));
+        sessions.save_to_path(&path).unwrap();
+
+        let loaded = Sessions::load_from_path(&path);
+        let key = [PathBuf::from("/logs/a.log"), PathBuf::from("/logs/b.log")];
+        let restored = loaded.get(&key).unwrap();
+        assert_eq!(restored.filters, session(&[]).filters);
+        assert_eq!(restored.bookmarks, session(&[]).bookmarks);
+        assert_eq!(
+            restored.cursor.as_ref().and_then(SavedLine::anchor),
+            Some(LineAnchor {
+                path: Some(PathBuf::from("/logs/a.log")),
+                hash: u64::MAX,
+                offset_in_file: 42,
+            })
+        );
+        assert_eq!(restored.search, ["timeout", "retry"]);
+        assert!(loaded.get(&key[..1]).is_none());
+    }
+
+    #[test]
+    fn test_project_session_comes_first() {
+        let project = TempDir::new().unwrap();
+        let home = TempDir::new().unwrap();
+        let home_path = home.path().join(".qlog/session.toml");
+        assert_eq!(
+            Sessions::path_in(project.path(), Some(home.path().to_path_buf())),
+            Some(home_path)
+        );
+
+        fs::create_dir(project.path().join(".qlog")).unwrap();
+        assert_eq!(
+            Sessions::path_in(project.path(), Some(home.path().to_path_buf())),
+            Some(project.path().join(".qlog/session.toml"))
+        );
+        assert_eq!(Sessions::path_in(Path::new("/nonexistent"), None), None);
+    }
+
+    #[test]
+    fn test_put_replaces_and_truncates() {
+        let mut sessions = Sessions::default();
+        for i in 0..(MAX_SESSIONS + 3) {
+            sessions.put(session(&[&format!("/logs/{}.log", i)]));
+        }
+        sessions.put(session(&["/logs/12.log"]));
+        assert_eq!(sessions.len(), MAX_SESSIONS);
+        assert_eq!(sessions.sessions[0].paths, [PathBuf::from("/logs/12.log")]);
+
+        assert!(sessions.remove(&[PathBuf::from("/logs/12.log")]));
+        assert!(!sessions.remove(&[PathBuf::from("/logs/12.log")]));
+    }
+}
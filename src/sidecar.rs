@@ -0,0 +1,448 @@
+//! SQLite sidecar index.
+//!
+//! With `[index] sidecar = true` each fully indexed file gets a database
+//! next to it (`app.log` → `app.log.qlog.db`) holding its line index, the
+//! level detected on each line, and its bookmarks with their notes.
+//! Reopening a file that has not changed since reads the index from the
+//! database instead of scanning the file; a file that only grew (an active
+//! log) keeps its stored index and just the appended part is scanned.
+//! Bookmarks come back in the next session, and `:sql` can query the tables
+//! (see [`crate::query`]):
+//!
+//! - `lines(line, offset, length, timestamp, level)`: `line` starts at 1 as
+//!   in the viewer, `timestamp` is nanoseconds since the Unix epoch (or
+//!   NULL), `level` is `error`, `warn`, `info`, `debug`, `trace` or NULL
+//! - `bookmarks(line, note)`
+
+use std::fs::File;
+use std::io::{Read, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+use std::time::UNIX_EPOCH;
+
+use chrono::DateTime;
+use rusqlite::{params, Connection, OptionalExtension};
+
+use crate::error::QlogError;
+use crate::model::anchor::line_hash;
+use crate::model::{Bookmark, IndexMode, LineInfo, LogLevel, LogStorage, OpenProgress};
+
+/// Bumped whenever the tables change; older databases are rebuilt.
+const SCHEMA_VERSION: i64 = 1;
+
+/// Bytes before the stored end of a file that must be unchanged for the file
+/// to count as grown rather than rewritten.
+const GROWTH_CHECK_BYTES: u64 = 4096;
+
+const SCHEMA: &str = "
+    CREATE TABLE IF NOT EXISTS meta (key TEXT PRIMARY KEY, value INTEGER NOT NULL);
+    CREATE TABLE IF NOT EXISTS lines (
+        line INTEGER PRIMARY KEY,
+        offset INTEGER NOT NULL,
+        length INTEGER NOT NULL,
+        timestamp INTEGER,
+        level TEXT
+    );
+    CREATE TABLE IF NOT EXISTS bookmarks (line INTEGER PRIMARY KEY, note TEXT);
+";
+
+/// Line index read from a sidecar database.
+#[derive(Debug)]
+pub struct StoredIndex {
+    pub lines: Vec<LineInfo>,
+    /// Whether the file grew since the index was stored; the lines written
+    /// after it are not in `lines`
+    pub grown: bool,
+}
+
+/// Sidecar database of one log file.
+#[derive(Debug)]
+pub struct Sidecar {
+    conn: Connection,
+    path: PathBuf,
+}
+
+impl Sidecar {
+    /// Location of the sidecar database of `log`.
+    pub fn path_for(log: &Path) -> PathBuf {
+        let mut name = log.as_os_str().to_os_string();
+        name.push(".qlog.db");
+        PathBuf::from(name)
+    }
+
+    /// Open the sidecar database of `log`, creating it if needed.
+    pub fn open(log: &Path) -> Result<Self, QlogError> {
+        let path = Self::path_for(log);
+        let sidecar = Connection::open(&path)
+            .map(|conn| Self {
+                conn,
+                path: path.clone(),
+            })
+            .map_err(|source| QlogError::Sidecar { path, source })?;
+        sidecar
+            .conn
+            .execute_batch(SCHEMA)
+            .map_err(|e| sidecar.error(e))?;
+        Ok(sidecar)
+    }
+
+    fn error(&self, source: rusqlite::Error) -> QlogError {
+        QlogError::Sidecar {
+            path: self.path.clone(),
+            source,
+        }
+    }
+
+    fn meta(&self, key: &str) -> Result<Option<i64>, QlogError> {
+        self.conn
+            .query_row("SELECT value FROM meta WHERE key = ?1", [key], |row| {
+                row.get(0)
+            })
+            .optional()
+            .map_err(|e| self.error(e))
+    }
+
+    /// Line index stored for `log`, or None if there is none or the file
+    /// changed since it was stored other than by growing.
+    pub fn load_index(&self, log: &Path) -> Result<Option<StoredIndex>, QlogError> {
+        let stored = (
+            self.meta("schema")?,
+            self.meta("size")?,
+            self.meta("modified")?,
+        );
+        let ((Some(SCHEMA_VERSION), Some(size), Some(modified)), Some(current)) =
+            (stored, fingerprint(log))
+        else {
+            return Ok(None);
+        };
+        let grown = if (size, modified) == current {
+            false
+        } else {
+            let stored_tail = self.meta("tail_hash")?;
+            if current.0 <= size || stored_tail.is_none() || stored_tail != tail_hash(log, size) {
+                return Ok(None);
+            }
+            true
+        };
+
+        let mut stmt = self
+            .conn
+            .prepare("SELECT offset, length, timestamp, level FROM lines ORDER BY line")
+            .map_err(|e| self.error(e))?;
+        let lines = stmt
+            .query_map([], |row| {
+                let timestamp: Option<i64> = row.get(2)?;
+                let level: Option<String> = row.get(3)?;
+                Ok(LineInfo::with_timestamp(
+                    0,
+                    row.get(0)?,
+                    row.get(1)?,
+                    timestamp.map(DateTime::from_timestamp_nanos),
+                )
+                .with_level(level.as_deref().and_then(LogLevel::from_keyword)))
+            })
+            .and_then(|rows| rows.collect::<Result<Vec<_>, _>>())
+            .map_err(|e| self.error(e))?;
+        Ok(Some(StoredIndex { lines, grown }))
+    }
+
+    /// Replace the stored line index with that of file `file_idx` of
+    /// `storage`, which must be fully indexed.
+    pub fn save_index(&mut self, storage: &LogStorage, file_idx: usize) -> Result<(), QlogError> {
+        self.save_index_from(storage, file_idx, 0)
+    }
+
+    /// Like [`Sidecar::save_index`], keeping the stored lines before line
+    /// `first` (counted from 0) and writing only the ones from there on.
+    pub fn save_index_from(
+        &mut self,
+        storage: &LogStorage,
+        file_idx: usize,
+        first: usize,
+    ) -> Result<(), QlogError> {
+        let (Some(lines), Some(log)) = (storage.file_lines(file_idx), storage.file_path(file_idx))
+        else {
+            return Ok(());
+        };
+        let Some((size, modified)) = fingerprint(log) else {
+            return Ok(());
+        };
+        let tail = tail_hash(log, size);
+
+        let path = self.path.clone();
+        let error = |source| QlogError::Sidecar {
+            path: path.clone(),
+            source,
+        };
+        let tx = self.conn.transaction().map_err(error)?;
+        tx.execute("DELETE FROM lines WHERE line > ?1", [first as i64])
+            .map_err(error)?;
+        {
+            let mut insert = tx
+                .prepare(
+                    "INSERT INTO lines (line, offset, length, timestamp, level) \
+                     VALUES (?1, ?2, ?3, ?4, ?5)",
+                )
+                .map_err(error)?;
+            for (local, info) in lines.iter().enumerate().skip(first) {
+                let level = info.level().map(LogLevel::name);
+                insert
+                    .execute(params![
+                        local as i64 + 1,
+                        info.offset as i64,
+                        info.length,
+                        info.timestamp_nanos(),
+                        level
+                    ])
+                    .map_err(error)?;
+            }
+        }
+        for (key, value) in [
+            ("schema", Some(SCHEMA_VERSION)),
+            ("size", Some(size)),
+            ("modified", Some(modified)),
+            ("tail_hash", tail),
+        ] {
+            let Some(value) = value else {
+                tx.execute("DELETE FROM meta WHERE key = ?1", [key])
+                    .map_err(error)?;
+                continue;
+            };
+            tx.execute(
+                "INSERT OR REPLACE INTO meta (key, value) VALUES (?1, ?2)",
+                params![key, value],
+            )
+            .map_err(error)?;
+        }
+        tx.commit().map_err(error)
+    }
+
+    /// Stored bookmarks as (line index within the file, bookmark).
+    pub fn load_bookmarks(&self) -> Result<Vec<(usize, Bookmark)>, QlogError> {
+        let mut stmt = self
+            .conn
+            .prepare("SELECT line, note FROM bookmarks WHERE line > 0 ORDER BY line")
+            .map_err(|e| self.error(e))?;
+        stmt.query_map([], |row| {
+            let line: i64 = row.get(0)?;
+            Ok((line as usize - 1, Bookmark { note: row.get(1)? }))
+        })
+        .and_then(|rows| rows.collect())
+        .map_err(|e| self.error(e))
+    }
+
+    /// Replace the stored bookmarks, given as (line index within the file,
+    /// bookmark).
+    pub fn save_bookmarks<'a>(
+        &mut self,
+        marks: impl IntoIterator<Item = (usize, &'a Bookmark)>,
+    ) -> Result<(), QlogError> {
+        let path = self.path.clone();
+        let error = |source| QlogError::Sidecar {
+            path: path.clone(),
+            source,
+        };
+        let tx = self.conn.transaction().map_err(error)?;
+        tx.execute("DELETE FROM bookmarks", []).map_err(error)?;
+        for (local, mark) in marks {
+            tx.execute(
+                "INSERT INTO bookmarks (line, note) VALUES (?1, ?2)",
+                params![local as i64 + 1, mark.note],
+            )
+            .map_err(error)?;
+        }
+        tx.commit().map_err(error)
+    }
+}
+
+/// Size and modification time (nanoseconds since the epoch) of a file.
+fn fingerprint(path: &Path) -> Option<(i64, i64)> {
+    let metadata = std::fs::metadata(path).ok()?;
+    let modified = metadata.modified().ok()?.duration_since(UNIX_EPOCH).ok()?;
+    Some((metadata.len() as i64, modified.as_nanos() as i64))
+}
+
+/// Hash of the last `GROWTH_CHECK_BYTES` of a file before offset `end`.
+fn tail_hash(path: &Path, end: i64) -> Option<i64> {
+    let end = end as u64;
+    let start = end.saturating_sub(GROWTH_CHECK_BYTES);
+    let mut file = File::open(path).ok()?;
+    file.seek(SeekFrom::Start(start)).ok()?;
+    let mut bytes = vec![0; (end - start) as usize];
+    file.read_exact(&mut bytes).ok()?;
+    Some(line_hash(&bytes) as i64)
+}
+
+/// Load a file with a full line index, reading the index from its sidecar
+/// database if it is current and storing it there otherwise. For a file
+/// that grew, the stored index is extended by the appended lines.
+///
+/// Problems with the database are logged and the file is indexed as usual;
+/// only errors opening the log file itself are returned.
+pub fn load_storage(path: &Path) -> Result<LogStorage, QlogError> {
+    load_storage_with_progress(path, &mut |_| {})
+}
+
+/// Like [`load_storage`], reporting progress as
+/// [`LogStorage::from_file_with_progress`] does.
+pub fn load_storage_with_progress(
+    path: &Path,
+    progress: &mut dyn FnMut(OpenProgress),
+) -> Result<LogStorage, QlogError> {
+    let mut sidecar = match Sidecar::open(path) {
+        Ok(sidecar) => sidecar,
+        Err(e) => {
+            tracing::warn!("{}", e);
+            return LogStorage::from_file_with_progress(path, IndexMode::Full, progress);
+        }
+    };
+    match sidecar.load_index(path) {
+        Ok(Some(stored)) => {
+            // The last stored line may have been incomplete; it is indexed again
+            let kept = stored.lines.len().saturating_sub(1);
+            let storage = LogStorage::from_file_indexed(path, stored.lines, progress)?;
+            if stored.grown {
+                if let Err(e) = sidecar.save_index_from(&storage, 0, kept) {
+                    tracing::warn!("{}", e);
+                }
+            }
+            return Ok(storage);
+        }
+        Ok(None) => {}
+        Err(e) => tracing::warn!("{}", e),
+    }
+
+    let storage = LogStorage::from_file_with_progress(path, IndexMode::Full, progress)?;
+    if let Err(e) = sidecar.save_index(&storage, 0) {
+        tracing::warn!("{}", e);
+    }
+    Ok(storage)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+    use tempfile::TempDir;
+
+    fn write_log(dir: &TempDir, text: &str) -> PathBuf {
+        let path = dir.path().join("app.log");
+        std::fs::File::create(&path)
+            .unwrap()
+            .write_all(text.as_bytes())
+            .unwrap();
+        path
+    }
+
+    #[test]
+    fn test_index_is_reused_until_file_changes() {
+        let dir = TempDir::new().unwrap();
+        let log = write_log(
+            &dir,
+            "2024-01-01 10:00:00 INFO start\n2024-01-01 10:00:01 ERROR failed\n",
+        );
+
+        let first = load_storage(&log).unwrap();
+        assert!(Sidecar::path_for(&log).exists());
+        let sidecar = Sidecar::open(&log).unwrap();
+        let stored = sidecar.load_index(&log).unwrap().unwrap();
+        assert!(!stored.grown);
+        let lines = stored.lines;
+        assert_eq!(lines.len(), 2);
+        assert_eq!(lines[1].offset, first.get_line_info(1).unwrap().offset);
+        assert_eq!(
+            lines[1].timestamp(),
+            first.get_line_info(1).unwrap().timestamp()
+        );
+
+        let reopened = load_storage(&log).unwrap();
+        assert_eq!(
+            reopened.get_line(1).unwrap().as_bytes(),
+            b"2024-01-01 10:00:01 ERROR failed"
+        );
+
+        std::fs::OpenOptions::new()
+            .append(true)
+            .open(&log)
+            .unwrap()
+            .write_all(b"2024-01-01 10:00:02 INFO again\n")
+            .unwrap();
+        assert_eq!(load_storage(&log).unwrap().len(), 3);
+
+        // Rewritten rather than grown: the stored index is not used
+        write_log(&dir, "replaced\n");
+        assert!(sidecar.load_index(&log).unwrap().is_none());
+        assert_eq!(load_storage(&log).unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_grown_file_extends_stored_index() {
+        let dir = TempDir::new().unwrap();
+        let log = write_log(
+            &dir,
+            "2024-01-01 10:00:00 INFO start\n2024-01-01 10:00:01 ERR",
+        );
+        assert_eq!(load_storage(&log).unwrap().len(), 2);
+
+        std::fs::OpenOptions::new()
+            .append(true)
+            .open(&log)
+            .unwrap()
+            .write_all(b"OR failed\n2024-01-01 10:00:02 INFO again\n")
+            .unwrap();
+        let sidecar = Sidecar::open(&log).unwrap();
+        assert!(sidecar.load_index(&log).unwrap().unwrap().grown);
+
+        let storage = load_storage(&log).unwrap();
+        assert_eq!(storage.len(), 3);
+        assert_eq!(
+            storage.get_line(1).unwrap().as_bytes(),
+            b"2024-01-01 10:00:01 ERROR failed"
+        );
+        let stored = sidecar.load_index(&log).unwrap().unwrap();
+        assert!(!stored.grown);
+        assert_eq!(stored.lines.len(), 3);
+        assert_eq!(
+            stored.lines[2].offset,
+            storage.get_line_info(2).unwrap().offset
+        );
+    }
+
+    #[test]
+    fn test_bookmarks_round_trip() {
+        let dir = TempDir::new().unwrap();
+        let log = write_log(&dir, "a\nb\nc\n");
+        let mut sidecar = Sidecar::open(&log).unwrap();
+        let marks = [
+            Bookmark::default(),
+            Bookmark {
+                note: Some("root cause".to_string()),
+            },
+        ];
+
+        sidecar
+            .save_bookmarks([(0, &marks[0]), (2, &marks[1])])
+            .unwrap();
+
+        assert_eq!(
+            sidecar.load_bookmarks().unwrap(),
+            vec![(0, marks[0].clone()), (2, marks[1].clone())]
+        );
+    }
+
+    #[test]
+    fn test_tables_can_be_queried() {
+        let dir = TempDir::new().unwrap();
+        let log = write_log(&dir, "INFO start\nERROR failed\nERROR again\n");
+        let storage = load_storage(&log).unwrap();
+
+        let result = crate::query::run(
+            &storage,
+            &[],
+            Some(&Sidecar::path_for(&log)),
+            "SELECT line, level FROM lines WHERE level = 'error'",
+        )
+        .unwrap();
+        assert_eq!(result.columns, vec!["line", "level"]);
+        assert_eq!(result.rows, vec![vec!["2", "error"], vec!["3", "error"]]);
+    }
+}
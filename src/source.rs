@@ -0,0 +1,221 @@
+//! Line sources the viewer can show besides log files.
+//!
+//! A [`LineSource`] is anything that can hand out lines by index: an
+//! in-memory buffer, lines received over a channel, or rows read from a
+//! database. [`crate::run_with_source`] shows one in the viewer; lines the
+//! source gains while it runs are appended like followed lines.
+//!
+//! Piped input (`kubectl logs -f ... | qlog`) cannot be memory-mapped; the
+//! `qlog` binary reads it through [`ChannelSource::from_reader`] instead.
+
+use std::io::{BufRead, BufReader, Read};
+use std::sync::mpsc::{channel, Receiver};
+use std::thread;
+
+use chrono::{DateTime, Utc};
+
+use crate::model::{detect_level, detect_timestamp, LogLevel, LogStorage, MmapStr};
+
+/// Lines to show in the viewer, addressed by index.
+pub trait LineSource {
+    /// Number of lines available.
+    fn len(&self) -> usize;
+
+    /// Whether the source has no lines.
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Text of line `idx`, without the line terminator.
+    fn get_line(&self, idx: usize) -> Option<MmapStr<'_>>;
+
+    /// Bytes of line `idx` as stored, including its terminator if it has
+    /// one; None for sources that keep only the text.
+    fn raw_line(&self, _idx: usize) -> Option<&[u8]> {
+        None
+    }
+
+    /// Timestamp of line `idx`; detected from the text unless overridden.
+    fn get_timestamp(&self, idx: usize) -> Option<DateTime<Utc>> {
+        detect_timestamp(&self.get_line(idx)?.as_str_lossy())
+    }
+
+    /// Level of line `idx`; detected from the text unless overridden.
+    fn line_level(&self, idx: usize) -> Option<LogLevel> {
+        detect_level(&self.get_line(idx)?.as_str_lossy())
+    }
+
+    /// All lines in order. (Not `iter`, which would shadow `Vec::iter`
+    /// wherever the trait is in scope.)
+    fn lines(&self) -> Box<dyn Iterator<Item = MmapStr<'_>> + '_> {
+        Box::new((0..self.len()).filter_map(|idx| self.get_line(idx)))
+    }
+
+    /// Pick up lines that arrived since the last call. The viewer calls
+    /// this once per frame; sources that only grow through `&mut self`
+    /// (channels, sockets) receive here.
+    fn refresh(&mut self) {}
+
+    /// Let go of the lines before `idx`: the viewer has taken them in and
+    /// does not read them again. Sources that buffer what they receive
+    /// free those lines here; `len` still counts them.
+    fn release(&mut self, _idx: usize) {}
+}
+
+impl LineSource for LogStorage {
+    fn len(&self) -> usize {
+        LogStorage::len(self)
+    }
+
+    fn get_line(&self, idx: usize) -> Option<MmapStr<'_>> {
+        LogStorage::get_line(self, idx)
+    }
+
+    fn raw_line(&self, idx: usize) -> Option<&[u8]> {
+        LogStorage::raw_line(self, idx)
+    }
+
+    fn get_timestamp(&self, idx: usize) -> Option<DateTime<Utc>> {
+        self.get_line_info(idx)?.timestamp()
+    }
+
+    fn line_level(&self, idx: usize) -> Option<LogLevel> {
+        LogStorage::line_level(self, idx)
+    }
+
+    fn lines(&self) -> Box<dyn Iterator<Item = MmapStr<'_>> + '_> {
+        Box::new(LogStorage::iter(self))
+    }
+}
+
+impl LineSource for Vec<String> {
+    fn len(&self) -> usize {
+        self.as_slice().len()
+    }
+
+    fn get_line(&self, idx: usize) -> Option<MmapStr<'_>> {
+        self.get(idx).map(|line| MmapStr::new(line.as_bytes()))
+    }
+}
+
+/// Lines sent over a channel, one message per line.
+///
+/// Received lines are buffered until the viewer releases them, so piped
+/// input is not kept twice.
+#[derive(Debug)]
+pub struct ChannelSource {
+    rx: Receiver<String>,
+    /// Lines received and not released yet, from line `released` on
+    lines: Vec<String>,
+    released: usize,
+}
+
+impl ChannelSource {
+    pub fn new(rx: Receiver<String>) -> Self {
+        Self {
+            rx,
+            lines: Vec::new(),
+            released: 0,
+        }
+    }
+
+    /// Read lines from `reader` (standard input, a named pipe) on a
+    /// background thread until it ends. Invalid UTF-8 is replaced.
+    pub fn from_reader(reader: impl Read + Send + 'static) -> Self {
+        let (tx, rx) = channel();
+        thread::spawn(move || {
+            let mut reader = BufReader::new(reader);
+            let mut buf = Vec::new();
+            loop {
+                buf.clear();
+                match reader.read_until(b'\n', &mut buf) {
+                    Ok(0) | Err(_) => break,
+                    Ok(_) => {}
+                }
+                let line = buf.strip_suffix(b"\n").unwrap_or(&buf);
+                let line = line.strip_suffix(b"\r").unwrap_or(line);
+                if tx.send(String::from_utf8_lossy(line).into_owned()).is_err() {
+                    break;
+                }
+            }
+        });
+        Self::new(rx)
+    }
+}
+
+impl LineSource for ChannelSource {
+    fn len(&self) -> usize {
+        self.released + self.lines.len()
+    }
+
+    fn get_line(&self, idx: usize) -> Option<MmapStr<'_>> {
+        self.lines
+            .get(idx.checked_sub(self.released)?)
+            .map(|line| MmapStr::new(line.as_bytes()))
+    }
+
+    fn refresh(&mut self) {
+        self.lines.extend(self.rx.try_iter());
+    }
+
+    fn release(&mut self, idx: usize) {
+        let count = idx.saturating_sub(self.released).min(self.lines.len());
+        self.lines.drain(..count);
+        self.released += count;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::mpsc::channel;
+
+    #[test]
+    fn test_vec_source() {
+        let lines = vec![
+            "2024-01-01 10:00:00 INFO start".to_string(),
+            "  detail".to_string(),
+        ];
+        assert_eq!(LineSource::len(&lines), 2);
+        assert_eq!(lines.get_line(1).unwrap().as_bytes(), b"  detail");
+        assert!(lines.get_timestamp(0).is_some());
+        assert_eq!(lines.get_timestamp(1), None);
+        assert_eq!(lines.lines().count(), 2);
+    }
+
+    #[test]
+    fn test_channel_source_receives_on_refresh() {
+        let (tx, rx) = channel();
+        let mut source = ChannelSource::new(rx);
+        tx.send("one".to_string()).unwrap();
+        tx.send("two".to_string()).unwrap();
+        assert!(source.is_empty());
+
+        source.refresh();
+        assert_eq!(source.len(), 2);
+        assert_eq!(source.get_line(1).unwrap().as_bytes(), b"two");
+
+        // Released lines are gone but still counted
+        source.release(1);
+        tx.send("three".to_string()).unwrap();
+        source.refresh();
+        assert_eq!(source.len(), 3);
+        assert!(source.get_line(0).is_none());
+        assert_eq!(source.get_line(2).unwrap().as_bytes(), b"three");
+    }
+
+    #[test]
+    fn test_reader_source_splits_lines() {
+        let input: &[u8] = b"one\r\ntw\xffo\nlast";
+        let mut source = ChannelSource::from_reader(input);
+        let deadline = std::time::Instant::now() + std::time::Duration::from_secs(5);
+        while source.len() < 3 && std::time::Instant::now() < deadline {
+            source.refresh();
+        }
+        let lines: Vec<String> = source
+            .lines()
+            .map(|line| line.as_str_lossy().into_owned())
+            .collect();
+        assert_eq!(lines, vec!["one", "tw\u{fffd}o", "last"]);
+    }
+}
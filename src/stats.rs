@@ -0,0 +1,338 @@
+//! Statistics of the lines in view, shown by `:stats`.
+//!
+//! Every row of the overlay leads into the logs: a level count limits the
+//! view to that level, a top `SourceContext` adds an include filter for it,
+//! and a histogram bucket limits the view to the bucket's time range.
+
+use std::collections::HashMap;
+
+use chrono::{DateTime, Utc};
+use rayon::prelude::*;
+
+use crate::detail::parse_json;
+use crate::jobs::JobContext;
+use crate::model::{LevelSet, LogLevel, LogStorage};
+use crate::source::LineSource;
+
+/// Number of time buckets in the histogram.
+pub const HISTOGRAM_BUCKETS: usize = 12;
+
+/// Number of most common `SourceContext` values listed.
+const TOP_CONTEXTS: usize = 5;
+
+/// JSON field naming the logger (Serilog and other .NET loggers).
+const CONTEXT_FIELD: &str = "SourceContext";
+
+/// Lines tallied by a job between checks for cancellation.
+const STATS_CHUNK_LINES: usize = 10_000;
+
+/// One row of the stats overlay.
+#[derive(Debug, Clone, PartialEq)]
+pub enum StatsRow {
+    Level {
+        level: LogLevel,
+        count: usize,
+    },
+    Context {
+        name: String,
+        count: usize,
+    },
+    /// Lines with a timestamp in `[from, to)`
+    Bucket {
+        from: DateTime<Utc>,
+        to: DateTime<Utc>,
+        count: usize,
+    },
+}
+
+impl StatsRow {
+    pub fn count(&self) -> usize {
+        match self {
+            StatsRow::Level { count, .. }
+            | StatsRow::Context { count, .. }
+            | StatsRow::Bucket { count, .. } => *count,
+        }
+    }
+
+    /// Include filter pattern of a context row: `"SourceContext":"name"`,
+    /// as compact JSON loggers write the field.
+    pub fn context_filter(&self) -> Option<String> {
+        match self {
+            StatsRow::Context { name, .. } => Some(format!(
+                "\"{}\":{}",
+                CONTEXT_FIELD,
+                serde_json::to_string(name).ok()?
+            )),
+            _ => None,
+        }
+    }
+}
+
+/// Per-thread tallies, merged after the parallel pass.
+#[derive(Debug, Default)]
+struct Tally {
+    levels: [usize; LogLevel::ALL.len()],
+    contexts: HashMap<String, usize>,
+}
+
+impl Tally {
+    fn of(source: &(dyn LineSource + Sync), indices: &[usize]) -> Tally {
+        indices
+            .par_iter()
+            .fold(Tally::default, |mut tally, &idx| {
+                if let Some(level) = source.line_level(idx) {
+                    tally.levels[level as usize] += 1;
+                }
+                let Some(line) = source.get_line(idx) else {
+                    return tally;
+                };
+                if let Some(name) = source_context(&line.as_str_lossy()) {
+                    *tally.contexts.entry(name).or_default() += 1;
+                }
+                tally
+            })
+            .reduce(Tally::default, Tally::merge)
+    }
+
+    fn merge(mut self, other: Tally) -> Tally {
+        for (count, other) in self.levels.iter_mut().zip(other.levels) {
+            *count += other;
+        }
+        for (name, count) in other.contexts {
+            *self.contexts.entry(name).or_default() += count;
+        }
+        self
+    }
+}
+
+/// Level counts, top contexts and time histogram of the lines `indices`
+/// refer to. Levels and buckets without lines are left out.
+pub fn compute(source: &(dyn LineSource + Sync), indices: &[usize]) -> Vec<StatsRow> {
+    rows(source, indices, Tally::of(source, indices))
+}
+
+/// [`compute`] on the thread of a job, in chunks so it reports progress;
+/// None once the job is cancelled.
+pub fn compute_in_job<U>(
+    source: &(dyn LineSource + Sync),
+    indices: &[usize],
+    ctx: &JobContext<U>,
+) -> Option<Vec<StatsRow>> {
+    let mut tally = Tally::default();
+    for chunk in indices.chunks(STATS_CHUNK_LINES) {
+        if ctx.is_cancelled() {
+            return None;
+        }
+        tally = tally.merge(Tally::of(source, chunk));
+        ctx.advance(chunk.len());
+    }
+    Some(rows(source, indices, tally))
+}
+
+fn rows(source: &dyn LineSource, indices: &[usize], tally: Tally) -> Vec<StatsRow> {
+    let mut rows: Vec<StatsRow> = LogLevel::ALL
+        .iter()
+        .zip(tally.levels)
+        .filter(|(_, count)| *count > 0)
+        .map(|(&level, count)| StatsRow::Level { level, count })
+        .collect();
+
+    let mut contexts: Vec<(String, usize)> = tally.contexts.into_iter().collect();
+    contexts.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+    rows.extend(
+        contexts
+            .into_iter()
+            .take(TOP_CONTEXTS)
+            .map(|(name, count)| StatsRow::Context { name, count }),
+    );
+
+    rows.extend(histogram(source, indices));
+    rows
+}
+
+/// `SourceContext` of a JSON line, parsing only lines that mention it.
+fn source_context(text: &str) -> Option<String> {
+    if !text.contains(CONTEXT_FIELD) {
+        return None;
+    }
+    let (_, json) = parse_json(text)?;
+    Some(json.get(CONTEXT_FIELD)?.as_str()?.to_string())
+}
+
+/// Lines per time bucket, splitting the span of the timestamps evenly.
+fn histogram(source: &dyn LineSource, indices: &[usize]) -> Vec<StatsRow> {
+    let times: Vec<i64> = indices
+        .iter()
+        .filter_map(|&idx| source.get_timestamp(idx)?.timestamp_nanos_opt())
+        .collect();
+    let (Some(&first), Some(&last)) = (times.iter().min(), times.iter().max()) else {
+        return Vec::new();
+    };
+
+    // Bucket width rounded up so the newest line falls in the last bucket
+    let width = (last - first) / HISTOGRAM_BUCKETS as i64 + 1;
+    let mut counts = [0usize; HISTOGRAM_BUCKETS];
+    for time in times {
+        counts[((time - first) / width) as usize] += 1;
+    }
+    counts
+        .iter()
+        .enumerate()
+        .filter(|(_, &count)| count > 0)
+        .map(|(bucket, &count)| {
+            let from = first + bucket as i64 * width;
+            StatsRow::Bucket {
+                from: DateTime::from_timestamp_nanos(from),
+                to: DateTime::from_timestamp_nanos(from + width),
+                count,
+            }
+        })
+        .collect()
+}
+
+/// Limits the stats overlay puts on the view, on top of the filters.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct DrillDown {
+    /// Levels shown; lines without a level are hidden while this is set
+    pub levels: Option<LevelSet>,
+    /// Time range `[from, to)`
+    pub time: Option<(DateTime<Utc>, DateTime<Utc>)>,
+}
+
+impl DrillDown {
+    pub fn is_empty(&self) -> bool {
+        self.levels.is_none() && self.time.is_none()
+    }
+
+    /// Check whether line `idx` stays in view. Lines are checked in order;
+    /// `entry_time` carries the last timestamp seen, so continuation lines
+    /// count as part of the entry above them.
+    pub fn admits(
+        &self,
+        storage: &LogStorage,
+        idx: usize,
+        entry_time: &mut Option<DateTime<Utc>>,
+    ) -> bool {
+        if let Some((from, to)) = self.time {
+            if let Some(time) = storage.get_line_info(idx).and_then(|info| info.timestamp()) {
+                *entry_time = Some(time);
+            }
+            if !entry_time.is_some_and(|time| from <= time && time < to) {
+                return false;
+            }
+        }
+        match self.levels {
+            Some(levels) => storage
+                .line_level(idx)
+                .is_some_and(|level| levels.contains(level)),
+            None => true,
+        }
+    }
+
+    /// Shown in the filter bar, e.g. `level warn+, 10:00:00..10:05:00`.
+    pub fn label(&self) -> String {
+        let mut parts = Vec::new();
+        if let Some(levels) = self.levels {
+            parts.push(format!("level {}", levels.label()));
+        }
+        if let Some((from, to)) = self.time {
+            parts.push(time_range_label(from, to));
+        }
+        parts.join(", ")
+    }
+}
+
+/// `10:00:00..10:05:00`, with dates only when the range crosses midnight.
+pub fn time_range_label(from: DateTime<Utc>, to: DateTime<Utc>) -> String {
+    let format = if from.date_naive() == to.date_naive() {
+        "%H:%M:%S"
+    } else {
+        "%Y-%m-%d %H:%M:%S"
+    };
+    format!("{}..{}", from.format(format), to.format(format))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::log_storage::storage_from;
+
+    #[test]
+    fn test_compute_rows() {
+        let (_file, storage) = storage_from(&[
+            r#"2024-01-01 10:00:00 INFO {"SourceContext":"Api.Orders"}"#,
+            r#"2024-01-01 10:00:30 ERROR {"SourceContext":"Api.Orders"}"#,
+            r#"2024-01-01 10:12:00 ERROR {"SourceContext":"Db.Pool"}"#,
+            "    at Db.Pool.Open()",
+        ]);
+
+        let rows = compute(&storage, &[0, 1, 2, 3]);
+
+        assert_eq!(
+            rows[..4],
+            [
+                StatsRow::Level {
+                    level: LogLevel::Error,
+                    count: 2
+                },
+                StatsRow::Level {
+                    level: LogLevel::Info,
+                    count: 1
+                },
+                StatsRow::Context {
+                    name: "Api.Orders".to_string(),
+                    count: 2
+                },
+                StatsRow::Context {
+                    name: "Db.Pool".to_string(),
+                    count: 1
+                },
+            ]
+        );
+        assert_eq!(
+            rows[2].context_filter().as_deref(),
+            Some(r#""SourceContext":"Api.Orders""#)
+        );
+        let buckets: Vec<usize> = rows[4..].iter().map(StatsRow::count).collect();
+        assert_eq!(buckets, vec![2, 1]);
+    }
+
+    #[test]
+    fn test_compute_over_any_source() {
+        let lines = [
+            r#"2024-01-01 10:00:00 INFO {"SourceContext":"Api.Orders"}"#,
+            "2024-01-01 10:12:00 ERROR failed",
+            "    at Db.Pool.Open()",
+        ];
+        let (_file, storage) = storage_from(&lines);
+        let source: Vec<String> = lines.iter().map(|line| line.to_string()).collect();
+
+        assert_eq!(compute(&source, &[0, 1, 2]), compute(&storage, &[0, 1, 2]));
+    }
+
+    #[test]
+    fn test_drill_down_keeps_continuation_lines() {
+        let (_file, storage) = storage_from(&[
+            "2024-01-01 10:00:00 INFO start",
+            "2024-01-01 10:05:00 ERROR failed",
+            "    at Foo.Bar()",
+            "2024-01-01 10:10:00 ERROR again",
+        ]);
+        let at = |text: &str| {
+            chrono::NaiveDateTime::parse_from_str(text, "%Y-%m-%d %H:%M:%S")
+                .unwrap()
+                .and_utc()
+        };
+        let drill = DrillDown {
+            levels: None,
+            time: Some((at("2024-01-01 10:05:00"), at("2024-01-01 10:10:00"))),
+        };
+
+        let mut entry_time = None;
+        let kept: Vec<usize> = (0..4)
+            .filter(|&idx| drill.admits(&storage, idx, &mut entry_time))
+            .collect();
+        assert_eq!(kept, vec![1, 2]);
+        assert_eq!(drill.label(), "10:05:00..10:10:00");
+    }
+}
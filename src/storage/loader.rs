@@ -1,6 +1,7 @@
 use std::path::Path;
 use std::sync::{Arc, Mutex};
 
+use crate::error::QlogError;
 use crate::model::LogStorage;
 
 /// Statistics about the loading process.
@@ -33,7 +34,7 @@ impl LogLoader {
     pub fn load_logs<P: AsRef<Path>>(
         &self,
         paths: &[P],
-    ) -> Result<(Vec<LogStorage>, LoadStat), Box<dyn std::error::Error>> {
+    ) -> Result<(Vec<LogStorage>, LoadStat), QlogError> {
         let mut storages = Vec::new();
         let mut total_lines = 0;
 
@@ -71,7 +72,7 @@ impl LogLoader {
     }
 
     /// Load a single file into a LogStorage.
-    fn load_file<P: AsRef<Path>>(&self, path: P) -> Result<LogStorage, Box<dyn std::error::Error>> {
+    fn load_file<P: AsRef<Path>>(&self, path: P) -> Result<LogStorage, QlogError> {
         LogStorage::from_file(path)
     }
 
@@ -100,14 +101,14 @@ pub fn create_loader<P: AsRef<Path>>(paths: &[P]) -> LogLoader {
 }
 
 /// Load logs from a single file.
-pub fn load_single_file<P: AsRef<Path>>(path: P) -> Result<LogStorage, Box<dyn std::error::Error>> {
+pub fn load_single_file<P: AsRef<Path>>(path: P) -> Result<LogStorage, QlogError> {
     LogStorage::from_file(path)
 }
 
 /// Load logs from multiple files.
 pub fn load_multiple_files<P: AsRef<Path>>(
     paths: &[P],
-) -> Result<(Vec<LogStorage>, LoadStat), Box<dyn std::error::Error>> {
+) -> Result<(Vec<LogStorage>, LoadStat), QlogError> {
     let loader = create_loader(paths);
     loader.load_logs(paths)
 }
@@ -0,0 +1,250 @@
+//! Summary of a freshly loaded file set.
+//!
+//! Shown in a popup when loading completes so users immediately see what
+//! they opened: how many lines, which formats, the time span covered and
+//! how many errors and warnings it contains.
+
+use std::collections::HashMap;
+
+use chrono::{DateTime, Duration, Utc};
+use rayon::prelude::*;
+
+use crate::model::{detect_format, detect_level, LogFormat, LogLevel, LogStorage};
+
+/// Lines sampled from the start of each file to recognize its format.
+const FORMAT_SAMPLE_LINES: usize = 50;
+
+/// Lines searched from each end of a file for its first and last timestamp.
+const TIMESTAMP_SCAN_LINES: usize = 1000;
+
+/// Error and warning line counts.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct LevelCounts {
+    pub errors: usize,
+    pub warnings: usize,
+}
+
+/// What a load produced.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LoadSummary {
+    pub files_loaded: usize,
+    pub files_failed: usize,
+    /// Files loaded with a tail-only or on-demand index
+    pub files_partial: usize,
+    pub total_lines: usize,
+    /// Detected formats with the number of files in each, most common first
+    pub formats: Vec<(LogFormat, usize)>,
+    /// Oldest and newest timestamp
+    pub time_span: Option<(DateTime<Utc>, DateTime<Utc>)>,
+    /// `None` when some lines are not indexed yet, so counting would force a full index
+    pub levels: Option<LevelCounts>,
+}
+
+impl LoadSummary {
+    /// Summarize `storage` after a load that opened `files_loaded` files.
+    pub fn new(
+        storage: &LogStorage,
+        files_loaded: usize,
+        files_failed: usize,
+        files_partial: usize,
+    ) -> Self {
+        let files = 0..storage.file_count();
+
+        let mut format_counts: HashMap<LogFormat, usize> = HashMap::new();
+        for file in files.clone() {
+            *format_counts
+                .entry(file_format(storage, file_indices(storage, file)))
+                .or_default() += 1;
+        }
+        let mut formats: Vec<(LogFormat, usize)> = format_counts.into_iter().collect();
+        formats.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(&b.0)));
+
+        let time_span = files
+            .filter_map(|file| file_time_span(storage, file_indices(storage, file)))
+            .reduce(|(first, last), (f, l)| (first.min(f), last.max(l)));
+
+        let levels = (!storage.has_unindexed_blocks()).then(|| count_levels(storage));
+
+        Self {
+            files_loaded,
+            files_failed,
+            files_partial,
+            total_lines: storage.len(),
+            formats,
+            time_span,
+            levels,
+        }
+    }
+
+    /// Time covered by the logs.
+    pub fn duration(&self) -> Option<Duration> {
+        self.time_span.map(|(first, last)| last - first)
+    }
+
+    /// Format names with file counts, e.g. `ISO 8601 (2), JSON (1)`.
+    pub fn formats_label(&self) -> String {
+        self.formats
+            .iter()
+            .map(|(format, count)| format!("{} ({})", format.name(), count))
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+
+    /// One-line version for the status bar.
+    pub fn status_line(&self) -> String {
+        let mut line = format!(
+            "Loaded {} lines from {} file{}",
+            self.total_lines,
+            self.files_loaded,
+            if self.files_loaded == 1 { "" } else { "s" }
+        );
+        if self.files_failed > 0 {
+            line.push_str(&format!(" ({} failed)", self.files_failed));
+        }
+        if self.files_partial > 0 {
+            line.push_str(&format!(", {} in tail/on-demand mode", self.files_partial));
+        }
+        if let Some(duration) = self.duration() {
+            line.push_str(&format!(", spanning {}", format_duration(duration)));
+        }
+        if let Some(levels) = self.levels {
+            line.push_str(&format!(
+                ", {} errors, {} warnings",
+                levels.errors, levels.warnings
+            ));
+        }
+        line
+    }
+}
+
+/// Human-readable duration, e.g. `2h 05m`, `3m 12s` or `1.250s`.
+pub fn format_duration(duration: Duration) -> String {
+    let negative = duration < Duration::zero();
+    let duration = if negative { -duration } else { duration };
+    let sign = if negative { "-" } else { "" };
+
+    let total_secs = duration.num_seconds();
+    let (days, hours, mins, secs) = (
+        total_secs / 86_400,
+        total_secs / 3600 % 24,
+        total_secs / 60 % 60,
+        total_secs % 60,
+    );
+    if days > 0 {
+        format!("{}{}d {:02}h", sign, days, hours)
+    } else if hours > 0 {
+        format!("{}{}h {:02}m", sign, hours, mins)
+    } else if mins > 0 {
+        format!("{}{}m {:02}s", sign, mins, secs)
+    } else {
+        format!(
+            "{}{:.3}s",
+            sign,
+            duration.num_milliseconds() as f64 / 1000.0
+        )
+    }
+}
+
+/// Global indices of a file's lines, in file order.
+fn file_indices(
+    storage: &LogStorage,
+    file: usize,
+) -> impl DoubleEndedIterator<Item = usize> + Clone + '_ {
+    let len = storage.file_len(file).unwrap_or(0);
+    (0..len).filter_map(move |local| storage.file_line_index(file, local))
+}
+
+/// Most common format among the first lines of a file.
+///
+/// Continuation lines (stack traces, wrapped messages) look like plain
+/// text, so plain only wins when no line has a recognizable format.
+fn file_format(storage: &LogStorage, lines: impl Iterator<Item = usize>) -> LogFormat {
+    let mut counts: HashMap<LogFormat, usize> = HashMap::new();
+    for idx in lines.take(FORMAT_SAMPLE_LINES) {
+        if let Some(line) = storage.get_line(idx) {
+            *counts
+                .entry(detect_format(&line.as_str_lossy()))
+                .or_default() += 1;
+        }
+    }
+    counts
+        .into_iter()
+        .filter(|(format, _)| *format != LogFormat::Plain)
+        .max_by(|a, b| a.1.cmp(&b.1).then(b.0.cmp(&a.0)))
+        .map(|(format, _)| format)
+        .unwrap_or(LogFormat::Plain)
+}
+
+/// First and last timestamp of a file, looking only near its ends.
+fn file_time_span(
+    storage: &LogStorage,
+    lines: impl DoubleEndedIterator<Item = usize> + Clone,
+) -> Option<(DateTime<Utc>, DateTime<Utc>)> {
+    let timestamp = |idx: usize| storage.get_line_info(idx)?.timestamp();
+    let first = lines
+        .clone()
+        .take(TIMESTAMP_SCAN_LINES)
+        .find_map(timestamp)?;
+    let last = lines.rev().take(TIMESTAMP_SCAN_LINES).find_map(timestamp)?;
+    Some((first.min(last), first.max(last)))
+}
+
+fn count_levels(storage: &LogStorage) -> LevelCounts {
+    (0..storage.len())
+        .into_par_iter()
+        .fold(LevelCounts::default, |mut counts, idx| {
+            if let Some(line) = storage.get_line(idx) {
+                match detect_level(&line.as_str_lossy()) {
+                    Some(LogLevel::Error) => counts.errors += 1,
+                    Some(LogLevel::Warn) => counts.warnings += 1,
+                    _ => {}
+                }
+            }
+            counts
+        })
+        .reduce(LevelCounts::default, |a, b| LevelCounts {
+            errors: a.errors + b.errors,
+            warnings: a.warnings + b.warnings,
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::log_storage::storage_from;
+
+    #[test]
+    fn test_summary_counts_levels_and_span() {
+        let (_file, storage) = storage_from(&[
+            "2024-01-01 10:00:00 INFO start",
+            "2024-01-01 10:30:00 WARN slow",
+            "    at com.example.Foo",
+            "2024-01-01 12:05:00 ERROR failed",
+        ]);
+
+        let summary = LoadSummary::new(&storage, 1, 0, 0);
+
+        assert_eq!(summary.total_lines, 4);
+        assert_eq!(summary.formats, vec![(LogFormat::DateTime, 1)]);
+        assert_eq!(
+            summary.levels,
+            Some(LevelCounts {
+                errors: 1,
+                warnings: 1
+            })
+        );
+        assert_eq!(format_duration(summary.duration().unwrap()), "2h 05m");
+        assert_eq!(
+            summary.status_line(),
+            "Loaded 4 lines from 1 file, spanning 2h 05m, 1 errors, 1 warnings"
+        );
+    }
+
+    #[test]
+    fn test_format_duration() {
+        assert_eq!(format_duration(Duration::milliseconds(1250)), "1.250s");
+        assert_eq!(format_duration(Duration::seconds(192)), "3m 12s");
+        assert_eq!(format_duration(Duration::hours(50)), "2d 02h");
+        assert_eq!(format_duration(Duration::seconds(-90)), "-1m 30s");
+    }
+}
@@ -0,0 +1,493 @@
+//! Color themes.
+//!
+//! A theme decides how log levels are drawn when no `[colors]` pattern from
+//! the config matches a line, and the colors of the UI chrome around them:
+//! borders, status bar, selection and scrollbars. The accessible themes do
+//! not rely on hue alone: error, warn and info lines also differ in
+//! brightness and weight, and the level symbol gutter is on by default.
+//!
+//! Besides the built-in themes, the config can define its own in
+//! `[themes.<name>]` tables, each starting from a built-in one.
+//!
+//! The gutter itself is independent of the theme: it can show letters or
+//! symbols with any theme, including when colors are not configured.
+
+use std::borrow::Cow;
+
+use ratatui::style::{Color, Modifier, Style};
+
+use crate::model::LogLevel;
+
+/// How one log level is drawn.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LevelStyle {
+    pub fg: Color,
+    pub modifiers: Modifier,
+}
+
+impl LevelStyle {
+    pub const fn new(fg: Color, modifiers: Modifier) -> Self {
+        Self { fg, modifiers }
+    }
+
+    /// Text style for lines of this level.
+    pub fn style(&self) -> Style {
+        Style::default().fg(self.fg).add_modifier(self.modifiers)
+    }
+}
+
+/// Colors of everything around the log lines.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Chrome {
+    /// Border of the log view; the status bar's follows its text
+    pub border: Color,
+    /// Borders of overlays (filters, bookmarks, messages, ...)
+    pub overlay_border: Color,
+    /// Status bar text in normal mode
+    pub status_fg: Color,
+    /// Status bar background
+    pub status_bg: Color,
+    /// Background of the cursor line
+    pub cursor_bg: Color,
+    /// Background of other lines in a range selection
+    pub range_bg: Color,
+    /// Scrollbar arrows, track and thumb
+    pub scrollbar: Color,
+}
+
+impl Chrome {
+    /// The chrome qlog has always had: terminal colors, cyan overlays.
+    pub const DEFAULT: Chrome = Chrome {
+        border: Color::Reset,
+        overlay_border: Color::Cyan,
+        status_fg: Color::Green,
+        status_bg: Color::Reset,
+        cursor_bg: Color::DarkGray,
+        range_bg: Color::Gray,
+        scrollbar: Color::Reset,
+    };
+}
+
+impl Default for Chrome {
+    fn default() -> Self {
+        Self::DEFAULT
+    }
+}
+
+/// A named set of level styles and chrome colors.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Theme {
+    pub name: Cow<'static, str>,
+    pub description: Cow<'static, str>,
+    /// Styles for error, warn, info, debug and trace; `None` leaves lines uncolored
+    pub levels: Option<[LevelStyle; 5]>,
+    /// Level gutter shown by default with this theme
+    pub gutter: Gutter,
+    pub chrome: Chrome,
+}
+
+/// Level indicator column drawn in front of every line.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Gutter {
+    #[default]
+    Off,
+    /// `E`, `W`, `I`, `D`, `T`
+    Letters,
+    /// `✖`, `▲`, `◆`, `◇`, `·`
+    Symbols,
+}
+
+impl Gutter {
+    /// Parse a gutter name (`off`, `letters`, `symbols`).
+    pub fn parse(name: &str) -> Option<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "off" | "none" => Some(Gutter::Off),
+            "letters" | "letter" => Some(Gutter::Letters),
+            "symbols" | "symbol" | "unicode" => Some(Gutter::Symbols),
+            _ => None,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Gutter::Off => "off",
+            Gutter::Letters => "letters",
+            Gutter::Symbols => "symbols",
+        }
+    }
+
+    /// The next gutter in the `off -> letters -> symbols` cycle.
+    pub fn next(self) -> Self {
+        match self {
+            Gutter::Off => Gutter::Letters,
+            Gutter::Letters => Gutter::Symbols,
+            Gutter::Symbols => Gutter::Off,
+        }
+    }
+
+    /// Indicator for a line of the given level; a blank for lines without
+    /// a detected level so text stays aligned. `None` when the gutter is off.
+    pub fn glyph(self, level: Option<LogLevel>) -> Option<&'static str> {
+        match (self, level) {
+            (Gutter::Off, _) => None,
+            (_, None) => Some(" "),
+            (Gutter::Letters, Some(level)) => Some(level.letter()),
+            (Gutter::Symbols, Some(level)) => Some(level.symbol()),
+        }
+    }
+
+    /// Columns taken by the gutter, including the separating space.
+    pub fn width(self) -> usize {
+        match self {
+            Gutter::Off => 0,
+            Gutter::Letters | Gutter::Symbols => 2,
+        }
+    }
+}
+
+/// Okabe-Ito palette colors, distinguishable with red-green color blindness.
+const VERMILLION: Color = Color::Rgb(213, 94, 0);
+const ORANGE: Color = Color::Rgb(230, 159, 0);
+const YELLOW: Color = Color::Rgb(240, 228, 66);
+const SKY_BLUE: Color = Color::Rgb(86, 180, 233);
+
+/// Nord palette colors.
+const NORD_FROST: Color = Color::Rgb(136, 192, 208);
+const NORD_GREEN: Color = Color::Rgb(163, 190, 140);
+const NORD_RED: Color = Color::Rgb(191, 97, 106);
+const NORD_YELLOW: Color = Color::Rgb(235, 203, 139);
+const NORD_SNOW: Color = Color::Rgb(216, 222, 233);
+const NORD_COMMENT: Color = Color::Rgb(97, 110, 136);
+const NORD_POLAR: Color = Color::Rgb(59, 66, 82);
+const NORD_POLAR_LIGHT: Color = Color::Rgb(76, 86, 106);
+
+const BOLD_REVERSED: Modifier = Modifier::BOLD.union(Modifier::REVERSED);
+
+/// Built-in themes; the first one is the default.
+pub const THEMES: &[Theme] = &[
+    Theme {
+        name: Cow::Borrowed("default"),
+        description: Cow::Borrowed("Colors from the [colors] config only"),
+        levels: None,
+        gutter: Gutter::Off,
+        chrome: Chrome::DEFAULT,
+    },
+    Theme {
+        name: Cow::Borrowed("high-contrast"),
+        description: Cow::Borrowed("Bright, bold levels on the terminal background"),
+        levels: Some([
+            LevelStyle::new(Color::LightRed, BOLD_REVERSED),
+            LevelStyle::new(Color::LightYellow, Modifier::BOLD),
+            LevelStyle::new(Color::White, Modifier::empty()),
+            LevelStyle::new(Color::Gray, Modifier::empty()),
+            LevelStyle::new(Color::Gray, Modifier::DIM),
+        ]),
+        gutter: Gutter::Symbols,
+        chrome: Chrome::DEFAULT,
+    },
+    Theme {
+        name: Cow::Borrowed("deuteranopia"),
+        description: Cow::Borrowed("Vermillion/yellow/blue levels, safe for green-weak vision"),
+        levels: Some([
+            LevelStyle::new(VERMILLION, BOLD_REVERSED),
+            LevelStyle::new(YELLOW, Modifier::BOLD),
+            LevelStyle::new(SKY_BLUE, Modifier::empty()),
+            LevelStyle::new(Color::Gray, Modifier::empty()),
+            LevelStyle::new(Color::Gray, Modifier::DIM),
+        ]),
+        gutter: Gutter::Symbols,
+        chrome: Chrome::DEFAULT,
+    },
+    Theme {
+        name: Cow::Borrowed("protanopia"),
+        description: Cow::Borrowed("Orange/yellow/blue levels, safe for red-weak vision"),
+        levels: Some([
+            LevelStyle::new(ORANGE, BOLD_REVERSED),
+            LevelStyle::new(YELLOW, Modifier::BOLD),
+            LevelStyle::new(SKY_BLUE, Modifier::empty()),
+            LevelStyle::new(Color::Gray, Modifier::empty()),
+            LevelStyle::new(Color::Gray, Modifier::DIM),
+        ]),
+        gutter: Gutter::Symbols,
+        chrome: Chrome::DEFAULT,
+    },
+    Theme {
+        name: Cow::Borrowed("nord"),
+        description: Cow::Borrowed("Arctic blues and muted levels on any dark background"),
+        levels: Some([
+            LevelStyle::new(NORD_RED, BOLD_REVERSED),
+            LevelStyle::new(NORD_YELLOW, Modifier::BOLD),
+            LevelStyle::new(NORD_SNOW, Modifier::empty()),
+            LevelStyle::new(NORD_COMMENT, Modifier::empty()),
+            LevelStyle::new(NORD_COMMENT, Modifier::DIM),
+        ]),
+        gutter: Gutter::Symbols,
+        chrome: Chrome {
+            border: NORD_POLAR_LIGHT,
+            overlay_border: NORD_FROST,
+            status_fg: NORD_GREEN,
+            status_bg: NORD_POLAR,
+            cursor_bg: NORD_POLAR_LIGHT,
+            range_bg: NORD_POLAR,
+            scrollbar: NORD_FROST,
+        },
+    },
+];
+
+impl Theme {
+    /// Look up a built-in theme by name (case-insensitive).
+    pub fn by_name(name: &str) -> Option<&'static Theme> {
+        THEMES.iter().find(|t| t.name.eq_ignore_ascii_case(name))
+    }
+
+    /// Names of all built-in themes.
+    pub fn names() -> Vec<&'static str> {
+        THEMES.iter().map(|t| t.name.as_ref()).collect()
+    }
+
+    /// Look up a theme among `custom` ones from the config, then the
+    /// built-in ones; a custom theme can replace a built-in of that name.
+    pub fn find<'a>(custom: &'a [Theme], name: &str) -> Option<&'a Theme> {
+        custom
+            .iter()
+            .find(|t| t.name.eq_ignore_ascii_case(name))
+            .or_else(|| Theme::by_name(name))
+    }
+
+    /// Names of the `custom` themes followed by the built-in ones they do
+    /// not replace.
+    pub fn all_names(custom: &[Theme]) -> Vec<&str> {
+        let mut names: Vec<&str> = custom.iter().map(|t| t.name.as_ref()).collect();
+        for name in Theme::names() {
+            if !names.iter().any(|n| n.eq_ignore_ascii_case(name)) {
+                names.push(name);
+            }
+        }
+        names
+    }
+
+    /// Set the color of one level, keeping its weight. A theme without
+    /// level colors gets uncolored levels first.
+    pub fn set_level_color(&mut self, level: LogLevel, fg: Color) {
+        let levels = self
+            .levels
+            .get_or_insert([LevelStyle::new(Color::Reset, Modifier::empty()); 5]);
+        levels[level_index(level)].fg = fg;
+    }
+
+    /// Style for the gutter indicator of a level.
+    ///
+    /// Uses the level's color without reversing, so the indicator stays
+    /// readable on selected lines; errors and warnings are bold either way.
+    pub fn gutter_style(&self, level: LogLevel) -> Style {
+        let style = match self.level_style(level) {
+            Some(level_style) => Style::default().fg(level_style.fg),
+            None => Style::default(),
+        };
+        match level {
+            LogLevel::Error | LogLevel::Warn => style.add_modifier(Modifier::BOLD),
+            _ => style,
+        }
+    }
+
+    /// Style for a level, if the theme colors levels.
+    pub fn level_style(&self, level: LogLevel) -> Option<&LevelStyle> {
+        self.levels
+            .as_ref()
+            .map(|levels| &levels[level_index(level)])
+    }
+}
+
+/// Position of a level in `Theme::levels`.
+fn level_index(level: LogLevel) -> usize {
+    match level {
+        LogLevel::Error => 0,
+        LogLevel::Warn => 1,
+        LogLevel::Info => 2,
+        LogLevel::Debug => 3,
+        LogLevel::Trace => 4,
+    }
+}
+
+/// Minimum contrast ratio kept between text and a selection background.
+const MIN_CONTRAST: f32 = 2.5;
+
+/// Approximate relative luminance (0.0 to 1.0) of a terminal color.
+///
+/// Named colors use typical xterm values (dark gray as most dark themes draw it). Returns `None` for colors whose
+/// appearance depends entirely on the terminal (`Reset`, `Indexed`).
+pub fn luminance(color: Color) -> Option<f32> {
+    let (r, g, b) = match color {
+        Color::Black => (0, 0, 0),
+        Color::Red => (205, 0, 0),
+        Color::Green => (0, 205, 0),
+        Color::Yellow => (205, 205, 0),
+        Color::Blue => (0, 0, 238),
+        Color::Magenta => (205, 0, 205),
+        Color::Cyan => (0, 205, 205),
+        Color::Gray => (229, 229, 229),
+        Color::DarkGray => (102, 102, 102),
+        Color::LightRed => (255, 0, 0),
+        Color::LightGreen => (0, 255, 0),
+        Color::LightYellow => (255, 255, 0),
+        Color::LightBlue => (92, 92, 255),
+        Color::LightMagenta => (255, 0, 255),
+        Color::LightCyan => (0, 255, 255),
+        Color::White => (255, 255, 255),
+        Color::Rgb(r, g, b) => (r, g, b),
+        Color::Reset | Color::Indexed(_) => return None,
+    };
+    let channel = |c: u8| {
+        let c = c as f32 / 255.0;
+        if c <= 0.039_28 {
+            c / 12.92
+        } else {
+            ((c + 0.055) / 1.055).powf(2.4)
+        }
+    };
+    Some(0.2126 * channel(r) + 0.7152 * channel(g) + 0.0722 * channel(b))
+}
+
+/// WCAG contrast ratio between two luminances (1.0 to 21.0).
+pub fn contrast_ratio(a: f32, b: f32) -> f32 {
+    let (light, dark) = if a > b { (a, b) } else { (b, a) };
+    (light + 0.05) / (dark + 0.05)
+}
+
+/// Foreground color to draw on `bg`: `fg` itself when it is readable there,
+/// otherwise black or white, whichever contrasts more.
+///
+/// A `None` foreground is the terminal default, whose color is unknown, so
+/// it is always replaced.
+pub fn readable_fg(fg: Option<Color>, bg: Color) -> Option<Color> {
+    let Some(bg_lum) = luminance(bg) else {
+        return fg;
+    };
+    if let Some(fg_lum) = fg.and_then(luminance) {
+        if contrast_ratio(fg_lum, bg_lum) >= MIN_CONTRAST {
+            return fg;
+        }
+    } else if fg.is_some() {
+        return fg;
+    }
+    let on_black = contrast_ratio(0.0, bg_lum);
+    let on_white = contrast_ratio(1.0, bg_lum);
+    Some(if on_black > on_white {
+        Color::Black
+    } else {
+        Color::White
+    })
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        THEMES[0].clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_by_name() {
+        assert_eq!(Theme::by_name("Deuteranopia").unwrap().name, "deuteranopia");
+        assert!(Theme::by_name("solarized").is_none());
+        assert_eq!(Theme::default().name, "default");
+        assert!(Theme::default().level_style(LogLevel::Error).is_none());
+    }
+
+    #[test]
+    fn test_custom_themes_come_first() {
+        let mut custom = Theme::by_name("nord").unwrap().clone();
+        custom.name = Cow::Borrowed("Deuteranopia");
+        custom.set_level_color(LogLevel::Info, Color::Rgb(1, 2, 3));
+        let mut plain = Theme {
+            name: Cow::Borrowed("plain"),
+            ..Theme::default()
+        };
+        plain.set_level_color(LogLevel::Error, Color::Red);
+        let custom = [custom, plain];
+
+        let found = Theme::find(&custom, "deuteranopia").unwrap();
+        assert_eq!(found.chrome.status_bg, NORD_POLAR);
+        let info = found.level_style(LogLevel::Info).unwrap();
+        assert_eq!(info.fg, Color::Rgb(1, 2, 3));
+        let plain = Theme::find(&custom, "plain").unwrap();
+        assert_eq!(plain.level_style(LogLevel::Error).unwrap().fg, Color::Red);
+        assert_eq!(plain.level_style(LogLevel::Warn).unwrap().fg, Color::Reset);
+        assert_eq!(
+            Theme::find(&custom, "protanopia").unwrap().name,
+            "protanopia"
+        );
+        assert_eq!(
+            Theme::all_names(&custom),
+            [
+                "Deuteranopia",
+                "plain",
+                "default",
+                "high-contrast",
+                "protanopia",
+                "nord"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_gutter_glyphs() {
+        assert_eq!(Gutter::Off.glyph(Some(LogLevel::Error)), None);
+        assert_eq!(Gutter::Letters.glyph(Some(LogLevel::Error)), Some("E"));
+        assert_eq!(Gutter::Symbols.glyph(Some(LogLevel::Warn)), Some("▲"));
+        assert_eq!(Gutter::Letters.glyph(None), Some(" "));
+        assert_eq!(Gutter::parse("Unicode"), Some(Gutter::Symbols));
+        assert_eq!(Gutter::Symbols.next(), Gutter::Off);
+    }
+
+    #[test]
+    fn test_readable_fg_keeps_contrasting_colors() {
+        assert_eq!(
+            readable_fg(Some(Color::LightYellow), Color::DarkGray),
+            Some(Color::LightYellow)
+        );
+        assert_eq!(
+            readable_fg(Some(Color::Black), Color::LightYellow),
+            Some(Color::Black)
+        );
+        assert_eq!(
+            readable_fg(Some(Color::Cyan), Color::DarkGray),
+            Some(Color::Cyan)
+        );
+    }
+
+    #[test]
+    fn test_readable_fg_replaces_low_contrast_colors() {
+        assert_eq!(
+            readable_fg(Some(Color::Yellow), Color::Gray),
+            Some(Color::Black)
+        );
+        assert_eq!(
+            readable_fg(Some(Color::Red), Color::DarkGray),
+            Some(Color::White)
+        );
+        assert_eq!(readable_fg(None, Color::Gray), Some(Color::Black));
+        assert_eq!(
+            readable_fg(Some(Color::Indexed(42)), Color::Gray),
+            Some(Color::Indexed(42))
+        );
+    }
+
+    #[test]
+    fn test_accessible_themes_distinguish_levels_without_hue() {
+        for theme in THEMES.iter().filter(|t| t.name != "default") {
+            assert_eq!(theme.gutter, Gutter::Symbols, "{}", theme.name);
+            let styles: Vec<&LevelStyle> = LogLevel::ALL
+                .iter()
+                .map(|&level| theme.level_style(level).unwrap())
+                .collect();
+            // Error, warn and info differ in weight or brightness, not just color
+            assert_ne!(styles[0].modifiers, styles[1].modifiers, "{}", theme.name);
+            assert_ne!(styles[0].modifiers, styles[2].modifiers, "{}", theme.name);
+            assert_ne!(styles[1].modifiers, styles[2].modifiers, "{}", theme.name);
+        }
+    }
+}
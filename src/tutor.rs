@@ -0,0 +1,198 @@
+//! Guided tutorial (`qlog --tutor`).
+//!
+//! The tutorial opens a generated sample log and walks through navigation,
+//! filtering, search, selection and export one step at a time. Each step
+//! tells what to press and has a goal that is checked against the viewer
+//! after every key; once the goal is reached the next step is shown, so
+//! the tutorial follows along with whatever order the keys are found in.
+
+use std::fs::File;
+use std::io::{self, BufWriter};
+use std::path::{Path, PathBuf};
+
+use crate::app::App;
+use crate::generate::{self, GenerateOptions};
+
+/// Size of the sample log: an order service with health checks to hide,
+/// payments to filter for and a few timeouts to search.
+const SAMPLE_LINES: usize = 150;
+
+/// Error rate of the sample log, high enough for a handful of timeouts.
+const SAMPLE_ERROR_RATE: f64 = 0.05;
+
+/// File name of the sample log written for `--tutor`.
+const SAMPLE_FILE_NAME: &str = "qlog-tutor.log";
+
+/// One step of the tutorial.
+pub struct Step {
+    pub title: &'static str,
+    /// Instructions, one line each
+    pub text: &'static [&'static str],
+    /// Whether the goal of the step is reached
+    done: fn(&App) -> bool,
+}
+
+pub const STEPS: &[Step] = &[
+    Step {
+        title: "Moving around",
+        text: &[
+            "j and k (or the arrow keys) move the cursor one line down and up.",
+            "Press G to jump to the last line.",
+        ],
+        done: |app| app.filtered_len() > 1 && app.selected_line + 1 == app.filtered_len(),
+    },
+    Step {
+        title: "Back to the top",
+        text: &["Press g to jump to the first line."],
+        done: |app| app.selected_line == 0,
+    },
+    Step {
+        title: "Filtering",
+        text: &[
+            "Type :filter payment and press Enter.",
+            "Only lines containing 'payment' stay; the status bar counts them.",
+        ],
+        done: |app| has_filter(app.filters.includes(), "payment"),
+    },
+    Step {
+        title: "Hiding noise",
+        text: &[
+            "Drop the filter with :filter-clear, then hide the health checks",
+            "with :filter-out healthcheck. :list-filters shows them all.",
+        ],
+        done: |app| {
+            app.filters.includes().is_empty() && has_filter(app.filters.excludes(), "healthcheck")
+        },
+    },
+    Step {
+        title: "Searching",
+        text: &[
+            "Type /timeout and press Enter to highlight every timeout.",
+            "n jumps to the next match, N back to the previous one.",
+        ],
+        done: |app| {
+            app.search_state
+                .as_ref()
+                .is_some_and(|state| state.query == "timeout" && state.current_idx > 0)
+        },
+    },
+    Step {
+        title: "Selecting lines",
+        text: &[
+            "Press x to start a selection at the cursor,",
+            "then j to stretch it over three lines or more.",
+        ],
+        done: |app| {
+            app.selection
+                .range(app.selected_line)
+                .is_some_and(|(start, end)| end >= start + 2)
+        },
+    },
+    Step {
+        title: "Copy and export",
+        text: &[
+            "y copies the selection to the clipboard; Esc clears it.",
+            "Save the lines in view with :w tutor-export.log",
+        ],
+        done: |app| app.status_message.starts_with("Saved "),
+    },
+    Step {
+        title: "Done",
+        text: &[
+            "That's the basics. Also try :level error+, :stats, Enter on a line",
+            "and :context 2. :tutor off closes this panel, :q quits.",
+        ],
+        done: |_| false,
+    },
+];
+
+fn has_filter(rules: &[crate::model::FilterRule], pattern: &str) -> bool {
+    rules
+        .iter()
+        .any(|rule| rule.pattern().eq_ignore_ascii_case(pattern))
+}
+
+/// Progress through the tutorial.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Tutor {
+    step: usize,
+}
+
+impl Tutor {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Position of the current step, counted from 0.
+    pub fn position(&self) -> usize {
+        self.step
+    }
+
+    pub fn current(&self) -> &'static Step {
+        &STEPS[self.step]
+    }
+
+    /// Move past every step whose goal `app` has reached. Returns the
+    /// title of the last step completed, if any.
+    pub fn advance(&mut self, app: &App) -> Option<&'static str> {
+        let mut completed = None;
+        while (self.current().done)(app) {
+            completed = Some(self.current().title);
+            self.skip();
+        }
+        completed
+    }
+
+    /// Go on to the next step without reaching the goal of this one.
+    pub fn skip(&mut self) {
+        self.step = (self.step + 1).min(STEPS.len() - 1);
+    }
+}
+
+/// Write the sample log to `dir`, returning its path.
+pub fn write_sample(dir: &Path) -> io::Result<PathBuf> {
+    let path = dir.join(SAMPLE_FILE_NAME);
+    let options = GenerateOptions {
+        lines: SAMPLE_LINES,
+        error_rate: SAMPLE_ERROR_RATE,
+        ..GenerateOptions::default()
+    };
+    generate::generate(&options, BufWriter::new(File::create(&path)?))?;
+    Ok(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::LogStorage;
+
+    #[test]
+    fn test_steps_follow_the_viewer() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = write_sample(dir.path()).unwrap();
+        let mut app = App::with_config(None);
+        app.set_storage(LogStorage::from_file(&path).unwrap());
+        let mut tutor = Tutor::new();
+
+        assert_eq!(tutor.advance(&app), None);
+        app.selected_line = app.filtered_len() - 1;
+        // Reaching the bottom completes the first step; the second waits
+        assert_eq!(tutor.advance(&app), Some("Moving around"));
+        assert_eq!(tutor.current().title, "Back to the top");
+        app.selected_line = 0;
+        assert_eq!(tutor.advance(&app), Some("Back to the top"));
+
+        app.filters.add_include("Payment");
+        assert_eq!(tutor.advance(&app), Some("Filtering"));
+        app.filters.clear();
+        app.filters.add_exclude("healthcheck");
+        assert_eq!(tutor.advance(&app), Some("Hiding noise"));
+        assert_eq!(tutor.position(), 4);
+
+        for _ in 0..STEPS.len() {
+            tutor.skip();
+        }
+        assert_eq!(tutor.current().title, "Done");
+        assert_eq!(tutor.advance(&app), None);
+    }
+}
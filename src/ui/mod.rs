@@ -1,6 +1,22 @@
 use crate::app::{App, LoadingStatus};
+use crate::columns::Columns;
+use crate::config::SelectionConfig;
+use crate::detail::DetailView;
+use crate::exceptions::ExceptionGroup;
+use crate::export::format_size;
+use crate::hex::{ByteClass, HexView, BYTES_PER_ROW};
 use crate::key_bindings::Mode;
+use crate::loader::LoadProgress;
 use crate::model::filter::FilterKind;
+use crate::model::text;
+use crate::model::{detect_level, CaseMode};
+use crate::notify::Severity as NoticeSeverity;
+use crate::perf::{format_elapsed, resident_bytes};
+use crate::quickfix::Severity;
+use crate::stats::{self, StatsRow};
+use crate::summary::format_duration;
+use crate::theme::readable_fg;
+use crate::tutor::{Tutor, STEPS};
 use ratatui::{
     layout::{Alignment, Constraint, Direction, Layout, Margin, Rect},
     style::{Color, Modifier, Style},
@@ -10,14 +26,107 @@ use ratatui::{
     },
     Frame,
 };
+use std::ops::Range;
+
+/// Style of lines matching a dim filter, replacing their own colors.
+const DIMMED_STYLE: Style = Style::new().fg(Color::DarkGray).add_modifier(Modifier::DIM);
+
+/// Marker rendered in front of bookmarked lines.
+const BOOKMARK_MARKER: &str = "● ";
+
+/// Style of the "last read" marker row in follow mode.
+const LAST_READ_STYLE: Style = Style::new().fg(Color::Yellow);
+
+/// Style of the separator row between groups of matches and their context.
+const CONTEXT_BREAK_STYLE: Style = Style::new().fg(Color::DarkGray);
+
+/// Pinned lines shown above the log view before the pane stops growing.
+const MAX_PINNED_ROWS: usize = 5;
+
+/// Width of the watch sidebar, borders included.
+const WATCH_SIDEBAR_WIDTH: u16 = 32;
+
+/// Background behind text under the column ruler.
+const RULER_BG: Color = Color::Indexed(236);
+
+/// Per-line render data: filtered index, text, timestamp, text style, gutter
+/// indicator and the region colored by a regex capture rule.
+type LineData = (
+    usize,
+    String,
+    Option<chrono::DateTime<chrono::Utc>>,
+    Style,
+    Option<(&'static str, Style)>,
+    Option<(Range<usize>, Style)>,
+);
+
+/// Push the byte range `range` of `line` as spans in `style`, using the
+/// region's style for the part of it inside the colored region.
+fn push_text_spans(
+    spans: &mut Vec<Span<'static>>,
+    line: &str,
+    range: Range<usize>,
+    style: Style,
+    region: Option<&(Range<usize>, Style)>,
+) {
+    let mut push = |range: Range<usize>, style: Style| {
+        let text = text::slice_bytes(line, range);
+        if !text.is_empty() {
+            spans.push(Span::styled(text.to_string(), style));
+        }
+    };
+    match region {
+        Some((colored, region_style)) if colored.start < range.end && range.start < colored.end => {
+            let start = colored.start.max(range.start);
+            let end = colored.end.min(range.end);
+            push(range.start..start, style);
+            push(start..end, *region_style);
+            push(end..range.end, style);
+        }
+        _ => push(range, style),
+    }
+}
+
+/// Put `style` on a selection background, keeping its text readable.
+///
+/// Uses the configured selection foreground if set, otherwise keeps the
+/// line's own color when it contrasts enough with `bg`.
+fn on_selection(style: Style, bg: Color, selection: &SelectionConfig) -> Style {
+    let style = style.remove_modifier(Modifier::REVERSED).bg(bg);
+    match selection.fg.or_else(|| readable_fg(style.fg, bg)) {
+        Some(fg) => style.fg(fg),
+        None => style,
+    }
+}
+
+/// Mark a text column (1-based, from the left edge of the log view) with a
+/// vertical ruler: a faint bar in empty cells and a subtle background
+/// behind text.
+fn draw_ruler(frame: &mut Frame, area: Rect, column: usize, horizontal_scroll: usize) {
+    let Some(offset) = column.saturating_sub(1).checked_sub(horizontal_scroll) else {
+        return;
+    };
+    if offset >= area.width as usize {
+        return;
+    }
+    let x = area.x + offset as u16;
+    let buf = frame.buffer_mut();
+    for y in area.top()..area.bottom() {
+        let cell = buf.get_mut(x, y);
+        if cell.symbol == " " {
+            cell.set_symbol("│").set_fg(Color::DarkGray);
+        } else if cell.bg == Color::Reset {
+            cell.set_bg(RULER_BG);
+        }
+    }
+}
 
 /// Calculate how many visual lines a text will occupy when wrapped.
 fn count_visual_lines(text_width: usize, viewport_width: usize) -> usize {
     if viewport_width == 0 || text_width == 0 {
         return 1;
     }
-    // Ceiling division: (text_width + viewport_width - 1) / viewport_width
-    ((text_width + viewport_width - 1) / viewport_width).max(1)
+    text_width.div_ceil(viewport_width).max(1)
 }
 
 /// Main draw function that routes to appropriate screen based on app state.
@@ -26,7 +135,7 @@ pub fn draw(frame: &mut Frame, app: &mut App) {
     app.check_for_loaded_logs();
 
     if let LoadingStatus::Loading { current, total } = &app.loading_status {
-        draw_loading_screen(frame, *current, *total, app.total_lines());
+        draw_loading_screen(frame, *current, *total, &app.load_progress);
         return;
     }
 
@@ -45,7 +154,18 @@ pub fn draw(frame: &mut Frame, app: &mut App) {
                 Constraint::Min(0),
                 Constraint::Length(3),
             ],
-            Mode::FilterList => vec![
+            Mode::FilterList
+            | Mode::Recent
+            | Mode::Prompt
+            | Mode::Pins
+            | Mode::Bookmarks
+            | Mode::Errors
+            | Mode::Pods
+            | Mode::Sql
+            | Mode::Stats
+            | Mode::Exceptions
+            | Mode::Gaps
+            | Mode::Messages => vec![
                 Constraint::Length(3),
                 Constraint::Length(12),
                 Constraint::Min(0),
@@ -80,14 +200,314 @@ pub fn draw(frame: &mut Frame, app: &mut App) {
             main_chunk = chunks[2];
             status_chunk = chunks[3];
         }
+        Mode::Recent => {
+            draw_recent_list(frame, app, chunks[1]);
+            main_chunk = chunks[2];
+            status_chunk = chunks[3];
+        }
+        Mode::Prompt => {
+            draw_prompt(frame, app, chunks[1]);
+            main_chunk = chunks[2];
+            status_chunk = chunks[3];
+        }
+        Mode::Pins => {
+            draw_pin_list(frame, app, chunks[1]);
+            main_chunk = chunks[2];
+            status_chunk = chunks[3];
+        }
+        Mode::Bookmarks => {
+            draw_bookmark_list(frame, app, chunks[1]);
+            main_chunk = chunks[2];
+            status_chunk = chunks[3];
+        }
+        Mode::Errors => {
+            draw_error_list(frame, app, chunks[1]);
+            main_chunk = chunks[2];
+            status_chunk = chunks[3];
+        }
+        Mode::Pods => {
+            draw_pod_list(frame, app, chunks[1]);
+            main_chunk = chunks[2];
+            status_chunk = chunks[3];
+        }
+        Mode::Sql => {
+            draw_sql_result(frame, app, chunks[1]);
+            main_chunk = chunks[2];
+            status_chunk = chunks[3];
+        }
+        Mode::Stats => {
+            draw_stats(frame, app, chunks[1]);
+            main_chunk = chunks[2];
+            status_chunk = chunks[3];
+        }
+        Mode::Exceptions => {
+            draw_exceptions(frame, app, chunks[1]);
+            main_chunk = chunks[2];
+            status_chunk = chunks[3];
+        }
+        Mode::Gaps => {
+            draw_gaps(frame, app, chunks[1]);
+            main_chunk = chunks[2];
+            status_chunk = chunks[3];
+        }
+        Mode::Messages => {
+            draw_message_list(frame, app, chunks[1]);
+            main_chunk = chunks[2];
+            status_chunk = chunks[3];
+        }
         _ => {
             main_chunk = chunks[1];
             status_chunk = chunks[2];
         }
     }
 
+    let main_chunk = if app.pins.is_empty() {
+        main_chunk
+    } else {
+        let rows = app.pins.len().min(MAX_PINNED_ROWS) as u16 + 2;
+        let split = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Length(rows), Constraint::Min(0)])
+            .split(main_chunk);
+        draw_pinned_pane(frame, app, split[0]);
+        split[1]
+    };
+
+    let main_chunk = match &app.detail {
+        Some(detail) if app.mode == Mode::Detail => {
+            let split = Layout::default()
+                .direction(Direction::Vertical)
+                .constraints([Constraint::Percentage(50), Constraint::Percentage(50)])
+                .split(main_chunk);
+            draw_detail_pane(frame, detail, app.theme.chrome.overlay_border, split[1]);
+            split[0]
+        }
+        _ => match &app.hex {
+            Some(hex) if app.mode == Mode::Hex => {
+                let split = Layout::default()
+                    .direction(Direction::Vertical)
+                    .constraints([Constraint::Percentage(50), Constraint::Percentage(50)])
+                    .split(main_chunk);
+                draw_hex_pane(frame, hex, app.theme.chrome.overlay_border, split[1]);
+                split[0]
+            }
+            _ => main_chunk,
+        },
+    };
+
+    let main_chunk = match &app.tutor {
+        Some(tutor) => {
+            let rows = tutor.current().text.len() as u16 + 3;
+            let split = Layout::default()
+                .direction(Direction::Vertical)
+                .constraints([Constraint::Min(0), Constraint::Length(rows)])
+                .split(main_chunk);
+            draw_tutor_pane(frame, tutor, split[1]);
+            split[0]
+        }
+        None => main_chunk,
+    };
+
+    // The sidebar gives way on narrow terminals
+    let main_chunk = if app.watches.is_empty() || main_chunk.width < WATCH_SIDEBAR_WIDTH * 2 {
+        main_chunk
+    } else {
+        let split = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints([Constraint::Min(0), Constraint::Length(WATCH_SIDEBAR_WIDTH)])
+            .split(main_chunk);
+        draw_watch_sidebar(frame, app, split[1]);
+        split[0]
+    };
+
     draw_main_view(frame, app, main_chunk);
     draw_status_bar(frame, app, status_chunk);
+
+    if app.show_perf {
+        draw_perf_overlay(frame, app, main_chunk);
+    }
+    draw_jobs_overlay(frame, app, main_chunk);
+    if app.load_summary.is_some() {
+        draw_load_summary(frame, app, main_chunk);
+    }
+    if app.mode == Mode::Help {
+        draw_help(frame, app, frame.size());
+    }
+}
+
+/// Widest key column of a help section.
+const HELP_KEY_WIDTH: usize = 24;
+
+/// The `?` help: the keys of every mode and the commands, in a popup over
+/// the middle of the screen.
+fn draw_help(frame: &mut Frame, app: &App, area: Rect) {
+    let mut lines: Vec<Line> = Vec::new();
+    for section in &app.help {
+        // Longer keys push their text along rather than widen the column
+        let key_width = section
+            .entries
+            .iter()
+            .map(|(keys, _)| keys.chars().count())
+            .max()
+            .unwrap_or(0)
+            .min(HELP_KEY_WIDTH);
+        lines.push(Line::from(Span::styled(
+            section.title.clone(),
+            Style::default()
+                .fg(Color::Yellow)
+                .add_modifier(Modifier::BOLD),
+        )));
+        for (keys, text) in &section.entries {
+            lines.push(Line::from(vec![
+                Span::styled(
+                    format!("  {:<width$}  ", keys, width = key_width),
+                    Style::default().fg(Color::Cyan),
+                ),
+                Span::raw(text.clone()),
+            ]));
+        }
+        lines.push(Line::default());
+    }
+
+    let width = area.width.saturating_sub(4).min(100);
+    let height = area.height.saturating_sub(2);
+    let popup = Rect {
+        x: area.x + (area.width - width) / 2,
+        y: area.y + (area.height - height) / 2,
+        width,
+        height,
+    };
+    frame.render_widget(Clear, popup);
+    frame.render_widget(
+        Paragraph::new(lines)
+            .scroll((app.help_scroll as u16, 0))
+            .block(
+                Block::default()
+                    .title(" Help (q to close) ")
+                    .borders(Borders::ALL)
+                    .border_style(Style::default().fg(app.theme.chrome.overlay_border)),
+            ),
+        popup,
+    );
+}
+
+/// Timings, cache hit rates and memory counters in the top-right corner.
+fn draw_perf_overlay(frame: &mut Frame, app: &App, area: Rect) {
+    let label_style = Style::default().fg(Color::Gray);
+    let value_style = Style::default().fg(Color::White);
+    let row = |label: &str, value: String| {
+        Line::from(vec![
+            Span::styled(format!(" {:<10}", label), label_style),
+            Span::styled(value, value_style),
+        ])
+    };
+    let elapsed = |elapsed: Option<std::time::Duration>| {
+        elapsed.map_or_else(|| "-".to_string(), format_elapsed)
+    };
+
+    let visual_cache = app.visual_cache();
+    let mut lines = vec![
+        row("Frame", elapsed(app.perf.frame)),
+        row(
+            "Filter",
+            format!(
+                "{} ({}/{} lines)",
+                elapsed(app.perf.filter),
+                app.filtered_len(),
+                app.total_lines()
+            ),
+        ),
+        row(
+            "Visual",
+            format!(
+                "{} entries, {}",
+                visual_cache.len(),
+                visual_cache.stats().label()
+            ),
+        ),
+        row("Matches", app.perf.match_cache.label()),
+    ];
+    if let Some(storage) = &app.storage {
+        lines.push(row("Mapped", format_size(storage.mapped_bytes())));
+        lines.push(row("Index", format_size(storage.index_bytes() as u64)));
+    }
+    if let Some(resident) = resident_bytes() {
+        lines.push(row("Resident", format_size(resident)));
+    }
+
+    let width = lines
+        .iter()
+        .map(|line| line.width() as u16 + 3)
+        .max()
+        .unwrap_or(0)
+        .min(area.width);
+    let height = (lines.len() as u16 + 2).min(area.height);
+    let overlay = Rect {
+        x: area.x + area.width - width,
+        y: area.y,
+        width,
+        height,
+    };
+
+    frame.render_widget(Clear, overlay);
+    frame.render_widget(
+        Paragraph::new(lines).block(
+            Block::default()
+                .title(" Perf ")
+                .borders(Borders::ALL)
+                .border_style(Style::default().fg(Color::Magenta)),
+        ),
+        overlay,
+    );
+}
+
+/// Running background jobs in the bottom-right corner, newest last.
+fn draw_jobs_overlay(frame: &mut Frame, app: &App, area: Rect) {
+    let jobs = app.running_jobs();
+    if jobs.is_empty() {
+        return;
+    }
+    let mut lines: Vec<Line> = jobs
+        .iter()
+        .map(|(name, percent)| {
+            Line::from(vec![
+                Span::styled(
+                    format!(" {:>3}% ", percent),
+                    Style::default().fg(Color::Cyan),
+                ),
+                Span::raw(format!("{} ", name)),
+            ])
+        })
+        .collect();
+    lines.push(Line::from(Span::styled(
+        " Esc: cancel the newest ",
+        Style::default().fg(Color::DarkGray),
+    )));
+
+    let width = lines
+        .iter()
+        .map(|line| line.width() as u16 + 2)
+        .max()
+        .unwrap_or(0)
+        .min(area.width);
+    let height = (lines.len() as u16 + 2).min(area.height);
+    let overlay = Rect {
+        x: area.x + area.width - width,
+        y: area.y + area.height - height,
+        width,
+        height,
+    };
+
+    frame.render_widget(Clear, overlay);
+    frame.render_widget(
+        Paragraph::new(lines).block(
+            Block::default()
+                .title(" Jobs ")
+                .borders(Borders::ALL)
+                .border_style(Style::default().fg(app.theme.chrome.overlay_border)),
+        ),
+        overlay,
+    );
 }
 
 fn draw_filter_bar(frame: &mut Frame, app: &App, area: ratatui::layout::Rect) {
@@ -106,6 +526,13 @@ fn draw_filter_bar(frame: &mut Frame, app: &App, area: ratatui::layout::Rect) {
         ));
     }
 
+    if !app.drill_down.is_empty() {
+        spans.push(Span::styled(
+            format!(" | {}", app.drill_down.label()),
+            Style::default().fg(Color::Yellow),
+        ));
+    }
+
     let filter_bar = Paragraph::new(Line::from(spans))
         .block(Block::default().title("Filters").borders(Borders::ALL));
     frame.render_widget(filter_bar, area);
@@ -134,8 +561,19 @@ fn draw_search_input(frame: &mut Frame, app: &App, area: ratatui::layout::Rect)
         Span::styled(" ", cursor_style),
     ]);
 
-    let input_box =
-        Paragraph::new(line).block(Block::default().title("Search Input").borders(Borders::ALL));
+    let mut options = Vec::new();
+    if app.case_mode != CaseMode::Ignore {
+        options.push(app.case_mode.name());
+    }
+    if app.whole_word {
+        options.push("whole words");
+    }
+    let title = if options.is_empty() {
+        "Search Input".to_string()
+    } else {
+        format!("Search Input ({})", options.join(", "))
+    };
+    let input_box = Paragraph::new(line).block(Block::default().title(title).borders(Borders::ALL));
     frame.render_widget(input_box, area);
 }
 
@@ -149,6 +587,7 @@ fn draw_main_view(frame: &mut Frame, app: &mut App, area: ratatui::layout::Rect)
     let viewport_width = inner_area.width as usize;
     app.viewport_height.set(content_height);
     app.viewport_width.set(viewport_width);
+    app.view_area.set(inner_area);
 
     // Update visual cache viewport settings
     if app.visual_cache().viewport_width() != viewport_width {
@@ -158,7 +597,10 @@ fn draw_main_view(frame: &mut Frame, app: &mut App, area: ratatui::layout::Rect)
     // Calculate how many entries fit in the viewport, accounting for wrap mode
     let mut entries_to_take = 0usize;
     let mut total_visual_lines = 0usize;
+    // Filtered line on each screen row, for mouse clicks
+    let mut view_rows: Vec<Option<usize>> = Vec::with_capacity(content_height);
 
+    let marker_row = app.last_read_row();
     for idx in app.scroll_offset..app.filtered_len() {
         if let Some(mmap_str) = app.get_filtered_entry(idx) {
             let text = mmap_str.as_str_lossy();
@@ -167,41 +609,84 @@ fn draw_main_view(frame: &mut Frame, app: &mut App, area: ratatui::layout::Rect)
                 .as_ref()
                 .map(|_| 20)
                 .unwrap_or(0);
-            let text_width = ts_len + text.chars().count();
+            let mark_len = if app.is_bookmarked(idx) {
+                BOOKMARK_MARKER.chars().count()
+            } else {
+                0
+            };
+            let gutter_len = app.gutter.width();
+            let text_width = gutter_len + mark_len + ts_len + text.chars().count();
 
-            let visual_lines = if app.wrap_mode {
+            let visual_lines = if app.wraps() {
                 count_visual_lines(text_width, viewport_width)
             } else {
                 1
             };
 
+            // Separator rows of context groups are only drawn below the top line
+            let context_break = idx > app.scroll_offset && app.context_break(idx);
+            let visual_lines =
+                visual_lines + usize::from(marker_row == Some(idx)) + usize::from(context_break);
+
             if total_visual_lines + visual_lines > content_height {
                 break;
             }
 
             total_visual_lines += visual_lines;
             entries_to_take += 1;
+            if marker_row == Some(idx) {
+                view_rows.push(None);
+            }
+            if context_break {
+                view_rows.push(None);
+            }
+            view_rows.resize(total_visual_lines, Some(idx));
         }
     }
 
     // Ensure we take at least 1 entry if there are any
     if entries_to_take == 0 && app.filtered_len() > app.scroll_offset {
         entries_to_take = 1;
+        view_rows.resize(content_height, Some(app.scroll_offset));
     }
+    *app.view_rows.borrow_mut() = view_rows;
+
+    // In the column view, structured lines are shown as their field values
+    let top = app.scroll_offset;
+    let column_rows = match (&app.columns, app.source()) {
+        (Some(columns), Some(source)) => Columns::layout(
+            &(top..top + entries_to_take)
+                .map(|idx| columns.cells(source, app.filtered_indices[idx]))
+                .collect::<Vec<_>>(),
+        ),
+        _ => Vec::new(),
+    };
+    let column_row = |idx: usize| column_rows.get(idx - top).and_then(Option::as_deref);
 
     // Collect line data first to avoid borrow issues
-    let line_data: Vec<(
-        usize,
-        String,
-        Option<chrono::DateTime<chrono::Utc>>,
-        Option<Color>,
-    )> = (app.scroll_offset..app.scroll_offset + entries_to_take)
+    let line_data: Vec<LineData> = (top..top + entries_to_take)
         .filter_map(|idx| {
             app.get_filtered_entry(idx).map(|mmap_str| {
                 let line_text = mmap_str.as_str_lossy().to_string();
-                let line_fg_color = app.get_line_color(&line_text);
-                let timestamp = app.get_filtered_timestamp(idx);
-                (idx, line_text, timestamp, line_fg_color)
+                let level = detect_level(&line_text);
+                let dimmed = app.is_line_dimmed(app.filtered_indices[idx], mmap_str.as_bytes());
+                let (line_style, region) = if dimmed {
+                    (DIMMED_STYLE, None)
+                } else {
+                    (
+                        app.get_line_style(&line_text, level),
+                        app.pod_badge(&line_text)
+                            .or_else(|| app.get_color_region(&line_text)),
+                    )
+                };
+                let gutter = app.gutter_glyph(level);
+                match column_row(idx) {
+                    Some(row) => (idx, row.to_string(), None, line_style, gutter, None),
+                    None => {
+                        let timestamp = app.get_filtered_timestamp(idx);
+                        (idx, line_text, timestamp, line_style, gutter, region)
+                    }
+                }
             })
         })
         .collect();
@@ -209,107 +694,112 @@ fn draw_main_view(frame: &mut Frame, app: &mut App, area: ratatui::layout::Rect)
     // Pre-compute matches for all visible lines
     let line_matches: Vec<(usize, Vec<(usize, usize)>)> = line_data
         .iter()
-        .map(|(idx, _, _, _)| {
-            let matches = if app.has_search() {
-                app.get_line_matches(*idx)
-            } else {
-                Vec::new()
+        .map(|(idx, _, _, _, _, _)| {
+            let matches = match column_row(*idx) {
+                _ if !app.has_search() => Vec::new(),
+                Some(row) => app.text_matches(*idx, row),
+                None => app.get_line_matches(*idx),
             };
             (*idx, matches)
         })
         .collect();
 
     // Build log lines with highlighting
-    let log_lines: Vec<Line> = line_data
+    // `[selection]` colors win over the theme's
+    let selection = app.selection_config();
+    let cursor_bg = selection.cursor_bg.unwrap_or(app.theme.chrome.cursor_bg);
+    let range_bg = selection.range_bg.unwrap_or(app.theme.chrome.range_bg);
+    let mut log_lines: Vec<Line> = line_data
         .into_iter()
-        .zip(line_matches.into_iter())
-        .filter_map(
-            |((idx, line_text, timestamp, line_fg_color), (_, matches))| {
+        .zip(line_matches)
+        .map(
+            |((idx, line_text, timestamp, line_style, gutter, region), (_, matches))| {
                 let is_selected = idx == app.selected_line;
                 let is_in_selection = app.selection.contains(idx, app.selected_line);
 
                 // Selection takes precedence - set background
-                // Use DarkGray for cursor line, Gray for other selected lines
+                // Cursor line and range selection have separate backgrounds
                 let base_bg = if is_selected {
-                    Some(Color::DarkGray)
+                    Some(cursor_bg)
                 } else if is_in_selection {
-                    Some(Color::Gray)
+                    Some(range_bg)
                 } else {
                     None
                 };
+                let on_bg = |style: Style| match base_bg {
+                    Some(bg) => on_selection(style, bg, &selection),
+                    None => style,
+                };
+
+                let text_style = on_bg(line_style);
+                let region = region.map(|(range, style)| (range, on_bg(style)));
 
                 let mut spans = Vec::new();
 
+                // The gutter keeps its own colors so severity stays visible
+                // on selected lines
+                if let Some((glyph, gutter_style)) = gutter {
+                    spans.push(Span::styled(format!("{} ", glyph), gutter_style));
+                }
+
+                if app.is_bookmarked(idx) {
+                    let mark_style = Style::default()
+                        .fg(Color::Magenta)
+                        .add_modifier(Modifier::BOLD);
+                    spans.push(Span::styled(BOOKMARK_MARKER, on_bg(mark_style)));
+                }
+
                 // Add timestamp if available - always cyan
                 if let Some(ts) = timestamp {
-                    let ts_style = match base_bg {
-                        Some(bg) => Style::default().fg(Color::Cyan).bg(bg),
-                        None => Style::default().fg(Color::Cyan),
-                    };
+                    let ts_style = on_bg(Style::default().fg(Color::Cyan));
                     spans.push(Span::styled(
                         ts.format("%Y-%m-%d %H:%M:%S ").to_string(),
                         ts_style,
                     ));
                 }
 
-                if matches.is_empty() {
+                if matches.is_empty() && region.is_none() {
                     // No matches - add the whole line as one span
-                    let text_style = match (line_fg_color, base_bg) {
-                        (Some(fg), Some(bg)) => Style::default().fg(fg).bg(bg),
-                        (Some(fg), None) => Style::default().fg(fg),
-                        (None, Some(bg)) => Style::default().bg(bg),
-                        (None, None) => Style::default(),
-                    };
                     spans.push(Span::styled(line_text, text_style));
                 } else {
                     // Split line into spans around matches
-                    let line_bytes = line_text.as_bytes();
                     let mut last_end = 0;
 
                     for (match_start, match_end) in matches {
                         // Add text before match
                         if match_start > last_end {
-                            let before_text =
-                                String::from_utf8_lossy(&line_bytes[last_end..match_start]);
-                            let text_style = match (line_fg_color, base_bg) {
-                                (Some(fg), Some(bg)) => Style::default().fg(fg).bg(bg),
-                                (Some(fg), None) => Style::default().fg(fg),
-                                (None, Some(bg)) => Style::default().bg(bg),
-                                (None, None) => Style::default(),
-                            };
-                            spans.push(Span::styled(before_text.to_string(), text_style));
+                            push_text_spans(
+                                &mut spans,
+                                &line_text,
+                                last_end..match_start,
+                                text_style,
+                                region.as_ref(),
+                            );
                         }
 
                         // Add match span with highlight
-                        let match_text =
-                            String::from_utf8_lossy(&line_bytes[match_start..match_end]);
-                        let is_current = app.is_current_match(idx, match_start);
+                        let match_text = text::slice_bytes(&line_text, match_start..match_end);
+                        let is_current =
+                            column_row(idx).is_none() && app.is_current_match(idx, match_start);
 
                         let match_style = if let Some(search_config) = app.search_config() {
-                            if is_current {
-                                let style = search_config
+                            let style = if is_current {
+                                search_config
                                     .current_style
                                     .fg(search_config.current_fg)
-                                    .bg(search_config.current_bg);
-                                if base_bg.is_some() {
-                                    // Don't override selection bg
-                                    let base_style = style.bg(base_bg.unwrap());
-                                    base_style
-                                } else {
-                                    style
-                                }
+                                    .bg(search_config.current_bg)
                             } else {
-                                let style = search_config
+                                search_config
                                     .match_style
                                     .fg(search_config.match_fg)
-                                    .bg(search_config.match_bg);
-                                if base_bg.is_some() {
-                                    // Don't override selection bg
-                                    let base_style = style.bg(base_bg.unwrap());
-                                    base_style
-                                } else {
-                                    style
-                                }
+                                    .bg(search_config.match_bg)
+                            };
+                            if base_bg.is_some() {
+                                // Don't override selection bg; underline keeps
+                                // the match visible instead
+                                on_bg(style).add_modifier(Modifier::UNDERLINED)
+                            } else {
+                                style
                             }
                         } else {
                             // Fallback colors
@@ -325,27 +815,60 @@ fn draw_main_view(frame: &mut Frame, app: &mut App, area: ratatui::layout::Rect)
                     }
 
                     // Add remaining text after last match
-                    if last_end < line_bytes.len() {
-                        let after_text = String::from_utf8_lossy(&line_bytes[last_end..]);
-                        let text_style = match (line_fg_color, base_bg) {
-                            (Some(fg), Some(bg)) => Style::default().fg(fg).bg(bg),
-                            (Some(fg), None) => Style::default().fg(fg),
-                            (None, Some(bg)) => Style::default().bg(bg),
-                            (None, None) => Style::default(),
-                        };
-                        spans.push(Span::styled(after_text.to_string(), text_style));
+                    push_text_spans(
+                        &mut spans,
+                        &line_text,
+                        last_end..line_text.len(),
+                        text_style,
+                        region.as_ref(),
+                    );
+                }
+
+                if let Some(more) = app.throttled_after(idx) {
+                    spans.push(Span::styled(
+                        format!("  (+{} more like this)", more),
+                        on_bg(Style::default().fg(Color::DarkGray)),
+                    ));
+                }
+
+                if is_selected && app.cursor_underline {
+                    for span in &mut spans {
+                        span.patch_style(Style::default().add_modifier(Modifier::UNDERLINED));
                     }
                 }
 
-                Some(Line::from(spans))
+                Line::from(spans)
             },
         )
         .collect();
 
+    // Insert marker rows from the bottom up so earlier positions stay valid
+    for idx in (app.scroll_offset..app.scroll_offset + log_lines.len()).rev() {
+        let row = idx - app.scroll_offset;
+        if idx > app.scroll_offset && app.context_break(idx) {
+            log_lines.insert(
+                row,
+                Line::from(Span::styled(
+                    "┈".repeat(viewport_width),
+                    CONTEXT_BREAK_STYLE,
+                )),
+            );
+        }
+        if marker_row == Some(idx) {
+            log_lines.insert(
+                row,
+                Line::from(Span::styled(
+                    format!("{:─^1$}", " last read ", viewport_width),
+                    LAST_READ_STYLE,
+                )),
+            );
+        }
+    }
+
     // Calculate approximate max line width for scrollbar
-    let max_line_width = if let Some(storage) = &app.storage {
-        storage
-            .iter()
+    let max_line_width = if let Some(source) = app.source() {
+        source
+            .lines()
             .take(1000) // Sample first 1000 lines
             .map(|mmap_str| {
                 let text = mmap_str.as_str_lossy();
@@ -358,36 +881,100 @@ fn draw_main_view(frame: &mut Frame, app: &mut App, area: ratatui::layout::Rect)
     };
 
     let wrap_indicator = if app.wrap_mode { "[WRAP]" } else { "[nowrap]" };
+    let follow_indicator = match &app.replay {
+        Some(replay) => format!(
+            " [REPLAY {}x {}{}]",
+            replay.speed(),
+            replay.clock(std::time::Instant::now()).format("%H:%M:%S"),
+            if replay.is_paused() { " paused" } else { "" }
+        ),
+        None if app.is_following() => " [FOLLOW]".to_string(),
+        None => String::new(),
+    };
+    let throttle_indicator = app
+        .throttle
+        .as_ref()
+        .map(|t| format!(" [THROTTLE {}]", t.limit()))
+        .unwrap_or_default();
+    let context_indicator = app
+        .context
+        .as_ref()
+        .map(|c| format!(" [CONTEXT {}]", c.lines()))
+        .unwrap_or_default();
+    let columns_indicator = app
+        .columns
+        .as_ref()
+        .map(|c| format!(" [COLUMNS {}]", c.label()))
+        .unwrap_or_default();
+    // Tab bar, with the shown tab in brackets, once there is a second tab
+    let tabs = if app.tab_count() > 1 {
+        let labels: Vec<String> = app
+            .tab_labels()
+            .into_iter()
+            .enumerate()
+            .map(|(idx, label)| {
+                if idx == app.active_tab() {
+                    format!("[{}:{}]", idx + 1, label)
+                } else {
+                    format!("{}:{}", idx + 1, label)
+                }
+            })
+            .collect();
+        format!("{} | ", labels.join(" "))
+    } else {
+        String::new()
+    };
     let title = format!(
-        "Logs ({} total, {} filtered) {} [vw:{}]",
+        "{}Logs ({} total, {} filtered) {}{}{}{}{} [vw:{}]",
+        tabs,
         app.total_lines(),
         app.filtered_len(),
         wrap_indicator,
+        follow_indicator,
+        throttle_indicator,
+        context_indicator,
+        columns_indicator,
         inner_area.width
     );
 
     let mut main_view = Paragraph::new(log_lines)
-        .block(Block::default().title(title).borders(Borders::ALL))
+        .block(
+            Block::default()
+                .title(title)
+                .borders(Borders::ALL)
+                .border_style(Style::default().fg(app.theme.chrome.border)),
+        )
         .scroll((0, app.horizontal_scroll as u16));
 
-    if app.wrap_mode {
+    if app.wraps() {
         main_view = main_view.wrap(Wrap { trim: true });
     }
 
     frame.render_widget(main_view, area);
 
+    if let Some(column) = app.ruler_column {
+        draw_ruler(frame, inner_area, column, app.horizontal_scroll);
+    }
+
+    // Overlays can leave the view no rows on a short terminal, and
+    // scrollbars need room for their end symbols
+    if area.height < 2 || area.width < 2 {
+        return;
+    }
+
     // Fast scrollbar calculation - use entry counts, not visual lines
     let total_entries = app.filtered_len();
     let scroll_position = app.scroll_offset;
 
     let show_vertical = total_entries > content_height;
-    let show_horizontal = !app.wrap_mode && max_line_width > viewport_width;
+    let show_horizontal = !app.wraps() && max_line_width > viewport_width;
 
     if show_vertical {
         let vertical_scrollbar = Scrollbar::new(ScrollbarOrientation::VerticalRight)
             .begin_symbol(Some("▲"))
             .track_symbol(Some("│"))
-            .end_symbol(Some("▼"));
+            .end_symbol(Some("▼"))
+            .style(Style::default().fg(app.theme.chrome.scrollbar));
 
         let mut v_scroll_state = ScrollbarState::new(total_entries)
             .viewport_content_length(content_height)
@@ -412,7 +999,8 @@ fn draw_main_view(frame: &mut Frame, app: &mut App, area: ratatui::layout::Rect)
         let horizontal_scrollbar = Scrollbar::new(ScrollbarOrientation::HorizontalBottom)
             .begin_symbol(Some("◄"))
             .track_symbol(Some("─"))
-            .end_symbol(Some("►"));
+            .end_symbol(Some("►"))
+            .style(Style::default().fg(app.theme.chrome.scrollbar));
 
         let mut h_scroll_state = ScrollbarState::new(max_line_width)
             .viewport_content_length(viewport_width)
@@ -428,24 +1016,88 @@ fn draw_status_bar(frame: &mut Frame, app: &App, area: ratatui::layout::Rect) {
         Mode::FilterList => "FILTERS",
         Mode::Command => "COMMAND",
         Mode::SearchInput => "SEARCH",
+        Mode::Recent => "RECENT",
+        Mode::Prompt => "PROMPT",
+        Mode::Pins => "PINS",
+        Mode::Bookmarks => "BOOKMARKS",
+        Mode::Errors => "ERRORS",
+        Mode::Pods => "PODS",
+        Mode::Detail => "DETAIL",
+        Mode::Hex => "HEX",
+        Mode::Sql => "SQL",
+        Mode::Stats => "STATS",
+        Mode::Exceptions => "EXCEPTIONS",
+        Mode::Gaps => "GAPS",
+        Mode::Messages => "MESSAGES",
+        Mode::Help => "HELP",
     };
 
     let help_text = match app.mode {
-        Mode::Normal => "j/k: Scroll | h/l: H-scroll | w: Wrap | g/G: Top/Bottom | /: Search | n/N: Next/Prev match | q: Quit",
-        Mode::FilterList => "j/k: Select filter | d: Delete | q: Close",
+        Mode::Normal => "j/k: Scroll | h/l: H-scroll | w: Wrap | g/G: Top/Bottom | /: Search | n/N: Next/Prev match | m: Bookmark | ?: Help | q: Quit",
+        Mode::FilterList => "j/k: Select filter | Space: Toggle | d: Delete | q: Close",
         Mode::Command => "Enter: Execute | Esc: Cancel",
         Mode::SearchInput => "Enter: Execute search | Esc: Cancel | Backspace: Delete char",
+        Mode::Recent => "j/k: Select | Enter: Open | q: Close",
+        Mode::Prompt => "j/k: Select | Enter: Choose | Key: Choose it | Esc: Cancel",
+        Mode::Pins => "j/k: Select | J/K: Move | d: Unpin | Enter: Go to line | q: Close",
+        Mode::Bookmarks => "j/k: Select | d: Remove | Enter: Go to line | q/': Close",
+        Mode::Errors => "j/k: Select | Enter: Go to line | ]q/[q: Next/prev in view | q: Close",
+        Mode::Pods => "j/k: Select | p: Pause/resume | m: Mute/unmute | q: Close",
+        Mode::Detail => "j/k: Field | y: Copy value | +/-: Filter by field | Enter/Space: Fold/unfold | E/C: Expand/collapse all | q/i: Close",
+        Mode::Hex => "j/k: Scroll | g/G: Top/Bottom | q/X: Close",
+        Mode::Sql => "j/k: Select row | Enter: Go to line | +/-: Filter by row | q: Close",
+        Mode::Stats => "j/k: Select | Enter: Show these lines | q: Close",
+        Mode::Exceptions => "j/k: Select | Enter: Go to latest | q: Close",
+        Mode::Gaps => "q: Close",
+        Mode::Messages => "j/k: Scroll | q: Close",
+        Mode::Help => "j/k: Scroll | Space/PgUp: Page | q: Close",
     };
 
     let mode_style = match app.mode {
-        Mode::Normal => Style::default().fg(Color::Green),
+        Mode::Normal => Style::default().fg(app.theme.chrome.status_fg),
         Mode::FilterList => Style::default().fg(Color::Cyan),
         Mode::Command => Style::default().fg(Color::Magenta),
         Mode::SearchInput => Style::default().fg(Color::Yellow),
+        Mode::Recent => Style::default().fg(Color::Cyan),
+        Mode::Prompt => Style::default().fg(Color::Red),
+        Mode::Pins => Style::default().fg(Color::Cyan),
+        Mode::Bookmarks => Style::default().fg(Color::Cyan),
+        Mode::Errors => Style::default().fg(Color::Cyan),
+        Mode::Pods => Style::default().fg(Color::Cyan),
+        Mode::Detail => Style::default().fg(Color::Cyan),
+        Mode::Hex => Style::default().fg(Color::Cyan),
+        Mode::Sql => Style::default().fg(Color::Cyan),
+        Mode::Stats => Style::default().fg(Color::Cyan),
+        Mode::Exceptions => Style::default().fg(Color::Red),
+        Mode::Gaps => Style::default().fg(Color::Cyan),
+        Mode::Messages => Style::default().fg(Color::Cyan),
+        Mode::Help => Style::default().fg(Color::Cyan),
     };
 
+    // Size and elapsed time of the selection, updated as it grows
+    let selection_label = app.selection_summary().map(|summary| {
+        let mut label = format!(
+            "{} line{}, {}",
+            summary.lines,
+            if summary.lines == 1 { "" } else { "s" },
+            format_size(summary.bytes)
+        );
+        if let Some(span) = summary.span {
+            label.push_str(&format!(", Δ {}", format_duration(span)));
+        }
+        label
+    });
+
     let status_text = if !app.status_message.is_empty() {
-        format!("[{}] {}", mode_name, app.status_message)
+        // Messages queued behind the one shown
+        let more = match app.notifications.more() {
+            0 => String::new(),
+            more => format!(" (+{} more)", more),
+        };
+        match &selection_label {
+            Some(label) => format!("[{}] {}{} | {}", mode_name, app.status_message, more, label),
+            None => format!("[{}] {}{}", mode_name, app.status_message, more),
+        }
     } else {
         // Build status parts
         let mut parts = vec![format!("[{}]", mode_name)];
@@ -457,13 +1109,31 @@ fn draw_status_bar(frame: &mut Frame, app: &App, area: ratatui::layout::Rect) {
             app.filtered_len()
         ));
 
+        // File followed for --follow-latest
+        if let Some(name) = app.latest_file_name() {
+            parts.push(format!("Following {}", name));
+        }
+
         // Search status if active
-        if let Some(query) = app.get_search_query() {
-            if let Some(match_display) = app.current_match_display() {
-                parts.push(format!("Search: '{}' {}", query, match_display));
-            } else {
-                parts.push(format!("Search: '{}' (0 matches)", query));
+        if let Some(query) = app.search_breadcrumb() {
+            let mut search_part = match app.current_match_display() {
+                Some(match_display) => format!("Search: {} {}", query, match_display),
+                None if app.search_progress().is_some() => {
+                    format!("Search: {} (counting)", query)
+                }
+                None => format!("Search: {} (0 matches)", query),
+            };
+            if let Some(range) = app.search_range_label() {
+                search_part.push_str(&format!(" in {}", range));
+            }
+            if let Some(percent) = app.search_progress() {
+                search_part.push_str(&format!(" searching {}% (Esc: stop)", percent));
             }
+            parts.push(search_part);
+        }
+
+        if let Some(label) = selection_label {
+            parts.push(label);
         }
 
         parts.push(help_text.to_string());
@@ -471,22 +1141,24 @@ fn draw_status_bar(frame: &mut Frame, app: &App, area: ratatui::layout::Rect) {
         parts.join(" | ")
     };
 
+    let status_style = match app.status_severity() {
+        _ if app.status_message.is_empty() => mode_style,
+        NoticeSeverity::Error => Style::default().fg(Color::Red),
+        NoticeSeverity::Warning => Style::default().fg(Color::Yellow),
+        NoticeSeverity::Info => mode_style,
+    };
     let status_bar = Paragraph::new(status_text)
         .block(Block::default().borders(Borders::ALL))
-        .style(mode_style);
+        .style(status_style.bg(app.theme.chrome.status_bg));
     frame.render_widget(status_bar, area);
 }
 
-fn draw_loading_screen(frame: &mut Frame, current: usize, total: usize, entries: usize) {
+fn draw_loading_screen(frame: &mut Frame, current: usize, total: usize, progress: &LoadProgress) {
     let area = frame.size();
 
-    let progress_pct = if total > 0 {
-        (current * 100) / total
-    } else {
-        0
-    };
+    let progress_pct = (current * 100).checked_div(total).unwrap_or(0);
 
-    let loading_text = Text::from(vec![
+    let mut loading_text = Text::from(vec![
         Line::from(vec![Span::styled(
             "Loading qlog...",
             Style::default()
@@ -503,7 +1175,10 @@ fn draw_loading_screen(frame: &mut Frame, current: usize, total: usize, entries:
         ]),
         Line::from(vec![
             Span::raw("Entries: "),
-            Span::styled(entries.to_string(), Style::default().fg(Color::Yellow)),
+            Span::styled(
+                progress.lines.to_string(),
+                Style::default().fg(Color::Yellow),
+            ),
         ]),
         Line::from(""),
         Line::from(vec![
@@ -529,6 +1204,25 @@ fn draw_loading_screen(frame: &mut Frame, current: usize, total: usize, entries:
             Style::default().fg(Color::Gray),
         )]),
     ]);
+    // Stages of the current file, in the order they run
+    let stages = [
+        ("Decompressing: ", progress.decompressed),
+        ("Indexing: ", progress.indexed),
+    ];
+    for (row, (label, (done, size))) in stages
+        .into_iter()
+        .filter_map(|(label, stage)| Some((label, stage?)))
+        .enumerate()
+    {
+        let pct = (done * 100).checked_div(size).unwrap_or(0);
+        loading_text.lines.insert(
+            4 + row,
+            Line::from(vec![
+                Span::raw(label),
+                Span::styled(format!("{}%", pct), Style::default().fg(Color::Yellow)),
+            ]),
+        );
+    }
 
     let loading_paragraph = Paragraph::new(loading_text)
         .alignment(Alignment::Center)
@@ -546,6 +1240,16 @@ fn draw_loading_screen(frame: &mut Frame, current: usize, total: usize, entries:
 pub fn draw_filter_list(frame: &mut Frame, app: &App, area: Rect) {
     // Clear the area
     frame.render_widget(Clear, area);
+    // Entries start below the border, the header and its blank lines
+    let entries = area.inner(&Margin {
+        vertical: 1,
+        horizontal: 1,
+    });
+    app.filter_list_area.set(Rect {
+        y: entries.y + 3,
+        height: entries.height.saturating_sub(3),
+        ..entries
+    });
 
     // Build the filter list content
     let filter_list = &app.filters;
@@ -554,7 +1258,7 @@ pub fn draw_filter_list(frame: &mut Frame, app: &App, area: Rect) {
         Line::from(vec![
             Span::styled("Active Filters (", Style::default().fg(Color::Cyan)),
             Span::styled(
-                filter_list.len().to_string(),
+                app.filter_list_len().to_string(),
                 Style::default()
                     .fg(Color::Yellow)
                     .add_modifier(Modifier::BOLD),
@@ -564,7 +1268,7 @@ pub fn draw_filter_list(frame: &mut Frame, app: &App, area: Rect) {
         Line::from(""),
     ];
 
-    if filter_list.is_empty() {
+    if app.filter_list_len() == 0 {
         lines.push(Line::from(vec![Span::styled(
             "  No active filters",
             Style::default().fg(Color::DarkGray),
@@ -575,8 +1279,25 @@ pub fn draw_filter_list(frame: &mut Frame, app: &App, area: Rect) {
             let kind = rule.kind();
 
             let kind_style = match kind {
+                _ if !rule.is_enabled() => Style::default().fg(Color::DarkGray),
                 FilterKind::Include => Style::default().fg(Color::Green),
                 FilterKind::Exclude => Style::default().fg(Color::Red),
+                FilterKind::Dim => Style::default().fg(Color::DarkGray),
+            };
+            let pattern_style = if rule.is_enabled() {
+                Style::default().fg(Color::White)
+            } else {
+                Style::default()
+                    .fg(Color::DarkGray)
+                    .add_modifier(Modifier::CROSSED_OUT)
+            };
+            let scope = match &rule.file {
+                Some(file) => format!("  in {}", file),
+                None => String::new(),
+            };
+            let count = match app.filter_index.count(rule) {
+                Some(count) => format!("  ({} line{})", count, if count == 1 { "" } else { "s" }),
+                None => String::new(),
             };
 
             let prefix = if is_selected { ">" } else { " " };
@@ -584,6 +1305,7 @@ pub fn draw_filter_list(frame: &mut Frame, app: &App, area: Rect) {
             let kind_text = match kind {
                 FilterKind::Include => "INCLUDE",
                 FilterKind::Exclude => "EXCLUDE",
+                FilterKind::Dim => "DIM    ",
             };
 
             lines.push(Line::from(vec![
@@ -602,17 +1324,49 @@ pub fn draw_filter_list(frame: &mut Frame, app: &App, area: Rect) {
                     kind_style.add_modifier(Modifier::BOLD),
                 ),
                 Span::raw("  "),
-                Span::styled(rule.pattern(), Style::default().fg(Color::White)),
+                Span::styled(rule.pattern(), pattern_style),
+                Span::styled(scope, Style::default().fg(Color::Cyan)),
+                Span::styled(count, Style::default().fg(Color::DarkGray)),
             ]));
         }
     }
 
+    if !app.hidden.is_empty() {
+        let idx = filter_list.len();
+        let is_selected = idx == app.filter_list_selected;
+        lines.push(Line::from(vec![
+            Span::styled(
+                format!("{}{} ", if is_selected { ">" } else { " " }, idx + 1),
+                if is_selected {
+                    Style::default()
+                        .fg(Color::Yellow)
+                        .add_modifier(Modifier::BOLD)
+                } else {
+                    Style::default()
+                },
+            ),
+            Span::styled(
+                "HIDDEN",
+                Style::default()
+                    .fg(Color::Magenta)
+                    .add_modifier(Modifier::BOLD),
+            ),
+            Span::raw("   "),
+            Span::styled(
+                format!("manually hidden ({})", app.hidden.len()),
+                Style::default().fg(Color::White),
+            ),
+        ]));
+    }
+
     lines.push(Line::from(""));
     lines.push(Line::from(vec![
         Span::styled("j/k", Style::default().fg(Color::Yellow)),
         Span::raw(" navigate, "),
+        Span::styled("Space", Style::default().fg(Color::Yellow)),
+        Span::raw(" toggle, "),
         Span::styled("d", Style::default().fg(Color::Yellow)),
-        Span::raw(" delete, "),
+        Span::raw(" delete/restore, "),
         Span::styled("q", Style::default().fg(Color::Yellow)),
         Span::raw("/"),
         Span::styled("Esc", Style::default().fg(Color::Yellow)),
@@ -622,7 +1376,7 @@ pub fn draw_filter_list(frame: &mut Frame, app: &App, area: Rect) {
     let filter_block = Block::default()
         .title(" Filter List ")
         .borders(Borders::ALL)
-        .border_style(Style::default().fg(Color::Cyan));
+        .border_style(Style::default().fg(app.theme.chrome.overlay_border));
 
     let filter_paragraph = Paragraph::new(lines)
         .block(filter_block)
@@ -630,3 +1384,948 @@ pub fn draw_filter_list(frame: &mut Frame, app: &App, area: Rect) {
 
     frame.render_widget(filter_paragraph, area);
 }
+
+/// Draw pinned lines in a pane above the log view, without wrapping.
+fn draw_pinned_pane(frame: &mut Frame, app: &App, area: Rect) {
+    let lines: Vec<Line> = app
+        .pins
+        .iter()
+        .take(MAX_PINNED_ROWS)
+        .filter_map(|idx| {
+            let text = app.get_line(idx)?.as_str_lossy().into_owned();
+            let style = app.get_line_style(&text, detect_level(&text));
+            Some(Line::from(vec![
+                Span::styled(
+                    format!("{:>6} ", idx + 1),
+                    Style::default().fg(Color::DarkGray),
+                ),
+                Span::styled(text, style),
+            ]))
+        })
+        .collect();
+
+    let hidden = app.pins.len().saturating_sub(MAX_PINNED_ROWS);
+    let title = if hidden > 0 {
+        format!(" Pinned ({} more, :pins) ", hidden)
+    } else {
+        " Pinned ".to_string()
+    };
+    let pane = Paragraph::new(lines).block(
+        Block::default()
+            .title(title)
+            .borders(Borders::ALL)
+            .border_style(Style::default().fg(app.theme.chrome.overlay_border)),
+    );
+    frame.render_widget(pane, area);
+}
+
+/// Instructions of the current tutorial step below the log view.
+/// Draw the counts of the watch expressions, with the matches of the last
+/// minute under each while following.
+fn draw_watch_sidebar(frame: &mut Frame, app: &App, area: Rect) {
+    let now = std::time::Instant::now();
+    let inner_width = area.width.saturating_sub(2) as usize;
+    let mut lines: Vec<Line> = Vec::new();
+    for (pos, watch) in app.watches.iter().enumerate() {
+        let number = format!("{} ", pos + 1);
+        let count = watch.count().to_string();
+        let expr_width = inner_width.saturating_sub(number.len() + count.len() + 1);
+        let expr: String = watch.expr().chars().take(expr_width).collect();
+        lines.push(Line::from(vec![
+            Span::styled(number, Style::default().fg(Color::DarkGray)),
+            Span::styled(
+                format!("{:<width$} ", expr, width = expr_width),
+                Style::default().fg(Color::White),
+            ),
+            Span::styled(count, Style::default().add_modifier(Modifier::BOLD)),
+        ]));
+        let rate = watch.per_minute(now);
+        if app.is_following() && rate > 0 {
+            lines.push(Line::from(Span::styled(
+                format!("  +{}/min", rate),
+                Style::default().fg(Color::Yellow),
+            )));
+        }
+    }
+
+    let pane = Paragraph::new(lines).block(
+        Block::default()
+            .title(" Watch ")
+            .borders(Borders::ALL)
+            .border_style(Style::default().fg(app.theme.chrome.overlay_border)),
+    );
+    frame.render_widget(pane, area);
+}
+
+fn draw_tutor_pane(frame: &mut Frame, tutor: &Tutor, area: Rect) {
+    let step = tutor.current();
+    let mut lines: Vec<Line> = step.text.iter().map(|&text| Line::from(text)).collect();
+    lines.push(Line::from(Span::styled(
+        ":tutor skip moves on, :tutor off closes the tutorial",
+        Style::default().fg(Color::DarkGray),
+    )));
+    let title = format!(
+        " Tutorial {}/{}: {} ",
+        tutor.position() + 1,
+        STEPS.len(),
+        step.title
+    );
+    let pane = Paragraph::new(lines).block(
+        Block::default()
+            .title(title)
+            .borders(Borders::ALL)
+            .border_style(Style::default().fg(Color::Green)),
+    );
+    frame.render_widget(pane, area);
+}
+
+/// Draw the detail pane: one row per JSON field, folded values highlighted.
+fn draw_detail_pane(frame: &mut Frame, detail: &DetailView, border: Color, area: Rect) {
+    frame.render_widget(Clear, area);
+
+    let rows = detail.rows();
+    // Scroll so the cursor row stays inside the borders
+    let visible = area.height.saturating_sub(2) as usize;
+    let skip = (detail.cursor + 1).saturating_sub(visible);
+
+    let lines: Vec<Line> = rows
+        .iter()
+        .enumerate()
+        .skip(skip)
+        .take(visible)
+        .map(|(pos, row)| {
+            let mut spans = vec![Span::raw(" ".repeat(row.depth * 2))];
+            if let Some(key) = &row.key {
+                spans.push(Span::styled(key.clone(), Style::default().fg(Color::Cyan)));
+                spans.push(Span::raw(": "));
+            }
+            let text_style = if row.folded {
+                Style::default().fg(Color::Yellow)
+            } else {
+                Style::default()
+            };
+            spans.push(Span::styled(row.text.clone(), text_style));
+
+            let mut line = Line::from(spans);
+            if pos == detail.cursor {
+                line.patch_style(Style::default().bg(Color::DarkGray));
+            }
+            line
+        })
+        .collect();
+
+    let title = if detail.is_serilog() {
+        " Detail (Serilog) "
+    } else if detail.is_json() {
+        " Detail (JSON) "
+    } else {
+        " Detail "
+    };
+    let block = Block::default()
+        .title(title)
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(border));
+    let mut paragraph = Paragraph::new(lines).block(block);
+    if !detail.is_json() {
+        paragraph = paragraph.wrap(Wrap { trim: false });
+    }
+    frame.render_widget(paragraph, area);
+}
+
+/// Draw the :pins overlay for reordering and removing pins
+pub fn draw_pin_list(frame: &mut Frame, app: &App, area: Rect) {
+    frame.render_widget(Clear, area);
+
+    // Scroll so the selected pin stays inside the borders
+    let visible = area.height.saturating_sub(2) as usize;
+    let skip = (app.pin_list_selected + 1).saturating_sub(visible);
+
+    let mut lines: Vec<Line> = Vec::new();
+    if app.source().is_some() {
+        for (pos, idx) in app.pins.iter().enumerate().skip(skip) {
+            let is_selected = pos == app.pin_list_selected;
+            let prefix = if is_selected { ">" } else { " " };
+            let text = app
+                .get_line(idx)
+                .map(|line| line.as_str_lossy().into_owned())
+                .unwrap_or_default();
+            lines.push(Line::from(vec![
+                Span::styled(
+                    format!("{}{} ", prefix, pos + 1),
+                    if is_selected {
+                        Style::default()
+                            .fg(Color::Yellow)
+                            .add_modifier(Modifier::BOLD)
+                    } else {
+                        Style::default()
+                    },
+                ),
+                Span::styled(
+                    format!("line {:<7} ", idx + 1),
+                    Style::default().fg(Color::DarkGray),
+                ),
+                Span::styled(text, Style::default().fg(Color::White)),
+            ]));
+        }
+    }
+
+    let block = Block::default()
+        .title(" Pinned Lines ")
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(app.theme.chrome.overlay_border));
+    frame.render_widget(Paragraph::new(lines).block(block), area);
+}
+
+/// Draw the bookmark list, with each bookmark's note before its line
+pub fn draw_bookmark_list(frame: &mut Frame, app: &App, area: Rect) {
+    frame.render_widget(Clear, area);
+
+    // Scroll so the selected bookmark stays inside the borders
+    let visible = area.height.saturating_sub(2) as usize;
+    let skip = (app.bookmark_list_selected + 1).saturating_sub(visible);
+
+    let mut lines: Vec<Line> = Vec::new();
+    if app.source().is_some() {
+        for (pos, (idx, mark)) in app.bookmarks.iter().enumerate().skip(skip) {
+            let is_selected = pos == app.bookmark_list_selected;
+            let prefix = if is_selected { ">" } else { " " };
+            let text = app
+                .get_line(idx)
+                .map(|line| line.as_str_lossy().into_owned())
+                .unwrap_or_default();
+            let mut spans = vec![
+                Span::styled(
+                    format!("{}{} ", prefix, pos + 1),
+                    if is_selected {
+                        Style::default()
+                            .fg(Color::Yellow)
+                            .add_modifier(Modifier::BOLD)
+                    } else {
+                        Style::default()
+                    },
+                ),
+                Span::styled(
+                    format!("line {:<7} ", idx + 1),
+                    Style::default().fg(Color::DarkGray),
+                ),
+            ];
+            if let Some(note) = &mark.note {
+                spans.push(Span::styled(
+                    format!("[{}] ", note),
+                    Style::default().fg(Color::Magenta),
+                ));
+            }
+            spans.push(Span::styled(text, Style::default().fg(Color::White)));
+            lines.push(Line::from(spans));
+        }
+    }
+
+    let block = Block::default()
+        .title(format!(" Bookmarks ({}) ", app.bookmarks.len()))
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(app.theme.chrome.overlay_border));
+    frame.render_widget(Paragraph::new(lines).block(block), area);
+}
+
+/// Draw the :errors list of compiler and test-runner diagnostics
+pub fn draw_error_list(frame: &mut Frame, app: &App, area: Rect) {
+    frame.render_widget(Clear, area);
+    let entries = app.quickfix.as_deref().unwrap_or_default();
+
+    // Scroll so the selected entry stays inside the borders
+    let visible = area.height.saturating_sub(2) as usize;
+    let skip = (app.quickfix_selected + 1).saturating_sub(visible);
+
+    let lines: Vec<Line> = entries
+        .iter()
+        .enumerate()
+        .skip(skip)
+        .map(|(pos, entry)| {
+            let is_selected = pos == app.quickfix_selected;
+            let prefix = if is_selected { ">" } else { " " };
+            let severity_style = match entry.severity {
+                Severity::Error => Style::default().fg(Color::Red),
+                Severity::Warning => Style::default().fg(Color::Yellow),
+            };
+            Line::from(vec![
+                Span::styled(
+                    format!("{}{} ", prefix, pos + 1),
+                    if is_selected {
+                        Style::default()
+                            .fg(Color::Yellow)
+                            .add_modifier(Modifier::BOLD)
+                    } else {
+                        Style::default()
+                    },
+                ),
+                Span::styled(format!("{:<8}", entry.severity.name()), severity_style),
+                Span::styled(
+                    format!("{} ", entry.location()),
+                    Style::default().fg(Color::Cyan),
+                ),
+                Span::styled(entry.message.clone(), Style::default().fg(Color::White)),
+            ])
+        })
+        .collect();
+
+    let block = Block::default()
+        .title(format!(" Errors ({}) ", entries.len()))
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(app.theme.chrome.overlay_border));
+    frame.render_widget(Paragraph::new(lines).block(block), area);
+}
+
+/// Draw the :messages log of status messages, newest last
+pub fn draw_message_list(frame: &mut Frame, app: &App, area: Rect) {
+    frame.render_widget(Clear, area);
+    let log = app.notifications.log();
+
+    // Scroll so the selected message stays inside the borders
+    let visible = area.height.saturating_sub(2) as usize;
+    let skip = (app.message_list_selected + 1).saturating_sub(visible);
+
+    let lines: Vec<Line> = log
+        .iter()
+        .enumerate()
+        .skip(skip)
+        .map(|(pos, notification)| {
+            let is_selected = pos == app.message_list_selected;
+            let prefix = if is_selected { ">" } else { " " };
+            let severity_style = match notification.severity {
+                NoticeSeverity::Error => Style::default().fg(Color::Red),
+                NoticeSeverity::Warning => Style::default().fg(Color::Yellow),
+                NoticeSeverity::Info => Style::default().fg(Color::Green),
+            };
+            Line::from(vec![
+                Span::styled(
+                    format!("{}{} ", prefix, notification.time.format("%H:%M:%S")),
+                    if is_selected {
+                        Style::default()
+                            .fg(Color::Yellow)
+                            .add_modifier(Modifier::BOLD)
+                    } else {
+                        Style::default().fg(Color::DarkGray)
+                    },
+                ),
+                Span::styled(
+                    format!("{:<8}", notification.severity.name()),
+                    severity_style,
+                ),
+                Span::styled(notification.text.clone(), Style::default().fg(Color::White)),
+            ])
+        })
+        .collect();
+
+    let block = Block::default()
+        .title(format!(" Messages ({}) ", log.len()))
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(app.theme.chrome.overlay_border));
+    frame.render_widget(Paragraph::new(lines).block(block), area);
+}
+
+/// Draw the :pods list with each pod's line rate and state
+pub fn draw_pod_list(frame: &mut Frame, app: &App, area: Rect) {
+    frame.render_widget(Clear, area);
+    let now = std::time::Instant::now();
+
+    // Scroll so the selected pod stays inside the borders
+    let visible = area.height.saturating_sub(2) as usize;
+    let skip = (app.pod_list_selected + 1).saturating_sub(visible);
+    let name_width = app.pods.iter().map(|pod| pod.name.len()).max().unwrap_or(0);
+
+    let lines: Vec<Line> = app
+        .pods
+        .iter()
+        .enumerate()
+        .skip(skip)
+        .map(|(pos, pod)| {
+            let is_selected = pos == app.pod_list_selected;
+            let prefix = if is_selected { ">" } else { " " };
+            let (state, state_style) = if pod.paused {
+                (
+                    format!("paused ({} held)", pod.held()),
+                    Style::default().fg(Color::Yellow),
+                )
+            } else if app.is_pod_muted(&pod.name) {
+                ("muted".to_string(), Style::default().fg(Color::DarkGray))
+            } else {
+                ("live".to_string(), Style::default().fg(Color::Green))
+            };
+            Line::from(vec![
+                Span::styled(
+                    format!("{} ", prefix),
+                    if is_selected {
+                        Style::default()
+                            .fg(Color::Yellow)
+                            .add_modifier(Modifier::BOLD)
+                    } else {
+                        Style::default()
+                    },
+                ),
+                Span::styled(
+                    format!(" {:<width$} ", pod.name, width = name_width),
+                    Style::default().fg(Color::Black).bg(pod.color),
+                ),
+                Span::styled(
+                    format!(" {:>8} lines {:>7.1}/s  ", pod.lines, pod.rate(now)),
+                    Style::default().fg(Color::White),
+                ),
+                Span::styled(state, state_style),
+            ])
+        })
+        .collect();
+
+    let block = Block::default()
+        .title(format!(" Pods ({}) ", app.pods.len()))
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(app.theme.chrome.overlay_border));
+    frame.render_widget(Paragraph::new(lines).block(block), area);
+}
+
+/// Style of a byte in the hex dump by its class.
+fn byte_style(class: ByteClass) -> Style {
+    match class {
+        ByteClass::Printable => Style::default(),
+        ByteClass::Whitespace => Style::default().fg(Color::DarkGray),
+        ByteClass::Control => Style::default().fg(Color::Yellow),
+        ByteClass::NonAscii => Style::default().fg(Color::Cyan),
+        ByteClass::Invalid => Style::default()
+            .fg(Color::Red)
+            .add_modifier(Modifier::BOLD | Modifier::REVERSED),
+    }
+}
+
+/// Draw the hex dump of a line: offset, hex bytes and ASCII column, below
+/// a summary of its size, terminator and unusual bytes.
+fn draw_hex_pane(frame: &mut Frame, hex: &HexView, border: Color, area: Rect) {
+    frame.render_widget(Clear, area);
+    let visible = area.height.saturating_sub(3) as usize;
+    let skip = hex.scroll.min(hex.rows().saturating_sub(visible.max(1)));
+
+    let mut lines = vec![Line::from(Span::styled(
+        hex.summary(),
+        Style::default().fg(Color::White),
+    ))];
+    for row in (skip..hex.rows()).take(visible) {
+        let (address, range) = hex.row(row);
+        let mut spans = vec![Span::styled(
+            format!("{:08x}  ", address),
+            Style::default().fg(Color::DarkGray),
+        )];
+        for slot in 0..BYTES_PER_ROW {
+            let gap = if slot == BYTES_PER_ROW / 2 { "  " } else { " " };
+            if slot > 0 {
+                spans.push(Span::raw(gap));
+            }
+            match range.clone().nth(slot) {
+                Some(pos) => spans.push(Span::styled(
+                    format!("{:02x}", hex.bytes()[pos]),
+                    byte_style(hex.class(pos)),
+                )),
+                None => spans.push(Span::raw("  ")),
+            }
+        }
+        spans.push(Span::styled("  |", Style::default().fg(Color::DarkGray)));
+        for pos in range {
+            let byte = hex.bytes()[pos];
+            let shown = if byte.is_ascii_graphic() || byte == b' ' {
+                byte as char
+            } else {
+                '.'
+            };
+            spans.push(Span::styled(shown.to_string(), byte_style(hex.class(pos))));
+        }
+        spans.push(Span::styled("|", Style::default().fg(Color::DarkGray)));
+        lines.push(Line::from(spans));
+    }
+
+    let title = match hex.offset() {
+        Some(offset) => format!(" Hex: {} @ {:#x} ", hex.source, offset),
+        None => format!(" Hex: {} ", hex.source),
+    };
+    let block = Block::default()
+        .title(title)
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(border));
+    frame.render_widget(Paragraph::new(lines).block(block), area);
+}
+
+/// Widest a column of the SQL overlay gets before its values are cut.
+const MAX_SQL_COLUMN_WIDTH: usize = 40;
+
+/// Draw the result of a `:sql` query as a table
+pub fn draw_sql_result(frame: &mut Frame, app: &App, area: Rect) {
+    frame.render_widget(Clear, area);
+    let Some(view) = &app.sql_view else {
+        return;
+    };
+    let result = &view.result;
+
+    // Header plus rows; scroll so the selected row stays inside the borders
+    let visible = area.height.saturating_sub(3) as usize;
+    let skip = (view.selected + 1).saturating_sub(visible);
+    let widths: Vec<usize> = (0..result.columns.len())
+        .map(|col| {
+            result
+                .rows
+                .iter()
+                .filter_map(|row| row.get(col))
+                .chain([&result.columns[col]])
+                .map(|cell| cell.chars().count())
+                .max()
+                .unwrap_or(0)
+                .min(MAX_SQL_COLUMN_WIDTH)
+        })
+        .collect();
+    let cells = |row: &[String]| {
+        row.iter()
+            .zip(&widths)
+            .map(|(cell, &width)| {
+                let cell: String = cell.chars().take(width).collect();
+                format!("{:<width$}", cell, width = width)
+            })
+            .collect::<Vec<_>>()
+            .join("  ")
+    };
+
+    let mut lines = vec![Line::from(Span::styled(
+        format!("  {}", cells(&result.columns)),
+        Style::default()
+            .fg(Color::Cyan)
+            .add_modifier(Modifier::BOLD),
+    ))];
+    for (pos, row) in result.rows.iter().enumerate().skip(skip) {
+        let is_selected = pos == view.selected;
+        lines.push(Line::from(Span::styled(
+            format!("{} {}", if is_selected { ">" } else { " " }, cells(row)),
+            if is_selected {
+                Style::default()
+                    .fg(Color::Yellow)
+                    .add_modifier(Modifier::BOLD)
+            } else {
+                Style::default().fg(Color::White)
+            },
+        )));
+    }
+
+    let block = Block::default()
+        .title(format!(" SQL: {} ", view.query))
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(app.theme.chrome.overlay_border));
+    frame.render_widget(Paragraph::new(lines).block(block), area);
+}
+
+/// Width of the longest histogram bar in the stats overlay.
+const STATS_BAR_WIDTH: usize = 24;
+
+/// Longest context name shown in the stats overlay.
+const MAX_STATS_LABEL_WIDTH: usize = 40;
+
+/// Draw the `:stats` overlay: level counts, top contexts and a histogram.
+pub fn draw_stats(frame: &mut Frame, app: &App, area: Rect) {
+    frame.render_widget(Clear, area);
+    let Some(view) = &app.stats_view else {
+        return;
+    };
+
+    let busiest = view
+        .rows
+        .iter()
+        .filter(|row| matches!(row, StatsRow::Bucket { .. }))
+        .map(StatsRow::count)
+        .max()
+        .unwrap_or(1);
+    let visible = area.height.saturating_sub(2) as usize;
+    let skip = (view.selected + 1).saturating_sub(visible);
+
+    let mut lines = Vec::new();
+    for (pos, row) in view.rows.iter().enumerate().skip(skip) {
+        let (kind, label, style) = match row {
+            StatsRow::Level { level, .. } => (
+                "Level",
+                level.name().to_string(),
+                app.theme.gutter_style(*level),
+            ),
+            StatsRow::Context { name, .. } => (
+                "Context",
+                name.chars().take(MAX_STATS_LABEL_WIDTH).collect(),
+                Style::default().fg(Color::White),
+            ),
+            StatsRow::Bucket { from, count, .. } => (
+                "Time",
+                format!(
+                    "{} {:<width$}",
+                    from.format("%m-%d %H:%M:%S"),
+                    "█".repeat((count * STATS_BAR_WIDTH).div_ceil(busiest)),
+                    width = STATS_BAR_WIDTH
+                ),
+                Style::default().fg(Color::Cyan),
+            ),
+        };
+        let is_selected = pos == view.selected;
+        let style = if is_selected {
+            style.add_modifier(Modifier::BOLD | Modifier::REVERSED)
+        } else {
+            style
+        };
+        lines.push(Line::from(vec![
+            Span::raw(if is_selected { "> " } else { "  " }),
+            Span::styled(format!("{:<8}", kind), Style::default().fg(Color::Gray)),
+            Span::styled(
+                format!("{:<width$}", label, width = MAX_STATS_LABEL_WIDTH),
+                style,
+            ),
+            Span::styled(
+                format!(" {:>9}", row.count()),
+                Style::default().fg(Color::White),
+            ),
+        ]));
+    }
+
+    let block = Block::default()
+        .title(format!(" Stats of {} lines ", app.filtered_len()))
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(app.theme.chrome.overlay_border));
+    frame.render_widget(Paragraph::new(lines).block(block), area);
+}
+
+/// Width of the longest bar in the gaps overlay.
+const GAPS_BAR_WIDTH: usize = 40;
+
+/// Draw the `:gaps` overlay: min, median, p95 and max of the gaps, then a
+/// bar per bucket.
+pub fn draw_gaps(frame: &mut Frame, app: &App, area: Rect) {
+    frame.render_widget(Clear, area);
+    let Some(view) = &app.gaps_view else {
+        return;
+    };
+    let gaps = &view.gaps;
+
+    let label = Style::default().fg(Color::Gray);
+    let value = Style::default()
+        .fg(Color::White)
+        .add_modifier(Modifier::BOLD);
+    let mut summary = Vec::new();
+    for (name, duration) in [
+        ("min", gaps.min),
+        ("median", gaps.median),
+        ("p95", gaps.p95),
+        ("max", gaps.max),
+    ] {
+        summary.push(Span::styled(format!("  {} ", name), label));
+        summary.push(Span::styled(format_duration(duration), value));
+    }
+    let mut lines = vec![Line::from(summary)];
+
+    let tallest = gaps
+        .buckets
+        .iter()
+        .map(|bucket| bucket.count)
+        .max()
+        .unwrap_or(1)
+        .max(1);
+    for bucket in &gaps.buckets {
+        lines.push(Line::from(vec![
+            Span::styled(format!("  {:>8} ", bucket.label()), label),
+            Span::styled(
+                format!(
+                    "{:<width$}",
+                    "█".repeat((bucket.count * GAPS_BAR_WIDTH).div_ceil(tallest)),
+                    width = GAPS_BAR_WIDTH
+                ),
+                Style::default().fg(Color::Cyan),
+            ),
+            Span::styled(
+                format!(" {:>9}", bucket.count),
+                Style::default().fg(Color::White),
+            ),
+        ]));
+    }
+
+    let block = Block::default()
+        .title(format!(
+            " Gaps between {} {} ",
+            gaps.count + 1,
+            view.subject
+        ))
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(app.theme.chrome.overlay_border));
+    frame.render_widget(Paragraph::new(lines).block(block), area);
+}
+
+/// Longest exception type shown in the exceptions overlay.
+const MAX_EXCEPTION_NAME_WIDTH: usize = 48;
+
+/// Draw the `:exceptions` overlay: a row per exception type and top frame
+/// with its count and when it was first and last seen.
+pub fn draw_exceptions(frame: &mut Frame, app: &App, area: Rect) {
+    frame.render_widget(Clear, area);
+    let Some(view) = &app.exceptions_view else {
+        return;
+    };
+
+    let name_width = view
+        .groups
+        .iter()
+        .map(|group| group.name.chars().count())
+        .max()
+        .unwrap_or(0)
+        .min(MAX_EXCEPTION_NAME_WIDTH);
+    let visible = area.height.saturating_sub(2) as usize;
+    let skip = (view.selected + 1).saturating_sub(visible);
+
+    let mut lines = Vec::new();
+    for (pos, group) in view.groups.iter().enumerate().skip(skip) {
+        let is_selected = pos == view.selected;
+        let name_style = if is_selected {
+            Style::default()
+                .fg(Color::Red)
+                .add_modifier(Modifier::BOLD | Modifier::REVERSED)
+        } else {
+            Style::default().fg(Color::Red)
+        };
+        let seen = match (group.first_seen, group.last_seen) {
+            (Some(first), Some(last)) if first == last => first.format("%H:%M:%S").to_string(),
+            (Some(first), Some(last)) => stats::time_range_label(first, last),
+            _ => String::new(),
+        };
+        let name: String = group.name.chars().take(name_width).collect();
+        let mut spans = vec![
+            Span::raw(if is_selected { "> " } else { "  " }),
+            Span::styled(
+                format!("{:>6}  ", group.count()),
+                Style::default().fg(Color::White),
+            ),
+            Span::styled(format!("{:<width$}", name, width = name_width), name_style),
+            Span::styled(format!("  {}", seen), Style::default().fg(Color::Cyan)),
+        ];
+        if let Some(frame_text) = &group.frame {
+            spans.push(Span::styled(
+                format!("  at {}", frame_text),
+                Style::default().fg(Color::DarkGray),
+            ));
+        }
+        lines.push(Line::from(spans));
+    }
+
+    let total: usize = view.groups.iter().map(ExceptionGroup::count).sum();
+    let block = Block::default()
+        .title(format!(
+            " Exceptions: {} in {} groups ",
+            total,
+            view.groups.len()
+        ))
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(app.theme.chrome.overlay_border));
+    frame.render_widget(Paragraph::new(lines).block(block), area);
+}
+
+/// Draw the recently opened files overlay
+pub fn draw_recent_list(frame: &mut Frame, app: &App, area: Rect) {
+    frame.render_widget(Clear, area);
+
+    let mut lines: Vec<Line> = Vec::new();
+
+    if app.recent.is_empty() {
+        lines.push(Line::from(vec![Span::styled(
+            "  No recent files",
+            Style::default().fg(Color::DarkGray),
+        )]));
+    } else {
+        for (idx, entry) in app.recent.entries().iter().enumerate() {
+            let is_selected = idx == app.recent_selected;
+            let prefix = if is_selected { ">" } else { " " };
+            let label_style = if is_selected {
+                Style::default()
+                    .fg(Color::Yellow)
+                    .add_modifier(Modifier::BOLD)
+            } else {
+                Style::default().fg(Color::White)
+            };
+
+            lines.push(Line::from(vec![
+                Span::styled(format!("{}{} ", prefix, idx + 1), label_style),
+                Span::styled(
+                    entry.opened_at.format("%Y-%m-%d %H:%M").to_string(),
+                    Style::default().fg(Color::Cyan),
+                ),
+                Span::raw("  "),
+                Span::styled(entry.label(), label_style),
+            ]));
+        }
+    }
+
+    lines.push(Line::from(""));
+    lines.push(Line::from(vec![
+        Span::styled("j/k", Style::default().fg(Color::Yellow)),
+        Span::raw(" navigate, "),
+        Span::styled("Enter", Style::default().fg(Color::Yellow)),
+        Span::raw(" open, "),
+        Span::styled("q", Style::default().fg(Color::Yellow)),
+        Span::raw("/"),
+        Span::styled("Esc", Style::default().fg(Color::Yellow)),
+        Span::raw(" close"),
+    ]));
+
+    let block = Block::default()
+        .title(" Recent Files ")
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(app.theme.chrome.overlay_border));
+
+    frame.render_widget(Paragraph::new(lines).block(block), area);
+}
+
+/// Draw the prompt asking how oversized files should be indexed
+/// Transient popup describing the file set that just finished loading.
+fn draw_load_summary(frame: &mut Frame, app: &App, area: Rect) {
+    let Some(summary) = &app.load_summary else {
+        return;
+    };
+    let label_style = Style::default().fg(Color::Gray);
+    let row = |label: &str, value: String, value_style: Style| {
+        Line::from(vec![
+            Span::styled(format!("  {:<10}", label), label_style),
+            Span::styled(value, value_style),
+        ])
+    };
+
+    let mut files = summary.files_loaded.to_string();
+    if summary.files_failed > 0 {
+        files.push_str(&format!(" ({} failed)", summary.files_failed));
+    }
+    if summary.files_partial > 0 {
+        files.push_str(&format!(" ({} tail/on-demand)", summary.files_partial));
+    }
+
+    let mut lines = vec![
+        row("Files", files, Style::default().fg(Color::White)),
+        row(
+            "Lines",
+            summary.total_lines.to_string(),
+            Style::default().fg(Color::White),
+        ),
+        row(
+            "Formats",
+            summary.formats_label(),
+            Style::default().fg(Color::White),
+        ),
+    ];
+    lines.push(match (summary.time_span, summary.duration()) {
+        (Some((first, last)), Some(duration)) => row(
+            "Time span",
+            format!(
+                "{} - {} ({})",
+                first.format("%Y-%m-%d %H:%M:%S"),
+                last.format("%Y-%m-%d %H:%M:%S"),
+                format_duration(duration)
+            ),
+            Style::default().fg(Color::Cyan),
+        ),
+        _ => row(
+            "Time span",
+            "no timestamps".to_string(),
+            Style::default().fg(Color::DarkGray),
+        ),
+    });
+    lines.push(match summary.levels {
+        Some(levels) => Line::from(vec![
+            Span::styled(format!("  {:<10}", "Levels"), label_style),
+            Span::styled(
+                format!("{} errors", levels.errors),
+                Style::default().fg(Color::Red).add_modifier(Modifier::BOLD),
+            ),
+            Span::raw(", "),
+            Span::styled(
+                format!("{} warnings", levels.warnings),
+                Style::default().fg(Color::Yellow),
+            ),
+        ]),
+        None => row(
+            "Levels",
+            "not counted (on-demand index)".to_string(),
+            Style::default().fg(Color::DarkGray),
+        ),
+    });
+    lines.push(Line::from(""));
+    lines.push(Line::from(Span::styled(
+        "  Press any key to dismiss",
+        Style::default().fg(Color::DarkGray),
+    )));
+
+    let width = lines
+        .iter()
+        .map(|line| line.width() as u16 + 4)
+        .max()
+        .unwrap_or(0)
+        .min(area.width);
+    let height = (lines.len() as u16 + 2).min(area.height);
+    let popup = Rect {
+        x: area.x + (area.width - width) / 2,
+        y: area.y + (area.height - height) / 2,
+        width,
+        height,
+    };
+
+    frame.render_widget(Clear, popup);
+    frame.render_widget(
+        Paragraph::new(lines).block(
+            Block::default()
+                .title(" Loaded ")
+                .borders(Borders::ALL)
+                .border_style(Style::default().fg(app.theme.chrome.overlay_border)),
+        ),
+        popup,
+    );
+}
+
+/// Draw the prompt overlay: what is at stake, then the choices with their
+/// keys, the highlighted one marked
+pub fn draw_prompt(frame: &mut Frame, app: &App, area: Rect) {
+    frame.render_widget(Clear, area);
+
+    let Some(prompt) = &app.prompt else {
+        return;
+    };
+    let key_style = Style::default()
+        .fg(Color::Yellow)
+        .add_modifier(Modifier::BOLD);
+
+    let mut lines: Vec<Line> = prompt
+        .message
+        .iter()
+        .map(|line| {
+            Line::from(Span::styled(
+                format!("  {}", line),
+                Style::default().fg(Color::White),
+            ))
+        })
+        .collect();
+    if !lines.is_empty() {
+        lines.push(Line::from(""));
+    }
+    for (pos, choice) in prompt.choices.iter().enumerate() {
+        let is_selected = pos == prompt.selected;
+        let label_style = if is_selected {
+            Style::default().add_modifier(Modifier::REVERSED)
+        } else {
+            Style::default()
+        };
+        lines.push(Line::from(vec![
+            Span::raw(if is_selected { "> " } else { "  " }),
+            Span::styled(choice.key.to_string(), key_style),
+            Span::raw("  "),
+            Span::styled(choice.label.clone(), label_style),
+        ]));
+    }
+    lines.push(Line::from(vec![
+        Span::styled("  Esc", key_style),
+        Span::raw(" Cancel"),
+    ]));
+
+    let block = Block::default()
+        .title(prompt.title.clone())
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(Color::Red));
+
+    frame.render_widget(Paragraph::new(lines).block(block), area);
+}
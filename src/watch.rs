@@ -0,0 +1,247 @@
+//! Watch expressions (`:watch`).
+//!
+//! A watch counts the lines matching an expression and keeps counting as
+//! followed lines arrive; the counts are shown in a sidebar next to the log
+//! view, with how many matches arrived in the last minute. An expression is
+//! text matched like a filter (case-insensitive, with `unless` exceptions),
+//! in quotes if it has spaces or an `=`, or `key=value`, which matches
+//! `key=value` pairs and JSON lines whose field `key` has that value.
+
+use std::collections::VecDeque;
+use std::ops::Range;
+use std::time::{Duration, Instant};
+
+use crate::detail::parse_json;
+use crate::export::json_field;
+use crate::model::{FilterKind, FilterRule};
+use crate::source::LineSource;
+
+/// Window of the arrival rate shown next to each count.
+const RATE_WINDOW: Duration = Duration::from_secs(60);
+
+/// One watched expression and its count.
+#[derive(Debug, Clone)]
+pub struct Watch {
+    expr: String,
+    rule: FilterRule,
+    /// Field and value of a `key=value` expression
+    field: Option<(String, String)>,
+    count: usize,
+    /// Matches among appended lines, by when they arrived
+    arrivals: VecDeque<(Instant, usize)>,
+}
+
+impl Watch {
+    /// Parse an expression; None if it is empty.
+    pub fn parse(expr: &str) -> Option<Self> {
+        let expr = expr.trim();
+        let quoted = ['"', '\'']
+            .iter()
+            .find_map(|&quote| expr.strip_prefix(quote)?.strip_suffix(quote));
+        let text = quoted.unwrap_or(expr);
+        if text.is_empty() {
+            return None;
+        }
+        let field = match quoted {
+            Some(_) => None,
+            None => text
+                .split_once('=')
+                .filter(|(key, value)| {
+                    !key.is_empty() && !value.is_empty() && !key.contains(char::is_whitespace)
+                })
+                .map(|(key, value)| (key.to_string(), value.trim_matches('"').to_lowercase())),
+        };
+        Some(Self {
+            expr: expr.to_string(),
+            rule: FilterRule::new(text, FilterKind::Include),
+            field,
+            count: 0,
+            arrivals: VecDeque::new(),
+        })
+    }
+
+    /// The expression as typed.
+    pub fn expr(&self) -> &str {
+        &self.expr
+    }
+
+    /// Lines matched so far.
+    pub fn count(&self) -> usize {
+        self.count
+    }
+
+    /// Whether a line matches the expression.
+    pub fn matches(&self, text: &str) -> bool {
+        if self.rule.matches(text.as_bytes()) {
+            return true;
+        }
+        let Some((key, value)) = &self.field else {
+            return false;
+        };
+        // Parse only lines that contain the value somewhere
+        if !text.to_lowercase().contains(value.as_str()) {
+            return false;
+        }
+        parse_json(text)
+            .and_then(|(_, json)| json_field(&json, key))
+            .is_some_and(|field| field.eq_ignore_ascii_case(value))
+    }
+
+    /// Count the matching lines of `source` again from scratch.
+    pub fn recount(&mut self, source: &dyn LineSource) {
+        self.count = self.scan(source, 0..source.len());
+        self.arrivals.clear();
+    }
+
+    /// Count the matches among lines `range` of `source`, appended at `now`.
+    pub fn append(&mut self, source: &dyn LineSource, range: Range<usize>, now: Instant) {
+        let matched = self.scan(source, range);
+        self.count += matched;
+        self.expire(now);
+        if matched > 0 {
+            self.arrivals.push_back((now, matched));
+        }
+    }
+
+    /// Matches that arrived in the last minute before `now`.
+    pub fn per_minute(&self, now: Instant) -> usize {
+        self.arrivals
+            .iter()
+            .filter(|(at, _)| now.duration_since(*at) < RATE_WINDOW)
+            .map(|(_, matched)| matched)
+            .sum()
+    }
+
+    fn expire(&mut self, now: Instant) {
+        while self
+            .arrivals
+            .front()
+            .is_some_and(|(at, _)| now.duration_since(*at) >= RATE_WINDOW)
+        {
+            self.arrivals.pop_front();
+        }
+    }
+
+    fn scan(&self, source: &dyn LineSource, range: Range<usize>) -> usize {
+        range
+            .filter_map(|idx| source.get_line(idx))
+            .filter(|line| self.matches(&line.as_str_lossy()))
+            .count()
+    }
+}
+
+/// The watches of a file set, in the order they were added.
+#[derive(Debug, Clone, Default)]
+pub struct Watches {
+    list: Vec<Watch>,
+}
+
+impl Watches {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a watch counted over `source`. Returns false if the expression
+    /// is watched already.
+    pub fn add(&mut self, mut watch: Watch, source: Option<&dyn LineSource>) -> bool {
+        if self.list.iter().any(|w| w.expr == watch.expr) {
+            return false;
+        }
+        if let Some(source) = source {
+            watch.recount(source);
+        }
+        self.list.push(watch);
+        true
+    }
+
+    /// Remove the watch with 1-based number `target`, or with the
+    /// expression `target`.
+    pub fn remove(&mut self, target: &str) -> Option<Watch> {
+        let pos = match target.parse::<usize>() {
+            Ok(number) => number.checked_sub(1).filter(|&pos| pos < self.list.len()),
+            Err(_) => self.list.iter().position(|w| w.expr == target.trim()),
+        }?;
+        Some(self.list.remove(pos))
+    }
+
+    pub fn clear(&mut self) {
+        self.list.clear();
+    }
+
+    /// Count every watch again over a newly loaded `source`.
+    pub fn recount(&mut self, source: &dyn LineSource) {
+        for watch in &mut self.list {
+            watch.recount(source);
+        }
+    }
+
+    /// Count the matches among lines `range` of `source`, just appended.
+    pub fn append(&mut self, source: &dyn LineSource, range: Range<usize>, now: Instant) {
+        for watch in &mut self.list {
+            watch.append(source, range.clone(), now);
+        }
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &Watch> {
+        self.list.iter()
+    }
+
+    pub fn len(&self) -> usize {
+        self.list.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.list.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::log_storage::storage_from;
+
+    #[test]
+    fn test_expressions_match_text_and_fields() {
+        let error = Watch::parse("\"ERROR\"").unwrap();
+        assert_eq!(error.expr(), "\"ERROR\"");
+        assert!(error.matches("10:00 error: boom"));
+        assert!(!error.matches("10:00 INFO ok"));
+
+        let status = Watch::parse("status=500").unwrap();
+        assert!(status.matches("method=GET status=500 ms=12"));
+        assert!(status.matches(r#"{"msg":"GET /","status":500}"#));
+        assert!(!status.matches(r#"{"msg":"GET /","status":200,"ms":500}"#));
+
+        // Quotes make `=` plain text
+        let quoted = Watch::parse("'a=b c'").unwrap();
+        assert!(quoted.matches("x a=b c y"));
+        assert!(!quoted.matches(r#"{"a":"b c"}"#));
+        assert!(Watch::parse(" \"\" ").is_none());
+    }
+
+    #[test]
+    fn test_counts_follow_appended_lines() {
+        let (_file, mut storage) = storage_from(&["ERROR one", "INFO two", "ERROR three"]);
+
+        let mut watches = Watches::new();
+        assert!(watches.add(Watch::parse("error").unwrap(), Some(&storage)));
+        assert!(!watches.add(Watch::parse("error").unwrap(), Some(&storage)));
+        assert!(watches.add(Watch::parse("info").unwrap(), Some(&storage)));
+        let counts: Vec<usize> = watches.iter().map(Watch::count).collect();
+        assert_eq!(counts, [2, 1]);
+
+        let start = Instant::now();
+        storage.append_line(0, b"ERROR four", None);
+        storage.append_line(0, b"ERROR five", None);
+        watches.append(&storage, 3..5, start);
+        let error = watches.iter().next().unwrap();
+        assert_eq!(error.count(), 4);
+        assert_eq!(error.per_minute(start + Duration::from_secs(30)), 2);
+        assert_eq!(error.per_minute(start + Duration::from_secs(61)), 0);
+
+        assert_eq!(watches.remove("2").unwrap().expr(), "info");
+        assert!(watches.remove("2").is_none());
+        assert_eq!(watches.remove("error").unwrap().expr(), "error");
+        assert!(watches.is_empty());
+    }
+}
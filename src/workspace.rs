@@ -0,0 +1,220 @@
+//! Workspaces: several log directories opened as one session.
+//!
+//! `qlog --workspace prod/` reads `prod/qlog-workspace.toml`, which lists
+//! the directories to load and the filters to start with, so a team opens
+//! "the prod logs" the same way every time:
+//!
+//! ```toml
+//! # Filters for every source, one `:filter-file` line each (`!` excludes)
+//! filters = ["!healthcheck"]
+//!
+//! [[source]]
+//! dir = "api"
+//! files = ["*.log", "*.log.gz"]
+//! filters = ["!GET /metrics"]
+//!
+//! [[source]]
+//! dir = "worker"
+//! ```
+//!
+//! Directories are relative to the manifest and searched recursively;
+//! `files` defaults to `*.log`. A source's filters only apply to lines from
+//! files below its directory. Without `[[source]]` tables the manifest's own
+//! directory is the one source.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use serde::Deserialize;
+use walkdir::WalkDir;
+
+use crate::error::QlogError;
+use crate::glob;
+use crate::model::{parse_filter_file, FilterRule};
+
+/// File name of the manifest inside a workspace directory.
+pub const MANIFEST_NAME: &str = "qlog-workspace.toml";
+
+/// A workspace manifest.
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Workspace {
+    /// Directory holding the manifest; source directories are relative to it
+    #[serde(skip)]
+    pub root: PathBuf,
+    /// Filters for lines from every source
+    #[serde(default)]
+    pub filters: Vec<String>,
+    #[serde(default, rename = "source")]
+    pub sources: Vec<WorkspaceSource>,
+}
+
+/// One directory of a workspace.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct WorkspaceSource {
+    pub dir: PathBuf,
+    /// File name patterns (`*.log`), or path patterns if they contain `/`
+    #[serde(default = "default_files")]
+    pub files: Vec<String>,
+    /// Filters for lines from this directory only
+    #[serde(default)]
+    pub filters: Vec<String>,
+}
+
+fn default_files() -> Vec<String> {
+    vec!["*.log".to_string()]
+}
+
+impl Workspace {
+    /// Load workspace `path`: a directory holding `qlog-workspace.toml`, or
+    /// a manifest file.
+    pub fn load(path: &Path) -> Result<Self, QlogError> {
+        let manifest = if path.is_dir() {
+            path.join(MANIFEST_NAME)
+        } else {
+            path.to_path_buf()
+        };
+        let content = fs::read_to_string(&manifest).map_err(|source| QlogError::ReadConfig {
+            path: manifest.clone(),
+            source,
+        })?;
+        let mut workspace: Workspace =
+            toml::from_str(&content).map_err(|e| QlogError::InvalidConfig {
+                path: manifest.clone(),
+                message: e.message().to_string(),
+            })?;
+        workspace.root = manifest.parent().map(Path::to_path_buf).unwrap_or_default();
+        Ok(workspace)
+    }
+
+    /// The sources, or the root directory if the manifest lists none.
+    fn sources(&self) -> Vec<WorkspaceSource> {
+        if self.sources.is_empty() {
+            vec![WorkspaceSource {
+                dir: PathBuf::new(),
+                files: default_files(),
+                filters: Vec::new(),
+            }]
+        } else {
+            self.sources.clone()
+        }
+    }
+
+    /// Log files of every source, in manifest order and sorted by path
+    /// within a source.
+    pub fn files(&self, max_open_dirs: usize) -> Vec<PathBuf> {
+        let mut files = Vec::new();
+        for source in self.sources() {
+            let mut found: Vec<PathBuf> = WalkDir::new(self.root.join(&source.dir))
+                .max_open(max_open_dirs)
+                .into_iter()
+                .filter_map(|e| e.ok())
+                .filter(|e| e.file_type().is_file())
+                .map(|e| e.into_path())
+                .filter(|path| !files.contains(path))
+                .filter(|path| {
+                    source
+                        .files
+                        .iter()
+                        .any(|pattern| glob::matches_path(path, pattern))
+                })
+                .collect();
+            found.sort();
+            files.extend(found);
+        }
+        files
+    }
+
+    /// Filters to start the session with: the shared ones, then each
+    /// source's, limited to its directory.
+    pub fn filter_rules(&self) -> Vec<FilterRule> {
+        let rules = |filters: &[String], scope: Option<String>| {
+            parse_filter_file(&filters.join("\n"))
+                .into_iter()
+                .map(move |(kind, pattern)| FilterRule::new(pattern, kind).with_file(scope.clone()))
+        };
+        let mut all: Vec<FilterRule> = rules(&self.filters, None).collect();
+        for source in &self.sources {
+            let dir = self.root.join(&source.dir);
+            all.extend(rules(
+                &source.filters,
+                Some(dir.to_string_lossy().into_owned()),
+            ));
+        }
+        all
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::{FilterKind, FilterList};
+    use tempfile::TempDir;
+
+    fn touch(dir: &TempDir, path: &str) {
+        let path = dir.path().join(path);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, "line\n").unwrap();
+    }
+
+    #[test]
+    fn test_workspace_files_and_filters() {
+        let dir = TempDir::new().unwrap();
+        touch(&dir, "api/b.log");
+        touch(&dir, "api/a.log.gz");
+        touch(&dir, "api/notes.txt");
+        touch(&dir, "worker/2024/w.log");
+        fs::write(
+            dir.path().join(MANIFEST_NAME),
+            r#"
+filters = ["!healthcheck"]
+
+[[source]]
+dir = "worker"
+
+[[source]]
+dir = "api"
+files = ["*.log", "*.log.gz"]
+filters = ["!GET /metrics"]
+"#,
+        )
+        .unwrap();
+
+        let workspace = Workspace::load(dir.path()).unwrap();
+        let files: Vec<PathBuf> = workspace
+            .files(10)
+            .into_iter()
+            .map(|path| path.strip_prefix(dir.path()).unwrap().to_path_buf())
+            .collect();
+        assert_eq!(
+            files,
+            vec![
+                PathBuf::from("worker/2024/w.log"),
+                PathBuf::from("api/a.log.gz"),
+                PathBuf::from("api/b.log"),
+            ]
+        );
+
+        let rules = workspace.filter_rules();
+        assert_eq!(rules.len(), 2);
+        assert_eq!(rules[1].kind, FilterKind::Exclude);
+        let mut filters = FilterList::new();
+        rules.into_iter().for_each(|rule| filters.add_rule(rule));
+        let api = dir.path().join("api/b.log");
+        let worker = dir.path().join("worker/2024/w.log");
+        assert!(!filters.matches_from(b"GET /metrics 200", Some(&api)));
+        assert!(filters.matches_from(b"GET /metrics 200", Some(&worker)));
+        assert!(!filters.matches_from(b"healthcheck ok", Some(&worker)));
+    }
+
+    #[test]
+    fn test_unknown_keys_are_rejected() {
+        let dir = TempDir::new().unwrap();
+        let manifest = dir.path().join("prod.toml");
+        fs::write(&manifest, "[[source]]\ndirectory = \"api\"\n").unwrap();
+        let err = Workspace::load(&manifest).unwrap_err();
+        assert!(matches!(err, QlogError::InvalidConfig { .. }));
+        assert!(err.to_string().contains("directory"));
+    }
+}
@@ -0,0 +1,211 @@
+//! Scenario harness for end-to-end tests.
+//!
+//! A `Scenario` loads log lines into an `App`, feeds it scripted key events
+//! the way the main loop does, and renders every step to a ratatui
+//! `TestBackend` so tests can assert on the screen as well as on state.
+//!
+//! Keys are written as text: plain characters are typed as-is and special
+//! keys use angle brackets, e.g. `":filter error<ret>"` or `"xjj<esc>"`.
+//! Supported names: `ret`, `esc`, `tab`, `bs`, `space`, `up`, `down`,
+//! `left`, `right`, `lt` (a literal `<`), and `C-<char>` for Ctrl.
+
+#![allow(dead_code)]
+
+use std::io::Write;
+
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use qlog::app::App;
+use qlog::model::LogStorage;
+use ratatui::backend::TestBackend;
+use ratatui::style::{Color, Style};
+use ratatui::Terminal;
+use tempfile::NamedTempFile;
+
+pub const WIDTH: u16 = 100;
+pub const HEIGHT: u16 = 20;
+
+pub struct Scenario {
+    pub app: App,
+    terminal: Terminal<TestBackend>,
+    _log: NamedTempFile,
+}
+
+impl Scenario {
+    /// Start with `lines` loaded into a fresh app, without user config
+    /// or clipboard so results do not depend on the machine.
+    pub fn with_lines(lines: &[&str]) -> Self {
+        let mut log = NamedTempFile::new().unwrap();
+        for line in lines {
+            writeln!(log, "{}", line).unwrap();
+        }
+        let mut app = App::new();
+        app.config = None;
+        app.clipboard = None;
+        app.set_storage(LogStorage::from_file(log.path()).unwrap());
+
+        let mut scenario = Self {
+            app,
+            terminal: Terminal::new(TestBackend::new(WIDTH, HEIGHT)).unwrap(),
+            _log: log,
+        };
+        scenario.render();
+        scenario
+    }
+
+    /// Render at a different terminal size from now on.
+    pub fn resize(&mut self, width: u16, height: u16) -> &mut Self {
+        self.terminal = Terminal::new(TestBackend::new(width, height)).unwrap();
+        self.render();
+        self
+    }
+
+    /// Press each key in `keys`, running a main loop tick and a redraw
+    /// after every key.
+    pub fn keys(&mut self, keys: &str) -> &mut Self {
+        for key in parse_keys(keys) {
+            self.app.handle_key(key);
+            self.app.poll_follow();
+            self.app.poll_search_job();
+            self.render();
+        }
+        self
+    }
+
+    /// Redraw the screen, e.g. after changing app state directly.
+    pub fn render(&mut self) {
+        let app = &mut self.app;
+        self.terminal.draw(|f| qlog::ui::draw(f, app)).unwrap();
+    }
+
+    /// The rendered screen as text, one row per line with trailing spaces
+    /// trimmed.
+    pub fn screen(&self) -> String {
+        let buffer = self.terminal.backend().buffer();
+        let width = buffer.area.width as usize;
+        buffer
+            .content
+            .chunks(width)
+            .map(|row| {
+                let text: String = row.iter().map(|cell| cell.symbol.as_str()).collect();
+                text.trim_end().to_string()
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    /// The screen followed by the styled regions of each row, for snapshot
+    /// tests: one `row start..end style` entry per run of cells sharing a
+    /// non-default style.
+    pub fn snapshot(&self) -> String {
+        let buffer = self.terminal.backend().buffer();
+        let width = buffer.area.width as usize;
+        let mut out = self.screen();
+        out.push_str("\n--- styles ---");
+        for (row, cells) in buffer.content.chunks(width).enumerate() {
+            let styles: Vec<String> = cells.iter().map(|c| describe_style(c.style())).collect();
+            let mut start = 0;
+            while start < styles.len() {
+                let end = (start..styles.len())
+                    .find(|&col| styles[col] != styles[start])
+                    .unwrap_or(styles.len());
+                if !styles[start].is_empty() {
+                    out.push_str(&format!(
+                        "\n{:>2} {}..{} {}",
+                        row, start, end, styles[start]
+                    ));
+                }
+                start = end;
+            }
+        }
+        out
+    }
+
+    pub fn status(&self) -> &str {
+        &self.app.status_message
+    }
+
+    /// Assert that `text` appears somewhere on the screen.
+    pub fn assert_screen_contains(&self, text: &str) -> &Self {
+        let screen = self.screen();
+        assert!(
+            screen.contains(text),
+            "expected {:?} on screen:\n{}",
+            text,
+            screen
+        );
+        self
+    }
+
+    /// Assert that `text` appears nowhere on the screen.
+    pub fn assert_screen_lacks(&self, text: &str) -> &Self {
+        let screen = self.screen();
+        assert!(
+            !screen.contains(text),
+            "expected no {:?} on screen:\n{}",
+            text,
+            screen
+        );
+        self
+    }
+}
+
+/// Compact description of a cell style, e.g. `fg=Cyan bg=DarkGray BOLD`.
+fn describe_style(style: Style) -> String {
+    let mut parts = Vec::new();
+    if let Some(fg) = style.fg.filter(|&c| c != Color::Reset) {
+        parts.push(format!("fg={:?}", fg));
+    }
+    if let Some(bg) = style.bg.filter(|&c| c != Color::Reset) {
+        parts.push(format!("bg={:?}", bg));
+    }
+    if !style.add_modifier.is_empty() {
+        parts.push(format!("{:?}", style.add_modifier));
+    }
+    parts.join(" ")
+}
+
+/// Turn a key script into key events.
+pub fn parse_keys(keys: &str) -> Vec<KeyEvent> {
+    let mut events = Vec::new();
+    let mut chars = keys.chars();
+    while let Some(c) = chars.next() {
+        if c != '<' {
+            events.push(char_key(c));
+            continue;
+        }
+        let name: String = chars.by_ref().take_while(|&c| c != '>').collect();
+        let code = match name.as_str() {
+            "ret" => KeyCode::Enter,
+            "esc" => KeyCode::Esc,
+            "tab" => KeyCode::Tab,
+            "bs" => KeyCode::Backspace,
+            "space" => KeyCode::Char(' '),
+            "up" => KeyCode::Up,
+            "down" => KeyCode::Down,
+            "left" => KeyCode::Left,
+            "right" => KeyCode::Right,
+            "lt" => KeyCode::Char('<'),
+            _ => match name.strip_prefix("C-").and_then(|c| c.chars().next()) {
+                Some(c) => {
+                    events.push(KeyEvent::new(KeyCode::Char(c), KeyModifiers::CONTROL));
+                    continue;
+                }
+                None => match name.strip_prefix('F').and_then(|n| n.parse().ok()) {
+                    Some(n) => KeyCode::F(n),
+                    None => panic!("unknown key <{}>", name),
+                },
+            },
+        };
+        events.push(KeyEvent::new(code, KeyModifiers::NONE));
+    }
+    events
+}
+
+fn char_key(c: char) -> KeyEvent {
+    let modifiers = if c.is_ascii_uppercase() {
+        KeyModifiers::SHIFT
+    } else {
+        KeyModifiers::NONE
+    };
+    KeyEvent::new(KeyCode::Char(c), modifiers)
+}
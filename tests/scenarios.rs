@@ -0,0 +1,102 @@
+mod common;
+
+use common::{parse_keys, Scenario};
+use crossterm::event::{KeyCode, KeyModifiers};
+use qlog::Mode;
+
+const LOG: &[&str] = &[
+    "2024-01-01T10:00:00Z INFO service started",
+    "2024-01-01T10:00:01Z ERROR payment timeout for order 17",
+    "2024-01-01T10:00:02Z INFO heartbeat",
+    "2024-01-01T10:00:03Z ERROR db connection lost",
+    "2024-01-01T10:00:04Z ERROR payment timeout for order 18",
+];
+
+#[test]
+fn test_filter_search_yank_write() {
+    let mut scenario = Scenario::with_lines(LOG);
+    scenario
+        .keys(":filter error<ret>")
+        .assert_screen_contains("5 total, 3 filtered")
+        .assert_screen_lacks("heartbeat");
+
+    scenario.keys("/timeout<ret>n");
+    assert_eq!(scenario.app.selected_line, 2);
+    assert_eq!(scenario.app.total_matches(), 2);
+
+    scenario.keys("gxj");
+    assert_eq!(
+        scenario.app.selection.range(scenario.app.selected_line),
+        Some((0, 1))
+    );
+    scenario.keys("y");
+    assert_eq!(
+        scenario.status(),
+        "Clipboard unavailable - install display server or use :clipboard osc52"
+    );
+
+    let out = tempfile::NamedTempFile::new().unwrap();
+    let path = out.path().to_str().unwrap();
+    // The file exists, so writing asks first
+    scenario.keys(&format!("<esc>:w {}<ret>", path));
+    assert_eq!(scenario.app.mode, Mode::Prompt);
+    scenario.keys("y");
+    let written = std::fs::read_to_string(path).unwrap();
+    assert_eq!(
+        scenario.status(),
+        format!("Saved 3 lines ({} B) to {}", written.len(), path)
+    );
+    assert_eq!(written.lines().count(), 3);
+    assert!(written.lines().all(|line| line.contains("ERROR")));
+}
+
+#[test]
+fn test_filter_list_deletes_filter() {
+    let mut scenario = Scenario::with_lines(LOG);
+    scenario.keys(":filter-out heartbeat<ret>:list-filters<ret>");
+    assert_eq!(scenario.app.mode, Mode::FilterList);
+    scenario.assert_screen_contains("EXCLUDE  heartbeat");
+
+    scenario.keys("d");
+    assert_eq!(scenario.app.mode, Mode::Normal);
+    scenario.assert_screen_contains("5 total, 5 filtered");
+}
+
+#[test]
+fn test_detail_pane_opens_and_closes() {
+    let mut scenario = Scenario::with_lines(&[r#"{"level":"error","RequestId":"abc-1"}"#]);
+    scenario.keys("<ret>");
+    assert_eq!(scenario.app.mode, Mode::Detail);
+    scenario.assert_screen_contains(r#""RequestId": "abc-1""#);
+
+    scenario.keys("q");
+    assert_eq!(scenario.app.mode, Mode::Normal);
+}
+
+#[test]
+fn test_perf_overlay_toggles() {
+    let mut scenario = Scenario::with_lines(LOG);
+    scenario
+        .keys("<F12>")
+        .assert_screen_contains(" Perf ")
+        .assert_screen_contains("Filter")
+        .assert_screen_contains("(5/5 lines)");
+
+    scenario.keys("<F12>").assert_screen_lacks(" Perf ");
+}
+
+#[test]
+fn test_parse_keys() {
+    let keys = parse_keys("G<ret><C-c><lt><F12>");
+    let codes: Vec<_> = keys.iter().map(|k| (k.code, k.modifiers)).collect();
+    assert_eq!(
+        codes,
+        vec![
+            (KeyCode::Char('G'), KeyModifiers::SHIFT),
+            (KeyCode::Enter, KeyModifiers::NONE),
+            (KeyCode::Char('c'), KeyModifiers::CONTROL),
+            (KeyCode::Char('<'), KeyModifiers::NONE),
+            (KeyCode::F(12), KeyModifiers::NONE),
+        ]
+    );
+}
@@ -0,0 +1,144 @@
+//! Snapshot tests of rendered screens.
+//!
+//! Each snapshot holds the screen text followed by its styled regions, so
+//! changes to layout, highlighting or colors show up as a snapshot diff.
+//! Review changes with `cargo insta review` (or rerun with
+//! `INSTA_UPDATE=always` and inspect the diff).
+
+mod common;
+
+use std::path::PathBuf;
+
+use common::Scenario;
+use qlog::app::LoadingStatus;
+use qlog::loader::LoadEvent;
+use qlog::summary::LoadSummary;
+
+const LOG: &[&str] = &[
+    "2024-01-01T10:00:00Z INFO service started",
+    "2024-01-01T10:00:01Z ERROR payment timeout for order 17",
+    "2024-01-01T10:00:02Z INFO heartbeat",
+    "2024-01-01T10:00:03Z WARN slow query took 1200ms",
+    "2024-01-01T10:00:04Z ERROR payment timeout for order 18",
+];
+
+fn scenario() -> Scenario {
+    let mut scenario = Scenario::with_lines(LOG);
+    scenario.resize(72, 14);
+    scenario
+}
+
+#[test]
+fn test_main_view_with_search_and_selection() {
+    let mut scenario = scenario();
+    scenario.keys("/timeout<ret>xj");
+    insta::assert_snapshot!(scenario.snapshot());
+}
+
+#[test]
+fn test_filter_list() {
+    let mut scenario = scenario();
+    scenario.keys(":filter payment<ret>:filter-out 18<ret>:dim heartbeat<ret>:list-filters<ret>");
+    insta::assert_snapshot!(scenario.snapshot());
+}
+
+#[test]
+fn test_loading_screen() {
+    let mut scenario = scenario();
+    scenario.app.loading_status = LoadingStatus::Loading {
+        current: 0,
+        total: 3,
+    };
+    let events = [
+        LoadEvent::FileStarted {
+            file: 1,
+            total: 3,
+            path: PathBuf::from("app.log"),
+        },
+        LoadEvent::FileDone {
+            path: PathBuf::from("app.log"),
+            lines: 5,
+            partial: false,
+        },
+        LoadEvent::FileStarted {
+            file: 2,
+            total: 3,
+            path: PathBuf::from("worker.log"),
+        },
+        LoadEvent::IndexedChunk {
+            lines: 2,
+            bytes: 16 << 20,
+            total: 48 << 20,
+        },
+    ];
+    for event in &events {
+        scenario.app.on_load_event(event);
+    }
+    scenario.render();
+    insta::assert_snapshot!(scenario.snapshot());
+}
+
+#[test]
+fn test_load_summary_overlay() {
+    let mut scenario = scenario();
+    let summary = LoadSummary::new(scenario.app.storage.as_ref().unwrap(), 1, 0, 0);
+    scenario.app.show_load_summary(summary);
+    scenario.render();
+    insta::assert_snapshot!(scenario.snapshot());
+}
+
+#[test]
+fn test_context_lines_and_separator() {
+    let mut lines = LOG.to_vec();
+    lines.extend([
+        "2024-01-01T10:00:05Z INFO heartbeat",
+        "2024-01-01T10:00:06Z INFO heartbeat",
+        "2024-01-01T10:00:07Z ERROR disk full",
+    ]);
+    let mut scenario = Scenario::with_lines(&lines);
+    scenario.resize(72, 14);
+    scenario.keys(":filter error<ret>:filter-out 18<ret>:context 1<ret>k");
+    insta::assert_snapshot!(scenario.snapshot());
+}
+
+#[test]
+fn test_tutorial_panel() {
+    let mut scenario = Scenario::with_lines(LOG);
+    scenario.resize(72, 14);
+    scenario.keys(":tutor<ret>G");
+    assert_eq!(scenario.status(), "✓ Moving around");
+    insta::assert_snapshot!(scenario.snapshot());
+}
+
+#[test]
+fn test_column_view_of_structured_lines() {
+    let mut scenario = Scenario::with_lines(&[
+        r#"{"time":"2024-01-01T10:00:00Z","level":"info","logger":"api","msg":"GET /orders","ms":12}"#,
+        r#"{"time":"2024-01-01T10:00:01Z","level":"error","logger":"payments","msg":"gateway timeout","ms":5000}"#,
+        "plain text without fields",
+        r#"time=2024-01-01T10:00:02Z level=warn source=db msg="slow query" ms=812"#,
+    ]);
+    scenario.resize(72, 12);
+    scenario.keys(":columns level,source,ms,msg<ret>/timeout<ret>k");
+    insta::assert_snapshot!(scenario.snapshot());
+}
+
+#[test]
+fn test_help_overlay() {
+    let mut scenario = scenario();
+    scenario.keys("?j");
+    insta::assert_snapshot!(scenario.snapshot());
+}
+
+#[test]
+fn test_overlay_on_a_short_terminal() {
+    let mut scenario = Scenario::with_lines(&[
+        "2024-01-01T10:00:00Z INFO service started",
+        "2024-01-01T10:00:01Z ERROR payment failed",
+        "java.lang.IllegalStateException: pool closed",
+        "\tat com.acme.Pool.get(Pool.java:40)",
+    ]);
+    scenario.resize(40, 10);
+    scenario.keys(":columns ts,level,msg<ret>:exceptions<ret>");
+    insta::assert_snapshot!(scenario.snapshot());
+}